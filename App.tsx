@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
 import { useState, useEffect } from 'react';
 import './styles/index.css';
 
@@ -7,6 +8,10 @@ interface SteamControllerInfo {
   connection_type: string;
   product_name: string;
   serial: string;
+  path: string;
+  slot: number | null;
+  device_id: string | null;
+  nickname: string | null;
 }
 
 interface ButtonState {
@@ -50,6 +55,62 @@ interface GyroData {
   roll: number;
 }
 
+interface ImuData {
+  accel_x: number;
+  accel_y: number;
+  accel_z: number;
+  quat_w: number;
+  quat_x: number;
+  quat_y: number;
+  quat_z: number;
+}
+
+// Degrees, computed from the quaternion; null without IMU data
+interface Orientation {
+  pitch: number;
+  roll: number;
+  yaw: number;
+}
+
+interface InputFrame {
+  seq: number;
+  coalesced: number;
+  input: ControllerInput;
+}
+
+// Error shape of the read commands (`ControllerError` in ctrlspace-core)
+interface ControllerError {
+  kind: 'not_initialized' | 'not_connected' | 'read' | 'parse' | 'timeout';
+  message?: string;
+}
+
+function describeError(e: unknown): string {
+  if (typeof e === 'object' && e !== null && 'kind' in e) {
+    const err = e as ControllerError;
+    return err.message ? `${err.kind}: ${err.message}` : err.kind;
+  }
+  return String(e);
+}
+
+interface StressTestReport {
+  duration_ms: number;
+  reports: number;
+  report_rate_hz: number;
+  missed_sequence_numbers: number;
+  empty_reads: number;
+  read_errors: number;
+  cpu_time_ms: number;
+  cpu_usage_percent: number;
+}
+
+// Sent by the backend after `frontend_ready`, so a reload picks up the live session
+interface SessionSnapshot {
+  controller: SteamControllerInfo | null;
+  streaming: boolean;
+  active_profile: unknown;
+  mapping_enabled: boolean;
+}
+
 interface ControllerInput {
   buttons: ButtonState;
   left_trackpad: TrackpadData;
@@ -57,7 +118,10 @@ interface ControllerInput {
   stick: StickData;
   triggers: TriggersData;
   gyro: GyroData;
+  imu: ImuData;
+  orientation: Orientation | null;
   timestamp: number;
+  host_timestamp_us: number;
 }
 
 function App() {
@@ -66,6 +130,10 @@ function App() {
   const [error, setError] = useState<string>('');
   const [input, setInput] = useState<ControllerInput | null>(null);
   const [isPolling, setIsPolling] = useState(false);
+  const [mappingEnabled, setMappingEnabled] = useState(true);
+  const [developerMode, setDeveloperMode] = useState(false);
+  const [featureBytes, setFeatureBytes] = useState('');
+  const [featureReply, setFeatureReply] = useState('');
 
   // Check connection status periodically
   useEffect(() => {
@@ -83,26 +151,71 @@ function App() {
     return () => clearInterval(interval);
   }, []);
 
-  // Poll for input when connected
   useEffect(() => {
-    if (!isConnected || !isPolling) return;
+    invoke<{ developer_mode: boolean }>('get_app_settings')
+      .then((settings) => setDeveloperMode(settings.developer_mode))
+      .catch(() => {});
+  }, []);
 
-    const pollInput = async () => {
-      try {
-        const inputData = await invoke<ControllerInput>('read_controller_input');
-        setInput(inputData);
-        setError('');
-      } catch (e) {
-        // Silently ignore "No data available" errors
-        const errorMsg = String(e);
-        if (!errorMsg.includes('No data available')) {
-          setError(errorMsg);
-        }
-      }
+  // After a reload (or webview crash) the backend still holds the controller,
+  // reader thread and profile - resync from its snapshot instead of reconnecting
+  useEffect(() => {
+    const unlistenPromise = listen<SessionSnapshot>('session-snapshot', (event) => {
+      const { controller, streaming, mapping_enabled } = event.payload;
+      setControllerInfo(controller);
+      setIsConnected(controller !== null);
+      setIsPolling(controller !== null && streaming);
+      setMappingEnabled(mapping_enabled);
+    });
+    unlistenPromise.then(() => invoke('frontend_ready')).catch(() => {});
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
     };
+  }, []);
 
-    const interval = setInterval(pollInput, 30); // ~33Hz for minimal latency
-    return () => clearInterval(interval);
+  // The backend re-detects whenever the OS reports HID devices coming or going
+  useEffect(() => {
+    const unlistenPromise = listen<SteamControllerInfo | null>('devices-changed', (event) => {
+      setControllerInfo(event.payload);
+    });
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Mapping can be paused from the global hotkey or controller chord
+  useEffect(() => {
+    invoke<boolean>('is_mapping_enabled').then(setMappingEnabled).catch(() => {});
+    const unlistenPromise = listen<boolean>('mapping-toggled', (event) => {
+      setMappingEnabled(event.payload);
+    });
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  // Stream input when connected (backend pushes frames, we ack once painted)
+  useEffect(() => {
+    if (!isConnected || !isPolling) return;
+
+    let cancelled = false;
+    const unlistenPromise = listen<InputFrame>('controller-input', (event) => {
+      if (cancelled) return;
+      setInput(event.payload.input);
+      setError('');
+      const seq = event.payload.seq;
+      requestAnimationFrame(() => {
+        invoke('ack_controller_input', { seq }).catch(() => {});
+      });
+    });
+
+    invoke('stream_controller_input').catch((e) => setError(String(e)));
+
+    return () => {
+      cancelled = true;
+      unlistenPromise.then((unlisten) => unlisten());
+      invoke('stop_controller_input_stream').catch(() => {});
+    };
   }, [isConnected, isPolling]);
 
   const detectController = async () => {
@@ -120,7 +233,20 @@ function App() {
       const info = await invoke<SteamControllerInfo>('connect_steam_controller');
       setControllerInfo(info);
       setIsConnected(true);
-      setIsPolling(true); // Re-enabled auto-polling with reduced timeout (50ms backend)
+      setIsPolling(true); // Starts the backend input stream
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const renameController = async () => {
+    if (!controllerInfo?.device_id) return;
+    const nickname = window.prompt('Controller name', controllerInfo.nickname ?? '');
+    if (nickname === null) return;
+    try {
+      await invoke('set_device_nickname', { deviceId: controllerInfo.device_id, nickname });
+      setControllerInfo({ ...controllerInfo, nickname: nickname.trim() || null });
       setError('');
     } catch (e) {
       setError(String(e));
@@ -141,13 +267,17 @@ function App() {
 
   const testRawInput = async () => {
     try {
-      const rawData = await invoke<string>('read_raw_input_debug');
+      const rawData = await invoke<string | null>('read_raw_input_debug');
+      if (rawData === null) {
+        setError('No report within the read timeout - controller idle?');
+        return;
+      }
       console.log('✅ Raw Input Data:');
       console.log(rawData);
       setError('Raw data logged to console (F12)');
     } catch (e) {
       console.error('❌ Error reading raw input:', e);
-      setError(String(e));
+      setError(describeError(e));
     }
   };
 
@@ -163,6 +293,58 @@ function App() {
     }
   };
 
+  const testHaptics = async (side: 'left' | 'right') => {
+    const ok = await invoke<boolean>('test_haptics', { side, strength: 1.0 });
+    setError(ok ? '' : `Haptics test failed on the ${side} pad`);
+  };
+
+  const toggleDeveloperMode = async () => {
+    try {
+      const settings = await invoke<{ developer_mode: boolean }>('set_developer_mode', { enabled: !developerMode });
+      setDeveloperMode(settings.developer_mode);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  // Hex like "8f 07 00" -> [0x8f, 0x07, 0x00]
+  const parseHex = (text: string) =>
+    text.trim().split(/[\s,]+/).filter(Boolean).map((b) => parseInt(b.replace(/^0x/i, ''), 16));
+
+  const toHex = (bytes: number[]) => bytes.map((b) => b.toString(16).padStart(2, '0')).join(' ');
+
+  const sendFeatureReport = async () => {
+    try {
+      await invoke('send_feature_report', { bytes: parseHex(featureBytes) });
+      setFeatureReply('Sent');
+    } catch (e) {
+      setFeatureReply(String(e));
+    }
+  };
+
+  const getFeatureReport = async () => {
+    try {
+      const [id] = parseHex(featureBytes);
+      const reply = await invoke<number[]>('get_feature_report', { id: id ?? 0, len: 65 });
+      setFeatureReply(toHex(reply));
+    } catch (e) {
+      setFeatureReply(String(e));
+    }
+  };
+
+  const runStressTest = async () => {
+    try {
+      setError('Running 5s stress test...');
+      const report = await invoke<StressTestReport>('stress_test_controller', { seconds: 5 });
+      console.log('⏱️ Stress Test Report:');
+      console.table(report);
+      setError(`Stress test: ${report.report_rate_hz.toFixed(1)} Hz, ${report.missed_sequence_numbers} missed - check console (F12)`);
+    } catch (e) {
+      console.error('❌ Error running stress test:', e);
+      setError(String(e));
+    }
+  };
+
   return (
     <div className="min-h-screen bg-gray-900 text-white p-8">
       <div className="max-w-6xl mx-auto">
@@ -179,6 +361,15 @@ function App() {
             <span className="text-lg">
               {isConnected ? 'Connected' : 'Not Connected'}
             </span>
+            <button
+              onClick={() => invoke<boolean>('toggle_mapping').then(setMappingEnabled)}
+              className={`ml-auto px-3 py-1 rounded text-sm transition ${
+                mappingEnabled ? 'bg-gray-700 hover:bg-gray-600' : 'bg-yellow-600 hover:bg-yellow-700'
+              }`}
+              title="Global hotkey: Ctrl+Shift+F12 · Controller: Steam + Start"
+            >
+              {mappingEnabled ? 'Mapping Active' : '⏸️ Mapping Paused'}
+            </button>
           </div>
 
           <div className="flex gap-4">
@@ -227,10 +418,43 @@ function App() {
             >
               🔍 List HID Interfaces
             </button>
+            <button
+              onClick={runStressTest}
+              disabled={!isConnected}
+              className={`px-4 py-2 rounded transition ${
+                !isConnected
+                  ? 'bg-gray-600 cursor-not-allowed'
+                  : 'bg-orange-600 hover:bg-orange-700'
+              }`}
+            >
+              ⏱️ Stress Test
+            </button>
+            {(['left', 'right'] as const).map((side) => (
+              <button
+                key={side}
+                onClick={() => testHaptics(side)}
+                disabled={!isConnected}
+                className={`px-4 py-2 rounded transition ${
+                  !isConnected
+                    ? 'bg-gray-600 cursor-not-allowed'
+                    : 'bg-teal-600 hover:bg-teal-700'
+                }`}
+              >
+                📳 Test {side === 'left' ? 'Left' : 'Right'}
+              </button>
+            ))}
           </div>
 
           {controllerInfo && (
             <div className="mt-4 p-4 bg-gray-700 rounded">
+              {controllerInfo.device_id && (
+                <p>
+                  <strong>Name:</strong> {controllerInfo.nickname ?? 'Unnamed'}{' '}
+                  <button onClick={renameController} className="ml-2 text-sm text-blue-300 hover:underline">
+                    ✏️ Rename
+                  </button>
+                </p>
+              )}
               <p><strong>Product:</strong> {controllerInfo.product_name}</p>
               <p><strong>Connection:</strong> {controllerInfo.connection_type}</p>
               <p><strong>Serial:</strong> {controllerInfo.serial}</p>
@@ -244,6 +468,36 @@ function App() {
           )}
         </div>
 
+        {/* Feature Report Console (developer mode) */}
+        <div className="bg-gray-800 rounded-lg p-6 mb-6">
+          <div className="flex items-center justify-between">
+            <h2 className="text-2xl font-semibold">Feature Report Console</h2>
+            <label className="text-sm">
+              <input type="checkbox" checked={developerMode} onChange={toggleDeveloperMode} className="mr-2" />
+              Developer mode
+            </label>
+          </div>
+          {developerMode && (
+            <div className="mt-4 space-y-2">
+              <input
+                value={featureBytes}
+                onChange={(e) => setFeatureBytes(e.target.value)}
+                placeholder="Hex bytes, e.g. 8f 07 00 00 04"
+                className="w-full px-3 py-2 rounded bg-gray-700 font-mono"
+              />
+              <div className="flex gap-2">
+                <button onClick={sendFeatureReport} disabled={!isConnected} className="px-4 py-2 rounded bg-red-700 hover:bg-red-800">
+                  Send
+                </button>
+                <button onClick={getFeatureReport} disabled={!isConnected} className="px-4 py-2 rounded bg-gray-600 hover:bg-gray-700">
+                  Get (first byte = ID)
+                </button>
+              </div>
+              {featureReply && <pre className="p-2 bg-gray-900 rounded text-xs whitespace-pre-wrap">{featureReply}</pre>}
+            </div>
+          )}
+        </div>
+
         {/* Input Debug View */}
         {isConnected && (
           <div className="bg-gray-800 rounded-lg p-6">
@@ -321,6 +575,15 @@ function App() {
                   <p className="text-sm">Pitch: {input.gyro.pitch}</p>
                   <p className="text-sm">Yaw: {input.gyro.yaw}</p>
                   <p className="text-sm">Roll: {input.gyro.roll}</p>
+                  <p className="text-sm mt-2">
+                    Accel: {input.imu.accel_x}, {input.imu.accel_y}, {input.imu.accel_z}
+                  </p>
+                  {input.orientation && (
+                    <p className="text-sm">
+                      Orientation: {input.orientation.pitch.toFixed(1)}° pitch, {input.orientation.roll.toFixed(1)}° roll,{' '}
+                      {input.orientation.yaw.toFixed(1)}° yaw
+                    </p>
+                  )}
                 </div>
               </div>
             ) : (
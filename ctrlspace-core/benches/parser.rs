@@ -0,0 +1,85 @@
+//! Parse-path throughput at sustained streaming rates. Frames carry gyro data
+//! and alternate stick/pad like a controller with both in use, so the
+//! multiplexing branch is taken as it is live.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ctrlspace_core::frame::{RawFrame, REPORT_LEN};
+use ctrlspace_core::input_parser::{InputParser, INPUT_PACKET};
+
+const FRAMES: usize = 1000;
+const CONTROLLERS: usize = 4;
+
+fn stream() -> Vec<[u8; REPORT_LEN]> {
+    (0..FRAMES)
+        .map(|i| {
+            let mut data = [0u8; REPORT_LEN];
+            data[0] = 0x01;
+            data[1] = i as u8;
+            data[2] = INPUT_PACKET;
+            data[4..8].copy_from_slice(&(i as u32 * 1000).to_le_bytes());
+            data[10] = if i % 2 == 0 { 0x88 } else { 0x80 } | 0x10;
+            data[12] = (i % 256) as u8;
+            let wave = ((i as f32 / 50.0).sin() * 20000.0) as i16;
+            for (offset, value) in [(16, wave), (18, -wave), (20, wave / 2), (22, wave / 3), (48, wave), (50, -wave), (52, wave / 4)] {
+                data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            data
+        })
+        .collect()
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let frames = stream();
+    let mut group = c.benchmark_group("parser");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+
+    group.bench_function("parse_frame", |b| {
+        let mut parser = InputParser::new();
+        let mut out = RawFrame::default();
+        b.iter(|| {
+            for data in &frames {
+                parser.parse_frame(black_box(data), &mut out).unwrap();
+                black_box(&out);
+            }
+        })
+    });
+
+    group.bench_function("parse", |b| {
+        let mut parser = InputParser::new();
+        b.iter(|| {
+            for data in &frames {
+                black_box(parser.parse(black_box(data)).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("parse_packet", |b| {
+        let mut parser = InputParser::new();
+        b.iter(|| {
+            for data in &frames {
+                black_box(parser.parse_packet(black_box(data)).unwrap());
+            }
+        })
+    });
+    group.finish();
+
+    // Several controllers interleaved, each with its own parser, into one flat batch
+    let mut group = c.benchmark_group("multi_controller");
+    group.throughput(Throughput::Elements((FRAMES * CONTROLLERS) as u64));
+    group.bench_function("parse_frame", |b| {
+        let mut parsers = vec![InputParser::new(); CONTROLLERS];
+        let mut batch = vec![RawFrame::default(); CONTROLLERS];
+        b.iter(|| {
+            for data in &frames {
+                for (parser, out) in parsers.iter_mut().zip(batch.iter_mut()) {
+                    parser.parse_frame(black_box(data), out).unwrap();
+                }
+                black_box(&batch);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parser);
+criterion_main!(benches);
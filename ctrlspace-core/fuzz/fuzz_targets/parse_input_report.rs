@@ -0,0 +1,22 @@
+#![no_main]
+
+use ctrlspace_core::frame::RawFrame;
+use ctrlspace_core::input_parser::{parse_input_report, InputParser};
+use libfuzzer_sys::fuzz_target;
+
+// Run with: cargo +nightly fuzz run parse_input_report (from ctrlspace-core/)
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_input_report(data);
+
+    // Feed the same bytes as a stream of length-prefixed packets so the
+    // multiplexed stick/pad state is exercised across frames of varying length
+    let mut parser = InputParser::new();
+    let mut frame = RawFrame::default();
+    let mut rest = data;
+    while let Some((&len, tail)) = rest.split_first() {
+        let (packet, next) = tail.split_at((len as usize).min(tail.len()));
+        let _ = parser.parse(packet);
+        let _ = parser.parse_frame(packet, &mut frame);
+        rest = next;
+    }
+});
@@ -0,0 +1,721 @@
+//! Import and export of AntiMicroX (`.amgp`) profiles, for the keyboard and
+//! mouse subset both tools share.
+//!
+//! AntiMicroX maps SDL game controller inputs, numbered from 1: buttons are
+//! SDL button + 1 (A = 1, back = 5, left paddle = 18), the left pad is the
+//! SDL dpad, the stick and right pad are SDL sticks 1 and 2, and triggers are
+//! SDL axes 5 and 6. Each bound input holds `<slot>`s that are pressed
+//! together: a Qt key code (`keyboard`), a mouse button or wheel click
+//! (`mousebutton`), or a cursor direction (`mousemovement`). Everything else
+//! either side can do is listed in the conversion report rather than dropped
+//! silently.
+
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use crate::cursor::CursorMode;
+use crate::mirror::Mirror;
+use crate::profiles::{save_new_profile, Action, AnalogMode, ButtonId, MouseButton, Profile};
+
+/// Result of converting one AntiMicroX profile
+#[derive(Debug, Clone, Serialize)]
+pub struct AntimicroxImport {
+    pub profile: Profile,
+    /// Human-readable notes for every feature that was dropped or simplified
+    pub unsupported: Vec<String>,
+}
+
+/// Result of converting a profile to AntiMicroX
+#[derive(Debug, Clone, Serialize)]
+pub struct AntimicroxExport {
+    pub xml: String,
+    /// Human-readable notes for every feature AntiMicroX can't express
+    pub unsupported: Vec<String>,
+}
+
+/// AntiMicroX button numbers (SDL game controller button + 1)
+const BUTTONS: [(ButtonId, u32); 13] = [
+    (ButtonId::A, 1),
+    (ButtonId::B, 2),
+    (ButtonId::X, 3),
+    (ButtonId::Y, 4),
+    (ButtonId::Select, 5),
+    (ButtonId::Steam, 6),
+    (ButtonId::Start, 7),
+    (ButtonId::StickClick, 8),
+    (ButtonId::RpadClick, 9),
+    (ButtonId::Lb, 10),
+    (ButtonId::Rb, 11),
+    (ButtonId::Rgrip, 17),
+    (ButtonId::Lgrip, 18),
+];
+
+/// SDL dpad buttons, up/down/left/right (some files store the dpad this way)
+const DPAD_BUTTONS: [u32; 4] = [12, 13, 14, 15];
+
+/// `<dpadbutton>` numbers (hat bits), up/down/left/right
+const DPAD_DIRECTIONS: [u32; 4] = [1, 4, 8, 2];
+
+/// `<stickbutton>` numbers, up/down/left/right; the even ones are diagonals
+const STICK_DIRECTIONS: [u32; 4] = [1, 5, 7, 3];
+
+const LEFT_STICK: u32 = 1;
+const RIGHT_STICK: u32 = 2;
+
+/// Trigger axes; the pull is `<triggerbutton index="2">`
+const TRIGGERS: [(ButtonId, u32); 2] = [(ButtonId::Lt, 5), (ButtonId::Rt, 6)];
+const TRIGGER_PULL: u32 = 2;
+
+/// `mousebutton` slot codes
+const MOUSE_LEFT: u32 = 1;
+const MOUSE_MIDDLE: u32 = 2;
+const MOUSE_RIGHT: u32 = 3;
+const WHEEL_UP: u32 = 4;
+const WHEEL_DOWN: u32 = 5;
+
+/// `mousemovement` slot codes, up/down/left/right
+const MOUSE_DIRECTIONS: [u32; 4] = [1, 2, 3, 4];
+
+/// AntiMicroX's default mouse speed, taken as sensitivity 1.0
+const BASE_MOUSE_SPEED: f32 = 50.0;
+
+/// Qt key codes for the named keys; letters and digits are their ASCII codes
+const QT_KEYS: &[(&str, u32)] = &[
+    ("space", 0x20),
+    ("apostrophe", 0x27),
+    ("comma", 0x2c),
+    ("minus", 0x2d),
+    ("dot", 0x2e),
+    ("slash", 0x2f),
+    ("semicolon", 0x3b),
+    ("equal", 0x3d),
+    ("leftbrace", 0x5b),
+    ("backslash", 0x5c),
+    ("rightbrace", 0x5d),
+    ("grave", 0x60),
+    ("escape", 0x0100_0000),
+    ("tab", 0x0100_0001),
+    ("backspace", 0x0100_0003),
+    ("enter", 0x0100_0004),
+    ("insert", 0x0100_0006),
+    ("delete", 0x0100_0007),
+    ("pause", 0x0100_0008),
+    ("home", 0x0100_0010),
+    ("end", 0x0100_0011),
+    ("left", 0x0100_0012),
+    ("up", 0x0100_0013),
+    ("right", 0x0100_0014),
+    ("down", 0x0100_0015),
+    ("pageup", 0x0100_0016),
+    ("pagedown", 0x0100_0017),
+    ("shift", 0x0100_0020),
+    ("ctrl", 0x0100_0021),
+    ("meta", 0x0100_0022),
+    ("alt", 0x0100_0023),
+    ("capslock", 0x0100_0024),
+];
+
+/// Qt::Key_F1; F2-F24 follow
+const QT_KEY_F1: u32 = 0x0100_0030;
+
+fn qt_key(key: &str) -> Option<u32> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return Some(c.to_ascii_uppercase() as u32);
+        }
+    }
+    if let Some(n) = key.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(QT_KEY_F1 + n - 1);
+        }
+    }
+    QT_KEYS.iter().find(|(name, _)| *name == key).map(|(_, code)| *code)
+}
+
+fn key_from_qt(code: u32) -> Option<String> {
+    match code {
+        0x30..=0x39 | 0x41..=0x5a => return Some((code as u8 as char).to_ascii_lowercase().to_string()),
+        // Old AntiMicro files store X11 keysyms, whose letters are lowercase
+        0x61..=0x7a => return Some((code as u8 as char).to_string()),
+        _ => {}
+    }
+    if (QT_KEY_F1..QT_KEY_F1 + 24).contains(&code) {
+        return Some(format!("f{}", code - QT_KEY_F1 + 1));
+    }
+    QT_KEYS.iter().find(|(_, c)| *c == code).map(|(name, _)| name.to_string())
+}
+
+// Profile key of a button ("lpad_click")
+fn button_name(button: ButtonId) -> String {
+    serde_json::to_value(button)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Collects notes while converting, prefixed with the input being converted
+struct Report<'a> {
+    notes: &'a mut Vec<String>,
+    input: String,
+}
+
+impl Report<'_> {
+    fn note(&mut self, message: impl AsRef<str>) {
+        self.notes.push(format!("{}: {}", self.input, message.as_ref()));
+    }
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>, tag: &'static str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |child| child.has_tag_name(tag))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &'static str) -> Option<&'a str> {
+    elements(node, tag).next().and_then(|child| child.text()).map(str::trim)
+}
+
+fn index(node: Node) -> Option<u32> {
+    node.attribute("index")?.parse().ok()
+}
+
+/// `0x41` or `65`
+fn parse_code(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// A slot: (mode, code)
+fn slots(node: Node) -> Vec<(String, u32)> {
+    elements(node, "slots")
+        .flat_map(|slots| elements(slots, "slot"))
+        .filter_map(|slot| {
+            let code = parse_code(child_text(slot, "code")?)?;
+            let mode = child_text(slot, "mode").unwrap_or("keyboard").to_string();
+            Some((mode, code))
+        })
+        .collect()
+}
+
+// Per-button options CtrlSpace has no equivalent for
+fn note_button_options(node: Node, report: &mut Report) {
+    for option in ["toggle", "turbo"] {
+        if child_text(node, option) == Some("true") {
+            report.note(format!("{} is not imported", option));
+        }
+    }
+    if elements(node, "setselect").next().is_some() {
+        report.note("set switching is not imported");
+    }
+}
+
+fn convert_slots(node: Node, report: &mut Report) -> Action {
+    note_button_options(node, report);
+    let mut keys = Vec::new();
+    let mut mouse = None;
+    for (mode, code) in slots(node) {
+        match mode.as_str() {
+            "keyboard" => match key_from_qt(code) {
+                Some(key) => keys.push(key),
+                None => report.note(format!("key code 0x{:x} is not supported", code)),
+            },
+            "mousebutton" => {
+                let action = match code {
+                    MOUSE_LEFT => Action::Mouse { button: MouseButton::Left },
+                    MOUSE_MIDDLE => Action::Mouse { button: MouseButton::Middle },
+                    MOUSE_RIGHT => Action::Mouse { button: MouseButton::Right },
+                    WHEEL_UP => Action::Scroll { amount: 1 },
+                    WHEEL_DOWN => Action::Scroll { amount: -1 },
+                    _ => {
+                        report.note("horizontal scrolling and extra mouse buttons are not supported");
+                        continue;
+                    }
+                };
+                if mouse.is_some() {
+                    report.note("only one mouse action per input is supported");
+                } else {
+                    mouse = Some(action);
+                }
+            }
+            other => report.note(format!("{} slots are not imported", other)),
+        }
+    }
+
+    match (keys.len(), mouse) {
+        (0, Some(action)) => action,
+        (0, None) => Action::None,
+        (n, mouse) => {
+            if mouse.is_some() {
+                report.note("keys and a mouse action together are not supported, keeping the keys");
+            }
+            if n == 1 {
+                Action::Key { key: keys.remove(0) }
+            } else {
+                Action::Keys { keys }
+            }
+        }
+    }
+}
+
+/// Four directional buttons of a stick or dpad, up/down/left/right
+fn convert_directions(buttons: [Option<Node>; 4], report: &mut Report) -> AnalogMode {
+    let movement: Vec<_> = buttons.iter().flatten().flat_map(|node| slots(*node)).collect();
+    if !movement.is_empty() && movement.iter().all(|(mode, _)| mode == "mousemovement") {
+        let speed = buttons
+            .iter()
+            .flatten()
+            .find_map(|node| child_text(*node, "mousespeedx").and_then(|s| s.parse::<f32>().ok()))
+            .unwrap_or(BASE_MOUSE_SPEED);
+        return AnalogMode::Mouse {
+            sensitivity: speed / BASE_MOUSE_SPEED,
+            cursor: CursorMode::Relative,
+            acceleration: None,
+        };
+    }
+
+    let [up, down, left, right] = buttons.map(|node| match node {
+        Some(node) => convert_slots(node, report),
+        None => Action::None,
+    });
+    if up == (Action::Scroll { amount: 1 }) && down == (Action::Scroll { amount: -1 }) {
+        return AnalogMode::Scroll { sensitivity: 1.0 };
+    }
+    if [&up, &down, &left, &right].iter().all(|action| **action == Action::None) {
+        return AnalogMode::Disabled;
+    }
+    AnalogMode::Directional { up, down, left, right }
+}
+
+fn convert_stick(stick: Node, report: &mut Report) -> AnalogMode {
+    let find = |n: u32| elements(stick, "stickbutton").find(|b| index(*b) == Some(n));
+    if elements(stick, "stickbutton").any(|b| index(b).map_or(false, |n| n % 2 == 0) && !slots(b).is_empty()) {
+        report.note("diagonal bindings are not imported");
+    }
+    convert_directions(STICK_DIRECTIONS.map(find), report)
+}
+
+/// Convert the XML of an `.amgp` file into a CtrlSpace profile; `name` is
+/// used if the file doesn't name the profile
+pub fn import_amgp(name: &str, xml: &str) -> Result<AntimicroxImport, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Not an AntiMicroX profile: {}", e))?;
+    let root = doc.root_element();
+    match root.tag_name().name() {
+        "gamecontroller" => {}
+        "joystick" => {
+            return Err("AntiMicroX raw joystick profiles are not supported; save it in game controller mode".to_string())
+        }
+        other => return Err(format!("Not an AntiMicroX profile: unexpected <{}>", other)),
+    }
+
+    let name = child_text(root, "profilename").filter(|n| !n.is_empty()).unwrap_or(name);
+    let mut profile = Profile::new(name);
+    profile.description = "Imported from AntiMicroX".to_string();
+    let mut notes = Vec::new();
+
+    let sets: Vec<Node> = elements(root, "sets").flat_map(|sets| elements(sets, "set")).collect();
+    for set in sets.iter().filter(|set| index(**set) != Some(1) && set.children().any(|c| c.is_element())) {
+        notes.push(format!("set {}: only set 1 is imported", index(*set).unwrap_or(0)));
+    }
+    let set = match sets.iter().find(|set| index(**set) == Some(1)) {
+        Some(set) => *set,
+        None => return Ok(AntimicroxImport { profile, unsupported: notes }),
+    };
+
+    let mut dpad_buttons = [None; 4];
+    for node in set.children().filter(|n| n.is_element()) {
+        let n = index(node).unwrap_or(0);
+        let mut report = Report {
+            notes: &mut notes,
+            input: format!("{} {}", node.tag_name().name(), n),
+        };
+        match node.tag_name().name() {
+            "button" => {
+                if let Some(i) = DPAD_BUTTONS.iter().position(|&b| b == n) {
+                    dpad_buttons[i] = Some(node);
+                } else if let Some((button, _)) = BUTTONS.iter().find(|(_, b)| *b == n) {
+                    report.input = button_name(*button);
+                    let action = convert_slots(node, &mut report);
+                    if action != Action::None {
+                        profile.buttons.insert(*button, action);
+                    }
+                } else if !slots(node).is_empty() {
+                    report.note("has no Steam Controller equivalent");
+                }
+            }
+            "trigger" => match TRIGGERS.iter().find(|(_, axis)| *axis == n) {
+                Some((button, _)) => {
+                    report.input = button_name(*button);
+                    let pull = elements(node, "triggerbutton").find(|b| index(*b) == Some(TRIGGER_PULL));
+                    let action = pull.map_or(Action::None, |pull| convert_slots(pull, &mut report));
+                    if action != Action::None {
+                        profile.buttons.insert(*button, action);
+                    }
+                }
+                None => report.note("has no Steam Controller equivalent"),
+            },
+            "stick" if n == LEFT_STICK => {
+                report.input = "stick".to_string();
+                profile.stick = convert_stick(node, &mut report);
+            }
+            "stick" if n == RIGHT_STICK => {
+                report.input = "right_pad".to_string();
+                profile.right_pad = convert_stick(node, &mut report);
+            }
+            "dpad" => {
+                report.input = "left_pad".to_string();
+                let find = |d: u32| elements(node, "dpadbutton").find(|b| index(*b) == Some(d));
+                profile.left_pad = convert_directions(DPAD_DIRECTIONS.map(find), &mut report);
+            }
+            "stickaxisassociation" | "vdpad" => report.note("is not imported"),
+            _ => {}
+        }
+    }
+    if dpad_buttons.iter().any(Option::is_some) {
+        let mut report = Report { notes: &mut notes, input: "left_pad".to_string() };
+        profile.left_pad = convert_directions(dpad_buttons, &mut report);
+    }
+
+    Ok(AntimicroxImport { profile, unsupported: notes })
+}
+
+/// Import an `.amgp` file into `profiles_dir`, never overwriting an existing profile
+pub fn import_amgp_file(path: &Path, profiles_dir: &Path) -> Result<AntimicroxImport, String> {
+    let xml = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.trim_end_matches(".amgp").trim_end_matches(".gamecontroller"))
+        .unwrap_or("Imported");
+
+    let import = import_amgp(name, &xml)?;
+    let (profile, saved) = save_new_profile(profiles_dir, import.profile)?;
+    println!(
+        "✅ Imported {} to {} ({} unsupported features)",
+        path.display(),
+        saved.display(),
+        import.unsupported.len()
+    );
+
+    Ok(AntimicroxImport { profile, unsupported: import.unsupported })
+}
+
+/// Indented XML, built element by element
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn line(&mut self, text: &str) {
+        self.out.push_str(&"    ".repeat(self.depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn open(&mut self, tag: &str, index: Option<u32>) {
+        match index {
+            Some(i) => self.line(&format!("<{} index=\"{}\">", tag, i)),
+            None => self.line(&format!("<{}>", tag)),
+        }
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{}>", tag));
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) {
+        let escaped = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;");
+        self.line(&format!("<{}>{}</{}>", tag, escaped, tag));
+    }
+
+    /// A bound input with its slots; nothing for an empty list
+    fn input(&mut self, tag: &str, index: u32, slots: &[(&str, u32)], speed: Option<u32>) {
+        if slots.is_empty() {
+            return;
+        }
+        self.open(tag, Some(index));
+        if let Some(speed) = speed {
+            self.leaf("mousespeedx", &speed.to_string());
+            self.leaf("mousespeedy", &speed.to_string());
+        }
+        self.open("slots", None);
+        for (mode, code) in slots {
+            self.open("slot", None);
+            self.leaf("code", &format!("0x{:x}", code));
+            self.leaf("mode", mode);
+            self.close("slot");
+        }
+        self.close("slots");
+        self.close(tag);
+    }
+}
+
+fn action_slots(action: &Action, report: &mut Report) -> Vec<(&'static str, u32)> {
+    let key = |key: &str, report: &mut Report| match qt_key(key) {
+        Some(code) => Some(("keyboard", code)),
+        None => {
+            report.note(format!("key '{}' has no AntiMicroX equivalent", key));
+            None
+        }
+    };
+    let mouse = |button: &MouseButton| match button {
+        MouseButton::Left => ("mousebutton", MOUSE_LEFT),
+        MouseButton::Middle => ("mousebutton", MOUSE_MIDDLE),
+        MouseButton::Right => ("mousebutton", MOUSE_RIGHT),
+    };
+    match action {
+        Action::None => Vec::new(),
+        Action::Key { key: k } => key(k, report).into_iter().collect(),
+        Action::Keys { keys } => keys.iter().filter_map(|k| key(k, report)).collect(),
+        Action::Mouse { button } => vec![mouse(button)],
+        Action::DoubleClick { button } => {
+            report.note("double click exported as a single click");
+            vec![mouse(button)]
+        }
+        Action::Scroll { amount } => {
+            if amount.abs() > 1 {
+                report.note(format!("scroll amount {} exported as one wheel click", amount));
+            }
+            vec![("mousebutton", if *amount > 0 { WHEEL_UP } else { WHEEL_DOWN })]
+        }
+        Action::SlowMode { .. } => {
+            report.note("slow mode has no AntiMicroX equivalent");
+            Vec::new()
+        }
+        Action::Plugin { .. } => {
+            report.note("plugin actions have no AntiMicroX equivalent");
+            Vec::new()
+        }
+    }
+}
+
+/// Four directional inputs (`tag` numbered by `indexes`, up/down/left/right) for an analog mode
+fn write_directions(xml: &mut XmlWriter, tag: &str, indexes: [u32; 4], mode: &AnalogMode, report: &mut Report) {
+    match mode {
+        AnalogMode::Disabled => {}
+        AnalogMode::Mouse { sensitivity, cursor, acceleration } => {
+            if *cursor != CursorMode::Relative {
+                report.note("anchored cursor exported as relative mouse movement");
+            }
+            if acceleration.is_some() {
+                report.note("acceleration curve is not exported");
+            }
+            let speed = (sensitivity * BASE_MOUSE_SPEED).round().max(1.0) as u32;
+            for (index, direction) in indexes.iter().zip(MOUSE_DIRECTIONS) {
+                xml.input(tag, *index, &[("mousemovement", direction)], Some(speed));
+            }
+        }
+        AnalogMode::Scroll { sensitivity } => {
+            if *sensitivity != 1.0 {
+                report.note("scroll sensitivity is not exported");
+            }
+            xml.input(tag, indexes[0], &[("mousebutton", WHEEL_UP)], None);
+            xml.input(tag, indexes[1], &[("mousebutton", WHEEL_DOWN)], None);
+        }
+        AnalogMode::Directional { up, down, left, right } => {
+            for (index, action) in indexes.iter().zip([up, down, left, right]) {
+                let slots = action_slots(action, report);
+                xml.input(tag, *index, &slots, None);
+            }
+        }
+        AnalogMode::EdgeSpin { .. } => report.note("edge spin has no AntiMicroX equivalent"),
+        AnalogMode::Stick { .. } => report.note("virtual pad sticks are not exported"),
+        AnalogMode::GyroMouse { .. } => report.note("gyro mouse has no AntiMicroX equivalent"),
+        AnalogMode::Trackball { .. } => report.note("trackball has no AntiMicroX equivalent"),
+        AnalogMode::TouchMenu { .. } => report.note("touch menus have no AntiMicroX equivalent"),
+    }
+}
+
+/// Convert a profile to AntiMicroX XML
+pub fn export_amgp(profile: &Profile) -> AntimicroxExport {
+    let mut notes = Vec::new();
+    let mut xml = XmlWriter { out: String::new(), depth: 0 };
+    xml.line(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.line(r#"<gamecontroller configversion="19" appversion="3.4.0">"#);
+    xml.depth += 1;
+    xml.leaf("sdlname", "Steam Controller");
+    xml.leaf("profilename", &profile.name);
+    xml.open("sets", None);
+    xml.open("set", Some(1));
+
+    for (button, action) in &profile.buttons {
+        let mut report = Report { notes: &mut notes, input: button_name(*button) };
+        let slots = action_slots(action, &mut report);
+        if let Some((_, axis)) = TRIGGERS.iter().find(|(b, _)| b == button) {
+            if !slots.is_empty() {
+                xml.open("trigger", Some(*axis));
+                xml.input("triggerbutton", TRIGGER_PULL, &slots, None);
+                xml.close("trigger");
+            }
+        } else if let Some((_, index)) = BUTTONS.iter().find(|(b, _)| b == button) {
+            xml.input("button", *index, &slots, None);
+        } else if !slots.is_empty() {
+            report.note("has no AntiMicroX equivalent");
+        }
+    }
+
+    for (input, index, mode) in [("stick", LEFT_STICK, &profile.stick), ("right_pad", RIGHT_STICK, &profile.right_pad)] {
+        if *mode == AnalogMode::Disabled {
+            continue;
+        }
+        let mut report = Report { notes: &mut notes, input: input.to_string() };
+        xml.open("stick", Some(index));
+        write_directions(&mut xml, "stickbutton", STICK_DIRECTIONS, mode, &mut report);
+        xml.close("stick");
+    }
+    if profile.left_pad != AnalogMode::Disabled {
+        let mut report = Report { notes: &mut notes, input: "left_pad".to_string() };
+        xml.open("dpad", Some(1));
+        write_directions(&mut xml, "dpadbutton", DPAD_DIRECTIONS, &profile.left_pad, &mut report);
+        xml.close("dpad");
+    }
+
+    xml.close("set");
+    xml.close("sets");
+    xml.depth -= 1;
+    xml.line("</gamecontroller>");
+
+    let dropped = [
+        ("gyro", profile.gyro != AnalogMode::Disabled),
+        ("pad gestures", !profile.gestures.is_empty()),
+        ("layers", !profile.layers.is_empty()),
+        ("toggles", !profile.toggles.is_empty()),
+        ("haptic feedback", !profile.haptics.is_empty()),
+        ("key repeat", profile.repeat.is_some()),
+        ("left-handed mirroring", profile.mirror != Mirror::Off),
+        ("script", profile.script.is_some()),
+    ];
+    for (feature, used) in dropped {
+        if used {
+            notes.push(format!("{}: not exported", feature));
+        }
+    }
+
+    AntimicroxExport { xml: xml.out, unsupported: notes }
+}
+
+/// Write a profile as an `.amgp` file; returns the conversion notes
+pub fn export_amgp_file(profile: &Profile, path: &Path) -> Result<Vec<String>, String> {
+    let export = export_amgp(profile);
+    fs::write(path, &export.xml).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!(
+        "✅ Exported '{}' to {} ({} unsupported features)",
+        profile.name,
+        path.display(),
+        export.unsupported.len()
+    );
+    Ok(export.unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_amgp() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gamecontroller configversion="19" appversion="3.3.4">
+                <sdlname>Steam Controller</sdlname>
+                <profilename>Shooter</profilename>
+                <sets>
+                    <set index="1">
+                        <button index="1">
+                            <slots><slot><code>0x20</code><mode>keyboard</mode></slot></slots>
+                        </button>
+                        <button index="5">
+                            <slots>
+                                <slot><code>0x1000023</code><mode>keyboard</mode></slot>
+                                <slot><code>0x1000001</code><mode>keyboard</mode></slot>
+                            </slots>
+                        </button>
+                        <button index="2">
+                            <toggle>true</toggle>
+                            <slots><slot><code>3</code><mode>mousebutton</mode></slot></slots>
+                        </button>
+                        <button index="3">
+                            <slots><slot><code>2000</code><mode>pause</mode></slot></slots>
+                        </button>
+                        <trigger index="6">
+                            <triggerbutton index="2">
+                                <slots><slot><code>0x1</code><mode>mousebutton</mode></slot></slots>
+                            </triggerbutton>
+                        </trigger>
+                        <stick index="2">
+                            <stickbutton index="1">
+                                <mousespeedx>100</mousespeedx>
+                                <slots><slot><code>1</code><mode>mousemovement</mode></slot></slots>
+                            </stickbutton>
+                            <stickbutton index="5">
+                                <slots><slot><code>2</code><mode>mousemovement</mode></slot></slots>
+                            </stickbutton>
+                        </stick>
+                        <dpad index="1">
+                            <dpadbutton index="1"><slots><slot><code>0x57</code><mode>keyboard</mode></slot></slots></dpadbutton>
+                            <dpadbutton index="4"><slots><slot><code>0x53</code><mode>keyboard</mode></slot></slots></dpadbutton>
+                        </dpad>
+                    </set>
+                    <set index="2">
+                        <button index="1"><slots><slot><code>0x41</code><mode>keyboard</mode></slot></slots></button>
+                    </set>
+                </sets>
+            </gamecontroller>"#;
+
+        let import = import_amgp("file", xml).unwrap();
+        let p = &import.profile;
+        assert_eq!(p.name, "Shooter");
+        assert_eq!(p.buttons[&ButtonId::A], Action::Key { key: "space".to_string() });
+        assert_eq!(
+            p.buttons[&ButtonId::Select],
+            Action::Keys { keys: vec!["alt".to_string(), "tab".to_string()] }
+        );
+        assert_eq!(p.buttons[&ButtonId::B], Action::Mouse { button: MouseButton::Right });
+        assert_eq!(p.buttons[&ButtonId::Rt], Action::Mouse { button: MouseButton::Left });
+        assert!(!p.buttons.contains_key(&ButtonId::X));
+        assert_eq!(p.right_pad, AnalogMode::Mouse { sensitivity: 2.0, cursor: CursorMode::Relative, acceleration: None });
+        assert!(matches!(&p.left_pad, AnalogMode::Directional { up: Action::Key { key }, .. } if key == "w"));
+
+        let notes = import.unsupported.join("\n");
+        for expected in ["toggle", "pause slots", "set 2"] {
+            assert!(notes.contains(expected), "missing note about {}: {}", expected, notes);
+        }
+        assert!(import_amgp("x", "<joystick/>").is_err());
+        assert!(import_amgp("x", "not xml").is_err());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut profile = Profile::new("R&D");
+        profile.buttons.insert(ButtonId::A, Action::Keys { keys: vec!["ctrl".to_string(), "f5".to_string()] });
+        profile.buttons.insert(ButtonId::Lt, Action::Scroll { amount: -1 });
+        profile.buttons.insert(ButtonId::LpadClick, Action::Key { key: "e".to_string() });
+        profile.stick = AnalogMode::Directional {
+            up: Action::Key { key: "w".to_string() },
+            down: Action::Key { key: "s".to_string() },
+            left: Action::Key { key: "a".to_string() },
+            right: Action::Key { key: "d".to_string() },
+        };
+        profile.right_pad = AnalogMode::Mouse { sensitivity: 1.5, cursor: CursorMode::Relative, acceleration: None };
+        profile.gyro = AnalogMode::Scroll { sensitivity: 1.0 };
+
+        let export = export_amgp(&profile);
+        assert!(export.xml.contains("<profilename>R&amp;D</profilename>"));
+        let notes = export.unsupported.join("\n");
+        assert!(notes.contains("lpad_click") && notes.contains("gyro"), "{}", notes);
+
+        let back = import_amgp("x", &export.xml).unwrap();
+        assert!(back.unsupported.is_empty(), "{:?}", back.unsupported);
+        let mut expected = profile.clone();
+        expected.description = "Imported from AntiMicroX".to_string();
+        expected.buttons.remove(&ButtonId::LpadClick);
+        expected.gyro = AnalogMode::Disabled;
+        assert_eq!(back.profile, expected);
+    }
+}
@@ -0,0 +1,216 @@
+//! Battery status reports from wireless controllers and low-battery warnings.
+//!
+//! The dongle interleaves status reports with input reports. They share the
+//! 0x01 report ID; byte 2 holds the packet type (0x01 input, 0x04 battery).
+
+use serde::{Deserialize, Serialize};
+use crate::haptics::{HapticPulse, HapticSide};
+
+/// Event name for threshold crossings
+pub const BATTERY_LOW_EVENT: &str = "battery-low";
+
+/// Packet type (byte 2) of a battery status report
+pub const BATTERY_STATUS_PACKET: u8 = 0x04;
+
+/// Percentage points above a threshold before it can fire again, so a level
+/// hovering around the threshold doesn't warn on every report
+pub const RECOVERY_MARGIN: u8 = 5;
+
+/// Battery state from one status report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BatteryStatus {
+    pub voltage_mv: u16,
+    pub percent: u8,
+}
+
+impl BatteryStatus {
+    /// Parse a battery status report; `None` for any other report.
+    /// Bytes 12-13 hold the voltage (mV, LE) and byte 14 the percentage.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 15 || data[0] != 0x01 || data[2] != BATTERY_STATUS_PACKET {
+            return None;
+        }
+        Some(Self {
+            voltage_mv: u16::from_le_bytes([data[12], data[13]]),
+            percent: data[14].min(100),
+        })
+    }
+}
+
+/// When to warn, persisted in app settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryThresholds {
+    pub warning_percent: u8,
+    pub critical_percent: u8,
+    /// Also play the "dying battery" rumble on the controller
+    pub haptic_alert: bool,
+}
+
+impl Default for BatteryThresholds {
+    fn default() -> Self {
+        Self {
+            warning_percent: 20,
+            critical_percent: 5,
+            haptic_alert: true,
+        }
+    }
+}
+
+impl BatteryThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.warning_percent > 100 {
+            return Err(format!("Warning threshold must be 0-100%, got {}", self.warning_percent));
+        }
+        if self.critical_percent > self.warning_percent {
+            return Err(format!(
+                "Critical threshold ({}%) must not be above the warning threshold ({}%)",
+                self.critical_percent, self.warning_percent
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryLevel {
+    Normal,
+    Low,
+    Critical,
+}
+
+/// Payload of the `battery-low` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BatteryLowEvent {
+    pub level: BatteryLevel,
+    pub status: BatteryStatus,
+}
+
+/// Turns a stream of status reports into one warning per threshold crossing
+#[derive(Debug, Clone)]
+pub struct BatteryMonitor {
+    level: BatteryLevel,
+    last: Option<BatteryStatus>,
+}
+
+impl Default for BatteryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatteryMonitor {
+    pub fn new() -> Self {
+        Self {
+            level: BatteryLevel::Normal,
+            last: None,
+        }
+    }
+
+    /// Most recent status report, if any arrived yet
+    pub fn last_status(&self) -> Option<BatteryStatus> {
+        self.last
+    }
+
+    /// Feed a status report; returns an event when the battery drops into a
+    /// lower level than it was already warned about
+    pub fn update(&mut self, status: BatteryStatus, thresholds: &BatteryThresholds) -> Option<BatteryLowEvent> {
+        self.last = Some(status);
+
+        let level = if status.percent <= thresholds.critical_percent {
+            BatteryLevel::Critical
+        } else if status.percent <= thresholds.warning_percent {
+            BatteryLevel::Low
+        } else {
+            BatteryLevel::Normal
+        };
+
+        if level > self.level {
+            self.level = level;
+            return Some(BatteryLowEvent { level, status });
+        }
+
+        // Only step back up once clearly above the threshold (e.g. charging)
+        let recovered = match self.level {
+            BatteryLevel::Critical => status.percent > thresholds.critical_percent.saturating_add(RECOVERY_MARGIN),
+            BatteryLevel::Low => status.percent > thresholds.warning_percent.saturating_add(RECOVERY_MARGIN),
+            BatteryLevel::Normal => false,
+        };
+        if recovered {
+            self.level = level;
+        }
+        None
+    }
+}
+
+/// Slow, low-pitched rumble on both pads - distinct from the confirm tick
+pub fn dying_battery_pattern(level: BatteryLevel) -> Vec<HapticPulse> {
+    let count = match level {
+        BatteryLevel::Critical => 120,
+        _ => 60,
+    };
+    [HapticSide::Left, HapticSide::Right]
+        .iter()
+        .map(|&side| HapticPulse {
+            side,
+            amplitude: 2048,
+            period: 8000,
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(percent: u8) -> BatteryStatus {
+        BatteryStatus { voltage_mv: 3600, percent }
+    }
+
+    #[test]
+    fn test_parse_status_report() {
+        let mut report = vec![0u8; 64];
+        report[0] = 0x01;
+        report[2] = BATTERY_STATUS_PACKET;
+        report[12..14].copy_from_slice(&2850u16.to_le_bytes());
+        report[14] = 42;
+        assert_eq!(BatteryStatus::parse(&report), Some(BatteryStatus { voltage_mv: 2850, percent: 42 }));
+
+        report[2] = 0x01;
+        assert_eq!(BatteryStatus::parse(&report), None);
+        assert_eq!(BatteryStatus::parse(&report[..10]), None);
+    }
+
+    #[test]
+    fn test_warns_once_per_crossing() {
+        let thresholds = BatteryThresholds::default();
+        let mut monitor = BatteryMonitor::new();
+        assert_eq!(monitor.last_status(), None);
+        let levels: Vec<Option<BatteryLevel>> = [50, 20, 19, 21, 18, 5, 4, 12, 30, 15]
+            .iter()
+            .map(|&p| monitor.update(status(p), &thresholds).map(|e| e.level))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                None,
+                Some(BatteryLevel::Low),
+                None,
+                None,
+                None,
+                Some(BatteryLevel::Critical),
+                None,
+                // 12% recovers from critical to low without warning again
+                None,
+                None,
+                // Back above 25% earlier, so dropping below 20% warns again
+                Some(BatteryLevel::Low),
+            ]
+        );
+        assert_eq!(monitor.last_status(), Some(status(15)));
+
+        assert!(BatteryThresholds { critical_percent: 30, ..thresholds }.validate().is_err());
+    }
+}
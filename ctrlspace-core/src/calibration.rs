@@ -0,0 +1,151 @@
+//! Gyro bias calibration. A resting gyro doesn't read zero: each unit has its
+//! own offset per axis, which gyro aiming turns into a slow cursor crawl.
+//! `calibrate_gyro` averages a few hundred reports while the controller lies
+//! still and returns the offset to store as the unit's `gyro_bias` (see
+//! [`Calibration`](crate::device_settings::Calibration)), which parsing
+//! subtracts from then on.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use crate::controller::Controller;
+use crate::input_parser::{GyroData, Packet};
+
+/// Reports averaged when the caller doesn't say (about a second wired)
+pub const DEFAULT_GYRO_SAMPLES: usize = 200;
+
+pub const MAX_GYRO_SAMPLES: usize = 2000;
+
+/// Largest spread (max - min) an axis may show while "resting", about 15 °/s;
+/// anything more means the controller was moved or held
+const MAX_REST_SPREAD: i32 = 250;
+
+/// Result of a gyro calibration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GyroCalibration {
+    /// Raw reading at rest (pitch, yaw, roll), to subtract from every frame
+    pub bias: [i16; 3],
+    pub samples: usize,
+    /// Max - min seen per axis while sampling
+    pub spread: [u16; 3],
+}
+
+/// Running statistics over gyro frames
+#[derive(Debug, Clone)]
+pub struct GyroSampler {
+    sum: [i64; 3],
+    min: [i16; 3],
+    max: [i16; 3],
+    count: usize,
+}
+
+impl Default for GyroSampler {
+    fn default() -> Self {
+        Self { sum: [0; 3], min: [i16::MAX; 3], max: [i16::MIN; 3], count: 0 }
+    }
+}
+
+impl GyroSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, gyro: &GyroData) {
+        for (axis, value) in [gyro.pitch, gyro.yaw, gyro.roll].into_iter().enumerate() {
+            self.sum[axis] += value as i64;
+            self.min[axis] = self.min[axis].min(value);
+            self.max[axis] = self.max[axis].max(value);
+        }
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The bias, given the frames were already corrected by `current_bias`
+    /// (so recalibrating refines the stored value instead of replacing it
+    /// with what is left over). Fails if the controller moved.
+    pub fn finish(&self, current_bias: [i16; 3]) -> Result<GyroCalibration, String> {
+        if self.count == 0 {
+            return Err("No gyro samples".to_string());
+        }
+        let mut bias = [0i16; 3];
+        let mut spread = [0u16; 3];
+        for axis in 0..3 {
+            let axis_spread = self.max[axis] as i32 - self.min[axis] as i32;
+            if axis_spread > MAX_REST_SPREAD {
+                return Err("The controller moved during gyro calibration - lay it flat and leave it alone".to_string());
+            }
+            spread[axis] = axis_spread as u16;
+            let mean = (self.sum[axis] as f64 / self.count as f64).round() as i64;
+            bias[axis] = (current_bias[axis] as i64 + mean).clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+        }
+        Ok(GyroCalibration { bias, samples: self.count, spread })
+    }
+}
+
+/// Average `samples` frames from a resting controller (giving up after
+/// `samples` × 20 ms). `current_bias` is the bias the driver already
+/// subtracts.
+pub fn calibrate_gyro(
+    controller: &dyn Controller,
+    samples: usize,
+    current_bias: [i16; 3],
+) -> Result<GyroCalibration, String> {
+    if samples == 0 || samples > MAX_GYRO_SAMPLES {
+        return Err(format!("Gyro calibration takes between 1 and {} samples", MAX_GYRO_SAMPLES));
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(20 * samples as u64);
+    let mut sampler = GyroSampler::new();
+    while sampler.count() < samples && Instant::now() < deadline {
+        if !controller.is_connected() {
+            return Err("Controller disconnected during gyro calibration".to_string());
+        }
+        let raw = match controller.read_input_blocking(controller.read_settings().timeout_ms.max(1)) {
+            Ok(Some(raw)) => raw,
+            _ => continue,
+        };
+        if let Ok(Packet::InputFrame(input)) = controller.parse_packet_at(&raw, 0) {
+            sampler.add(&input.gyro);
+        }
+    }
+
+    if sampler.count() < samples {
+        return Err(format!("Only {} of {} reports arrived", sampler.count(), samples));
+    }
+    let calibration = sampler.finish(current_bias)?;
+    println!("🎯 Gyro bias {:?} from {} samples (spread {:?})", calibration.bias, calibration.samples, calibration.spread);
+    Ok(calibration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gyro(pitch: i16, yaw: i16, roll: i16) -> GyroData {
+        GyroData { pitch, yaw, roll }
+    }
+
+    #[test]
+    fn test_gyro_sampler() {
+        let mut sampler = GyroSampler::new();
+        assert!(sampler.finish([0; 3]).is_err());
+
+        // Sensor noise around a per-unit offset averages out to the offset
+        for i in 0..100 {
+            let noise = if i % 2 == 0 { 6 } else { -6 };
+            sampler.add(&gyro(12 + noise, -40 - noise, 3));
+        }
+        let calibration = sampler.finish([0; 3]).unwrap();
+        assert_eq!(calibration.bias, [12, -40, 3]);
+        assert_eq!((calibration.samples, calibration.spread), (100, [12, 12, 0]));
+
+        // Frames already corrected by an old bias refine it
+        assert_eq!(sampler.finish([-10, 40, 0]).unwrap().bias, [2, 0, 3]);
+
+        // A controller that is being turned is refused
+        sampler.add(&gyro(12, 900, 3));
+        assert!(sampler.finish([0; 3]).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::input_parser::ButtonState;
+use crate::profiles::ButtonId;
+
+/// Default controller chord for pausing/resuming mapping output (Steam + Start)
+pub const DEFAULT_TOGGLE_CHORD: [ButtonId; 2] = [ButtonId::Steam, ButtonId::Start];
+
+/// Fires once when every button of a chord is held, and not again until
+/// the chord has been released
+#[derive(Debug, Clone)]
+pub struct ChordDetector {
+    buttons: Vec<ButtonId>,
+    held: bool,
+}
+
+impl ChordDetector {
+    pub fn new(buttons: Vec<ButtonId>) -> Result<Self, String> {
+        if buttons.len() < 2 {
+            return Err("A chord needs at least two buttons".to_string());
+        }
+        Ok(Self { buttons, held: false })
+    }
+
+    pub fn buttons(&self) -> &[ButtonId] {
+        &self.buttons
+    }
+
+    /// Feed the latest button state; true on the frame the chord completes
+    pub fn update(&mut self, state: &ButtonState) -> bool {
+        let all_held = self.buttons.iter().all(|b| b.is_pressed(state));
+        let fired = all_held && !self.held;
+        self.held = all_held;
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_fires_once_per_press() {
+        let mut chord = ChordDetector::new(DEFAULT_TOGGLE_CHORD.to_vec()).unwrap();
+        let mut state = ButtonState {
+            steam: true,
+            ..ButtonState::default()
+        };
+        assert!(!chord.update(&state));
+        state.start = true;
+        assert!(chord.update(&state));
+        assert!(!chord.update(&state));
+
+        state.start = false;
+        assert!(!chord.update(&state));
+        state.start = true;
+        assert!(chord.update(&state));
+    }
+
+    #[test]
+    fn test_chord_needs_two_buttons() {
+        assert!(ChordDetector::new(vec![ButtonId::A]).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Monotonic host clock for stamping input frames. The controller's own 32-bit
+//! timestamp wraps and runs on its own clock, so it can't be compared with host
+//! events; this one can.
+
+use once_cell::sync::Lazy;
+use std::time::Instant;
+
+// Fixed on first use, so every stamp in the process shares one origin
+static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Microseconds since the process first read the clock. Never goes backwards.
+pub fn monotonic_us() -> u64 {
+    EPOCH.elapsed().as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monotonic() {
+        let a = monotonic_us();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let b = monotonic_us();
+        assert!(b >= a + 1000);
+    }
+}
@@ -0,0 +1,237 @@
+//! Combined mode: two physical controllers drive one virtual pad, e.g. one
+//! player steering while a helper handles buttons (accessibility or co-pilot
+//! play). Each group of controls comes from the primary, the secondary, or
+//! either of them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::input_parser::{ControllerInput, TrackpadData};
+
+/// Controls that are always taken from the same device together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlGroup {
+    /// A, B, X, Y
+    FaceButtons,
+    /// LB, RB
+    Bumpers,
+    /// Trigger clicks and analog values
+    Triggers,
+    Grips,
+    /// Start, select, Steam
+    Menu,
+    /// Stick and stick click
+    Stick,
+    /// Left pad and its click
+    LeftPad,
+    RightPad,
+    Gyro,
+}
+
+impl ControlGroup {
+    pub const ALL: [ControlGroup; 9] = [
+        ControlGroup::FaceButtons,
+        ControlGroup::Bumpers,
+        ControlGroup::Triggers,
+        ControlGroup::Grips,
+        ControlGroup::Menu,
+        ControlGroup::Stick,
+        ControlGroup::LeftPad,
+        ControlGroup::RightPad,
+        ControlGroup::Gyro,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlSource {
+    Primary,
+    Secondary,
+    /// Buttons from either device; analogs from whichever is deflected further
+    Either,
+}
+
+/// Which device each control group comes from; unlisted groups use `Either`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CombineConfig {
+    #[serde(default)]
+    pub sources: BTreeMap<ControlGroup, ControlSource>,
+}
+
+impl CombineConfig {
+    pub fn source(&self, group: ControlGroup) -> ControlSource {
+        self.sources.get(&group).copied().unwrap_or(ControlSource::Either)
+    }
+}
+
+fn magnitude(x: i16, y: i16) -> i32 {
+    (x as i32).abs().max((y as i32).abs())
+}
+
+// The touched pad wins; if both are touched, the primary
+fn pick_pad(primary: &TrackpadData, secondary: &TrackpadData) -> TrackpadData {
+    if primary.active || !secondary.active {
+        *primary
+    } else {
+        *secondary
+    }
+}
+
+/// One virtual frame from the latest frames of both devices. Timestamps come
+/// from the primary.
+pub fn merge(primary: &ControllerInput, secondary: &ControllerInput, config: &CombineConfig) -> ControllerInput {
+    let mut out = primary.clone();
+    let (p, s) = (&primary.buttons, &secondary.buttons);
+
+    for group in ControlGroup::ALL {
+        let source = config.source(group);
+        let either = source == ControlSource::Either;
+        // Digital controls: the chosen device, or either device pressing
+        let pick = |a: bool, b: bool| match source {
+            ControlSource::Primary => a,
+            ControlSource::Secondary => b,
+            ControlSource::Either => a || b,
+        };
+
+        match group {
+            ControlGroup::FaceButtons => {
+                out.buttons.a = pick(p.a, s.a);
+                out.buttons.b = pick(p.b, s.b);
+                out.buttons.x = pick(p.x, s.x);
+                out.buttons.y = pick(p.y, s.y);
+            }
+            ControlGroup::Bumpers => {
+                out.buttons.lb = pick(p.lb, s.lb);
+                out.buttons.rb = pick(p.rb, s.rb);
+            }
+            ControlGroup::Triggers => {
+                out.buttons.lt = pick(p.lt, s.lt);
+                out.buttons.rt = pick(p.rt, s.rt);
+                out.triggers = match source {
+                    ControlSource::Primary => primary.triggers,
+                    ControlSource::Secondary => secondary.triggers,
+                    ControlSource::Either => {
+                        let mut triggers = primary.triggers;
+                        triggers.left = triggers.left.max(secondary.triggers.left);
+                        triggers.right = triggers.right.max(secondary.triggers.right);
+                        triggers
+                    }
+                };
+            }
+            ControlGroup::Grips => {
+                out.buttons.lgrip = pick(p.lgrip, s.lgrip);
+                out.buttons.rgrip = pick(p.rgrip, s.rgrip);
+            }
+            ControlGroup::Menu => {
+                out.buttons.start = pick(p.start, s.start);
+                out.buttons.select = pick(p.select, s.select);
+                out.buttons.steam = pick(p.steam, s.steam);
+            }
+            ControlGroup::Stick => {
+                out.buttons.stick_click = pick(p.stick_click, s.stick_click);
+                let use_secondary = source == ControlSource::Secondary
+                    || (either
+                        && magnitude(secondary.stick.x, secondary.stick.y) > magnitude(primary.stick.x, primary.stick.y));
+                if use_secondary {
+                    out.stick = secondary.stick;
+                }
+            }
+            ControlGroup::LeftPad => {
+                out.buttons.lpad_click = pick(p.lpad_click, s.lpad_click);
+                out.left_trackpad = match source {
+                    ControlSource::Primary => primary.left_trackpad,
+                    ControlSource::Secondary => secondary.left_trackpad,
+                    ControlSource::Either => pick_pad(&primary.left_trackpad, &secondary.left_trackpad),
+                };
+            }
+            ControlGroup::RightPad => {
+                out.buttons.rpad_click = pick(p.rpad_click, s.rpad_click);
+                out.right_trackpad = match source {
+                    ControlSource::Primary => primary.right_trackpad,
+                    ControlSource::Secondary => secondary.right_trackpad,
+                    ControlSource::Either => pick_pad(&primary.right_trackpad, &secondary.right_trackpad),
+                };
+            }
+            ControlGroup::Gyro => {
+                let g = &secondary.gyro;
+                let use_secondary = source == ControlSource::Secondary
+                    || (either
+                        && magnitude(g.yaw, g.pitch) > magnitude(primary.gyro.yaw, primary.gyro.pitch));
+                if use_secondary {
+                    out.gyro = secondary.gyro;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Keeps the latest frame from each device and merges on every primary frame
+#[derive(Debug, Clone, Default)]
+pub struct Combiner {
+    config: CombineConfig,
+    secondary: ControllerInput,
+}
+
+impl Combiner {
+    pub fn new(config: CombineConfig) -> Self {
+        Self {
+            config,
+            secondary: ControllerInput::default(),
+        }
+    }
+
+    pub fn config(&self) -> &CombineConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: CombineConfig) {
+        self.config = config;
+    }
+
+    pub fn update_secondary(&mut self, input: ControllerInput) {
+        self.secondary = input;
+    }
+
+    /// The merged frame; output runs at the primary's report rate
+    pub fn merge_primary(&self, primary: &ControllerInput) -> ControllerInput {
+        merge(primary, &self.secondary, &self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_by_group() {
+        let mut primary = ControllerInput::default();
+        primary.buttons.a = true;
+        primary.stick.x = 20000;
+        primary.timestamp = 7;
+        let mut secondary = ControllerInput::default();
+        secondary.buttons.b = true;
+        secondary.stick.y = -30000;
+        secondary.triggers.right = 200;
+
+        // Everything on Either: buttons OR'd, larger stick deflection wins
+        let merged = merge(&primary, &secondary, &CombineConfig::default());
+        assert!(merged.buttons.a && merged.buttons.b);
+        assert_eq!((merged.stick.x, merged.stick.y), (0, -30000));
+        assert_eq!(merged.triggers.right, 200);
+        assert_eq!(merged.timestamp, 7);
+
+        // Stick pinned to the primary, face buttons to the secondary
+        let config = CombineConfig {
+            sources: [
+                (ControlGroup::Stick, ControlSource::Primary),
+                (ControlGroup::FaceButtons, ControlSource::Secondary),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let merged = merge(&primary, &secondary, &config);
+        assert!(!merged.buttons.a && merged.buttons.b);
+        assert_eq!((merged.stick.x, merged.stick.y), (20000, 0));
+    }
+}
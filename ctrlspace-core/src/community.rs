@@ -0,0 +1,242 @@
+//! Community profile repository: a static `index.json` served over HTTPS
+//! that lists shared profiles, each a plain profile JSON next to it.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "profiles": [
+//!     {
+//!       "id": "portal-2-gyro",
+//!       "name": "Portal 2 (Gyro)",
+//!       "author": "someone",
+//!       "tags": ["fps", "gyro"],
+//!       "steam_app_ids": [620],
+//!       "file": "profiles/portal-2-gyro.json",
+//!       "sha256": "9f86d08..."
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Fetching is left to the app; this module parses the index, searches it
+//! and checks downloads before anything is written to the profiles dir.
+//! Installed profiles lose whatever would act without the user choosing the
+//! profile - its script, the games it activates for, exclusive access - and
+//! the install result lists what was removed so the UI can say so.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use crate::profiles::{save_new_profile, Profile};
+
+/// Index format this build understands
+pub const COMMUNITY_INDEX_VERSION: u32 = 1;
+
+/// Largest profile download accepted (profiles are a few KB)
+pub const MAX_PROFILE_BYTES: u64 = 1024 * 1024;
+
+/// Largest index download accepted
+pub const MAX_INDEX_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommunityIndex {
+    pub version: u32,
+    #[serde(default)]
+    pub profiles: Vec<CommunityEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommunityEntry {
+    /// Unique within the index
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Steam games the profile is made for
+    #[serde(default)]
+    pub steam_app_ids: Vec<u32>,
+    /// Profile JSON, relative to the index or an absolute HTTPS URL
+    pub file: String,
+    /// Hex SHA-256 of the profile file; checked when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// A saved community profile and the keys stripped from it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommunityInstall {
+    pub profile: Profile,
+    /// `script`, `steam_app_ids` and/or `exclusive`, if the download set them
+    pub stripped: Vec<String>,
+}
+
+/// Clear the keys that run code or take over on their own from a profile
+/// someone else wrote; returns the names of the ones that were set
+pub fn strip_untrusted(profile: &mut Profile) -> Vec<String> {
+    let mut stripped = Vec::new();
+    if profile.script.take().is_some() {
+        stripped.push("script".to_string());
+    }
+    if !profile.steam_app_ids.is_empty() {
+        profile.steam_app_ids.clear();
+        stripped.push("steam_app_ids".to_string());
+    }
+    if std::mem::replace(&mut profile.exclusive, false) {
+        stripped.push("exclusive".to_string());
+    }
+    stripped
+}
+
+/// Only HTTPS is fetched, so a profile can't be swapped in transit
+pub fn validate_url(url: &str) -> Result<(), String> {
+    if url.starts_with("https://") && url.len() > "https://".len() {
+        Ok(())
+    } else {
+        Err(format!("Community repository URLs must use HTTPS: {}", url))
+    }
+}
+
+pub fn parse_index(json: &str) -> Result<CommunityIndex, String> {
+    let index: CommunityIndex =
+        serde_json::from_str(json).map_err(|e| format!("Invalid community index: {}", e))?;
+    if index.version > COMMUNITY_INDEX_VERSION {
+        return Err(format!(
+            "Community index uses format version {}, newer than supported ({})",
+            index.version, COMMUNITY_INDEX_VERSION
+        ));
+    }
+    Ok(index)
+}
+
+impl CommunityIndex {
+    pub fn entry(&self, id: &str) -> Option<&CommunityEntry> {
+        self.profiles.iter().find(|entry| entry.id == id)
+    }
+
+    /// Entries matching every word of `query` (name, description, author,
+    /// tags or AppID); an empty query lists everything
+    pub fn search(&self, query: &str) -> Vec<&CommunityEntry> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.profiles
+            .iter()
+            .filter(|entry| words.iter().all(|word| entry.matches(word)))
+            .collect()
+    }
+}
+
+impl CommunityEntry {
+    fn matches(&self, word: &str) -> bool {
+        let text = [&self.name, &self.description, &self.author];
+        text.iter().any(|field| field.to_lowercase().contains(word))
+            || self.tags.iter().any(|tag| tag.to_lowercase() == word)
+            || self.steam_app_ids.iter().any(|id| id.to_string() == word)
+    }
+
+    /// Where to download the profile from, given where the index came from
+    pub fn url(&self, index_url: &str) -> Result<String, String> {
+        let url = if self.file.contains("://") {
+            self.file.clone()
+        } else {
+            let base = &index_url[..index_url.rfind('/').map_or(index_url.len(), |i| i + 1)];
+            format!("{}{}", base, self.file.trim_start_matches('/'))
+        };
+        validate_url(&url)?;
+        Ok(url)
+    }
+
+    /// Check a downloaded file against the index and the profile schema
+    pub fn verify(&self, json: &str) -> Result<Profile, String> {
+        if json.len() as u64 > MAX_PROFILE_BYTES {
+            return Err(format!("Community profile '{}' is too large", self.id));
+        }
+        if let Some(expected) = &self.sha256 {
+            let actual: String = Sha256::digest(json.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(format!("Community profile '{}' failed its checksum", self.id));
+            }
+        }
+        Profile::from_json(json).map_err(|e| format!("Community profile '{}': {}", self.id, e))
+    }
+
+    /// Verify a download, strip it (see [`strip_untrusted`]) and save it to
+    /// `dir` without overwriting anything
+    pub fn install(&self, json: &str, dir: &Path) -> Result<CommunityInstall, String> {
+        let mut profile = self.verify(json)?;
+        let stripped = strip_untrusted(&mut profile);
+        let (profile, path) = save_new_profile(dir, profile)?;
+        println!("✅ Installed community profile '{}' to {}", self.id, path.display());
+        if !stripped.is_empty() {
+            println!("🔒 Removed from '{}': {}", self.id, stripped.join(", "));
+        }
+        Ok(CommunityInstall { profile, stripped })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_search_and_verify() {
+        let index = parse_index(
+            r#"{"version": 1, "profiles": [
+                {"id": "portal", "name": "Portal 2 (Gyro)", "tags": ["fps"], "steam_app_ids": [620],
+                 "file": "profiles/portal.json",
+                 "sha256": "0000000000000000000000000000000000000000000000000000000000000000"},
+                {"id": "desktop", "name": "Desktop", "author": "Valve fan", "file": "https://cdn.example/desktop.json"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(index.search("").len(), 2);
+        assert_eq!(index.search("GYRO 620")[0].id, "portal");
+        assert_eq!(index.search("fan")[0].id, "desktop");
+        assert!(index.search("racing").is_empty());
+
+        let portal = index.entry("portal").unwrap();
+        assert_eq!(
+            portal.url("https://example.org/ctrlspace/index.json").unwrap(),
+            "https://example.org/ctrlspace/profiles/portal.json"
+        );
+        assert!(portal.verify(r#"{"name": "Portal 2"}"#).unwrap_err().contains("checksum"));
+
+        let desktop = index.entry("desktop").unwrap();
+        assert_eq!(desktop.verify(r#"{"name": "Desktop"}"#).unwrap().name, "Desktop");
+        assert!(desktop.verify(r#"{"buttons": {}}"#).is_err());
+        assert!(validate_url("http://example.org/index.json").is_err());
+    }
+
+    #[test]
+    fn test_install_strips_script_and_auto_activation() {
+        let dir = std::env::temp_dir().join(format!("ctrlspace-community-{}", std::process::id()));
+        let entry = CommunityEntry {
+            id: "portal".to_string(),
+            name: "Portal 2".to_string(),
+            description: String::new(),
+            author: String::new(),
+            tags: Vec::new(),
+            steam_app_ids: vec![620],
+            file: "portal.json".to_string(),
+            sha256: None,
+        };
+
+        let json = r#"{"name": "Portal 2", "script": "fn on_frame(input) {}", "steam_app_ids": [620], "exclusive": true}"#;
+        let installed = entry.install(json, &dir).unwrap();
+        assert_eq!(installed.stripped, vec!["script", "steam_app_ids", "exclusive"]);
+        assert_eq!(installed.profile.script, None);
+        assert!(installed.profile.steam_app_ids.is_empty());
+        assert!(!installed.profile.exclusive);
+
+        // What was saved is the stripped profile
+        let saved = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        let saved = Profile::from_json(&std::fs::read_to_string(saved).unwrap()).unwrap();
+        assert_eq!(saved, installed.profile);
+
+        let plain = entry.install(r#"{"name": "Desktop"}"#, &dir).unwrap();
+        assert!(plain.stripped.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,178 @@
+//! Binding conflict detection. A profile can be perfectly valid and still
+//! contain bindings that never fire or fire alongside something else: a layer
+//! activator is consumed by the layer engine, so whatever else is bound to it
+//! is dead, and reserved chords (mapping toggle, profile switch) run on top of
+//! the profile's own bindings. These are reported as warnings for the editor
+//! rather than rejected, since some are deliberate.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use crate::profiles::{Action, ButtonId, Profile};
+
+/// Event name for conflicts found when a profile is activated
+pub const PROFILE_CONFLICTS_EVENT: &str = "profile-conflicts";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// One physical input with two jobs in the same layer
+    DuplicateBinding,
+    /// A single-press binding that can't fire because the button starts a chord
+    ShadowedByChord,
+    /// Bindings that collide with a chord the app reserves
+    ReservedChord,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MappingWarning {
+    pub kind: ConflictKind,
+    /// Layer the conflict is in, `None` for the base bindings
+    pub layer: Option<String>,
+    pub buttons: Vec<ButtonId>,
+    pub message: String,
+}
+
+/// A chord handled before the profile's bindings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReservedChord {
+    /// What the chord does, for messages ("mapping toggle")
+    pub name: String,
+    pub buttons: Vec<ButtonId>,
+}
+
+fn is_bound(buttons: &BTreeMap<ButtonId, Action>, button: ButtonId) -> bool {
+    buttons.get(&button).map_or(false, |action| *action != Action::None)
+}
+
+fn layer_label(layer: Option<&str>) -> String {
+    match layer {
+        Some(name) => format!("layer '{}'", name),
+        None => "the base layer".to_string(),
+    }
+}
+
+/// Every conflict in `profile`, base layer first, then layers by name
+pub fn detect_conflicts(profile: &Profile, reserved: &[ReservedChord]) -> Vec<MappingWarning> {
+    let mut warnings = Vec::new();
+
+    for (name, layer) in &profile.layers {
+        let activator = layer.activator;
+        if is_bound(&profile.buttons, activator) {
+            warnings.push(MappingWarning {
+                kind: ConflictKind::ShadowedByChord,
+                layer: None,
+                buttons: vec![activator],
+                message: format!(
+                    "{:?} enters layer '{}', so its own binding never fires",
+                    activator, name
+                ),
+            });
+        }
+        if profile.toggles.contains_key(&activator) {
+            warnings.push(MappingWarning {
+                kind: ConflictKind::DuplicateBinding,
+                layer: None,
+                buttons: vec![activator],
+                message: format!("{:?} is both a toggle and the activator of layer '{}'", activator, name),
+            });
+        }
+        for (other_name, other) in &profile.layers {
+            if is_bound(&other.buttons, activator) {
+                warnings.push(MappingWarning {
+                    kind: ConflictKind::DuplicateBinding,
+                    layer: Some(other_name.clone()),
+                    buttons: vec![activator],
+                    message: format!(
+                        "{:?} is bound in {} but also activates layer '{}'",
+                        activator,
+                        layer_label(Some(other_name)),
+                        name
+                    ),
+                });
+            }
+        }
+    }
+
+    for chord in reserved {
+        if chord.buttons.iter().all(|&button| is_bound(&profile.buttons, button)) {
+            warnings.push(reserved_warning(chord, None));
+        }
+        // A layer's activator plus its bindings make chords of their own
+        for (name, layer) in &profile.layers {
+            let covered = chord
+                .buttons
+                .iter()
+                .all(|&button| button == layer.activator || is_bound(&layer.buttons, button));
+            if chord.buttons.contains(&layer.activator) && covered {
+                warnings.push(reserved_warning(chord, Some(name)));
+            }
+        }
+    }
+
+    warnings.sort_by(|a, b| a.layer.cmp(&b.layer));
+    warnings
+}
+
+fn reserved_warning(chord: &ReservedChord, layer: Option<&str>) -> MappingWarning {
+    MappingWarning {
+        kind: ConflictKind::ReservedChord,
+        layer: layer.map(str::to_string),
+        buttons: chord.buttons.clone(),
+        message: format!(
+            "{:?} is reserved for the {}; the bindings in {} fire along with it",
+            chord.buttons,
+            chord.name,
+            layer_label(layer)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::{Layer, LayerActivation, ToggleBinding};
+
+    #[test]
+    fn test_detects_each_conflict_kind() {
+        let key = |k: &str| Action::Key { key: k.to_string() };
+        let mut profile = Profile::new("Conflicts");
+        profile.buttons.insert(ButtonId::Lgrip, key("shift"));
+        profile.buttons.insert(ButtonId::A, key("space"));
+        profile.toggles.insert(ButtonId::Lgrip, ToggleBinding::default());
+        let mut layer = Layer {
+            activator: ButtonId::Lgrip,
+            activation: LayerActivation::Hold,
+            buttons: Default::default(),
+            left_pad: None,
+            right_pad: None,
+            stick: None,
+            gyro: None,
+        };
+        layer.buttons.insert(ButtonId::Lgrip, key("ctrl"));
+        layer.buttons.insert(ButtonId::Start, key("escape"));
+        profile.layers.insert("fn".to_string(), layer);
+
+        let reserved = [ReservedChord {
+            name: "mapping toggle".to_string(),
+            buttons: vec![ButtonId::Lgrip, ButtonId::Start],
+        }];
+        let warnings = detect_conflicts(&profile, &reserved);
+        let kinds: Vec<(ConflictKind, Option<&str>)> =
+            warnings.iter().map(|w| (w.kind, w.layer.as_deref())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ConflictKind::ShadowedByChord, None),
+                (ConflictKind::DuplicateBinding, None),
+                (ConflictKind::DuplicateBinding, Some("fn")),
+                (ConflictKind::ReservedChord, Some("fn")),
+            ]
+        );
+
+        // A clean profile has nothing to report
+        profile.buttons.remove(&ButtonId::Lgrip);
+        profile.toggles.clear();
+        profile.layers.clear();
+        assert!(detect_conflicts(&profile, &reserved).is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! The device-independent side of a controller driver. Reader threads
+//! (input streams, workers, raw diffs, learning, fixture capture) only need
+//! to find a device, open it, read reports and turn them into
+//! [`ControllerInput`]s, so they take a [`ControllerHandle`] instead of a
+//! [`SteamControllerManager`]. Another device family (DualShock 4,
+//! DualSense) is added by implementing [`Controller`] for its own driver
+//! and listing it alongside the Steam Controller's (see [`detect_first`]).
+//!
+//! Device-specific extras (lizard mode, registers, firmware info, drift
+//! compensation) stay on the concrete driver.
+
+use std::sync::Arc;
+use crate::error::ControllerError;
+use crate::haptics::HapticPulse;
+use crate::input_parser::Packet;
+use crate::steam_controller::{ReadSettings, SteamControllerInfo, SteamControllerManager};
+
+/// What every controller driver can do
+pub trait Controller: Send + Sync {
+    /// Driver name for logs and diagnostics (`"steam_controller"`)
+    fn kind(&self) -> &'static str;
+
+    /// Re-enumerate and describe the first matching device, without opening it
+    fn detect(&self) -> Option<SteamControllerInfo>;
+
+    /// Open the first matching device
+    fn connect(&self) -> Result<SteamControllerInfo, String>;
+
+    /// Close the device, restoring whatever the driver changed on it
+    fn disconnect(&self);
+
+    fn is_connected(&self) -> bool;
+
+    /// Buffer size and timeout reader threads should use
+    fn read_settings(&self) -> ReadSettings;
+
+    /// Read one report into `buf`; 0 means nothing arrived before the timeout
+    fn read_input_into(&self, buf: &mut [u8], timeout_ms: i32) -> Result<usize, ControllerError>;
+
+    /// Read one report; `Ok(None)` on timeout
+    fn read_input_blocking(&self, timeout_ms: i32) -> Result<Option<Vec<u8>>, ControllerError> {
+        let mut buf = vec![0u8; self.read_settings().buffer_size];
+        let size = self.read_input_into(&mut buf, timeout_ms)?;
+        if size == 0 {
+            return Ok(None);
+        }
+        buf.truncate(size);
+        Ok(Some(buf))
+    }
+
+    /// Decode a report read from this device; input frames are stamped with
+    /// `host_timestamp_us` (µs, [`crate::clock::monotonic_us`])
+    fn parse_packet_at(&self, raw: &[u8], host_timestamp_us: u64) -> Result<Packet, String>;
+
+    /// Write a configuration feature report (first byte is the report ID)
+    fn send_feature_report(&self, data: &[u8]) -> Result<(), String>;
+
+    /// Read feature report `report_id`, up to `len` bytes
+    fn get_feature_report(&self, report_id: u8, len: usize) -> Result<Vec<u8>, String>;
+
+    fn send_haptic_pulse(&self, pulse: HapticPulse) -> Result<(), String>;
+}
+
+/// A shared, cloneable handle on one driver instance
+pub type ControllerHandle = Arc<dyn Controller>;
+
+impl Controller for SteamControllerManager {
+    fn kind(&self) -> &'static str {
+        "steam_controller"
+    }
+
+    fn detect(&self) -> Option<SteamControllerInfo> {
+        SteamControllerManager::detect(self)
+    }
+
+    fn connect(&self) -> Result<SteamControllerInfo, String> {
+        SteamControllerManager::connect(self)
+    }
+
+    fn disconnect(&self) {
+        SteamControllerManager::disconnect(self)
+    }
+
+    fn is_connected(&self) -> bool {
+        SteamControllerManager::is_connected(self)
+    }
+
+    fn read_settings(&self) -> ReadSettings {
+        SteamControllerManager::read_settings(self)
+    }
+
+    fn read_input_into(&self, buf: &mut [u8], timeout_ms: i32) -> Result<usize, ControllerError> {
+        SteamControllerManager::read_input_into(self, buf, timeout_ms)
+    }
+
+    fn read_input_blocking(&self, timeout_ms: i32) -> Result<Option<Vec<u8>>, ControllerError> {
+        SteamControllerManager::read_input_blocking(self, timeout_ms)
+    }
+
+    fn parse_packet_at(&self, raw: &[u8], host_timestamp_us: u64) -> Result<Packet, String> {
+        SteamControllerManager::parse_packet_at(self, raw, host_timestamp_us)
+    }
+
+    fn send_feature_report(&self, data: &[u8]) -> Result<(), String> {
+        SteamControllerManager::send_feature_report(self, data)
+    }
+
+    fn get_feature_report(&self, report_id: u8, len: usize) -> Result<Vec<u8>, String> {
+        SteamControllerManager::get_feature_report(self, report_id, len)
+    }
+
+    fn send_haptic_pulse(&self, pulse: HapticPulse) -> Result<(), String> {
+        SteamControllerManager::send_haptic_pulse(self, pulse)
+    }
+}
+
+/// The first driver that finds a device, with what it found. Drivers are
+/// asked in order, so list the most specific first.
+pub fn detect_first(drivers: &[ControllerHandle]) -> Option<(ControllerHandle, SteamControllerInfo)> {
+    drivers
+        .iter()
+        .find_map(|driver| driver.detect().map(|info| (Arc::clone(driver), info)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use crate::frame::REPORT_LEN;
+    use crate::input_parser::{ControllerInput, CONTROLLER_REPORT_ID, INPUT_PACKET};
+    use crate::input_stream::{InputStream, StreamEnd, StreamEvent};
+
+    /// A device that plays back canned reports, then unplugs itself
+    struct FakeController {
+        name: &'static str,
+        present: bool,
+        reports: Mutex<VecDeque<Vec<u8>>>,
+    }
+
+    impl FakeController {
+        fn handle(name: &'static str, present: bool, reports: Vec<Vec<u8>>) -> ControllerHandle {
+            Arc::new(Self { name, present, reports: Mutex::new(reports.into()) })
+        }
+
+        fn info(&self) -> SteamControllerInfo {
+            SteamControllerInfo {
+                connected: true,
+                connection_type: "USB".to_string(),
+                product_name: self.name.to_string(),
+                serial: String::new(),
+                path: self.name.to_string(),
+                slot: None,
+                device_id: None,
+                nickname: None,
+            }
+        }
+    }
+
+    impl Controller for FakeController {
+        fn kind(&self) -> &'static str {
+            self.name
+        }
+
+        fn detect(&self) -> Option<SteamControllerInfo> {
+            if self.present {
+                Some(self.info())
+            } else {
+                None
+            }
+        }
+
+        fn connect(&self) -> Result<SteamControllerInfo, String> {
+            self.detect().ok_or_else(|| "No device".to_string())
+        }
+
+        fn disconnect(&self) {
+            self.reports.lock().unwrap().clear();
+        }
+
+        fn is_connected(&self) -> bool {
+            !self.reports.lock().unwrap().is_empty()
+        }
+
+        fn read_settings(&self) -> ReadSettings {
+            ReadSettings::default()
+        }
+
+        fn read_input_into(&self, buf: &mut [u8], _timeout_ms: i32) -> Result<usize, ControllerError> {
+            let report = self.reports.lock().unwrap().pop_front().ok_or(ControllerError::NotConnected)?;
+            // An empty report stands in for a failed read
+            if report.is_empty() {
+                return Err(ControllerError::Read("device error".to_string()));
+            }
+            buf[..report.len()].copy_from_slice(&report);
+            Ok(report.len())
+        }
+
+        fn parse_packet_at(&self, raw: &[u8], host_timestamp_us: u64) -> Result<Packet, String> {
+            let mut packet = crate::input_parser::parse_packet(raw)?;
+            if let Packet::InputFrame(input) = &mut packet {
+                input.host_timestamp_us = host_timestamp_us;
+            }
+            Ok(packet)
+        }
+
+        fn send_feature_report(&self, _data: &[u8]) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn get_feature_report(&self, report_id: u8, len: usize) -> Result<Vec<u8>, String> {
+            let mut reply = vec![0u8; len];
+            reply[0] = report_id;
+            Ok(reply)
+        }
+
+        fn send_haptic_pulse(&self, _pulse: HapticPulse) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn input_report(trigger: u8) -> Vec<u8> {
+        let mut report = vec![0u8; REPORT_LEN];
+        report[0] = CONTROLLER_REPORT_ID;
+        report[2] = INPUT_PACKET;
+        report[13] = trigger;
+        report
+    }
+
+    #[test]
+    fn test_stream_reads_through_handle() {
+        let absent = FakeController::handle("absent", false, Vec::new());
+        let fake = FakeController::handle("fake", true, vec![input_report(10), input_report(200)]);
+        let (driver, info) = detect_first(&[absent, fake]).unwrap();
+        assert_eq!((driver.kind(), info.product_name.as_str()), ("fake", "fake"));
+
+        let (tx, rx) = mpsc::channel::<ControllerInput>();
+        let (end_tx, end_rx) = mpsc::channel::<StreamEvent>();
+        let stream = InputStream::spawn_with_events(
+            driver,
+            u64::MAX,
+            move |frame| tx.send(frame.input.clone()).is_ok(),
+            move |event| end_tx.send(event).unwrap(),
+        );
+        let first = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        let second = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!((first.triggers.left, second.triggers.left), (10, 200));
+        // Out of reports the fake reads as unplugged, which ends the stream
+        assert_eq!(end_rx.recv_timeout(Duration::from_secs(2)).unwrap(), StreamEvent::Ended(StreamEnd::Disconnected));
+        stream.stop();
+
+        assert!(detect_first(&[FakeController::handle("absent", false, Vec::new())]).is_none());
+    }
+
+    #[test]
+    fn test_stream_ends_on_read_error() {
+        let fake = FakeController::handle("fake", true, vec![input_report(10), Vec::new(), input_report(20)]);
+        let (tx, rx) = mpsc::channel::<ControllerInput>();
+        let (end_tx, end_rx) = mpsc::channel::<StreamEvent>();
+        let stream = InputStream::spawn_with_events(
+            fake,
+            u64::MAX,
+            move |frame| tx.send(frame.input.clone()).is_ok(),
+            move |event| end_tx.send(event).unwrap(),
+        );
+        assert_eq!(rx.recv_timeout(Duration::from_secs(2)).unwrap().triggers.left, 10);
+        assert_eq!(
+            end_rx.recv_timeout(Duration::from_secs(2)).unwrap(),
+            StreamEvent::Ended(StreamEnd::ReadError("Read error: device error".to_string()))
+        );
+        // Nothing is read after the failure
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+        assert!(!stream.is_running());
+        stream.stop();
+
+    }
+}
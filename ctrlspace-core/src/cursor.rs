@@ -0,0 +1,205 @@
+//! Pad-to-cursor movement for pads in mouse mode.
+//!
+//! Relative mode moves the cursor by each frame's finger movement with
+//! touchpad-style acceleration, so quick flicks cover more screen. Anchored
+//! mode treats the touch-down point as the origin and keeps the cursor's offset
+//! from where it started proportional to the finger's offset from that point:
+//! putting the finger back puts the cursor back. Lifting re-anchors on the
+//! next touch.
+
+use serde::{Deserialize, Serialize};
+use crate::curve::Curve;
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{AnalogMode, Profile};
+
+/// Pad units per pixel at sensitivity 1.0 (a full pad width is ~1000 px)
+pub const PAD_UNITS_PER_PIXEL: f32 = 64.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorMode {
+    Relative,
+    /// Touch-down point is the origin; cursor offset follows finger offset
+    AnchoredAbsolute,
+}
+
+/// Per-frame movement (px) at which relative mode reaches its top gain; an
+/// acceleration curve's x runs from 0 to this speed
+pub const ACCELERATION_SPEED: f32 = 20.0;
+/// Extra gain at full speed (1.0 doubles fast movement) without a curve
+const ACCELERATION_GAIN: f32 = 1.0;
+
+/// Cursor movement from one frame of one pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CursorMove {
+    pub pad: Pad,
+    pub dx: i32,
+    /// Positive is down, as on screen
+    pub dy: i32,
+}
+
+/// Cursor state for one pad
+#[derive(Debug, Clone, Default)]
+pub struct PadCursor {
+    /// Finger position at touch-down
+    origin: Option<(i16, i16)>,
+    last: (i16, i16),
+    /// Sub-pixel movement carried to the next frame (relative)
+    remainder: (f32, f32),
+    /// Pixels already moved since touch-down (anchored)
+    moved: (i32, i32),
+}
+
+impl PadCursor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pixels to move the cursor this frame
+    pub fn update(&mut self, pad: &TrackpadData, mode: CursorMode, sensitivity: f32) -> (i32, i32) {
+        self.update_with(pad, mode, sensitivity, None)
+    }
+
+    /// Like `update`, with relative-mode gain taken from `acceleration`
+    /// (speed as a fraction of `ACCELERATION_SPEED` -> gain) when given
+    pub fn update_with(
+        &mut self,
+        pad: &TrackpadData,
+        mode: CursorMode,
+        sensitivity: f32,
+        acceleration: Option<&Curve>,
+    ) -> (i32, i32) {
+        if !pad.active {
+            *self = Self::default();
+            return (0, 0);
+        }
+        let position = (pad.x, pad.y);
+        let scale = sensitivity / PAD_UNITS_PER_PIXEL;
+
+        let origin = match self.origin {
+            Some(origin) => origin,
+            None => {
+                // Touch-down: anchor here, no movement yet
+                self.origin = Some(position);
+                self.last = position;
+                return (0, 0);
+            }
+        };
+
+        // Pad Y grows upwards, screen Y downwards
+        match mode {
+            CursorMode::Relative => {
+                let dx = (position.0 as i32 - self.last.0 as i32) as f32 * scale;
+                let dy = (self.last.1 as i32 - position.1 as i32) as f32 * scale;
+                self.last = position;
+
+                let speed = (dx * dx + dy * dy).sqrt();
+                let gain = match acceleration {
+                    Some(curve) => curve.eval(speed / ACCELERATION_SPEED),
+                    None => 1.0 + ACCELERATION_GAIN * (speed / ACCELERATION_SPEED).min(1.0),
+                };
+                let dx = dx * gain + self.remainder.0;
+                let dy = dy * gain + self.remainder.1;
+                let step = (dx.trunc(), dy.trunc());
+                self.remainder = (dx - step.0, dy - step.1);
+                (step.0 as i32, step.1 as i32)
+            }
+            CursorMode::AnchoredAbsolute => {
+                let target_x = ((position.0 as i32 - origin.0 as i32) as f32 * scale).round() as i32;
+                let target_y = ((origin.1 as i32 - position.1 as i32) as f32 * scale).round() as i32;
+                let step = (target_x - self.moved.0, target_y - self.moved.1);
+                self.moved = (target_x, target_y);
+                step
+            }
+        }
+    }
+}
+
+/// Cursor movement for both pads, driven by the active profile's pad modes
+#[derive(Debug, Clone, Default)]
+pub struct CursorTracker {
+    left: PadCursor,
+    right: PadCursor,
+}
+
+impl CursorTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>) -> Vec<CursorMove> {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return Vec::new(),
+        };
+        let pads = [
+            (Pad::Left, &mut self.left, &input.left_trackpad, &profile.left_pad),
+            (Pad::Right, &mut self.right, &input.right_trackpad, &profile.right_pad),
+        ];
+
+        let mut moves = Vec::new();
+        for (pad, cursor, data, mode) in pads {
+            if let AnalogMode::Mouse { sensitivity, cursor: cursor_mode, acceleration } = mode {
+                let (dx, dy) = cursor.update_with(data, *cursor_mode, *sensitivity, acceleration.as_ref());
+                if (dx, dy) != (0, 0) {
+                    moves.push(CursorMove { pad, dx, dy });
+                }
+            }
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{CurvePoint, Interpolation};
+
+    fn touch(x: i16, y: i16) -> TrackpadData {
+        TrackpadData { x, y, active: true }
+    }
+
+    // Total movement over a path of pad positions
+    fn total(mode: CursorMode, path: &[(i16, i16)]) -> (i32, i32) {
+        let mut cursor = PadCursor::new();
+        path.iter().fold((0, 0), |(x, y), &(px, py)| {
+            let (dx, dy) = cursor.update(&touch(px, py), mode, 1.0);
+            (x + dx, y + dy)
+        })
+    }
+
+    #[test]
+    fn test_anchored_follows_finger_displacement() {
+        let path: Vec<(i16, i16)> = (0..100).map(|i| (5000 + i * 30, -i * 10)).collect();
+        assert_eq!(total(CursorMode::AnchoredAbsolute, &path), (46, 15));
+
+        // Returning to the touch-down point returns the cursor, however fast
+        let flick = [(0, 0), (12800, 0), (0, 0)];
+        assert_eq!(total(CursorMode::AnchoredAbsolute, &flick), (0, 0));
+        // Relative mode accelerates the fast part of the same flick
+        let (x, _) = total(CursorMode::Relative, &flick[..2]);
+        assert!(x > 200, "flick moved {} px", x);
+
+        // A flat acceleration curve turns it off
+        let flat = Curve::new(
+            vec![CurvePoint { x: 0.0, y: 1.0 }, CurvePoint { x: 1.0, y: 1.0 }],
+            Interpolation::Linear,
+        )
+        .unwrap();
+        let mut cursor = PadCursor::new();
+        cursor.update_with(&touch(0, 0), CursorMode::Relative, 1.0, Some(&flat));
+        assert_eq!(cursor.update_with(&touch(12800, 0), CursorMode::Relative, 1.0, Some(&flat)), (200, 0));
+    }
+
+    #[test]
+    fn test_lift_reanchors() {
+        let mut cursor = PadCursor::new();
+        assert_eq!(cursor.update(&touch(1000, 0), CursorMode::AnchoredAbsolute, 1.0), (0, 0));
+        assert_eq!(cursor.update(&touch(1640, 0), CursorMode::AnchoredAbsolute, 1.0), (10, 0));
+        cursor.update(&TrackpadData::default(), CursorMode::AnchoredAbsolute, 1.0);
+        // New touch far away is a new origin, not a jump
+        assert_eq!(cursor.update(&touch(-20000, 9000), CursorMode::AnchoredAbsolute, 1.0), (0, 0));
+        assert_eq!(cursor.update(&touch(-20000, 8360), CursorMode::AnchoredAbsolute, 1.0), (0, 10));
+    }
+}
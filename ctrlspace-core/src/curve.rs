@@ -0,0 +1,222 @@
+//! Editable response curves: control points plus an interpolation mode, the
+//! one representation behind the UI's curve editor wherever an input is
+//! reshaped: stick, pad and trigger responses (`ResponseCurve::Custom`) and
+//! cursor acceleration.
+//!
+//! Input runs 0.0..=1.0 and is clamped; output is whatever the points say
+//! (a gain can exceed 1.0). A curve is validated and sampled into a lookup
+//! table when it is built or deserialized, so evaluating it per frame is a
+//! table lookup and one lerp.
+
+use serde::{Deserialize, Serialize};
+
+/// Lookup table resolution
+const TABLE_SIZE: usize = 256;
+
+/// Most control points a curve may have
+pub const MAX_CURVE_POINTS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Straight segments between points
+    Linear,
+    /// Smooth through every point without overshooting (monotone cubic)
+    Smooth,
+    /// Each point's value holds until the next point
+    Step,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Serialized form of a [`Curve`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveSpec {
+    pub points: Vec<CurvePoint>,
+    #[serde(default = "default_interpolation")]
+    pub interpolation: Interpolation,
+}
+
+fn default_interpolation() -> Interpolation {
+    Interpolation::Linear
+}
+
+/// A validated curve with its lookup table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "CurveSpec", into = "CurveSpec")]
+pub struct Curve {
+    spec: CurveSpec,
+    table: Vec<f32>,
+}
+
+impl Curve {
+    pub fn new(points: Vec<CurvePoint>, interpolation: Interpolation) -> Result<Self, String> {
+        Self::try_from(CurveSpec { points, interpolation })
+    }
+
+    /// Identity: output equals input
+    pub fn linear() -> Self {
+        Self::new(vec![CurvePoint { x: 0.0, y: 0.0 }, CurvePoint { x: 1.0, y: 1.0 }], Interpolation::Linear).unwrap()
+    }
+
+    /// `x^exponent` approximated by a smooth curve through 9 points
+    pub fn power(exponent: f32) -> Result<Self, String> {
+        if !(exponent > 0.0 && exponent.is_finite()) {
+            return Err(format!("Curve exponent must be positive: {}", exponent));
+        }
+        let points = (0..=8)
+            .map(|i| {
+                let x = i as f32 / 8.0;
+                CurvePoint { x, y: x.powf(exponent) }
+            })
+            .collect();
+        Self::new(points, Interpolation::Smooth)
+    }
+
+    pub fn points(&self) -> &[CurvePoint] {
+        &self.spec.points
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.spec.interpolation
+    }
+
+    /// Output at `x`, clamped to 0.0..=1.0
+    pub fn eval(&self, x: f32) -> f32 {
+        let pos = if x.is_nan() { 0.0 } else { x.clamp(0.0, 1.0) } * (TABLE_SIZE - 1) as f32;
+        let i = (pos as usize).min(TABLE_SIZE - 2);
+        if self.spec.interpolation == Interpolation::Step {
+            return self.table[pos.round() as usize];
+        }
+        let t = pos - i as f32;
+        self.table[i] + (self.table[i + 1] - self.table[i]) * t
+    }
+
+    /// `count` evenly spaced (x, y) samples, for drawing the curve
+    pub fn sample(&self, count: usize) -> Vec<(f32, f32)> {
+        let count = count.max(2);
+        (0..count)
+            .map(|i| {
+                let x = i as f32 / (count - 1) as f32;
+                (x, self.eval(x))
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<CurveSpec> for Curve {
+    type Error = String;
+
+    fn try_from(spec: CurveSpec) -> Result<Self, String> {
+        let points = &spec.points;
+        if points.len() < 2 || points.len() > MAX_CURVE_POINTS {
+            return Err(format!("A curve needs 2-{} points, got {}", MAX_CURVE_POINTS, points.len()));
+        }
+        if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite() || !(0.0..=1.0).contains(&p.x)) {
+            return Err("Curve points need finite values and x between 0.0 and 1.0".to_string());
+        }
+        if points.windows(2).any(|w| w[1].x <= w[0].x) {
+            return Err("Curve points must be sorted by strictly increasing x".to_string());
+        }
+
+        let slopes = match spec.interpolation {
+            Interpolation::Smooth => monotone_slopes(points),
+            _ => Vec::new(),
+        };
+        let table = (0..TABLE_SIZE)
+            .map(|i| interpolate(&spec, &slopes, i as f32 / (TABLE_SIZE - 1) as f32))
+            .collect();
+        Ok(Self { spec, table })
+    }
+}
+
+impl From<Curve> for CurveSpec {
+    fn from(curve: Curve) -> Self {
+        curve.spec
+    }
+}
+
+// Tangents for a monotone cubic Hermite spline (Fritsch-Carlson)
+fn monotone_slopes(points: &[CurvePoint]) -> Vec<f32> {
+    let secants: Vec<f32> = points.windows(2).map(|w| (w[1].y - w[0].y) / (w[1].x - w[0].x)).collect();
+    let mut slopes = Vec::with_capacity(points.len());
+    slopes.push(secants[0]);
+    for pair in secants.windows(2) {
+        slopes.push(if pair[0] * pair[1] <= 0.0 { 0.0 } else { (pair[0] + pair[1]) / 2.0 });
+    }
+    slopes.push(secants[secants.len() - 1]);
+
+    for (k, &secant) in secants.iter().enumerate() {
+        if secant == 0.0 {
+            slopes[k] = 0.0;
+            slopes[k + 1] = 0.0;
+            continue;
+        }
+        let (a, b) = (slopes[k] / secant, slopes[k + 1] / secant);
+        let length = a.hypot(b);
+        if length > 3.0 {
+            slopes[k] = 3.0 * a / length * secant;
+            slopes[k + 1] = 3.0 * b / length * secant;
+        }
+    }
+    slopes
+}
+
+// Exact curve value at `x`; outside the points the end values hold
+fn interpolate(spec: &CurveSpec, slopes: &[f32], x: f32) -> f32 {
+    let points = &spec.points;
+    let first = points[0];
+    let last = points[points.len() - 1];
+    if x <= first.x {
+        return first.y;
+    }
+    if x >= last.x {
+        return last.y;
+    }
+    let k = points.windows(2).position(|w| x < w[1].x).unwrap_or(points.len() - 2);
+    let (p0, p1) = (points[k], points[k + 1]);
+    let h = p1.x - p0.x;
+    let t = (x - p0.x) / h;
+    match spec.interpolation {
+        Interpolation::Step => p0.y,
+        Interpolation::Linear => p0.y + (p1.y - p0.y) * t,
+        Interpolation::Smooth => {
+            let (t2, t3) = (t * t, t * t * t);
+            (2.0 * t3 - 3.0 * t2 + 1.0) * p0.y
+                + (t3 - 2.0 * t2 + t) * h * slopes[k]
+                + (-2.0 * t3 + 3.0 * t2) * p1.y
+                + (t3 - t2) * h * slopes[k + 1]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_modes_and_serialization() {
+        let linear = Curve::linear();
+        assert!((linear.eval(0.3) - 0.3).abs() < 1e-3);
+        assert_eq!(linear.eval(2.0), 1.0);
+        assert_eq!(linear.eval(f32::NAN), 0.0);
+
+        // Smooth passes through its points and stays within them
+        let squared = Curve::power(2.0).unwrap();
+        assert!((squared.eval(0.5) - 0.25).abs() < 0.01);
+        assert!(squared.sample(64).iter().all(|&(_, y)| (0.0..=1.0).contains(&y)));
+
+        let json = r#"{"points": [{"x": 0.0, "y": 0.0}, {"x": 0.5, "y": 1.0}], "interpolation": "step"}"#;
+        let step: Curve = serde_json::from_str(json).unwrap();
+        assert_eq!((step.eval(0.2), step.eval(0.7)), (0.0, 1.0));
+        let round_trip: Curve = serde_json::from_str(&serde_json::to_string(&step).unwrap()).unwrap();
+        assert_eq!(round_trip, step);
+
+        let unsorted = r#"{"points": [{"x": 0.5, "y": 0.0}, {"x": 0.2, "y": 1.0}]}"#;
+        assert!(serde_json::from_str::<Curve>(unsorted).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Dual-pad on-screen keyboard (daisywheel). The left pad picks one of eight
+//! petals, the right pad picks one of the petal's four characters, and a right
+//! pad click types it. Face buttons cover the keys that don't fit on a petal.
+//!
+//! The backend only resolves input into key actions and overlay events; drawing
+//! the wheel is up to the overlay UI.
+
+use serde::Serialize;
+use std::f32::consts::PI;
+use crate::input_parser::{ButtonState, ControllerInput, TrackpadData};
+use crate::overlay::OverlayEvent;
+use crate::profiles::{Action, ButtonId};
+
+pub const PETALS: usize = 8;
+pub const KEYS_PER_PETAL: usize = 4;
+
+/// Pad deflection (fraction of full scale) needed to select a sector, so a
+/// thumb resting near the center doesn't flicker between petals
+const SELECT_RADIUS: f32 = 0.35;
+
+// Petals start at the top and run clockwise; keys within a petal run up, right, down, left
+const LETTERS: [[&str; KEYS_PER_PETAL]; PETALS] = [
+    ["a", "b", "c", "d"],
+    ["e", "f", "g", "h"],
+    ["i", "j", "k", "l"],
+    ["m", "n", "o", "p"],
+    ["q", "r", "s", "t"],
+    ["u", "v", "w", "x"],
+    ["y", "z", ",", "."],
+    ["'", "-", "/", ";"],
+];
+
+const SYMBOLS: [[&str; KEYS_PER_PETAL]; PETALS] = [
+    ["1", "2", "3", "4"],
+    ["5", "6", "7", "8"],
+    ["9", "0", "-", "="],
+    ["[", "]", "\\", ";"],
+    ["'", ",", ".", "/"],
+    ["`", "tab", "escape", "delete"],
+    ["up", "right", "down", "left"],
+    ["home", "end", "pageup", "pagedown"],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardPage {
+    Letters,
+    Symbols,
+}
+
+impl KeyboardPage {
+    fn petals(self) -> &'static [[&'static str; KEYS_PER_PETAL]; PETALS] {
+        match self {
+            KeyboardPage::Letters => &LETTERS,
+            KeyboardPage::Symbols => &SYMBOLS,
+        }
+    }
+}
+
+/// What one frame of input produced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaisywheelUpdate {
+    /// Keys to type, in order
+    pub actions: Vec<Action>,
+    /// Layout/highlight changes for the overlay
+    pub events: Vec<OverlayEvent>,
+    /// The user closed the keyboard (B)
+    pub closed: bool,
+}
+
+/// Sector of a pad position, 0 at the top and counting clockwise.
+/// `None` while the pad isn't touched or the thumb is near the center.
+pub fn pad_sector(pad: &TrackpadData, sectors: usize) -> Option<usize> {
+    if !pad.active || sectors == 0 {
+        return None;
+    }
+    let (x, y) = (pad.x as f32 / 32768.0, pad.y as f32 / 32768.0);
+    if (x * x + y * y).sqrt() < SELECT_RADIUS {
+        return None;
+    }
+    let angle = x.atan2(y).rem_euclid(2.0 * PI);
+    let width = 2.0 * PI / sectors as f32;
+    Some(((angle / width).round() as usize) % sectors)
+}
+
+/// Daisywheel typing state, fed one frame at a time
+#[derive(Debug, Clone)]
+pub struct Daisywheel {
+    page: KeyboardPage,
+    shift: bool,
+    petal: Option<usize>,
+    key: Option<usize>,
+    previous: ButtonState,
+}
+
+impl Default for Daisywheel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Daisywheel {
+    pub fn new() -> Self {
+        Self {
+            page: KeyboardPage::Letters,
+            shift: false,
+            petal: None,
+            key: None,
+            previous: ButtonState::default(),
+        }
+    }
+
+    /// Full layout for the overlay; sent on open and whenever page/shift change
+    pub fn layout_event(&self) -> OverlayEvent {
+        OverlayEvent::KeyboardOpened {
+            page: self.page,
+            shift: self.shift,
+            petals: self
+                .page
+                .petals()
+                .iter()
+                .map(|petal| petal.iter().map(|key| self.label(key)).collect())
+                .collect(),
+        }
+    }
+
+    pub fn update(&mut self, input: &ControllerInput) -> DaisywheelUpdate {
+        let mut update = DaisywheelUpdate::default();
+        let pressed = |button: ButtonId| {
+            button.is_pressed(&input.buttons) && !button.is_pressed(&self.previous)
+        };
+
+        if pressed(ButtonId::B) {
+            update.closed = true;
+            update.events.push(OverlayEvent::KeyboardClosed);
+            self.previous = input.buttons.clone();
+            return update;
+        }
+
+        let mut layout_changed = false;
+        if pressed(ButtonId::Lb) {
+            self.shift = !self.shift;
+            layout_changed = true;
+        }
+        if pressed(ButtonId::Rb) {
+            self.page = match self.page {
+                KeyboardPage::Letters => KeyboardPage::Symbols,
+                KeyboardPage::Symbols => KeyboardPage::Letters,
+            };
+            layout_changed = true;
+        }
+
+        let petal = pad_sector(&input.left_trackpad, PETALS);
+        let key = petal.and(pad_sector(&input.right_trackpad, KEYS_PER_PETAL));
+        if (petal, key) != (self.petal, self.key) {
+            self.petal = petal;
+            self.key = key;
+            update.events.push(OverlayEvent::KeyboardHighlight { petal, key });
+        }
+
+        if pressed(ButtonId::RpadClick) {
+            if let (Some(petal), Some(key)) = (self.petal, self.key) {
+                let name = self.page.petals()[petal][key];
+                update.actions.push(self.key_action(name));
+                if self.shift {
+                    // Shift is one-shot, like a phone keyboard
+                    self.shift = false;
+                    layout_changed = true;
+                }
+            }
+        }
+        if pressed(ButtonId::X) {
+            update.actions.push(key_press("backspace"));
+        }
+        if pressed(ButtonId::Y) {
+            update.actions.push(key_press("space"));
+        }
+        if pressed(ButtonId::A) {
+            update.actions.push(key_press("enter"));
+        }
+
+        if layout_changed {
+            update.events.push(self.layout_event());
+        }
+        self.previous = input.buttons.clone();
+        update
+    }
+
+    fn label(&self, name: &str) -> String {
+        if self.shift && name.len() == 1 {
+            name.to_uppercase()
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn key_action(&self, name: &str) -> Action {
+        if self.shift && name.len() == 1 {
+            Action::Keys {
+                keys: vec!["shift".to_string(), name.to_string()],
+            }
+        } else {
+            key_press(name)
+        }
+    }
+}
+
+fn key_press(name: &str) -> Action {
+    Action::Key {
+        key: name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(x: i16, y: i16) -> TrackpadData {
+        TrackpadData { x, y, active: true }
+    }
+
+    #[test]
+    fn test_pad_sector() {
+        assert_eq!(pad_sector(&pad(0, 30000), PETALS), Some(0));
+        assert_eq!(pad_sector(&pad(30000, 0), PETALS), Some(2));
+        assert_eq!(pad_sector(&pad(-21000, 21000), PETALS), Some(7));
+        assert_eq!(pad_sector(&pad(0, -30000), KEYS_PER_PETAL), Some(2));
+        assert_eq!(pad_sector(&pad(100, 200), PETALS), None);
+        assert_eq!(pad_sector(&TrackpadData::default(), PETALS), None);
+    }
+
+    #[test]
+    fn test_type_with_shift_and_page() {
+        let mut wheel = Daisywheel::new();
+        // Petal 1 (up-right), key 2 (down) -> "g"
+        let mut input = ControllerInput {
+            left_trackpad: pad(21000, 21000),
+            right_trackpad: pad(0, -30000),
+            ..Default::default()
+        };
+        let update = wheel.update(&input);
+        assert_eq!(update.events, vec![OverlayEvent::KeyboardHighlight { petal: Some(1), key: Some(2) }]);
+
+        input.buttons.rpad_click = true;
+        assert_eq!(wheel.update(&input).actions, vec![key_press("g")]);
+        // Held click doesn't repeat
+        assert!(wheel.update(&input).actions.is_empty());
+
+        input.buttons.rpad_click = false;
+        input.buttons.lb = true;
+        wheel.update(&input);
+        input.buttons.lb = false;
+        input.buttons.rpad_click = true;
+        let update = wheel.update(&input);
+        assert_eq!(
+            update.actions,
+            vec![Action::Keys { keys: vec!["shift".to_string(), "g".to_string()] }]
+        );
+        // Shift released after one key
+        assert!(matches!(update.events.last(), Some(OverlayEvent::KeyboardOpened { shift: false, .. })));
+
+        input.buttons.rpad_click = false;
+        input.buttons.rb = true;
+        wheel.update(&input);
+        input.buttons.rb = false;
+        input.buttons.rpad_click = true;
+        assert_eq!(wheel.update(&input).actions, vec![key_press("7")]);
+
+        input.buttons.b = true;
+        assert!(wheel.update(&input).closed);
+    }
+}
@@ -0,0 +1,223 @@
+//! Developer console: a small line-based language run against the live
+//! manager, for poking at the protocol without rebuilding the app. One
+//! command per line, `#` starts a comment, numbers are hex for bytes and
+//! decimal otherwise:
+//!
+//! ```text
+//! send fr 81 00          # feature report out (first byte is the command)
+//! get fr 83 64           # feature report in: id, length
+//! read 200ms             # raw reports arriving within the window
+//! dump state             # manager state as JSON
+//! haptic left 5000 3     # side, amplitude, count [, period µs]
+//! ```
+//!
+//! The whole script is parsed before anything runs; execution stops at the
+//! first failing command.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use crate::haptics::{HapticPulse, HapticSide};
+use crate::registers::ReportRateConfig;
+use crate::steam_controller::{ReadSettings, SteamControllerManager};
+
+/// Longest `read` window
+pub const MAX_READ_MS: u64 = 5000;
+
+/// Reports listed per `read`; the rest are only counted
+pub const MAX_READ_REPORTS: usize = 64;
+
+// Period of the settings "Test" buzz, used when a `haptic` line gives none
+const DEFAULT_HAPTIC_PERIOD: u16 = 1250;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugCommand {
+    SendFeature(Vec<u8>),
+    GetFeature { id: u8, len: usize },
+    Read(Duration),
+    DumpState,
+    Haptic(HapticPulse),
+}
+
+/// One executed line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DebugLine {
+    /// 1-based line number in the script
+    pub line: usize,
+    pub command: String,
+    pub output: String,
+    pub ok: bool,
+}
+
+/// Manager state printed by `dump state`
+#[derive(Debug, Clone, Serialize)]
+struct StateDump {
+    connected: bool,
+    healthy: bool,
+    lizard_mode: bool,
+    read_settings: ReadSettings,
+    report_rate: ReportRateConfig,
+    drift_offset: (i16, i16),
+}
+
+fn hex_byte(token: &str) -> Result<u8, String> {
+    let digits = token.trim_start_matches("0x");
+    u8::from_str_radix(digits, 16).map_err(|_| format!("'{}' is not a hex byte", token))
+}
+
+fn number<T: std::str::FromStr>(token: &str, what: &str) -> Result<T, String> {
+    token.parse().map_err(|_| format!("'{}' is not a valid {}", token, what))
+}
+
+// "200ms", "2s", or a bare number of milliseconds
+fn duration(token: &str) -> Result<Duration, String> {
+    let ms = if let Some(ms) = token.strip_suffix("ms") {
+        number::<u64>(ms, "duration")?
+    } else if let Some(secs) = token.strip_suffix('s') {
+        number::<u64>(secs, "duration")?.saturating_mul(1000)
+    } else {
+        number::<u64>(token, "duration")?
+    };
+    if ms == 0 || ms > MAX_READ_MS {
+        return Err(format!("Read window must be between 1 and {} ms", MAX_READ_MS));
+    }
+    Ok(Duration::from_millis(ms))
+}
+
+fn parse_line(line: &str) -> Result<Option<DebugCommand>, String> {
+    let code = line.split('#').next().unwrap_or("");
+    let tokens: Vec<&str> = code.split_whitespace().collect();
+    let command = match tokens.as_slice() {
+        [] => return Ok(None),
+        ["send", "fr", bytes @ ..] if !bytes.is_empty() => {
+            DebugCommand::SendFeature(bytes.iter().map(|b| hex_byte(b)).collect::<Result<_, _>>()?)
+        }
+        ["get", "fr", id, len] => DebugCommand::GetFeature { id: hex_byte(id)?, len: number(len, "length")? },
+        ["read", window] => DebugCommand::Read(duration(window)?),
+        ["dump", "state"] => DebugCommand::DumpState,
+        ["haptic", side, amplitude, count, rest @ ..] if rest.len() <= 1 => DebugCommand::Haptic(HapticPulse {
+            side: match *side {
+                "left" => HapticSide::Left,
+                "right" => HapticSide::Right,
+                other => return Err(format!("Unknown side '{}' (left or right)", other)),
+            },
+            amplitude: number(amplitude, "amplitude")?,
+            count: number(count, "count")?,
+            period: match rest.first() {
+                Some(period) => number(period, "period")?,
+                None => DEFAULT_HAPTIC_PERIOD,
+            },
+        }),
+        _ => return Err(format!("Unknown command '{}'", code.trim())),
+    };
+    Ok(Some(command))
+}
+
+/// Parse a whole script; commands come with their 1-based line numbers
+pub fn parse_debug_script(script: &str) -> Result<Vec<(usize, DebugCommand)>, String> {
+    let mut commands = Vec::new();
+    for (i, line) in script.lines().enumerate() {
+        if let Some(command) = parse_line(line).map_err(|e| format!("Line {}: {}", i + 1, e))? {
+            commands.push((i + 1, command));
+        }
+    }
+    Ok(commands)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+fn execute(manager: &SteamControllerManager, command: &DebugCommand) -> Result<String, String> {
+    match command {
+        DebugCommand::SendFeature(bytes) => {
+            manager.send_feature_report(bytes)?;
+            Ok(format!("sent {}", hex(bytes)))
+        }
+        DebugCommand::GetFeature { id, len } => manager.get_feature_report(*id, *len).map(|reply| hex(&reply)),
+        DebugCommand::Read(window) => {
+            let deadline = Instant::now() + *window;
+            let mut lines = Vec::new();
+            let mut count = 0;
+            while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|d| !d.is_zero()) {
+                let timeout_ms = left.as_millis().clamp(1, i32::MAX as u128) as i32;
+                if let Some(raw) = manager.read_input_blocking(timeout_ms)? {
+                    count += 1;
+                    if lines.len() < MAX_READ_REPORTS {
+                        lines.push(hex(&raw));
+                    }
+                }
+            }
+            if count > lines.len() {
+                lines.push(format!("... {} more", count - lines.len()));
+            }
+            lines.push(format!("{} report(s) in {} ms", count, window.as_millis()));
+            Ok(lines.join("\n"))
+        }
+        DebugCommand::DumpState => serde_json::to_string_pretty(&StateDump {
+            connected: manager.is_connected(),
+            healthy: manager.is_healthy(),
+            lizard_mode: manager.lizard_mode(),
+            read_settings: manager.read_settings(),
+            report_rate: manager.report_rate(),
+            drift_offset: manager.drift_offset(),
+        })
+        .map_err(|e| e.to_string()),
+        DebugCommand::Haptic(pulse) => {
+            manager.send_haptic_pulse(*pulse)?;
+            Ok(format!("{:?} pulse: amplitude {}, period {} µs, count {}", pulse.side, pulse.amplitude, pulse.period, pulse.count))
+        }
+    }
+}
+
+/// Parse and run `script`, stopping at the first failing command. A script
+/// that doesn't parse runs nothing.
+pub fn run_debug_script(manager: &SteamControllerManager, script: &str) -> Result<Vec<DebugLine>, String> {
+    let commands = parse_debug_script(script)?;
+    let source: Vec<&str> = script.lines().collect();
+    let mut out = Vec::new();
+    for (line, command) in commands {
+        let result = execute(manager, &command);
+        let ok = result.is_ok();
+        out.push(DebugLine {
+            line,
+            command: source[line - 1].trim().to_string(),
+            output: result.unwrap_or_else(|e| e),
+            ok,
+        });
+        if !ok {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "# disable lizard mode\nsend fr 81 00\n\nget fr 0x83 64\nread 200ms\nread 2s  # longer\ndump state\nhaptic left 5000 3\nhaptic right 100 2 900\n";
+        let commands: Vec<DebugCommand> = parse_debug_script(script).unwrap().into_iter().map(|(_, c)| c).collect();
+        assert_eq!(
+            commands,
+            vec![
+                DebugCommand::SendFeature(vec![0x81, 0x00]),
+                DebugCommand::GetFeature { id: 0x83, len: 64 },
+                DebugCommand::Read(Duration::from_millis(200)),
+                DebugCommand::Read(Duration::from_secs(2)),
+                DebugCommand::DumpState,
+                DebugCommand::Haptic(HapticPulse { side: HapticSide::Left, amplitude: 5000, period: 1250, count: 3 }),
+                DebugCommand::Haptic(HapticPulse { side: HapticSide::Right, amplitude: 100, period: 900, count: 2 }),
+            ]
+        );
+        assert_eq!(parse_debug_script("send fr 81\nread 200ms").unwrap()[1].0, 2);
+
+        assert_eq!(parse_debug_script("send fr 81\nsend fr zz").unwrap_err(), "Line 2: 'zz' is not a hex byte");
+        assert_eq!(parse_debug_script("read 10s").unwrap_err(), "Line 1: Read window must be between 1 and 5000 ms");
+        assert_eq!(parse_debug_script("haptic up 1 1").unwrap_err(), "Line 1: Unknown side 'up' (left or right)");
+        assert_eq!(parse_debug_script("reboot").unwrap_err(), "Line 1: Unknown command 'reboot'");
+        assert!(parse_debug_script("send fr").is_err());
+        assert!(parse_debug_script("haptic left 70000 1").is_err());
+    }
+}
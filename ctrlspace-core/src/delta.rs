@@ -0,0 +1,167 @@
+//! Changed-fields-only frames for bandwidth-sensitive consumers (IPC clients,
+//! WebSockets). Each delta carries just the input fields that changed since the
+//! previous frame; every `keyframe_interval` frames a full keyframe is sent so a
+//! consumer that missed a frame (or joined late) resynchronises.
+//!
+//! ```text
+//! {"seq":1,"coalesced":0,"keyframe":true,"input":{...full ControllerInput...}}
+//! {"seq":2,"coalesced":0,"keyframe":false,"input":{"timestamp":1234,"host_timestamp_us":5678,"buttons":{"a":true}}}
+//! ```
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::input_stream::InputFrame;
+
+/// Event name for delta-encoded frames
+pub const CONTROLLER_INPUT_DELTA_EVENT: &str = "controller-input-delta";
+
+/// Frames between keyframes (about a second at the wired report rate)
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 250;
+
+/// A frame with only the changed input fields, or the full input on keyframes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeltaFrame {
+    pub seq: u64,
+    pub coalesced: u64,
+    pub keyframe: bool,
+    /// Partial `ControllerInput`: nested objects hold only changed leaves
+    pub input: Value,
+}
+
+/// Fields of `new` that differ from `old`, recursing into objects.
+/// `None` when nothing changed.
+pub fn diff_values(old: &Value, new: &Value) -> Option<Value> {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let changed: Map<String, Value> = new
+                .iter()
+                .filter_map(|(key, value)| {
+                    let diff = match old.get(key) {
+                        Some(previous) => diff_values(previous, value)?,
+                        None => value.clone(),
+                    };
+                    Some((key.clone(), diff))
+                })
+                .collect();
+            if changed.is_empty() {
+                None
+            } else {
+                Some(Value::Object(changed))
+            }
+        }
+        _ if old == new => None,
+        _ => Some(new.clone()),
+    }
+}
+
+/// Merge a delta into the last full state (what a consumer does on receipt)
+pub fn apply_delta(state: &mut Value, delta: &Value) {
+    match (state, delta) {
+        (Value::Object(state), Value::Object(delta)) => {
+            for (key, value) in delta {
+                match state.get_mut(key) {
+                    Some(existing) => apply_delta(existing, value),
+                    None => {
+                        state.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (state, delta) => *state = delta.clone(),
+    }
+}
+
+/// Turns a stream of full frames into deltas plus periodic keyframes
+#[derive(Debug, Clone)]
+pub struct DeltaEncoder {
+    keyframe_interval: u32,
+    since_keyframe: u32,
+    last: Option<Value>,
+}
+
+impl DeltaEncoder {
+    pub fn new(keyframe_interval: u32) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            since_keyframe: 0,
+            last: None,
+        }
+    }
+
+    /// Force the next frame to be a keyframe (e.g. after a consumer reconnects)
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    pub fn encode(&mut self, frame: &InputFrame) -> DeltaFrame {
+        let full = serde_json::to_value(&frame.input).unwrap_or(Value::Null);
+
+        let keyframe = self.last.is_none() || self.since_keyframe + 1 >= self.keyframe_interval;
+        let input = match &self.last {
+            Some(last) if !keyframe => {
+                diff_values(last, &full).unwrap_or_else(|| Value::Object(Map::new()))
+            }
+            _ => full.clone(),
+        };
+
+        self.since_keyframe = if keyframe { 0 } else { self.since_keyframe + 1 };
+        self.last = Some(full);
+
+        DeltaFrame {
+            seq: frame.seq,
+            coalesced: frame.coalesced,
+            keyframe,
+            input,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parser::ControllerInput;
+
+    fn frame(seq: u64, input: &ControllerInput) -> InputFrame {
+        InputFrame {
+            seq,
+            coalesced: 0,
+            input: input.clone(),
+        }
+    }
+
+    #[test]
+    fn test_delta_contains_only_changes() {
+        let mut encoder = DeltaEncoder::new(DEFAULT_KEYFRAME_INTERVAL);
+        let mut input = ControllerInput::default();
+
+        let first = encoder.encode(&frame(1, &input));
+        assert!(first.keyframe);
+        let mut state = first.input.clone();
+
+        input.buttons.a = true;
+        input.stick.x = 1200;
+        let delta = encoder.encode(&frame(2, &input));
+        assert!(!delta.keyframe);
+        assert_eq!(
+            delta.input,
+            serde_json::json!({ "buttons": { "a": true }, "stick": { "x": 1200 } })
+        );
+
+        apply_delta(&mut state, &delta.input);
+        assert_eq!(state, serde_json::to_value(&input).unwrap());
+
+        // Nothing changed at all
+        assert_eq!(encoder.encode(&frame(3, &input)).input, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_periodic_keyframes() {
+        let mut encoder = DeltaEncoder::new(3);
+        let input = ControllerInput::default();
+        let keyframes: Vec<bool> = (1..=7).map(|seq| encoder.encode(&frame(seq, &input)).keyframe).collect();
+        assert_eq!(keyframes, vec![true, false, false, true, false, false, true]);
+
+        encoder.reset();
+        assert!(encoder.encode(&frame(8, &input)).keyframe);
+    }
+}
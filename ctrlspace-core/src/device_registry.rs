@@ -0,0 +1,298 @@
+//! Registry of every controller we've seen, keyed by serial number (falling
+//! back to the HID path when a device doesn't report one). Each physical unit
+//! gets a stable internal ID (`sc-1`, `sc-2`, ...) that survives reconnects
+//! and dongle slot changes, so settings, calibration and stats can hang off it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::device_settings::DeviceSettings;
+use crate::steam_controller::SteamControllerInfo;
+
+/// File in the app config dir holding the registry
+pub const DEVICE_REGISTRY_FILE: &str = "devices.json";
+
+pub const MAX_NICKNAME_LEN: usize = 32;
+
+/// A controller we've seen at least once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownDevice {
+    pub id: String,
+    /// `None` when the device never reported a usable serial
+    pub serial: Option<String>,
+    pub last_path: String,
+    pub connection_type: String,
+    pub product_name: String,
+    /// Unix seconds
+    pub first_seen: u64,
+    pub last_seen: u64,
+    #[serde(default)]
+    pub settings: DeviceSettings,
+}
+
+impl KnownDevice {
+    /// Nickname if set, otherwise the product name plus the end of the serial/ID
+    pub fn display_name(&self) -> String {
+        if let Some(nickname) = &self.settings.nickname {
+            return nickname.clone();
+        }
+        match &self.serial {
+            Some(serial) => {
+                let tail: String = serial.chars().skip(serial.chars().count().saturating_sub(4)).collect();
+                format!("{} ({})", self.product_name, tail)
+            }
+            None => format!("{} ({})", self.product_name, self.id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceRegistry {
+    next_id: u32,
+    devices: Vec<KnownDevice>,
+}
+
+/// Serial numbers some firmwares/drivers report when they have none
+pub fn usable_serial(serial: &str) -> Option<&str> {
+    let serial = serial.trim();
+    if serial.is_empty() || serial.eq_ignore_ascii_case("unknown") || serial.chars().all(|c| c == '0') {
+        None
+    } else {
+        Some(serial)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl DeviceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from disk; a missing file is an empty registry
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid device registry {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize device registry: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn devices(&self) -> &[KnownDevice] {
+        &self.devices
+    }
+
+    pub fn get(&self, id: &str) -> Option<&KnownDevice> {
+        self.devices.iter().find(|d| d.id == id)
+    }
+
+    // Serial match first, then a serial-less entry at the same path
+    fn position(&self, info: &SteamControllerInfo) -> Option<usize> {
+        let by_path = || {
+            self.devices
+                .iter()
+                .position(|d| d.serial.is_none() && d.last_path == info.path)
+        };
+        match usable_serial(&info.serial) {
+            Some(serial) => self
+                .devices
+                .iter()
+                .position(|d| d.serial.as_deref() == Some(serial))
+                .or_else(by_path),
+            None => by_path(),
+        }
+    }
+
+    /// The entry for a controller, without registering it
+    pub fn find(&self, info: &SteamControllerInfo) -> Option<&KnownDevice> {
+        self.position(info).map(|i| &self.devices[i])
+    }
+
+    /// Find (or create) the entry for a connected controller and return its ID.
+    /// A path-only entry is upgraded in place once its serial becomes known.
+    pub fn identify(&mut self, info: &SteamControllerInfo) -> String {
+        let serial = usable_serial(&info.serial).map(str::to_string);
+        let now = now_secs();
+        let existing = self.position(info);
+
+        if let Some(index) = existing {
+            let device = &mut self.devices[index];
+            if device.serial.is_none() {
+                device.serial = serial;
+            }
+            device.last_path = info.path.clone();
+            device.connection_type = info.connection_type.clone();
+            device.product_name = info.product_name.clone();
+            device.last_seen = now;
+            return device.id.clone();
+        }
+
+        self.next_id += 1;
+        let id = format!("sc-{}", self.next_id);
+        println!("🎮 New controller registered as {}", id);
+        self.devices.push(KnownDevice {
+            id: id.clone(),
+            serial,
+            last_path: info.path.clone(),
+            connection_type: info.connection_type.clone(),
+            product_name: info.product_name.clone(),
+            first_seen: now,
+            last_seen: now,
+            settings: DeviceSettings::default(),
+        });
+        id
+    }
+
+    /// Replace a device's settings
+    pub fn set_settings(&mut self, id: &str, settings: DeviceSettings) -> Result<(), String> {
+        settings.validate()?;
+        match self.devices.iter_mut().find(|d| d.id == id) {
+            Some(device) => {
+                device.settings = settings;
+                Ok(())
+            }
+            None => Err(format!("Unknown device '{}'", id)),
+        }
+    }
+
+    /// Give a device a friendly name (`None` or blank clears it). Names must be
+    /// unique so they can stand in for serials everywhere.
+    pub fn set_nickname(&mut self, id: &str, nickname: Option<String>) -> Result<(), String> {
+        let nickname = nickname
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        if let Some(name) = &nickname {
+            if name.chars().count() > MAX_NICKNAME_LEN {
+                return Err(format!("Nickname is longer than {} characters", MAX_NICKNAME_LEN));
+            }
+            let taken = self.devices.iter().any(|d| {
+                d.id != id
+                    && d.settings
+                        .nickname
+                        .as_ref()
+                        .map(|n| n.eq_ignore_ascii_case(name))
+                        .unwrap_or(false)
+            });
+            if taken {
+                return Err(format!("Another controller is already called '{}'", name));
+            }
+        }
+
+        match self.devices.iter_mut().find(|d| d.id == id) {
+            Some(device) => {
+                device.settings.nickname = nickname;
+                Ok(())
+            }
+            None => Err(format!("Unknown device '{}'", id)),
+        }
+    }
+
+    /// Forget a device; returns whether it was known
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.devices.len();
+        self.devices.retain(|d| d.id != id);
+        self.devices.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(serial: &str, path: &str) -> SteamControllerInfo {
+        SteamControllerInfo {
+            connected: true,
+            connection_type: "Wireless".to_string(),
+            product_name: "Steam Controller".to_string(),
+            serial: serial.to_string(),
+            path: path.to_string(),
+            slot: Some(0),
+            device_id: None,
+            nickname: None,
+        }
+    }
+
+    #[test]
+    fn test_serial_survives_path_change() {
+        let mut registry = DeviceRegistry::new();
+        let a = registry.identify(&info("FXAA1234", "/dev/hidraw3"));
+        let b = registry.identify(&info("FXBB5678", "/dev/hidraw4"));
+        assert_ne!(a, b);
+
+        // Same unit moved to another dongle slot / port
+        assert_eq!(registry.identify(&info("FXAA1234", "/dev/hidraw7")), a);
+        assert_eq!(registry.get(&a).unwrap().last_path, "/dev/hidraw7");
+        assert_eq!(registry.devices().len(), 2);
+
+        // IDs are never reused
+        assert!(registry.remove(&b));
+        assert_eq!(registry.identify(&info("FXCC0000", "/dev/hidraw4")), "sc-3");
+    }
+
+    #[test]
+    fn test_path_fallback_upgrades_to_serial() {
+        let mut registry = DeviceRegistry::new();
+        let id = registry.identify(&info("Unknown", "/dev/hidraw3"));
+        assert_eq!(registry.identify(&info("", "/dev/hidraw3")), id);
+        assert!(registry.get(&id).unwrap().serial.is_none());
+
+        assert_eq!(registry.identify(&info("FXAA1234", "/dev/hidraw3")), id);
+        assert_eq!(registry.identify(&info("FXAA1234", "/dev/hidraw9")), id);
+        assert_eq!(registry.get(&id).unwrap().serial.as_deref(), Some("FXAA1234"));
+    }
+
+    #[test]
+    fn test_nicknames() {
+        let mut registry = DeviceRegistry::new();
+        let couch = registry.identify(&info("FXAA1234", "/dev/hidraw3"));
+        let desk = registry.identify(&info("FXBB5678", "/dev/hidraw4"));
+        assert_eq!(registry.get(&couch).unwrap().display_name(), "Steam Controller (1234)");
+
+        registry.set_nickname(&couch, Some("  Couch SC ".to_string())).unwrap();
+        assert_eq!(registry.get(&couch).unwrap().display_name(), "Couch SC");
+        assert!(registry.set_nickname(&desk, Some("couch sc".to_string())).is_err());
+        assert!(registry.set_nickname(&desk, Some("x".repeat(MAX_NICKNAME_LEN + 1))).is_err());
+        // Renaming to its own name is fine
+        registry.set_nickname(&couch, Some("Couch SC".to_string())).unwrap();
+
+        registry.set_nickname(&couch, Some(" ".to_string())).unwrap();
+        assert!(registry.get(&couch).unwrap().settings.nickname.is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("ctrlspace-devices-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(DeviceRegistry::load(&path).unwrap(), DeviceRegistry::new());
+
+        let mut registry = DeviceRegistry::new();
+        let id = registry.identify(&info("FXAA1234", "/dev/hidraw3"));
+        let settings = DeviceSettings {
+            preferred_profile: Some("Desktop".to_string()),
+            haptic_strength: 0.5,
+            ..DeviceSettings::default()
+        };
+        registry.set_settings(&id, settings.clone()).unwrap();
+        assert!(registry.set_settings("sc-99", settings).is_err());
+        registry.save(&path).unwrap();
+        assert_eq!(DeviceRegistry::load(&path).unwrap(), registry);
+        let _ = fs::remove_file(&path);
+    }
+}
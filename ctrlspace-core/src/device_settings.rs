@@ -0,0 +1,182 @@
+//! Settings that belong to one physical controller rather than to a profile:
+//! nickname, preferred profile, haptic strength and calibration (stick center
+//! and range, gyro bias). Stored with the unit's entry in the device registry
+//! and applied on connect.
+
+use serde::{Deserialize, Serialize};
+use crate::drift::AntiDriftConfig;
+use crate::input_parser::ControllerInput;
+use crate::layout::ReportLayout;
+
+pub const DEFAULT_HAPTIC_STRENGTH: f32 = 1.0;
+
+/// Per-unit corrections applied to raw readings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calibration {
+    /// Stick reading at rest
+    #[serde(default)]
+    pub stick_center: (i16, i16),
+    /// Gyro reading at rest (pitch, yaw, roll)
+    #[serde(default)]
+    pub gyro_bias: [i16; 3],
+    /// Furthest deflection this stick reaches per axis after centering;
+    /// stretched to full scale. 0 leaves that axis unscaled.
+    #[serde(default)]
+    pub stick_extent: (u16, u16),
+    /// Optional reshaping of stick output after range scaling
+    #[serde(default)]
+    pub stick_shape: Option<StickShape>,
+}
+
+/// Square/circle restitution for stick output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickShape {
+    /// Stretch the round gate onto the full square so diagonals reach the corners
+    CircleToSquare,
+    /// Pull the square's corners in to the unit circle
+    SquareToCircle,
+}
+
+// Scale an axis so `extent` maps to full deflection
+fn scale_axis(value: i16, extent: u16) -> i16 {
+    if extent == 0 {
+        return value;
+    }
+    (value as i32 * i16::MAX as i32 / extent as i32).clamp(-(i16::MAX as i32), i16::MAX as i32) as i16
+}
+
+impl StickShape {
+    pub fn apply(self, x: i16, y: i16) -> (i16, i16) {
+        let (u, v) = (x as f32 / i16::MAX as f32, y as f32 / i16::MAX as f32);
+        let length = (u * u + v * v).sqrt();
+        let longest_axis = u.abs().max(v.abs());
+        if longest_axis == 0.0 {
+            return (x, y);
+        }
+        // Both shapes keep the direction and rescale the distance along it
+        let factor = match self {
+            StickShape::CircleToSquare => length / longest_axis,
+            StickShape::SquareToCircle => longest_axis / length,
+        };
+        let axis = |n: f32| ((n * factor).clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        (axis(u), axis(v))
+    }
+}
+
+impl Calibration {
+    pub fn apply(&self, input: &mut ControllerInput) {
+        input.stick.x = scale_axis(input.stick.x.saturating_sub(self.stick_center.0), self.stick_extent.0);
+        input.stick.y = scale_axis(input.stick.y.saturating_sub(self.stick_center.1), self.stick_extent.1);
+        if let Some(shape) = self.stick_shape {
+            let (x, y) = shape.apply(input.stick.x, input.stick.y);
+            input.stick.x = x;
+            input.stick.y = y;
+        }
+        input.gyro.pitch = input.gyro.pitch.saturating_sub(self.gyro_bias[0]);
+        input.gyro.yaw = input.gyro.yaw.saturating_sub(self.gyro_bias[1]);
+        input.gyro.roll = input.gyro.roll.saturating_sub(self.gyro_bias[2]);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSettings {
+    #[serde(default)]
+    pub nickname: Option<String>,
+    /// Profile (by name) activated when this controller connects
+    #[serde(default)]
+    pub preferred_profile: Option<String>,
+    /// Scales every haptic amplitude, 0.0 (off) to 1.0
+    #[serde(default = "default_haptic_strength")]
+    pub haptic_strength: f32,
+    #[serde(default)]
+    pub calibration: Calibration,
+    /// Re-centering of a stick that no longer rests at center
+    #[serde(default)]
+    pub anti_drift: AntiDriftConfig,
+    /// Report layout from the mapping wizard, for unrecognized firmware
+    #[serde(default)]
+    pub report_layout: Option<ReportLayout>,
+}
+
+fn default_haptic_strength() -> f32 {
+    DEFAULT_HAPTIC_STRENGTH
+}
+
+impl Default for DeviceSettings {
+    fn default() -> Self {
+        Self {
+            nickname: None,
+            preferred_profile: None,
+            haptic_strength: DEFAULT_HAPTIC_STRENGTH,
+            calibration: Calibration::default(),
+            anti_drift: AntiDriftConfig::default(),
+            report_layout: None,
+        }
+    }
+}
+
+impl DeviceSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.haptic_strength) {
+            return Err(format!(
+                "Haptic strength must be between 0.0 and 1.0: {}",
+                self.haptic_strength
+            ));
+        }
+        self.anti_drift.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_apply() {
+        let calibration = Calibration {
+            stick_center: (120, -80),
+            gyro_bias: [5, -3, i16::MAX],
+            ..Calibration::default()
+        };
+        let mut input = ControllerInput::default();
+        input.stick.x = 100;
+        input.gyro.roll = i16::MIN;
+        calibration.apply(&mut input);
+
+        assert_eq!((input.stick.x, input.stick.y), (-20, 80));
+        assert_eq!((input.gyro.pitch, input.gyro.yaw), (-5, 3));
+        assert_eq!(input.gyro.roll, i16::MIN);
+    }
+
+    #[test]
+    fn test_stick_range_and_shape() {
+        let calibration = Calibration {
+            stick_extent: (24000, 30000),
+            stick_shape: Some(StickShape::CircleToSquare),
+            ..Calibration::default()
+        };
+        // A unit that tops out at 24000 on X still reaches full scale
+        let mut input = ControllerInput::default();
+        input.stick.x = -24000;
+        calibration.apply(&mut input);
+        assert_eq!((input.stick.x, input.stick.y), (-32767, 0));
+
+        // A full-deflection diagonal on the round gate reaches the corner
+        let diagonal = (i16::MAX as f32 * std::f32::consts::FRAC_1_SQRT_2) as i16;
+        let (x, y) = StickShape::CircleToSquare.apply(diagonal, -diagonal);
+        assert!(x > 32700 && y < -32700);
+        let (x, y) = StickShape::SquareToCircle.apply(i16::MAX, i16::MAX);
+        assert!((x - diagonal).abs() <= 1 && (y - diagonal).abs() <= 1);
+    }
+
+    #[test]
+    fn test_settings_defaults_and_validation() {
+        let settings: DeviceSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, DeviceSettings::default());
+        assert!(settings.validate().is_ok());
+
+        let loud = DeviceSettings { haptic_strength: 1.5, ..DeviceSettings::default() };
+        assert!(loud.validate().is_err());
+    }
+}
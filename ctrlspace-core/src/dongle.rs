@@ -0,0 +1,212 @@
+//! Wireless dongle interface layout. The receiver enumerates one HID interface
+//! per USB interface: interface 0 is the control interface (lizard-mode
+//! keyboard/mouse and receiver commands) and interfaces 1-4 each carry one
+//! controller slot's reports. Opening the first VID/PID match lands on the
+//! control interface, whose reads never return controller input, so the slot
+//! interfaces are picked out by interface number and opened by path.
+//!
+//! Pairing is driven through the control interface: the receiver is made
+//! discoverable for a while (0xad), a controller that pairs in that window is
+//! kept with a commit (0xb3), and a paired controller is forgotten by its
+//! unit serial (0xb2).
+
+use serde::Serialize;
+use crate::enumeration::HidDeviceEntry;
+use crate::steam_controller::{SC_WIRED_PID, SC_WIRELESS_PID, STEAM_DECK_PID, VALVE_VENDOR_ID, VENDOR_USAGE_PAGE};
+use crate::steam_deck::DECK_CONTROLLER_INTERFACE;
+
+/// Controller slots per dongle
+pub const DONGLE_SLOTS: u8 = 4;
+
+/// Interface number of the dongle's control interface; slot `n` is interface `n + 1`
+const CONTROL_INTERFACE: i32 = 0;
+
+/// Receiver command: start or stop accepting new controllers
+pub const ENABLE_PAIRING: u8 = 0xad;
+/// Receiver command: keep the controller paired in the current window
+pub const DONGLE_COMMIT_DEVICE: u8 = 0xb3;
+
+/// How long the receiver stays discoverable when the caller doesn't say
+pub const DEFAULT_PAIRING_SECS: u8 = 60;
+pub const MAX_PAIRING_SECS: u8 = 180;
+
+// Feature reports are padded to the full 64-byte HID report
+const FEATURE_REPORT_SIZE: usize = 64;
+
+/// Role of one of the dongle's interfaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DongleInterface {
+    Control,
+    Slot(u8),
+}
+
+impl DongleInterface {
+    /// `None` where the platform doesn't report interface numbers (-1)
+    pub fn from_interface_number(interface_number: i32) -> Option<Self> {
+        match interface_number {
+            CONTROL_INTERFACE => Some(DongleInterface::Control),
+            n if n > CONTROL_INTERFACE && n <= DONGLE_SLOTS as i32 => Some(DongleInterface::Slot((n - 1) as u8)),
+            _ => None,
+        }
+    }
+}
+
+/// An interface that carries one controller's input reports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControllerInterface {
+    pub entry: HidDeviceEntry,
+    /// Dongle slot, `None` for wired controllers
+    pub slot: Option<u8>,
+}
+
+/// The interfaces a controller can be read from, wired ones first, then each
+/// dongle's slots in order, then a Steam Deck's built-in controls. Wired
+/// controllers, and dongles and Decks on platforms without interface
+/// numbers, fall back to the vendor-specific usage page.
+pub fn controller_interfaces(entries: &[HidDeviceEntry]) -> Vec<ControllerInterface> {
+    let valve = |pid: u16| {
+        entries
+            .iter()
+            .filter(move |e| e.vendor_id == VALVE_VENDOR_ID && e.product_id == pid)
+    };
+    let by_usage_page = |e: &&HidDeviceEntry| e.usage_page == VENDOR_USAGE_PAGE;
+
+    let mut out: Vec<ControllerInterface> = valve(SC_WIRED_PID)
+        .filter(by_usage_page)
+        .map(|e| ControllerInterface { entry: e.clone(), slot: None })
+        .collect();
+
+    let numbered = valve(SC_WIRELESS_PID).any(|e| DongleInterface::from_interface_number(e.interface_number).is_some());
+    if numbered {
+        let mut slots: Vec<ControllerInterface> = valve(SC_WIRELESS_PID)
+            .filter_map(|e| match DongleInterface::from_interface_number(e.interface_number) {
+                Some(DongleInterface::Slot(slot)) => Some(ControllerInterface { entry: e.clone(), slot: Some(slot) }),
+                _ => None,
+            })
+            .collect();
+        // Keep each dongle's slots together
+        slots.sort_by(|a, b| (&a.entry.serial, a.slot).cmp(&(&b.entry.serial, b.slot)));
+        out.extend(slots);
+    } else {
+        out.extend(
+            valve(SC_WIRELESS_PID)
+                .filter(by_usage_page)
+                .map(|e| ControllerInterface { entry: e.clone(), slot: None }),
+        );
+    }
+    out.extend(
+        valve(STEAM_DECK_PID)
+            .filter(|e| match e.interface_number {
+                -1 => e.usage_page == VENDOR_USAGE_PAGE,
+                n => n == DECK_CONTROLLER_INTERFACE,
+            })
+            .map(|e| ControllerInterface { entry: e.clone(), slot: None }),
+    );
+    out
+}
+
+/// Each dongle's control interface, which takes receiver commands. Without
+/// interface numbers the vendor-specific interface is the best guess.
+pub fn control_interfaces(entries: &[HidDeviceEntry]) -> Vec<HidDeviceEntry> {
+    let dongles: Vec<&HidDeviceEntry> = entries
+        .iter()
+        .filter(|e| e.vendor_id == VALVE_VENDOR_ID && e.product_id == SC_WIRELESS_PID)
+        .collect();
+    let numbered = dongles.iter().any(|e| DongleInterface::from_interface_number(e.interface_number).is_some());
+    dongles
+        .into_iter()
+        .filter(|e| {
+            if numbered {
+                DongleInterface::from_interface_number(e.interface_number) == Some(DongleInterface::Control)
+            } else {
+                e.usage_page == VENDOR_USAGE_PAGE
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// 0xad report: accept new controllers for `timeout_secs`, or stop
+pub fn pairing_report(enable: bool, timeout_secs: u8) -> Result<Vec<u8>, String> {
+    if enable && !(1..=MAX_PAIRING_SECS).contains(&timeout_secs) {
+        return Err(format!("Pairing window must be 1-{} s, got {}", MAX_PAIRING_SECS, timeout_secs));
+    }
+    let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+    report[0] = ENABLE_PAIRING;
+    report[1] = 0x02; // payload length
+    report[2] = enable as u8;
+    report[3] = if enable { timeout_secs } else { 0 };
+    Ok(report)
+}
+
+/// 0xb3 report keeping the controller that paired in the current window
+pub fn commit_report() -> Vec<u8> {
+    let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+    report[0] = DONGLE_COMMIT_DEVICE;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(product_id: u16, interface_number: i32, usage_page: u16, path: &str) -> HidDeviceEntry {
+        HidDeviceEntry {
+            vendor_id: VALVE_VENDOR_ID,
+            product_id,
+            product: "Steam Controller".to_string(),
+            manufacturer: None,
+            serial: Some("DONGLE1".to_string()),
+            path: path.to_string(),
+            interface_number,
+            usage_page,
+            usage: 0x01,
+        }
+    }
+
+    #[test]
+    fn test_dongle_slots_skip_control_interface() {
+        let entries = vec![
+            interface(SC_WIRELESS_PID, 0, VENDOR_USAGE_PAGE, "/dev/hidraw0"),
+            interface(SC_WIRELESS_PID, 2, VENDOR_USAGE_PAGE, "/dev/hidraw2"),
+            interface(SC_WIRELESS_PID, 1, VENDOR_USAGE_PAGE, "/dev/hidraw1"),
+            interface(SC_WIRED_PID, 2, 0x01, "/dev/hidraw5"),
+            interface(SC_WIRED_PID, 1, VENDOR_USAGE_PAGE, "/dev/hidraw6"),
+            // Deck: keyboard/mouse on 0-1, controls on 2
+            interface(STEAM_DECK_PID, 0, 0x01, "/dev/hidraw7"),
+            interface(STEAM_DECK_PID, 2, 0xffff, "/dev/hidraw8"),
+        ];
+        let found: Vec<(String, Option<u8>)> = controller_interfaces(&entries)
+            .into_iter()
+            .map(|c| (c.entry.path, c.slot))
+            .collect();
+        let expected = [
+            ("/dev/hidraw6", None),
+            ("/dev/hidraw1", Some(0)),
+            ("/dev/hidraw2", Some(1)),
+            ("/dev/hidraw8", None),
+        ];
+        assert_eq!(found, expected.iter().map(|&(p, s)| (p.to_string(), s)).collect::<Vec<_>>());
+
+        // No interface numbers (some macOS versions): fall back to the usage page
+        let entries = vec![interface(SC_WIRELESS_PID, -1, VENDOR_USAGE_PAGE, "IOService:/dongle")];
+        assert_eq!(controller_interfaces(&entries)[0].slot, None);
+    }
+
+    #[test]
+    fn test_receiver_commands() {
+        let entries = vec![
+            interface(SC_WIRELESS_PID, 1, VENDOR_USAGE_PAGE, "/dev/hidraw1"),
+            interface(SC_WIRELESS_PID, 0, VENDOR_USAGE_PAGE, "/dev/hidraw0"),
+            interface(SC_WIRED_PID, 0, VENDOR_USAGE_PAGE, "/dev/hidraw5"),
+        ];
+        let control: Vec<String> = control_interfaces(&entries).into_iter().map(|e| e.path).collect();
+        assert_eq!(control, vec!["/dev/hidraw0".to_string()]);
+
+        assert_eq!(&pairing_report(true, 60).unwrap()[..4], &[0xad, 0x02, 0x01, 60]);
+        assert_eq!(&pairing_report(false, 60).unwrap()[..4], &[0xad, 0x02, 0x00, 0x00]);
+        assert!(pairing_report(true, 0).is_err());
+        assert_eq!(commit_report()[0], 0xb3);
+    }
+}
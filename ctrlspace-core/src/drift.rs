@@ -0,0 +1,211 @@
+//! Anti-drift: while the stick is left alone, its resting position is tracked
+//! and subtracted from the output, so a worn stick that no longer returns to
+//! center stops nudging the cursor or camera. The correction is capped, and a
+//! resting offset past the warning level is reported once so the user knows
+//! the hardware is degrading rather than the calibration being off.
+
+use serde::{Deserialize, Serialize};
+use crate::input_parser::ControllerInput;
+
+/// Event name for drift warnings
+pub const STICK_DRIFT_EVENT: &str = "stick-drift";
+
+/// Largest frame-to-frame change (per axis) of a stick nobody is touching
+const STILL_DELTA: i32 = 256;
+
+/// Fraction of the gap to the latest resting sample closed per idle frame
+const ADAPT_RATE: f32 = 0.02;
+
+/// Settle time at the correction limit, as a multiple of `settle_ms`. A thumb
+/// holding the stick a little off center is far more common than wear that
+/// far out, so the further from center, the longer it must sit still.
+const FAR_SETTLE_FACTOR: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AntiDriftConfig {
+    pub enabled: bool,
+    /// Largest resting offset (per axis) that is corrected; a stick resting
+    /// further out is being held, not drifting
+    pub max_offset: u16,
+    /// Resting offset at which the user is warned
+    pub warn_offset: u16,
+    /// How long the stick must sit still at center before its position
+    /// counts as rest; up to 10 times longer towards `max_offset`
+    pub settle_ms: u32,
+}
+
+impl Default for AntiDriftConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_offset: 4000,
+            warn_offset: 2500,
+            settle_ms: 2000,
+        }
+    }
+}
+
+impl AntiDriftConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_offset > i16::MAX as u16 {
+            return Err(format!("Anti-drift limit must be at most {}: {}", i16::MAX, self.max_offset));
+        }
+        if self.warn_offset > self.max_offset {
+            return Err(format!(
+                "Drift warning level ({}) must not be above the anti-drift limit ({})",
+                self.warn_offset, self.max_offset
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Payload of `STICK_DRIFT_EVENT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DriftWarning {
+    /// Estimated resting position, after calibration
+    pub offset: (i16, i16),
+    /// Distance of the resting position from center
+    pub magnitude: u16,
+}
+
+/// Resting-position estimate for one stick
+#[derive(Debug, Clone, Default)]
+pub struct DriftCompensator {
+    config: AntiDriftConfig,
+    estimate: (f32, f32),
+    last: Option<(i16, i16)>,
+    still_since_us: Option<u64>,
+    warned: bool,
+    pending: Option<DriftWarning>,
+}
+
+impl DriftCompensator {
+    pub fn new(config: AntiDriftConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Change the config; the estimate is kept unless anti-drift is switched off
+    pub fn set_config(&mut self, config: AntiDriftConfig) {
+        if !config.enabled {
+            *self = Self::new(config);
+        } else {
+            self.config = config;
+        }
+    }
+
+    /// Start over, e.g. for a different unit
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Current correction
+    pub fn offset(&self) -> (i16, i16) {
+        (self.estimate.0.round() as i16, self.estimate.1.round() as i16)
+    }
+
+    /// Learn from this frame's stick and re-center it
+    pub fn apply(&mut self, input: &mut ControllerInput) {
+        if !self.config.enabled {
+            return;
+        }
+        let (x, y) = (input.stick.x, input.stick.y);
+        let still = self.last.map_or(false, |(lx, ly)| {
+            (x as i32 - lx as i32).abs() <= STILL_DELTA && (y as i32 - ly as i32).abs() <= STILL_DELTA
+        });
+        self.last = Some((x, y));
+
+        let limit = self.config.max_offset as i32;
+        let distance = (x as i32).abs().max((y as i32).abs());
+        if still && distance <= limit && !input.buttons.stick_click {
+            let since = *self.still_since_us.get_or_insert(input.host_timestamp_us);
+            let reach = if limit > 0 { distance as f32 / limit as f32 } else { 0.0 };
+            let settle_us = self.config.settle_ms as f32 * 1000.0 * (1.0 + (FAR_SETTLE_FACTOR - 1.0) * reach);
+            if input.host_timestamp_us.saturating_sub(since) as f32 >= settle_us {
+                self.estimate.0 += (x as f32 - self.estimate.0) * ADAPT_RATE;
+                self.estimate.1 += (y as f32 - self.estimate.1) * ADAPT_RATE;
+                self.check_warning();
+            }
+        } else {
+            self.still_since_us = None;
+        }
+
+        let (dx, dy) = self.offset();
+        input.stick.x = x.saturating_sub(dx);
+        input.stick.y = y.saturating_sub(dy);
+    }
+
+    // Warn once per crossing; re-arm after the offset falls back below 3/4 of the level
+    fn check_warning(&mut self) {
+        let magnitude = self.estimate.0.hypot(self.estimate.1);
+        let level = self.config.warn_offset as f32;
+        if !self.warned && magnitude >= level {
+            self.warned = true;
+            self.pending = Some(DriftWarning {
+                offset: self.offset(),
+                magnitude: magnitude.round() as u16,
+            });
+        } else if self.warned && magnitude < level * 0.75 {
+            self.warned = false;
+        }
+    }
+
+    /// The warning raised since the last call, if any
+    pub fn take_warning(&mut self) -> Option<DriftWarning> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(x: i16, y: i16, at_ms: u64) -> ControllerInput {
+        let mut input = ControllerInput::default();
+        input.stick.x = x;
+        input.stick.y = y;
+        input.host_timestamp_us = at_ms * 1000;
+        input
+    }
+
+    #[test]
+    fn test_recenters_resting_stick_and_warns_once() {
+        let mut drift = DriftCompensator::new(AntiDriftConfig { enabled: true, ..AntiDriftConfig::default() });
+
+        // Resting 3000 off center: a thumb holding it there for a few seconds
+        // isn't learned...
+        for ms in 0..2500 {
+            drift.apply(&mut frame(3000, -100, ms * 4));
+        }
+        assert_eq!(drift.offset(), (0, 0));
+        // ...but sitting there for the (longer, that far out) settle time is
+        for ms in 2500..5000 {
+            drift.apply(&mut frame(3000, -100, ms * 4));
+        }
+        let mut last = frame(3000, -100, 20_000);
+        drift.apply(&mut last);
+        assert!(last.stick.x.abs() < 50, "still {} off", last.stick.x);
+        let warning = drift.take_warning().unwrap();
+        assert!(warning.magnitude >= 2500);
+        assert_eq!(drift.take_warning(), None);
+
+        // Deflection is still relative to the learned center
+        let mut pushed = frame(20000, -100, 21_000);
+        drift.apply(&mut pushed);
+        assert!((pushed.stick.x - 17000).abs() < 50);
+
+        // Held far out: not rest, so the estimate doesn't follow
+        let before = drift.offset();
+        for ms in 0..1000 {
+            drift.apply(&mut frame(20000, 0, 22_000 + ms * 4));
+        }
+        assert_eq!(drift.offset(), before);
+
+        let config = AntiDriftConfig { warn_offset: 5000, ..AntiDriftConfig::default() };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Dry-run previews: while a profile is being tried out, everything the
+//! mapping engine would send to the OS is described instead ("Would press
+//! Key w", "Would move mouse (12, -3)"), so a new profile can be checked on
+//! live input without it typing or clicking into whatever has focus.
+
+use serde::Serialize;
+use crate::cursor::CursorMove;
+use crate::edge_spin::SpinEvent;
+use crate::gestures::{BoundGesture, GestureEvent, Pad};
+use crate::gyro_mouse::GyroMove;
+use crate::hook_process::HookCommand;
+use crate::layers::{BindingEvent, BindingPhase};
+use crate::overlay::describe_action;
+use crate::touch_menu::TouchMenuSelection;
+#[cfg(feature = "plugins")]
+use crate::plugins::PluginEmit;
+#[cfg(feature = "scripting")]
+use crate::scripting::ScriptOutput;
+
+/// Event name for dry-run previews
+pub const MAPPING_PREVIEW_EVENT: &str = "mapping-preview";
+
+/// Payload of `MAPPING_PREVIEW_EVENT`: one output that was held back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreviewEvent {
+    /// What produced it, as keyed in profiles (`"a"`, `"right_double_tap"`, `"left_pad"`)
+    pub source: String,
+    /// Layer the binding was resolved on; `None` for the base layer
+    pub layer: Option<String>,
+    pub description: String,
+}
+
+// Profile key of a button or gesture
+fn key_name<T: Serialize>(input: &T) -> String {
+    serde_json::to_value(input)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn pad_name(pad: Pad) -> String {
+    format!("{}_pad", key_name(&pad))
+}
+
+pub fn preview_binding(event: &BindingEvent) -> PreviewEvent {
+    let verb = match event.phase {
+        BindingPhase::Pressed => "press",
+        BindingPhase::Repeated => "repeat",
+        BindingPhase::Released => "release",
+    };
+    PreviewEvent {
+        source: key_name(&event.button),
+        layer: event.layer.clone(),
+        description: format!("Would {} {}", verb, describe_action(&event.action)),
+    }
+}
+
+/// `None` for gestures the profile doesn't bind
+pub fn preview_gesture(gesture: &BoundGesture) -> Option<PreviewEvent> {
+    let action = gesture.action.as_ref()?;
+    let verb = match gesture.event {
+        GestureEvent::Fired(_) => "trigger",
+        GestureEvent::Started(_) => "press",
+        GestureEvent::Ended(_) => "release",
+    };
+    Some(PreviewEvent {
+        source: key_name(&gesture.event.gesture()),
+        layer: None,
+        description: format!("Would {} {}", verb, describe_action(action)),
+    })
+}
+
+pub fn preview_cursor(movement: &CursorMove) -> PreviewEvent {
+    PreviewEvent {
+        source: pad_name(movement.pad),
+        layer: None,
+        description: format!("Would move mouse ({}, {})", movement.dx, movement.dy),
+    }
+}
+
+pub fn preview_gyro_cursor(movement: &GyroMove) -> PreviewEvent {
+    PreviewEvent {
+        source: "gyro".to_string(),
+        layer: None,
+        description: format!("Would move mouse ({}, {})", movement.dx, movement.dy),
+    }
+}
+
+pub fn preview_spin(event: &SpinEvent) -> PreviewEvent {
+    PreviewEvent {
+        source: pad_name(event.pad),
+        layer: None,
+        description: format!("Would {} {:+} steps", key_name(&event.output), event.steps),
+    }
+}
+
+pub fn preview_menu_selection(selection: &TouchMenuSelection) -> PreviewEvent {
+    PreviewEvent {
+        source: pad_name(selection.pad),
+        layer: None,
+        description: format!("Would trigger {} ({})", describe_action(&selection.action), selection.label),
+    }
+}
+
+/// `None` for commands that don't produce output (log lines)
+pub fn preview_hook(command: &HookCommand) -> Option<PreviewEvent> {
+    let description = match command {
+        HookCommand::Press { action } => format!("Would press {}", describe_action(action)),
+        HookCommand::Release { action } => format!("Would release {}", describe_action(action)),
+        HookCommand::Log { .. } => return None,
+    };
+    Some(PreviewEvent {
+        source: "hook".to_string(),
+        layer: None,
+        description,
+    })
+}
+
+#[cfg(feature = "scripting")]
+pub fn preview_script(output: &ScriptOutput) -> PreviewEvent {
+    let description = match output {
+        ScriptOutput::Press { key } => format!("Would press Key {}", key),
+        ScriptOutput::Release { key } => format!("Would release Key {}", key),
+        ScriptOutput::Tap { key } => format!("Would tap Key {}", key),
+        ScriptOutput::MoveMouse { dx, dy } => format!("Would move mouse ({}, {})", dx, dy),
+        ScriptOutput::Scroll { amount } => format!("Would scroll {:+}", amount),
+    };
+    PreviewEvent {
+        source: "script".to_string(),
+        layer: None,
+        description,
+    }
+}
+
+#[cfg(feature = "plugins")]
+pub fn preview_plugin(plugin: &str, emit: &PluginEmit) -> PreviewEvent {
+    let description = match emit {
+        PluginEmit::Press { action } => format!("Would press {}", describe_action(action)),
+        PluginEmit::Release { action } => format!("Would release {}", describe_action(action)),
+        PluginEmit::Output { output, value } => format!("Would set {} to {}", output, value),
+    };
+    PreviewEvent {
+        source: format!("plugin:{}", plugin),
+        layer: None,
+        description,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gestures::GestureId;
+    use crate::profiles::{Action, ButtonId};
+
+    #[test]
+    fn test_previews_describe_outputs() {
+        let press = BindingEvent {
+            button: ButtonId::LpadClick,
+            action: Action::Key { key: "w".to_string() },
+            phase: BindingPhase::Pressed,
+            layer: Some("fn".to_string()),
+        };
+        let preview = preview_binding(&press);
+        assert_eq!(preview.source, "lpad_click");
+        assert_eq!(preview.description, "Would press Key w");
+
+        let movement = CursorMove { pad: Pad::Right, dx: 12, dy: -3 };
+        assert_eq!(preview_cursor(&movement).description, "Would move mouse (12, -3)");
+        assert_eq!(preview_cursor(&movement).source, "right_pad");
+
+        let unbound = GestureEvent::Fired(GestureId::LeftTap).bind(None);
+        assert_eq!(preview_gesture(&unbound), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Edge-spin: circling a finger around the outer rim of a pad, like an iPod
+//! click wheel, produces scroll/volume/zoom steps. Each step is a detent, so
+//! output rate follows angular velocity. A touch that starts inside the rim is
+//! left alone, which keeps this apart from linear swipe scrolling.
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{AnalogMode, Profile};
+
+/// Distance from center (fraction of full scale) where the rim starts
+pub const RIM_RADIUS: f32 = 0.7;
+
+/// What spinning controls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpinOutput {
+    Scroll,
+    Volume,
+    Zoom,
+}
+
+/// Steps produced by one frame of spinning; positive is clockwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SpinEvent {
+    pub pad: Pad,
+    pub output: SpinOutput,
+    pub steps: i32,
+    /// Play a detent tick for this event
+    pub haptics: bool,
+}
+
+fn rim_angle(pad: &TrackpadData) -> Option<f32> {
+    let (x, y) = (pad.x as f32 / 32768.0, pad.y as f32 / 32768.0);
+    if !pad.active || (x * x + y * y).sqrt() < RIM_RADIUS {
+        return None;
+    }
+    // 0 at the top, increasing clockwise
+    Some(x.atan2(y))
+}
+
+/// Rim tracking for one pad
+#[derive(Debug, Clone, Default)]
+pub struct EdgeSpin {
+    last_angle: Option<f32>,
+    /// Rotation (radians) not yet turned into a whole detent
+    accumulated: f32,
+    /// The touch started inside the rim; ignore it until lift-off
+    ignored: bool,
+}
+
+impl EdgeSpin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one frame; returns whole detents passed (positive clockwise)
+    pub fn update(&mut self, pad: &TrackpadData, detents_per_turn: u32) -> i32 {
+        if !pad.active {
+            *self = Self::default();
+            return 0;
+        }
+        if self.ignored {
+            return 0;
+        }
+
+        let angle = match rim_angle(pad) {
+            Some(angle) => angle,
+            None => {
+                // Leaving the rim ends the spin; starting inside never begins one
+                if self.last_angle.is_none() {
+                    self.ignored = true;
+                }
+                self.last_angle = None;
+                self.accumulated = 0.0;
+                return 0;
+            }
+        };
+
+        let last = match self.last_angle.replace(angle) {
+            Some(last) => last,
+            None => return 0,
+        };
+        // Shortest way around, so crossing the top doesn't look like a full turn
+        let mut delta = angle - last;
+        if delta > PI {
+            delta -= 2.0 * PI;
+        } else if delta < -PI {
+            delta += 2.0 * PI;
+        }
+
+        let detent = 2.0 * PI / detents_per_turn.max(1) as f32;
+        self.accumulated += delta;
+        let steps = (self.accumulated / detent).trunc();
+        self.accumulated -= steps * detent;
+        steps as i32
+    }
+}
+
+/// Edge-spin for both pads, driven by the active profile's pad modes
+#[derive(Debug, Clone, Default)]
+pub struct EdgeSpinDetector {
+    left: EdgeSpin,
+    right: EdgeSpin,
+}
+
+impl EdgeSpinDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>) -> Vec<SpinEvent> {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return Vec::new(),
+        };
+        let pads = [
+            (Pad::Left, &mut self.left, &input.left_trackpad, &profile.left_pad),
+            (Pad::Right, &mut self.right, &input.right_trackpad, &profile.right_pad),
+        ];
+
+        let mut events = Vec::new();
+        for (pad, spin, data, mode) in pads {
+            if let AnalogMode::EdgeSpin { output, detents_per_turn, haptics } = mode {
+                let steps = spin.update(data, *detents_per_turn);
+                if steps != 0 {
+                    events.push(SpinEvent {
+                        pad,
+                        output: *output,
+                        steps,
+                        haptics: *haptics,
+                    });
+                }
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Point on the rim at `degrees` clockwise from the top
+    fn rim(degrees: f32) -> TrackpadData {
+        let radians = degrees.to_radians();
+        TrackpadData {
+            x: (radians.sin() * 30000.0) as i16,
+            y: (radians.cos() * 30000.0) as i16,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_spin_produces_detents_both_ways() {
+        let mut spin = EdgeSpin::new();
+        // 12 detents per turn = one every 30 degrees; cross the top going clockwise
+        let clockwise: i32 = [330.0, 350.0, 10.0, 30.0, 50.0].iter().map(|&d| spin.update(&rim(d), 12)).sum();
+        assert_eq!(clockwise, 2);
+
+        let back: i32 = [20.0, 0.0, 340.0, 320.0, 290.0].iter().map(|&d| spin.update(&rim(d), 12)).sum();
+        assert_eq!(back, -3);
+    }
+
+    #[test]
+    fn test_touch_inside_rim_is_ignored() {
+        let mut spin = EdgeSpin::new();
+        let center = TrackpadData { x: 0, y: 1000, active: true };
+        assert_eq!(spin.update(&center, 12), 0);
+        // Sliding out to the rim doesn't start a spin mid-touch
+        let moved: i32 = [0.0, 90.0, 180.0].iter().map(|&d| spin.update(&rim(d), 12)).sum();
+        assert_eq!(moved, 0);
+
+        spin.update(&TrackpadData::default(), 12);
+        let fresh: i32 = [0.0, 50.0, 100.0].iter().map(|&d| spin.update(&rim(d), 12)).sum();
+        assert_eq!(fresh, 3);
+    }
+}
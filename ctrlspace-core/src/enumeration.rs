@@ -0,0 +1,108 @@
+//! HID device enumeration for the device list: every interface with its path,
+//! usage and serial, narrowed by an optional filter.
+
+use hidapi::DeviceInfo;
+use serde::{Deserialize, Serialize};
+use crate::steam_controller::{SC_WIRED_PID, SC_WIRELESS_PID, STEAM_DECK_PID, VALVE_VENDOR_ID};
+
+/// Generic Desktop usage page and its joystick/gamepad usages
+const GENERIC_DESKTOP_PAGE: u16 = 0x01;
+const USAGE_JOYSTICK: u16 = 0x04;
+const USAGE_GAMEPAD: u16 = 0x05;
+
+/// One HID interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HidDeviceEntry {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub product: String,
+    pub manufacturer: Option<String>,
+    pub serial: Option<String>,
+    pub path: String,
+    pub interface_number: i32,
+    pub usage_page: u16,
+    pub usage: u16,
+}
+
+impl HidDeviceEntry {
+    pub fn from_info(info: &DeviceInfo) -> Self {
+        Self {
+            vendor_id: info.vendor_id(),
+            product_id: info.product_id(),
+            product: info.product_string().unwrap_or("Unknown").to_string(),
+            manufacturer: info.manufacturer_string().map(str::to_string),
+            serial: info.serial_number().filter(|s| !s.is_empty()).map(str::to_string),
+            path: info.path().to_string_lossy().into_owned(),
+            interface_number: info.interface_number(),
+            usage_page: info.usage_page(),
+            usage: info.usage(),
+        }
+    }
+
+    /// Joystick/gamepad usage, or a Steam Controller or Deck (whose vendor
+    /// interface doesn't declare a gamepad usage)
+    pub fn is_gamepad(&self) -> bool {
+        (self.usage_page == GENERIC_DESKTOP_PAGE && (self.usage == USAGE_JOYSTICK || self.usage == USAGE_GAMEPAD))
+            || (self.vendor_id == VALVE_VENDOR_ID
+                && matches!(self.product_id, SC_WIRELESS_PID | SC_WIRED_PID | STEAM_DECK_PID))
+    }
+}
+
+/// Optional narrowing of the device list; an empty filter matches everything
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceFilter {
+    pub vendor_id: Option<u16>,
+    pub usage_page: Option<u16>,
+    pub gamepads_only: bool,
+}
+
+impl DeviceFilter {
+    pub fn matches(&self, device: &HidDeviceEntry) -> bool {
+        self.vendor_id.map_or(true, |vid| device.vendor_id == vid)
+            && self.usage_page.map_or(true, |page| device.usage_page == page)
+            && (!self.gamepads_only || device.is_gamepad())
+    }
+}
+
+/// Devices in an enumerated list that match `filter` (refreshing is up to the caller)
+pub fn list_devices<'a>(devices: impl Iterator<Item = &'a DeviceInfo>, filter: &DeviceFilter) -> Vec<HidDeviceEntry> {
+    devices
+        .map(HidDeviceEntry::from_info)
+        .filter(|device| filter.matches(device))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(vendor_id: u16, product_id: u16, usage_page: u16, usage: u16) -> HidDeviceEntry {
+        HidDeviceEntry {
+            vendor_id,
+            product_id,
+            product: "Test".to_string(),
+            manufacturer: None,
+            serial: None,
+            path: String::new(),
+            interface_number: 0,
+            usage_page,
+            usage,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let keyboard = device(0x046d, 0xc31c, 0x01, 0x06);
+        let pad = device(0x045e, 0x028e, 0x01, 0x05);
+        let steam = device(VALVE_VENDOR_ID, SC_WIRELESS_PID, 0xff00, 0x01);
+
+        assert!([&keyboard, &pad, &steam].iter().all(|d| DeviceFilter::default().matches(d)));
+
+        let gamepads = DeviceFilter { gamepads_only: true, ..Default::default() };
+        assert!(!gamepads.matches(&keyboard) && gamepads.matches(&pad) && gamepads.matches(&steam));
+
+        let valve_vendor_page = DeviceFilter { vendor_id: Some(VALVE_VENDOR_ID), usage_page: Some(0xff00), gamepads_only: false };
+        assert!(valve_vendor_page.matches(&steam) && !valve_vendor_page.matches(&pad));
+    }
+}
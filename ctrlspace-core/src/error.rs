@@ -0,0 +1,52 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Failure reading from the controller. An idle read timeout is not an error -
+/// read APIs return `Ok(None)` for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum ControllerError {
+    /// The manager hasn't been created (HID API unavailable)
+    NotInitialized,
+    NotConnected,
+    /// hidapi reported a failure, usually because the device went away
+    Read(String),
+    /// A report arrived but couldn't be parsed
+    Parse(String),
+    /// The controller didn't answer in time (see [`crate::timeout`])
+    Timeout(String),
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControllerError::NotInitialized => write!(f, "Steam Controller manager not initialized"),
+            ControllerError::NotConnected => write!(f, "Controller not connected"),
+            ControllerError::Read(e) => write!(f, "Read error: {}", e),
+            ControllerError::Parse(e) => write!(f, "Parse error: {}", e),
+            ControllerError::Timeout(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ControllerError {}
+
+// Most of the crate still reports errors as strings
+impl From<ControllerError> for String {
+    fn from(e: ControllerError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json_shape() {
+        let json = serde_json::to_value(ControllerError::Read("device gone".to_string())).unwrap();
+        assert_eq!(json["kind"], "read");
+        assert_eq!(json["message"], "device gone");
+        assert_eq!(serde_json::to_value(ControllerError::NotConnected).unwrap()["kind"], "not_connected");
+    }
+}
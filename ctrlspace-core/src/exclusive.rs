@@ -0,0 +1,69 @@
+//! Exclusive access: while a profile with `exclusive` set is mapping, other
+//! processes shouldn't see the controller's input too (double input in
+//! games, kiosk setups). How that is done depends on the platform, so the
+//! app does the grabbing; this module holds the pieces that can be tested:
+//! - Windows: HidHide hides the device from every app but CtrlSpace, keyed
+//!   by the device instance ID derived from the HID path
+//! - Linux: the keyboard/mouse evdev nodes the controller exposes (Lizard
+//!   Mode) are grabbed with `EVIOCGRAB`, found by vendor/product in sysfs
+//! - macOS: hidapi already opens the device exclusively
+
+use serde::Serialize;
+use crate::steam_controller::{SC_WIRED_PID, SC_WIRELESS_PID, VALVE_VENDOR_ID};
+
+/// State reported by `get_exclusive_status`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExclusiveStatus {
+    /// The active profile asks for exclusive access
+    pub requested: bool,
+    /// Other processes are currently kept away from the controller
+    pub active: bool,
+    /// How it is done on this platform ("hidhide", "evdev-grab", "exclusive-open")
+    pub mechanism: Option<String>,
+    /// What was hidden or grabbed (instance IDs, evdev nodes)
+    pub devices: Vec<String>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+}
+
+/// Device instance ID (`HID\VID_28DE&PID_1102&MI_02\7&1A2B3C&0&0000`) of a
+/// Windows HID interface path, as HidHide expects it
+pub fn hid_instance_id(path: &str) -> Option<String> {
+    let rest = path.strip_prefix(r"\\?\").or_else(|| path.strip_prefix(r"\\.\"))?;
+    // Drop the interface class GUID: `...#{4d1e55b2-f16f-11cf-88cb-001111000030}`
+    let device = match rest.rfind("#{") {
+        Some(i) => &rest[..i],
+        None => rest,
+    };
+    if !device.to_ascii_lowercase().starts_with("hid#") {
+        return None;
+    }
+    Some(device.replace('#', "\\").to_ascii_uppercase())
+}
+
+/// Whether sysfs `id/vendor` and `id/product` (hex) belong to a Steam Controller
+pub fn is_controller_ids(vendor: &str, product: &str) -> bool {
+    let parse = |hex: &str| u16::from_str_radix(hex.trim(), 16).ok();
+    parse(vendor) == Some(VALVE_VENDOR_ID) && matches!(parse(product), Some(SC_WIRED_PID) | Some(SC_WIRELESS_PID))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_id_and_ids() {
+        assert_eq!(
+            hid_instance_id(r"\\?\hid#vid_28de&pid_1142&mi_01#8&2f1a3b4c&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}")
+                .as_deref(),
+            Some(r"HID\VID_28DE&PID_1142&MI_01\8&2F1A3B4C&0&0000")
+        );
+        assert_eq!(hid_instance_id("/dev/hidraw3"), None);
+        assert_eq!(hid_instance_id(r"\\?\usb#vid_28de&pid_1102#abc#{guid}"), None);
+
+        assert!(is_controller_ids("28de\n", "1102\n"));
+        assert!(is_controller_ids("28DE", "1142"));
+        assert!(!is_controller_ids("28de", "1205"));
+        assert!(!is_controller_ids("046d", "1102"));
+    }
+}
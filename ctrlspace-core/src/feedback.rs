@@ -0,0 +1,86 @@
+//! Per-binding haptic feedback: when a bound button is pressed or a bound
+//! gesture fires, play the feedback the profile attached to it.
+
+use crate::gestures::GestureEvent;
+use crate::haptics::HapticPulse;
+use crate::input_events::InputEvent;
+use crate::profiles::{BindingInput, Profile};
+
+/// Pulses for the frame's button presses (from the input event detector)
+/// that have both a binding and feedback
+pub fn binding_feedback(events: &[InputEvent], profile: Option<&Profile>) -> Vec<HapticPulse> {
+    let profile = match profile {
+        Some(profile) => profile,
+        None => return Vec::new(),
+    };
+    events
+        .iter()
+        .filter_map(|event| match event {
+            InputEvent::ButtonPressed { button } if profile.buttons.contains_key(button) => {
+                profile.haptics.get(&BindingInput::Button(*button))
+            }
+            _ => None,
+        })
+        .flat_map(|feedback| feedback.pulses())
+        .collect()
+}
+
+/// Pulses for a gesture as it fires (or, for a tap-drag, as it starts)
+pub fn gesture_feedback(event: &GestureEvent, profile: Option<&Profile>) -> Vec<HapticPulse> {
+    let profile = match (event, profile) {
+        (GestureEvent::Ended(_), _) | (_, None) => return Vec::new(),
+        (_, Some(profile)) => profile,
+    };
+    let gesture = event.gesture();
+    if !profile.gestures.contains_key(&gesture) {
+        return Vec::new();
+    }
+    profile
+        .haptics
+        .get(&BindingInput::Gesture(gesture))
+        .map(|feedback| feedback.pulses())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gestures::GestureId;
+    use crate::haptics::{HapticFeedback, HapticPattern, HapticSide};
+    use crate::profiles::{Action, ButtonId};
+
+    #[test]
+    fn test_feedback_on_press_only() {
+        let json = r#"{
+            "name": "Haptic",
+            "buttons": { "a": { "type": "key", "key": "enter" } },
+            "gestures": { "right_tap": { "type": "key", "key": "space" } },
+            "haptics": {
+                "a": { "pattern": "tick", "side": "right" },
+                "b": { "pattern": "thunk" },
+                "right_tap": { "pattern": "buzz", "side": "left" }
+            }
+        }"#;
+        let profile = Profile::from_json(json).unwrap();
+        assert_eq!(profile.haptics.len(), 3);
+        assert_eq!(
+            profile.haptics[&BindingInput::from(ButtonId::A)],
+            HapticFeedback { pattern: HapticPattern::Tick, side: Some(HapticSide::Right), strength: 1.0 }
+        );
+
+        // B has feedback but no binding; a held button isn't pressed again
+        let pressed = |button| InputEvent::ButtonPressed { button };
+        let events = [pressed(ButtonId::A), pressed(ButtonId::B), InputEvent::ButtonHeld { button: ButtonId::A }];
+        assert_eq!(binding_feedback(&events, Some(&profile)), vec![HapticPulse::confirm(HapticSide::Right)]);
+        assert!(binding_feedback(&events, None).is_empty());
+
+        let tap = GestureEvent::Fired(GestureId::RightTap);
+        assert_eq!(gesture_feedback(&tap, Some(&profile)).len(), 1);
+        assert!(gesture_feedback(&GestureEvent::Fired(GestureId::LeftTap), Some(&profile)).is_empty());
+
+        let mut unbound = profile.clone();
+        unbound.buttons.insert(ButtonId::B, Action::None);
+        // Both pads play `thunk`
+        assert_eq!(binding_feedback(&[pressed(ButtonId::B)], Some(&unbound)).len(), 2);
+    }
+}
@@ -0,0 +1,208 @@
+//! Labeled report captures ("holding A", "gyro roll left") saved as parser
+//! fixtures. Copy a capture into `ctrlspace-core/fixtures/` and add an
+//! `expect` object to turn it into a regression test: every report must parse,
+//! and the parsed `ControllerInput` JSON must contain `expect` (a subset match).
+//!
+//! ```text
+//! {"version":1,"label":"holding A","created":1760000000,"device":"sc-1",
+//!  "reports":["0100...","0100..."],"expect":{"buttons":{"a":true}}}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::input_parser::InputParser;
+use crate::profiles::profile_file_stem;
+use crate::controller::Controller;
+
+pub const FIXTURE_VERSION: u32 = 1;
+
+/// Subdirectory of the app data dir captures are written to
+pub const FIXTURES_DIR_NAME: &str = "fixtures";
+
+pub const MAX_CAPTURE_REPORTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub version: u32,
+    pub label: String,
+    /// Unix seconds
+    pub created: u64,
+    /// Registry ID of the controller, if known
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Raw reports as lowercase hex
+    pub reports: Vec<String>,
+    /// Subset of the parsed input every report must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<Value>,
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err(format!("Odd-length hex string ({} chars)", hex.len()));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("Invalid hex at position {}", i))
+        })
+        .collect()
+}
+
+impl Capture {
+    pub fn new(label: &str, device: Option<String>, reports: &[Vec<u8>]) -> Self {
+        Self {
+            version: FIXTURE_VERSION,
+            label: label.to_string(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            device,
+            reports: reports.iter().map(|r| to_hex(r)).collect(),
+            expect: None,
+        }
+    }
+
+    pub fn raw_reports(&self) -> Result<Vec<Vec<u8>>, String> {
+        self.reports.iter().map(|r| from_hex(r)).collect()
+    }
+
+    /// Write to `dir` as `<label>-<created>.json`, returning the path
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!("{}-{}.json", profile_file_stem(&self.label), self.created));
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize capture: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid capture {}: {}", path.display(), e))
+    }
+
+    /// Run the reports through a fresh parser and check `expect`
+    pub fn verify(&self) -> Result<(), String> {
+        let mut parser = InputParser::new();
+        for (i, raw) in self.raw_reports()?.iter().enumerate() {
+            let input = parser
+                .parse(raw)
+                .map_err(|e| format!("'{}' report {}: {}", self.label, i, e))?;
+            if let Some(expect) = &self.expect {
+                let actual = serde_json::to_value(&input).map_err(|e| e.to_string())?;
+                if !json_contains(&actual, expect) {
+                    return Err(format!(
+                        "'{}' report {}: expected {} in {}",
+                        self.label, i, expect, actual
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Every key in `expected` exists in `actual` with a matching value
+fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).map(|a| json_contains(a, value)).unwrap_or(false)),
+        _ => actual == expected,
+    }
+}
+
+/// Read `count` reports from the controller (giving up after `count` × 100 ms)
+pub fn capture_reports(manager: &dyn Controller, count: usize) -> Result<Vec<Vec<u8>>, String> {
+    if count == 0 || count > MAX_CAPTURE_REPORTS {
+        return Err(format!("Capture size must be between 1 and {} reports", MAX_CAPTURE_REPORTS));
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(100 * count as u64);
+    let mut reports = Vec::with_capacity(count);
+    while reports.len() < count && Instant::now() < deadline {
+        if !manager.is_connected() {
+            return Err("Controller disconnected during capture".to_string());
+        }
+        if let Ok(Some(raw)) = manager.read_input_blocking(manager.read_settings().timeout_ms.max(1)) {
+            reports.push(raw);
+        }
+    }
+
+    if reports.len() < count {
+        return Err(format!("Only {} of {} reports arrived", reports.len(), count));
+    }
+    Ok(reports)
+}
+
+/// Verify every `*.json` capture in `dir`; returns how many were checked
+pub fn verify_fixtures_dir(dir: &Path) -> Result<usize, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut checked = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            Capture::load(&path)?.verify()?;
+            checked += 1;
+        }
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parser::INPUT_PACKET;
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(to_hex(&[0x01, 0xab, 0x00]), "01ab00");
+        assert_eq!(from_hex("01ab00").unwrap(), vec![0x01, 0xab, 0x00]);
+        assert!(from_hex("01a").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_capture_verify() {
+        let dir = std::env::temp_dir().join(format!("ctrlspace-fixtures-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut report = vec![0u8; 64];
+        report[0] = 0x01;
+        report[2] = INPUT_PACKET;
+        report[8] = 0x01; // RT click
+        let mut capture = Capture::new("holding RT", Some("sc-1".to_string()), &[report.clone(), report]);
+        capture.expect = Some(serde_json::json!({"buttons": {"rt": true}}));
+
+        let path = capture.save(&dir).unwrap();
+        assert!(path.file_name().unwrap().to_str().unwrap().starts_with("holding-rt-"));
+        assert_eq!(Capture::load(&path).unwrap(), capture);
+        assert_eq!(verify_fixtures_dir(&dir).unwrap(), 1);
+
+        capture.expect = Some(serde_json::json!({"buttons": {"lt": true}}));
+        assert!(capture.verify().is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Regression harness: every capture checked into ctrlspace-core/fixtures
+    #[test]
+    fn test_checked_in_fixtures() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        verify_fixtures_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Flat input frame for the high-rate parse path. [`InputParser::parse_frame`]
+//! decodes a report straight from the read buffer into a [`RawFrame`]: no
+//! allocation, no intermediate structs and no per-field bounds checks once
+//! the length is known. [`ControllerInput`] is built from it for everything
+//! that wants the richer type. `benches/parser.rs` keeps an eye on throughput.
+//!
+//! [`InputParser::parse_frame`]: crate::input_parser::InputParser::parse_frame
+
+use crate::input_parser::{ButtonState, ControllerInput, GyroData, ImuData, StickData, TrackpadData, TriggersData};
+use std::fmt;
+
+/// Length of a controller state report
+pub const REPORT_LEN: usize = 64;
+
+// Bits of `RawFrame::buttons`, in the same order as the C API's `CTRLSPACE_BUTTON_*`
+pub const BUTTON_A: u32 = 1 << 0;
+pub const BUTTON_B: u32 = 1 << 1;
+pub const BUTTON_X: u32 = 1 << 2;
+pub const BUTTON_Y: u32 = 1 << 3;
+pub const BUTTON_LB: u32 = 1 << 4;
+pub const BUTTON_RB: u32 = 1 << 5;
+pub const BUTTON_LT: u32 = 1 << 6;
+pub const BUTTON_RT: u32 = 1 << 7;
+pub const BUTTON_LGRIP: u32 = 1 << 8;
+pub const BUTTON_RGRIP: u32 = 1 << 9;
+pub const BUTTON_START: u32 = 1 << 10;
+pub const BUTTON_SELECT: u32 = 1 << 11;
+pub const BUTTON_STEAM: u32 = 1 << 12;
+pub const BUTTON_LPAD_CLICK: u32 = 1 << 13;
+pub const BUTTON_RPAD_CLICK: u32 = 1 << 14;
+pub const BUTTON_STICK_CLICK: u32 = 1 << 15;
+
+/// One decoded input report, stick/pad multiplexing already resolved.
+/// Plain C layout (56 bytes), so a batch of frames is one flat buffer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RawFrame {
+    /// See [`ControllerInput::extended_timestamp`]
+    pub extended_timestamp: u64,
+    pub timestamp: u32,
+    /// Bitmask of `BUTTON_*`
+    pub buttons: u32,
+    pub stick_x: i16,
+    pub stick_y: i16,
+    pub left_pad_x: i16,
+    pub left_pad_y: i16,
+    pub right_pad_x: i16,
+    pub right_pad_y: i16,
+    pub gyro_pitch: i16,
+    pub gyro_yaw: i16,
+    pub gyro_roll: i16,
+    pub accel_x: i16,
+    pub accel_y: i16,
+    pub accel_z: i16,
+    pub quat_w: i16,
+    pub quat_x: i16,
+    pub quat_y: i16,
+    pub quat_z: i16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    pub left_pad_active: u8,
+    pub right_pad_active: u8,
+}
+
+impl RawFrame {
+    pub fn pressed(&self, button: u32) -> bool {
+        self.buttons & button != 0
+    }
+}
+
+/// Why a buffer didn't decode as an input report. `Copy`, so the fast path
+/// doesn't allocate even when it fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// Shorter than [`REPORT_LEN`]; holds the length
+    TooShort(usize),
+    InvalidReportId(u8),
+    /// Another packet type (byte 2); see `InputParser::parse_packet`
+    NotInput(u8),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::TooShort(len) => write!(f, "Invalid report size: {} bytes", len),
+            FrameError::InvalidReportId(id) => write!(f, "Invalid report type: 0x{:02x}", id),
+            FrameError::NotInput(kind) => write!(f, "Not an input report: packet type 0x{:02x}", kind),
+        }
+    }
+}
+
+impl From<&RawFrame> for ControllerInput {
+    fn from(frame: &RawFrame) -> Self {
+        let pressed = |button| frame.pressed(button);
+        let imu = ImuData {
+            accel_x: frame.accel_x,
+            accel_y: frame.accel_y,
+            accel_z: frame.accel_z,
+            quat_w: frame.quat_w,
+            quat_x: frame.quat_x,
+            quat_y: frame.quat_y,
+            quat_z: frame.quat_z,
+        };
+        Self {
+            buttons: ButtonState {
+                a: pressed(BUTTON_A),
+                b: pressed(BUTTON_B),
+                x: pressed(BUTTON_X),
+                y: pressed(BUTTON_Y),
+                lb: pressed(BUTTON_LB),
+                rb: pressed(BUTTON_RB),
+                lt: pressed(BUTTON_LT),
+                rt: pressed(BUTTON_RT),
+                lgrip: pressed(BUTTON_LGRIP),
+                rgrip: pressed(BUTTON_RGRIP),
+                start: pressed(BUTTON_START),
+                select: pressed(BUTTON_SELECT),
+                steam: pressed(BUTTON_STEAM),
+                lpad_click: pressed(BUTTON_LPAD_CLICK),
+                rpad_click: pressed(BUTTON_RPAD_CLICK),
+                stick_click: pressed(BUTTON_STICK_CLICK),
+            },
+            left_trackpad: TrackpadData {
+                x: frame.left_pad_x,
+                y: frame.left_pad_y,
+                active: frame.left_pad_active != 0,
+            },
+            right_trackpad: TrackpadData {
+                x: frame.right_pad_x,
+                y: frame.right_pad_y,
+                active: frame.right_pad_active != 0,
+            },
+            stick: StickData { x: frame.stick_x, y: frame.stick_y },
+            right_stick: StickData::default(),
+            triggers: TriggersData { left: frame.left_trigger, right: frame.right_trigger },
+            gyro: GyroData { pitch: frame.gyro_pitch, yaw: frame.gyro_yaw, roll: frame.gyro_roll },
+            imu,
+            orientation: imu.orientation(),
+            timestamp: frame.timestamp,
+            extended_timestamp: frame.extended_timestamp,
+            host_timestamp_us: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parser::{InputParser, INPUT_PACKET};
+
+    #[test]
+    fn test_parse_frame_matches_parse() {
+        assert_eq!(std::mem::size_of::<RawFrame>(), 56);
+
+        let mut data = [0u8; REPORT_LEN];
+        data[0] = 0x01;
+        data[2] = INPUT_PACKET;
+        data[4..8].copy_from_slice(&123_456u32.to_le_bytes());
+        data[8] = 0x01; // RT
+        data[10] = 0x14; // Right pad touched and clicked
+        data[12] = 200;
+        data[13] = 17;
+        data[16..18].copy_from_slice(&(-1500i16).to_le_bytes());
+        data[18..20].copy_from_slice(&2500i16.to_le_bytes());
+        data[20..22].copy_from_slice(&300i16.to_le_bytes());
+        data[22..24].copy_from_slice(&(-400i16).to_le_bytes());
+        data[48..50].copy_from_slice(&11i16.to_le_bytes());
+        data[50..52].copy_from_slice(&(-22i16).to_le_bytes());
+        data[52..54].copy_from_slice(&33i16.to_le_bytes());
+
+        let mut frame = RawFrame::default();
+        InputParser::new().parse_frame(&data, &mut frame).unwrap();
+        assert_eq!(frame.buttons, BUTTON_RT | BUTTON_RPAD_CLICK);
+        assert_eq!((frame.stick_x, frame.stick_y), (-1500, 2500));
+        assert_eq!((frame.right_pad_x, frame.right_pad_y, frame.right_pad_active), (300, -400, 1));
+        assert_eq!((frame.gyro_pitch, frame.gyro_yaw, frame.gyro_roll), (11, -22, 33));
+        assert_eq!((frame.left_trigger, frame.right_trigger), (17, 200));
+
+        let input = InputParser::new().parse(&data).unwrap();
+        assert_eq!(serde_json::to_value(ControllerInput::from(&frame)).unwrap(), serde_json::to_value(&input).unwrap());
+
+        // Failures leave the frame alone
+        let before = frame;
+        let mut parser = InputParser::new();
+        assert_eq!(parser.parse_frame(&data[..32], &mut frame), Err(FrameError::TooShort(32)));
+        data[2] = 0x04;
+        assert_eq!(parser.parse_frame(&data, &mut frame), Err(FrameError::NotInput(0x04)));
+        data[0] = 0x02;
+        assert_eq!(parser.parse_frame(&data, &mut frame), Err(FrameError::InvalidReportId(0x02)));
+        assert_eq!(frame, before);
+    }
+}
@@ -0,0 +1,139 @@
+//! Frames in the shape of the W3C Gamepad API's "standard" mapping (an `axes`
+//! array and a `buttons` array of `{pressed, touched, value}`), so web gamepad
+//! testers and visualizers can consume the stream without knowing our struct.
+//!
+//! The Steam Controller has no right stick or D-pad: the right pad stands in
+//! for the right stick, and left pad clicks become D-pad presses by quadrant.
+//! Grips and the right pad click go after the 17 standard buttons.
+
+use serde::Serialize;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::steam_controller::VALVE_VENDOR_ID;
+
+/// Event name for Gamepad-API-shaped frames
+pub const GAMEPAD_API_EVENT: &str = "gamepad-api-input";
+
+/// Standard buttons plus left grip, right grip and right pad click
+pub const GAMEPAD_API_BUTTONS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct GamepadButton {
+    pub pressed: bool,
+    pub touched: bool,
+    /// 0.0..=1.0
+    pub value: f64,
+}
+
+impl GamepadButton {
+    fn digital(pressed: bool) -> Self {
+        Self {
+            pressed,
+            touched: pressed,
+            value: if pressed { 1.0 } else { 0.0 },
+        }
+    }
+
+    fn analog(value: u8, clicked: bool) -> Self {
+        Self {
+            pressed: clicked,
+            touched: value > 0 || clicked,
+            value: value as f64 / 255.0,
+        }
+    }
+}
+
+/// Same fields as a `Gamepad` object in the browser
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StandardGamepad {
+    pub id: String,
+    pub index: u32,
+    pub connected: bool,
+    pub mapping: &'static str,
+    /// Milliseconds, from the host read time
+    pub timestamp: f64,
+    /// Left stick x/y, right stick x/y; -1.0..=1.0 with down positive
+    pub axes: [f64; 4],
+    pub buttons: Vec<GamepadButton>,
+}
+
+fn axis(value: i16) -> f64 {
+    (value as f64 / 32767.0).clamp(-1.0, 1.0)
+}
+
+/// D-pad button (up, down, left, right) a left pad click lands on
+pub(crate) fn dpad(pad: &TrackpadData, clicked: bool) -> [bool; 4] {
+    if !clicked {
+        return [false; 4];
+    }
+    let (x, y) = (pad.x as i32, pad.y as i32);
+    if y.abs() >= x.abs() {
+        [y > 0, y <= 0, false, false]
+    } else {
+        [false, false, x < 0, x > 0]
+    }
+}
+
+pub fn to_standard_gamepad(input: &ControllerInput) -> StandardGamepad {
+    let b = &input.buttons;
+    let [up, down, left, right] = dpad(&input.left_trackpad, b.lpad_click);
+    let right_pad = if input.right_trackpad.active {
+        [axis(input.right_trackpad.x), -axis(input.right_trackpad.y)]
+    } else {
+        [0.0, 0.0]
+    };
+
+    let mut buttons: Vec<GamepadButton> = [b.a, b.b, b.x, b.y, b.lb, b.rb]
+        .iter()
+        .map(|&pressed| GamepadButton::digital(pressed))
+        .collect();
+    buttons.push(GamepadButton::analog(input.triggers.left, b.lt));
+    buttons.push(GamepadButton::analog(input.triggers.right, b.rt));
+    buttons.extend(
+        [
+            b.select, b.start, b.stick_click, false, up, down, left, right, b.steam, b.lgrip, b.rgrip,
+            b.rpad_click,
+        ]
+        .iter()
+        .map(|&pressed| GamepadButton::digital(pressed)),
+    );
+    // The right pad is a touch surface: count a touch as touching its button
+    buttons[19].touched |= input.right_trackpad.active;
+
+    StandardGamepad {
+        id: format!("Steam Controller (STANDARD GAMEPAD Vendor: {:04x})", VALVE_VENDOR_ID),
+        index: 0,
+        connected: true,
+        mapping: "standard",
+        timestamp: input.host_timestamp_us as f64 / 1000.0,
+        axes: [axis(input.stick.x), -axis(input.stick.y), right_pad[0], right_pad[1]],
+        buttons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_mapping() {
+        let mut input = ControllerInput::default();
+        input.buttons.a = true;
+        input.buttons.lpad_click = true;
+        input.left_trackpad = TrackpadData { x: -20000, y: 3000, active: true };
+        input.triggers.right = 255;
+        input.buttons.rt = true;
+        input.stick.y = 32767;
+        input.host_timestamp_us = 1500;
+
+        let pad = to_standard_gamepad(&input);
+        assert_eq!(pad.buttons.len(), GAMEPAD_API_BUTTONS);
+        assert!(pad.buttons[0].pressed && !pad.buttons[1].pressed);
+        assert_eq!(pad.buttons[7], GamepadButton { pressed: true, touched: true, value: 1.0 });
+        // Left pad clicked on its left side: D-pad left only
+        let dpad: Vec<bool> = pad.buttons[12..16].iter().map(|b| b.pressed).collect();
+        assert_eq!(dpad, vec![false, false, true, false]);
+        // Stick pushed up is -1 in the Gamepad API
+        assert_eq!(pad.axes, [0.0, -1.0, 0.0, 0.0]);
+        assert_eq!(pad.timestamp, 1.5);
+    }
+}
@@ -0,0 +1,281 @@
+//! Tap gestures on the trackpads, detected from touch timing rather than the
+//! click switch: a quick touch-and-lift is a tap, two in a row a double-tap,
+//! and a tap followed by touch-and-hold is a tap-drag (held until lift-off).
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{Action, Profile};
+
+/// Longest touch that still counts as a tap
+pub const TAP_MAX_DURATION: Duration = Duration::from_millis(180);
+/// How long after a tap a second touch turns it into a double-tap or tap-drag
+pub const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(250);
+/// Largest movement (pad units, per axis) during a tap
+pub const TAP_MAX_TRAVEL: i32 = 2500;
+
+/// Bindable gestures, keyed in profiles as e.g. `"right_double_tap"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureId {
+    LeftTap,
+    LeftDoubleTap,
+    /// Held from the second touch until lift-off
+    LeftTapDrag,
+    RightTap,
+    RightDoubleTap,
+    RightTapDrag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pad {
+    Left,
+    Right,
+}
+
+impl Pad {
+    fn gesture(self, kind: GestureKind) -> GestureId {
+        match (self, kind) {
+            (Pad::Left, GestureKind::Tap) => GestureId::LeftTap,
+            (Pad::Left, GestureKind::DoubleTap) => GestureId::LeftDoubleTap,
+            (Pad::Left, GestureKind::TapDrag) => GestureId::LeftTapDrag,
+            (Pad::Right, GestureKind::Tap) => GestureId::RightTap,
+            (Pad::Right, GestureKind::DoubleTap) => GestureId::RightDoubleTap,
+            (Pad::Right, GestureKind::TapDrag) => GestureId::RightTapDrag,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GestureKind {
+    Tap,
+    DoubleTap,
+    TapDrag,
+}
+
+/// Taps and double-taps are instantaneous; a tap-drag starts and later ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", content = "gesture", rename_all = "snake_case")]
+pub enum GestureEvent {
+    Fired(GestureId),
+    Started(GestureId),
+    Ended(GestureId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TapState {
+    Idle,
+    /// First touch in progress
+    Touching { since: Instant, origin: (i16, i16), clicked: bool },
+    /// One tap done; waiting to see if a second touch follows
+    TappedOnce { at: Instant },
+    /// Second touch in progress - a double-tap if short, otherwise a drag
+    SecondTouch { since: Instant, origin: (i16, i16), clicked: bool },
+    Dragging,
+    /// Touch that can no longer become a gesture; wait for lift-off
+    Ignored,
+}
+
+/// Tap state machine for one pad
+#[derive(Debug, Clone)]
+pub struct TapDetector {
+    pad: Pad,
+    state: TapState,
+}
+
+fn within_travel(origin: (i16, i16), pad: &TrackpadData) -> bool {
+    (pad.x as i32 - origin.0 as i32).abs() <= TAP_MAX_TRAVEL
+        && (pad.y as i32 - origin.1 as i32).abs() <= TAP_MAX_TRAVEL
+}
+
+impl TapDetector {
+    pub fn new(pad: Pad) -> Self {
+        Self {
+            pad,
+            state: TapState::Idle,
+        }
+    }
+
+    /// Feed one frame. `clicked` is the pad's click switch - a touch that
+    /// clicks is a click, not a tap.
+    pub fn update(&mut self, pad: &TrackpadData, clicked: bool, now: Instant) -> Option<GestureEvent> {
+        let touching = pad.active;
+        let position = (pad.x, pad.y);
+
+        let (next, event) = match self.state {
+            TapState::Idle if touching => (
+                TapState::Touching { since: now, origin: position, clicked },
+                None,
+            ),
+            TapState::Idle => (TapState::Idle, None),
+
+            TapState::Touching { since, origin, clicked: was_clicked } => {
+                let clicked = clicked || was_clicked;
+                if touching {
+                    let stale = now.duration_since(since) > TAP_MAX_DURATION || !within_travel(origin, pad);
+                    if stale || clicked {
+                        (TapState::Ignored, None)
+                    } else {
+                        (TapState::Touching { since, origin, clicked }, None)
+                    }
+                } else if clicked {
+                    (TapState::Idle, None)
+                } else {
+                    (TapState::TappedOnce { at: now }, None)
+                }
+            }
+
+            TapState::TappedOnce { at } => {
+                if touching {
+                    (TapState::SecondTouch { since: now, origin: position, clicked }, None)
+                } else if now.duration_since(at) > DOUBLE_TAP_WINDOW {
+                    (TapState::Idle, Some(self.fired(GestureKind::Tap)))
+                } else {
+                    (TapState::TappedOnce { at }, None)
+                }
+            }
+
+            TapState::SecondTouch { since, origin, clicked: was_clicked } => {
+                let clicked = clicked || was_clicked;
+                if !touching {
+                    if clicked {
+                        // The first touch was still a tap
+                        (TapState::Idle, Some(self.fired(GestureKind::Tap)))
+                    } else {
+                        (TapState::Idle, Some(self.fired(GestureKind::DoubleTap)))
+                    }
+                } else if now.duration_since(since) > TAP_MAX_DURATION || !within_travel(origin, pad) {
+                    (
+                        TapState::Dragging,
+                        Some(GestureEvent::Started(self.pad.gesture(GestureKind::TapDrag))),
+                    )
+                } else {
+                    (TapState::SecondTouch { since, origin, clicked }, None)
+                }
+            }
+
+            TapState::Dragging if touching => (TapState::Dragging, None),
+            TapState::Dragging => (
+                TapState::Idle,
+                Some(GestureEvent::Ended(self.pad.gesture(GestureKind::TapDrag))),
+            ),
+
+            TapState::Ignored if touching => (TapState::Ignored, None),
+            TapState::Ignored => (TapState::Idle, None),
+        };
+
+        self.state = next;
+        event
+    }
+
+    fn fired(&self, kind: GestureKind) -> GestureEvent {
+        GestureEvent::Fired(self.pad.gesture(kind))
+    }
+}
+
+/// A detected gesture and what the profile binds it to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoundGesture {
+    pub event: GestureEvent,
+    pub action: Option<Action>,
+}
+
+impl GestureEvent {
+    pub fn gesture(&self) -> GestureId {
+        match *self {
+            GestureEvent::Fired(id) | GestureEvent::Started(id) | GestureEvent::Ended(id) => id,
+        }
+    }
+
+    /// Look up the binding for this gesture in `profile`
+    pub fn bind(self, profile: Option<&Profile>) -> BoundGesture {
+        BoundGesture {
+            event: self,
+            action: profile.and_then(|p| p.gestures.get(&self.gesture()).cloned()),
+        }
+    }
+}
+
+/// Tap detection for both pads
+#[derive(Debug, Clone)]
+pub struct GestureDetector {
+    left: TapDetector,
+    right: TapDetector,
+}
+
+impl Default for GestureDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GestureDetector {
+    pub fn new() -> Self {
+        Self {
+            left: TapDetector::new(Pad::Left),
+            right: TapDetector::new(Pad::Right),
+        }
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, now: Instant) -> Vec<GestureEvent> {
+        let left = self.left.update(&input.left_trackpad, input.buttons.lpad_click, now);
+        let right = self.right.update(&input.right_trackpad, input.buttons.rpad_click, now);
+        left.into_iter().chain(right).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOUCH: TrackpadData = TrackpadData { x: 1000, y: -500, active: true };
+    const LIFT: TrackpadData = TrackpadData { x: 0, y: 0, active: false };
+
+    // Feed (pad, ms since start) frames, collecting the events
+    fn run(frames: &[(TrackpadData, u64)]) -> Vec<GestureEvent> {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(Pad::Right);
+        frames
+            .iter()
+            .filter_map(|(pad, ms)| detector.update(pad, false, start + Duration::from_millis(*ms)))
+            .collect()
+    }
+
+    #[test]
+    fn test_tap_fires_after_window() {
+        let events = run(&[(TOUCH, 0), (TOUCH, 50), (LIFT, 100), (LIFT, 200), (LIFT, 400)]);
+        assert_eq!(events, vec![GestureEvent::Fired(GestureId::RightTap)]);
+    }
+
+    #[test]
+    fn test_double_tap_replaces_tap() {
+        let events = run(&[(TOUCH, 0), (LIFT, 80), (TOUCH, 160), (LIFT, 220), (LIFT, 600)]);
+        assert_eq!(events, vec![GestureEvent::Fired(GestureId::RightDoubleTap)]);
+    }
+
+    #[test]
+    fn test_tap_drag() {
+        let moved = TrackpadData { x: 9000, ..TOUCH };
+        let events = run(&[(TOUCH, 0), (LIFT, 80), (TOUCH, 160), (moved, 200), (moved, 900), (LIFT, 950)]);
+        assert_eq!(
+            events,
+            vec![
+                GestureEvent::Started(GestureId::RightTapDrag),
+                GestureEvent::Ended(GestureId::RightTapDrag),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_touch_or_click_is_not_a_tap() {
+        assert!(run(&[(TOUCH, 0), (TOUCH, 400), (LIFT, 420), (LIFT, 900)]).is_empty());
+
+        let start = Instant::now();
+        let mut detector = TapDetector::new(Pad::Left);
+        assert_eq!(detector.update(&TOUCH, false, start), None);
+        assert_eq!(detector.update(&TOUCH, true, start + Duration::from_millis(30)), None);
+        assert_eq!(detector.update(&LIFT, false, start + Duration::from_millis(60)), None);
+        assert_eq!(detector.update(&LIFT, false, start + Duration::from_millis(600)), None);
+    }
+}
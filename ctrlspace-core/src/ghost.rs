@@ -0,0 +1,322 @@
+//! Input session recording and "ghost" replay: recorded frames are fed back
+//! through the mapping engine at their original timing, as if the controller
+//! were being used. Handy for demonstrating a profile, reproducing a bug in a
+//! game, and testing the output layer without hands on the hardware.
+//!
+//! Frames are recorded after calibration and anti-drift, so a replay on a
+//! different unit behaves the same as on the one it was recorded with.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::clock;
+use crate::input_parser::ControllerInput;
+use crate::input_stream::InputFrame;
+use crate::profiles::profile_file_stem;
+
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Subdirectory of the app data dir recordings are written to
+pub const RECORDINGS_DIR_NAME: &str = "recordings";
+
+/// About 20 minutes at the controller's 250 Hz
+pub const MAX_RECORDED_FRAMES: usize = 300_000;
+
+/// Event name for replayed frames (shaped like `controller-input`)
+pub const GHOST_INPUT_EVENT: &str = "ghost-input";
+
+/// Event name for replay start/end
+pub const GHOST_REPLAY_EVENT: &str = "ghost-replay";
+
+// Longest sleep between stop checks
+const STOP_POLL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Time since the first frame, in µs
+    pub at_us: u64,
+    pub input: ControllerInput,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub version: u32,
+    pub name: String,
+    /// Unix seconds
+    pub created: u64,
+    /// Registry ID of the controller it was recorded with, if known
+    #[serde(default)]
+    pub device: Option<String>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// A recording on disk, without its frames
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordingInfo {
+    /// File name inside the recordings dir, used to replay it
+    pub file: String,
+    pub name: String,
+    pub created: u64,
+    pub device: Option<String>,
+    pub frames: usize,
+    pub duration_ms: u64,
+}
+
+impl Recording {
+    pub fn duration_us(&self) -> u64 {
+        self.frames.last().map_or(0, |f| f.at_us)
+    }
+
+    /// Summary for the recording saved as `file`
+    pub fn info(&self, file: String) -> RecordingInfo {
+        RecordingInfo {
+            file,
+            name: self.name.clone(),
+            created: self.created,
+            device: self.device.clone(),
+            frames: self.frames.len(),
+            duration_ms: self.duration_us() / 1000,
+        }
+    }
+
+    /// Write to `dir` as `<name>-<created>.json`
+    pub fn save(&self, dir: &Path) -> Result<RecordingInfo, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let file = format!("{}-{}.json", profile_file_stem(&self.name), self.created);
+        let path = dir.join(&file);
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to serialize recording: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(self.info(file))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let recording: Self =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid recording {}: {}", path.display(), e))?;
+        if recording.version > RECORDING_VERSION {
+            return Err(format!(
+                "Recording {} uses format version {}, newer than supported ({})",
+                path.display(),
+                recording.version,
+                RECORDING_VERSION
+            ));
+        }
+        Ok(recording)
+    }
+}
+
+/// Path of a recording by file name; names with path parts are refused
+pub fn recording_path(dir: &Path, file: &str) -> Result<PathBuf, String> {
+    let plain = Path::new(file).file_name().and_then(|n| n.to_str()) == Some(file);
+    if !plain || !file.ends_with(".json") {
+        return Err(format!("Invalid recording name: {}", file));
+    }
+    Ok(dir.join(file))
+}
+
+/// Every recording in `dir`, newest first; unreadable files are skipped
+pub fn list_recordings(dir: &Path) -> Result<Vec<RecordingInfo>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut recordings = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file = match path.file_name().and_then(|n| n.to_str()) {
+            Some(file) if file.ends_with(".json") => file.to_string(),
+            _ => continue,
+        };
+        match Recording::load(&path) {
+            Ok(recording) => recordings.push(recording.info(file)),
+            Err(e) => eprintln!("❌ {}", e),
+        }
+    }
+    recordings.sort_by_key(|r| std::cmp::Reverse(r.created));
+    Ok(recordings)
+}
+
+/// Collects frames from the live stream
+#[derive(Debug, Clone, Default)]
+pub struct SessionRecorder {
+    started_us: Option<u64>,
+    frames: Vec<RecordedFrame>,
+}
+
+impl SessionRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a frame; false once the recording is full
+    pub fn push(&mut self, input: &ControllerInput) -> bool {
+        if self.frames.len() >= MAX_RECORDED_FRAMES {
+            return false;
+        }
+        let started = *self.started_us.get_or_insert(input.host_timestamp_us);
+        self.frames.push(RecordedFrame {
+            at_us: input.host_timestamp_us.saturating_sub(started),
+            input: input.clone(),
+        });
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn finish(self, name: &str, device: Option<String>) -> Result<Recording, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Recording name is empty".to_string());
+        }
+        if self.frames.is_empty() {
+            return Err("Nothing was recorded - is input streaming?".to_string());
+        }
+        Ok(Recording {
+            version: RECORDING_VERSION,
+            name: name.to_string(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            device,
+            frames: self.frames,
+        })
+    }
+}
+
+/// Payload of `GHOST_REPLAY_EVENT`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum GhostReplayEvent {
+    Started { recording: RecordingInfo },
+    /// `completed` is false when it was stopped early
+    Finished { name: String, completed: bool },
+}
+
+/// Plays a recording on its own thread
+pub struct GhostReplay {
+    stop: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl GhostReplay {
+    /// Feed `emit` every frame at its recorded offset, restamped with the
+    /// current host time so time-based gestures see the original pacing.
+    /// With `looped` it starts over until stopped. `on_end` gets whether the
+    /// replay ran to the end (false when stopped).
+    pub fn spawn<F, E>(recording: Recording, looped: bool, emit: F, on_end: E) -> Self
+    where
+        F: Fn(&InputFrame) + Send + 'static,
+        E: FnOnce(bool) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_stop = Arc::clone(&stop);
+        let thread_running = Arc::clone(&running);
+
+        let handle = thread::spawn(move || {
+            println!("🎮 Replaying '{}' ({} frames)", recording.name, recording.frames.len());
+            let mut seq = 0;
+            let completed = 'replay: loop {
+                let base = clock::monotonic_us();
+                for frame in &recording.frames {
+                    let due = base + frame.at_us;
+                    loop {
+                        if thread_stop.load(Ordering::Relaxed) {
+                            break 'replay false;
+                        }
+                        let now = clock::monotonic_us();
+                        if now >= due {
+                            break;
+                        }
+                        thread::sleep(Duration::from_micros(due - now).min(STOP_POLL));
+                    }
+                    seq += 1;
+                    let mut input = frame.input.clone();
+                    input.host_timestamp_us = due;
+                    emit(&InputFrame { seq, coalesced: 0, input });
+                }
+                if !looped || recording.frames.is_empty() {
+                    break true;
+                }
+            };
+            thread_running.store(false, Ordering::Release);
+            println!("🎮 Replay of '{}' {}", recording.name, if completed { "finished" } else { "stopped" });
+            on_end(completed);
+        });
+
+        Self {
+            stop,
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// Stop and wait for the replay thread
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    #[test]
+    fn test_record_save_and_replay_at_original_timing() {
+        let mut recorder = SessionRecorder::new();
+        for (i, at_ms) in [1000u64, 1010, 1040].iter().enumerate() {
+            let mut input = ControllerInput::default();
+            input.buttons.a = i == 1;
+            input.host_timestamp_us = at_ms * 1000;
+            assert!(recorder.push(&input));
+        }
+        let recording = recorder.finish("A tap", Some("sc-1".to_string())).unwrap();
+        assert_eq!(recording.duration_us(), 40_000);
+
+        let dir = std::env::temp_dir().join(format!("ctrlspace-recordings-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let info = recording.save(&dir).unwrap();
+        assert!(info.file.starts_with("a-tap-"));
+        assert_eq!(list_recordings(&dir).unwrap(), vec![info.clone()]);
+        assert!(recording_path(&dir, "../escape.json").is_err());
+        let loaded = Recording::load(&recording_path(&dir, &info.file).unwrap()).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        let (tx, rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel();
+        let started = Instant::now();
+        let replay = GhostReplay::spawn(
+            loaded,
+            false,
+            move |frame| tx.send((frame.input.buttons.a, frame.input.host_timestamp_us)).unwrap(),
+            move |completed| done_tx.send(completed).unwrap(),
+        );
+        assert!(done_rx.recv_timeout(Duration::from_secs(5)).unwrap());
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert!(!replay.is_running());
+
+        let frames: Vec<(bool, u64)> = rx.try_iter().collect();
+        assert_eq!(frames.iter().map(|f| f.0).collect::<Vec<_>>(), vec![false, true, false]);
+        assert_eq!(frames[2].1 - frames[0].1, 40_000);
+    }
+}
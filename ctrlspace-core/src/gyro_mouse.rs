@@ -0,0 +1,164 @@
+//! Gyro aiming: with the gyro in `gyro_mouse` mode, turning the controller
+//! moves the cursor. Each frame's angular velocity is integrated over the
+//! time since the previous frame (host read times), so the cursor turns by the
+//! same amount whatever the report rate. Movement only happens while the
+//! profile's activation condition holds, e.g. a finger on the right pad, so
+//! the controller can be re-centered by letting go.
+
+use serde::{Deserialize, Serialize};
+use crate::input_parser::ControllerInput;
+use crate::profiles::{AnalogMode, ButtonId, Profile};
+
+/// Raw gyro counts per degree per second (±2000 °/s full scale)
+pub const GYRO_COUNTS_PER_DEGREE: f32 = 16.4;
+
+/// Cursor pixels per degree turned at sensitivity 1.0
+pub const PIXELS_PER_DEGREE: f32 = 20.0;
+
+/// Longest gap between frames that is integrated; a stall or a dropped
+/// connection doesn't become one big jump
+const MAX_FRAME_GAP_US: u64 = 50_000;
+
+/// When gyro aiming moves the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GyroActivation {
+    Always,
+    /// While a finger rests on the left pad
+    LeftPadTouch,
+    /// While a finger rests on the right pad
+    RightPadTouch,
+    /// While a button is held (`{"held": "rt"}`)
+    Held(ButtonId),
+}
+
+impl Default for GyroActivation {
+    fn default() -> Self {
+        GyroActivation::Always
+    }
+}
+
+impl GyroActivation {
+    pub fn is_active(&self, input: &ControllerInput) -> bool {
+        match self {
+            GyroActivation::Always => true,
+            GyroActivation::LeftPadTouch => input.left_trackpad.active,
+            GyroActivation::RightPadTouch => input.right_trackpad.active,
+            GyroActivation::Held(button) => button.is_pressed(&input.buttons),
+        }
+    }
+}
+
+/// Cursor movement from one frame of gyro aiming
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GyroMove {
+    pub dx: i32,
+    /// Positive is down, as on screen
+    pub dy: i32,
+}
+
+/// Gyro aiming state carried between frames
+#[derive(Debug, Clone, Default)]
+pub struct GyroMouse {
+    last_us: Option<u64>,
+    /// Sub-pixel movement carried to the next frame
+    remainder: (f32, f32),
+}
+
+impl GyroMouse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Movement for this frame, scaled by `factor` (slow mode and the like).
+    /// `None` when the gyro isn't in `gyro_mouse` mode, the activation
+    /// condition doesn't hold, or the movement rounds to nothing.
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>, factor: f32) -> Option<GyroMove> {
+        let (sensitivity, invert_x, invert_y, activation) = match profile.map(|p| &p.gyro) {
+            Some(AnalogMode::GyroMouse { sensitivity, invert_x, invert_y, activation }) => {
+                (*sensitivity, *invert_x, *invert_y, *activation)
+            }
+            _ => {
+                *self = Self::default();
+                return None;
+            }
+        };
+        if !activation.is_active(input) {
+            *self = Self::default();
+            return None;
+        }
+
+        let now = input.host_timestamp_us;
+        let elapsed_us = self.last_us.map_or(0, |last| now.saturating_sub(last).min(MAX_FRAME_GAP_US));
+        self.last_us = Some(now);
+        if elapsed_us == 0 {
+            return None;
+        }
+
+        let seconds = elapsed_us as f32 / 1_000_000.0;
+        let scale = seconds / GYRO_COUNTS_PER_DEGREE * PIXELS_PER_DEGREE * sensitivity * factor;
+        // Positive yaw turns left and positive pitch tilts up; the screen's
+        // x grows to the right and y downwards
+        let sign = |invert: bool| if invert { 1.0 } else { -1.0 };
+        let dx = input.gyro.yaw as f32 * scale * sign(invert_x) + self.remainder.0;
+        let dy = input.gyro.pitch as f32 * scale * sign(invert_y) + self.remainder.1;
+        let step = (dx.trunc(), dy.trunc());
+        self.remainder = (dx - step.0, dy - step.1);
+
+        let (dx, dy) = (step.0 as i32, step.1 as i32);
+        if (dx, dy) == (0, 0) {
+            None
+        } else {
+            Some(GyroMove { dx, dy })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(yaw: i16, pitch: i16, at_us: u64, rt: bool) -> ControllerInput {
+        let mut input = ControllerInput::default();
+        input.gyro.yaw = yaw;
+        input.gyro.pitch = pitch;
+        input.host_timestamp_us = at_us;
+        input.buttons.rt = rt;
+        input
+    }
+
+    #[test]
+    fn test_gyro_mouse() {
+        let mut profile = Profile::new("Shooter");
+        profile.gyro = AnalogMode::GyroMouse {
+            sensitivity: 1.0,
+            invert_x: false,
+            invert_y: true,
+            activation: GyroActivation::Held(ButtonId::Rt),
+        };
+        let mut gyro = GyroMouse::new();
+
+        // Not held: nothing, and the first held frame only starts the clock
+        assert_eq!(gyro.update(&frame(1640, 0, 0, false), Some(&profile), 1.0), None);
+        assert_eq!(gyro.update(&frame(1640, 0, 10_000, true), Some(&profile), 1.0), None);
+
+        // 100 °/s for 100 ms is 10° = 200 px; yaw left moves the cursor left
+        let mut total = (0, 0);
+        for i in 1..=10 {
+            if let Some(movement) = gyro.update(&frame(1640, -820, 10_000 + i * 10_000, true), Some(&profile), 1.0) {
+                total = (total.0 + movement.dx, total.1 + movement.dy);
+            }
+        }
+        assert!((total.0 + 200).abs() <= 1, "{:?}", total);
+        // Pitch is inverted: tilting down (negative pitch) moves the cursor up
+        assert!((total.1 + 100).abs() <= 1, "{:?}", total);
+
+        // A long stall counts as 50 ms, and slow mode scales it
+        let movement = gyro.update(&frame(1640, 0, 2_000_000, true), Some(&profile), 0.5).unwrap();
+        assert!((movement.dx + 50).abs() <= 1, "{:?}", movement);
+
+        // Other gyro modes leave the cursor alone
+        profile.gyro = AnalogMode::Disabled;
+        assert_eq!(gyro.update(&frame(1640, 0, 2_010_000, true), Some(&profile), 1.0), None);
+    }
+}
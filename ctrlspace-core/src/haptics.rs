@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+/// Feature report that drives the trackpad actuators
+pub const HAPTIC_FEEDBACK_REPORT: u8 = 0x8f;
+
+/// Global scale for every pad haptic, on top of the per-device strength
+pub const DEFAULT_HAPTIC_INTENSITY: f32 = 1.0;
+
+pub fn validate_haptic_intensity(intensity: f32) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&intensity) {
+        return Err(format!("Haptic intensity must be between 0.0 and 1.0: {}", intensity));
+    }
+    Ok(())
+}
+
+/// Most pulses sent per second to each actuator; 0 turns the limit off
+pub const DEFAULT_MAX_HAPTIC_RATE: u32 = 60;
+
+pub const MAX_HAPTIC_RATE_LIMIT: u32 = 1000;
+
+pub fn validate_max_haptic_rate(rate: u32) -> Result<(), String> {
+    if rate > MAX_HAPTIC_RATE_LIMIT {
+        return Err(format!(
+            "Haptic rate limit must be at most {} pulses per second (0 for none): {}",
+            MAX_HAPTIC_RATE_LIMIT, rate
+        ));
+    }
+    Ok(())
+}
+
+// Feature reports are padded to the full 64-byte HID report
+const FEATURE_REPORT_SIZE: usize = 64;
+
+/// Which trackpad actuator to drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HapticSide {
+    Right,
+    Left,
+}
+
+/// One haptic burst: `count` pulses of `amplitude`, each `period` µs apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HapticPulse {
+    pub side: HapticSide,
+    pub amplitude: u16,
+    pub period: u16,
+    pub count: u16,
+}
+
+impl HapticPulse {
+    /// Short tick used to confirm controller-side actions (profile switch, toggles)
+    pub const fn confirm(side: HapticSide) -> Self {
+        Self {
+            side,
+            amplitude: 1024,
+            period: 2048,
+            count: 4,
+        }
+    }
+
+    /// Faint click for one detent of a rim spin
+    pub const fn detent(side: HapticSide) -> Self {
+        Self {
+            side,
+            amplitude: 512,
+            period: 1024,
+            count: 2,
+        }
+    }
+
+    /// Standard quarter-second buzz for "Test left / Test right" in settings.
+    /// `strength` is 0.0-1.0 of the strongest test amplitude.
+    pub fn test_buzz(side: HapticSide, strength: f32) -> Self {
+        Self {
+            side,
+            amplitude: 4096,
+            period: 1250,
+            count: 200,
+        }
+        .scaled(strength)
+    }
+
+    /// Same pulse with the amplitude scaled by `strength` (0.0-1.0)
+    pub fn scaled(self, strength: f32) -> Self {
+        Self {
+            amplitude: (self.amplitude as f32 * strength.clamp(0.0, 1.0)).round() as u16,
+            ..self
+        }
+    }
+
+    /// Feature report bytes: 0x8f, length, side, then amplitude/period/count as u16 LE
+    pub fn to_report(&self) -> Vec<u8> {
+        let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+        report[0] = HAPTIC_FEEDBACK_REPORT;
+        report[1] = 0x07; // payload length
+        report[2] = match self.side {
+            HapticSide::Right => 0x00,
+            HapticSide::Left => 0x01,
+        };
+        report[3..5].copy_from_slice(&self.amplitude.to_le_bytes());
+        report[5..7].copy_from_slice(&self.period.to_le_bytes());
+        report[7..9].copy_from_slice(&self.count.to_le_bytes());
+        report
+    }
+}
+
+/// Keeps each actuator under a maximum command rate. A pulse arriving too
+/// soon after the last one sent on its side is held, and folded into the next
+/// pulse (strongest amplitude and longest count win). [`flush`](Self::flush)
+/// sends it once its side is due again, so a fast spin still ends on a
+/// full-strength detent rather than a flood of writes. A held pulse older
+/// than one interval is dropped rather than sent late.
+#[derive(Debug, Clone, Default)]
+pub struct HapticRateLimiter {
+    last_sent_us: [Option<u64>; 2],
+    /// Pulse waiting for its side, and when it was last merged
+    held: [Option<(HapticPulse, u64)>; 2],
+    coalesced: u64,
+}
+
+fn pulse_interval_us(max_rate: u32) -> u64 {
+    if max_rate == 0 {
+        0
+    } else {
+        1_000_000 / max_rate as u64
+    }
+}
+
+fn side_index(side: HapticSide) -> usize {
+    match side {
+        HapticSide::Right => 0,
+        HapticSide::Left => 1,
+    }
+}
+
+impl HapticRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pulse to send now, if any, at `max_rate` pulses per second per side
+    pub fn admit(&mut self, pulse: HapticPulse, now_us: u64, max_rate: u32) -> Option<HapticPulse> {
+        let i = side_index(pulse.side);
+        let interval = pulse_interval_us(max_rate);
+        let merged = match self.held[i].take() {
+            Some((held, held_at)) if now_us.saturating_sub(held_at) <= interval => HapticPulse {
+                amplitude: held.amplitude.max(pulse.amplitude),
+                count: held.count.max(pulse.count),
+                ..pulse
+            },
+            _ => pulse,
+        };
+        if self.is_due(i, now_us, interval) {
+            self.last_sent_us[i] = Some(now_us);
+            Some(merged)
+        } else {
+            self.held[i] = Some((merged, now_us));
+            self.coalesced += 1;
+            None
+        }
+    }
+
+    /// Held pulses whose side is due again, to send now; call this often
+    /// (more than once per interval) for held pulses to go out on time
+    pub fn flush(&mut self, now_us: u64, max_rate: u32) -> Vec<HapticPulse> {
+        let interval = pulse_interval_us(max_rate);
+        let mut due = Vec::new();
+        for i in 0..self.held.len() {
+            let (pulse, held_at) = match self.held[i] {
+                Some(held) => held,
+                None => continue,
+            };
+            if now_us.saturating_sub(held_at) > interval {
+                self.held[i] = None;
+            } else if self.is_due(i, now_us, interval) {
+                self.held[i] = None;
+                self.last_sent_us[i] = Some(now_us);
+                due.push(pulse);
+            }
+        }
+        due
+    }
+
+    fn is_due(&self, i: usize, now_us: u64, interval: u64) -> bool {
+        self.last_sent_us[i].map_or(true, |last| now_us.saturating_sub(last) >= interval)
+    }
+
+    /// Pulses folded into later ones so far
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+}
+
+/// Feedback shapes a binding can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HapticPattern {
+    /// Light click, e.g. menu selections
+    Tick,
+    /// Heavy low thud, e.g. layer switches
+    Thunk,
+    /// Sustained buzz
+    Buzz,
+}
+
+/// Haptic feedback played when a binding activates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HapticFeedback {
+    pub pattern: HapticPattern,
+    /// Pad to play on; both when unset
+    #[serde(default)]
+    pub side: Option<HapticSide>,
+    /// 0.0-1.0 of the pattern's full amplitude
+    #[serde(default = "default_feedback_strength")]
+    pub strength: f32,
+}
+
+fn default_feedback_strength() -> f32 {
+    1.0
+}
+
+impl HapticFeedback {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.strength) {
+            return Err(format!("Haptic strength must be between 0.0 and 1.0: {}", self.strength));
+        }
+        Ok(())
+    }
+
+    pub fn pulses(&self) -> Vec<HapticPulse> {
+        let sides: &[HapticSide] = match &self.side {
+            Some(side) => std::slice::from_ref(side),
+            None => &[HapticSide::Left, HapticSide::Right],
+        };
+        sides
+            .iter()
+            .map(|&side| {
+                let (amplitude, period, count) = match self.pattern {
+                    HapticPattern::Tick => (1024, 2048, 4),
+                    HapticPattern::Thunk => (4096, 6000, 12),
+                    HapticPattern::Buzz => (2048, 1250, 80),
+                };
+                HapticPulse { side, amplitude, period, count }.scaled(self.strength)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pulse_report_layout() {
+        let pulse = HapticPulse {
+            side: HapticSide::Left,
+            amplitude: 0x0102,
+            period: 0x0304,
+            count: 0x0506,
+        };
+        let report = pulse.to_report();
+        assert_eq!(report.len(), 64);
+        assert_eq!(&report[..9], &[0x8f, 0x07, 0x01, 0x02, 0x01, 0x04, 0x03, 0x06, 0x05]);
+        assert!(report[9..].iter().all(|&b| b == 0));
+
+        assert_eq!(pulse.scaled(0.5).amplitude, 0x0081);
+        assert_eq!(pulse.scaled(2.0), pulse);
+        assert_eq!(HapticPulse::test_buzz(HapticSide::Right, 0.25).amplitude, 1024);
+    }
+
+    #[test]
+    fn test_rate_limit_coalesces_per_side() {
+        let mut limiter = HapticRateLimiter::new();
+        let tick = HapticPulse::detent(HapticSide::Right);
+        let strong = HapticPulse { amplitude: 3000, ..tick };
+
+        // 50 per second: one pulse per 20 ms on each side
+        assert_eq!(limiter.admit(tick, 0, 50), Some(tick));
+        assert_eq!(limiter.admit(strong, 5_000, 50), None);
+        assert_eq!(limiter.admit(tick, 10_000, 50), None);
+        assert_eq!(limiter.admit(HapticPulse::detent(HapticSide::Left), 10_000, 50).unwrap().side, HapticSide::Left);
+        // The held strong pulse goes out with the next one that is due
+        assert_eq!(limiter.admit(tick, 20_000, 50).unwrap().amplitude, 3000);
+        assert_eq!(limiter.admit(tick, 40_000, 50), Some(tick));
+        assert_eq!(limiter.coalesced(), 2);
+
+        assert_eq!(limiter.admit(tick, 40_001, 0), Some(tick));
+        assert!(validate_max_haptic_rate(5000).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_flushes_the_last_pulse() {
+        let mut limiter = HapticRateLimiter::new();
+        let tick = HapticPulse::detent(HapticSide::Right);
+        let strong = HapticPulse { amplitude: 3000, ..tick };
+
+        // The burst's last pulse goes out once its side is due, with nothing after it
+        assert_eq!(limiter.admit(tick, 0, 50), Some(tick));
+        assert_eq!(limiter.admit(strong, 5_000, 50), None);
+        assert!(limiter.flush(15_000, 50).is_empty());
+        assert_eq!(limiter.flush(20_000, 50), vec![strong]);
+        assert!(limiter.flush(25_000, 50).is_empty());
+
+        // Held for more than an interval without a flush: dropped, not merged later
+        assert_eq!(limiter.admit(tick, 30_000, 50), None);
+        assert_eq!(limiter.admit(tick, 2_000_000, 50), Some(tick));
+        assert_eq!(limiter.admit(strong, 2_010_000, 50), None);
+        assert!(limiter.flush(2_040_000, 50).is_empty());
+        assert_eq!(limiter.admit(tick, 3_000_000, 50), Some(tick));
+    }
+
+    #[test]
+    fn test_feedback_pulses() {
+        let feedback: HapticFeedback = serde_json::from_str(r#"{"pattern": "thunk", "strength": 0.5}"#).unwrap();
+        let pulses = feedback.pulses();
+        assert_eq!(pulses.len(), 2);
+        assert!(pulses.iter().all(|p| p.amplitude == 2048 && p.period == 6000));
+
+        let tick = HapticFeedback { pattern: HapticPattern::Tick, side: Some(HapticSide::Right), strength: 1.0 };
+        assert_eq!(tick.pulses(), vec![HapticPulse::confirm(HapticSide::Right)]);
+    }
+}
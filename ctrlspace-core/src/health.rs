@@ -0,0 +1,532 @@
+//! Long-term hardware wear per controller: where the stick and triggers rest
+//! when nobody touches them (tracked day by day, so slow drift and creep show
+//! up as a trend), trackpad touch heatmaps that reveal dead spots, and how
+//! many haptic writes failed. A `HealthTracker` watches the live stream and
+//! is merged into the persisted `HealthStore` now and then.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::input_parser::{ControllerInput, TrackpadData};
+
+/// File in the app config dir holding the history
+pub const HEALTH_FILE: &str = "health.json";
+
+/// Days of rest samples kept per device
+pub const MAX_HISTORY_DAYS: usize = 365;
+
+/// Heatmap resolution (cells per side)
+pub const HEATMAP_GRID: usize = 16;
+
+/// Largest frame-to-frame change (per axis) of a stick nobody is touching
+const STILL_DELTA: i32 = 256;
+
+/// How long a control must sit still before its position counts as rest
+const SETTLE_US: u64 = 500_000;
+
+/// Raw stick positions further out than this are being held, not resting
+const STICK_REST_LIMIT: i32 = 8000;
+
+/// Raw trigger values above this are being pressed, not resting
+const TRIGGER_REST_LIMIT: u8 = 60;
+
+/// Touched frames a pad needs before dead spots are looked for
+const MIN_HEATMAP_TOUCHES: u64 = 50_000;
+
+/// Host time between automatic saves of the running session
+pub const FLUSH_INTERVAL_US: u64 = 5 * 60 * 1_000_000;
+
+// Report warning levels
+const STICK_WARN: f32 = 2500.0;
+const STICK_CHANGE_WARN: f32 = 1000.0;
+const TRIGGER_WARN: f32 = 25.0;
+const HAPTIC_FAILURE_WARN: f32 = 0.05;
+const MIN_HAPTICS_FOR_RATE: u64 = 20;
+
+/// Mean resting stick position over one day, raw (before anti-drift)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StickRest {
+    /// Days since the Unix epoch
+    pub day: u64,
+    pub x: f32,
+    pub y: f32,
+    pub samples: u64,
+}
+
+/// Mean resting trigger values over one day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TriggerRest {
+    pub day: u64,
+    pub left: f32,
+    pub right: f32,
+    pub samples: u64,
+}
+
+/// Touch counts on a `HEATMAP_GRID` x `HEATMAP_GRID` grid, row-major from the bottom left
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PadHeatmap {
+    pub cells: Vec<u64>,
+}
+
+impl Default for PadHeatmap {
+    fn default() -> Self {
+        Self { cells: vec![0; HEATMAP_GRID * HEATMAP_GRID] }
+    }
+}
+
+fn cell_index(v: i16) -> usize {
+    ((v as i32 + 32768) as usize * HEATMAP_GRID) >> 16
+}
+
+// Cell center in pad coordinates, -1.0..1.0
+fn cell_center(i: usize) -> f32 {
+    (i as f32 + 0.5) / HEATMAP_GRID as f32 * 2.0 - 1.0
+}
+
+impl PadHeatmap {
+    pub fn record(&mut self, pad: &TrackpadData) {
+        self.cells[cell_index(pad.y) * HEATMAP_GRID + cell_index(pad.x)] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.cells.iter().sum()
+    }
+
+    fn merge(&mut self, other: &PadHeatmap) {
+        if self.cells.len() != other.cells.len() {
+            self.cells = vec![0; HEATMAP_GRID * HEATMAP_GRID];
+        }
+        for (cell, count) in self.cells.iter_mut().zip(&other.cells) {
+            *cell += count;
+        }
+    }
+
+    /// Never-touched cells inside the pad circle whose four neighbours are
+    /// all well used - a finger sweeping across should have hit them too
+    pub fn dead_spots(&self) -> Vec<(i16, i16)> {
+        let total = self.total();
+        if total < MIN_HEATMAP_TOUCHES || self.cells.len() != HEATMAP_GRID * HEATMAP_GRID {
+            return Vec::new();
+        }
+        let inside = |col: usize, row: usize| cell_center(col).hypot(cell_center(row)) <= 0.8;
+        let inside_count = (0..HEATMAP_GRID * HEATMAP_GRID)
+            .filter(|i| inside(i % HEATMAP_GRID, i / HEATMAP_GRID))
+            .count() as u64;
+        let well_used = (total / inside_count / 10).max(1);
+        let count = |col: usize, row: usize| self.cells[row * HEATMAP_GRID + col];
+
+        let mut spots = Vec::new();
+        for row in 1..HEATMAP_GRID - 1 {
+            for col in 1..HEATMAP_GRID - 1 {
+                let neighbours = [count(col - 1, row), count(col + 1, row), count(col, row - 1), count(col, row + 1)];
+                if inside(col, row) && count(col, row) == 0 && neighbours.iter().all(|&n| n >= well_used) {
+                    let to_pad = |i: usize| (cell_center(i) * i16::MAX as f32) as i16;
+                    spots.push((to_pad(col), to_pad(row)));
+                }
+            }
+        }
+        spots
+    }
+}
+
+/// Everything recorded for one controller
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceHealth {
+    #[serde(default)]
+    pub stick_rest: Vec<StickRest>,
+    #[serde(default)]
+    pub trigger_rest: Vec<TriggerRest>,
+    #[serde(default)]
+    pub left_pad: PadHeatmap,
+    #[serde(default)]
+    pub right_pad: PadHeatmap,
+    #[serde(default)]
+    pub haptic_writes: u64,
+    #[serde(default)]
+    pub haptic_failures: u64,
+}
+
+// Fold a weighted mean into `history` for `day`, dropping the oldest days past the limit
+fn add_day<T>(history: &mut Vec<T>, day: u64, samples: u64, day_of: fn(&T) -> u64, new: impl FnOnce() -> T, merge: impl FnOnce(&mut T)) {
+    if samples == 0 {
+        return;
+    }
+    match history.last_mut() {
+        Some(last) if day_of(last) == day => merge(last),
+        _ => history.push(new()),
+    }
+    if history.len() > MAX_HISTORY_DAYS {
+        history.drain(..history.len() - MAX_HISTORY_DAYS);
+    }
+}
+
+fn weighted(old: f32, old_n: u64, new: f64, new_n: u64) -> f32 {
+    ((old as f64 * old_n as f64 + new * new_n as f64) / (old_n + new_n) as f64) as f32
+}
+
+/// Per-device health history, persisted as `HEALTH_FILE`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthStore {
+    devices: BTreeMap<String, DeviceHealth>,
+}
+
+impl HealthStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from disk; a missing file is an empty history
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid health history {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to serialize health history: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, device_id: &str) -> Option<&DeviceHealth> {
+        self.devices.get(device_id)
+    }
+
+    pub fn report(&self, device_id: &str) -> Option<HealthReport> {
+        self.get(device_id).map(|health| HealthReport::new(device_id, health))
+    }
+}
+
+// Settle timer for one control
+#[derive(Debug, Clone, Default)]
+struct RestDetector {
+    last: Option<(i32, i32)>,
+    still_since_us: Option<u64>,
+}
+
+impl RestDetector {
+    // True once the value has stayed within `delta` for the settle time
+    fn update(&mut self, value: (i32, i32), delta: i32, resting: bool, at_us: u64) -> bool {
+        let still = self.last.map_or(false, |(lx, ly)| {
+            (value.0 - lx).abs() <= delta && (value.1 - ly).abs() <= delta
+        });
+        self.last = Some(value);
+        if still && resting {
+            let since = *self.still_since_us.get_or_insert(at_us);
+            at_us.saturating_sub(since) >= SETTLE_US
+        } else {
+            self.still_since_us = None;
+            false
+        }
+    }
+}
+
+/// Watches the stream of one connected controller
+#[derive(Debug, Clone)]
+pub struct HealthTracker {
+    device_id: String,
+    stick: RestDetector,
+    triggers: RestDetector,
+    // Sums since the last flush
+    stick_sum: (f64, f64, u64),
+    trigger_sum: (f64, f64, u64),
+    left_pad: PadHeatmap,
+    right_pad: PadHeatmap,
+    // Haptic write totals already counted
+    haptics_seen: (u64, u64),
+    last_flush_us: Option<u64>,
+}
+
+impl HealthTracker {
+    /// `haptics` is the driver's (writes, failures) so far, counted as already seen
+    pub fn new(device_id: &str, haptics: (u64, u64)) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            stick: RestDetector::default(),
+            triggers: RestDetector::default(),
+            stick_sum: (0.0, 0.0, 0),
+            trigger_sum: (0.0, 0.0, 0),
+            left_pad: PadHeatmap::default(),
+            right_pad: PadHeatmap::default(),
+            haptics_seen: haptics,
+            last_flush_us: None,
+        }
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Record one frame. `drift_offset` is what anti-drift already subtracted,
+    /// so the raw position is tracked. True when a flush is due.
+    pub fn update(&mut self, input: &ControllerInput, drift_offset: (i16, i16)) -> bool {
+        let at = input.host_timestamp_us;
+        let x = input.stick.x as i32 + drift_offset.0 as i32;
+        let y = input.stick.y as i32 + drift_offset.1 as i32;
+        let near = x.abs() <= STICK_REST_LIMIT && y.abs() <= STICK_REST_LIMIT;
+        if self.stick.update((x, y), STILL_DELTA, near && !input.buttons.stick_click, at) {
+            self.stick_sum.0 += x as f64;
+            self.stick_sum.1 += y as f64;
+            self.stick_sum.2 += 1;
+        }
+
+        let (left, right) = (input.triggers.left, input.triggers.right);
+        let released = left <= TRIGGER_REST_LIMIT && right <= TRIGGER_REST_LIMIT && !input.buttons.lt && !input.buttons.rt;
+        if self.triggers.update((left as i32, right as i32), 1, released, at) {
+            self.trigger_sum.0 += left as f64;
+            self.trigger_sum.1 += right as f64;
+            self.trigger_sum.2 += 1;
+        }
+
+        if input.left_trackpad.active {
+            self.left_pad.record(&input.left_trackpad);
+        }
+        if input.right_trackpad.active {
+            self.right_pad.record(&input.right_trackpad);
+        }
+
+        let last = *self.last_flush_us.get_or_insert(at);
+        at.saturating_sub(last) >= FLUSH_INTERVAL_US
+    }
+
+    /// Move what was recorded into `store` under `day` (days since the epoch).
+    /// `haptics` is the driver's current (writes, failures).
+    pub fn flush(&mut self, store: &mut HealthStore, day: u64, haptics: (u64, u64)) {
+        let health = store.devices.entry(self.device_id.clone()).or_default();
+
+        let (sx, sy, n) = self.stick_sum;
+        add_day(
+            &mut health.stick_rest,
+            day,
+            n,
+            |s| s.day,
+            || StickRest { day, x: (sx / n as f64) as f32, y: (sy / n as f64) as f32, samples: n },
+            |s| {
+                s.x = weighted(s.x, s.samples, sx / n as f64, n);
+                s.y = weighted(s.y, s.samples, sy / n as f64, n);
+                s.samples += n;
+            },
+        );
+        let (tl, tr, n) = self.trigger_sum;
+        add_day(
+            &mut health.trigger_rest,
+            day,
+            n,
+            |t| t.day,
+            || TriggerRest { day, left: (tl / n as f64) as f32, right: (tr / n as f64) as f32, samples: n },
+            |t| {
+                t.left = weighted(t.left, t.samples, tl / n as f64, n);
+                t.right = weighted(t.right, t.samples, tr / n as f64, n);
+                t.samples += n;
+            },
+        );
+        health.left_pad.merge(&self.left_pad);
+        health.right_pad.merge(&self.right_pad);
+
+        // A reconnect restarts the driver's counters
+        if haptics.0 < self.haptics_seen.0 {
+            self.haptics_seen = (0, 0);
+        }
+        health.haptic_writes += haptics.0 - self.haptics_seen.0;
+        health.haptic_failures += haptics.1.saturating_sub(self.haptics_seen.1);
+        self.haptics_seen = haptics;
+
+        self.stick_sum = (0.0, 0.0, 0);
+        self.trigger_sum = (0.0, 0.0, 0);
+        self.left_pad = PadHeatmap::default();
+        self.right_pad = PadHeatmap::default();
+        self.last_flush_us = None;
+    }
+}
+
+/// Stick rest position at the start and end of the history
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StickTrend {
+    pub first: (i16, i16),
+    pub latest: (i16, i16),
+    /// Distance of the latest rest position from center
+    pub magnitude: u16,
+    /// Change in that distance per 30 days; `None` with under a day of history
+    pub change_per_30_days: Option<f32>,
+}
+
+/// Trigger rest values at the start and end of the history
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TriggerTrend {
+    pub left_first: f32,
+    pub left_latest: f32,
+    pub right_first: f32,
+    pub right_latest: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PadSide {
+    Left,
+    Right,
+}
+
+/// A pad spot nobody's finger registers on, at its center in pad coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DeadSpot {
+    pub pad: PadSide,
+    pub x: i16,
+    pub y: i16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub device_id: String,
+    /// Days with any rest samples
+    pub days_tracked: usize,
+    pub stick: Option<StickTrend>,
+    pub triggers: Option<TriggerTrend>,
+    pub dead_spots: Vec<DeadSpot>,
+    pub haptic_writes: u64,
+    pub haptic_failures: u64,
+    /// Plain-language findings; empty when nothing looks worn
+    pub warnings: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn new(device_id: &str, health: &DeviceHealth) -> Self {
+        let mut warnings = Vec::new();
+
+        let stick = match (health.stick_rest.first(), health.stick_rest.last()) {
+            (Some(first), Some(latest)) => {
+                let magnitude = latest.x.hypot(latest.y);
+                let days = latest.day - first.day;
+                let change = (days > 0).then(|| (magnitude - first.x.hypot(first.y)) / days as f32 * 30.0);
+                if magnitude >= STICK_WARN {
+                    warnings.push(format!("Stick rests {:.0} from center", magnitude));
+                }
+                if magnitude - first.x.hypot(first.y) >= STICK_CHANGE_WARN {
+                    warnings.push("Stick drift is getting worse".to_string());
+                }
+                let round = |x: f32, y: f32| (x.round() as i16, y.round() as i16);
+                Some(StickTrend {
+                    first: round(first.x, first.y),
+                    latest: round(latest.x, latest.y),
+                    magnitude: magnitude.round() as u16,
+                    change_per_30_days: change,
+                })
+            }
+            _ => None,
+        };
+
+        let triggers = match (health.trigger_rest.first(), health.trigger_rest.last()) {
+            (Some(first), Some(latest)) => {
+                for (name, value) in [("Left", latest.left), ("Right", latest.right)] {
+                    if value >= TRIGGER_WARN {
+                        warnings.push(format!("{} trigger rests at {:.0} instead of 0", name, value));
+                    }
+                }
+                Some(TriggerTrend {
+                    left_first: first.left,
+                    left_latest: latest.left,
+                    right_first: first.right,
+                    right_latest: latest.right,
+                })
+            }
+            _ => None,
+        };
+
+        let mut dead_spots = Vec::new();
+        for (pad, heatmap) in [(PadSide::Left, &health.left_pad), (PadSide::Right, &health.right_pad)] {
+            dead_spots.extend(heatmap.dead_spots().into_iter().map(|(x, y)| DeadSpot { pad, x, y }));
+        }
+        if !dead_spots.is_empty() {
+            warnings.push(format!("{} trackpad dead spot(s) found", dead_spots.len()));
+        }
+
+        if health.haptic_writes >= MIN_HAPTICS_FOR_RATE
+            && health.haptic_failures as f32 / health.haptic_writes as f32 >= HAPTIC_FAILURE_WARN
+        {
+            warnings.push(format!(
+                "{} of {} haptic commands failed",
+                health.haptic_failures, health.haptic_writes
+            ));
+        }
+
+        let mut days: Vec<u64> = health.stick_rest.iter().map(|s| s.day).collect();
+        days.extend(health.trigger_rest.iter().map(|t| t.day));
+        days.sort_unstable();
+        days.dedup();
+
+        Self {
+            device_id: device_id.to_string(),
+            days_tracked: days.len(),
+            stick,
+            triggers,
+            dead_spots,
+            haptic_writes: health.haptic_writes,
+            haptic_failures: health.haptic_failures,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracks_wear_across_days() {
+        let mut store = HealthStore::new();
+        let mut tracker = HealthTracker::new("sc-1", (10, 0));
+
+        // Day 1: stick rests near center (anti-drift had already removed 100 of it)
+        for ms in 0..2000u64 {
+            let mut input = ControllerInput::default();
+            input.stick.x = 100;
+            input.host_timestamp_us = ms * 1000;
+            tracker.update(&input, (100, 0));
+        }
+        tracker.flush(&mut store, 100, (30, 0));
+
+        // Day 40: stick rests far out, left trigger creeps, haptics failing,
+        // and the right pad is used everywhere but one spot
+        for ms in 0..2000u64 {
+            let mut input = ControllerInput::default();
+            input.stick.x = 3000;
+            input.triggers.left = 30;
+            input.host_timestamp_us = ms * 1000;
+            tracker.update(&input, (0, 0));
+        }
+        let dead = (cell_index(8000), cell_index(-4000));
+        for pass in 0..300 {
+            for row in 0..HEATMAP_GRID {
+                for col in 0..HEATMAP_GRID {
+                    if (col, row) != dead {
+                        let mut input = ControllerInput::default();
+                        input.right_trackpad.active = true;
+                        input.right_trackpad.x = (cell_center(col) * 32767.0) as i16;
+                        input.right_trackpad.y = (cell_center(row) * 32767.0) as i16;
+                        input.host_timestamp_us = 2_000_000 + pass;
+                        tracker.update(&input, (0, 0));
+                    }
+                }
+            }
+        }
+        tracker.flush(&mut store, 140, (130, 20));
+
+        let report = store.report("sc-1").unwrap();
+        assert_eq!(report.days_tracked, 2);
+        let stick = report.stick.unwrap();
+        assert_eq!(stick.first, (200, 0));
+        assert_eq!(stick.latest, (3000, 0));
+        assert!(stick.change_per_30_days.unwrap() > 2000.0);
+        assert_eq!(report.triggers.unwrap().left_latest, 30.0);
+        assert_eq!(report.dead_spots.len(), 1);
+        assert_eq!(report.dead_spots[0].pad, PadSide::Right);
+        assert_eq!((report.haptic_writes, report.haptic_failures), (120, 20));
+        assert_eq!(report.warnings.len(), 5, "{:?}", report.warnings);
+        assert!(store.report("sc-2").is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! External hook process: a user-chosen program gets input events as JSON
+//! lines on stdin and answers with action commands as JSON lines on stdout,
+//! so behavior can be scripted in any language without the plugin system.
+//!
+//! To the child, one object per line:
+//! - `{"type": "frame", "input": {...}}` for every input frame
+//! - `{"type": "binding", "event": {...}}` for every `binding-action`
+//!
+//! From the child, one object per line:
+//! - `{"type": "press", "action": {...}}` / `{"type": "release", "action": {...}}`
+//! - `{"type": "log", "message": "..."}`
+//!
+//! Writes go through a bounded queue on their own thread, so a slow or stuck
+//! child loses events instead of stalling the input thread.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use crate::input_parser::ControllerInput;
+use crate::layers::BindingEvent;
+use crate::profiles::Action;
+
+/// Event name for actions the hook process asked for
+pub const HOOK_ACTION_EVENT: &str = "hook-action";
+
+/// Event name for the hook process exiting on its own
+pub const HOOK_EXITED_EVENT: &str = "hook-exited";
+
+/// Lines waiting for the child before new ones are dropped
+const QUEUE_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookConfig {
+    /// Executable to run (looked up on PATH)
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Send every input frame, not just binding events
+    #[serde(default = "default_frames")]
+    pub frames: bool,
+}
+
+fn default_frames() -> bool {
+    true
+}
+
+/// A line for the child
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookMessage<'a> {
+    Frame { input: &'a ControllerInput },
+    Binding { event: &'a BindingEvent },
+}
+
+/// A line from the child
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookCommand {
+    Press { action: Action },
+    Release { action: Action },
+    Log { message: String },
+}
+
+/// What the reader thread reports
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookReply {
+    Command(HookCommand),
+    /// A line that isn't a valid command
+    Invalid { line: String, error: String },
+    /// The child closed stdout, usually because it exited
+    Exited,
+}
+
+/// Parse one line from the child
+pub fn parse_reply(line: &str) -> HookReply {
+    let parsed = serde_json::from_str::<HookCommand>(line).map_err(|e| e.to_string()).and_then(|command| {
+        match &command {
+            HookCommand::Press { action } | HookCommand::Release { action } => action.validate()?,
+            HookCommand::Log { .. } => {}
+        }
+        Ok(command)
+    });
+    match parsed {
+        Ok(command) => HookReply::Command(command),
+        Err(error) => HookReply::Invalid {
+            line: line.to_string(),
+            error,
+        },
+    }
+}
+
+/// A running hook process
+pub struct HookProcess {
+    config: HookConfig,
+    child: Child,
+    lines: SyncSender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl HookProcess {
+    /// Start the program; `on_reply` runs on a background thread for every
+    /// line it prints and once more when it exits
+    pub fn spawn<F>(config: HookConfig, on_reply: F) -> Result<Self, String>
+    where
+        F: Fn(HookReply) + Send + 'static,
+    {
+        let mut child = Command::new(&config.program)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start hook process {}: {}", config.program, e))?;
+        let mut stdin = child.stdin.take().ok_or_else(|| "Hook process has no stdin".to_string())?;
+        let stdout = child.stdout.take().ok_or_else(|| "Hook process has no stdout".to_string())?;
+
+        let (lines, queue) = mpsc::sync_channel::<String>(QUEUE_DEPTH);
+        thread::spawn(move || {
+            for line in queue {
+                if writeln!(stdin, "{}", line).and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) if line.trim().is_empty() => {}
+                    Ok(line) => on_reply(parse_reply(&line)),
+                    Err(_) => break,
+                }
+            }
+            on_reply(HookReply::Exited);
+        });
+
+        Ok(Self {
+            config,
+            child,
+            lines,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    pub fn config(&self) -> &HookConfig {
+        &self.config
+    }
+
+    /// Lines dropped because the child wasn't reading
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queue a message; false once the child has gone away
+    pub fn send(&self, message: &HookMessage) -> bool {
+        if matches!(message, HookMessage::Frame { .. }) && !self.config.frames {
+            return true;
+        }
+        let line = match serde_json::to_string(message) {
+            Ok(line) => line,
+            Err(_) => return true,
+        };
+        match self.lines.try_send(line) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Kill the child (dropping the handle does the same)
+    pub fn stop(self) {}
+}
+
+impl Drop for HookProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_format_and_reply_parsing() {
+        let input = ControllerInput::default();
+        let line = serde_json::to_string(&HookMessage::Frame { input: &input }).unwrap();
+        assert!(line.starts_with(r#"{"type":"frame","input":{"#));
+
+        let press = parse_reply(r#"{"type": "press", "action": {"type": "key", "key": "w"}}"#);
+        assert_eq!(
+            press,
+            HookReply::Command(HookCommand::Press {
+                action: Action::Key { key: "w".to_string() }
+            })
+        );
+        assert!(matches!(parse_reply("not json"), HookReply::Invalid { .. }));
+        let slow = r#"{"type": "press", "action": {"type": "slow_mode", "factor": 5.0}}"#;
+        assert!(matches!(parse_reply(slow), HookReply::Invalid { .. }));
+    }
+}
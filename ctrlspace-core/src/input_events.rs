@@ -0,0 +1,223 @@
+//! Discrete input events from consecutive [`ControllerInput`] snapshots:
+//! button presses, holds and releases, pad touches and lifts, and triggers
+//! crossing a threshold. Consumers that only care about edges (the frontend,
+//! mapping code) get them from here instead of keeping their own copy of the
+//! previous frame.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::ButtonId;
+
+/// Event name for each frame's input events (a non-empty array)
+pub const INPUT_EVENTS_EVENT: &str = "input-events";
+
+/// How long a button stays down before it counts as held
+pub const DEFAULT_HOLD_MS: u32 = 500;
+
+/// Trigger travel (0-255) that counts as pulled
+pub const DEFAULT_TRIGGER_THRESHOLD: u8 = 200;
+
+/// How far a pulled trigger must drop below the threshold to count as
+/// released, so a trigger resting on it doesn't chatter
+const TRIGGER_HYSTERESIS: u8 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputEvent {
+    ButtonPressed { button: ButtonId },
+    /// Once per press, after the button has been down for the hold time
+    ButtonHeld { button: ButtonId },
+    ButtonReleased { button: ButtonId, held_ms: u32 },
+    TrackpadTouchStart { pad: Pad, x: i16, y: i16 },
+    TrackpadTouchEnd { pad: Pad },
+    /// `pulled` is true going up past the threshold, false dropping back
+    TriggerCrossedThreshold { trigger: Trigger, pulled: bool, value: u8 },
+}
+
+/// Thresholds for [`InputEventDetector`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputEventConfig {
+    #[serde(default = "default_hold_ms")]
+    pub hold_ms: u32,
+    #[serde(default = "default_trigger_threshold")]
+    pub trigger_threshold: u8,
+}
+
+fn default_hold_ms() -> u32 {
+    DEFAULT_HOLD_MS
+}
+
+fn default_trigger_threshold() -> u8 {
+    DEFAULT_TRIGGER_THRESHOLD
+}
+
+impl Default for InputEventConfig {
+    fn default() -> Self {
+        Self { hold_ms: DEFAULT_HOLD_MS, trigger_threshold: DEFAULT_TRIGGER_THRESHOLD }
+    }
+}
+
+impl InputEventConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hold_ms == 0 {
+            return Err("Hold time must be at least 1 ms".to_string());
+        }
+        if self.trigger_threshold <= TRIGGER_HYSTERESIS {
+            return Err(format!("Trigger threshold must be above {}, got {}", TRIGGER_HYSTERESIS, self.trigger_threshold));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    since: Instant,
+    held: bool,
+}
+
+/// Diffs each frame against the last one. Everything starts released, so a
+/// button already down on the first frame is reported as pressed.
+#[derive(Debug, Clone, Default)]
+pub struct InputEventDetector {
+    config: InputEventConfig,
+    pressed: BTreeMap<ButtonId, Press>,
+    touching: [bool; 2],
+    pulled: [bool; 2],
+}
+
+impl InputEventDetector {
+    pub fn new(config: InputEventConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> InputEventConfig {
+        self.config
+    }
+
+    /// New thresholds, applied from the next frame
+    pub fn set_config(&mut self, config: InputEventConfig) {
+        self.config = config;
+    }
+
+    /// Forget the last frame (the controller went away) without reporting releases
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Events for one frame, in a fixed order: buttons, pads, then triggers
+    pub fn update(&mut self, input: &ControllerInput, now: Instant) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        let hold = Duration::from_millis(self.config.hold_ms as u64);
+
+        for button in ButtonId::ALL {
+            let down = button.is_pressed(&input.buttons);
+            match (self.pressed.get_mut(&button), down) {
+                (None, true) => {
+                    self.pressed.insert(button, Press { since: now, held: false });
+                    events.push(InputEvent::ButtonPressed { button });
+                }
+                (Some(press), true) => {
+                    if !press.held && now.duration_since(press.since) >= hold {
+                        press.held = true;
+                        events.push(InputEvent::ButtonHeld { button });
+                    }
+                }
+                (Some(press), false) => {
+                    let held_ms = now.duration_since(press.since).as_millis().min(u32::MAX as u128) as u32;
+                    self.pressed.remove(&button);
+                    events.push(InputEvent::ButtonReleased { button, held_ms });
+                }
+                (None, false) => {}
+            }
+        }
+
+        for (i, (pad, data)) in [(Pad::Left, &input.left_trackpad), (Pad::Right, &input.right_trackpad)].into_iter().enumerate() {
+            if let Some(event) = touch_edge(pad, data, &mut self.touching[i]) {
+                events.push(event);
+            }
+        }
+
+        let threshold = self.config.trigger_threshold;
+        for (i, (trigger, value)) in [(Trigger::Left, input.triggers.left), (Trigger::Right, input.triggers.right)].into_iter().enumerate() {
+            let pulled = if self.pulled[i] { value >= threshold.saturating_sub(TRIGGER_HYSTERESIS) } else { value >= threshold };
+            if pulled != self.pulled[i] {
+                self.pulled[i] = pulled;
+                events.push(InputEvent::TriggerCrossedThreshold { trigger, pulled, value });
+            }
+        }
+
+        events
+    }
+}
+
+fn touch_edge(pad: Pad, data: &TrackpadData, touching: &mut bool) -> Option<InputEvent> {
+    if data.active == *touching {
+        return None;
+    }
+    *touching = data.active;
+    Some(if data.active {
+        InputEvent::TrackpadTouchStart { pad, x: data.x, y: data.y }
+    } else {
+        InputEvent::TrackpadTouchEnd { pad }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_edges() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut detector = InputEventDetector::new(InputEventConfig::default());
+        let mut input = ControllerInput::default();
+        assert!(detector.update(&input, at(0)).is_empty());
+
+        input.buttons.a = true;
+        input.right_trackpad = TrackpadData { x: 100, y: -200, active: true };
+        assert_eq!(
+            detector.update(&input, at(10)),
+            vec![
+                InputEvent::ButtonPressed { button: ButtonId::A },
+                InputEvent::TrackpadTouchStart { pad: Pad::Right, x: 100, y: -200 },
+            ]
+        );
+        // Held fires once, however long the button stays down
+        assert!(detector.update(&input, at(400)).is_empty());
+        assert_eq!(detector.update(&input, at(510)), vec![InputEvent::ButtonHeld { button: ButtonId::A }]);
+        assert!(detector.update(&input, at(900)).is_empty());
+
+        input.buttons.a = false;
+        input.right_trackpad.active = false;
+        assert_eq!(
+            detector.update(&input, at(1010)),
+            vec![
+                InputEvent::ButtonReleased { button: ButtonId::A, held_ms: 1000 },
+                InputEvent::TrackpadTouchEnd { pad: Pad::Right },
+            ]
+        );
+
+        // Triggers cross on the way up, and only well below the threshold on the way down
+        let crossed = |trigger, pulled, value| vec![InputEvent::TriggerCrossedThreshold { trigger, pulled, value }];
+        input.triggers.left = 210;
+        assert_eq!(detector.update(&input, at(1020)), crossed(Trigger::Left, true, 210));
+        input.triggers.left = 195;
+        assert!(detector.update(&input, at(1030)).is_empty());
+        input.triggers.left = 100;
+        assert_eq!(detector.update(&input, at(1040)), crossed(Trigger::Left, false, 100));
+
+        assert!(InputEventConfig { hold_ms: 0, ..InputEventConfig::default() }.validate().is_err());
+        assert!(InputEventConfig { trigger_threshold: 10, ..InputEventConfig::default() }.validate().is_err());
+    }
+}
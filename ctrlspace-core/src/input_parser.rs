@@ -0,0 +1,831 @@
+use serde::{Deserialize, Serialize};
+use crate::battery::{BatteryStatus, BATTERY_STATUS_PACKET};
+use crate::frame::*;
+use crate::layout::ReportLayout;
+use crate::steam_deck::{parse_deck_state, DECK_STATE_PACKET};
+use std::fmt;
+
+/// Button bit flags for Steam Controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Buttons {
+    pub a: bool,
+    pub b: bool,
+    pub x: bool,
+    pub y: bool,
+    pub lb: bool,         // Left bumper
+    pub rb: bool,         // Right bumper
+    pub lt: bool,         // Left trigger click
+    pub rt: bool,         // Right trigger click
+    pub lgrip: bool,      // Left grip
+    pub rgrip: bool,      // Right grip
+    pub start: bool,
+    pub select: bool,     // Back button
+    pub steam: bool,      // Steam button
+    pub lpad_click: bool, // Left trackpad click
+    pub rpad_click: bool, // Right trackpad click
+    pub stick_click: bool,
+}
+
+impl Default for Buttons {
+    fn default() -> Self {
+        Self {
+            a: false,
+            b: false,
+            x: false,
+            y: false,
+            lb: false,
+            rb: false,
+            lt: false,
+            rt: false,
+            lgrip: false,
+            rgrip: false,
+            start: false,
+            select: false,
+            steam: false,
+            lpad_click: false,
+            rpad_click: false,
+            stick_click: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TrackpadData {
+    pub x: i16,
+    pub y: i16,
+    pub active: bool,
+}
+
+impl Default for TrackpadData {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            active: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StickData {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl Default for StickData {
+    fn default() -> Self {
+        Self { x: 0, y: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TriggersData {
+    pub left: u8,
+    pub right: u8,
+}
+
+impl Default for TriggersData {
+    fn default() -> Self {
+        Self { left: 0, right: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GyroData {
+    pub pitch: i16,
+    pub yaw: i16,
+    pub roll: i16,
+}
+
+impl Default for GyroData {
+    fn default() -> Self {
+        Self {
+            pitch: 0,
+            yaw: 0,
+            roll: 0,
+        }
+    }
+}
+
+/// Accelerometer and the controller's own sensor-fusion orientation. Zero
+/// when the firmware isn't sending IMU data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImuData {
+    pub accel_x: i16,
+    pub accel_y: i16,
+    pub accel_z: i16,
+    /// Orientation quaternion, each component scaled to ±32767
+    pub quat_w: i16,
+    pub quat_x: i16,
+    pub quat_y: i16,
+    pub quat_z: i16,
+}
+
+/// Orientation in degrees: the quaternion's Z-Y-X (yaw, pitch, roll) angles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Orientation {
+    /// About the Y axis, -90 to 90
+    pub pitch: f32,
+    /// About the X axis, -180 to 180
+    pub roll: f32,
+    /// About the Z axis, -180 to 180; relative to wherever the controller
+    /// pointed when its sensor fusion started
+    pub yaw: f32,
+}
+
+impl ImuData {
+    /// The quaternion as angles, or `None` while it is all zeros
+    pub fn orientation(&self) -> Option<Orientation> {
+        let q = [self.quat_w, self.quat_x, self.quat_y, self.quat_z].map(|c| c as f32 / i16::MAX as f32);
+        let norm = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+        if norm < 1e-3 {
+            return None;
+        }
+        let [w, x, y, z] = q.map(|c| c / norm);
+        Some(Orientation {
+            pitch: (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin().to_degrees(),
+            roll: (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y)).to_degrees(),
+            yaw: (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z)).to_degrees(),
+        })
+    }
+}
+
+/// Complete input state from Steam Controller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControllerInput {
+    pub buttons: ButtonState,
+    pub left_trackpad: TrackpadData,
+    pub right_trackpad: TrackpadData,
+    pub stick: StickData,
+    /// Steam Deck only; centered on a Steam Controller, which has one stick
+    #[serde(default)]
+    pub right_stick: StickData,
+    pub triggers: TriggersData,
+    pub gyro: GyroData,
+    #[serde(default)]
+    pub imu: ImuData,
+    /// Computed from `imu`'s quaternion for display; `None` without IMU data
+    #[serde(default)]
+    pub orientation: Option<Orientation>,
+    /// Controller clock (wraps at 32 bits)
+    pub timestamp: u32,
+    /// `timestamp` with its wraps counted since the parser started (or was
+    /// reset), so deltas stay correct across a rollover
+    #[serde(default)]
+    pub extended_timestamp: u64,
+    /// Host monotonic time the report was read, in µs (see [`crate::clock`])
+    pub host_timestamp_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ButtonState {
+    pub a: bool,
+    pub b: bool,
+    pub x: bool,
+    pub y: bool,
+    pub lb: bool,
+    pub rb: bool,
+    pub lt: bool,
+    pub rt: bool,
+    pub lgrip: bool,
+    pub rgrip: bool,
+    pub start: bool,
+    pub select: bool,
+    pub steam: bool,
+    pub lpad_click: bool,
+    pub rpad_click: bool,
+    pub stick_click: bool,
+}
+
+impl Default for ControllerInput {
+    fn default() -> Self {
+        Self {
+            buttons: ButtonState::default(),
+            left_trackpad: TrackpadData::default(),
+            right_trackpad: TrackpadData::default(),
+            stick: StickData::default(),
+            right_stick: StickData::default(),
+            triggers: TriggersData::default(),
+            gyro: GyroData::default(),
+            imu: ImuData::default(),
+            orientation: None,
+            timestamp: 0,
+            extended_timestamp: 0,
+            host_timestamp_us: 0,
+        }
+    }
+}
+
+impl Default for ButtonState {
+    fn default() -> Self {
+        Self {
+            a: false,
+            b: false,
+            x: false,
+            y: false,
+            lb: false,
+            rb: false,
+            lt: false,
+            rt: false,
+            lgrip: false,
+            rgrip: false,
+            start: false,
+            select: false,
+            steam: false,
+            lpad_click: false,
+            rpad_click: false,
+            stick_click: false,
+        }
+    }
+}
+
+impl fmt::Display for ControllerInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Input[buttons={:?}, lpad=({},{}), rpad=({},{}), stick=({},{}), triggers=({},{})]",
+            self.buttons,
+            self.left_trackpad.x,
+            self.left_trackpad.y,
+            self.right_trackpad.x,
+            self.right_trackpad.y,
+            self.stick.x,
+            self.stick.y,
+            self.triggers.left,
+            self.triggers.right
+        )
+    }
+}
+
+/// Report ID every controller packet starts with
+pub const CONTROLLER_REPORT_ID: u8 = 0x01;
+
+/// Packet type (byte 2) of a controller state report
+pub const INPUT_PACKET: u8 = 0x01;
+
+/// Packet type (byte 2) of a wireless connection status report
+pub const WIRELESS_STATUS_PACKET: u8 = 0x03;
+
+/// Wireless connection change reported by the dongle (byte 4 of a status packet)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WirelessStatus {
+    Disconnected,
+    Connected,
+    Pairing,
+    Unknown(u8),
+}
+
+impl WirelessStatus {
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0x01 => WirelessStatus::Disconnected,
+            0x02 => WirelessStatus::Connected,
+            0x03 => WirelessStatus::Pairing,
+            other => WirelessStatus::Unknown(other),
+        }
+    }
+}
+
+/// One packet from the controller, by packet type
+#[derive(Debug, Clone)]
+pub enum Packet {
+    InputFrame(ControllerInput),
+    StatusEvent(WirelessStatus),
+    BatteryEvent(BatteryStatus),
+    /// Another report ID or packet type; kept for diagnostics, not an error
+    Unknown { raw: Vec<u8> },
+}
+
+/// Parse any packet from the controller. Stateless, like [`parse_input_report`].
+pub fn parse_packet(data: &[u8]) -> Result<Packet, String> {
+    InputParser::new().parse_packet(data)
+}
+
+/// Parse HID report from Steam Controller
+///
+/// Empirically determined format based on actual USB wired controller data:
+/// - Byte 0: Report ID (0x01)
+/// - Byte 1: Sequence number
+/// - Byte 2: Packet type (0x01 input, 0x03 wireless status, 0x04 battery,
+///   0x09 Steam Deck state, see [`crate::steam_deck`])
+/// - Byte 3: Payload length
+/// - Bytes 4-7: Timestamp (32-bit LE)
+/// - Byte 8: 0x01=RT, 0x02=LT, 0x04=RB, 0x08=LB, 0x10=Y, 0x20=B, 0x40=X, 0x80=A
+/// - Byte 9: 0x10=Back, 0x20=Steam, 0x40=Start, 0x80=left grip (low nibble:
+///   left pad region under a click)
+/// - Byte 10: 0x01=right grip, 0x02=L click, 0x04=R click, 0x08=L touch,
+///   0x10=R touch, 0x40=stick click, 0x80=stick and left pad both in use
+/// - Byte 12: Right trigger analog (0-255)
+/// - Byte 13: Left trigger analog (0-255)
+/// - Bytes 16-19: Left trackpad X,Y (16-bit LE) OR Stick X,Y, see [`InputParser`]
+/// - Bytes 20-23: Right trackpad X,Y (16-bit LE)
+/// - Bytes 48+: Gyroscope/Accelerometer data
+///
+/// Stateless: a frame carrying left pad data reports the stick as centered and
+/// vice versa. Use [`InputParser`] on a live stream to keep both. Any packet
+/// other than an input report is an error; see [`parse_packet`].
+pub fn parse_input_report(data: &[u8]) -> Result<ControllerInput, String> {
+    InputParser::new().parse(data)
+}
+
+// Byte 10 flags that select what bytes 16-19 hold
+const LPAD_TOUCH_FLAG: u8 = 0x08;
+const LPAD_AND_JOY_FLAG: u8 = 0x80;
+const RPAD_TOUCH_FLAG: u8 = 0x10;
+
+// Bits of bytes 8-10 read as one little-endian word, and the button each one is.
+// Byte 9's low nibble is the left pad region under a click, which the pad
+// position already gives.
+const BUTTON_BITS: [(u32, u32); 16] = [
+    (0x00_00_01, BUTTON_RT),
+    (0x00_00_02, BUTTON_LT),
+    (0x00_00_04, BUTTON_RB),
+    (0x00_00_08, BUTTON_LB),
+    (0x00_00_10, BUTTON_Y),
+    (0x00_00_20, BUTTON_B),
+    (0x00_00_40, BUTTON_X),
+    (0x00_00_80, BUTTON_A),
+    (0x00_10_00, BUTTON_SELECT),
+    (0x00_20_00, BUTTON_STEAM),
+    (0x00_40_00, BUTTON_START),
+    (0x00_80_00, BUTTON_LGRIP),
+    (0x01_00_00, BUTTON_RGRIP),
+    (0x02_00_00, BUTTON_LPAD_CLICK),
+    (0x04_00_00, BUTTON_RPAD_CLICK),
+    (0x40_00_00, BUTTON_STICK_CLICK),
+];
+
+/// `BUTTON_*` mask for bytes 8-10 read as one little-endian word
+pub(crate) fn decode_buttons(bits: u32) -> u32 {
+    BUTTON_BITS
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .fold(0, |buttons, (_, button)| buttons | button)
+}
+
+/// Bounds-checked view over a raw report. Every read returns an error instead
+/// of panicking, so a short or malformed packet can never take down the reader.
+#[derive(Clone, Copy)]
+struct Report<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Report<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N], String> {
+        let mut out = [0u8; N];
+        let src = offset
+            .checked_add(N)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(|| {
+                format!(
+                    "Report too short: need {} bytes at offset {}, have {}",
+                    N,
+                    offset,
+                    self.data.len()
+                )
+            })?;
+        out.copy_from_slice(src);
+        Ok(out)
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8, String> {
+        self.bytes::<1>(offset).map(|b| b[0])
+    }
+}
+
+/// Stateful parser for a stream of reports from one controller.
+///
+/// The stick and the left pad share bytes 16-19: the lpad-touch flag says which
+/// one a frame carries. While both are in use the firmware sets 0x80 in byte 10
+/// and alternates frame types, so each side keeps its last value from the frame
+/// that carried it instead of dropping to zero.
+#[derive(Debug, Clone, Default)]
+pub struct InputParser {
+    stick: StickData,
+    left_trackpad: TrackpadData,
+    layout: Option<ReportLayout>,
+    clock: ClockTracker,
+}
+
+/// Un-wraps the controller's 32-bit timestamp. Each frame moves the extended
+/// clock by the signed 32-bit difference to the previous one, so a rollover
+/// carries into the high bits and a frame arriving slightly out of order
+/// steps back instead of looking like a whole wrap.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClockTracker {
+    last: Option<(u32, u64)>,
+}
+
+impl ClockTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn extend(&mut self, timestamp: u32) -> u64 {
+        let extended = match self.last {
+            Some((last, extended)) => {
+                let delta = timestamp.wrapping_sub(last) as i32 as i64;
+                (extended as i64 + delta).max(0) as u64
+            }
+            None => timestamp as u64,
+        };
+        self.last = Some((timestamp, extended));
+        extended
+    }
+}
+
+impl InputParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the retained stick/pad values (e.g. after reconnecting)
+    pub fn reset(&mut self) {
+        self.stick = StickData::default();
+        self.left_trackpad = TrackpadData::default();
+        self.clock = ClockTracker::new();
+    }
+
+    /// Layout descriptor (from the mapping wizard) applied on top of the
+    /// built-in offsets; `None` uses the built-in offsets only
+    pub fn set_layout(&mut self, layout: Option<ReportLayout>) {
+        self.layout = layout;
+    }
+
+    /// Parse one input report; any other packet is an error.
+    pub fn parse(&mut self, data: &[u8]) -> Result<ControllerInput, String> {
+        match self.parse_packet(data)? {
+            Packet::InputFrame(input) => Ok(input),
+            Packet::Unknown { raw } if raw.first() != Some(&CONTROLLER_REPORT_ID) => {
+                Err(format!("Invalid report type: 0x{:02x}", raw.first().copied().unwrap_or(0)))
+            }
+            other => Err(format!("Not an input report: {:?}", other)),
+        }
+    }
+
+    /// Parse one packet, dispatching on the report ID and packet type. Total
+    /// over arbitrary input: a truncated packet of a known type is an error,
+    /// never a panic, and leaves the retained stick/pad values untouched.
+    pub fn parse_packet(&mut self, data: &[u8]) -> Result<Packet, String> {
+        let report = Report::new(data);
+        if report.u8(0)? != CONTROLLER_REPORT_ID {
+            return Ok(Packet::Unknown { raw: data.to_vec() });
+        }
+
+        match report.u8(2)? {
+            INPUT_PACKET => self.parse_input(data).map(Packet::InputFrame),
+            DECK_STATE_PACKET => parse_deck_state(data, &mut self.clock).map(Packet::InputFrame),
+            WIRELESS_STATUS_PACKET => Ok(Packet::StatusEvent(WirelessStatus::from_byte(report.u8(4)?))),
+            BATTERY_STATUS_PACKET => BatteryStatus::parse(data)
+                .map(Packet::BatteryEvent)
+                .ok_or_else(|| format!("Battery status report too short: {} bytes", data.len())),
+            _ => Ok(Packet::Unknown { raw: data.to_vec() }),
+        }
+    }
+
+    fn parse_input(&mut self, data: &[u8]) -> Result<ControllerInput, String> {
+        let mut frame = RawFrame::default();
+        self.parse_frame(data, &mut frame).map_err(|e| e.to_string())?;
+        let mut input = ControllerInput::from(&frame);
+
+        if let Some(layout) = &self.layout {
+            layout.apply(data, &mut input);
+        }
+
+        Ok(input)
+    }
+
+    /// Decode an input report straight into `out`, without allocating. The
+    /// hot path behind [`parse`](Self::parse); the layout descriptor is only
+    /// applied on that path, since it works on [`ControllerInput`]. On error
+    /// neither `out` nor the retained stick/pad values change.
+    pub fn parse_frame(&mut self, data: &[u8], out: &mut RawFrame) -> Result<(), FrameError> {
+        let data: &[u8; REPORT_LEN] = match data.get(..REPORT_LEN).and_then(|d| d.try_into().ok()) {
+            Some(data) => data,
+            None => return Err(FrameError::TooShort(data.len())),
+        };
+        if data[0] != CONTROLLER_REPORT_ID {
+            return Err(FrameError::InvalidReportId(data[0]));
+        }
+        if data[2] != INPUT_PACKET {
+            return Err(FrameError::NotInput(data[2]));
+        }
+        // Fixed offsets into a fixed-size array: no bounds checks left past this point
+        let i16_at = |offset: usize| i16::from_le_bytes([data[offset], data[offset + 1]]);
+
+        // Bytes 8-10: button bits, low byte first
+        let buttons = decode_buttons(u32::from_le_bytes([data[8], data[9], data[10], 0]));
+
+        // Byte 10 also says what bytes 16-19 hold, and whether the right pad is touched
+        let trackpad_flags = data[10];
+        let lpad_touched = (trackpad_flags & LPAD_TOUCH_FLAG) != 0;
+        let lpad_and_joy = (trackpad_flags & LPAD_AND_JOY_FLAG) != 0;
+        let rpad_touched = (trackpad_flags & RPAD_TOUCH_FLAG) != 0;
+
+        // Stick OR left trackpad (bytes 16-19: X,Y as 16-bit LE)
+        let x1619 = i16_at(16);
+        let y1619 = i16_at(18);
+
+        let timestamp = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+
+        // Every check passed - only now update the state carried across frames
+        let extended_timestamp = self.clock.extend(timestamp);
+        if lpad_touched {
+            self.left_trackpad = TrackpadData {
+                x: x1619,
+                y: y1619,
+                active: true,
+            };
+            if !lpad_and_joy {
+                self.stick = StickData::default(); // Stick is centered
+            }
+        } else {
+            self.stick = StickData { x: x1619, y: y1619 };
+            if !lpad_and_joy {
+                self.left_trackpad = TrackpadData::default(); // Pad released
+            }
+        }
+
+        *out = RawFrame {
+            extended_timestamp,
+            // Bytes 4-7 as u32 LE
+            timestamp,
+            buttons,
+            stick_x: self.stick.x,
+            stick_y: self.stick.y,
+            left_pad_x: self.left_trackpad.x,
+            left_pad_y: self.left_trackpad.y,
+            // Right trackpad (bytes 20-23: X,Y as 16-bit LE)
+            right_pad_x: i16_at(20),
+            right_pad_y: i16_at(22),
+            // Gyroscope (bytes 48-55: empirically observed to change with movement)
+            gyro_pitch: i16_at(48),
+            gyro_yaw: i16_at(50),
+            gyro_roll: i16_at(52),
+            // Accelerometer (bytes 28-33) and orientation quaternion (bytes 40-47: W,X,Y,Z)
+            accel_x: i16_at(28),
+            accel_y: i16_at(30),
+            accel_z: i16_at(32),
+            quat_w: i16_at(40),
+            quat_x: i16_at(42),
+            quat_y: i16_at(44),
+            quat_z: i16_at(46),
+            // Analog triggers (bytes 12-13)
+            // Note: Resting values are around 0xe0-0xff, not 0x00!
+            left_trigger: data[13],
+            right_trigger: data[12],
+            left_pad_active: self.left_trackpad.active as u8,
+            right_pad_active: rpad_touched as u8,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_report() {
+        let mut data = vec![0u8; 64];
+        data[0] = 0x01; // Set valid report type
+        data[2] = INPUT_PACKET;
+
+        let result = parse_input_report(&data);
+        assert!(result.is_ok());
+
+        let input = result.unwrap();
+        assert_eq!(input.stick.x, 0);
+        assert_eq!(input.stick.y, 0);
+        assert!(!input.buttons.a);
+        assert!(input.orientation.is_none());
+    }
+
+    #[test]
+    fn test_imu_parsing() {
+        let mut data = vec![0u8; 64];
+        data[0] = CONTROLLER_REPORT_ID;
+        data[2] = INPUT_PACKET;
+        data[28..30].copy_from_slice(&(-120i16).to_le_bytes());
+        data[32..34].copy_from_slice(&16384i16.to_le_bytes());
+        // 90° about Z: w = z = cos 45°
+        data[40..42].copy_from_slice(&23170i16.to_le_bytes());
+        data[46..48].copy_from_slice(&23170i16.to_le_bytes());
+
+        let input = parse_input_report(&data).unwrap();
+        assert_eq!((input.imu.accel_x, input.imu.accel_y, input.imu.accel_z), (-120, 0, 16384));
+        assert_eq!((input.imu.quat_w, input.imu.quat_x, input.imu.quat_z), (23170, 0, 23170));
+        let orientation = input.orientation.unwrap();
+        assert!((orientation.yaw - 90.0).abs() < 0.1, "{:?}", orientation);
+        assert!(orientation.pitch.abs() < 0.1 && orientation.roll.abs() < 0.1, "{:?}", orientation);
+
+        // 30° about Y, from a quaternion that isn't quite unit length
+        let imu = ImuData { quat_w: 31000, quat_y: 8306, ..ImuData::default() };
+        let orientation = imu.orientation().unwrap();
+        assert!((orientation.pitch - 30.0).abs() < 0.1, "{:?}", orientation);
+        assert!(orientation.yaw.abs() < 0.1, "{:?}", orientation);
+    }
+
+    #[test]
+    fn test_invalid_report_size() {
+        let data = vec![0u8; 32]; // Too small
+        let result = parse_input_report(&data);
+        assert!(result.is_err());
+    }
+
+    fn mux_frame(flags: u8, x: i16, y: i16) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[0] = 0x01;
+        data[2] = INPUT_PACKET;
+        data[10] = flags;
+        data[16..18].copy_from_slice(&x.to_le_bytes());
+        data[18..20].copy_from_slice(&y.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_stick_and_pad_multiplexing() {
+        let mut parser = InputParser::new();
+
+        let input = parser.parse(&mux_frame(0x00, 1000, -2000)).unwrap();
+        assert_eq!((input.stick.x, input.stick.y), (1000, -2000));
+        assert!(!input.left_trackpad.active);
+
+        // Both in use: pad frame keeps the last stick value...
+        let input = parser.parse(&mux_frame(0x88, 300, 400)).unwrap();
+        assert_eq!((input.stick.x, input.stick.y), (1000, -2000));
+        assert_eq!((input.left_trackpad.x, input.left_trackpad.y), (300, 400));
+
+        // ...and stick frame keeps the last pad value
+        let input = parser.parse(&mux_frame(0x80, 1100, -2100)).unwrap();
+        assert_eq!((input.stick.x, input.stick.y), (1100, -2100));
+        assert!(input.left_trackpad.active);
+        assert_eq!(input.left_trackpad.x, 300);
+
+        // Pad only: stick has returned to center
+        let input = parser.parse(&mux_frame(0x08, 310, 410)).unwrap();
+        assert_eq!((input.stick.x, input.stick.y), (0, 0));
+
+        // Stick only: pad released
+        let input = parser.parse(&mux_frame(0x00, 50, 60)).unwrap();
+        assert!(!input.left_trackpad.active);
+        assert_eq!((input.stick.x, input.stick.y), (50, 60));
+    }
+
+    #[test]
+    fn test_timestamp_rollover() {
+        let mut parser = InputParser::new();
+        let mut frame = |timestamp: u32| {
+            let mut data = mux_frame(0x00, 0, 0);
+            data[4..8].copy_from_slice(&timestamp.to_le_bytes());
+            parser.parse(&data).unwrap().extended_timestamp
+        };
+
+        assert_eq!(frame(u32::MAX - 10), u32::MAX as u64 - 10);
+        assert_eq!(frame(5), u32::MAX as u64 + 6);
+        // Out of order by a little: a step back, not another wrap
+        assert_eq!(frame(1), u32::MAX as u64 + 2);
+        assert_eq!(frame(100), u32::MAX as u64 + 101);
+
+        parser.reset();
+        let mut data = mux_frame(0x00, 0, 0);
+        data[4..8].copy_from_slice(&7u32.to_le_bytes());
+        assert_eq!(parser.parse(&data).unwrap().extended_timestamp, 7);
+    }
+
+    #[test]
+    fn test_report_accessors_are_checked() {
+        let report = Report::new(&[0x01, 0x02, 0x03]);
+        assert_eq!(report.u8(2), Ok(0x03));
+        assert!(report.u8(3).is_err());
+    }
+
+    #[test]
+    fn test_parse_is_total() {
+        // Deterministic xorshift so failures reproduce; the fuzz target covers the rest
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut parser = InputParser::new();
+        let mut layout = ReportLayout::new("fuzz");
+        layout.axes.insert(
+            crate::layout::AxisId::StickX,
+            crate::layout::AxisField { offset: usize::MAX, format: crate::layout::AxisFormat::I16Le },
+        );
+        parser.set_layout(Some(layout));
+
+        for len in 0..=80 {
+            for _ in 0..32 {
+                let data: Vec<u8> = (0..len)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        state as u8
+                    })
+                    .collect();
+                let _ = parser.parse(&data);
+                let mut valid = data.clone();
+                if let Some(first) = valid.first_mut() {
+                    *first = 0x01;
+                }
+                if let Some(kind) = valid.get_mut(2) {
+                    *kind = [INPUT_PACKET, WIRELESS_STATUS_PACKET, BATTERY_STATUS_PACKET, DECK_STATE_PACKET][len % 4];
+                }
+                let _ = parser.parse(&valid);
+            }
+        }
+    }
+
+    #[test]
+    fn test_packet_dispatch() {
+        let mut data = vec![0u8; 64];
+        data[0] = CONTROLLER_REPORT_ID;
+
+        data[2] = WIRELESS_STATUS_PACKET;
+        data[4] = 0x01;
+        assert!(matches!(parse_packet(&data), Ok(Packet::StatusEvent(WirelessStatus::Disconnected))));
+
+        data[2] = BATTERY_STATUS_PACKET;
+        data[14] = 80;
+        assert!(matches!(parse_packet(&data), Ok(Packet::BatteryEvent(BatteryStatus { percent: 80, .. }))));
+        assert!(parse_input_report(&data).is_err());
+
+        // Unknown packet types and report IDs pass through instead of failing
+        data[2] = 0x0b;
+        assert!(matches!(parse_packet(&data), Ok(Packet::Unknown { raw }) if raw == data));
+        assert!(matches!(parse_packet(&[0x02, 0x00]), Ok(Packet::Unknown { .. })));
+
+        data[2] = INPUT_PACKET;
+        assert!(matches!(parse_packet(&data), Ok(Packet::InputFrame(_))));
+        assert!(parse_packet(&data[..32]).is_err());
+    }
+
+    #[test]
+    fn test_button_parsing() {
+        let mut data = vec![0u8; 64];
+        data[0] = 0x01;
+        data[2] = INPUT_PACKET;
+        data[8] = 0x80; // A button pressed
+
+        let result = parse_input_report(&data).unwrap();
+        assert!(result.buttons.a);
+        assert!(!result.buttons.b);
+
+        // Every bit maps to exactly one button
+        let mut parser = InputParser::new();
+        let mut frame = RawFrame::default();
+        for (bit, button) in BUTTON_BITS.iter() {
+            let mut data = [0u8; REPORT_LEN];
+            data[0] = 0x01;
+            data[2] = INPUT_PACKET;
+            data[8..11].copy_from_slice(&bit.to_le_bytes()[..3]);
+            parser.parse_frame(&data, &mut frame).unwrap();
+            assert_eq!(frame.buttons, *button, "bit {:06x}", bit);
+        }
+    }
+
+    // Wired reports: header, timestamp, bytes 8-10, triggers, then bytes 16-23
+    fn report(head: &str) -> Vec<u8> {
+        let mut data = crate::fixtures::from_hex(head).unwrap();
+        data.resize(REPORT_LEN, 0);
+        data
+    }
+
+    #[test]
+    fn test_button_reports() {
+        let buttons = |head: &str| parse_input_report(&report(head)).unwrap().buttons;
+
+        // A + LB, RT pulled to the click
+        let b = buttons("0100013c2a1b0000890000ff");
+        assert!(b.a && b.lb && b.rt);
+        assert!(!b.b && !b.x && !b.y && !b.rb && !b.lt);
+
+        // Back, Steam, Start and the left grip
+        let b = buttons("0100013c2b1b000000f000");
+        assert!(b.select && b.steam && b.start && b.lgrip);
+        assert!(!b.rgrip && !b.a);
+
+        // Right grip and stick click with the stick pushed right
+        let input = parse_input_report(&report("0100013c2c1b00000000410000000000e82e0000")).unwrap();
+        assert!(input.buttons.rgrip && input.buttons.stick_click);
+        assert!(!input.buttons.lpad_click);
+        assert_eq!(input.stick.x, 12008);
+
+        // Left pad clicked at the top (region bits in byte 9 don't add a button)
+        let input = parse_input_report(&report("0100013c2d1b000000010a0000000000f4010030")).unwrap();
+        assert!(input.buttons.lpad_click && !input.buttons.rpad_click && !input.buttons.stick_click);
+        assert!(input.left_trackpad.active);
+        assert_eq!((input.left_trackpad.x, input.left_trackpad.y), (500, 12288));
+
+        // Right pad touched, then clicked
+        let input = parse_input_report(&report("0100013c2e1b0000000010000000000000000000401f00f0")).unwrap();
+        assert!(input.right_trackpad.active && !input.buttons.rpad_click);
+        let input = parse_input_report(&report("0100013c2f1b0000000014000000000000000000401f00f0")).unwrap();
+        assert!(input.buttons.rpad_click);
+        assert_eq!((input.right_trackpad.x, input.right_trackpad.y), (8000, -4096));
+    }
+}
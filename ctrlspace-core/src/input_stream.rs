@@ -0,0 +1,251 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use crate::battery::BatteryStatus;
+use crate::clock;
+use crate::controller::ControllerHandle;
+use crate::error::ControllerError;
+use crate::input_parser::{ControllerInput, Packet, WirelessStatus};
+use crate::steam_controller::MAX_READ_BUFFER_SIZE;
+
+/// Event name the frontend listens on for parsed frames
+pub const CONTROLLER_INPUT_EVENT: &str = "controller-input";
+
+/// Event name for a stream that ended on its own, with its [`StreamEnd`]
+pub const INPUT_STREAM_ENDED_EVENT: &str = "input-stream-ended";
+
+/// Frames the frontend may have unacknowledged before we start coalescing
+pub const DEFAULT_MAX_IN_FLIGHT: u64 = 4;
+
+/// Why the reader thread gave up (not reported for `stop` or a consumer that went away)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", content = "error", rename_all = "snake_case")]
+pub enum StreamEnd {
+    Disconnected,
+    ReadError(String),
+}
+
+/// Reports that aren't frames, passed to the stream's event callback
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Battery(BatteryStatus),
+    Ended(StreamEnd),
+}
+
+/// A parsed frame pushed to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFrame {
+    /// Stream-local sequence number, echoed back by the frontend as an ack
+    pub seq: u64,
+    /// Frames replaced by this one because the consumer was behind
+    pub coalesced: u64,
+    pub input: ControllerInput,
+}
+
+/// Decides whether a frame may be sent or must wait for the consumer to catch up
+struct Backpressure {
+    max_in_flight: u64,
+    sent: u64,
+}
+
+impl Backpressure {
+    fn new(max_in_flight: u64) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            sent: 0,
+        }
+    }
+
+    fn can_send(&self, acked: u64) -> bool {
+        self.sent.saturating_sub(acked) < self.max_in_flight
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.sent += 1;
+        self.sent
+    }
+}
+
+/// Background reader that pushes parsed frames to a sink instead of being polled
+pub struct InputStream {
+    stop: Arc<AtomicBool>,
+    acked: Arc<AtomicU64>,
+    /// Sequence number of the last frame emitted
+    sent: Arc<AtomicU64>,
+    max_in_flight: u64,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl InputStream {
+    /// Start reading on a dedicated thread. `emit` returns `false` once the
+    /// consumer is gone, which ends the stream.
+    pub fn spawn<F>(manager: ControllerHandle, max_in_flight: u64, emit: F) -> Self
+    where
+        F: Fn(&InputFrame) -> bool + Send + 'static,
+    {
+        Self::spawn_with_events(manager, max_in_flight, emit, |_| {})
+    }
+
+    /// Like `spawn`, also passing wireless battery status reports and the
+    /// reason the stream ended (device gone, read failure) to `on_event`
+    pub fn spawn_with_events<F, S>(manager: ControllerHandle, max_in_flight: u64, emit: F, on_event: S) -> Self
+    where
+        F: Fn(&InputFrame) -> bool + Send + 'static,
+        S: Fn(StreamEvent) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let acked = Arc::new(AtomicU64::new(0));
+        let sent = Arc::new(AtomicU64::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+        let max_in_flight = max_in_flight.max(1);
+
+        let thread_stop = Arc::clone(&stop);
+        let thread_acked = Arc::clone(&acked);
+        let thread_sent = Arc::clone(&sent);
+        let thread_running = Arc::clone(&running);
+
+        let handle = thread::spawn(move || {
+            let mut backpressure = Backpressure::new(max_in_flight);
+            let mut pending: Option<ControllerInput> = None;
+            let mut coalesced = 0u64;
+            // One read buffer for the life of the stream
+            let mut buf = [0u8; MAX_READ_BUFFER_SIZE];
+
+            println!("📡 Input stream started (max {} frames in flight)", backpressure.max_in_flight);
+
+            let mut end = None;
+            while !thread_stop.load(Ordering::Relaxed) {
+                if !manager.is_connected() {
+                    end = Some(StreamEnd::Disconnected);
+                    break;
+                }
+
+                let settings = manager.read_settings();
+                let buffer_size = settings.buffer_size.min(MAX_READ_BUFFER_SIZE);
+                let size = match manager.read_input_into(&mut buf[..buffer_size], settings.timeout_ms.max(1)) {
+                    Ok(size) => size,
+                    Err(ControllerError::NotConnected) => {
+                        end = Some(StreamEnd::Disconnected);
+                        break;
+                    }
+                    Err(e) => {
+                        end = Some(StreamEnd::ReadError(e.to_string()));
+                        break;
+                    }
+                };
+                // 0 is a read timeout: nothing arrived, but the device is still there
+                if size > 0 {
+                    // Stamp before parsing so the time reflects the read, not our processing
+                    let read_at = clock::monotonic_us();
+                    match manager.parse_packet_at(&buf[..size], read_at) {
+                        Ok(Packet::InputFrame(input)) => {
+                            if pending.replace(input).is_some() {
+                                coalesced += 1;
+                            }
+                        }
+                        Ok(Packet::BatteryEvent(status)) => on_event(StreamEvent::Battery(status)),
+                        Ok(Packet::StatusEvent(WirelessStatus::Disconnected)) => {
+                            println!("🔌 Wireless controller went to sleep or out of range");
+                        }
+                        Ok(Packet::StatusEvent(_)) | Ok(Packet::Unknown { .. }) => {}
+                        Err(e) => eprintln!("❌ Input stream parse error: {}", e),
+                    }
+                }
+
+                if pending.is_some() && backpressure.can_send(thread_acked.load(Ordering::Acquire)) {
+                    let frame = InputFrame {
+                        seq: backpressure.next_seq(),
+                        coalesced,
+                        input: pending.take().unwrap(),
+                    };
+                    coalesced = 0;
+                    thread_sent.store(frame.seq, Ordering::Release);
+
+                    if !emit(&frame) {
+                        println!("🔌 Input stream stopping - consumer gone");
+                        break;
+                    }
+                }
+            }
+
+            thread_running.store(false, Ordering::Release);
+            match end {
+                Some(StreamEnd::Disconnected) => println!("🔌 Input stream stopped - controller disconnected"),
+                Some(StreamEnd::ReadError(ref e)) => eprintln!("❌ Input stream stopped - {}", e),
+                None => println!("✅ Input stream stopped"),
+            }
+            if let Some(end) = end {
+                on_event(StreamEvent::Ended(end));
+            }
+        });
+
+        Self {
+            stop,
+            acked,
+            sent,
+            max_in_flight,
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    /// Record that the consumer has processed every frame up to `seq`
+    pub fn ack(&self, seq: u64) {
+        self.acked.fetch_max(seq, Ordering::AcqRel);
+    }
+
+    /// Treat every frame sent so far as processed, for when the consumer was
+    /// replaced (a reloaded frontend never acks the old page's frames)
+    pub fn ack_all(&self) {
+        self.ack(self.sent.load(Ordering::Acquire));
+    }
+
+    pub fn max_in_flight(&self) -> u64 {
+        self.max_in_flight
+    }
+
+    /// Whether the reader thread is still running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// Signal the reader thread to stop and wait for it
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Signal the reader thread to stop without waiting, for when it may be
+    /// blocked in a HID call that never returns
+    pub fn abandon(self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backpressure_limits_in_flight() {
+        let mut bp = Backpressure::new(2);
+        assert!(bp.can_send(0));
+        bp.next_seq();
+        assert!(bp.can_send(0));
+        bp.next_seq();
+        assert!(!bp.can_send(0));
+        assert!(bp.can_send(1));
+    }
+
+    #[test]
+    fn test_backpressure_minimum_window() {
+        let mut bp = Backpressure::new(0);
+        assert!(bp.can_send(0));
+        assert_eq!(bp.next_seq(), 1);
+        assert!(!bp.can_send(0));
+    }
+}
@@ -0,0 +1,487 @@
+//! Binding layers and key repeat, so a profile can put every control within
+//! reach of one hand.
+//!
+//! A layer is entered with its activator button and overrides some button
+//! bindings and analog modes while active:
+//! - `hold`: active while the activator is held (Steam's "mode shift", e.g.
+//!   holding the left grip turns the right pad into a scroll wheel)
+//! - `toggle`: each press switches it on or off
+//! - `sticky`: a tap applies it to the next binding only (no need to hold two
+//!   buttons at once); a second tap locks it on, a third turns it off
+//!
+//! Held buttons bound to keys or scrolling repeat after a delay, at a rate the
+//! profile chooses (slow rates suit users who release buttons slowly).
+//!
+//! Toggled buttons latch: the first press holds the binding down and the next
+//! press releases it, with an optional timeout that releases it anyway.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use crate::input_parser::{ButtonState, ControllerInput};
+use crate::profiles::{Action, AnalogMode, ButtonId, Profile};
+
+/// Event name for resolved binding presses, repeats and releases
+pub const BINDING_ACTION_EVENT: &str = "binding-action";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerActivation {
+    Hold,
+    Toggle,
+    /// Tap for the next binding only, tap twice to lock
+    Sticky,
+}
+
+/// Bindings that apply while a layer is active; unlisted buttons and unset
+/// analogs keep their base binding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub activator: ButtonId,
+    pub activation: LayerActivation,
+    #[serde(default)]
+    pub buttons: BTreeMap<ButtonId, Action>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left_pad: Option<AnalogMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right_pad: Option<AnalogMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stick: Option<AnalogMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gyro: Option<AnalogMode>,
+}
+
+/// Auto-repeat for held key and scroll bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepeatRate {
+    /// Hold time before the first repeat
+    pub delay_ms: u32,
+    pub interval_ms: u32,
+}
+
+impl RepeatRate {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_ms < 10 {
+            return Err(format!("Repeat interval must be at least 10 ms, got {}", self.interval_ms));
+        }
+        Ok(())
+    }
+}
+
+/// Turns a button's presses into on/off toggles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToggleBinding {
+    /// Release automatically after this long, in case the user can't press again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u32>,
+}
+
+impl ToggleBinding {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_ms == Some(0) {
+            return Err("Toggle timeout must be above 0 ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Checks layer names and that no button activates two layers
+pub fn validate_layers(layers: &BTreeMap<String, Layer>) -> Result<(), String> {
+    let mut activators = BTreeMap::new();
+    for (name, layer) in layers {
+        if name.trim().is_empty() {
+            return Err("Layer names must not be empty".to_string());
+        }
+        if let Some(other) = activators.insert(layer.activator, name) {
+            return Err(format!("Layers '{}' and '{}' share an activator button", other, name));
+        }
+    }
+    Ok(())
+}
+
+impl Profile {
+    /// The profile as seen with `layer` active; `None` for an unknown layer
+    pub fn with_layer(&self, layer: &str) -> Option<Profile> {
+        let overrides = self.layers.get(layer)?;
+        let mut profile = self.clone();
+        profile.buttons.extend(overrides.buttons.clone());
+        let analogs = [
+            (&mut profile.left_pad, &overrides.left_pad),
+            (&mut profile.right_pad, &overrides.right_pad),
+            (&mut profile.stick, &overrides.stick),
+            (&mut profile.gyro, &overrides.gyro),
+        ];
+        for (mode, layer_mode) in analogs {
+            if let Some(layer_mode) = layer_mode {
+                *mode = layer_mode.clone();
+            }
+        }
+        Some(profile)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingPhase {
+    Pressed,
+    Repeated,
+    Released,
+}
+
+/// Payload of the `binding-action` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BindingEvent {
+    pub button: ButtonId,
+    pub action: Action,
+    pub phase: BindingPhase,
+    /// Layer the binding was resolved on; `None` for the base layer
+    pub layer: Option<String>,
+}
+
+/// What one frame did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayerUpdate {
+    pub events: Vec<BindingEvent>,
+    /// `Some` when the active layer changed (`Some(None)` = back to base)
+    pub layer_changed: Option<Option<String>>,
+}
+
+#[derive(Debug, Clone)]
+struct HeldBinding {
+    action: Action,
+    layer: Option<String>,
+    next_repeat: Option<Instant>,
+    /// Toggled on; stays held after the button is let go
+    latched: bool,
+    /// Toggle safety timeout
+    expires: Option<Instant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ActiveLayer {
+    name: String,
+    /// Sticky layer tapped once: drops after the next binding
+    one_shot: bool,
+}
+
+/// Resolves button presses against the active profile and its layers
+#[derive(Debug, Clone, Default)]
+pub struct LayerEngine {
+    previous: ButtonState,
+    active: Option<ActiveLayer>,
+    held: BTreeMap<ButtonId, HeldBinding>,
+}
+
+fn repeats(action: &Action) -> bool {
+    matches!(action, Action::Key { .. } | Action::Keys { .. } | Action::Scroll { .. })
+}
+
+impl LayerEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the active layer, if any
+    pub fn active_layer(&self) -> Option<&str> {
+        self.active.as_ref().map(|layer| layer.name.as_str())
+    }
+
+    /// Cursor output factor from held (or toggled) slow-mode bindings; the
+    /// slowest wins, 1.0 when none are held
+    pub fn output_scale(&self) -> f32 {
+        self.held
+            .values()
+            .filter_map(|held| match held.action {
+                Action::SlowMode { factor } => Some(factor),
+                _ => None,
+            })
+            .fold(1.0, f32::min)
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>, now: Instant) -> LayerUpdate {
+        let previous = std::mem::replace(&mut self.previous, input.buttons.clone());
+        let mut update = LayerUpdate::default();
+        let profile = match profile {
+            Some(profile) => profile,
+            None => {
+                self.held.clear();
+                if self.active.take().is_some() {
+                    update.layer_changed = Some(None);
+                }
+                return update;
+            }
+        };
+        let before = self.active_layer().map(str::to_string);
+        if self.active.as_ref().map_or(false, |layer| !profile.layers.contains_key(&layer.name)) {
+            self.active = None;
+        }
+
+        for button in ButtonId::ALL {
+            let (is, was) = (button.is_pressed(&input.buttons), button.is_pressed(&previous));
+            if is == was {
+                continue;
+            }
+            if let Some((name, layer)) = profile.layers.iter().find(|(_, layer)| layer.activator == button) {
+                self.activator_changed(name, layer.activation, is);
+            } else if is && self.held.get(&button).map_or(false, |held| held.latched) {
+                // Second press of a toggle
+                self.release(button, &mut update.events);
+            } else if is {
+                self.press(button, profile, now, &mut update.events);
+            } else if !self.held.get(&button).map_or(true, |held| held.latched) {
+                self.release(button, &mut update.events);
+            }
+        }
+
+        let expired: Vec<ButtonId> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.expires.map_or(false, |expires| now >= expires))
+            .map(|(button, _)| *button)
+            .collect();
+        for button in expired {
+            self.release(button, &mut update.events);
+        }
+
+        if let Some(rate) = profile.repeat {
+            let interval = Duration::from_millis(rate.interval_ms as u64);
+            for (button, held) in self.held.iter_mut() {
+                if let Some(next) = held.next_repeat.filter(|next| now >= *next) {
+                    held.next_repeat = Some(next + interval);
+                    update.events.push(BindingEvent {
+                        button: *button,
+                        action: held.action.clone(),
+                        phase: BindingPhase::Repeated,
+                        layer: held.layer.clone(),
+                    });
+                }
+            }
+        }
+
+        let after = self.active_layer().map(str::to_string);
+        if after != before {
+            update.layer_changed = Some(after);
+        }
+        update
+    }
+
+    /// Let go of every held binding, toggled ones included, and drop back to
+    /// the base layer; for when the input behind the engine goes away
+    pub fn release_all(&mut self) -> Vec<BindingEvent> {
+        let mut events = Vec::new();
+        let held: Vec<ButtonId> = self.held.keys().copied().collect();
+        for button in held {
+            self.release(button, &mut events);
+        }
+        self.previous = ButtonState::default();
+        self.active = None;
+        events
+    }
+
+    fn activator_changed(&mut self, name: &str, activation: LayerActivation, pressed: bool) {
+        let current = self.active.as_ref().filter(|layer| layer.name == name);
+        self.active = match (activation, pressed, current) {
+            (LayerActivation::Hold, true, _) => Some(ActiveLayer { name: name.to_string(), one_shot: false }),
+            (LayerActivation::Hold, false, Some(_)) => None,
+            (LayerActivation::Toggle, true, Some(_)) => None,
+            (LayerActivation::Toggle, true, None) => Some(ActiveLayer { name: name.to_string(), one_shot: false }),
+            // Second tap locks, third releases
+            (LayerActivation::Sticky, true, Some(layer)) if layer.one_shot => {
+                Some(ActiveLayer { name: name.to_string(), one_shot: false })
+            }
+            (LayerActivation::Sticky, true, Some(_)) => None,
+            (LayerActivation::Sticky, true, None) => Some(ActiveLayer { name: name.to_string(), one_shot: true }),
+            _ => return,
+        };
+    }
+
+    fn press(&mut self, button: ButtonId, profile: &Profile, now: Instant, events: &mut Vec<BindingEvent>) {
+        let layer = self.active.as_ref().map(|layer| layer.name.clone());
+        let action = layer
+            .as_ref()
+            .and_then(|name| profile.layers[name].buttons.get(&button))
+            .or_else(|| profile.buttons.get(&button));
+        let action = match action {
+            Some(action) if *action != Action::None => action.clone(),
+            _ => return,
+        };
+
+        if self.active.as_ref().map_or(false, |layer| layer.one_shot) {
+            self.active = None;
+        }
+        let toggle = profile.toggles.get(&button);
+        // A toggled key is a held key, so it doesn't auto-repeat
+        let next_repeat = profile
+            .repeat
+            .filter(|_| toggle.is_none() && repeats(&action))
+            .map(|rate| now + Duration::from_millis(rate.delay_ms as u64));
+        let expires = toggle
+            .and_then(|toggle| toggle.timeout_ms)
+            .map(|ms| now + Duration::from_millis(ms as u64));
+        events.push(BindingEvent {
+            button,
+            action: action.clone(),
+            phase: BindingPhase::Pressed,
+            layer: layer.clone(),
+        });
+        self.held.insert(
+            button,
+            HeldBinding {
+                action,
+                layer,
+                next_repeat,
+                latched: toggle.is_some(),
+                expires,
+            },
+        );
+    }
+
+    fn release(&mut self, button: ButtonId, events: &mut Vec<BindingEvent>) {
+        if let Some(held) = self.held.remove(&button) {
+            events.push(BindingEvent {
+                button,
+                action: held.action,
+                phase: BindingPhase::Released,
+                layer: held.layer,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Action {
+        Action::Key { key: key.to_string() }
+    }
+
+    fn profile(activation: LayerActivation) -> Profile {
+        let mut profile = Profile::new("One hand");
+        profile.buttons.insert(ButtonId::A, key("space"));
+        profile.buttons.insert(ButtonId::B, key("c"));
+        profile.layers.insert(
+            "Alt".to_string(),
+            Layer {
+                activator: ButtonId::Rgrip,
+                activation,
+                buttons: [(ButtonId::A, key("shift"))].into_iter().collect(),
+                left_pad: None,
+                right_pad: None,
+                stick: None,
+                gyro: None,
+            },
+        );
+        profile
+    }
+
+    // Press then release `buttons`, returning the pressed actions' keys
+    fn tap(engine: &mut LayerEngine, profile: &Profile, buttons: &[ButtonId], now: Instant) -> Vec<Action> {
+        let mut input = ControllerInput::default();
+        for button in buttons {
+            button.set_pressed(&mut input.buttons, true);
+        }
+        let pressed = engine.update(&input, Some(profile), now).events;
+        engine.update(&ControllerInput::default(), Some(profile), now);
+        pressed.into_iter().map(|event| event.action).collect()
+    }
+
+    #[test]
+    fn test_sticky_layer_applies_to_next_binding() {
+        let profile = profile(LayerActivation::Sticky);
+        let mut engine = LayerEngine::new();
+        let now = Instant::now();
+
+        assert!(tap(&mut engine, &profile, &[ButtonId::Rgrip], now).is_empty());
+        assert_eq!(engine.active_layer(), Some("Alt"));
+        assert_eq!(tap(&mut engine, &profile, &[ButtonId::A], now), vec![key("shift")]);
+        assert_eq!(engine.active_layer(), None);
+        assert_eq!(tap(&mut engine, &profile, &[ButtonId::A], now), vec![key("space")]);
+
+        // Double tap locks; buttons not in the layer fall through to the base
+        tap(&mut engine, &profile, &[ButtonId::Rgrip], now);
+        tap(&mut engine, &profile, &[ButtonId::Rgrip], now);
+        assert_eq!(tap(&mut engine, &profile, &[ButtonId::A], now), vec![key("shift")]);
+        assert_eq!(tap(&mut engine, &profile, &[ButtonId::B], now), vec![key("c")]);
+        tap(&mut engine, &profile, &[ButtonId::Rgrip], now);
+        assert_eq!(engine.active_layer(), None);
+    }
+
+    #[test]
+    fn test_hold_layer_shifts_pad_mode() {
+        let mut profile = profile(LayerActivation::Hold);
+        let shift = profile.layers.get_mut("Alt").unwrap();
+        shift.activator = ButtonId::Lgrip;
+        shift.right_pad = Some(AnalogMode::Scroll { sensitivity: 1.0 });
+        let mut engine = LayerEngine::new();
+        let now = Instant::now();
+        let mut input = ControllerInput::default();
+
+        input.buttons.lgrip = true;
+        let update = engine.update(&input, Some(&profile), now);
+        assert_eq!(update.layer_changed, Some(Some("Alt".to_string())));
+        // The activator itself binds nothing while it shifts
+        assert!(update.events.is_empty());
+        let shifted = profile.with_layer(engine.active_layer().unwrap()).unwrap();
+        assert_eq!(shifted.right_pad, AnalogMode::Scroll { sensitivity: 1.0 });
+        assert_eq!(shifted.left_pad, profile.left_pad);
+
+        input.buttons.lgrip = false;
+        assert_eq!(engine.update(&input, Some(&profile), now).layer_changed, Some(None));
+        assert_eq!(engine.active_layer(), None);
+    }
+
+    #[test]
+    fn test_toggle_latches_until_pressed_again_or_timeout() {
+        let mut profile = profile(LayerActivation::Hold);
+        profile.toggles.insert(ButtonId::B, ToggleBinding { timeout_ms: Some(5000) });
+        let mut engine = LayerEngine::new();
+        let start = Instant::now();
+        let mut pressed = ControllerInput::default();
+        pressed.buttons.b = true;
+        let released = ControllerInput::default();
+
+        let mut phases = |input: &ControllerInput, ms: u64| -> Vec<BindingPhase> {
+            let update = engine.update(input, Some(&profile), start + Duration::from_millis(ms));
+            update.events.into_iter().map(|event| event.phase).collect()
+        };
+        assert_eq!(phases(&pressed, 0), vec![BindingPhase::Pressed]);
+        assert_eq!(phases(&released, 100), vec![]);
+        assert_eq!(phases(&pressed, 1000), vec![BindingPhase::Released]);
+        assert_eq!(phases(&released, 1100), vec![]);
+
+        // Left on, the safety timeout lets go
+        assert_eq!(phases(&pressed, 2000), vec![BindingPhase::Pressed]);
+        assert_eq!(phases(&released, 2100), vec![]);
+        assert_eq!(phases(&released, 7000), vec![BindingPhase::Released]);
+    }
+
+    #[test]
+    fn test_held_keys_repeat_at_profile_rate() {
+        let mut profile = profile(LayerActivation::Hold);
+        profile.repeat = Some(RepeatRate { delay_ms: 800, interval_ms: 300 });
+        let mut engine = LayerEngine::new();
+        let start = Instant::now();
+        let mut input = ControllerInput::default();
+        input.buttons.b = true;
+
+        let phases: Vec<Vec<BindingPhase>> = [0, 500, 800, 1000, 1100, 1400]
+            .iter()
+            .map(|&ms| {
+                let update = engine.update(&input, Some(&profile), start + Duration::from_millis(ms));
+                update.events.into_iter().map(|event| event.phase).collect()
+            })
+            .collect();
+        assert_eq!(
+            phases,
+            vec![
+                vec![BindingPhase::Pressed],
+                vec![],
+                vec![BindingPhase::Repeated],
+                vec![],
+                vec![BindingPhase::Repeated],
+                vec![BindingPhase::Repeated],
+            ]
+        );
+    }
+}
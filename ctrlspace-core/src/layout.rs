@@ -0,0 +1,143 @@
+//! Report-layout descriptors: where each button bit and axis lives in the
+//! input report. Produced by the mapping wizard for firmware revisions the
+//! built-in parser doesn't know, and applied on top of it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::input_parser::ControllerInput;
+use crate::profiles::ButtonId;
+
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// Analog values a layout can relocate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisId {
+    LeftTrigger,
+    RightTrigger,
+    StickX,
+    StickY,
+    RpadX,
+    RpadY,
+}
+
+impl AxisId {
+    pub fn format(self) -> AxisFormat {
+        match self {
+            AxisId::LeftTrigger | AxisId::RightTrigger => AxisFormat::U8,
+            _ => AxisFormat::I16Le,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisFormat {
+    U8,
+    I16Le,
+}
+
+/// Button is pressed when all bits of `mask` are set at `offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitField {
+    pub offset: usize,
+    pub mask: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxisField {
+    pub offset: usize,
+    pub format: AxisFormat,
+}
+
+/// Where controls live in a report. Anything not listed keeps the built-in
+/// parser's value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportLayout {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub buttons: BTreeMap<ButtonId, BitField>,
+    #[serde(default)]
+    pub axes: BTreeMap<AxisId, AxisField>,
+}
+
+fn default_version() -> u32 {
+    LAYOUT_VERSION
+}
+
+impl ReportLayout {
+    pub fn new(name: &str) -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            name: name.to_string(),
+            buttons: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        }
+    }
+
+    /// Overwrite the fields this layout describes; out-of-range offsets are skipped
+    pub fn apply(&self, data: &[u8], input: &mut ControllerInput) {
+        for (button, field) in &self.buttons {
+            if let Some(&byte) = data.get(field.offset) {
+                button.set_pressed(&mut input.buttons, byte & field.mask == field.mask);
+            }
+        }
+
+        for (axis, field) in &self.axes {
+            let value = match field.format {
+                AxisFormat::U8 => data.get(field.offset).map(|&b| b as i16),
+                AxisFormat::I16Le => field
+                    .offset
+                    .checked_add(2)
+                    .and_then(|end| data.get(field.offset..end))
+                    .map(|b| i16::from_le_bytes([b[0], b[1]])),
+            };
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            match axis {
+                AxisId::LeftTrigger => input.triggers.left = value as u8,
+                AxisId::RightTrigger => input.triggers.right = value as u8,
+                AxisId::StickX => input.stick.x = value,
+                AxisId::StickY => input.stick.y = value,
+                AxisId::RpadX => input.right_trackpad.x = value,
+                AxisId::RpadY => input.right_trackpad.y = value,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_layout() {
+        let mut layout = ReportLayout::new("Rev B");
+        layout.buttons.insert(ButtonId::A, BitField { offset: 9, mask: 0x02 });
+        layout.axes.insert(AxisId::RightTrigger, AxisField { offset: 14, format: AxisFormat::U8 });
+        layout.axes.insert(AxisId::StickX, AxisField { offset: 30, format: AxisFormat::I16Le });
+        layout.axes.insert(AxisId::StickY, AxisField { offset: 63, format: AxisFormat::I16Le });
+
+        let mut data = vec![0u8; 64];
+        data[9] = 0x03;
+        data[14] = 200;
+        data[30..32].copy_from_slice(&(-1234i16).to_le_bytes());
+
+        let mut input = ControllerInput::default();
+        input.stick.y = 77;
+        layout.apply(&data, &mut input);
+
+        assert!(input.buttons.a);
+        assert_eq!(input.triggers.right, 200);
+        assert_eq!(input.stick.x, -1234);
+        // Runs past the end of the report - left alone
+        assert_eq!(input.stick.y, 77);
+
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(serde_json::from_str::<ReportLayout>(&json).unwrap(), layout);
+    }
+}
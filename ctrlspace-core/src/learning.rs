@@ -0,0 +1,236 @@
+//! Button discovery ("learning mode"): record a baseline while the controller
+//! is idle, then watch which report bits change while the user works one
+//! control. Bytes that change on their own during the baseline (sequence
+//! number, timestamp, IMU noise) are ignored.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::controller::ControllerHandle;
+
+/// Idle reports sampled before watching
+pub const BASELINE_FRAMES: usize = 50;
+
+/// Runs that are never finished stop on their own after this long
+pub const MAX_LEARNING_SECS: u64 = 30;
+
+const READ_TIMEOUT_MS: i32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// One or two bits flipped - a button or flag
+    Digital,
+    /// Several bits moved - an axis, trigger or pad coordinate byte
+    Analog,
+}
+
+/// Bits at one byte offset that changed while the control was used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LearnedBits {
+    pub offset: usize,
+    pub mask: u8,
+    pub kind: ChangeKind,
+    /// Frames in which these bits differed from the baseline
+    pub frames: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LearnResult {
+    pub control: String,
+    /// Most consistently changed first
+    pub changes: Vec<LearnedBits>,
+    pub frames_observed: usize,
+}
+
+/// Baseline plus accumulated changes; feed it raw reports
+#[derive(Debug, Clone)]
+pub struct LearningSession {
+    baseline: Vec<u8>,
+    noise: Vec<u8>,
+    changed: Vec<u8>,
+    hits: Vec<usize>,
+    frames: usize,
+}
+
+impl LearningSession {
+    /// Build from idle reports; needs at least two to estimate noise
+    pub fn from_baseline(reports: &[Vec<u8>]) -> Result<Self, String> {
+        if reports.len() < 2 {
+            return Err(format!("Need at least 2 baseline reports, got {}", reports.len()));
+        }
+        let len = reports.iter().map(|r| r.len()).min().unwrap_or(0);
+        let baseline = reports[0][..len].to_vec();
+
+        // A byte that moves at all while idle (counter, timestamp, IMU) is
+        // ignored entirely - its other bits will move too given time
+        let mut noise = vec![0u8; len];
+        for report in &reports[1..] {
+            for (i, n) in noise.iter_mut().enumerate() {
+                if report[i] != baseline[i] {
+                    *n = 0xff;
+                }
+            }
+        }
+
+        Ok(Self {
+            baseline,
+            noise,
+            changed: vec![0u8; len],
+            hits: vec![0; len],
+            frames: 0,
+        })
+    }
+
+    pub fn observe(&mut self, report: &[u8]) {
+        self.frames += 1;
+        for (i, &byte) in report.iter().enumerate().take(self.baseline.len()) {
+            let diff = (byte ^ self.baseline[i]) & !self.noise[i];
+            if diff != 0 {
+                self.changed[i] |= diff;
+                self.hits[i] += 1;
+            }
+        }
+    }
+
+    pub fn result(&self, control: &str) -> LearnResult {
+        let mut changes: Vec<LearnedBits> = self
+            .changed
+            .iter()
+            .enumerate()
+            .filter(|(_, &mask)| mask != 0)
+            .map(|(offset, &mask)| LearnedBits {
+                offset,
+                mask,
+                kind: if mask.count_ones() <= 2 { ChangeKind::Digital } else { ChangeKind::Analog },
+                frames: self.hits[offset],
+            })
+            .collect();
+        changes.sort_by(|a, b| b.frames.cmp(&a.frames).then(a.offset.cmp(&b.offset)));
+
+        LearnResult {
+            control: control.to_string(),
+            changes,
+            frames_observed: self.frames,
+        }
+    }
+}
+
+/// A learning session fed by its own reader thread
+pub struct LearningRun {
+    control: String,
+    session: Arc<Mutex<LearningSession>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl LearningRun {
+    /// Sample the baseline (the controller should be left alone), then watch
+    /// in the background until `finish`
+    pub fn start(manager: ControllerHandle, control: &str) -> Result<Self, String> {
+        let mut baseline = Vec::with_capacity(BASELINE_FRAMES);
+        let mut attempts = 0;
+        while baseline.len() < BASELINE_FRAMES && attempts < BASELINE_FRAMES * 4 {
+            attempts += 1;
+            if let Ok(Some(raw)) = manager.read_input_blocking(READ_TIMEOUT_MS) {
+                baseline.push(raw);
+            }
+        }
+        let session = Arc::new(Mutex::new(LearningSession::from_baseline(&baseline)?));
+        println!("🔍 Learning '{}' - baseline of {} reports recorded", control, baseline.len());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_session = Arc::clone(&session);
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let deadline = std::time::Instant::now() + Duration::from_secs(MAX_LEARNING_SECS);
+            while !thread_stop.load(Ordering::Relaxed) && std::time::Instant::now() < deadline {
+                if !manager.is_connected() {
+                    break;
+                }
+                if let Ok(Some(raw)) = manager.read_input_blocking(READ_TIMEOUT_MS) {
+                    thread_session.lock().unwrap().observe(&raw);
+                }
+            }
+        });
+
+        Ok(Self {
+            control: control.to_string(),
+            session,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn control(&self) -> &str {
+        &self.control
+    }
+
+    /// Stop watching and report what changed
+    pub fn finish(mut self) -> LearnResult {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let result = self.session.lock().unwrap().result(&self.control);
+        println!("✅ Learned '{}': {} candidate byte(s)", self.control, result.changes.len());
+        result
+    }
+}
+
+impl Drop for LearningRun {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(seq: u8, byte8: u8, trigger: u8) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[0] = 0x01;
+        data[1] = seq;
+        data[8] = byte8;
+        data[12] = trigger;
+        data
+    }
+
+    #[test]
+    fn test_detects_button_and_ignores_noise() {
+        let baseline: Vec<Vec<u8>> = (0..5).map(|seq| report(seq, 0x00, 0)).collect();
+        let mut session = LearningSession::from_baseline(&baseline).unwrap();
+
+        session.observe(&report(10, 0x00, 0));
+        session.observe(&report(11, 0x80, 0));
+        session.observe(&report(12, 0x80, 0));
+
+        let result = session.result("a");
+        assert_eq!(result.frames_observed, 3);
+        assert_eq!(
+            result.changes,
+            vec![LearnedBits { offset: 8, mask: 0x80, kind: ChangeKind::Digital, frames: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_analog_changes_rank_after_consistent_ones() {
+        let baseline = vec![report(0, 0, 0), report(1, 0, 0)];
+        let mut session = LearningSession::from_baseline(&baseline).unwrap();
+
+        session.observe(&report(2, 0x01, 0x3c));
+        session.observe(&report(3, 0x01, 0xff));
+        session.observe(&report(4, 0x01, 0x00));
+
+        let result = session.result("rt");
+        assert_eq!(result.changes[0].offset, 8);
+        assert_eq!(result.changes[1].offset, 12);
+        assert_eq!(result.changes[1].kind, ChangeKind::Analog);
+        assert_eq!(result.changes[1].frames, 2);
+
+        assert!(LearningSession::from_baseline(&baseline[..1]).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Steam Controller driver logic with no Tauri dependency: device management,
+//! HID report parsing, input streaming and mapping profiles. The desktop app's
+//! `commands.rs` is a thin adapter over this crate.
+
+pub mod antimicrox;
+pub mod battery;
+pub mod calibration;
+pub mod chord;
+pub mod clock;
+pub mod combine;
+pub mod community;
+pub mod conflicts;
+pub mod controller;
+pub mod cursor;
+pub mod curve;
+pub mod daisywheel;
+pub mod debug_console;
+pub mod delta;
+pub mod device_registry;
+pub mod device_settings;
+pub mod dongle;
+pub mod drift;
+pub mod dry_run;
+pub mod edge_spin;
+pub mod enumeration;
+pub mod error;
+pub mod exclusive;
+pub mod feedback;
+pub mod fixtures;
+pub mod frame;
+pub mod gamepad_api;
+pub mod gestures;
+pub mod ghost;
+pub mod gyro_mouse;
+pub mod haptics;
+pub mod health;
+pub mod hook_process;
+pub mod input_events;
+pub mod input_parser;
+pub mod input_stream;
+pub mod layers;
+pub mod layout;
+pub mod learning;
+pub mod mapping;
+pub mod melody;
+pub mod mirror;
+pub mod output;
+pub mod overlay;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod profile_switch;
+pub mod profiles;
+pub mod raw_diff;
+pub mod registers;
+pub mod response;
+pub mod rumble;
+pub mod runtime_state;
+pub mod sc_import;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod self_test;
+pub mod slow_mode;
+pub mod steam_apps;
+pub mod steam_controller;
+pub mod steam_deck;
+pub mod stress;
+pub mod tester_report;
+pub mod timeout;
+pub mod touch_menu;
+pub mod verification;
+pub mod virtual_gamepad;
+pub mod watchdog;
+pub mod wizard;
+pub mod workers;
+pub mod write_queue;
+pub mod write_stats;
+pub mod xinput;
@@ -0,0 +1,226 @@
+//! Trackball pads: a pad in `trackball` mode moves the cursor like a relative
+//! touchpad while touched, but a flick keeps the "ball" spinning after the
+//! finger lifts. The spin slows by the profile's friction (exponential decay
+//! per second) until it stops or a new touch catches it. Smoothing averages
+//! finger movement across frames, which also steadies the flick speed
+//! measured at lift-off.
+
+use crate::cursor::{CursorMove, PAD_UNITS_PER_PIXEL};
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{AnalogMode, Profile};
+
+/// Friction when a profile doesn't set one: the spin loses ~95% of its speed per second
+pub const DEFAULT_FRICTION: f32 = 3.0;
+
+/// Smoothing when a profile doesn't set one
+pub const DEFAULT_SMOOTHING: f32 = 0.3;
+
+/// Slowest lift-off speed (px/s) that keeps the ball spinning; slower lifts
+/// just stop the cursor
+const MIN_FLICK_SPEED: f32 = 300.0;
+
+/// Speed (px/s) below which a coasting ball stops
+const STOP_SPEED: f32 = 20.0;
+
+/// Longest gap between frames that is integrated
+const MAX_FRAME_GAP_US: u64 = 50_000;
+
+/// Checks a trackball mode's settings
+pub fn validate(friction: f32, smoothing: f32) -> Result<(), String> {
+    if !(friction > 0.0 && friction.is_finite()) {
+        return Err(format!("Trackball friction must be above 0, got {}", friction));
+    }
+    if !(0.0..1.0).contains(&smoothing) {
+        return Err(format!("Trackball smoothing must be from 0 up to 1, got {}", smoothing));
+    }
+    Ok(())
+}
+
+/// Trackball state for one pad
+#[derive(Debug, Clone, Default)]
+pub struct TrackballPad {
+    /// Finger position last frame, `None` while lifted
+    last: Option<(i16, i16)>,
+    last_us: Option<u64>,
+    /// Smoothed per-frame movement (px)
+    smoothed: (f32, f32),
+    /// Ball speed (px/s); non-zero while coasting after a flick
+    velocity: (f32, f32),
+    /// Sub-pixel movement carried to the next frame
+    remainder: (f32, f32),
+}
+
+impl TrackballPad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the ball is still spinning after a flick
+    pub fn is_coasting(&self) -> bool {
+        self.last.is_none() && self.velocity != (0.0, 0.0)
+    }
+
+    /// Pixels to move the cursor this frame (`now_us` is the host read time)
+    pub fn update(
+        &mut self,
+        pad: &TrackpadData,
+        now_us: u64,
+        sensitivity: f32,
+        friction: f32,
+        smoothing: f32,
+    ) -> (i32, i32) {
+        let elapsed_us = self.last_us.map_or(0, |last| now_us.saturating_sub(last).min(MAX_FRAME_GAP_US));
+        self.last_us = Some(now_us);
+        let seconds = elapsed_us as f32 / 1_000_000.0;
+
+        let (dx, dy) = if pad.active {
+            let position = (pad.x, pad.y);
+            let last = match self.last.replace(position) {
+                Some(last) => last,
+                None => {
+                    // Touch-down catches the ball
+                    self.velocity = (0.0, 0.0);
+                    self.smoothed = (0.0, 0.0);
+                    return (0, 0);
+                }
+            };
+            // Pad Y grows upwards, screen Y downwards
+            let scale = sensitivity / PAD_UNITS_PER_PIXEL;
+            let raw_x = (position.0 as i32 - last.0 as i32) as f32 * scale;
+            let raw_y = (last.1 as i32 - position.1 as i32) as f32 * scale;
+            self.smoothed = (
+                self.smoothed.0 * smoothing + raw_x * (1.0 - smoothing),
+                self.smoothed.1 * smoothing + raw_y * (1.0 - smoothing),
+            );
+            if seconds > 0.0 {
+                self.velocity = (self.smoothed.0 / seconds, self.smoothed.1 / seconds);
+            }
+            self.smoothed
+        } else {
+            if self.last.take().is_some() {
+                // Lift-off: only a flick keeps spinning
+                self.smoothed = (0.0, 0.0);
+                if speed(self.velocity) < MIN_FLICK_SPEED {
+                    self.velocity = (0.0, 0.0);
+                }
+            }
+            if self.velocity == (0.0, 0.0) {
+                self.remainder = (0.0, 0.0);
+                return (0, 0);
+            }
+            let step = (self.velocity.0 * seconds, self.velocity.1 * seconds);
+            let decay = (-friction * seconds).exp();
+            self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
+            if speed(self.velocity) < STOP_SPEED {
+                self.velocity = (0.0, 0.0);
+            }
+            step
+        };
+
+        let dx = dx + self.remainder.0;
+        let dy = dy + self.remainder.1;
+        let step = (dx.trunc(), dy.trunc());
+        self.remainder = (dx - step.0, dy - step.1);
+        (step.0 as i32, step.1 as i32)
+    }
+}
+
+fn speed(velocity: (f32, f32)) -> f32 {
+    (velocity.0 * velocity.0 + velocity.1 * velocity.1).sqrt()
+}
+
+/// Trackball movement for both pads, driven by the active profile's pad modes
+#[derive(Debug, Clone, Default)]
+pub struct TrackballTracker {
+    left: TrackballPad,
+    right: TrackballPad,
+}
+
+impl TrackballTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>) -> Vec<CursorMove> {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => {
+                *self = Self::default();
+                return Vec::new();
+            }
+        };
+        let pads = [
+            (Pad::Left, &mut self.left, &input.left_trackpad, &profile.left_pad),
+            (Pad::Right, &mut self.right, &input.right_trackpad, &profile.right_pad),
+        ];
+
+        let mut moves = Vec::new();
+        for (pad, ball, data, mode) in pads {
+            match mode {
+                AnalogMode::Trackball { sensitivity, friction, smoothing } => {
+                    let (dx, dy) = ball.update(data, input.host_timestamp_us, *sensitivity, *friction, *smoothing);
+                    if (dx, dy) != (0, 0) {
+                        moves.push(CursorMove { pad, dx, dy });
+                    }
+                }
+                // Switching modes stops a spinning ball
+                _ => *ball = TrackballPad::default(),
+            }
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(x: i16) -> TrackpadData {
+        TrackpadData { x, y: 0, active: true }
+    }
+
+    #[test]
+    fn test_flick_coasts_and_stops() {
+        let mut ball = TrackballPad::new();
+        let lifted = TrackpadData::default();
+        let mut now = 0;
+        let mut step = |ball: &mut TrackballPad, pad: &TrackpadData| {
+            now += 4_000;
+            ball.update(pad, now, 1.0, DEFAULT_FRICTION, 0.0)
+        };
+
+        // 640 pad units per 4 ms frame is 10 px per frame, 2500 px/s
+        assert_eq!(step(&mut ball, &touch(0)), (0, 0));
+        for i in 1..=5 {
+            assert_eq!(step(&mut ball, &touch(i * 640)), (10, 0));
+        }
+
+        // After lift-off the ball keeps going, slowing down, then stops
+        let coast: Vec<i32> = (0..1000).map(|_| step(&mut ball, &lifted).0).collect();
+        assert_eq!(coast[0], 10);
+        assert!(coast[100] < coast[0] && coast[100] > 0, "{:?}", &coast[..120]);
+        assert!(!ball.is_coasting());
+        assert_eq!(coast[999], 0);
+        // Distance is about v / friction
+        let total: i32 = coast.iter().sum();
+        assert!((total - 820).abs() < 30, "coasted {} px", total);
+
+        // A slow lift doesn't spin, and a touch catches a spinning ball
+        step(&mut ball, &touch(0));
+        step(&mut ball, &touch(10));
+        step(&mut ball, &lifted);
+        assert!(!ball.is_coasting());
+        for i in 0..3 {
+            step(&mut ball, &touch(i * 640));
+        }
+        step(&mut ball, &lifted);
+        assert!(ball.is_coasting());
+        assert_eq!(step(&mut ball, &touch(5000)), (0, 0));
+        assert!(!ball.is_coasting());
+
+        assert!(validate(DEFAULT_FRICTION, DEFAULT_SMOOTHING).is_ok());
+        assert!(validate(0.0, 0.5).is_err());
+        assert!(validate(3.0, 1.0).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Sound through the trackpad actuators. The firmware has built-in melodies
+//! (the jingles it plays at power on and off) that can be played on demand
+//! (0xb6) or assigned to the startup and shutdown events (0xc1). Single
+//! tones are plain 0x8f pulses whose on and off times are each half the
+//! tone's period, repeated for the duration.
+
+use serde::{Deserialize, Serialize};
+use crate::haptics::{HapticPulse, HapticSide};
+
+/// Feature report that plays a built-in melody
+pub const PLAY_AUDIO_REPORT: u8 = 0xb6;
+
+/// Feature report that assigns built-in melodies to controller events
+pub const SET_AUDIO_MAPPING_REPORT: u8 = 0xc1;
+
+/// Built-in melodies are numbered from 0 up to this (exclusive)
+pub const MELODY_COUNT: u8 = 16;
+
+/// Tones outside this range are inaudible or don't fit the pulse timing
+pub const MIN_TONE_HZ: u32 = 20;
+pub const MAX_TONE_HZ: u32 = 4000;
+
+pub const MAX_TONE_MS: u32 = 10_000;
+
+// Feature reports are padded to the full 64-byte HID report
+const FEATURE_REPORT_SIZE: usize = 64;
+
+// Events in the 0xc1 mapping payload, in order. Only the first two are known;
+// the firmware has no report to read the mapping back, so the payload stops
+// after them rather than guessing values for the rest.
+const STARTUP_SLOT: usize = 0;
+const SHUTDOWN_SLOT: usize = 1;
+const AUDIO_MAPPING_SLOTS: usize = 2;
+
+pub fn validate_melody(id: u8) -> Result<(), String> {
+    if id >= MELODY_COUNT {
+        return Err(format!("Melody must be 0-{}, got {}", MELODY_COUNT - 1, id));
+    }
+    Ok(())
+}
+
+/// 0xb6 report playing melody `id`
+pub fn play_melody_report(id: u8) -> Result<Vec<u8>, String> {
+    validate_melody(id)?;
+    let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+    report[0] = PLAY_AUDIO_REPORT;
+    report[1] = 0x04; // payload length
+    report[2] = id;
+    Ok(report)
+}
+
+/// Melodies played when the controller turns on and off. Both are always
+/// written, since the current ones can't be read back to keep one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jingles {
+    pub startup: u8,
+    pub shutdown: u8,
+}
+
+impl Jingles {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_melody(self.startup)?;
+        validate_melody(self.shutdown)
+    }
+
+    /// 0xc1 report assigning these melodies
+    pub fn to_report(&self) -> Result<Vec<u8>, String> {
+        self.validate()?;
+        let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+        report[0] = SET_AUDIO_MAPPING_REPORT;
+        report[1] = AUDIO_MAPPING_SLOTS as u8;
+        report[2 + STARTUP_SLOT] = self.startup;
+        report[2 + SHUTDOWN_SLOT] = self.shutdown;
+        Ok(report)
+    }
+}
+
+/// A single note on one actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tone {
+    pub side: HapticSide,
+    pub frequency_hz: u32,
+    pub duration_ms: u32,
+}
+
+impl Tone {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_TONE_HZ..=MAX_TONE_HZ).contains(&self.frequency_hz) {
+            return Err(format!(
+                "Tone frequency must be {}-{} Hz, got {}",
+                MIN_TONE_HZ, MAX_TONE_HZ, self.frequency_hz
+            ));
+        }
+        if self.duration_ms == 0 || self.duration_ms > MAX_TONE_MS {
+            return Err(format!("Tone duration must be 1-{} ms, got {}", MAX_TONE_MS, self.duration_ms));
+        }
+        Ok(())
+    }
+
+    /// The pulse train that sounds this tone: on for half a period, off for
+    /// the other half. Not to be scaled - the "amplitude" is the on time, so
+    /// scaling it would change the pitch.
+    pub fn to_pulse(&self) -> Result<HapticPulse, String> {
+        self.validate()?;
+        let half_period_us = (500_000 / self.frequency_hz) as u16;
+        let cycles = (self.duration_ms as u64 * self.frequency_hz as u64 / 1000).max(1);
+        Ok(HapticPulse {
+            side: self.side,
+            amplitude: half_period_us,
+            period: half_period_us,
+            count: cycles.min(u16::MAX as u64) as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_melody_and_tone_reports() {
+        assert_eq!(&play_melody_report(3).unwrap()[..4], &[0xb6, 0x04, 0x03, 0x00]);
+        assert!(play_melody_report(MELODY_COUNT).is_err());
+
+        let report = Jingles { startup: 5, shutdown: 2 }.to_report().unwrap();
+        assert_eq!(&report[..5], &[0xc1, 0x02, 0x05, 0x02, 0x00]);
+        assert!(Jingles { startup: 0, shutdown: 16 }.validate().is_err());
+
+        // A4 for half a second: 1136 µs on, 1136 µs off, 220 times
+        let a4 = Tone { side: HapticSide::Left, frequency_hz: 440, duration_ms: 500 };
+        assert_eq!(
+            a4.to_pulse().unwrap(),
+            HapticPulse { side: HapticSide::Left, amplitude: 1136, period: 1136, count: 220 }
+        );
+        assert!(Tone { frequency_hz: 10, ..a4 }.validate().is_err());
+        assert!(Tone { duration_ms: 0, ..a4 }.validate().is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Left-handed layouts: a profile-level transform that swaps the controller's
+//! left and right sides before the profile is applied, so one profile serves
+//! both hands.
+
+use serde::{Deserialize, Serialize};
+use std::mem::swap;
+use crate::input_parser::{ControllerInput, TrackpadData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mirror {
+    Off,
+    /// Swap pads, triggers, bumpers and grips; directions stay as they are
+    Swap,
+    /// Swap and also flip pad X, so positions are anatomically mirrored
+    /// (the inner edge of one pad becomes the inner edge of the other)
+    Reflect,
+}
+
+impl Mirror {
+    pub fn is_off(&self) -> bool {
+        *self == Mirror::Off
+    }
+}
+
+fn flip_x(pad: &mut TrackpadData) {
+    pad.x = pad.x.checked_neg().unwrap_or(i16::MAX);
+}
+
+/// `input` as the profile should see it. The stick and gyro have no mirror
+/// image on the controller and are left alone.
+pub fn mirror_input(input: &ControllerInput, mirror: Mirror) -> ControllerInput {
+    let mut out = input.clone();
+    if mirror.is_off() {
+        return out;
+    }
+
+    let buttons = &mut out.buttons;
+    swap(&mut buttons.lb, &mut buttons.rb);
+    swap(&mut buttons.lt, &mut buttons.rt);
+    swap(&mut buttons.lgrip, &mut buttons.rgrip);
+    swap(&mut buttons.lpad_click, &mut buttons.rpad_click);
+    swap(&mut out.triggers.left, &mut out.triggers.right);
+    swap(&mut out.left_trackpad, &mut out.right_trackpad);
+
+    if mirror == Mirror::Reflect {
+        flip_x(&mut out.left_trackpad);
+        flip_x(&mut out.right_trackpad);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_swaps_sides() {
+        let mut input = ControllerInput::default();
+        input.buttons.lt = true;
+        input.buttons.rgrip = true;
+        input.triggers.left = 255;
+        input.left_trackpad = TrackpadData { x: 1000, y: 2000, active: true };
+        input.right_trackpad = TrackpadData { x: i16::MIN, y: 0, active: false };
+        input.stick.x = -500;
+
+        let swapped = mirror_input(&input, Mirror::Swap);
+        assert!(swapped.buttons.rt && !swapped.buttons.lt);
+        assert!(swapped.buttons.lgrip && !swapped.buttons.rgrip);
+        assert_eq!((swapped.triggers.left, swapped.triggers.right), (0, 255));
+        assert_eq!((swapped.right_trackpad.x, swapped.right_trackpad.y), (1000, 2000));
+        assert!(swapped.right_trackpad.active && !swapped.left_trackpad.active);
+        assert_eq!(swapped.stick.x, -500);
+
+        let reflected = mirror_input(&input, Mirror::Reflect);
+        assert_eq!(reflected.right_trackpad.x, -1000);
+        assert_eq!(reflected.left_trackpad.x, i16::MAX);
+
+        assert!(mirror_input(&input, Mirror::Off).buttons.lt);
+    }
+}
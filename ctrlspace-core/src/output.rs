@@ -0,0 +1,337 @@
+//! Keyboard and mouse output: binding actions turned into key, button,
+//! wheel and pointer events for the OS. The platform side (SendInput on
+//! Windows, a uinput keyboard/mouse on Linux) lives in the app behind
+//! [`OutputBackend`]; this module decides which events an action sends and
+//! remembers what is held, so everything can be released when the
+//! controller goes away.
+//!
+//! Key names are the ones profiles use (`"w"`, `"space"`, `"ctrl"`,
+//! `"f5"`, `"leftbrace"`), matched case-insensitively.
+
+use serde::Serialize;
+use crate::edge_spin::{SpinEvent, SpinOutput};
+use crate::layers::BindingPhase;
+use crate::profiles::{Action, MouseButton};
+
+/// A key as each backend addresses it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct KeyCode {
+    /// Linux `KEY_*` code
+    pub evdev: u16,
+    /// Windows virtual-key code
+    pub vk: u16,
+}
+
+/// Named keys other than letters, digits and F-keys: (name, evdev, vk)
+const KEYS: &[(&str, u16, u16)] = &[
+    ("space", 57, 0x20),
+    ("enter", 28, 0x0d),
+    ("escape", 1, 0x1b),
+    ("tab", 15, 0x09),
+    ("backspace", 14, 0x08),
+    ("insert", 110, 0x2d),
+    ("delete", 111, 0x2e),
+    ("home", 102, 0x24),
+    ("end", 107, 0x23),
+    ("pageup", 104, 0x21),
+    ("pagedown", 109, 0x22),
+    ("left", 105, 0x25),
+    ("up", 103, 0x26),
+    ("right", 106, 0x27),
+    ("down", 108, 0x28),
+    ("shift", 42, 0x10),
+    ("ctrl", 29, 0x11),
+    ("alt", 56, 0x12),
+    ("meta", 125, 0x5b),
+    ("capslock", 58, 0x14),
+    ("pause", 119, 0x13),
+    ("minus", 12, 0xbd),
+    ("equal", 13, 0xbb),
+    ("comma", 51, 0xbc),
+    ("dot", 52, 0xbe),
+    ("slash", 53, 0xbf),
+    ("semicolon", 39, 0xba),
+    ("apostrophe", 40, 0xde),
+    ("leftbrace", 26, 0xdb),
+    ("rightbrace", 27, 0xdd),
+    ("backslash", 43, 0xdc),
+    ("grave", 41, 0xc0),
+    ("mute", 113, 0xad),
+    ("volumedown", 114, 0xae),
+    ("volumeup", 115, 0xaf),
+];
+
+/// `KEY_A`..`KEY_Z` (evdev codes follow the QWERTY rows, not the alphabet)
+const LETTERS: [u16; 26] = [
+    30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17, 45, 21, 44,
+];
+
+/// evdev codes of F1-F24
+fn evdev_f_key(n: u16) -> u16 {
+    match n {
+        1..=10 => 58 + n,
+        11 | 12 => 76 + n,
+        _ => 170 + n,
+    }
+}
+
+/// The key a profile key name stands for
+pub fn key_code(name: &str) -> Option<KeyCode> {
+    let name = name.to_ascii_lowercase();
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        match c {
+            'a'..='z' => {
+                let evdev = LETTERS[(c as u8 - b'a') as usize];
+                return Some(KeyCode { evdev, vk: c.to_ascii_uppercase() as u16 });
+            }
+            // KEY_1..KEY_9 are 2..10, KEY_0 is 11
+            '0' => return Some(KeyCode { evdev: 11, vk: c as u16 }),
+            '1'..='9' => return Some(KeyCode { evdev: (c as u8 - b'0') as u16 + 1, vk: c as u16 }),
+            _ => {}
+        }
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u16>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(KeyCode { evdev: evdev_f_key(n), vk: 0x6f + n });
+        }
+    }
+    KEYS.iter()
+        .find(|(key, _, _)| *key == name)
+        .map(|(_, evdev, vk)| KeyCode { evdev: *evdev, vk: *vk })
+}
+
+fn key(name: &str) -> Result<KeyCode, String> {
+    key_code(name).ok_or_else(|| format!("Unknown key '{}'", name))
+}
+
+/// One OS input event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+    Key { key: KeyCode, down: bool },
+    Button { button: MouseButton, down: bool },
+    /// Wheel clicks; positive is up
+    Scroll { amount: i32 },
+    /// Relative pointer movement in pixels; positive `dy` is down
+    Move { dx: i32, dy: i32 },
+}
+
+/// Events for one phase of a binding. Keys and buttons go down on press (and
+/// again on repeat, like a held key) and up on release; chords release in
+/// reverse order. Scrolls and double clicks happen on press (scrolls also on
+/// repeat). Slow mode and plugin actions are handled elsewhere and send
+/// nothing.
+pub fn action_events(action: &Action, phase: BindingPhase) -> Result<Vec<OutputEvent>, String> {
+    let press = phase != BindingPhase::Released;
+    let events = match action {
+        Action::Key { key: name } => vec![OutputEvent::Key { key: key(name)?, down: press }],
+        Action::Keys { keys } => {
+            let mut events = keys
+                .iter()
+                .map(|name| Ok(OutputEvent::Key { key: key(name)?, down: press }))
+                .collect::<Result<Vec<_>, String>>()?;
+            if !press {
+                events.reverse();
+            }
+            events
+        }
+        Action::Mouse { button } => vec![OutputEvent::Button { button: *button, down: press }],
+        Action::DoubleClick { button } if phase == BindingPhase::Pressed => {
+            let click = |down| OutputEvent::Button { button: *button, down };
+            vec![click(true), click(false), click(true), click(false)]
+        }
+        Action::Scroll { amount } if press => vec![OutputEvent::Scroll { amount: *amount }],
+        _ => Vec::new(),
+    };
+    Ok(events)
+}
+
+/// Press then release, as `test_output_action` sends it
+pub fn tap_events(action: &Action) -> Result<Vec<OutputEvent>, String> {
+    let mut events = action_events(action, BindingPhase::Pressed)?;
+    events.extend(action_events(action, BindingPhase::Released)?);
+    Ok(events)
+}
+
+/// Events for one frame of rim spinning (positive steps are clockwise):
+/// clockwise scrolls down, turns the volume up, or zooms in (ctrl + wheel up)
+pub fn spin_events(event: &SpinEvent) -> Result<Vec<OutputEvent>, String> {
+    let steps = event.steps;
+    if steps == 0 {
+        return Ok(Vec::new());
+    }
+    let events = match event.output {
+        SpinOutput::Scroll => vec![OutputEvent::Scroll { amount: -steps }],
+        SpinOutput::Volume => {
+            let volume = key(if steps > 0 { "volumeup" } else { "volumedown" })?;
+            (0..steps.unsigned_abs())
+                .flat_map(|_| [OutputEvent::Key { key: volume, down: true }, OutputEvent::Key { key: volume, down: false }])
+                .collect()
+        }
+        SpinOutput::Zoom => {
+            let ctrl = key("ctrl")?;
+            vec![
+                OutputEvent::Key { key: ctrl, down: true },
+                OutputEvent::Scroll { amount: steps },
+                OutputEvent::Key { key: ctrl, down: false },
+            ]
+        }
+    };
+    Ok(events)
+}
+
+/// Whether bindings reach the OS, and through what
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputStatus {
+    /// Bindings and cursor movement are injected (`set_output_injection`)
+    pub enabled: bool,
+    /// `SendInput` or `uinput`; empty where injection isn't supported
+    pub backend: String,
+    /// Why the last attempt to create or use the backend failed
+    pub error: Option<String>,
+}
+
+/// A platform's way of injecting input
+pub trait OutputBackend: Send {
+    fn backend(&self) -> &'static str;
+    fn send(&mut self, events: &[OutputEvent]) -> Result<(), String>;
+}
+
+/// Sends events through a backend, tracking held keys and buttons
+pub struct Output {
+    backend: Box<dyn OutputBackend>,
+    held: Vec<OutputEvent>,
+}
+
+impl Output {
+    pub fn new(backend: Box<dyn OutputBackend>) -> Self {
+        Self { backend, held: Vec::new() }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.backend.backend()
+    }
+
+    pub fn send(&mut self, events: &[OutputEvent]) -> Result<(), String> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.backend.send(events)?;
+        for event in events {
+            match *event {
+                OutputEvent::Key { key, down } => self.track(OutputEvent::Key { key, down: true }, down),
+                OutputEvent::Button { button, down } => self.track(OutputEvent::Button { button, down: true }, down),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn track(&mut self, pressed: OutputEvent, down: bool) {
+        self.held.retain(|held| *held != pressed);
+        if down {
+            self.held.push(pressed);
+        }
+    }
+
+    /// Release everything still held, last pressed first
+    pub fn release_all(&mut self) -> Result<(), String> {
+        let releases: Vec<OutputEvent> = self
+            .held
+            .iter()
+            .rev()
+            .map(|held| match *held {
+                OutputEvent::Key { key, .. } => OutputEvent::Key { key, down: false },
+                OutputEvent::Button { button, .. } => OutputEvent::Button { button, down: false },
+                other => other,
+            })
+            .collect();
+        self.send(&releases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct FakeBackend(Arc<Mutex<Vec<OutputEvent>>>);
+
+    impl OutputBackend for FakeBackend {
+        fn backend(&self) -> &'static str {
+            "fake"
+        }
+
+        fn send(&mut self, events: &[OutputEvent]) -> Result<(), String> {
+            self.0.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_action_events() {
+        assert_eq!(key_code("W"), Some(KeyCode { evdev: 17, vk: 0x57 }));
+        assert_eq!(key_code("0"), Some(KeyCode { evdev: 11, vk: 0x30 }));
+        assert_eq!(key_code("f12"), Some(KeyCode { evdev: 88, vk: 0x7b }));
+        assert_eq!(key_code("f13"), Some(KeyCode { evdev: 183, vk: 0x7c }));
+        assert_eq!(key_code("f25"), None);
+
+        let chord = Action::Keys { keys: vec!["ctrl".to_string(), "f5".to_string()] };
+        let ctrl = key_code("ctrl").unwrap();
+        let f5 = key_code("f5").unwrap();
+        assert_eq!(
+            tap_events(&chord).unwrap(),
+            vec![
+                OutputEvent::Key { key: ctrl, down: true },
+                OutputEvent::Key { key: f5, down: true },
+                OutputEvent::Key { key: f5, down: false },
+                OutputEvent::Key { key: ctrl, down: false },
+            ]
+        );
+        assert_eq!(action_events(&Action::DoubleClick { button: MouseButton::Left }, BindingPhase::Released).unwrap(), vec![]);
+        assert_eq!(action_events(&Action::SlowMode { factor: 0.5 }, BindingPhase::Pressed).unwrap(), vec![]);
+        assert!(tap_events(&Action::Key { key: "hyper".to_string() }).is_err());
+
+        // Whatever is still down is released on request, and only once
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut output = Output::new(Box::new(FakeBackend(sent.clone())));
+        output.send(&action_events(&chord, BindingPhase::Pressed).unwrap()).unwrap();
+        output.send(&action_events(&Action::Mouse { button: MouseButton::Right }, BindingPhase::Pressed).unwrap()).unwrap();
+        output.send(&action_events(&chord, BindingPhase::Repeated).unwrap()).unwrap();
+        sent.lock().unwrap().clear();
+        output.release_all().unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                OutputEvent::Key { key: f5, down: false },
+                OutputEvent::Key { key: ctrl, down: false },
+                OutputEvent::Button { button: MouseButton::Right, down: false },
+            ]
+        );
+        sent.lock().unwrap().clear();
+        output.release_all().unwrap();
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_spin_events() {
+        let spin = |output, steps| SpinEvent { pad: crate::gestures::Pad::Right, output, steps, haptics: false };
+        assert_eq!(spin_events(&spin(SpinOutput::Scroll, 2)).unwrap(), vec![OutputEvent::Scroll { amount: -2 }]);
+
+        let down = key_code("volumedown").unwrap();
+        let tap = [OutputEvent::Key { key: down, down: true }, OutputEvent::Key { key: down, down: false }];
+        assert_eq!(spin_events(&spin(SpinOutput::Volume, -2)).unwrap(), [tap, tap].concat());
+
+        let ctrl = key_code("ctrl").unwrap();
+        assert_eq!(
+            spin_events(&spin(SpinOutput::Zoom, 1)).unwrap(),
+            vec![
+                OutputEvent::Key { key: ctrl, down: true },
+                OutputEvent::Scroll { amount: 1 },
+                OutputEvent::Key { key: ctrl, down: false },
+            ]
+        );
+        assert!(spin_events(&spin(SpinOutput::Zoom, 0)).unwrap().is_empty());
+    }
+}
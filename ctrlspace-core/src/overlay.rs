@@ -0,0 +1,308 @@
+//! Overlay events: a stable, documented stream describing what the mapping is
+//! doing, so third-party in-game overlays can render hints without scraping
+//! our UI. Events serialize as JSON objects tagged by `type`:
+//!
+//! ```text
+//! {"type":"profile_activated","profile":"FPS (Gyro)","bindings":[{"input":"a","description":"Key space"}, ...]}
+//! {"type":"layer_changed","layer":"Scroll","bindings":[...]}     // layer null = base layer
+//! {"type":"menu_opened","menu":"Weapons","items":["Rifle","Pistol"]}
+//! {"type":"menu_selection","menu":"Weapons","index":1,"item":"Pistol"}
+//! {"type":"menu_closed","menu":"Weapons","selected":"Pistol"}    // selected null = cancelled
+//! {"type":"keyboard_opened","page":"letters","shift":false,"petals":[["a","b","c","d"], ...]}
+//! {"type":"keyboard_highlight","petal":1,"key":2}                // null = nothing selected
+//! {"type":"keyboard_closed"}
+//! ```
+//!
+//! A new subscriber first receives the events describing the current state
+//! (active profile, layer, open menu and keyboard), then live events.
+
+use serde::Serialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use crate::daisywheel::KeyboardPage;
+use crate::edge_spin::SpinOutput;
+use crate::profiles::{Action, AnalogMode, MouseButton, Profile};
+
+/// Event name used on the pipe/D-Bus/WebSocket transports
+pub const OVERLAY_EVENT: &str = "overlay";
+
+/// What one input currently does, in words
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BindingDescription {
+    pub input: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OverlayEvent {
+    ProfileActivated {
+        profile: String,
+        bindings: Vec<BindingDescription>,
+    },
+    /// `layer` is `None` when back on the base layer
+    LayerChanged {
+        layer: Option<String>,
+        bindings: Vec<BindingDescription>,
+    },
+    MenuOpened {
+        menu: String,
+        items: Vec<String>,
+    },
+    /// The highlighted slice/item changed
+    MenuSelection {
+        menu: String,
+        index: usize,
+        item: String,
+    },
+    /// `selected` is `None` when the menu was cancelled
+    MenuClosed {
+        menu: String,
+        selected: Option<String>,
+    },
+    /// On-screen keyboard shown, or its page/shift state changed. `petals`
+    /// run clockwise from the top, each listing keys up, right, down, left.
+    KeyboardOpened {
+        page: KeyboardPage,
+        shift: bool,
+        petals: Vec<Vec<String>>,
+    },
+    /// Petal/key currently under the thumbs
+    KeyboardHighlight {
+        petal: Option<usize>,
+        key: Option<usize>,
+    },
+    KeyboardClosed,
+}
+
+pub fn describe_action(action: &Action) -> String {
+    match action {
+        Action::None => "Nothing".to_string(),
+        Action::Key { key } => format!("Key {}", key),
+        Action::Keys { keys } => format!("Keys {}", keys.join("+")),
+        Action::Mouse { button } => format!("Mouse {}", describe_mouse_button(*button)),
+        Action::DoubleClick { button } => format!("Double click {}", describe_mouse_button(*button)),
+        Action::Scroll { amount } if *amount >= 0 => format!("Scroll up {}", amount),
+        Action::Scroll { amount } => format!("Scroll down {}", -amount),
+        Action::SlowMode { factor } => format!("Slow mode {}%", (factor * 100.0).round()),
+        Action::Plugin { plugin, action } => format!("{}: {}", plugin, action),
+    }
+}
+
+fn describe_mouse_button(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    }
+}
+
+fn describe_spin_output(output: SpinOutput) -> &'static str {
+    match output {
+        SpinOutput::Scroll => "scroll",
+        SpinOutput::Volume => "volume",
+        SpinOutput::Zoom => "zoom",
+    }
+}
+
+pub fn describe_analog(mode: &AnalogMode) -> String {
+    match mode {
+        AnalogMode::Disabled => "Disabled".to_string(),
+        AnalogMode::Mouse { .. } => "Mouse".to_string(),
+        AnalogMode::Scroll { .. } => "Scroll".to_string(),
+        AnalogMode::EdgeSpin { output, .. } => format!("Rim spin: {}", describe_spin_output(*output)),
+        AnalogMode::Stick { stick, .. } => format!("{:?} stick", stick),
+        AnalogMode::GyroMouse { .. } => "Gyro mouse".to_string(),
+        AnalogMode::Trackball { .. } => "Trackball".to_string(),
+        AnalogMode::TouchMenu { items, .. } => format!("Touch menu: {} items", items.len()),
+        AnalogMode::Directional { up, down, left, right } => format!(
+            "Up: {}, Down: {}, Left: {}, Right: {}",
+            describe_action(up),
+            describe_action(down),
+            describe_action(left),
+            describe_action(right)
+        ),
+    }
+}
+
+/// Descriptions of every bound input in a profile, in a stable order
+pub fn describe_bindings(profile: &Profile) -> Vec<BindingDescription> {
+    let mut out: Vec<BindingDescription> = profile
+        .buttons
+        .iter()
+        .map(|(button, action)| BindingDescription {
+            input: serde_json::to_value(button)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            description: describe_action(action),
+        })
+        .collect();
+
+    out.extend(profile.gestures.iter().map(|(gesture, action)| BindingDescription {
+        input: serde_json::to_value(gesture)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default(),
+        description: describe_action(action),
+    }));
+
+    let analogs = [
+        ("left_pad", &profile.left_pad),
+        ("right_pad", &profile.right_pad),
+        ("stick", &profile.stick),
+        ("gyro", &profile.gyro),
+    ];
+    for (input, mode) in analogs {
+        if *mode != AnalogMode::Disabled {
+            out.push(BindingDescription {
+                input: input.to_string(),
+                description: describe_analog(mode),
+            });
+        }
+    }
+
+    out
+}
+
+#[derive(Default)]
+struct OverlayState {
+    profile: Option<OverlayEvent>,
+    layer: Option<OverlayEvent>,
+    menu: Option<OverlayEvent>,
+    keyboard: Option<OverlayEvent>,
+}
+
+/// Fans overlay events out to any number of subscribers
+#[derive(Default)]
+pub struct OverlayBus {
+    subscribers: Mutex<Vec<Sender<OverlayEvent>>>,
+    state: Mutex<OverlayState>,
+}
+
+impl OverlayBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn publish(&self, event: OverlayEvent) {
+        {
+            let mut state = self.state.lock().unwrap();
+            match &event {
+                OverlayEvent::ProfileActivated { .. } => {
+                    state.profile = Some(event.clone());
+                    state.layer = None;
+                }
+                OverlayEvent::LayerChanged { layer, .. } => {
+                    state.layer = layer.as_ref().map(|_| event.clone());
+                }
+                OverlayEvent::MenuOpened { .. } => state.menu = Some(event.clone()),
+                OverlayEvent::MenuSelection { .. } => {}
+                OverlayEvent::MenuClosed { .. } => state.menu = None,
+                OverlayEvent::KeyboardOpened { .. } => state.keyboard = Some(event.clone()),
+                OverlayEvent::KeyboardHighlight { .. } => {}
+                OverlayEvent::KeyboardClosed => state.keyboard = None,
+            }
+        }
+
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Events that reproduce the current state, oldest first
+    pub fn snapshot(&self) -> Vec<OverlayEvent> {
+        let state = self.state.lock().unwrap();
+        [&state.profile, &state.layer, &state.menu, &state.keyboard]
+            .iter()
+            .filter_map(|e| (*e).clone())
+            .collect()
+    }
+
+    /// Receive the current state followed by every new event
+    pub fn subscribe(&self) -> Receiver<OverlayEvent> {
+        let (tx, rx) = mpsc::channel();
+        // Hold the subscriber list while replaying so no live event is missed
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for event in self.snapshot() {
+            let _ = tx.send(event);
+        }
+        subscribers.push(tx);
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::get_preset;
+
+    fn layer(name: Option<&str>) -> OverlayEvent {
+        OverlayEvent::LayerChanged {
+            layer: name.map(str::to_string),
+            bindings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_describe_bindings() {
+        let profile = get_preset("desktop").unwrap().profile;
+        let bindings = describe_bindings(&profile);
+
+        let find = |input: &str| bindings.iter().find(|b| b.input == input).map(|b| b.description.clone());
+        assert_eq!(find("a").as_deref(), Some("Key enter"));
+        assert_eq!(find("select").as_deref(), Some("Keys alt+tab"));
+        assert_eq!(find("rt").as_deref(), Some("Mouse left"));
+        assert_eq!(find("right_pad").as_deref(), Some("Mouse"));
+        assert_eq!(find("gyro"), None);
+    }
+
+    #[test]
+    fn test_late_subscriber_gets_current_state() {
+        let bus = OverlayBus::new();
+        bus.publish(OverlayEvent::ProfileActivated {
+            profile: "Desktop".to_string(),
+            bindings: Vec::new(),
+        });
+        bus.publish(layer(Some("Scroll")));
+        bus.publish(OverlayEvent::MenuOpened {
+            menu: "Apps".to_string(),
+            items: vec!["Browser".to_string()],
+        });
+        bus.publish(OverlayEvent::MenuClosed {
+            menu: "Apps".to_string(),
+            selected: None,
+        });
+
+        let rx = bus.subscribe();
+        let replay: Vec<OverlayEvent> = rx.try_iter().collect();
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replay[1], layer(Some("Scroll")));
+
+        bus.publish(layer(None));
+        assert_eq!(rx.try_recv().unwrap(), layer(None));
+        assert_eq!(bus.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn test_dropped_subscribers_are_pruned() {
+        let bus = OverlayBus::new();
+        drop(bus.subscribe());
+        let _kept = bus.subscribe();
+        bus.publish(layer(None));
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_event_json_shape() {
+        let json = serde_json::to_value(OverlayEvent::MenuSelection {
+            menu: "Weapons".to_string(),
+            index: 1,
+            item: "Pistol".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["type"], "menu_selection");
+        assert_eq!(json["index"], 1);
+    }
+}
@@ -0,0 +1,439 @@
+//! WASM plugins, so third parties can extend CtrlSpace without forking it.
+//! A plugin is a directory holding `plugin.json` (the manifest) and
+//! `plugin.wasm`, run in an interpreter with no WASI: everything it can do
+//! goes through the host functions below, each gated by a capability the
+//! user has granted.
+//!
+//! Interface (ABI version 1); payloads are UTF-8 JSON in plugin memory:
+//!
+//! - exports `memory` and `ctrlspace_alloc(len) -> ptr` for the host to write into
+//! - `ctrlspace_on_frame(ptr, len)` gets each input frame (`read_input`)
+//! - `ctrlspace_on_action(ptr, len)` gets each `binding-action` whose action
+//!   is one the manifest contributes, `{"type": "plugin", ...}` (`bindings`)
+//! - imports from module `ctrlspace`: `log(ptr, len)` and `emit(ptr, len)`,
+//!   where `emit` takes a [`PluginEmit`]: key/mouse presses (`send_input`) or
+//!   values for a declared virtual output (`virtual_outputs`)
+//!
+//! Each call runs on a fuel budget and memory is capped; a trap, an exhausted
+//! budget or an ungranted host call stops the plugin until plugins are
+//! reloaded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use wasmi::core::Trap;
+use wasmi::{Caller, Config, Engine, Extern, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use crate::input_parser::ControllerInput;
+use crate::layers::BindingEvent;
+use crate::profiles::Action;
+
+pub const PLUGINS_DIR_NAME: &str = "plugins";
+pub const PLUGIN_MANIFEST_FILE: &str = "plugin.json";
+pub const PLUGIN_MODULE_FILE: &str = "plugin.wasm";
+
+/// Interface version plugins are built against
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Event name for what plugins emit
+pub const PLUGIN_OUTPUT_EVENT: &str = "plugin-output";
+
+/// Instructions one call may run
+const FUEL_PER_CALL: u64 = 1_000_000;
+
+/// Largest payload a plugin may pass to a host function
+const MAX_EMIT_BYTES: usize = 64 * 1024;
+
+/// Most linear memory a plugin may declare or grow to
+const MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Receive every input frame
+    ReadInput,
+    /// Press keys and mouse buttons
+    SendInput,
+    /// Contribute actions profiles can bind
+    Bindings,
+    /// Drive the virtual outputs the manifest declares
+    VirtualOutputs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Interface version the module was built against
+    pub abi: u32,
+    /// What the plugin asks for; it only gets what the user grants
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    /// Bindable actions, e.g. `"next_weapon"`
+    #[serde(default)]
+    pub actions: Vec<String>,
+    /// Virtual outputs it drives, e.g. `"light_bar"`
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+impl PluginManifest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Plugin name must not be empty".to_string());
+        }
+        if self.abi != PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "Plugin '{}' targets interface version {}, expected {}",
+                self.name, self.abi, PLUGIN_ABI_VERSION
+            ));
+        }
+        if !self.actions.is_empty() && !self.capabilities.contains(&Capability::Bindings) {
+            return Err(format!("Plugin '{}' contributes actions without the bindings capability", self.name));
+        }
+        if !self.outputs.is_empty() && !self.capabilities.contains(&Capability::VirtualOutputs) {
+            return Err(format!("Plugin '{}' declares outputs without the virtual_outputs capability", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// What a plugin passes to `emit`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginEmit {
+    Press { action: Action },
+    Release { action: Action },
+    /// New value for one of its virtual outputs
+    Output { output: String, value: serde_json::Value },
+}
+
+/// Payload of `PLUGIN_OUTPUT_EVENT`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PluginOutput {
+    pub plugin: String,
+    #[serde(flatten)]
+    pub emit: PluginEmit,
+}
+
+/// What one call did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginEffects {
+    pub emits: Vec<PluginEmit>,
+    pub logs: Vec<String>,
+}
+
+/// A plugin as shown in the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginInfo {
+    pub manifest: PluginManifest,
+    /// Requested capabilities the user granted
+    pub granted: Vec<Capability>,
+    /// Why the plugin stopped, if it did
+    pub error: Option<String>,
+}
+
+struct HostState {
+    manifest: PluginManifest,
+    granted: Vec<Capability>,
+    effects: PluginEffects,
+    limits: StoreLimits,
+}
+
+fn read_payload(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, Trap> {
+    let memory = match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => memory,
+        _ => return Err(Trap::new("plugin exports no memory")),
+    };
+    let len = usize::try_from(len).map_err(|_| Trap::new("negative length"))?;
+    if len > MAX_EMIT_BYTES {
+        return Err(Trap::new(format!("payload over {} bytes", MAX_EMIT_BYTES)));
+    }
+    let mut buffer = vec![0; len];
+    memory
+        .read(caller, ptr as u32 as usize, &mut buffer)
+        .map_err(|e| Trap::new(e.to_string()))?;
+    Ok(buffer)
+}
+
+fn host_emit(caller: &mut Caller<'_, HostState>, payload: &[u8]) -> Result<(), Trap> {
+    let emit: PluginEmit =
+        serde_json::from_slice(payload).map_err(|e| Trap::new(format!("invalid emit: {}", e)))?;
+    let state = caller.data_mut();
+    let needed = match &emit {
+        PluginEmit::Press { action } | PluginEmit::Release { action } => {
+            action.validate().map_err(Trap::new)?;
+            Capability::SendInput
+        }
+        PluginEmit::Output { output, .. } => {
+            if !state.manifest.outputs.contains(output) {
+                return Err(Trap::new(format!("undeclared output '{}'", output)));
+            }
+            Capability::VirtualOutputs
+        }
+    };
+    if !state.granted.contains(&needed) {
+        return Err(Trap::new(format!("capability {:?} not granted", needed)));
+    }
+    state.effects.emits.push(emit);
+    Ok(())
+}
+
+/// A loaded plugin instance
+pub struct Plugin {
+    store: Store<HostState>,
+    instance: Instance,
+    error: Option<String>,
+}
+
+impl Plugin {
+    /// Load the plugin in `dir` with the capabilities the user granted it,
+    /// looked up in `grants` by plugin name
+    pub fn load(dir: &Path, grants: &BTreeMap<String, Vec<Capability>>) -> Result<Self, String> {
+        let manifest_path = dir.join(PLUGIN_MANIFEST_FILE);
+        let json = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest: PluginManifest = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid plugin manifest {}: {}", manifest_path.display(), e))?;
+        let module_path = dir.join(PLUGIN_MODULE_FILE);
+        let wasm = fs::read(&module_path).map_err(|e| format!("Failed to read {}: {}", module_path.display(), e))?;
+        let granted = grants.get(&manifest.name).cloned().unwrap_or_default();
+        Self::new(manifest, &wasm, &granted)
+    }
+
+    pub fn new(manifest: PluginManifest, wasm: &[u8], granted: &[Capability]) -> Result<Self, String> {
+        manifest.validate()?;
+        let name = manifest.name.clone();
+        let granted = manifest
+            .capabilities
+            .iter()
+            .copied()
+            .filter(|capability| granted.contains(capability))
+            .collect();
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &mut &wasm[..])
+            .map_err(|e| format!("Plugin '{}' is not a valid module: {}", name, e))?;
+        let mut store = Store::new(
+            &engine,
+            HostState {
+                manifest,
+                granted,
+                effects: PluginEffects::default(),
+                limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+
+        let mut linker = <Linker<HostState>>::new(&engine);
+        linker
+            .func_wrap("ctrlspace", "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let text = String::from_utf8_lossy(&read_payload(&caller, ptr, len)?).into_owned();
+                caller.data_mut().effects.logs.push(text);
+                Ok(())
+            })
+            .and_then(|linker| {
+                linker.func_wrap("ctrlspace", "emit", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let payload = read_payload(&caller, ptr, len)?;
+                    host_emit(&mut caller, &payload)
+                })
+            })
+            .map_err(|e| e.to_string())?;
+
+        store.add_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Plugin '{}' failed to start: {}", name, e))?;
+        Ok(Self { store, instance, error: None })
+    }
+
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.store.data().manifest
+    }
+
+    pub fn info(&self) -> PluginInfo {
+        PluginInfo {
+            manifest: self.manifest().clone(),
+            granted: self.store.data().granted.clone(),
+            error: self.error.clone(),
+        }
+    }
+
+    fn has(&self, capability: Capability) -> bool {
+        self.store.data().granted.contains(&capability)
+    }
+
+    /// Whether this plugin contributes `action`
+    pub fn provides(&self, action: &str) -> bool {
+        self.has(Capability::Bindings) && self.manifest().actions.iter().any(|a| a == action)
+    }
+
+    pub fn on_frame(&mut self, input: &ControllerInput) -> Result<PluginEffects, String> {
+        if !self.has(Capability::ReadInput) {
+            return Ok(PluginEffects::default());
+        }
+        self.call("ctrlspace_on_frame", input)
+    }
+
+    pub fn on_action(&mut self, event: &BindingEvent) -> Result<PluginEffects, String> {
+        if !self.has(Capability::Bindings) {
+            return Ok(PluginEffects::default());
+        }
+        self.call("ctrlspace_on_action", event)
+    }
+
+    // Copy `arg` into plugin memory as JSON and call `export` with it; the
+    // first failure stops the plugin
+    fn call<T: Serialize>(&mut self, export: &str, arg: &T) -> Result<PluginEffects, String> {
+        if self.error.is_some() {
+            return Ok(PluginEffects::default());
+        }
+        let result = self.try_call(export, arg);
+        let effects = std::mem::take(&mut self.store.data_mut().effects);
+        match result {
+            Ok(()) => Ok(effects),
+            Err(e) => {
+                let e = format!("Plugin '{}' stopped in {}: {}", self.manifest().name, export, e);
+                self.error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    fn try_call<T: Serialize>(&mut self, export: &str, arg: &T) -> Result<(), String> {
+        let function = match self.instance.get_typed_func::<(i32, i32), ()>(&self.store, export) {
+            Ok(function) => function,
+            // Hooks are optional
+            Err(_) => return Ok(()),
+        };
+        let payload = serde_json::to_vec(arg).map_err(|e| e.to_string())?;
+        let len = i32::try_from(payload.len()).map_err(|_| "payload too large".to_string())?;
+
+        // Top up to a fresh budget for this call
+        let remaining = self.store.consume_fuel(0).map_err(|e| e.to_string())?;
+        self.store
+            .add_fuel(FUEL_PER_CALL.saturating_sub(remaining))
+            .map_err(|e| e.to_string())?;
+
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "ctrlspace_alloc")
+            .map_err(|_| "missing export ctrlspace_alloc".to_string())?;
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| "missing export memory".to_string())?;
+        let ptr = alloc.call(&mut self.store, len).map_err(|e| e.to_string())?;
+        memory
+            .write(&mut self.store, ptr as u32 as usize, &payload)
+            .map_err(|e| e.to_string())?;
+        function.call(&mut self.store, (ptr, len)).map_err(|e| e.to_string())
+    }
+}
+
+/// Load every plugin under `dir` (one subdirectory each); plugins that fail
+/// to load are reported, not fatal
+pub fn load_plugins(dir: &Path, grants: &BTreeMap<String, Vec<Capability>>) -> (Vec<Plugin>, Vec<String>) {
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (plugins, errors),
+        Err(e) => return (plugins, vec![format!("Failed to read {}: {}", dir.display(), e)]),
+    };
+    let mut dirs: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    dirs.sort();
+    for path in dirs {
+        match Plugin::load(&path, grants) {
+            Ok(plugin) if plugins.iter().any(|p: &Plugin| p.manifest().name == plugin.manifest().name) => {
+                errors.push(format!("Duplicate plugin name '{}' in {}", plugin.manifest().name, path.display()))
+            }
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => errors.push(e),
+        }
+    }
+    (plugins, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Echoes each frame to the log, emits whatever JSON sits at offset 16, and
+    // spins forever on the second action
+    const ECHO_PLUGIN: &str = r#"(module
+        (import "ctrlspace" "log" (func $log (param i32 i32)))
+        (import "ctrlspace" "emit" (func $emit (param i32 i32)))
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (global $actions (mut i32) (i32.const 0))
+        (data (i32.const 16) "{\"type\":\"press\",\"action\":{\"type\":\"key\",\"key\":\"w\"}}")
+        (func (export "ctrlspace_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+        (func (export "ctrlspace_on_frame") (param i32 i32)
+            (call $log (local.get 0) (local.get 1)))
+        (func (export "ctrlspace_on_action") (param i32 i32)
+            (global.set $actions (i32.add (global.get $actions) (i32.const 1)))
+            (if (i32.gt_u (global.get $actions) (i32.const 1)) (then (loop $spin (br $spin))))
+            (call $emit (i32.const 16) (i32.const 50))))"#;
+
+    fn manifest() -> PluginManifest {
+        PluginManifest {
+            name: "echo".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            abi: PLUGIN_ABI_VERSION,
+            capabilities: vec![Capability::ReadInput, Capability::Bindings, Capability::SendInput],
+            actions: vec!["boost".to_string()],
+            outputs: Vec::new(),
+        }
+    }
+
+    fn action_event() -> BindingEvent {
+        BindingEvent {
+            button: crate::profiles::ButtonId::A,
+            action: Action::Plugin { plugin: "echo".to_string(), action: "boost".to_string() },
+            phase: crate::layers::BindingPhase::Pressed,
+            layer: None,
+        }
+    }
+
+    #[test]
+    fn test_plugin_calls_are_capability_gated() {
+        let wasm = wat::parse_str(ECHO_PLUGIN).unwrap();
+
+        let all = [Capability::ReadInput, Capability::Bindings, Capability::SendInput];
+        let mut plugin = Plugin::new(manifest(), &wasm, &all).unwrap();
+        let logged = plugin.on_frame(&ControllerInput::default()).unwrap().logs;
+        assert!(logged[0].contains("\"buttons\""));
+        let emitted = plugin.on_action(&action_event()).unwrap().emits;
+        assert_eq!(emitted, vec![PluginEmit::Press { action: Action::Key { key: "w".to_string() } }]);
+
+        // A runaway call runs out of fuel and stops the plugin
+        assert!(plugin.on_action(&action_event()).is_err());
+        assert!(plugin.info().error.is_some());
+
+        // Without send_input, emitting a key press traps
+        let mut plugin = Plugin::new(manifest(), &wasm, &[Capability::Bindings]).unwrap();
+        assert!(plugin.on_frame(&ControllerInput::default()).unwrap().logs.is_empty());
+        assert!(plugin.on_action(&action_event()).unwrap_err().contains("not granted"));
+    }
+
+    #[test]
+    fn test_plugin_memory_is_capped() {
+        // 512 pages is 32 MiB, over the cap
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 512))"#).unwrap();
+        assert!(Plugin::new(manifest(), &wasm, &[]).is_err());
+
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+        assert!(Plugin::new(manifest(), &wasm, &[]).is_ok());
+    }
+}
@@ -0,0 +1,162 @@
+//! Reserved controller chord for switching profiles: hold the modifier
+//! (Steam by default) and click a left-pad d-pad direction to cycle or jump.
+//! Checked before normal binding resolution so the chord never leaks into the
+//! active profile's bindings.
+
+use serde::{Deserialize, Serialize};
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{ButtonId, Profile};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SwitchTarget {
+    Next,
+    Previous,
+    /// Jump straight to a profile by name
+    Profile { name: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DpadDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Which d-pad direction does what while the modifier is held
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSwitchConfig {
+    pub modifier: ButtonId,
+    pub up: Option<SwitchTarget>,
+    pub down: Option<SwitchTarget>,
+    pub left: Option<SwitchTarget>,
+    pub right: Option<SwitchTarget>,
+}
+
+impl Default for ProfileSwitchConfig {
+    fn default() -> Self {
+        Self {
+            modifier: ButtonId::Steam,
+            up: None,
+            down: None,
+            left: Some(SwitchTarget::Previous),
+            right: Some(SwitchTarget::Next),
+        }
+    }
+}
+
+impl ProfileSwitchConfig {
+    pub fn target(&self, direction: DpadDirection) -> Option<&SwitchTarget> {
+        match direction {
+            DpadDirection::Up => self.up.as_ref(),
+            DpadDirection::Down => self.down.as_ref(),
+            DpadDirection::Left => self.left.as_ref(),
+            DpadDirection::Right => self.right.as_ref(),
+        }
+    }
+}
+
+/// D-pad quadrant of a left-pad position (dominant axis wins)
+pub fn dpad_direction(pad: &TrackpadData) -> Option<DpadDirection> {
+    if !pad.active || (pad.x == 0 && pad.y == 0) {
+        return None;
+    }
+    let (x, y) = (pad.x as i32, pad.y as i32);
+    Some(if x.abs() >= y.abs() {
+        if x > 0 { DpadDirection::Right } else { DpadDirection::Left }
+    } else if y > 0 {
+        DpadDirection::Up
+    } else {
+        DpadDirection::Down
+    })
+}
+
+/// Tracks the chord across frames so each click switches exactly once
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSwitchChord {
+    config: ProfileSwitchConfig,
+    pressed: Option<DpadDirection>,
+}
+
+impl ProfileSwitchChord {
+    pub fn new(config: ProfileSwitchConfig) -> Self {
+        Self { config, pressed: None }
+    }
+
+    pub fn config(&self) -> &ProfileSwitchConfig {
+        &self.config
+    }
+
+    /// Feed the latest frame; returns a target on the frame a direction is clicked
+    pub fn update(&mut self, input: &ControllerInput) -> Option<SwitchTarget> {
+        let held = self.config.modifier.is_pressed(&input.buttons) && input.buttons.lpad_click;
+        let direction = if held { dpad_direction(&input.left_trackpad) } else { None };
+
+        let changed = direction.is_some() && direction != self.pressed;
+        self.pressed = direction;
+        if !changed {
+            return None;
+        }
+        direction.and_then(|d| self.config.target(d).cloned())
+    }
+}
+
+/// Pick the profile a target refers to. `Next`/`Previous` wrap around and
+/// start from the first profile when none is active.
+pub fn resolve_target<'p>(target: &SwitchTarget, profiles: &'p [Profile], active: Option<&str>) -> Option<&'p Profile> {
+    if profiles.is_empty() {
+        return None;
+    }
+    let current = active.and_then(|name| profiles.iter().position(|p| p.name == name));
+
+    match target {
+        SwitchTarget::Profile { name } => profiles.iter().find(|p| &p.name == name),
+        SwitchTarget::Next => Some(&profiles[current.map(|i| (i + 1) % profiles.len()).unwrap_or(0)]),
+        SwitchTarget::Previous => Some(
+            &profiles[current
+                .map(|i| (i + profiles.len() - 1) % profiles.len())
+                .unwrap_or(0)],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(steam: bool, click: bool, x: i16, y: i16) -> ControllerInput {
+        let mut input = ControllerInput::default();
+        input.buttons.steam = steam;
+        input.buttons.lpad_click = click;
+        input.left_trackpad = TrackpadData { x, y, active: true };
+        input
+    }
+
+    #[test]
+    fn test_chord_switches_once_per_click() {
+        let mut chord = ProfileSwitchChord::default();
+
+        assert_eq!(chord.update(&frame(false, true, 20000, 0)), None);
+        assert_eq!(chord.update(&frame(true, true, 20000, 0)), Some(SwitchTarget::Next));
+        assert_eq!(chord.update(&frame(true, true, 21000, 500)), None);
+        assert_eq!(chord.update(&frame(true, false, 0, 0)), None);
+        assert_eq!(chord.update(&frame(true, true, -20000, 0)), Some(SwitchTarget::Previous));
+        // Up is unbound by default
+        assert_eq!(chord.update(&frame(true, true, 0, 20000)), None);
+    }
+
+    #[test]
+    fn test_resolve_target_wraps() {
+        let profiles: Vec<Profile> = ["A", "B", "C"].iter().map(|n| Profile::new(n)).collect();
+        let name = |p: Option<&Profile>| p.map(|p| p.name.clone());
+
+        assert_eq!(name(resolve_target(&SwitchTarget::Next, &profiles, None)), Some("A".to_string()));
+        assert_eq!(name(resolve_target(&SwitchTarget::Next, &profiles, Some("C"))), Some("A".to_string()));
+        assert_eq!(name(resolve_target(&SwitchTarget::Previous, &profiles, Some("A"))), Some("C".to_string()));
+        let jump = SwitchTarget::Profile { name: "B".to_string() };
+        assert_eq!(name(resolve_target(&jump, &profiles, Some("A"))), Some("B".to_string()));
+        assert!(resolve_target(&SwitchTarget::Next, &[], None).is_none());
+    }
+}
@@ -0,0 +1,568 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::cursor::CursorMode;
+use crate::curve::Curve;
+use crate::edge_spin::SpinOutput;
+use crate::gestures::GestureId;
+use crate::gyro_mouse::GyroActivation;
+use crate::haptics::HapticFeedback;
+use crate::input_parser::ButtonState;
+use crate::layers::{validate_layers, Layer, RepeatRate, ToggleBinding};
+use crate::mirror::Mirror;
+use crate::response::{AnalogResponse, AnalogSource};
+use crate::rumble::RumbleTuning;
+use crate::touch_menu::{validate_menu, MenuItem, MenuSelect};
+use crate::virtual_gamepad::GamepadStick;
+
+/// Bumped whenever the on-disk profile format changes incompatibly
+pub const PROFILE_VERSION: u32 = 1;
+
+/// Subdirectory of the app config dir holding user profiles
+pub const PROFILES_DIR_NAME: &str = "profiles";
+
+/// Physical buttons a profile can bind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonId {
+    A,
+    B,
+    X,
+    Y,
+    Lb,
+    Rb,
+    Lt,
+    Rt,
+    Lgrip,
+    Rgrip,
+    Start,
+    Select,
+    Steam,
+    LpadClick,
+    RpadClick,
+    StickClick,
+}
+
+impl ButtonId {
+    pub const ALL: [ButtonId; 16] = [
+        ButtonId::A,
+        ButtonId::B,
+        ButtonId::X,
+        ButtonId::Y,
+        ButtonId::Lb,
+        ButtonId::Rb,
+        ButtonId::Lt,
+        ButtonId::Rt,
+        ButtonId::Lgrip,
+        ButtonId::Rgrip,
+        ButtonId::Start,
+        ButtonId::Select,
+        ButtonId::Steam,
+        ButtonId::LpadClick,
+        ButtonId::RpadClick,
+        ButtonId::StickClick,
+    ];
+
+    pub fn is_pressed(self, buttons: &ButtonState) -> bool {
+        match self {
+            ButtonId::A => buttons.a,
+            ButtonId::B => buttons.b,
+            ButtonId::X => buttons.x,
+            ButtonId::Y => buttons.y,
+            ButtonId::Lb => buttons.lb,
+            ButtonId::Rb => buttons.rb,
+            ButtonId::Lt => buttons.lt,
+            ButtonId::Rt => buttons.rt,
+            ButtonId::Lgrip => buttons.lgrip,
+            ButtonId::Rgrip => buttons.rgrip,
+            ButtonId::Start => buttons.start,
+            ButtonId::Select => buttons.select,
+            ButtonId::Steam => buttons.steam,
+            ButtonId::LpadClick => buttons.lpad_click,
+            ButtonId::RpadClick => buttons.rpad_click,
+            ButtonId::StickClick => buttons.stick_click,
+        }
+    }
+
+    pub fn set_pressed(self, buttons: &mut ButtonState, pressed: bool) {
+        let field = match self {
+            ButtonId::A => &mut buttons.a,
+            ButtonId::B => &mut buttons.b,
+            ButtonId::X => &mut buttons.x,
+            ButtonId::Y => &mut buttons.y,
+            ButtonId::Lb => &mut buttons.lb,
+            ButtonId::Rb => &mut buttons.rb,
+            ButtonId::Lt => &mut buttons.lt,
+            ButtonId::Rt => &mut buttons.rt,
+            ButtonId::Lgrip => &mut buttons.lgrip,
+            ButtonId::Rgrip => &mut buttons.rgrip,
+            ButtonId::Start => &mut buttons.start,
+            ButtonId::Select => &mut buttons.select,
+            ButtonId::Steam => &mut buttons.steam,
+            ButtonId::LpadClick => &mut buttons.lpad_click,
+            ButtonId::RpadClick => &mut buttons.rpad_click,
+            ButtonId::StickClick => &mut buttons.stick_click,
+        };
+        *field = pressed;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// What a digital input produces. Keys use names like "w", "space", "ctrl".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    None,
+    /// Single key
+    Key { key: String },
+    /// Keys pressed together, e.g. ["alt", "tab"]
+    Keys { keys: Vec<String> },
+    Mouse { button: MouseButton },
+    /// Two clicks in quick succession, e.g. for a double-tap gesture
+    DoubleClick { button: MouseButton },
+    /// Scroll wheel clicks; positive is up
+    Scroll { amount: i32 },
+    /// While held, scale cursor output by `factor` (0.3 = 30% speed)
+    SlowMode { factor: f32 },
+    /// An action contributed by a plugin (see plugins.rs)
+    Plugin { plugin: String, action: String },
+}
+
+impl Default for Action {
+    fn default() -> Self {
+        Action::None
+    }
+}
+
+impl Action {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Action::SlowMode { factor } if !(*factor > 0.0 && *factor <= 1.0) => {
+                Err(format!("Slow mode factor must be in (0, 1], got {}", factor))
+            }
+            Action::Plugin { plugin, action } if plugin.is_empty() || action.is_empty() => {
+                Err("Plugin actions need a plugin and an action name".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Anything a profile binds an action to, keyed by its name (`"a"`, `"right_double_tap"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BindingInput {
+    Button(ButtonId),
+    Gesture(GestureId),
+}
+
+impl From<ButtonId> for BindingInput {
+    fn from(button: ButtonId) -> Self {
+        BindingInput::Button(button)
+    }
+}
+
+impl From<GestureId> for BindingInput {
+    fn from(gesture: GestureId) -> Self {
+        BindingInput::Gesture(gesture)
+    }
+}
+
+/// How an analog source (pad, stick, gyro) is translated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AnalogMode {
+    Disabled,
+    /// Move the cursor (pads choose relative or anchored movement)
+    Mouse {
+        sensitivity: f32,
+        #[serde(default = "default_cursor_mode")]
+        cursor: CursorMode,
+        /// Relative-mode gain by movement speed; built-in acceleration if unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        acceleration: Option<Curve>,
+    },
+    /// Scroll wheel (vertical)
+    Scroll { sensitivity: f32 },
+    /// Four directional actions, e.g. WASD or arrow keys
+    Directional {
+        up: Action,
+        down: Action,
+        left: Action,
+        right: Action,
+    },
+    /// Circle the outer rim of a pad for continuous steps (pads only)
+    EdgeSpin {
+        output: SpinOutput,
+        /// Steps (and detent ticks) per full turn
+        detents_per_turn: u32,
+        haptics: bool,
+    },
+    /// Drive a stick of the virtual X360 pad, on top of its mapping
+    Stick { stick: GamepadStick, sensitivity: f32 },
+    /// Turn the controller to move the cursor (gyro only)
+    GyroMouse {
+        sensitivity: f32,
+        #[serde(default)]
+        invert_x: bool,
+        #[serde(default)]
+        invert_y: bool,
+        #[serde(default)]
+        activation: GyroActivation,
+    },
+    /// Relative cursor whose flicks keep spinning after lift-off (pads only)
+    Trackball {
+        sensitivity: f32,
+        /// How quickly the spin slows (per second)
+        #[serde(default = "default_trackball_friction")]
+        friction: f32,
+        /// Share of the previous frame's movement blended in, 0 up to 1
+        #[serde(default = "default_trackball_smoothing")]
+        smoothing: f32,
+    },
+    /// Radial menu: one item per sector, picked by click or lift-off (pads only)
+    TouchMenu {
+        items: Vec<MenuItem>,
+        #[serde(default)]
+        select_on: MenuSelect,
+        /// Tick on each highlight change and selection
+        #[serde(default)]
+        haptics: bool,
+    },
+}
+
+fn default_cursor_mode() -> CursorMode {
+    CursorMode::Relative
+}
+
+fn default_trackball_friction() -> f32 {
+    crate::mapping::DEFAULT_FRICTION
+}
+
+fn default_trackball_smoothing() -> f32 {
+    crate::mapping::DEFAULT_SMOOTHING
+}
+
+impl Default for AnalogMode {
+    fn default() -> Self {
+        AnalogMode::Disabled
+    }
+}
+
+/// A complete controller mapping
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub buttons: BTreeMap<ButtonId, Action>,
+    #[serde(default)]
+    pub left_pad: AnalogMode,
+    #[serde(default)]
+    pub right_pad: AnalogMode,
+    #[serde(default)]
+    pub stick: AnalogMode,
+    #[serde(default)]
+    pub gyro: AnalogMode,
+    /// Pad tap / double-tap / tap-drag bindings
+    #[serde(default)]
+    pub gestures: BTreeMap<GestureId, Action>,
+    /// How game force feedback is played on the pads
+    #[serde(default)]
+    pub rumble: RumbleTuning,
+    /// Feedback played when a bound button or gesture activates
+    #[serde(default)]
+    pub haptics: BTreeMap<BindingInput, HapticFeedback>,
+    /// Left-handed play: swap the controller's sides before applying the bindings
+    #[serde(default = "default_mirror")]
+    pub mirror: Mirror,
+    /// Dead zones and response curves for the stick, pads and triggers, applied before mapping
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub response: BTreeMap<AnalogSource, AnalogResponse>,
+    /// Named binding layers, entered with their activator button
+    #[serde(default)]
+    pub layers: BTreeMap<String, Layer>,
+    /// Auto-repeat for held key bindings; `None` disables repeat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<RepeatRate>,
+    /// Buttons whose presses toggle their binding on and off
+    #[serde(default)]
+    pub toggles: BTreeMap<ButtonId, ToggleBinding>,
+    /// Rhai source with `on_frame` / `on_binding` hooks (see scripting.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Steam games this profile is activated for automatically
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steam_app_ids: Vec<u32>,
+    /// Keep other processes from seeing the controller while this profile
+    /// is active (see exclusive.rs)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exclusive: bool,
+}
+
+fn default_version() -> u32 {
+    PROFILE_VERSION
+}
+
+fn default_mirror() -> Mirror {
+    Mirror::Off
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            name: name.to_string(),
+            description: String::new(),
+            buttons: BTreeMap::new(),
+            left_pad: AnalogMode::Disabled,
+            right_pad: AnalogMode::Disabled,
+            stick: AnalogMode::Disabled,
+            gyro: AnalogMode::Disabled,
+            gestures: BTreeMap::new(),
+            rumble: RumbleTuning::default(),
+            haptics: BTreeMap::new(),
+            mirror: Mirror::Off,
+            response: BTreeMap::new(),
+            layers: BTreeMap::new(),
+            repeat: None,
+            toggles: BTreeMap::new(),
+            script: None,
+            steam_app_ids: Vec::new(),
+            exclusive: false,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let profile: Profile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid profile: {}", e))?;
+        if profile.version > PROFILE_VERSION {
+            return Err(format!(
+                "Profile '{}' uses format version {}, newer than supported ({})",
+                profile.name, profile.version, PROFILE_VERSION
+            ));
+        }
+        profile.rumble.validate()?;
+        for feedback in profile.haptics.values() {
+            feedback.validate()?;
+        }
+        validate_layers(&profile.layers)?;
+        let layer_actions = profile.layers.values().flat_map(|layer| layer.buttons.values());
+        for action in profile.buttons.values().chain(profile.gestures.values()).chain(layer_actions) {
+            action.validate()?;
+        }
+        if let Some(repeat) = profile.repeat {
+            repeat.validate()?;
+        }
+        for toggle in profile.toggles.values() {
+            toggle.validate()?;
+        }
+        for response in profile.response.values() {
+            response.validate()?;
+        }
+        let layer_pads = profile.layers.values().flat_map(|layer| [&layer.left_pad, &layer.right_pad]).flatten();
+        for pad in [&profile.left_pad, &profile.right_pad].into_iter().chain(layer_pads) {
+            match pad {
+                AnalogMode::Trackball { friction, smoothing, .. } => crate::mapping::validate(*friction, *smoothing)?,
+                AnalogMode::TouchMenu { items, .. } => validate_menu(items)?,
+                _ => {}
+            }
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &profile.script {
+            crate::scripting::ScriptHost::compile(script)?;
+        }
+        Ok(profile)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize profile: {}", e))
+    }
+}
+
+/// A built-in profile shipped inside the binary
+#[derive(Debug, Clone, Serialize)]
+pub struct Preset {
+    pub id: &'static str,
+    pub profile: Profile,
+}
+
+/// Preset activated whenever no game profile is (see `desktop_profile`)
+pub const DESKTOP_PRESET_ID: &str = "desktop";
+
+// (id, json) - files live in ctrlspace-core/presets/
+const PRESET_SOURCES: &[(&str, &str)] = &[
+    ("fps-gyro", include_str!("../presets/fps-gyro.json")),
+    ("desktop", include_str!("../presets/desktop.json")),
+    ("platformer", include_str!("../presets/platformer.json")),
+    ("racing", include_str!("../presets/racing.json")),
+    ("one-handed-left", include_str!("../presets/one-handed-left.json")),
+    ("one-handed-right", include_str!("../presets/one-handed-right.json")),
+];
+
+/// All bundled presets
+pub fn list_presets() -> Vec<Preset> {
+    PRESET_SOURCES
+        .iter()
+        .filter_map(|(id, json)| match Profile::from_json(json) {
+            Ok(profile) => Some(Preset { id, profile }),
+            Err(e) => {
+                eprintln!("❌ Bundled preset '{}' is broken: {}", id, e);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn get_preset(id: &str) -> Option<Preset> {
+    list_presets().into_iter().find(|p| p.id == id)
+}
+
+/// The bundled desktop profile: right pad mouse, left pad scroll, stick arrows,
+/// face buttons as enter/escape/backspace/space, triggers as clicks
+pub fn desktop_profile() -> Option<Profile> {
+    get_preset(DESKTOP_PRESET_ID).map(|preset| preset.profile)
+}
+
+/// File stem for a profile name: lowercase ASCII alphanumerics joined by '-'
+pub fn profile_file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-').to_string();
+    if stem.is_empty() {
+        "profile".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Write a profile to `dir`, returning the file path
+pub fn save_profile(dir: &Path, profile: &Profile) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.json", profile_file_stem(&profile.name)));
+    fs::write(&path, profile.to_json()?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Every readable profile in `dir`, sorted by name. A missing directory is
+/// just an empty list; unreadable files are logged and skipped.
+pub fn load_profiles(dir: &Path) -> Result<Vec<Profile>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut profiles = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|json| Profile::from_json(&json)) {
+            Ok(profile) => profiles.push(profile),
+            Err(e) => eprintln!("❌ Skipping profile {}: {}", path.display(), e),
+        }
+    }
+
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+/// Save a profile without overwriting an existing one. The profile gets a
+/// numbered name ("Racing 2") if its name is already taken.
+pub fn save_new_profile(dir: &Path, mut profile: Profile) -> Result<(Profile, PathBuf), String> {
+    let base_name = profile.name.clone();
+    let mut n = 1;
+    while dir.join(format!("{}.json", profile_file_stem(&profile.name))).exists() {
+        n += 1;
+        profile.name = format!("{} {}", base_name, n);
+    }
+
+    let path = save_profile(dir, &profile)?;
+    Ok((profile, path))
+}
+
+/// Copy a preset into the user's profile directory
+pub fn clone_preset(id: &str, dir: &Path) -> Result<Profile, String> {
+    let preset = match get_preset(id) {
+        Some(preset) => preset,
+        None => return Err(format!("Unknown preset '{}'", id)),
+    };
+
+    let (profile, path) = save_new_profile(dir, preset.profile)?;
+    println!("✅ Cloned preset '{}' to {}", id, path.display());
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_presets_parse() {
+        let presets = list_presets();
+        assert_eq!(presets.len(), PRESET_SOURCES.len());
+
+        let fps = get_preset("fps-gyro").unwrap();
+        assert!(matches!(fps.profile.gyro, AnalogMode::Mouse { .. }));
+        assert!(get_preset("nope").is_none());
+        assert!(matches!(desktop_profile().unwrap().right_pad, AnalogMode::Mouse { .. }));
+
+        // One-handed presets keep the off-hand side unbound and reach it through a grip layer
+        let right = get_preset("one-handed-right").unwrap().profile;
+        assert!(right.buttons.keys().all(|b| !matches!(b, ButtonId::Lb | ButtonId::Lt | ButtonId::Lgrip)));
+        let moving = right.with_layer("Move").unwrap();
+        assert!(matches!(moving.right_pad, AnalogMode::Directional { .. }));
+    }
+
+    #[test]
+    fn test_profile_file_stem() {
+        assert_eq!(profile_file_stem("FPS (Gyro)"), "fps-gyro");
+        assert_eq!(profile_file_stem("  Racing 2 "), "racing-2");
+        assert_eq!(profile_file_stem("!!!"), "profile");
+    }
+
+    #[test]
+    fn test_clone_preset_picks_free_name() {
+        let dir = std::env::temp_dir().join(format!("ctrlspace-presets-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let first = clone_preset("racing", &dir).unwrap();
+        let second = clone_preset("racing", &dir).unwrap();
+        assert_eq!(second.name, format!("{} 2", first.name));
+
+        let saved = fs::read_to_string(dir.join(format!("{}.json", profile_file_stem(&second.name)))).unwrap();
+        assert_eq!(Profile::from_json(&saved).unwrap(), second);
+
+        assert!(clone_preset("nope", &dir).is_err());
+
+        let names: Vec<String> = load_profiles(&dir).unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec![first.name.clone(), second.name.clone()]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let json = format!(r#"{{"version": {}, "name": "Future"}}"#, PROFILE_VERSION + 1);
+        assert!(Profile::from_json(&json).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Reverse-engineering aid: a stream of only the report bytes that changed
+//! since the previous report, instead of full 64-byte dumps.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use crate::controller::ControllerHandle;
+
+/// Event name for raw diffs
+pub const RAW_DIFF_EVENT: &str = "raw-report-diff";
+
+/// Sequence number and timestamp - change on every report
+pub const DEFAULT_IGNORED_BYTES: &[usize] = &[1, 4, 5, 6, 7];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ByteChange {
+    pub index: usize,
+    pub old: u8,
+    pub new: u8,
+}
+
+/// One report's worth of changes (never empty)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RawDiff {
+    /// Reports read so far, including unchanged ones
+    pub report: u64,
+    pub changes: Vec<ByteChange>,
+}
+
+/// Bytes that differ between two reports, skipping `ignore`. A length change
+/// shows the missing side as 0.
+pub fn diff_reports(old: &[u8], new: &[u8], ignore: &[usize]) -> Vec<ByteChange> {
+    (0..old.len().max(new.len()))
+        .filter(|i| !ignore.contains(i))
+        .filter_map(|index| {
+            let old = old.get(index).copied().unwrap_or(0);
+            let new = new.get(index).copied().unwrap_or(0);
+            (old != new).then(|| ByteChange { index, old, new })
+        })
+        .collect()
+}
+
+/// Background reader emitting `RawDiff`s; the first report is the baseline
+pub struct RawDiffStream {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RawDiffStream {
+    /// `emit` returns `false` once the consumer is gone, which ends the stream
+    pub fn spawn<F>(manager: ControllerHandle, ignore: Vec<usize>, emit: F) -> Self
+    where
+        F: Fn(&RawDiff) -> bool + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = thread::spawn(move || {
+            let mut previous: Option<Vec<u8>> = None;
+            let mut report = 0u64;
+            println!("🔍 Raw diff stream started (ignoring bytes {:?})", ignore);
+
+            while !thread_stop.load(Ordering::Relaxed) && manager.is_connected() {
+                let timeout_ms = manager.read_settings().timeout_ms.max(1);
+                let raw = match manager.read_input_blocking(timeout_ms) {
+                    Ok(Some(raw)) => raw,
+                    _ => continue,
+                };
+                report += 1;
+
+                if let Some(previous) = &previous {
+                    let changes = diff_reports(previous, &raw, &ignore);
+                    if !changes.is_empty() && !emit(&RawDiff { report, changes }) {
+                        break;
+                    }
+                }
+                previous = Some(raw);
+            }
+            println!("✅ Raw diff stream stopped");
+        });
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Signal the reader thread to stop and wait for it
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Signal the reader thread to stop without waiting, for when it may be
+    /// blocked in a HID call that never returns
+    pub fn abandon(self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_reports() {
+        let old = [0x01, 0x10, 0x00, 0x00, 0xaa, 0x00, 0x00, 0x00];
+        let new = [0x01, 0x11, 0x80, 0x00, 0xab, 0x00, 0x00, 0x00, 0x05];
+
+        let changes = diff_reports(&old, &new, DEFAULT_IGNORED_BYTES);
+        assert_eq!(
+            changes,
+            vec![
+                ByteChange { index: 2, old: 0x00, new: 0x80 },
+                ByteChange { index: 8, old: 0x00, new: 0x05 },
+            ]
+        );
+        assert_eq!(diff_reports(&old, &old, &[]), vec![]);
+        assert_eq!(diff_reports(&old, &new, &[]).len(), 4);
+    }
+}
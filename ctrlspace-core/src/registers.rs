@@ -0,0 +1,258 @@
+//! The controller's settings register block, written with feature report 0x87.
+//!
+//! Each entry is a register number followed by a u16 LE value. Register numbers
+//! follow the firmware's settings table (as used by Steam and SDL).
+//! [`SettingsBuilder`] assembles a report from typed settings.
+
+use serde::{Deserialize, Serialize};
+use crate::gestures::Pad;
+
+/// Feature report that writes settings registers
+pub const SET_SETTINGS_VALUES: u8 = 0x87;
+
+// Feature reports are padded to the full 64-byte HID report
+const FEATURE_REPORT_SIZE: usize = 64;
+
+/// Registers that fit after the command and length bytes
+pub const MAX_REGISTERS_PER_REPORT: usize = (FEATURE_REPORT_SIZE - 2) / 3;
+
+/// Settings registers this driver writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    LeftTrackpadMode = 0x07,
+    RightTrackpadMode = 0x08,
+    SmoothAbsoluteMouse = 0x18,
+    EnableFastScan = 0x2f,
+    ImuMode = 0x30,
+    WirelessPacketVersion = 0x31,
+    SleepInactivityTimeout = 0x32,
+}
+
+// Trackpad mode value that stops the firmware from emulating a mouse/keys
+const TRACKPAD_MODE_NONE: u16 = 0x07;
+const WIRELESS_PACKET_VERSION: u16 = 0x02;
+const SLEEP_TIMEOUT_SECS: u16 = 900;
+
+// ImuMode bit flags
+const IMU_SEND_ORIENTATION: u16 = 0x04;
+const IMU_SEND_RAW_ACCEL: u16 = 0x08;
+const IMU_SEND_RAW_GYRO: u16 = 0x10;
+
+/// What IMU data the controller puts into input reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImuReporting {
+    Off,
+    /// Raw accelerometer and gyro
+    Raw,
+    /// Raw data plus the sensor-fusion orientation quaternion
+    RawWithOrientation,
+}
+
+impl ImuReporting {
+    fn register_value(self) -> u16 {
+        match self {
+            ImuReporting::Off => 0,
+            ImuReporting::Raw => IMU_SEND_RAW_ACCEL | IMU_SEND_RAW_GYRO,
+            ImuReporting::RawWithOrientation => {
+                IMU_SEND_RAW_ACCEL | IMU_SEND_RAW_GYRO | IMU_SEND_ORIENTATION
+            }
+        }
+    }
+}
+
+/// What the firmware makes of a trackpad while it emulates a mouse and keys
+/// (lizard mode). Raw pad positions are reported whatever the mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackpadMode {
+    AbsoluteMouse,
+    RelativeMouse,
+    DpadFourWay,
+    DpadEightWay,
+    /// No emulation
+    None,
+}
+
+impl TrackpadMode {
+    fn register_value(self) -> u16 {
+        match self {
+            TrackpadMode::AbsoluteMouse => 0x00,
+            TrackpadMode::RelativeMouse => 0x01,
+            TrackpadMode::DpadFourWay => 0x02,
+            TrackpadMode::DpadEightWay => 0x04,
+            TrackpadMode::None => TRACKPAD_MODE_NONE,
+        }
+    }
+}
+
+/// Typed contents of a 0x87 report. Registers go out in the order they were
+/// first set; setting one again replaces its value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsBuilder {
+    registers: Vec<(Register, u16)>,
+}
+
+impl SettingsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, register: Register, value: u16) -> Self {
+        match self.registers.iter_mut().find(|(r, _)| *r == register) {
+            Some(entry) => entry.1 = value,
+            None => self.registers.push((register, value)),
+        }
+        self
+    }
+
+    pub fn imu(self, imu: ImuReporting) -> Self {
+        self.set(Register::ImuMode, imu.register_value())
+    }
+
+    pub fn trackpad_mode(self, pad: Pad, mode: TrackpadMode) -> Self {
+        let register = match pad {
+            Pad::Left => Register::LeftTrackpadMode,
+            Pad::Right => Register::RightTrackpadMode,
+        };
+        self.set(register, mode.register_value())
+    }
+
+    pub fn smoothing(self, enabled: bool) -> Self {
+        self.set(Register::SmoothAbsoluteMouse, enabled as u16)
+    }
+
+    pub fn fast_scan(self, enabled: bool) -> Self {
+        self.set(Register::EnableFastScan, enabled as u16)
+    }
+
+    pub fn sleep_timeout(self, secs: u16) -> Self {
+        self.set(Register::SleepInactivityTimeout, secs)
+    }
+
+    /// Smoothing, IMU reporting and scan rate from `rate`
+    pub fn rate(self, rate: &ReportRateConfig) -> Self {
+        self.smoothing(rate.smoothing).imu(rate.imu).fast_scan(rate.fast_scan)
+    }
+
+    pub fn registers(&self) -> &[(Register, u16)] {
+        &self.registers
+    }
+
+    /// The feature report to send
+    pub fn build(&self) -> Result<Vec<u8>, String> {
+        settings_report(&self.registers)
+    }
+}
+
+/// Report rate and smoothing - the part of the register block worth tuning per profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportRateConfig {
+    /// Scan pads and IMU at the faster rate (more reports, more battery)
+    pub fast_scan: bool,
+    /// Let the firmware smooth absolute trackpad positions (adds latency)
+    pub smoothing: bool,
+    pub imu: ImuReporting,
+}
+
+impl Default for ReportRateConfig {
+    fn default() -> Self {
+        Self {
+            fast_scan: true,
+            smoothing: false,
+            imu: ImuReporting::Raw,
+        }
+    }
+}
+
+impl ReportRateConfig {
+    /// Full-rate IMU reporting for gyro-heavy profiles
+    pub fn high_rate_imu() -> Self {
+        Self {
+            fast_scan: true,
+            smoothing: false,
+            imu: ImuReporting::RawWithOrientation,
+        }
+    }
+
+    pub fn registers(&self) -> Vec<(Register, u16)> {
+        SettingsBuilder::new().rate(self).registers().to_vec()
+    }
+}
+
+/// Registers written on connect to get raw input: pads stop emulating a mouse,
+/// the wireless packet format is pinned, and `rate` sets scan rate and IMU data
+pub fn raw_input_registers(rate: &ReportRateConfig) -> Vec<(Register, u16)> {
+    SettingsBuilder::new()
+        .sleep_timeout(SLEEP_TIMEOUT_SECS)
+        .smoothing(rate.smoothing)
+        .set(Register::WirelessPacketVersion, WIRELESS_PACKET_VERSION)
+        .trackpad_mode(Pad::Right, TrackpadMode::None)
+        .trackpad_mode(Pad::Left, TrackpadMode::None)
+        .imu(rate.imu)
+        .fast_scan(rate.fast_scan)
+        .registers()
+        .to_vec()
+}
+
+/// Build a 0x87 report: command, payload length, then (register, u16 LE) triples
+pub fn settings_report(registers: &[(Register, u16)]) -> Result<Vec<u8>, String> {
+    if registers.is_empty() || registers.len() > MAX_REGISTERS_PER_REPORT {
+        return Err(format!(
+            "A settings report holds 1-{} registers, got {}",
+            MAX_REGISTERS_PER_REPORT,
+            registers.len()
+        ));
+    }
+
+    let mut report = vec![0u8; FEATURE_REPORT_SIZE];
+    report[0] = SET_SETTINGS_VALUES;
+    report[1] = (registers.len() * 3) as u8;
+    for (i, (register, value)) in registers.iter().enumerate() {
+        let at = 2 + i * 3;
+        report[at] = *register as u8;
+        report[at + 1..at + 3].copy_from_slice(&value.to_le_bytes());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matches_legacy_blob() {
+        // The opaque report the driver used to send on connect
+        let mut legacy = vec![
+            0x87, 0x15, 0x32, 0x84, 0x03, 0x18, 0x00, 0x00,
+            0x31, 0x02, 0x00, 0x08, 0x07, 0x00, 0x07, 0x07,
+            0x00, 0x30, 0x18, 0x00, 0x2f, 0x01, 0x00,
+        ];
+        legacy.resize(64, 0);
+
+        let registers = raw_input_registers(&ReportRateConfig::default());
+        assert_eq!(settings_report(&registers).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_rate_config_registers() {
+        let report = settings_report(&ReportRateConfig::high_rate_imu().registers()).unwrap();
+        assert_eq!(&report[..11], &[0x87, 0x09, 0x18, 0x00, 0x00, 0x30, 0x1c, 0x00, 0x2f, 0x01, 0x00]);
+
+        assert!(settings_report(&[]).is_err());
+        let too_many = vec![(Register::ImuMode, 0); MAX_REGISTERS_PER_REPORT + 1];
+        assert!(settings_report(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_settings_builder() {
+        let builder = SettingsBuilder::new()
+            .imu(ImuReporting::RawWithOrientation)
+            .trackpad_mode(Pad::Left, TrackpadMode::DpadFourWay)
+            .imu(ImuReporting::Off);
+        // Setting a register again keeps its place and takes the new value
+        assert_eq!(builder.registers(), &[(Register::ImuMode, 0), (Register::LeftTrackpadMode, 0x02)]);
+        assert_eq!(&builder.build().unwrap()[..8], &[0x87, 0x06, 0x30, 0x00, 0x00, 0x07, 0x02, 0x00]);
+        assert!(SettingsBuilder::new().build().is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Dead zones and response curves for the stick, pads and triggers, a
+//! profile-level transform like mirroring: positions are reshaped before any
+//! mapping (cursor, directions, input events, virtual gamepad) sees them.
+//!
+//! Both dead zones are fractions of full deflection, radial for the stick and
+//! pads. Inside the inner one a source reads as centered (a trigger as
+//! released); from the outer one on it reads as fully deflected. In between,
+//! the distance is rescaled to 0..1 and put through the curve, keeping the
+//! direction.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::curve::Curve;
+use crate::input_parser::{ControllerInput, TrackpadData};
+
+/// The analog sources a response applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalogSource {
+    Stick,
+    LeftPad,
+    RightPad,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// How deflection past the inner dead zone maps to output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ResponseCurveSpec", into = "ResponseCurveSpec")]
+pub enum ResponseCurve {
+    Linear,
+    /// `x^exponent` as [`Curve::power`] samples it, so it matches what the
+    /// curve editor draws: above 1 gives finer control near the center
+    Exponential { exponent: f32, curve: Curve },
+    /// Points from the curve editor; output is clamped to 0..1
+    Custom { curve: Curve },
+}
+
+/// Serialized form of a [`ResponseCurve`]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseCurveSpec {
+    Linear,
+    Exponential { exponent: f32 },
+    Custom { curve: Curve },
+}
+
+impl TryFrom<ResponseCurveSpec> for ResponseCurve {
+    type Error = String;
+
+    fn try_from(spec: ResponseCurveSpec) -> Result<Self, String> {
+        match spec {
+            ResponseCurveSpec::Linear => Ok(ResponseCurve::Linear),
+            ResponseCurveSpec::Exponential { exponent } => ResponseCurve::exponential(exponent),
+            ResponseCurveSpec::Custom { curve } => Ok(ResponseCurve::Custom { curve }),
+        }
+    }
+}
+
+impl From<ResponseCurve> for ResponseCurveSpec {
+    fn from(curve: ResponseCurve) -> Self {
+        match curve {
+            ResponseCurve::Linear => ResponseCurveSpec::Linear,
+            ResponseCurve::Exponential { exponent, .. } => ResponseCurveSpec::Exponential { exponent },
+            ResponseCurve::Custom { curve } => ResponseCurveSpec::Custom { curve },
+        }
+    }
+}
+
+impl Default for ResponseCurve {
+    fn default() -> Self {
+        ResponseCurve::Linear
+    }
+}
+
+impl ResponseCurve {
+    pub fn exponential(exponent: f32) -> Result<Self, String> {
+        Ok(ResponseCurve::Exponential { exponent, curve: Curve::power(exponent)? })
+    }
+
+    pub fn eval(&self, x: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Exponential { curve, .. } | ResponseCurve::Custom { curve } => curve.eval(x).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Dead zones and curve for one source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalogResponse {
+    #[serde(default)]
+    pub inner_deadzone: f32,
+    #[serde(default = "default_outer_deadzone")]
+    pub outer_deadzone: f32,
+    #[serde(default)]
+    pub curve: ResponseCurve,
+}
+
+fn default_outer_deadzone() -> f32 {
+    1.0
+}
+
+impl Default for AnalogResponse {
+    fn default() -> Self {
+        Self { inner_deadzone: 0.0, outer_deadzone: 1.0, curve: ResponseCurve::Linear }
+    }
+}
+
+impl AnalogResponse {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.inner_deadzone) {
+            return Err(format!("Inner dead zone must be from 0 up to 1, got {}", self.inner_deadzone));
+        }
+        if !(self.outer_deadzone > self.inner_deadzone && self.outer_deadzone <= 1.0) {
+            return Err(format!(
+                "Outer dead zone must be above the inner one ({}) and at most 1, got {}",
+                self.inner_deadzone, self.outer_deadzone
+            ));
+        }
+        Ok(())
+    }
+
+    /// A position reshaped by the dead zones and curve
+    pub fn apply(&self, x: i16, y: i16) -> (i16, i16) {
+        let full = i16::MAX as f32;
+        let (u, v) = (x as f32 / full, y as f32 / full);
+        let distance = (u * u + v * v).sqrt();
+        if distance <= self.inner_deadzone {
+            return (0, 0);
+        }
+        let scaled = ((distance - self.inner_deadzone) / (self.outer_deadzone - self.inner_deadzone)).min(1.0);
+        let factor = self.curve.eval(scaled) / distance;
+        let axis = |n: f32| ((n * factor).clamp(-1.0, 1.0) * full).round() as i16;
+        (axis(u), axis(v))
+    }
+
+    /// A trigger pull reshaped by the dead zones and curve
+    pub fn apply_trigger(&self, value: u8) -> u8 {
+        let pull = value as f32 / u8::MAX as f32;
+        if pull <= self.inner_deadzone {
+            return 0;
+        }
+        let scaled = ((pull - self.inner_deadzone) / (self.outer_deadzone - self.inner_deadzone)).min(1.0);
+        (self.curve.eval(scaled).clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+    }
+}
+
+fn apply_pad(pad: &mut TrackpadData, response: Option<&AnalogResponse>) {
+    if let Some(response) = response.filter(|_| pad.active) {
+        let (x, y) = response.apply(pad.x, pad.y);
+        pad.x = x;
+        pad.y = y;
+    }
+}
+
+/// `input` with the profile's responses applied; sources without one pass through
+pub fn apply_responses(input: &ControllerInput, responses: &BTreeMap<AnalogSource, AnalogResponse>) -> ControllerInput {
+    let mut out = input.clone();
+    if responses.is_empty() {
+        return out;
+    }
+    if let Some(response) = responses.get(&AnalogSource::Stick) {
+        let (x, y) = response.apply(out.stick.x, out.stick.y);
+        out.stick.x = x;
+        out.stick.y = y;
+    }
+    apply_pad(&mut out.left_trackpad, responses.get(&AnalogSource::LeftPad));
+    apply_pad(&mut out.right_trackpad, responses.get(&AnalogSource::RightPad));
+    if let Some(response) = responses.get(&AnalogSource::LeftTrigger) {
+        out.triggers.left = response.apply_trigger(out.triggers.left);
+    }
+    if let Some(response) = responses.get(&AnalogSource::RightTrigger) {
+        out.triggers.right = response.apply_trigger(out.triggers.right);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{CurvePoint, Interpolation};
+    use crate::input_parser::TriggersData;
+
+    #[test]
+    fn test_deadzones_and_curves() {
+        let response = AnalogResponse { inner_deadzone: 0.1, outer_deadzone: 0.9, curve: ResponseCurve::Linear };
+        assert!(response.validate().is_ok());
+        // Resting noise is swallowed, and the edge of the range is reached early
+        assert_eq!(response.apply(3000, -1000), (0, 0));
+        assert_eq!(response.apply(0, 29490), (0, i16::MAX));
+        assert_eq!(response.apply(-32768, 0), (-i16::MAX, 0));
+        // Halfway between the dead zones is half deflection, same direction
+        let (x, y) = response.apply(16384, 0);
+        assert!((x - 16384).abs() < 50 && y == 0, "{}", x);
+
+        let squared = AnalogResponse { curve: ResponseCurve::exponential(2.0).unwrap(), ..AnalogResponse::default() };
+        let (x, _) = squared.apply(16384, 0);
+        assert!((x - 8192).abs() < 10, "{}", x);
+
+        let mut responses = BTreeMap::new();
+        responses.insert(AnalogSource::Stick, response.clone());
+        responses.insert(AnalogSource::LeftPad, response);
+        let mut input = ControllerInput::default();
+        input.stick.x = 2000;
+        input.left_trackpad = TrackpadData { x: 100, y: 100, active: false };
+        input.right_trackpad = TrackpadData { x: 100, y: 100, active: true };
+        let out = apply_responses(&input, &responses);
+        assert_eq!(out.stick.x, 0);
+        // Lifted pads keep their last position; pads without a response pass through
+        assert_eq!((out.left_trackpad.x, out.right_trackpad.x), (100, 100));
+
+        assert!(AnalogResponse { inner_deadzone: 0.5, outer_deadzone: 0.4, ..AnalogResponse::default() }.validate().is_err());
+        assert!(ResponseCurve::exponential(0.0).is_err());
+        // Only the exponent is stored; the curve is rebuilt on load
+        let json = serde_json::to_string(&squared.curve).unwrap();
+        assert_eq!(json, r#"{"type":"exponential","exponent":2.0}"#);
+        assert_eq!(serde_json::from_str::<ResponseCurve>(&json).unwrap(), squared.curve);
+        assert!(serde_json::from_str::<ResponseCurve>(r#"{"type":"exponential","exponent":-1.0}"#).is_err());
+    }
+
+    #[test]
+    fn test_trigger_curves() {
+        // A hair trigger: anything past a light pull is a full one
+        let hair = Curve::new(
+            vec![CurvePoint { x: 0.0, y: 0.0 }, CurvePoint { x: 0.2, y: 1.0 }, CurvePoint { x: 1.0, y: 1.0 }],
+            Interpolation::Linear,
+        )
+        .unwrap();
+        let mut responses = BTreeMap::new();
+        responses.insert(
+            AnalogSource::LeftTrigger,
+            AnalogResponse { inner_deadzone: 0.05, curve: ResponseCurve::Custom { curve: hair }, ..AnalogResponse::default() },
+        );
+        let mut input = ControllerInput { triggers: TriggersData { left: 64, right: 64 }, ..ControllerInput::default() };
+        let out = apply_responses(&input, &responses);
+        assert_eq!(out.triggers.left, 255);
+        // Triggers without a response pass through
+        assert_eq!(out.triggers.right, 64);
+
+        input.triggers.left = 10;
+        assert_eq!(apply_responses(&input, &responses).triggers.left, 0);
+    }
+}
@@ -0,0 +1,142 @@
+//! Rumble emulation: turns game force feedback (two XInput-style motors) into
+//! trackpad haptic pulses. The heavy low-frequency motor drives the left pad,
+//! the light high-frequency motor the right pad.
+//!
+//! A straight linear mapping buzzes harshly on the SC actuators, so each
+//! profile can tune the pitch, amplitude curve and longest burst.
+
+use serde::{Deserialize, Serialize};
+use crate::haptics::{HapticPulse, HapticSide};
+
+/// Strongest pulse amplitude rumble emulation will ask for
+pub const MAX_RUMBLE_AMPLITUDE: u16 = 4096;
+
+/// One force-feedback update from a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RumbleCommand {
+    /// Low-frequency (left, heavy) motor, 0-65535
+    pub low_motor: u16,
+    /// High-frequency (right, light) motor, 0-65535
+    pub high_motor: u16,
+    pub duration_ms: u32,
+}
+
+/// Pulse frequency range for one motor; stronger rumble moves towards `max_hz`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrequencyRange {
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
+/// Per-profile rumble tuning
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RumbleTuning {
+    pub low_motor_hz: FrequencyRange,
+    pub high_motor_hz: FrequencyRange,
+    /// Exponent applied to motor strength (1.0 linear, >1.0 softer at low strength)
+    pub amplitude_curve: f32,
+    /// Fraction (0.0-1.0) of `MAX_RUMBLE_AMPLITUDE` at full motor strength
+    pub max_amplitude: f32,
+    /// Motor strength (0.0-1.0) below which nothing is played
+    pub deadzone: f32,
+    /// Longest single burst; games that never stop rumbling get re-sent updates
+    pub max_duration_ms: u32,
+}
+
+impl Default for RumbleTuning {
+    fn default() -> Self {
+        Self {
+            low_motor_hz: FrequencyRange { min_hz: 40.0, max_hz: 80.0 },
+            high_motor_hz: FrequencyRange { min_hz: 120.0, max_hz: 200.0 },
+            amplitude_curve: 1.5,
+            max_amplitude: 1.0,
+            deadzone: 0.05,
+            max_duration_ms: 500,
+        }
+    }
+}
+
+impl RumbleTuning {
+    pub fn validate(&self) -> Result<(), String> {
+        for (motor, range) in [("low", self.low_motor_hz), ("high", self.high_motor_hz)] {
+            if !(range.min_hz > 0.0 && range.min_hz <= range.max_hz && range.max_hz <= 1000.0) {
+                return Err(format!(
+                    "Rumble {} motor range must satisfy 0 < min <= max <= 1000 Hz, got {}-{}",
+                    motor, range.min_hz, range.max_hz
+                ));
+            }
+        }
+        if !(self.amplitude_curve > 0.0 && self.amplitude_curve <= 5.0) {
+            return Err(format!("Rumble amplitude curve must be in (0, 5], got {}", self.amplitude_curve));
+        }
+        if !(0.0..=1.0).contains(&self.max_amplitude) || !(0.0..1.0).contains(&self.deadzone) {
+            return Err("Rumble max amplitude must be 0-1 and deadzone 0-<1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Pulses for one force-feedback update (none for motors in the deadzone)
+    pub fn translate(&self, command: &RumbleCommand) -> Vec<HapticPulse> {
+        let duration_ms = command.duration_ms.min(self.max_duration_ms);
+        [
+            (HapticSide::Left, command.low_motor, self.low_motor_hz),
+            (HapticSide::Right, command.high_motor, self.high_motor_hz),
+        ]
+        .iter()
+        .filter_map(|&(side, motor, range)| self.pulse(side, motor, range, duration_ms))
+        .collect()
+    }
+
+    fn pulse(&self, side: HapticSide, motor: u16, range: FrequencyRange, duration_ms: u32) -> Option<HapticPulse> {
+        let strength = motor as f32 / u16::MAX as f32;
+        if strength <= self.deadzone || duration_ms == 0 {
+            return None;
+        }
+        // Rescale past the deadzone so the curve still starts at zero
+        let strength = (strength - self.deadzone) / (1.0 - self.deadzone);
+
+        let amplitude = MAX_RUMBLE_AMPLITUDE as f32 * self.max_amplitude * strength.powf(self.amplitude_curve);
+        let hz = range.min_hz + (range.max_hz - range.min_hz) * strength;
+        let period_us = (1_000_000.0 / hz).round().clamp(1.0, u16::MAX as f32);
+        let count = (duration_ms as f32 * 1000.0 / period_us).round().clamp(1.0, u16::MAX as f32);
+
+        Some(HapticPulse {
+            side,
+            amplitude: amplitude.round() as u16,
+            period: period_us as u16,
+            count: count as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_full_and_idle_motors() {
+        let tuning = RumbleTuning::default();
+        let pulses = tuning.translate(&RumbleCommand { low_motor: u16::MAX, high_motor: 0, duration_ms: 2000 });
+        // 80 Hz for the clamped 500 ms
+        assert_eq!(
+            pulses,
+            vec![HapticPulse { side: HapticSide::Left, amplitude: 4096, period: 12500, count: 40 }]
+        );
+
+        let quiet = tuning.translate(&RumbleCommand { low_motor: 1000, high_motor: 1000, duration_ms: 100 });
+        assert!(quiet.is_empty());
+    }
+
+    #[test]
+    fn test_curve_softens_low_strength() {
+        let command = RumbleCommand { low_motor: 0, high_motor: u16::MAX / 2, duration_ms: 100 };
+        let linear = RumbleTuning { amplitude_curve: 1.0, deadzone: 0.0, ..Default::default() };
+        let soft = RumbleTuning { amplitude_curve: 2.0, deadzone: 0.0, ..Default::default() };
+        assert_eq!(linear.translate(&command)[0].amplitude, 2048);
+        assert_eq!(soft.translate(&command)[0].amplitude, 1024);
+
+        assert!(RumbleTuning { amplitude_curve: 0.0, ..Default::default() }.validate().is_err());
+        assert!(RumbleTuning::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+//! Crash recovery: what the app was doing (connected controller, active
+//! profile, Lizard Mode) is kept in a small state file while it runs. A clean
+//! exit marks the file as such; finding it still marked as running at launch
+//! means the last run died, and the app restores what it can.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const RUNTIME_STATE_FILE: &str = "runtime-state.json";
+
+/// Emitted with the previous run's state once it has been restored
+pub const SESSION_RECOVERED_EVENT: &str = "session-recovered";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeState {
+    /// Set while the app runs, cleared on a clean exit
+    pub running: bool,
+    /// Serial of the connected controller
+    pub device_serial: Option<String>,
+    pub active_profile: Option<String>,
+    /// We turned Lizard Mode off; a controller left like that has no mouse
+    /// emulation until it is power-cycled, so it is turned back on
+    pub lizard_mode_disabled: bool,
+}
+
+impl RuntimeState {
+    /// The saved state; `None` if there is no state file
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| format!("Invalid runtime state {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Written to a temporary file and renamed over the old one, so a crash
+    /// mid-write never leaves a truncated file behind
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize runtime state: {}", e))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+    }
+
+    /// The state a previous run left behind if it didn't exit cleanly and
+    /// there is something to restore
+    pub fn unclean_exit(previous: Option<Self>) -> Option<Self> {
+        previous.filter(|state| {
+            state.running
+                && (state.device_serial.is_some() || state.active_profile.is_some() || state.lizard_mode_disabled)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_and_unclean_exit() {
+        let path = std::env::temp_dir().join(format!("ctrlspace-runtime-{}", std::process::id())).join(RUNTIME_STATE_FILE);
+        let _ = fs::remove_file(&path);
+        assert_eq!(RuntimeState::load(&path).unwrap(), None);
+
+        let state = RuntimeState {
+            running: true,
+            device_serial: Some("FXAA12345".to_string()),
+            active_profile: Some("Racing".to_string()),
+            lizard_mode_disabled: true,
+        };
+        state.save(&path).unwrap();
+        let loaded = RuntimeState::load(&path).unwrap();
+        assert_eq!(loaded.as_ref(), Some(&state));
+        assert_eq!(RuntimeState::unclean_exit(loaded), Some(state.clone()));
+
+        // A clean exit, or a crash with nothing going on, needs no recovery
+        let clean = RuntimeState { running: false, ..state };
+        assert_eq!(RuntimeState::unclean_exit(Some(clean)), None);
+        let idle = RuntimeState { running: true, ..RuntimeState::default() };
+        assert_eq!(RuntimeState::unclean_exit(Some(idle)), None);
+        assert_eq!(RuntimeState::unclean_exit(None), None);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
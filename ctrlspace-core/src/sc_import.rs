@@ -0,0 +1,598 @@
+//! Importer for sc-controller (`.sccprofile`) profiles.
+//!
+//! sc-controller stores each binding as a small expression string, e.g.
+//! `button(Keys.KEY_ENTER)`, `dpad(button(Keys.KEY_UP), ...)` or
+//! `mode(LGRIP, mouse(Rels.REL_WHEEL), mouse())`. We parse those into a tiny
+//! AST and map what CtrlSpace can express; everything else is listed in the
+//! conversion report instead of being silently dropped.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use crate::cursor::CursorMode;
+use crate::profiles::{save_new_profile, Action, AnalogMode, ButtonId, MouseButton, Profile};
+
+/// Result of converting one sc-controller profile
+#[derive(Debug, Clone, Serialize)]
+pub struct ScImport {
+    pub profile: Profile,
+    /// Human-readable notes for every feature that was dropped or simplified
+    pub unsupported: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Call(String, Vec<Expr>),
+    Ident(String),
+    Number(f64),
+    Str(String),
+    /// `a and b` - actions fired together
+    And(Vec<Expr>),
+    /// `a; b` - actions fired in sequence
+    Macro(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Open,
+    Close,
+    Comma,
+    Semicolon,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' | ')' | ',' | ';' => {
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    ',' => Token::Comma,
+                    _ => Token::Semicolon,
+                });
+                i += 1;
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| format!("Unterminated string in '{}'", src))?;
+                tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse()
+                    .map_err(|_| format!("Bad number '{}' in '{}'", text, src))?;
+                tokens.push(Token::Number(n));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("Unexpected '{}' in '{}'", c, src)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // expr := all (';' all)*
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut steps = vec![self.all()?];
+        while self.peek() == Some(&Token::Semicolon) {
+            self.pos += 1;
+            steps.push(self.all()?);
+        }
+        Ok(if steps.len() == 1 { steps.remove(0) } else { Expr::Macro(steps) })
+    }
+
+    // all := primary ('and' primary)*
+    fn all(&mut self) -> Result<Expr, String> {
+        let mut parts = vec![self.primary()?];
+        while self.peek() == Some(&Token::Ident("and".to_string())) {
+            self.pos += 1;
+            parts.push(self.primary()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Expr::And(parts) })
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Str(s)) => Ok(Expr::Str(s)),
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::Open) {
+                    return Ok(Expr::Ident(name));
+                }
+                self.pos += 1;
+
+                let mut args = Vec::new();
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                    return Ok(Expr::Call(name, args));
+                }
+                loop {
+                    args.push(self.expr()?);
+                    match self.next() {
+                        Some(Token::Comma) => continue,
+                        Some(Token::Close) => break,
+                        _ => return Err(format!("Expected ',' or ')' in {}()", name)),
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            other => Err(format!("Unexpected token {:?}", other)),
+        }
+    }
+}
+
+fn parse_action(src: &str) -> Result<Expr, String> {
+    let mut parser = Parser { tokens: tokenize(src)?, pos: 0 };
+    let expr = parser.expr()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!("Trailing input in '{}'", src));
+    }
+    Ok(expr)
+}
+
+/// Key name for an evdev-style `KEY_*` / `BTN_*` constant (module prefix optional)
+fn convert_key(name: &str) -> Option<Action> {
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let mouse = |button| Some(Action::Mouse { button });
+    match name {
+        "BTN_LEFT" => return mouse(MouseButton::Left),
+        "BTN_RIGHT" => return mouse(MouseButton::Right),
+        "BTN_MIDDLE" => return mouse(MouseButton::Middle),
+        _ => {}
+    }
+
+    let key = name.strip_prefix("KEY_")?;
+    let key = match key {
+        "ESC" => "escape".to_string(),
+        "LEFTSHIFT" | "RIGHTSHIFT" => "shift".to_string(),
+        "LEFTCTRL" | "RIGHTCTRL" => "ctrl".to_string(),
+        "LEFTALT" | "RIGHTALT" => "alt".to_string(),
+        "LEFTMETA" | "RIGHTMETA" => "meta".to_string(),
+        "BACKSPACE" => "backspace".to_string(),
+        other => other.to_lowercase(),
+    };
+    Some(Action::Key { key })
+}
+
+/// Collects notes while converting, prefixed with the input being converted
+struct Report<'a> {
+    notes: &'a mut Vec<String>,
+    input: String,
+}
+
+impl Report<'_> {
+    fn note(&mut self, message: impl AsRef<str>) {
+        self.notes.push(format!("{}: {}", self.input, message.as_ref()));
+    }
+}
+
+// Stands in for an action that was dropped entirely
+static NO_ACTION: Expr = Expr::Ident(String::new());
+
+/// Strip wrappers that only tweak behaviour CtrlSpace doesn't model yet.
+/// Returns the wrapped action and an accumulated sensitivity multiplier.
+fn unwrap_modifiers<'e>(mut expr: &'e Expr, report: &mut Report) -> (&'e Expr, f32) {
+    let mut sensitivity = 1.0f32;
+    while let Expr::Call(name, args) = expr {
+        let inner = match args.last() {
+            Some(inner) => inner,
+            None => break,
+        };
+        match name.as_str() {
+            "name" => {}
+            "sens" => {
+                if let Some(Expr::Number(x)) = args.first() {
+                    sensitivity *= *x as f32;
+                }
+            }
+            "feedback" => report.note("haptic feedback is not imported"),
+            "smooth" => report.note("smoothing is not imported"),
+            "deadzone" => report.note("custom deadzone is not imported"),
+            "ball" => report.note("trackball momentum is not imported, using plain mouse"),
+            "hold" | "doubleclick" => {
+                report.note(format!("{}() is not supported, keeping only the normal press action", name))
+            }
+            "mode" => {
+                if args.len() % 2 == 0 {
+                    report.note("mode shift without a default action was dropped");
+                    return (&NO_ACTION, sensitivity);
+                }
+                report.note("mode shifts are not supported, keeping the default action");
+            }
+            _ => break,
+        }
+        expr = inner;
+    }
+    (expr, sensitivity)
+}
+
+fn convert_action(expr: &Expr, report: &mut Report) -> Action {
+    let (expr, _) = unwrap_modifiers(expr, report);
+    match expr {
+        Expr::Ident(name) if name.is_empty() || name == "None" => Action::None,
+        Expr::Call(name, args) if name == "button" => {
+            let key = match args.first() {
+                Some(Expr::Ident(key)) => key,
+                _ => {
+                    report.note("button() without a key");
+                    return Action::None;
+                }
+            };
+            match convert_key(key) {
+                Some(action) => action,
+                None => {
+                    report.note(format!("{} (gamepad output) is not supported", key));
+                    Action::None
+                }
+            }
+        }
+        Expr::And(parts) => {
+            let mut keys = Vec::new();
+            for part in parts {
+                match convert_action(part, report) {
+                    Action::Key { key } => keys.push(key),
+                    Action::None => {}
+                    _ => {
+                        report.note("only key combinations can be combined with 'and'");
+                        return Action::None;
+                    }
+                }
+            }
+            Action::Keys { keys }
+        }
+        Expr::Macro(_) => {
+            report.note("macros are not supported");
+            Action::None
+        }
+        Expr::Call(name, _) => {
+            report.note(format!("{}() is not supported", name));
+            Action::None
+        }
+        other => {
+            report.note(format!("unexpected value {:?}", other));
+            Action::None
+        }
+    }
+}
+
+fn convert_analog(expr: &Expr, sensitivity: f32, report: &mut Report) -> AnalogMode {
+    let (expr, scale) = unwrap_modifiers(expr, report);
+    let sensitivity = sensitivity * scale;
+    let (name, args) = match expr {
+        Expr::Ident(name) if name.is_empty() || name == "None" => return AnalogMode::Disabled,
+        Expr::Call(name, args) => (name.as_str(), args),
+        _ => {
+            report.note("unsupported action");
+            return AnalogMode::Disabled;
+        }
+    };
+
+    match name {
+        "mouse" | "trackpad" | "trackball" => {
+            if name == "trackball" {
+                report.note("trackball momentum is not imported, using plain mouse");
+            }
+            match args.first() {
+                Some(Expr::Ident(axis)) if axis.ends_with("REL_WHEEL") => AnalogMode::Scroll { sensitivity },
+                Some(Expr::Ident(axis)) if axis.ends_with("REL_HWHEEL") => {
+                    report.note("horizontal scrolling is not supported");
+                    AnalogMode::Disabled
+                }
+                _ => AnalogMode::Mouse { sensitivity, cursor: CursorMode::Relative, acceleration: None },
+            }
+        }
+        "dpad" | "dpad8" => {
+            if name == "dpad8" {
+                report.note("8-way dpad imported as 4-way");
+            }
+            let mut dirs = args.iter().map(|arg| convert_action(arg, report));
+            AnalogMode::Directional {
+                up: dirs.next().unwrap_or_default(),
+                down: dirs.next().unwrap_or_default(),
+                left: dirs.next().unwrap_or_default(),
+                right: dirs.next().unwrap_or_default(),
+            }
+        }
+        other => {
+            report.note(format!("{}() is not supported", other));
+            AnalogMode::Disabled
+        }
+    }
+}
+
+fn button_id(sc_name: &str) -> Option<ButtonId> {
+    Some(match sc_name {
+        "A" => ButtonId::A,
+        "B" => ButtonId::B,
+        "X" => ButtonId::X,
+        "Y" => ButtonId::Y,
+        "LB" => ButtonId::Lb,
+        "RB" => ButtonId::Rb,
+        "LT" => ButtonId::Lt,
+        "RT" => ButtonId::Rt,
+        "LGRIP" => ButtonId::Lgrip,
+        "RGRIP" => ButtonId::Rgrip,
+        "START" => ButtonId::Start,
+        "BACK" => ButtonId::Select,
+        "C" => ButtonId::Steam,
+        "LPAD" => ButtonId::LpadClick,
+        "RPAD" => ButtonId::RpadClick,
+        "STICKPRESS" => ButtonId::StickClick,
+        _ => return None,
+    })
+}
+
+// The action expression of one binding entry, plus its sensitivity multiplier
+fn binding_expr(entry: &Value, report: &mut Report) -> Option<(Expr, f32)> {
+    if entry.get("modes").is_some() {
+        report.note("per-input modes are not supported");
+    }
+    let src = entry.get("action").and_then(Value::as_str)?;
+    let sensitivity = entry
+        .get("sensitivity")
+        .and_then(Value::as_array)
+        .and_then(|s| s.first())
+        .and_then(Value::as_f64)
+        .unwrap_or(1.0) as f32;
+
+    match parse_action(src) {
+        Ok(expr) => Some((expr, sensitivity)),
+        Err(e) => {
+            report.note(format!("could not parse action: {}", e));
+            None
+        }
+    }
+}
+
+/// Convert the JSON of an `.sccprofile` into a CtrlSpace profile named `name`
+pub fn import_sccprofile(name: &str, json: &str) -> Result<ScImport, String> {
+    let root: Value =
+        serde_json::from_str(json).map_err(|e| format!("Not an sc-controller profile: {}", e))?;
+    if !root.is_object() {
+        return Err("Not an sc-controller profile: expected a JSON object".to_string());
+    }
+
+    let mut profile = Profile::new(name);
+    profile.description = "Imported from sc-controller".to_string();
+    let mut notes = Vec::new();
+
+    if let Some(buttons) = root.get("buttons").and_then(Value::as_object) {
+        for (sc_name, entry) in buttons {
+            let mut report = Report { notes: &mut notes, input: format!("button {}", sc_name) };
+            let id = match button_id(sc_name) {
+                Some(id) => id,
+                None => {
+                    report.note("no matching button");
+                    continue;
+                }
+            };
+            if let Some((expr, _)) = binding_expr(entry, &mut report) {
+                let action = convert_action(&expr, &mut report);
+                if action != Action::None {
+                    profile.buttons.insert(id, action);
+                }
+            }
+        }
+    }
+
+    // Pads and triggers moved from nested maps to top-level keys in newer versions
+    let lookup = |key: &str, legacy_group: &str, legacy_key: &str| {
+        root.get(key)
+            .or_else(|| root.get(legacy_group).and_then(|g| g.get(legacy_key)))
+            .cloned()
+    };
+
+    let analogs = [
+        ("left_pad", lookup("left_pad", "pads", "LEFT")),
+        ("right_pad", lookup("right_pad", "pads", "RIGHT")),
+        ("stick", root.get("stick").cloned()),
+        ("gyro", root.get("gyro").cloned()),
+    ];
+    for (input, entry) in analogs {
+        let entry = match entry {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let mut report = Report { notes: &mut notes, input: input.to_string() };
+        let mode = match binding_expr(&entry, &mut report) {
+            Some((expr, sensitivity)) => convert_analog(&expr, sensitivity, &mut report),
+            None => continue,
+        };
+        match input {
+            "left_pad" => profile.left_pad = mode,
+            "right_pad" => profile.right_pad = mode,
+            "stick" => profile.stick = mode,
+            _ => profile.gyro = mode,
+        }
+    }
+
+    let triggers = [
+        ("trigger_left", "LEFT", ButtonId::Lt),
+        ("trigger_right", "RIGHT", ButtonId::Rt),
+    ];
+    for (key, legacy_key, id) in triggers {
+        let entry = match lookup(key, "triggers", legacy_key) {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let mut report = Report { notes: &mut notes, input: key.to_string() };
+        let expr = match binding_expr(&entry, &mut report) {
+            Some((expr, _)) => expr,
+            None => continue,
+        };
+        // trigger(press_level, [release_level,] action) - we only have the full-pull click
+        let action = match &expr {
+            Expr::Call(name, args) if name == "trigger" => match args.last() {
+                Some(inner) => {
+                    report.note("analog press levels are not imported, bound to the full-pull click");
+                    convert_action(inner, &mut report)
+                }
+                None => Action::None,
+            },
+            Expr::Ident(name) if name == "None" => Action::None,
+            _ => {
+                report.note("analog trigger output is not supported");
+                Action::None
+            }
+        };
+        if action != Action::None {
+            profile.buttons.entry(id).or_insert(action);
+        }
+    }
+
+    if root.get("cpad").map(|c| c.as_object().map(|o| !o.is_empty()).unwrap_or(false)).unwrap_or(false) {
+        notes.push("cpad: Steam Deck center pad bindings are not supported".to_string());
+    }
+    if root.get("menus").and_then(Value::as_object).map(|m| !m.is_empty()).unwrap_or(false) {
+        notes.push("menus: on-screen menus are not supported".to_string());
+    }
+
+    Ok(ScImport { profile, unsupported: notes })
+}
+
+/// Import an `.sccprofile` file into `profiles_dir`, named after the file
+pub fn import_sccprofile_file(path: &Path, profiles_dir: &Path) -> Result<ScImport, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Imported");
+
+    let import = import_sccprofile(name, &json)?;
+    let (profile, saved) = save_new_profile(profiles_dir, import.profile)?;
+    println!(
+        "✅ Imported {} to {} ({} unsupported features)",
+        path.display(),
+        saved.display(),
+        import.unsupported.len()
+    );
+
+    Ok(ScImport { profile, unsupported: import.unsupported })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action_expressions() {
+        assert_eq!(
+            parse_action("button(Keys.KEY_A)").unwrap(),
+            Expr::Call("button".to_string(), vec![Expr::Ident("Keys.KEY_A".to_string())])
+        );
+        assert!(matches!(
+            parse_action("button(Keys.KEY_LEFTALT) and button(Keys.KEY_TAB)").unwrap(),
+            Expr::And(ref parts) if parts.len() == 2
+        ));
+        assert!(matches!(
+            parse_action("button(Keys.KEY_A); button(Keys.KEY_B)").unwrap(),
+            Expr::Macro(_)
+        ));
+        assert!(matches!(
+            parse_action("sens(2.0, -1.5, mouse())").unwrap(),
+            Expr::Call(ref name, ref args) if name == "sens" && args[1] == Expr::Number(-1.5)
+        ));
+        assert!(parse_action("button(Keys.KEY_A").is_err());
+        assert!(parse_action("button(Keys.KEY_A) )").is_err());
+    }
+
+    #[test]
+    fn test_import_sccprofile() {
+        let json = r#"{
+            "buttons": {
+                "A": {"action": "button(Keys.KEY_ENTER)"},
+                "B": {"action": "button(Keys.BTN_GAMEPAD)"},
+                "BACK": {"action": "button(Keys.KEY_LEFTALT) and button(Keys.KEY_TAB)"},
+                "X": {"action": "button(Keys.KEY_A); button(Keys.KEY_B)"},
+                "Y": {"action": "hold(button(Keys.KEY_F), button(Keys.KEY_E))"}
+            },
+            "left_pad": {"action": "dpad(button(Keys.KEY_UP), button(Keys.KEY_DOWN), button(Keys.KEY_LEFT), button(Keys.KEY_RIGHT))"},
+            "right_pad": {"action": "mode(LGRIP, mouse(Rels.REL_WHEEL), ball(mouse()))", "sensitivity": [2.0, 2.0, 1.0]},
+            "gyro": {"action": "sens(0.5, 0.5, mouse())"},
+            "trigger_right": {"action": "trigger(50, 255, button(Keys.BTN_LEFT))"},
+            "menus": {"main": []},
+            "version": 1.4
+        }"#;
+
+        let import = import_sccprofile("Shooter", json).unwrap();
+        let p = &import.profile;
+
+        assert_eq!(p.name, "Shooter");
+        assert_eq!(p.buttons[&ButtonId::A], Action::Key { key: "enter".to_string() });
+        assert_eq!(p.buttons[&ButtonId::Y], Action::Key { key: "e".to_string() });
+        assert_eq!(
+            p.buttons[&ButtonId::Select],
+            Action::Keys { keys: vec!["alt".to_string(), "tab".to_string()] }
+        );
+        assert_eq!(p.buttons[&ButtonId::Rt], Action::Mouse { button: MouseButton::Left });
+        assert!(!p.buttons.contains_key(&ButtonId::B));
+        assert!(!p.buttons.contains_key(&ButtonId::X));
+
+        assert!(matches!(p.left_pad, AnalogMode::Directional { .. }));
+        assert_eq!(p.right_pad, AnalogMode::Mouse { sensitivity: 2.0, cursor: CursorMode::Relative, acceleration: None });
+        assert_eq!(p.gyro, AnalogMode::Mouse { sensitivity: 0.5, cursor: CursorMode::Relative, acceleration: None });
+
+        let notes = import.unsupported.join("\n");
+        for expected in ["BTN_GAMEPAD", "macros", "hold()", "mode shifts", "trackball", "menus"] {
+            assert!(notes.contains(expected), "missing note about {}: {}", expected, notes);
+        }
+    }
+
+    #[test]
+    fn test_import_legacy_layout() {
+        let json = r#"{
+            "pads": {"LEFT": {"action": "mouse(Rels.REL_WHEEL)"}},
+            "triggers": {"LEFT": {"action": "trigger(200, button(Keys.BTN_RIGHT))"}}
+        }"#;
+        let import = import_sccprofile("Old", json).unwrap();
+        assert_eq!(import.profile.left_pad, AnalogMode::Scroll { sensitivity: 1.0 });
+        assert_eq!(import.profile.buttons[&ButtonId::Lt], Action::Mouse { button: MouseButton::Right });
+    }
+
+    #[test]
+    fn test_rejects_non_profiles() {
+        assert!(import_sccprofile("x", "not json").is_err());
+        assert!(import_sccprofile("x", "[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Profile scripts, for mapping logic the declarative bindings can't express
+//! (context-sensitive combos, adaptive sensitivity). Scripts are Rhai and may
+//! define two hooks:
+//!
+//! - `fn on_frame(input)`: every input frame, with the frame as a map
+//! - `fn on_binding(event)`: every binding press, repeat and release
+//!
+//! Inside a hook, `this` is a map that persists between calls, and the script
+//! acts through `press(key)`, `release(key)`, `tap(key)`, `move_mouse(dx, dy)`,
+//! `scroll(amount)` and `set_cursor_scale(factor)`. The engine has no file,
+//! network or process access, and each call is capped in operations so a
+//! runaway loop can't stall the input thread.
+
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, FLOAT, INT};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use crate::input_parser::ControllerInput;
+use crate::layers::BindingEvent;
+
+/// Event name for script output
+pub const SCRIPT_OUTPUT_EVENT: &str = "script-output";
+
+/// Event name for a script that failed and was stopped
+pub const SCRIPT_ERROR_EVENT: &str = "script-error";
+
+/// Operations one hook call may run; a frame budget, not a wall-clock limit
+const MAX_OPERATIONS: u64 = 50_000;
+const MAX_CALL_LEVELS: usize = 16;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_COLLECTION_SIZE: usize = 1024;
+
+/// Range `set_cursor_scale` is clamped to; NaN and infinities are ignored
+const MAX_CURSOR_SCALE: f32 = 4.0;
+
+/// Something a script asked the mapper to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScriptOutput {
+    /// Hold a key down until `release`
+    Press { key: String },
+    Release { key: String },
+    /// Press and release
+    Tap { key: String },
+    MoveMouse { dx: i32, dy: i32 },
+    /// Scroll wheel clicks; positive is up
+    Scroll { amount: i32 },
+}
+
+/// What one hook call did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptEffects {
+    pub outputs: Vec<ScriptOutput>,
+    /// Lines the script printed
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Sink {
+    outputs: Vec<ScriptOutput>,
+    logs: Vec<String>,
+    cursor_scale: Option<f32>,
+}
+
+/// A compiled profile script and its persistent state
+pub struct ScriptHost {
+    engine: Engine,
+    ast: AST,
+    state: Dynamic,
+    sink: Arc<Mutex<Sink>>,
+    cursor_scale: f32,
+    has_frame_hook: bool,
+    has_binding_hook: bool,
+    /// Set by the first runtime error; hooks stop running until reloaded
+    failed: bool,
+}
+
+fn sandboxed_engine(sink: &Arc<Mutex<Sink>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.disable_symbol("eval");
+
+    let out = Arc::clone(sink);
+    engine.on_print(move |text| out.lock().unwrap().logs.push(text.to_string()));
+    let out = Arc::clone(sink);
+    engine.on_debug(move |text, _, _| out.lock().unwrap().logs.push(text.to_string()));
+
+    let out = Arc::clone(sink);
+    engine.register_fn("press", move |key: &str| {
+        out.lock().unwrap().outputs.push(ScriptOutput::Press { key: key.to_string() })
+    });
+    let out = Arc::clone(sink);
+    engine.register_fn("release", move |key: &str| {
+        out.lock().unwrap().outputs.push(ScriptOutput::Release { key: key.to_string() })
+    });
+    let out = Arc::clone(sink);
+    engine.register_fn("tap", move |key: &str| {
+        out.lock().unwrap().outputs.push(ScriptOutput::Tap { key: key.to_string() })
+    });
+    let out = Arc::clone(sink);
+    engine.register_fn("move_mouse", move |dx: INT, dy: INT| {
+        out.lock().unwrap().outputs.push(ScriptOutput::MoveMouse { dx: dx as i32, dy: dy as i32 })
+    });
+    let out = Arc::clone(sink);
+    engine.register_fn("scroll", move |amount: INT| {
+        out.lock().unwrap().outputs.push(ScriptOutput::Scroll { amount: amount as i32 })
+    });
+    let out = Arc::clone(sink);
+    engine.register_fn("set_cursor_scale", move |factor: FLOAT| {
+        // NaN would get through the clamp; keep the current scale instead
+        if !factor.is_finite() {
+            return;
+        }
+        out.lock().unwrap().cursor_scale = Some((factor as f32).clamp(0.0, MAX_CURSOR_SCALE));
+    });
+    engine
+}
+
+impl ScriptHost {
+    /// Compile `source`; syntax errors and hooks with the wrong arity are rejected
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let sink = Arc::new(Mutex::new(Sink::default()));
+        let engine = sandboxed_engine(&sink);
+        let ast = engine.compile(source).map_err(|e| format!("Script error: {}", e))?;
+
+        let mut has_frame_hook = false;
+        let mut has_binding_hook = false;
+        for function in ast.iter_functions() {
+            let hook = match function.name {
+                "on_frame" => &mut has_frame_hook,
+                "on_binding" => &mut has_binding_hook,
+                _ => continue,
+            };
+            if function.params.len() != 1 {
+                return Err(format!("Script hook {} must take one parameter", function.name));
+            }
+            *hook = true;
+        }
+
+        Ok(Self {
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            sink,
+            cursor_scale: 1.0,
+            has_frame_hook,
+            has_binding_hook,
+            failed: false,
+        })
+    }
+
+    /// Cursor output factor the script last set (1.0 until it sets one)
+    pub fn cursor_scale(&self) -> f32 {
+        self.cursor_scale
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    pub fn on_frame(&mut self, input: &ControllerInput) -> Result<ScriptEffects, String> {
+        if !self.has_frame_hook {
+            return Ok(ScriptEffects::default());
+        }
+        self.call("on_frame", input)
+    }
+
+    pub fn on_binding(&mut self, event: &BindingEvent) -> Result<ScriptEffects, String> {
+        if !self.has_binding_hook {
+            return Ok(ScriptEffects::default());
+        }
+        self.call("on_binding", event)
+    }
+
+    // Run a hook with `arg` as its map parameter; the first error stops the script
+    fn call<T: Serialize>(&mut self, hook: &str, arg: &T) -> Result<ScriptEffects, String> {
+        if self.failed {
+            return Ok(ScriptEffects::default());
+        }
+        let arg = rhai::serde::to_dynamic(arg).map_err(|e| format!("Script {}: {}", hook, e))?;
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.state);
+        let result: Result<Dynamic, _> =
+            self.engine.call_fn_with_options(options, &mut Scope::new(), &self.ast, hook, (arg,));
+
+        let mut sink = self.sink.lock().unwrap();
+        if let Some(scale) = sink.cursor_scale.take() {
+            self.cursor_scale = scale;
+        }
+        let effects = ScriptEffects {
+            outputs: std::mem::take(&mut sink.outputs),
+            logs: std::mem::take(&mut sink.logs),
+        };
+        match result {
+            Ok(_) => Ok(effects),
+            Err(e) => {
+                self.failed = true;
+                Err(format!("Script {} stopped: {}", hook, e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks_keep_state_and_stop_on_error() {
+        let source = r#"
+            fn on_frame(input) {
+                if this.frames == () { this.frames = 0; }
+                this.frames += 1;
+                if input.buttons.a && this.frames == 2 { tap("space"); set_cursor_scale(0.5); }
+                if input.stick.x > 1000 { loop {} }
+            }
+        "#;
+        let mut host = ScriptHost::compile(source).unwrap();
+        let mut input = ControllerInput::default();
+        input.buttons.a = true;
+
+        assert_eq!(host.on_frame(&input).unwrap().outputs, vec![]);
+        let effects = host.on_frame(&input).unwrap();
+        assert_eq!(effects.outputs, vec![ScriptOutput::Tap { key: "space".to_string() }]);
+        assert_eq!(host.cursor_scale(), 0.5);
+
+        // A runaway loop hits the operation limit and the script stays stopped
+        input.stick.x = 2000;
+        assert!(host.on_frame(&input).is_err());
+        assert!(host.has_failed());
+        assert_eq!(host.on_frame(&input), Ok(ScriptEffects::default()));
+
+        assert!(ScriptHost::compile("fn on_frame() {}").is_err());
+    }
+
+    #[test]
+    fn test_cursor_scale_ignores_non_finite() {
+        let source = r#"
+            fn on_frame(input) {
+                if this.frames == () { this.frames = 0; }
+                this.frames += 1;
+                switch this.frames {
+                    1 => set_cursor_scale(9.0),
+                    2 => set_cursor_scale(parse_float("NaN")),
+                    3 => set_cursor_scale(parse_float("inf")),
+                    _ => set_cursor_scale(-1.0),
+                }
+            }
+        "#;
+        let mut host = ScriptHost::compile(source).unwrap();
+        let input = ControllerInput::default();
+
+        host.on_frame(&input).unwrap();
+        assert_eq!(host.cursor_scale(), MAX_CURSOR_SCALE);
+        host.on_frame(&input).unwrap();
+        assert_eq!(host.cursor_scale(), MAX_CURSOR_SCALE);
+        host.on_frame(&input).unwrap();
+        assert_eq!(host.cursor_scale(), MAX_CURSOR_SCALE);
+        host.on_frame(&input).unwrap();
+        assert_eq!(host.cursor_scale(), 0.0);
+    }
+}
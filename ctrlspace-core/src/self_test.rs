@@ -0,0 +1,432 @@
+//! Guided hardware self-test: prompts for each control in turn (press each
+//! button, sweep each pad, pull each trigger, circle the stick, rotate the
+//! controller) and checks from live input frames that it registers across
+//! its expected range. Handy for checking a second-hand controller.
+//!
+//! A step passes as soon as its control has done everything asked of it, and
+//! fails if that hasn't happened within `STEP_TIMEOUT_US` of its first frame.
+//! The report says what was missing, e.g. a pad that never reads far left.
+
+use serde::Serialize;
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::ButtonId;
+
+/// Event name for self-test progress (a `SelfTestStatus`)
+pub const SELF_TEST_EVENT: &str = "self-test";
+
+/// A step that hasn't passed this long after its first frame fails
+pub const STEP_TIMEOUT_US: u64 = 20_000_000;
+
+/// Pad position each sweep must reach in all four directions
+const PAD_REACH: i16 = 20_000;
+
+/// Stick deflection each direction must reach...
+const STICK_REACH: i16 = 28_000;
+
+/// ...before springing back to within this of the center
+const STICK_CENTER: i16 = 4_000;
+
+/// Trigger value that counts as fully pulled, and as released
+const TRIGGER_FULL: u8 = 230;
+const TRIGGER_REST: u8 = 20;
+
+/// Rotation rate each gyro axis must reach
+const GYRO_REACH: i16 = 1_500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// What a self-test step checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TestControl {
+    Button { button: ButtonId },
+    Pad { pad: Pad },
+    Trigger { side: Side },
+    Stick,
+    Gyro,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SelfTestStep {
+    pub control: TestControl,
+    pub prompt: &'static str,
+}
+
+const fn button(button: ButtonId, prompt: &'static str) -> SelfTestStep {
+    SelfTestStep { control: TestControl::Button { button }, prompt }
+}
+
+/// Every step, in the order the user is prompted
+pub const SELF_TEST_STEPS: &[SelfTestStep] = &[
+    button(ButtonId::A, "Press and release A"),
+    button(ButtonId::B, "Press and release B"),
+    button(ButtonId::X, "Press and release X"),
+    button(ButtonId::Y, "Press and release Y"),
+    button(ButtonId::Lb, "Press and release the left bumper"),
+    button(ButtonId::Rb, "Press and release the right bumper"),
+    button(ButtonId::Lgrip, "Squeeze and release the left grip"),
+    button(ButtonId::Rgrip, "Squeeze and release the right grip"),
+    button(ButtonId::Select, "Press and release the back (left arrow) button"),
+    button(ButtonId::Start, "Press and release the start (right arrow) button"),
+    button(ButtonId::Steam, "Press and release the Steam button"),
+    button(ButtonId::LpadClick, "Click the left pad"),
+    button(ButtonId::RpadClick, "Click the right pad"),
+    button(ButtonId::StickClick, "Click the stick in"),
+    SelfTestStep {
+        control: TestControl::Trigger { side: Side::Left },
+        prompt: "Pull the left trigger all the way until it clicks, then let go",
+    },
+    SelfTestStep {
+        control: TestControl::Trigger { side: Side::Right },
+        prompt: "Pull the right trigger all the way until it clicks, then let go",
+    },
+    SelfTestStep {
+        control: TestControl::Pad { pad: Pad::Left },
+        prompt: "Slide a finger to the top, bottom, left and right edges of the left pad",
+    },
+    SelfTestStep {
+        control: TestControl::Pad { pad: Pad::Right },
+        prompt: "Slide a finger to the top, bottom, left and right edges of the right pad",
+    },
+    SelfTestStep {
+        control: TestControl::Stick,
+        prompt: "Push the stick all the way up, down, left and right, then let go",
+    },
+    SelfTestStep {
+        control: TestControl::Gyro,
+        prompt: "Tilt the controller forward and back, turn it left and right, and roll it side to side",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControlResult {
+    pub control: TestControl,
+    pub outcome: TestOutcome,
+    /// What was observed, or what was missing for a failure
+    pub detail: String,
+}
+
+/// Where the self-test is, for the UI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelfTestStatus {
+    pub step: usize,
+    pub total: usize,
+    /// `None` once every step is done
+    pub current: Option<SelfTestStep>,
+    /// How much of the current step is done (0.0-1.0)
+    pub progress: f32,
+    pub results: Vec<ControlResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelfTestReport {
+    pub results: Vec<ControlResult>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// What the current step's control has done so far
+#[derive(Debug, Clone, Default)]
+struct Observed {
+    started_us: Option<u64>,
+    /// Button pressed, pad touched, trigger fully pulled, stick fully deflected...
+    engaged: bool,
+    /// ...and let go afterwards
+    released: bool,
+    min: [i16; 2],
+    max: [i16; 2],
+    peak: u8,
+    gyro: [i16; 3],
+}
+
+impl Observed {
+    fn extend(&mut self, x: i16, y: i16) {
+        self.min = [self.min[0].min(x), self.min[1].min(y)];
+        self.max = [self.max[0].max(x), self.max[1].max(y)];
+    }
+
+    /// Which of left, right, down, up reached `reach`
+    fn directions(&self, reach: i16) -> [bool; 4] {
+        [self.min[0] <= -reach, self.max[0] >= reach, self.min[1] <= -reach, self.max[1] >= reach]
+    }
+
+    fn range(&self) -> String {
+        format!(
+            "x {}..{}, y {}..{}",
+            self.min[0], self.max[0], self.min[1], self.max[1]
+        )
+    }
+}
+
+fn missing_directions(reached: [bool; 4]) -> String {
+    let names = ["left", "right", "down", "up"];
+    let missing: Vec<&str> = names.iter().zip(reached).filter(|(_, r)| !r).map(|(n, _)| *n).collect();
+    missing.join(", ")
+}
+
+fn fraction(done: &[bool]) -> f32 {
+    done.iter().filter(|d| **d).count() as f32 / done.len() as f32
+}
+
+pub struct SelfTest {
+    step: usize,
+    observed: Observed,
+    results: Vec<ControlResult>,
+}
+
+impl Default for SelfTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelfTest {
+    pub fn new() -> Self {
+        Self {
+            step: 0,
+            observed: Observed::default(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> Option<SelfTestStep> {
+        SELF_TEST_STEPS.get(self.step).copied()
+    }
+
+    pub fn status(&self) -> SelfTestStatus {
+        SelfTestStatus {
+            step: self.step,
+            total: SELF_TEST_STEPS.len(),
+            current: self.current(),
+            progress: self.current().map_or(1.0, |step| self.progress(step.control)),
+            results: self.results.clone(),
+        }
+    }
+
+    fn progress(&self, control: TestControl) -> f32 {
+        let o = &self.observed;
+        match control {
+            TestControl::Button { .. } | TestControl::Trigger { .. } => fraction(&[o.engaged, o.released]),
+            TestControl::Pad { .. } => fraction(&o.directions(PAD_REACH)),
+            TestControl::Stick => {
+                let d = o.directions(STICK_REACH);
+                fraction(&[d[0], d[1], d[2], d[3], o.released])
+            }
+            TestControl::Gyro => fraction(&o.gyro.map(|g| g >= GYRO_REACH)),
+        }
+    }
+
+    /// Feed a frame; returns true when the status changed (progress made,
+    /// or the step passed or timed out)
+    pub fn update(&mut self, input: &ControllerInput) -> bool {
+        let step = match self.current() {
+            Some(step) => step,
+            None => return false,
+        };
+        let now = input.host_timestamp_us;
+        let started = *self.observed.started_us.get_or_insert(now);
+        let before = self.progress(step.control);
+
+        let o = &mut self.observed;
+        match step.control {
+            TestControl::Button { button } => {
+                if button.is_pressed(&input.buttons) {
+                    o.engaged = true;
+                } else if o.engaged {
+                    o.released = true;
+                }
+            }
+            TestControl::Trigger { side } => {
+                let value = match side {
+                    Side::Left => input.triggers.left,
+                    Side::Right => input.triggers.right,
+                };
+                o.peak = o.peak.max(value);
+                if value >= TRIGGER_FULL {
+                    o.engaged = true;
+                } else if o.engaged && value <= TRIGGER_REST {
+                    o.released = true;
+                }
+            }
+            TestControl::Pad { pad } => {
+                let data: &TrackpadData = match pad {
+                    Pad::Left => &input.left_trackpad,
+                    Pad::Right => &input.right_trackpad,
+                };
+                if data.active {
+                    o.engaged = true;
+                    o.extend(data.x, data.y);
+                }
+            }
+            TestControl::Stick => {
+                o.extend(input.stick.x, input.stick.y);
+                if o.directions(STICK_REACH).iter().all(|d| *d) {
+                    o.engaged = true;
+                }
+                if o.engaged && input.stick.x.abs() <= STICK_CENTER && input.stick.y.abs() <= STICK_CENTER {
+                    o.released = true;
+                }
+            }
+            TestControl::Gyro => {
+                let rates = [input.gyro.pitch, input.gyro.yaw, input.gyro.roll];
+                for (max, rate) in o.gyro.iter_mut().zip(rates) {
+                    *max = (*max).max(rate.saturating_abs());
+                }
+            }
+        }
+
+        let progress = self.progress(step.control);
+        if progress >= 1.0 {
+            self.finish_step(TestOutcome::Passed, self.detail(step.control));
+        } else if now.saturating_sub(started) >= STEP_TIMEOUT_US {
+            self.finish_step(TestOutcome::Failed, self.failure(step.control));
+        } else {
+            return progress != before;
+        }
+        true
+    }
+
+    // What was seen, for a pass
+    fn detail(&self, control: TestControl) -> String {
+        let o = &self.observed;
+        match control {
+            TestControl::Button { .. } => "pressed and released".to_string(),
+            TestControl::Trigger { .. } => format!("pulled to {}", o.peak),
+            TestControl::Pad { .. } | TestControl::Stick => o.range(),
+            TestControl::Gyro => format!("pitch {}, yaw {}, roll {}", o.gyro[0], o.gyro[1], o.gyro[2]),
+        }
+    }
+
+    // What was missing, for a failure
+    fn failure(&self, control: TestControl) -> String {
+        let o = &self.observed;
+        match control {
+            TestControl::Button { .. } if !o.engaged => "never registered a press".to_string(),
+            TestControl::Button { .. } => "stuck pressed".to_string(),
+            TestControl::Trigger { .. } if !o.engaged => {
+                format!("only reached {} (needs {})", o.peak, TRIGGER_FULL)
+            }
+            TestControl::Trigger { .. } => format!("didn't return below {}", TRIGGER_REST),
+            TestControl::Pad { .. } if !o.engaged => "never registered a touch".to_string(),
+            TestControl::Pad { .. } => format!(
+                "never reached the {} edge ({})",
+                missing_directions(o.directions(PAD_REACH)),
+                o.range()
+            ),
+            TestControl::Stick if !o.engaged => format!(
+                "never reached full {} ({})",
+                missing_directions(o.directions(STICK_REACH)),
+                o.range()
+            ),
+            TestControl::Stick => format!("didn't return to center (within {})", STICK_CENTER),
+            TestControl::Gyro => {
+                let names = ["pitch", "yaw", "roll"];
+                let missing: Vec<String> = names
+                    .iter()
+                    .zip(o.gyro)
+                    .filter(|(_, rate)| *rate < GYRO_REACH)
+                    .map(|(name, rate)| format!("{} (peak {})", name, rate))
+                    .collect();
+                format!("too little rotation: {}", missing.join(", "))
+            }
+        }
+    }
+
+    fn finish_step(&mut self, outcome: TestOutcome, detail: String) {
+        if let Some(step) = self.current() {
+            self.results.push(ControlResult { control: step.control, outcome, detail });
+            self.step += 1;
+            self.observed = Observed::default();
+        }
+    }
+
+    /// Results of the steps done so far
+    pub fn results(&self) -> &[ControlResult] {
+        &self.results
+    }
+
+    /// Move on without testing the current control
+    pub fn skip(&mut self) {
+        self.finish_step(TestOutcome::Skipped, "skipped".to_string());
+    }
+
+    /// The report; steps not reached yet count as skipped
+    pub fn report(&self) -> SelfTestReport {
+        let mut results = self.results.clone();
+        results.extend(SELF_TEST_STEPS[self.results.len()..].iter().map(|step| ControlResult {
+            control: step.control,
+            outcome: TestOutcome::Skipped,
+            detail: "not reached".to_string(),
+        }));
+        let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+        SelfTestReport {
+            passed: count(TestOutcome::Passed),
+            failed: count(TestOutcome::Failed),
+            skipped: count(TestOutcome::Skipped),
+            results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(at_ms: u64, f: impl FnOnce(&mut ControllerInput)) -> ControllerInput {
+        let mut input = ControllerInput {
+            host_timestamp_us: at_ms * 1000,
+            ..Default::default()
+        };
+        f(&mut input);
+        input
+    }
+
+    #[test]
+    fn test_self_test_passes_and_fails_controls() {
+        let mut test = SelfTest::new();
+        assert!(!test.update(&frame(0, |_| {})));
+        assert!(test.update(&frame(10, |i| i.buttons.a = true)));
+        assert!(test.update(&frame(20, |_| {})));
+        assert_eq!(test.results[0].outcome, TestOutcome::Passed);
+
+        // B is never pressed
+        test.update(&frame(30, |_| {}));
+        assert!(test.update(&frame(30 + STEP_TIMEOUT_US / 1000, |_| {})));
+        assert_eq!(test.results[1].outcome, TestOutcome::Failed);
+        assert_eq!(test.results[1].detail, "never registered a press");
+
+        while test.current().map(|s| s.control) != Some(TestControl::Pad { pad: Pad::Left }) {
+            test.skip();
+        }
+        // A pad that can't read far left
+        for (x, y) in [(0, 30000), (0, -30000), (30000, 0), (-12000, 0)] {
+            test.update(&frame(100_000, |i| {
+                i.left_trackpad = TrackpadData { x, y, active: true };
+            }));
+        }
+        assert_eq!(test.status().progress, 0.75);
+        test.update(&frame(100_000 + STEP_TIMEOUT_US / 1000, |_| {}));
+        let failure = &test.results.last().unwrap().detail;
+        assert!(failure.starts_with("never reached the left edge"), "{}", failure);
+
+        let report = test.report();
+        assert_eq!(report.results.len(), SELF_TEST_STEPS.len());
+        assert_eq!((report.passed, report.failed), (1, 2));
+        assert_eq!(report.skipped, SELF_TEST_STEPS.len() - 3);
+    }
+}
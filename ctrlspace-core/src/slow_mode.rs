@@ -0,0 +1,65 @@
+//! Slow mode: while a `slow_mode` binding is held (or toggled on), cursor
+//! output is scaled down for precise aiming. Scaling happens on the final
+//! per-frame movement rather than inside each pad/gyro mode, so every mode
+//! that moves the cursor slows down the same way.
+
+use crate::cursor::CursorMove;
+use crate::gestures::Pad;
+
+/// Scales cursor movement, carrying sub-pixel remainders between frames so
+/// slow movement isn't rounded away
+#[derive(Debug, Clone, Default)]
+pub struct OutputScaler {
+    left: (f32, f32),
+    right: (f32, f32),
+}
+
+impl OutputScaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Movement scaled by `factor` (1.0 passes it through unchanged)
+    pub fn scale(&mut self, moves: Vec<CursorMove>, factor: f32) -> Vec<CursorMove> {
+        if factor == 1.0 {
+            *self = Self::default();
+            return moves;
+        }
+        moves
+            .into_iter()
+            .filter_map(|movement| {
+                let remainder = match movement.pad {
+                    Pad::Left => &mut self.left,
+                    Pad::Right => &mut self.right,
+                };
+                let dx = movement.dx as f32 * factor + remainder.0;
+                let dy = movement.dy as f32 * factor + remainder.1;
+                let step = (dx.trunc(), dy.trunc());
+                *remainder = (dx - step.0, dy - step.1);
+                let (dx, dy) = (step.0 as i32, step.1 as i32);
+                if (dx, dy) == (0, 0) {
+                    return None;
+                }
+                Some(CursorMove { dx, dy, ..movement })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_movement_accumulates() {
+        let mut scaler = OutputScaler::new();
+        let step = CursorMove { pad: Pad::Right, dx: 1, dy: -10 };
+        let moved = (0..10).fold((0, 0), |(x, y), _| {
+            scaler.scale(vec![step], 0.3).iter().fold((x, y), |(x, y), m| (x + m.dx, y + m.dy))
+        });
+        // 30% of (10, -100), give or take the carried remainder
+        assert!((2..=3).contains(&moved.0) && (-30..=-29).contains(&moved.1), "{:?}", moved);
+
+        assert_eq!(scaler.scale(vec![step], 1.0), vec![step]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Steam game detection. Steam records the AppID of the game it is running
+//! (`RunningAppID`: the Windows registry, or `registry.vdf` elsewhere), and
+//! each library's `appmanifest_<id>.acf` has the game's name. Profiles list
+//! the AppIDs they are for, which survives game updates, renamed executables
+//! and launchers far better than matching process names.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::profiles::Profile;
+
+/// Event name for the running Steam game changing
+pub const STEAM_GAME_CHANGED_EVENT: &str = "steam-game-changed";
+
+/// A running Steam game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SteamGame {
+    pub app_id: u32,
+    /// From the app manifest; `None` if it couldn't be found
+    pub name: Option<String>,
+}
+
+/// A node of Valve's KeyValues text format; keys are case-insensitive and
+/// stored lowercased
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Vdf {
+    Value(String),
+    Section(BTreeMap<String, Vdf>),
+}
+
+impl Vdf {
+    /// Child by (case-insensitive) key
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Section(children) => children.get(&key.to_ascii_lowercase()),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    /// Descend through `path`
+    pub fn path(&self, path: &[&str]) -> Option<&Vdf> {
+        path.iter().try_fold(self, |node, key| node.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(value) => Some(value),
+            Vdf::Section(_) => None,
+        }
+    }
+}
+
+// Quoted string with \\ and \" escapes, or a bare word
+fn read_token(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    loop {
+        match chars.peek()? {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '/' => {
+                // `// comment` to end of line
+                while chars.next().map_or(false, |c| c != '\n') {}
+            }
+            _ => break,
+        }
+    }
+    let mut token = String::new();
+    match chars.peek()? {
+        '{' | '}' => token.push(chars.next()?),
+        '"' => {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => return Some(token),
+                    '\\' => token.push(match chars.next()? {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => other,
+                    }),
+                    c => token.push(c),
+                }
+            }
+            return None;
+        }
+        _ => {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+        }
+    }
+    Some(token)
+}
+
+fn parse_section(chars: &mut std::iter::Peekable<std::str::Chars>, nested: bool) -> Result<Vdf, String> {
+    let mut children = BTreeMap::new();
+    loop {
+        let key = match read_token(chars) {
+            Some(key) if key == "}" && nested => return Ok(Vdf::Section(children)),
+            Some(key) if key == "{" || key == "}" => return Err(format!("Unexpected '{}' in VDF", key)),
+            Some(key) => key.to_ascii_lowercase(),
+            None if nested => return Err("Unterminated VDF section".to_string()),
+            None => return Ok(Vdf::Section(children)),
+        };
+        let value = match read_token(chars) {
+            Some(open) if open == "{" => parse_section(chars, true)?,
+            Some(close) if close == "}" => return Err(format!("VDF key '{}' has no value", key)),
+            Some(value) => Vdf::Value(value),
+            None => return Err(format!("VDF key '{}' has no value", key)),
+        };
+        children.insert(key, value);
+    }
+}
+
+/// Parse a KeyValues text file (`.vdf`, `.acf`)
+pub fn parse_vdf(text: &str) -> Result<Vdf, String> {
+    parse_section(&mut text.chars().peekable(), false)
+}
+
+/// `RunningAppID` from a Steam `registry.vdf` (Linux and macOS); `None` when
+/// no game is running
+pub fn running_app_id_from_registry(text: &str) -> Result<Option<u32>, String> {
+    let registry = parse_vdf(text)?;
+    let id = registry
+        .path(&["registry", "hkcu", "software", "valve", "steam", "runningappid"])
+        .and_then(Vdf::as_str)
+        .and_then(|id| id.parse::<u32>().ok())
+        .filter(|&id| id != 0);
+    Ok(id)
+}
+
+/// Library folders listed in `steamapps/libraryfolders.vdf`, which include the
+/// Steam root itself
+pub fn library_folders(steam_root: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![steam_root.to_path_buf()];
+    let path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    let libraries = match fs::read_to_string(&path).ok().and_then(|text| parse_vdf(&text).ok()) {
+        Some(vdf) => vdf,
+        None => return folders,
+    };
+    if let Some(Vdf::Section(entries)) = libraries.get("libraryfolders") {
+        for entry in entries.values() {
+            // Current format nests `path`; older files map the index straight to the path
+            let folder = match entry {
+                Vdf::Section(_) => entry.get("path").and_then(Vdf::as_str),
+                Vdf::Value(path) => Some(path.as_str()),
+            };
+            if let Some(folder) = folder.map(PathBuf::from) {
+                if !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
+        }
+    }
+    folders
+}
+
+/// The game's name from its app manifest in any library
+pub fn app_name(libraries: &[PathBuf], app_id: u32) -> Option<String> {
+    libraries.iter().find_map(|library| {
+        let manifest = library.join("steamapps").join(format!("appmanifest_{}.acf", app_id));
+        let text = fs::read_to_string(manifest).ok()?;
+        parse_vdf(&text)
+            .ok()?
+            .path(&["appstate", "name"])
+            .and_then(Vdf::as_str)
+            .map(str::to_string)
+    })
+}
+
+/// Default Steam install locations outside Windows (where the registry says)
+pub fn default_steam_root() -> Option<PathBuf> {
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    let candidates = if cfg!(target_os = "macos") {
+        vec![home.join("Library/Application Support/Steam")]
+    } else {
+        vec![home.join(".steam/steam"), home.join(".local/share/Steam")]
+    };
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+/// Where `registry.vdf` lives on Linux and macOS. On Linux it is always
+/// `~/.steam/registry.vdf`, wherever the install itself is (`~/.steam/steam`
+/// is usually a link to `~/.local/share/Steam`).
+pub fn registry_vdf_path() -> Option<PathBuf> {
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    Some(if cfg!(target_os = "macos") {
+        home.join("Library/Application Support/Steam/registry.vdf")
+    } else {
+        home.join(".steam/registry.vdf")
+    })
+}
+
+/// The first profile made for `app_id`
+pub fn profile_for_app(profiles: &[Profile], app_id: u32) -> Option<&Profile> {
+    profiles.iter().find(|profile| profile.steam_app_ids.contains(&app_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vdf_parsing() {
+        let registry = r#"
+            "Registry"
+            {
+                "HKCU"
+                {
+                    "Software"
+                    {
+                        "Valve"
+                        {
+                            "Steam"
+                            {
+                                "RunningAppID"		"620"
+                                "language"		"english" // trailing comment
+                            }
+                        }
+                    }
+                }
+            }"#;
+        assert_eq!(running_app_id_from_registry(registry), Ok(Some(620)));
+        assert_eq!(running_app_id_from_registry(&registry.replace("\"620\"", "\"0\"")), Ok(None));
+        assert!(parse_vdf(r#""AppState" { "name" "Portal 2""#).is_err());
+
+        let manifest = parse_vdf(r#""AppState" { "appid" "620" "name" "Portal \"2\"" }"#).unwrap();
+        assert_eq!(manifest.path(&["AppState", "Name"]).and_then(Vdf::as_str), Some("Portal \"2\""));
+    }
+}
@@ -0,0 +1,1207 @@
+use hidapi::{DeviceInfo, HidApi, HidDevice};
+use serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::clock;
+use crate::controller::ControllerHandle;
+use crate::enumeration::{self, DeviceFilter, HidDeviceEntry};
+use crate::dongle::{self, ControllerInterface, DONGLE_SLOTS};
+use crate::drift::{DriftCompensator, DriftWarning};
+use crate::device_settings::{Calibration, DeviceSettings, DEFAULT_HAPTIC_STRENGTH};
+use crate::error::ControllerError;
+use crate::haptics::{
+    validate_haptic_intensity, validate_max_haptic_rate, HapticPulse, HapticRateLimiter, DEFAULT_HAPTIC_INTENSITY,
+    DEFAULT_MAX_HAPTIC_RATE,
+};
+use crate::input_parser::{ControllerInput, InputParser, Packet};
+use crate::melody::{self, Jingles, Tone};
+use crate::gestures::Pad;
+use crate::registers::{self, ImuReporting, ReportRateConfig, SettingsBuilder, TrackpadMode};
+use crate::watchdog::{HidOp, OpTracker, StuckOperation};
+use crate::write_queue::{QueuedWrite, WriteQueue, WRITER_IDLE_TIMEOUT};
+use crate::write_stats::{WriteKind, WriteLatencyLog, WriteStats};
+
+// Steam Controller USB IDs
+pub const VALVE_VENDOR_ID: u16 = 0x28de;
+pub const SC_WIRELESS_PID: u16 = 0x1142; // Wireless dongle
+pub const SC_WIRED_PID: u16 = 0x1102;    // Wired connection
+pub const STEAM_DECK_PID: u16 = 0x1205;  // Steam Deck built-in controls
+
+// Usage page of the interfaces that carry raw controller reports
+pub const VENDOR_USAGE_PAGE: u16 = 0xFF00;
+
+// Read defaults (tuned for the wired USB path)
+pub const DEFAULT_READ_TIMEOUT_MS: i32 = 10;
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 64;
+pub const MAX_READ_BUFFER_SIZE: usize = 4096;
+
+// 64-byte report plus the leading report ID byte
+pub const MAX_FEATURE_REPORT_SIZE: usize = 65;
+
+// Feature report 0xae reads a string attribute; attribute 0x01 is the unit serial.
+// The dongle's USB serial belongs to the receiver, so wireless units are asked directly.
+const GET_STRING_ATTRIBUTE: u8 = 0xae;
+const ATTRIB_STR_UNIT_SERIAL: u8 = 0x01;
+
+// Feature report 0x83 lists numeric attributes as (id, u32 LE) pairs
+const GET_ATTRIBUTES_VALUES: u8 = 0x83;
+const ATTRIB_FIRMWARE_BUILD_TIME: u8 = 0x04;
+const ATTRIB_RADIO_FIRMWARE_BUILD_TIME: u8 = 0x05;
+
+/// Firmware builds as reported by the controller (Unix seconds, the same
+/// stamps Steam shows as the firmware version)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FirmwareInfo {
+    pub build_time: Option<u32>,
+    /// Wireless units only
+    pub radio_build_time: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SteamControllerInfo {
+    pub connected: bool,
+    pub connection_type: String,
+    pub product_name: String,
+    pub serial: String,
+    /// OS-specific HID path of the opened interface
+    pub path: String,
+    /// Wireless dongle slot (0-3), `None` for wired controllers
+    pub slot: Option<u8>,
+    /// Stable ID from the device registry, filled in by the caller
+    pub device_id: Option<String>,
+    /// User-assigned name from the device registry, filled in by the caller
+    pub nickname: Option<String>,
+}
+
+/// Raw HID report descriptor of one Steam Controller interface
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceDescriptor {
+    pub product_id: u16,
+    pub interface_number: i32,
+    pub usage_page: u16,
+    pub usage: u16,
+    pub path: String,
+    /// Whether `connect()` may open this interface (wired vendor interface or a dongle slot)
+    pub selected: bool,
+    /// Descriptor bytes, or `None` if the platform refused to hand them over
+    pub descriptor: Option<Vec<u8>>,
+    pub error: Option<String>,
+}
+
+/// How a read waits for data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadStrategy {
+    /// Wait up to `timeout_ms` for a report
+    Blocking,
+    /// Return immediately if no report is queued
+    NonBlocking,
+}
+
+/// Reader tuning - wireless and BLE paths need different values than wired USB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadSettings {
+    pub timeout_ms: i32,
+    pub buffer_size: usize,
+    pub strategy: ReadStrategy,
+}
+
+impl Default for ReadSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: DEFAULT_READ_TIMEOUT_MS,
+            buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            strategy: ReadStrategy::Blocking,
+        }
+    }
+}
+
+impl ReadSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_ms < 0 {
+            return Err(format!("Read timeout must not be negative: {} ms", self.timeout_ms));
+        }
+        if self.buffer_size == 0 || self.buffer_size > MAX_READ_BUFFER_SIZE {
+            return Err(format!(
+                "Read buffer size must be between 1 and {} bytes: {}",
+                MAX_READ_BUFFER_SIZE, self.buffer_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Timeout to hand to hidapi for the configured strategy
+    pub fn effective_timeout_ms(&self) -> i32 {
+        match self.strategy {
+            ReadStrategy::Blocking => self.timeout_ms,
+            ReadStrategy::NonBlocking => 0,
+        }
+    }
+}
+
+/// The open device; swapped out whole when a stuck call is abandoned
+type DeviceSlot = Arc<Mutex<Option<HidDevice>>>;
+
+const NOT_CONNECTED: &str = "Controller not connected";
+
+/// Cheap to clone - all state lives behind shared `Arc`s, so clones drive the same device.
+///
+/// Locking is split so enumeration never stalls input: `api` is only held to
+/// refresh or open devices, lookups go through the `devices` snapshot, the open
+/// device has its own lock, and settings read on every frame are `RwLock`s.
+/// The API handle and device slot sit behind an extra `RwLock` so
+/// [`recover`](Self::recover) can replace them while a hung call still holds
+/// the old ones. Feature-report writes go through a queue drained between
+/// reads or by a writer thread (see [`crate::write_queue`]), so callers
+/// never wait on the device lock for them.
+#[derive(Clone)]
+pub struct SteamControllerManager {
+    api: Arc<RwLock<Arc<Mutex<HidApi>>>>,
+    /// Device list as of the last refresh
+    devices: Arc<RwLock<Vec<DeviceInfo>>>,
+    device: Arc<RwLock<DeviceSlot>>,
+    /// HID calls in progress, for the watchdog
+    ops: Arc<OpTracker>,
+    /// Cleared when a stuck call is abandoned, set again on the next connect
+    healthy: Arc<AtomicBool>,
+    /// Whether the device slot holds a device, so checking doesn't wait behind a read
+    connected: Arc<AtomicBool>,
+    read_settings: Arc<RwLock<ReadSettings>>,
+    calibration: Arc<RwLock<Calibration>>,
+    haptic_strength: Arc<RwLock<f32>>,
+    /// App-wide haptic scale, shared with siblings
+    haptic_intensity: Arc<RwLock<f32>>,
+    /// App-wide pulses per second per actuator, shared with siblings
+    max_haptic_rate: Arc<RwLock<u32>>,
+    haptic_limiter: Arc<Mutex<HapticRateLimiter>>,
+    parser: Arc<Mutex<InputParser>>,
+    drift: Arc<Mutex<DriftCompensator>>,
+    report_rate: Arc<RwLock<ReportRateConfig>>,
+    write_log: Arc<Mutex<WriteLatencyLog>>,
+    /// Replaced along with the device slot, so a writer stuck in a hung call is left behind
+    writes: Arc<RwLock<Arc<WriteQueue>>>,
+    /// Mouse/keyboard emulation as we last set it (on is the firmware default)
+    lizard_mode: Arc<AtomicBool>,
+}
+
+impl SteamControllerManager {
+    pub fn new() -> Result<Self, String> {
+        let api = HidApi::new().map_err(|e| format!("Failed to create HID API: {}", e))?;
+        let devices = api.device_list().cloned().collect();
+
+        Ok(Self {
+            api: Arc::new(RwLock::new(Arc::new(Mutex::new(api)))),
+            devices: Arc::new(RwLock::new(devices)),
+            device: Arc::new(RwLock::new(Arc::new(Mutex::new(None)))),
+            ops: OpTracker::new(),
+            healthy: Arc::new(AtomicBool::new(true)),
+            connected: Arc::new(AtomicBool::new(false)),
+            read_settings: Arc::new(RwLock::new(ReadSettings::default())),
+            calibration: Arc::new(RwLock::new(Calibration::default())),
+            haptic_strength: Arc::new(RwLock::new(DEFAULT_HAPTIC_STRENGTH)),
+            haptic_intensity: Arc::new(RwLock::new(DEFAULT_HAPTIC_INTENSITY)),
+            max_haptic_rate: Arc::new(RwLock::new(DEFAULT_MAX_HAPTIC_RATE)),
+            haptic_limiter: Arc::new(Mutex::new(HapticRateLimiter::new())),
+            parser: Arc::new(Mutex::new(InputParser::new())),
+            drift: Arc::new(Mutex::new(DriftCompensator::default())),
+            report_rate: Arc::new(RwLock::new(ReportRateConfig::default())),
+            write_log: Arc::new(Mutex::new(WriteLatencyLog::new())),
+            writes: Arc::new(RwLock::new(Arc::new(WriteQueue::new()))),
+            lizard_mode: Arc::new(AtomicBool::new(true)),
+        })
+    }
+
+    /// Re-enumerate HID devices and replace the snapshot. Only the HID API
+    /// handle is held meanwhile; the open device and its reads are untouched.
+    pub fn refresh(&self) -> Result<(), String> {
+        let devices: Vec<DeviceInfo> = {
+            let api = self.api();
+            let mut api = api.lock().unwrap();
+            let _op = self.ops.begin(HidOp::Enumerate);
+            api.refresh_devices()
+                .map_err(|e| format!("Failed to refresh devices: {}", e))?;
+            api.device_list().cloned().collect()
+        };
+        *self.devices.write().unwrap() = devices;
+        Ok(())
+    }
+
+    /// Detect if a Steam Controller is connected
+    pub fn detect(&self) -> Option<SteamControllerInfo> {
+        // Refresh device list
+        if let Err(e) = self.refresh() {
+            eprintln!("{}", e);
+            return None;
+        }
+
+        self.controller_interfaces()
+            .first()
+            .map(|interface| controller_info(interface, None))
+    }
+
+    /// Dump the report descriptor of every Steam Controller interface, for
+    /// diagnosing which interface the OS exposes as the vendor-specific one
+    pub fn report_descriptors(&self) -> Result<Vec<InterfaceDescriptor>, String> {
+        self.refresh()?;
+
+        let devices = self.devices.read().unwrap().clone();
+        let readable: Vec<String> = self.controller_interfaces().into_iter().map(|c| c.entry.path).collect();
+        let mut out = vec![];
+        for device_info in &devices {
+            let pid = device_info.product_id();
+            if device_info.vendor_id() != VALVE_VENDOR_ID
+                || (pid != SC_WIRELESS_PID && pid != SC_WIRED_PID && pid != STEAM_DECK_PID)
+            {
+                continue;
+            }
+
+            let result = self
+                .open_path(device_info.path())
+                .and_then(|device| {
+                    let mut buf = [0u8; hidapi::MAX_REPORT_DESCRIPTOR_SIZE];
+                    let len = device
+                        .get_report_descriptor(&mut buf)
+                        .map_err(|e| format!("Failed to read report descriptor: {}", e))?;
+                    Ok(buf[..len].to_vec())
+                });
+
+            println!("🔍 Interface {} (usage_page=0x{:04x}, usage={}): {}",
+                device_info.interface_number(),
+                device_info.usage_page(),
+                device_info.usage(),
+                match &result {
+                    Ok(bytes) => format!("{} descriptor bytes", bytes.len()),
+                    Err(e) => e.clone(),
+                });
+
+            let path = device_info.path().to_string_lossy().into_owned();
+            let (descriptor, error) = match result {
+                Ok(bytes) => (Some(bytes), None),
+                Err(e) => (None, Some(e)),
+            };
+            out.push(InterfaceDescriptor {
+                product_id: pid,
+                interface_number: device_info.interface_number(),
+                usage_page: device_info.usage_page(),
+                usage: device_info.usage(),
+                path: path.clone(),
+                selected: readable.contains(&path),
+                descriptor,
+                error,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// This manager as a device-independent [`ControllerHandle`] for reader
+    /// threads; it shares the connection, settings and parser
+    pub fn handle(&self) -> ControllerHandle {
+        Arc::new(self.clone())
+    }
+
+    /// A manager for another controller, sharing this one's HID API handle
+    pub fn sibling(&self) -> Self {
+        Self {
+            api: Arc::clone(&self.api),
+            devices: Arc::clone(&self.devices),
+            device: Arc::new(RwLock::new(Arc::new(Mutex::new(None)))),
+            ops: OpTracker::new(),
+            healthy: Arc::new(AtomicBool::new(true)),
+            connected: Arc::new(AtomicBool::new(false)),
+            read_settings: Arc::new(RwLock::new(self.read_settings())),
+            calibration: Arc::new(RwLock::new(Calibration::default())),
+            haptic_strength: Arc::new(RwLock::new(DEFAULT_HAPTIC_STRENGTH)),
+            haptic_intensity: Arc::clone(&self.haptic_intensity),
+            max_haptic_rate: Arc::clone(&self.max_haptic_rate),
+            haptic_limiter: Arc::new(Mutex::new(HapticRateLimiter::new())),
+            parser: Arc::new(Mutex::new(InputParser::new())),
+            drift: Arc::new(Mutex::new(DriftCompensator::default())),
+            report_rate: Arc::new(RwLock::new(ReportRateConfig::default())),
+            write_log: Arc::new(Mutex::new(WriteLatencyLog::new())),
+            writes: Arc::new(RwLock::new(Arc::new(WriteQueue::new()))),
+            lizard_mode: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// HID interfaces matching `filter` from this manager's API handle; the
+    /// cached list is re-enumerated only when `refresh` is set
+    pub fn list_devices(&self, filter: &DeviceFilter, refresh: bool) -> Result<Vec<HidDeviceEntry>, String> {
+        if refresh {
+            self.refresh()?;
+        }
+        Ok(enumeration::list_devices(self.devices.read().unwrap().iter(), filter))
+    }
+
+    /// Every interface a controller can be read from (wired controllers and
+    /// each dongle slot), without opening them. `serial` is the USB serial
+    /// (the dongle's, for wireless units).
+    pub fn list_controllers(&self) -> Result<Vec<SteamControllerInfo>, String> {
+        self.refresh()?;
+
+        Ok(self
+            .controller_interfaces()
+            .iter()
+            .map(|interface| controller_info(interface, None))
+            .collect())
+    }
+
+    /// Dongle slots with a controller answering on them, probed by asking
+    /// each for its unit serial. A paired controller that is asleep or out of
+    /// range doesn't answer and isn't listed. `connected` is returned as is
+    /// for its own slot rather than probed, so the probe can't interleave with
+    /// feature reports on the open handle.
+    pub fn list_paired_controllers(&self, connected: Option<&SteamControllerInfo>) -> Result<Vec<SteamControllerInfo>, String> {
+        self.refresh()?;
+
+        let mut out = Vec::new();
+        for interface in self.controller_interfaces().iter().filter(|c| c.slot.is_some()) {
+            if let Some(info) = connected.filter(|info| info.path == interface.entry.path) {
+                out.push(info.clone());
+                continue;
+            }
+            let serial = CString::new(interface.entry.path.as_str())
+                .map_err(|e| format!("Invalid device path: {}", e))
+                .and_then(|path| self.open_path(&path))
+                .and_then(|device| {
+                    let _op = self.ops.begin(HidOp::FeatureRead);
+                    read_unit_serial(&device)
+                });
+            match serial {
+                Ok(serial) => out.push(controller_info(interface, Some(serial))),
+                Err(e) => println!("⏭️ Dongle slot {}: {}", interface.slot.unwrap_or(0), e),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Make the first dongle accept new controllers for `timeout_secs`.
+    /// A controller paired in that window is only kept once
+    /// [`stop_pairing`](Self::stop_pairing) commits it.
+    pub fn start_pairing(&self, timeout_secs: u8) -> Result<(), String> {
+        self.send_dongle_command(&dongle::pairing_report(true, timeout_secs)?)?;
+        println!("📡 Dongle pairing for {} s", timeout_secs);
+        Ok(())
+    }
+
+    /// End the pairing window, keeping (`keep`) or dropping whatever paired in it
+    pub fn stop_pairing(&self, keep: bool) -> Result<(), String> {
+        let report = if keep { dongle::commit_report() } else { dongle::pairing_report(false, 0)? };
+        self.send_dongle_command(&report)?;
+        println!("📡 Dongle pairing {}", if keep { "committed" } else { "cancelled" });
+        Ok(())
+    }
+
+    // Receiver commands go to the dongle's control interface, opened just for them
+    fn send_dongle_command(&self, report: &[u8]) -> Result<(), String> {
+        self.refresh()?;
+        let control = dongle::control_interfaces(&self.valve_devices())
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No wireless dongle found".to_string())?;
+        let device = CString::new(control.path.as_str())
+            .map_err(|e| format!("Invalid device path: {}", e))
+            .and_then(|path| self.open_path(&path))?;
+        let _op = self.ops.begin(HidOp::Write);
+        device
+            .send_feature_report(report)
+            .map_err(|e| format!("Dongle command 0x{:02x} failed: {}", report[0], e))
+    }
+
+    // Controller interfaces in the current snapshot
+    fn controller_interfaces(&self) -> Vec<ControllerInterface> {
+        dongle::controller_interfaces(&self.valve_devices())
+    }
+
+    fn valve_devices(&self) -> Vec<HidDeviceEntry> {
+        let filter = DeviceFilter {
+            vendor_id: Some(VALVE_VENDOR_ID),
+            ..Default::default()
+        };
+        enumeration::list_devices(self.devices.read().unwrap().iter(), &filter)
+    }
+
+    // The API handle is only needed for the open itself
+    fn open_path(&self, path: &CStr) -> Result<HidDevice, String> {
+        let api = self.api();
+        let api = api.lock().unwrap();
+        let _op = self.ops.begin(HidOp::Open);
+        api.open_path(path)
+            .map_err(|e| format!("Failed to open device path: {}", e))
+    }
+
+    fn api(&self) -> Arc<Mutex<HidApi>> {
+        Arc::clone(&self.api.read().unwrap())
+    }
+
+    fn slot(&self) -> DeviceSlot {
+        Arc::clone(&self.device.read().unwrap())
+    }
+
+    /// Connect to the Steam Controller
+    pub fn connect(&self) -> Result<SteamControllerInfo, String> {
+        self.connect_matching(None)
+    }
+
+    /// Connect to the controller at `path` (from [`list_controllers`](Self::list_controllers))
+    pub fn connect_path(&self, path: &str) -> Result<SteamControllerInfo, String> {
+        self.connect_matching(Some(path))
+    }
+
+    /// Connect to the controller in dongle slot `slot` (0-3), on the first
+    /// dongle that has one. If its controller is asleep the slot is opened
+    /// anyway and starts reporting once it wakes up.
+    pub fn connect_slot(&self, slot: u8) -> Result<SteamControllerInfo, String> {
+        if slot >= DONGLE_SLOTS {
+            return Err(format!("Dongle slot must be 0-{}, got {}", DONGLE_SLOTS - 1, slot));
+        }
+        self.refresh()?;
+        let path = self
+            .controller_interfaces()
+            .into_iter()
+            .find(|c| c.slot == Some(slot))
+            .map(|c| c.entry.path)
+            .ok_or_else(|| format!("No wireless dongle with slot {} found", slot))?;
+        self.connect_matching(Some(&path))
+    }
+
+    fn connect_matching(&self, path: Option<&str>) -> Result<SteamControllerInfo, String> {
+        let candidates: Vec<ControllerInterface> = self
+            .controller_interfaces()
+            .into_iter()
+            .filter(|c| path.map_or(true, |wanted| c.entry.path == wanted))
+            .collect();
+
+        // A dongle slot opens fine with no controller behind it, so a slot only
+        // wins once its controller answers; otherwise the first slot is kept
+        let mut idle: Option<(ControllerInterface, HidDevice)> = None;
+        let mut last_error = None;
+        for candidate in candidates {
+            println!("✅ Opening {} interface {} (usage_page=0x{:04x}, usage={})",
+                candidate.slot.map_or("vendor-specific".to_string(), |slot| format!("dongle slot {}", slot)),
+                candidate.entry.interface_number,
+                candidate.entry.usage_page,
+                candidate.entry.usage);
+
+            let device = match CString::new(candidate.entry.path.as_str())
+                .map_err(|e| format!("Invalid device path: {}", e))
+                .and_then(|path| self.open_path(&path))
+            {
+                Ok(device) => device,
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+
+            if candidate.slot.is_none() {
+                let info = controller_info(&candidate, None);
+                return Ok(self.store_device(device, info));
+            }
+
+            let serial = {
+                let _op = self.ops.begin(HidOp::FeatureRead);
+                read_unit_serial(&device)
+            };
+            match serial {
+                Ok(serial) => {
+                    let info = controller_info(&candidate, Some(serial));
+                    return Ok(self.store_device(device, info));
+                }
+                Err(e) => {
+                    println!("⏭️ No controller answering on dongle slot {}: {}", candidate.slot.unwrap_or(0), e);
+                    if idle.is_none() {
+                        idle = Some((candidate, device));
+                    }
+                }
+            }
+        }
+
+        // Controllers asleep or out of range: listen on the first slot, which
+        // starts reporting once its controller wakes up
+        if let Some((candidate, device)) = idle {
+            let info = controller_info(&candidate, None);
+            return Ok(self.store_device(device, info));
+        }
+        if let Some(e) = last_error {
+            return Err(e);
+        }
+        match path {
+            Some(path) => Err(format!("Steam Controller not found at {}", path)),
+            None => Err("Steam Controller not found".to_string()),
+        }
+    }
+
+    // Make `device` the open device and start parsing afresh
+    fn store_device(&self, device: HidDevice, info: SteamControllerInfo) -> SteamControllerInfo {
+        // Store the device
+        let slot = self.slot();
+        let mut device_lock = slot.lock().unwrap();
+        *device_lock = Some(device);
+        drop(device_lock); // Release lock
+        self.connected.store(true, Ordering::Release);
+        self.parser.lock().unwrap().reset();
+        self.drift.lock().unwrap().reset();
+        self.healthy.store(true, Ordering::Relaxed);
+
+        // NOTE: NOT disabling Lizard Mode for now - trying to read data
+        // while mouse emulation is still active. Many Steam Controller
+        // projects do this successfully.
+        println!("📡 Connected to vendor-specific interface - ready to read raw data");
+        println!("   (Lizard Mode still active - mouse will continue working)");
+        info
+    }
+
+    /// Disable Lizard Mode (mouse/keyboard emulation)
+    /// This allows us to read raw HID input data
+    fn disable_lizard_mode(&self) -> Result<(), String> {
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+
+        // Command 1: Disable mouse emulation
+        // Feature report 0x81 - turns off the default mouse behavior
+        let disable_mouse = vec![0x81, 0x00];
+
+        self.write_and_wait(WriteKind::LizardMode, disable_mouse)
+            .map_err(|e| format!("Failed to disable mouse mode: {}", e))?;
+
+        println!("  ✓ Mouse emulation disabled");
+
+        // Small delay
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        // Command 2: Enable full input mode
+        // Feature report 0x87 - configures the controller for raw input
+        let enable_input = registers::settings_report(&registers::raw_input_registers(
+            &self.report_rate.read().unwrap(),
+        ))?;
+
+        self.write_and_wait(WriteKind::Configuration, enable_input)
+            .map_err(|e| format!("Failed to enable input mode: {}", e))?;
+
+        println!("  ✓ Raw input mode enabled");
+        self.lizard_mode.store(false, Ordering::Relaxed);
+        println!("✅ Lizard Mode disabled - controller ready for raw input!");
+
+        Ok(())
+    }
+
+    /// Check if currently connected
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Acquire)
+    }
+
+    /// Disconnect from the device
+    pub fn disconnect(&self) {
+        // Re-enable Lizard Mode before disconnecting
+        println!("🦎 Re-enabling Lizard Mode...");
+        let _ = self.enable_lizard_mode();
+
+        self.connected.store(false, Ordering::Release);
+        *self.slot().lock().unwrap() = None;
+        self.write_queue().clear(NOT_CONNECTED);
+        println!("✅ Controller disconnected");
+    }
+
+    /// Whether mouse/keyboard emulation is on, as far as our own writes go
+    pub fn lizard_mode(&self) -> bool {
+        self.lizard_mode.load(Ordering::Relaxed)
+    }
+
+    /// Re-enable Lizard Mode (mouse/keyboard emulation)
+    /// This restores default controller behavior
+    pub fn enable_lizard_mode(&self) -> Result<(), String> {
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+
+        // Enable mouse emulation
+        let enable_mouse = vec![0x81, 0x01];
+
+        self.write_and_wait(WriteKind::LizardMode, enable_mouse)
+            .map_err(|e| format!("Failed to enable mouse mode: {}", e))?;
+
+        self.lizard_mode.store(true, Ordering::Relaxed);
+        println!("  ✓ Mouse emulation re-enabled");
+        Ok(())
+    }
+
+    /// Play a haptic burst on one of the trackpad actuators (feature report 0x8f),
+    /// scaled by the unit's haptic strength and the global intensity. Returns
+    /// once the pulse is queued; a pulse still waiting for the same side is
+    /// replaced, and write failures are logged and counted in the write stats.
+    /// Pulses over the rate limit are held and go out, merged with any later
+    /// ones, when their side is due again (checked between reads).
+    pub fn send_haptic_pulse(&self, pulse: HapticPulse) -> Result<(), String> {
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        let max_rate = self.max_haptic_rate();
+        if let Some(pulse) = self.haptic_limiter.lock().unwrap().admit(pulse, clock::monotonic_us(), max_rate) {
+            self.enqueue_haptic(pulse);
+        }
+        Ok(())
+    }
+
+    // Send pulses the rate limiter held back whose side is due again
+    fn flush_haptics(&self) {
+        let max_rate = self.max_haptic_rate();
+        for pulse in self.haptic_limiter.lock().unwrap().flush(clock::monotonic_us(), max_rate) {
+            self.enqueue_haptic(pulse);
+        }
+    }
+
+    fn enqueue_haptic(&self, pulse: HapticPulse) {
+        let scale = *self.haptic_strength.read().unwrap() * *self.haptic_intensity.read().unwrap();
+        self.enqueue_write(QueuedWrite {
+            kind: WriteKind::Haptics,
+            data: pulse.scaled(scale).to_report(),
+            coalesce: Some(pulse.side as u8),
+            reply: None,
+        });
+    }
+
+    pub fn max_haptic_rate(&self) -> u32 {
+        *self.max_haptic_rate.read().unwrap()
+    }
+
+    /// Cap pulses per second on each actuator (0 for no cap); applies to the next pulse
+    pub fn set_max_haptic_rate(&self, rate: u32) -> Result<(), String> {
+        validate_max_haptic_rate(rate)?;
+        *self.max_haptic_rate.write().unwrap() = rate;
+        Ok(())
+    }
+
+    /// Apply the connected unit's calibration, haptic strength and report layout
+    pub fn apply_device_settings(&self, settings: &DeviceSettings) {
+        *self.calibration.write().unwrap() = settings.calibration;
+        *self.haptic_strength.write().unwrap() = settings.haptic_strength;
+        self.parser.lock().unwrap().set_layout(settings.report_layout.clone());
+        self.drift.lock().unwrap().set_config(settings.anti_drift);
+    }
+
+    /// Resting stick offset currently being corrected by anti-drift
+    pub fn drift_offset(&self) -> (i16, i16) {
+        self.drift.lock().unwrap().offset()
+    }
+
+    /// Drift warning raised since the last call, if any
+    pub fn take_drift_warning(&self) -> Option<DriftWarning> {
+        self.drift.lock().unwrap().take_warning()
+    }
+
+    pub fn haptic_intensity(&self) -> f32 {
+        *self.haptic_intensity.read().unwrap()
+    }
+
+    /// Scale every pad haptic by `intensity` (0.0-1.0); applies to the next pulse
+    pub fn set_haptic_intensity(&self, intensity: f32) -> Result<(), String> {
+        validate_haptic_intensity(intensity)?;
+        *self.haptic_intensity.write().unwrap() = intensity;
+        Ok(())
+    }
+
+    /// Offsets to subtract from parsed input
+    pub fn calibration(&self) -> Calibration {
+        *self.calibration.read().unwrap()
+    }
+
+    /// Report rate / smoothing settings last written to the controller
+    pub fn report_rate(&self) -> ReportRateConfig {
+        *self.report_rate.read().unwrap()
+    }
+
+    /// Write report rate, smoothing and IMU reporting (0x87 registers) and keep
+    /// them for the next connect
+    pub fn set_report_rate(&self, config: ReportRateConfig) -> Result<(), String> {
+        let report = SettingsBuilder::new().rate(&config).build()?;
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        self.write_and_wait(WriteKind::Configuration, report)
+            .map_err(|e| format!("Failed to write report rate settings: {}", e))?;
+        *self.report_rate.write().unwrap() = config;
+        println!("✅ Report rate updated: {:?}", config);
+        Ok(())
+    }
+
+    /// Stop IMU reporting to save power, or turn it back on (raw data, or
+    /// whatever was last set); returns the resulting report rate config
+    pub fn set_imu_enabled(&self, enabled: bool) -> Result<ReportRateConfig, String> {
+        let mut config = self.report_rate();
+        config.imu = match (enabled, config.imu) {
+            (false, _) => ImuReporting::Off,
+            (true, ImuReporting::Off) => ReportRateConfig::default().imu,
+            (true, imu) => imu,
+        };
+        self.set_report_rate(config)?;
+        Ok(config)
+    }
+
+    /// Set what the firmware does with one pad, or both, while it emulates a
+    /// mouse (0x87 trackpad mode registers)
+    pub fn set_trackpad_mode(&self, pad: Option<Pad>, mode: TrackpadMode) -> Result<(), String> {
+        let pads = match pad {
+            Some(pad) => vec![pad],
+            None => vec![Pad::Left, Pad::Right],
+        };
+        let report = pads
+            .into_iter()
+            .fold(SettingsBuilder::new(), |builder, pad| builder.trackpad_mode(pad, mode))
+            .build()?;
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        self.write_and_wait(WriteKind::Configuration, report)
+            .map_err(|e| format!("Failed to write trackpad mode: {}", e))?;
+        println!("✅ Trackpad mode {:?} on {:?}", mode, pad.map_or("both pads".to_string(), |p| format!("{:?} pad", p)));
+        Ok(())
+    }
+
+    /// Play built-in melody `id` (0xb6). Returns once queued, like a tone.
+    pub fn play_melody(&self, id: u8) -> Result<(), String> {
+        let report = melody::play_melody_report(id)?;
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        self.enqueue_write(QueuedWrite { kind: WriteKind::Audio, data: report, coalesce: None, reply: None });
+        Ok(())
+    }
+
+    /// Sound one tone on a pad actuator. Skips the haptic rate limit and
+    /// strength scaling (both would distort it); muted at zero intensity.
+    pub fn play_tone(&self, tone: Tone) -> Result<(), String> {
+        let pulse = tone.to_pulse()?;
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        if *self.haptic_strength.read().unwrap() * *self.haptic_intensity.read().unwrap() <= 0.0 {
+            return Ok(());
+        }
+        self.enqueue_write(QueuedWrite {
+            kind: WriteKind::Haptics,
+            data: pulse.to_report(),
+            coalesce: Some(pulse.side as u8),
+            reply: None,
+        });
+        Ok(())
+    }
+
+    /// Assign the melodies played at power on and off (0xc1); the controller
+    /// keeps them. Returns once queued; a failed write is logged by the queue.
+    pub fn set_jingles(&self, jingles: Jingles) -> Result<(), String> {
+        let report = jingles.to_report()?;
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        self.enqueue_write(QueuedWrite { kind: WriteKind::Audio, data: report, coalesce: None, reply: None });
+        println!("🎵 Jingles: startup {}, shutdown {}", jingles.startup, jingles.shutdown);
+        Ok(())
+    }
+
+    fn write_queue(&self) -> Arc<WriteQueue> {
+        Arc::clone(&self.writes.read().unwrap())
+    }
+
+    // Queue a write, starting the writer thread if it isn't running
+    fn enqueue_write(&self, write: QueuedWrite) {
+        let queue = self.write_queue();
+        if queue.push(write) {
+            let manager = self.clone();
+            thread::spawn(move || manager.run_writer(queue));
+        }
+    }
+
+    // Queue a write and wait for the writer to perform it
+    fn write_and_wait(&self, kind: WriteKind, data: Vec<u8>) -> Result<(), String> {
+        let (reply, result) = mpsc::channel();
+        self.enqueue_write(QueuedWrite { kind, data, coalesce: None, reply: Some(reply) });
+        result
+            .recv()
+            .unwrap_or_else(|_| Err("Write was dropped".to_string()))
+    }
+
+    // Writer thread: drains `queue` until it has been idle for a while. While
+    // a reader holds the device it sends the writes itself, between reads.
+    fn run_writer(&self, queue: Arc<WriteQueue>) {
+        while queue.wait(WRITER_IDLE_TIMEOUT) {
+            let slot = self.slot();
+            let device_lock = slot.lock().unwrap();
+            queue.drain(|write| match device_lock.as_ref() {
+                Some(device) => self.timed_write(device, write.kind, &write.data).map_err(|e| e.to_string()),
+                None => Err(NOT_CONNECTED.to_string()),
+            });
+        }
+    }
+
+    /// Feature-report writes waiting for the writer thread
+    pub fn pending_writes(&self) -> usize {
+        self.write_queue().len()
+    }
+
+    // Every feature-report write goes through here so its latency is recorded
+    fn timed_write(&self, device: &HidDevice, kind: WriteKind, data: &[u8]) -> Result<(), hidapi::HidError> {
+        let at_us = clock::monotonic_us();
+        let started = Instant::now();
+        let result = {
+            let _op = self.ops.begin(HidOp::Write);
+            device.send_feature_report(data)
+        };
+        let report_id = data.first().copied().unwrap_or(0);
+        self.write_log
+            .lock()
+            .unwrap()
+            .record(kind, report_id, started.elapsed(), result.is_ok(), at_us);
+        result
+    }
+
+    /// Latency of recent feature-report writes, with the last `recent` samples
+    pub fn write_stats(&self, recent: usize) -> WriteStats {
+        self.write_log.lock().unwrap().stats(recent)
+    }
+
+    /// (writes, failures) of one kind since this manager was created
+    pub fn write_totals(&self, kind: WriteKind) -> (u64, u64) {
+        self.write_log.lock().unwrap().totals(kind)
+    }
+
+    pub fn clear_write_stats(&self) {
+        self.write_log.lock().unwrap().clear();
+    }
+
+    /// Send an arbitrary feature report (first byte is the report/command ID)
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), String> {
+        if data.is_empty() || data.len() > MAX_FEATURE_REPORT_SIZE {
+            return Err(format!(
+                "Feature report must be 1-{} bytes, got {}",
+                MAX_FEATURE_REPORT_SIZE,
+                data.len()
+            ));
+        }
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        self.write_and_wait(WriteKind::Raw, data.to_vec())
+            .map_err(|e| format!("Failed to send feature report: {}", e))?;
+        // Lizard Mode toggled from the console (0x81 00 turns it off)
+        if data[0] == 0x81 {
+            self.lizard_mode.store(data.get(1) != Some(&0), Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Read feature report `report_id`, up to `len` bytes (including the ID byte)
+    pub fn get_feature_report(&self, report_id: u8, len: usize) -> Result<Vec<u8>, String> {
+        if len == 0 || len > MAX_FEATURE_REPORT_SIZE {
+            return Err(format!(
+                "Feature report length must be 1-{}, got {}",
+                MAX_FEATURE_REPORT_SIZE, len
+            ));
+        }
+        let slot = self.slot();
+        let device_lock = slot.lock().unwrap();
+        match device_lock.as_ref() {
+            Some(device) => {
+                let mut buf = vec![0u8; len];
+                buf[0] = report_id;
+                let _op = self.ops.begin(HidOp::FeatureRead);
+                let size = device
+                    .get_feature_report(&mut buf)
+                    .map_err(|e| format!("Failed to read feature report: {}", e))?;
+                buf.truncate(size);
+                Ok(buf)
+            }
+            None => Err(NOT_CONNECTED.to_string()),
+        }
+    }
+
+    /// Ask the controller for its firmware build stamps (feature report 0x83)
+    pub fn firmware_info(&self) -> Result<FirmwareInfo, String> {
+        if !self.is_connected() {
+            return Err(NOT_CONNECTED.to_string());
+        }
+        let mut request = vec![0u8; 64];
+        request[0] = GET_ATTRIBUTES_VALUES;
+        self.write_and_wait(WriteKind::Configuration, request)
+            .map_err(|e| format!("Failed to request attributes: {}", e))?;
+        // Leading report ID byte plus the 64-byte reply
+        let reply = self.get_feature_report(0, 65)?;
+        let attributes = parse_attributes(&reply).ok_or_else(|| "Unexpected attributes reply".to_string())?;
+        let find = |id: u8| attributes.iter().find(|(attr, _)| *attr == id).map(|&(_, value)| value);
+        Ok(FirmwareInfo {
+            build_time: find(ATTRIB_FIRMWARE_BUILD_TIME),
+            radio_build_time: find(ATTRIB_RADIO_FIRMWARE_BUILD_TIME).filter(|&t| t != 0),
+        })
+    }
+
+    /// Parse a report from this controller and apply its calibration. Keeps
+    /// stick/left-pad state across frames, so feed it every report in order.
+    /// Stamps the frame with the current host time; reader threads that want the
+    /// exact read time use [`parse_report_at`](Self::parse_report_at).
+    pub fn parse_report(&self, raw: &[u8]) -> Result<ControllerInput, String> {
+        self.parse_report_at(raw, clock::monotonic_us())
+    }
+
+    /// Like `parse_report`, with the host time (µs, [`clock::monotonic_us`]) the report was read
+    pub fn parse_report_at(&self, raw: &[u8], host_timestamp_us: u64) -> Result<ControllerInput, String> {
+        match self.parse_packet_at(raw, host_timestamp_us)? {
+            Packet::InputFrame(input) => Ok(input),
+            other => Err(format!("Not an input report: {:?}", other)),
+        }
+    }
+
+    /// Parse any packet from this controller; input frames are stamped with
+    /// `host_timestamp_us`, calibrated and drift-corrected like
+    /// [`parse_report_at`](Self::parse_report_at)
+    pub fn parse_packet_at(&self, raw: &[u8], host_timestamp_us: u64) -> Result<Packet, String> {
+        let mut packet = self.parser.lock().unwrap().parse_packet(raw)?;
+        if let Packet::InputFrame(input) = &mut packet {
+            input.host_timestamp_us = host_timestamp_us;
+            self.calibration().apply(input);
+            self.drift.lock().unwrap().apply(input);
+        }
+        Ok(packet)
+    }
+
+    /// Get the HID device for reading/writing
+    pub fn get_device(&self) -> Arc<Mutex<Option<HidDevice>>> {
+        self.slot()
+    }
+
+    /// A HID call that has been running for longer than `threshold`, if any
+    pub fn stuck_operation(&self, threshold: Duration) -> Option<StuckOperation> {
+        self.ops.stuck(Instant::now(), threshold)
+    }
+
+    /// False after a stuck call was abandoned, until the next successful connect
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    /// Give up on a stuck HID call: leave the hung handle (and whatever thread
+    /// is blocked in it) behind with an empty device slot, and start over with
+    /// a fresh hidapi handle. Afterwards the manager reads as disconnected.
+    pub fn recover(&self) -> Result<(), String> {
+        self.healthy.store(false, Ordering::Relaxed);
+        self.connected.store(false, Ordering::Release);
+        *self.device.write().unwrap() = Arc::new(Mutex::new(None));
+        let stale = std::mem::replace(&mut *self.writes.write().unwrap(), Arc::new(WriteQueue::new()));
+        stale.clear(NOT_CONNECTED);
+        self.ops.clear();
+
+        let api = HidApi::new().map_err(|e| format!("Failed to reinitialize HID API: {}", e))?;
+        *self.devices.write().unwrap() = api.device_list().cloned().collect();
+        *self.api.write().unwrap() = Arc::new(Mutex::new(api));
+        Ok(())
+    }
+
+    /// Current reader settings
+    pub fn read_settings(&self) -> ReadSettings {
+        *self.read_settings.read().unwrap()
+    }
+
+    /// Replace the reader settings (applies to the next read)
+    pub fn set_read_settings(&self, settings: ReadSettings) -> Result<(), String> {
+        settings.validate()?;
+        *self.read_settings.write().unwrap() = settings;
+        println!("✅ Read settings updated: {:?}", settings);
+        Ok(())
+    }
+
+    /// Read input from the controller using the configured timeout, buffer size and strategy.
+    /// `Ok(None)` means no report arrived before the timeout.
+    pub fn read_input(&self) -> Result<Option<Vec<u8>>, ControllerError> {
+        let settings = self.read_settings();
+        self.read_with(settings.buffer_size, settings.effective_timeout_ms())
+    }
+
+    /// Read and wait for input (blocking with timeout); `Ok(None)` on timeout
+    pub fn read_input_blocking(&self, timeout_ms: i32) -> Result<Option<Vec<u8>>, ControllerError> {
+        self.read_with(self.read_settings().buffer_size, timeout_ms)
+    }
+
+    /// Read one report into a caller-owned buffer (blocking with timeout), so a
+    /// reader thread can reuse one buffer for every frame. Returns the report
+    /// length; 0 means no report arrived before the timeout. Queued writes are
+    /// sent between short slices of the read rather than waiting it out.
+    pub fn read_input_into(&self, buf: &mut [u8], timeout_ms: i32) -> Result<usize, ControllerError> {
+        let slot = self.slot();
+        let device_lock = slot.lock().unwrap();
+        let device = device_lock.as_ref().ok_or(ControllerError::NotConnected)?;
+
+        // Negative waits for a report
+        let timeout = u64::try_from(timeout_ms).ok().map(Duration::from_millis);
+        self.write_queue().read_between_writes(
+            timeout,
+            |slice| {
+                self.flush_haptics();
+                let _op = self.ops.begin(HidOp::Read);
+                device
+                    .read_timeout(buf, slice.as_millis() as i32)
+                    .map_err(|e| ControllerError::Read(e.to_string()))
+            },
+            |write| self.timed_write(device, write.kind, &write.data).map_err(|e| e.to_string()),
+        )
+    }
+
+    fn read_with(&self, buffer_size: usize, timeout_ms: i32) -> Result<Option<Vec<u8>>, ControllerError> {
+        let mut buf = vec![0u8; buffer_size];
+        match self.read_input_into(&mut buf, timeout_ms)? {
+            0 => Ok(None),
+            size => {
+                buf.truncate(size);
+                Ok(Some(buf))
+            }
+        }
+    }
+}
+
+/// Info for a controller interface; `serial` defaults to the USB serial
+fn controller_info(interface: &ControllerInterface, serial: Option<String>) -> SteamControllerInfo {
+    let entry = &interface.entry;
+    SteamControllerInfo {
+        connected: true,
+        connection_type: match entry.product_id {
+            SC_WIRELESS_PID => "Wireless",
+            STEAM_DECK_PID => "Built-in",
+            _ => "Wired",
+        }
+        .to_string(),
+        product_name: match (entry.product_id, entry.product.as_str()) {
+            (STEAM_DECK_PID, "Unknown") => "Steam Deck".to_string(),
+            (_, "Unknown") => "Steam Controller".to_string(),
+            _ => entry.product.clone(),
+        },
+        serial: serial
+            .or_else(|| entry.serial.clone())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        path: entry.path.clone(),
+        slot: interface.slot,
+        device_id: None,
+        nickname: None,
+    }
+}
+
+/// Ask the controller itself for its serial number (feature report 0xae)
+fn read_unit_serial(device: &HidDevice) -> Result<String, String> {
+    let mut request = vec![0u8; 64];
+    request[0] = GET_STRING_ATTRIBUTE;
+    request[1] = 0x15;
+    request[2] = ATTRIB_STR_UNIT_SERIAL;
+    device
+        .send_feature_report(&request)
+        .map_err(|e| format!("Failed to request serial: {}", e))?;
+
+    // Leading report ID byte plus the 64-byte reply
+    let mut reply = vec![0u8; 65];
+    let size = device
+        .get_feature_report(&mut reply)
+        .map_err(|e| format!("Failed to read serial: {}", e))?;
+    reply.truncate(size);
+    parse_unit_serial(&reply).ok_or_else(|| "Unexpected serial reply".to_string())
+}
+
+/// Reply layout: [0xae, length, 0x01, serial...], optionally behind a report ID byte
+fn parse_unit_serial(reply: &[u8]) -> Option<String> {
+    let reply = match reply.first() {
+        Some(0) => &reply[1..],
+        _ => reply,
+    };
+    if reply.len() < 4 || reply[0] != GET_STRING_ATTRIBUTE || reply[2] != ATTRIB_STR_UNIT_SERIAL {
+        return None;
+    }
+    // The length byte counts the attribute ID too
+    let end = (3 + reply[1].saturating_sub(1) as usize).min(reply.len());
+    let serial: String = reply[3..end]
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| b as char)
+        .collect();
+    if serial.is_empty() {
+        None
+    } else {
+        Some(serial)
+    }
+}
+
+/// Reply layout: [0x83, length, (id, u32 LE)...], optionally behind a report ID byte
+fn parse_attributes(reply: &[u8]) -> Option<Vec<(u8, u32)>> {
+    let reply = match reply.first() {
+        Some(0) => &reply[1..],
+        _ => reply,
+    };
+    if reply.len() < 2 || reply[0] != GET_ATTRIBUTES_VALUES {
+        return None;
+    }
+    let end = (2 + reply[1] as usize).min(reply.len());
+    Some(
+        reply[2..end]
+            .chunks_exact(5)
+            .map(|a| (a[0], u32::from_le_bytes([a[1], a[2], a[3], a[4]])))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_attributes() {
+        let mut reply = vec![0x00, 0x83, 0x0a, 0x01, 0x02, 0x11, 0x00, 0x00, 0x04, 0x80, 0x2f, 0x8c, 0x5a];
+        reply.resize(65, 0);
+        assert_eq!(parse_attributes(&reply), Some(vec![(0x01, 0x1102), (0x04, 1_519_136_640)]));
+        assert_eq!(parse_attributes(&[0xae, 0x00]), None);
+    }
+
+    #[test]
+    fn test_parse_unit_serial() {
+        let mut reply = vec![0x00, 0xae, 0x0b, 0x01];
+        reply.extend_from_slice(b"FXAA12345\0");
+        reply.resize(65, 0);
+        assert_eq!(parse_unit_serial(&reply).as_deref(), Some("FXAA12345"));
+        assert_eq!(parse_unit_serial(&reply[1..]).as_deref(), Some("FXAA12345"));
+        assert_eq!(parse_unit_serial(&[0x00, 0x87, 0x03, 0x01, 0x41]), None);
+        assert_eq!(parse_unit_serial(&[0xae, 0x01, 0x01, 0x00]), None);
+    }
+
+    #[test]
+    fn test_manager_creation() {
+        let manager = SteamControllerManager::new();
+        assert!(manager.is_ok());
+    }
+
+    #[test]
+    fn test_detection() {
+        let manager = SteamControllerManager::new().unwrap();
+        let result = manager.detect();
+        // Won't fail even if no controller is present
+        println!("Detection result: {:?}", result);
+    }
+
+    #[test]
+    fn test_read_settings_validation() {
+        let manager = SteamControllerManager::new().unwrap();
+        assert_eq!(manager.read_settings(), ReadSettings::default());
+
+        let bad_buffer = ReadSettings { buffer_size: 0, ..ReadSettings::default() };
+        assert!(manager.set_read_settings(bad_buffer).is_err());
+
+        let bad_timeout = ReadSettings { timeout_ms: -5, ..ReadSettings::default() };
+        assert!(manager.set_read_settings(bad_timeout).is_err());
+
+        let wireless = ReadSettings {
+            timeout_ms: 25,
+            buffer_size: 128,
+            strategy: ReadStrategy::NonBlocking,
+        };
+        assert!(manager.set_read_settings(wireless).is_ok());
+        assert_eq!(manager.read_settings(), wireless);
+        assert_eq!(wireless.effective_timeout_ms(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Steam Deck built-in controls (PID 0x1205). The Deck enumerates like a
+//! wired Steam Controller with its controls on interface 2, and its state
+//! reports share the 0x01 report ID with packet type 0x09 and a longer
+//! layout:
+//!
+//! - Bytes 4-7: Packet counter (32-bit LE)
+//! - Bytes 8-10: Buttons, the same bits as a Steam Controller; byte 9's low
+//!   nibble is the D-pad (0x01 up, 0x02 right, 0x04 left, 0x08 down), 0x80
+//!   in byte 9 is L5 and 0x01 in byte 10 is R5
+//! - Byte 11: 0x04=R3 (right stick click)
+//! - Byte 13: 0x02=L4, 0x04=R4
+//! - Byte 14: 0x04=Quick Access
+//! - Bytes 16-23: Left and right pad X,Y (16-bit LE)
+//! - Bytes 24-29: Accelerometer X,Y,Z; bytes 30-35: gyro X,Y,Z
+//! - Bytes 36-43: Orientation quaternion W,X,Y,Z
+//! - Bytes 44-47: Left and right trigger (0-32767, LE)
+//! - Bytes 48-55: Left and right stick X,Y (16-bit LE)
+//!
+//! Frames come out as [`ControllerInput`] so profiles work unchanged: the
+//! left stick is the stick, the right stick fills `right_stick`, L4/L5 are
+//! the left grip and R4/R5 the right grip, and a D-pad press reads as a left
+//! pad click on that edge, which is what a Steam Controller D-pad binding
+//! listens for. R3 and Quick Access have no Steam Controller counterpart and
+//! are ignored.
+
+use crate::frame::*;
+use crate::input_parser::{decode_buttons, ClockTracker, ControllerInput, StickData, CONTROLLER_REPORT_ID};
+
+/// Packet type (byte 2) of a Deck controller state report
+pub const DECK_STATE_PACKET: u8 = 0x09;
+
+/// USB interface number of the Deck's controls
+pub const DECK_CONTROLLER_INTERFACE: i32 = 2;
+
+// Byte 9's D-pad bits and where on the left pad each one clicks
+const DPAD: [(u8, (i16, i16)); 4] = [
+    (0x01, (0, DPAD_EDGE)),
+    (0x02, (DPAD_EDGE, 0)),
+    (0x04, (-DPAD_EDGE, 0)),
+    (0x08, (0, -DPAD_EDGE)),
+];
+const DPAD_EDGE: i16 = 24000;
+
+const LPAD_TOUCH_FLAG: u8 = 0x08;
+const RPAD_TOUCH_FLAG: u8 = 0x10;
+// Byte 13
+const L4_FLAG: u8 = 0x02;
+const R4_FLAG: u8 = 0x04;
+
+/// Decode a Deck state report. `clock` un-wraps the packet counter as it
+/// does a Steam Controller's timestamp.
+pub fn parse_deck_state(data: &[u8], clock: &mut ClockTracker) -> Result<ControllerInput, String> {
+    let data: &[u8; REPORT_LEN] = data
+        .get(..REPORT_LEN)
+        .and_then(|d| d.try_into().ok())
+        .ok_or_else(|| FrameError::TooShort(data.len()).to_string())?;
+    if data[0] != CONTROLLER_REPORT_ID {
+        return Err(FrameError::InvalidReportId(data[0]).to_string());
+    }
+    if data[2] != DECK_STATE_PACKET {
+        return Err(FrameError::NotInput(data[2]).to_string());
+    }
+    let i16_at = |offset: usize| i16::from_le_bytes([data[offset], data[offset + 1]]);
+    // 15-bit triggers down to the Steam Controller's 8 bits
+    let trigger_at = |offset: usize| (u16::from_le_bytes([data[offset], data[offset + 1]]) >> 7).min(255) as u8;
+
+    let mut buttons = decode_buttons(u32::from_le_bytes([data[8], data[9], data[10], 0]));
+    if data[13] & L4_FLAG != 0 {
+        buttons |= BUTTON_LGRIP;
+    }
+    if data[13] & R4_FLAG != 0 {
+        buttons |= BUTTON_RGRIP;
+    }
+
+    let timestamp = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    let mut frame = RawFrame {
+        extended_timestamp: clock.extend(timestamp),
+        timestamp,
+        buttons,
+        stick_x: i16_at(48),
+        stick_y: i16_at(50),
+        left_pad_x: i16_at(16),
+        left_pad_y: i16_at(18),
+        right_pad_x: i16_at(20),
+        right_pad_y: i16_at(22),
+        gyro_pitch: i16_at(30),
+        gyro_yaw: i16_at(34),
+        gyro_roll: i16_at(32),
+        accel_x: i16_at(24),
+        accel_y: i16_at(26),
+        accel_z: i16_at(28),
+        quat_w: i16_at(36),
+        quat_x: i16_at(38),
+        quat_y: i16_at(40),
+        quat_z: i16_at(42),
+        left_trigger: trigger_at(44),
+        right_trigger: trigger_at(46),
+        left_pad_active: (data[10] & LPAD_TOUCH_FLAG != 0) as u8,
+        right_pad_active: (data[10] & RPAD_TOUCH_FLAG != 0) as u8,
+    };
+
+    // A real pad click wins over the D-pad
+    if !frame.pressed(BUTTON_LPAD_CLICK) {
+        let pressed = DPAD.iter().filter(|(bit, _)| data[9] & bit != 0);
+        let (x, y) = pressed.fold((0i16, 0i16), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+        if (x, y) != (0, 0) {
+            frame.buttons |= BUTTON_LPAD_CLICK;
+            frame.left_pad_x = x;
+            frame.left_pad_y = y;
+        }
+    }
+
+    let mut input = ControllerInput::from(&frame);
+    input.right_stick = StickData { x: i16_at(52), y: i16_at(54) };
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parser::{parse_packet, Packet};
+
+    #[test]
+    fn test_deck_state() {
+        let mut data = [0u8; REPORT_LEN];
+        data[0] = CONTROLLER_REPORT_ID;
+        data[2] = DECK_STATE_PACKET;
+        data[3] = 0x40;
+        data[8] = 0x81; // A, R2 click
+        data[9] = 0x81; // L5, D-pad up
+        data[10] = 0x10; // Right pad touched
+        data[13] = R4_FLAG;
+        data[20..22].copy_from_slice(&(-3000i16).to_le_bytes());
+        data[28..30].copy_from_slice(&16384i16.to_le_bytes());
+        data[30..32].copy_from_slice(&40i16.to_le_bytes());
+        data[36..38].copy_from_slice(&i16::MAX.to_le_bytes());
+        data[34..36].copy_from_slice(&(-50i16).to_le_bytes());
+        data[44..46].copy_from_slice(&32767u16.to_le_bytes());
+        data[48..50].copy_from_slice(&1200i16.to_le_bytes());
+        data[54..56].copy_from_slice(&(-32768i16).to_le_bytes());
+
+        let input = match parse_packet(&data) {
+            Ok(Packet::InputFrame(input)) => input,
+            other => panic!("{:?}", other),
+        };
+        let b = &input.buttons;
+        assert!(b.a && b.rt && b.lgrip && b.rgrip && b.lpad_click);
+        assert!(!b.b && !b.lt && !b.rpad_click);
+        // D-pad up clicks the top of the left pad without touching it
+        assert_eq!((input.left_trackpad.x, input.left_trackpad.y, input.left_trackpad.active), (0, DPAD_EDGE, false));
+        assert_eq!((input.right_trackpad.x, input.right_trackpad.active), (-3000, true));
+        assert_eq!((input.gyro.pitch, input.gyro.yaw), (40, -50));
+        assert_eq!((input.imu.accel_z, input.imu.quat_w), (16384, i16::MAX));
+        assert_eq!(input.orientation.map(|o| o.yaw), Some(0.0));
+        assert_eq!((input.triggers.left, input.triggers.right), (255, 0));
+        assert_eq!((input.stick.x, input.right_stick.y), (1200, -32768));
+
+        // Down+left together land on the diagonal; a real click keeps the pad's position
+        data[9] = 0x0c;
+        let input = parse_deck_state(&data, &mut ClockTracker::new()).unwrap();
+        assert_eq!((input.left_trackpad.x, input.left_trackpad.y), (-DPAD_EDGE, -DPAD_EDGE));
+        data[10] |= 0x0a;
+        data[16..18].copy_from_slice(&500i16.to_le_bytes());
+        let input = parse_deck_state(&data, &mut ClockTracker::new()).unwrap();
+        assert_eq!((input.left_trackpad.x, input.left_trackpad.active), (500, true));
+
+        assert!(parse_deck_state(&data[..40], &mut ClockTracker::new()).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use cpu_time::ThreadTime;
+use hidapi::HidDevice;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::steam_controller::ReadSettings;
+
+/// Upper bound for a single stress run, so a typo can't lock up the reader for minutes
+pub const MAX_STRESS_DURATION_SECS: u64 = 60;
+
+/// Result of a sustained-throughput run against the HID device
+#[derive(Debug, Clone, Serialize)]
+pub struct StressTestReport {
+    pub duration_ms: u64,
+    pub reports: u64,
+    pub report_rate_hz: f64,
+    pub missed_sequence_numbers: u64,
+    pub empty_reads: u64,
+    pub read_errors: u64,
+    pub cpu_time_ms: u64,
+    pub cpu_usage_percent: f64,
+}
+
+/// Number of reports skipped between two consecutive sequence numbers (byte 1 of the report)
+pub(crate) fn missed_between(previous: u8, current: u8) -> u64 {
+    let delta = current.wrapping_sub(previous);
+    if delta == 0 {
+        0
+    } else {
+        (delta - 1) as u64
+    }
+}
+
+/// Read as fast as the device delivers for `duration` and report the achieved rate.
+///
+/// The device lock is taken per read (not for the whole run) so other commands
+/// can still reach the controller while the test is in progress.
+pub fn run_stress_test(
+    device: &Mutex<Option<HidDevice>>,
+    settings: ReadSettings,
+    duration: Duration,
+) -> Result<StressTestReport, String> {
+    let mut buf = vec![0u8; settings.buffer_size];
+    let timeout_ms = settings.effective_timeout_ms();
+    let mut reports = 0u64;
+    let mut missed = 0u64;
+    let mut empty_reads = 0u64;
+    let mut read_errors = 0u64;
+    let mut last_sequence: Option<u8> = None;
+
+    println!("🔍 Stress test running for {:?} with {:?}...", duration, settings);
+
+    let cpu_start = ThreadTime::now();
+    let start = Instant::now();
+
+    while start.elapsed() < duration {
+        let result = {
+            let device_lock = device.lock().unwrap();
+            match device_lock.as_ref() {
+                Some(device) => device.read_timeout(&mut buf, timeout_ms),
+                None => return Err("Controller not connected".to_string()),
+            }
+        };
+
+        match result {
+            Ok(0) => empty_reads += 1,
+            Ok(size) => {
+                reports += 1;
+                if size > 1 {
+                    if let Some(previous) = last_sequence {
+                        missed += missed_between(previous, buf[1]);
+                    }
+                    last_sequence = Some(buf[1]);
+                }
+            }
+            Err(e) => {
+                read_errors += 1;
+                eprintln!("❌ Stress test read error: {}", e);
+            }
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let cpu_time = cpu_start.elapsed();
+    let secs = elapsed.as_secs_f64();
+
+    let report = StressTestReport {
+        duration_ms: elapsed.as_millis() as u64,
+        reports,
+        report_rate_hz: if secs > 0.0 { reports as f64 / secs } else { 0.0 },
+        missed_sequence_numbers: missed,
+        empty_reads,
+        read_errors,
+        cpu_time_ms: cpu_time.as_millis() as u64,
+        cpu_usage_percent: if secs > 0.0 {
+            cpu_time.as_secs_f64() / secs * 100.0
+        } else {
+            0.0
+        },
+    };
+
+    println!(
+        "✅ Stress test done: {} reports ({:.1} Hz), {} missed, {} ms CPU",
+        report.reports, report.report_rate_hz, report.missed_sequence_numbers, report.cpu_time_ms
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missed_between_consecutive() {
+        assert_eq!(missed_between(10, 11), 0);
+        assert_eq!(missed_between(10, 10), 0);
+    }
+
+    #[test]
+    fn test_missed_between_gap_and_wrap() {
+        assert_eq!(missed_between(10, 14), 3);
+        assert_eq!(missed_between(254, 1), 2);
+    }
+}
@@ -0,0 +1,473 @@
+//! Gamepad tester report: a short raw capture summarised for bug reports
+//! ("my controller behaves weird"). Covers the device and firmware, the
+//! range seen on every axis, the polling rate, and anything that looks off
+//! (lost or unparseable reports, stalls, stuck buttons, an off-centre stick).
+//! Saved as JSON for us and as a self-contained HTML page for people.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::clock;
+use crate::input_parser::{ControllerInput, InputParser, Packet};
+use crate::steam_controller::{FirmwareInfo, SteamControllerInfo, SteamControllerManager};
+use crate::stress::missed_between;
+
+/// Subdirectory of the app data dir reports are written to
+pub const TESTER_REPORTS_DIR_NAME: &str = "tester-reports";
+
+pub const DEFAULT_TESTER_CAPTURE_SECS: u64 = 5;
+pub const MAX_TESTER_CAPTURE_SECS: u64 = 30;
+
+/// A gap between reports longer than this counts as a stall
+pub const STALL_US: u64 = 50_000;
+
+/// Below this the controller isn't keeping up (it normally reports at 125-250 Hz)
+pub const LOW_REPORT_RATE_HZ: f64 = 60.0;
+
+/// Closest the stick must come to centre during a capture
+pub const STICK_OFF_CENTRE: i32 = 4000;
+
+/// Lowest a trigger must go during a capture
+pub const TRIGGER_NOT_RELEASED: i32 = 25;
+
+type AxisReader = fn(&ControllerInput) -> Option<i32>;
+
+// Every axis in the report, with how to read it; pads only count while touched
+const AXES: &[(&str, AxisReader)] = &[
+    ("stick_x", |i| Some(i.stick.x as i32)),
+    ("stick_y", |i| Some(i.stick.y as i32)),
+    ("left_pad_x", |i| Some(i.left_trackpad.x as i32).filter(|_| i.left_trackpad.active)),
+    ("left_pad_y", |i| Some(i.left_trackpad.y as i32).filter(|_| i.left_trackpad.active)),
+    ("right_pad_x", |i| Some(i.right_trackpad.x as i32).filter(|_| i.right_trackpad.active)),
+    ("right_pad_y", |i| Some(i.right_trackpad.y as i32).filter(|_| i.right_trackpad.active)),
+    ("left_trigger", |i| Some(i.triggers.left as i32)),
+    ("right_trigger", |i| Some(i.triggers.right as i32)),
+    ("gyro_pitch", |i| Some(i.gyro.pitch as i32)),
+    ("gyro_yaw", |i| Some(i.gyro.yaw as i32)),
+    ("gyro_roll", |i| Some(i.gyro.roll as i32)),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AxisRange {
+    pub name: String,
+    /// Frames the axis had a value in (pads: while touched)
+    pub samples: u64,
+    pub min: i32,
+    pub max: i32,
+    pub mean: f64,
+    /// Closest value to zero seen
+    pub nearest_zero: i32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportCounts {
+    pub total: u64,
+    pub input: u64,
+    pub status: u64,
+    pub other: u64,
+    pub parse_errors: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PollingStats {
+    pub mean_hz: f64,
+    pub min_interval_us: u64,
+    pub max_interval_us: u64,
+    pub missed_sequence_numbers: u64,
+    /// Gaps longer than `STALL_US`
+    pub stalls: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TesterReport {
+    /// Unix seconds
+    pub created: u64,
+    pub app_version: String,
+    /// `<os>-<arch>`
+    pub platform: String,
+    pub device: Option<SteamControllerInfo>,
+    pub firmware: Option<FirmwareInfo>,
+    pub duration_ms: u64,
+    pub reports: ReportCounts,
+    pub polling: PollingStats,
+    pub axes: Vec<AxisRange>,
+    /// Buttons pressed at some point during the capture
+    pub buttons_pressed: Vec<String>,
+    pub anomalies: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct AxisStats {
+    samples: u64,
+    min: i32,
+    max: i32,
+    sum: i64,
+    nearest_zero: i32,
+}
+
+impl Default for AxisStats {
+    fn default() -> Self {
+        Self {
+            samples: 0,
+            min: i32::MAX,
+            max: i32::MIN,
+            sum: 0,
+            nearest_zero: i32::MAX,
+        }
+    }
+}
+
+/// Accumulates raw reports for a [`TesterReport`]
+#[derive(Debug, Default)]
+pub struct TesterCapture {
+    parser: InputParser,
+    counts: ReportCounts,
+    first_us: Option<u64>,
+    last_us: Option<u64>,
+    min_interval_us: Option<u64>,
+    max_interval_us: u64,
+    stalls: u64,
+    last_sequence: Option<u8>,
+    missed: u64,
+    axes: Vec<AxisStats>,
+    pressed: BTreeSet<String>,
+    /// Buttons down in every input frame so far
+    always_held: Option<BTreeSet<String>>,
+}
+
+impl TesterCapture {
+    pub fn new() -> Self {
+        Self {
+            axes: vec![AxisStats::default(); AXES.len()],
+            ..Self::default()
+        }
+    }
+
+    /// Add a raw report read at `host_us` (µs, [`clock::monotonic_us`])
+    pub fn push(&mut self, raw: &[u8], host_us: u64) {
+        self.counts.total += 1;
+        if let Some(last) = self.last_us {
+            let interval = host_us.saturating_sub(last);
+            self.min_interval_us = Some(self.min_interval_us.map_or(interval, |min| min.min(interval)));
+            self.max_interval_us = self.max_interval_us.max(interval);
+            if interval > STALL_US {
+                self.stalls += 1;
+            }
+        }
+        self.first_us.get_or_insert(host_us);
+        self.last_us = Some(host_us);
+
+        match self.parser.parse_packet(raw) {
+            Ok(Packet::InputFrame(input)) => {
+                self.counts.input += 1;
+                if let Some(previous) = self.last_sequence {
+                    self.missed += missed_between(previous, raw[1]);
+                }
+                self.last_sequence = Some(raw[1]);
+                self.add_input(&input);
+            }
+            Ok(Packet::StatusEvent(_)) | Ok(Packet::BatteryEvent(_)) => self.counts.status += 1,
+            Ok(Packet::Unknown { .. }) => self.counts.other += 1,
+            Err(_) => self.counts.parse_errors += 1,
+        }
+    }
+
+    fn add_input(&mut self, input: &ControllerInput) {
+        for ((_, read), stats) in AXES.iter().zip(self.axes.iter_mut()) {
+            if let Some(value) = read(input) {
+                stats.samples += 1;
+                stats.min = stats.min.min(value);
+                stats.max = stats.max.max(value);
+                stats.sum += value as i64;
+                if value.abs() < stats.nearest_zero.abs() {
+                    stats.nearest_zero = value;
+                }
+            }
+        }
+
+        let held = pressed_buttons(input);
+        self.pressed.extend(held.iter().cloned());
+        self.always_held = Some(match self.always_held.take() {
+            Some(always) => always.intersection(&held).cloned().collect(),
+            None => held,
+        });
+    }
+
+    pub fn finish(
+        self,
+        device: Option<SteamControllerInfo>,
+        firmware: Option<FirmwareInfo>,
+        app_version: &str,
+    ) -> TesterReport {
+        let duration_us = match (self.first_us, self.last_us) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0,
+        };
+        let polling = PollingStats {
+            mean_hz: if duration_us > 0 {
+                (self.counts.total - 1) as f64 / (duration_us as f64 / 1_000_000.0)
+            } else {
+                0.0
+            },
+            min_interval_us: self.min_interval_us.unwrap_or(0),
+            max_interval_us: self.max_interval_us,
+            missed_sequence_numbers: self.missed,
+            stalls: self.stalls,
+        };
+        let axes: Vec<AxisRange> = AXES
+            .iter()
+            .zip(&self.axes)
+            .filter(|(_, stats)| stats.samples > 0)
+            .map(|((name, _), stats)| AxisRange {
+                name: name.to_string(),
+                samples: stats.samples,
+                min: stats.min,
+                max: stats.max,
+                mean: stats.sum as f64 / stats.samples as f64,
+                nearest_zero: stats.nearest_zero,
+            })
+            .collect();
+        let anomalies = anomalies(&self.counts, &polling, &axes, self.always_held.as_ref());
+
+        TesterReport {
+            created: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            app_version: app_version.to_string(),
+            platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            device,
+            firmware,
+            duration_ms: duration_us / 1000,
+            reports: self.counts,
+            polling,
+            axes,
+            buttons_pressed: self.pressed.into_iter().collect(),
+            anomalies,
+        }
+    }
+}
+
+// Names of the buttons down in a frame, as in the `buttons` JSON
+fn pressed_buttons(input: &ControllerInput) -> BTreeSet<String> {
+    match serde_json::to_value(&input.buttons) {
+        Ok(Value::Object(buttons)) => buttons
+            .into_iter()
+            .filter(|(_, down)| down.as_bool() == Some(true))
+            .map(|(name, _)| name)
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+fn anomalies(
+    counts: &ReportCounts,
+    polling: &PollingStats,
+    axes: &[AxisRange],
+    always_held: Option<&BTreeSet<String>>,
+) -> Vec<String> {
+    let mut anomalies = Vec::new();
+    if counts.input == 0 {
+        anomalies.push("No input reports arrived".to_string());
+        return anomalies;
+    }
+    if counts.parse_errors > 0 {
+        anomalies.push(format!("{} reports could not be parsed", counts.parse_errors));
+    }
+    if polling.missed_sequence_numbers > 0 {
+        anomalies.push(format!("{} reports were lost (sequence gaps)", polling.missed_sequence_numbers));
+    }
+    if polling.mean_hz < LOW_REPORT_RATE_HZ {
+        anomalies.push(format!("Reports arrived at only {:.0} Hz", polling.mean_hz));
+    }
+    if polling.stalls > 0 {
+        anomalies.push(format!(
+            "{} gaps over {} ms between reports (longest {} ms)",
+            polling.stalls,
+            STALL_US / 1000,
+            polling.max_interval_us / 1000
+        ));
+    }
+    for held in always_held.into_iter().flatten() {
+        anomalies.push(format!("'{}' was held for the whole capture (stuck button?)", held));
+    }
+    for axis in axes {
+        let stick = axis.name.starts_with("stick_") && axis.nearest_zero.abs() > STICK_OFF_CENTRE;
+        let trigger = axis.name.ends_with("_trigger") && axis.min > TRIGGER_NOT_RELEASED;
+        if stick {
+            anomalies.push(format!("{} never came back to centre (closest {})", axis.name, axis.nearest_zero));
+        } else if trigger {
+            anomalies.push(format!("{} never fully released (lowest {})", axis.name, axis.min));
+        }
+    }
+    anomalies
+}
+
+/// Read raw reports for `duration` into a capture
+pub fn capture_tester_report(manager: &SteamControllerManager, duration: Duration) -> Result<TesterCapture, String> {
+    let mut capture = TesterCapture::new();
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if !manager.is_connected() {
+            return Err("Controller disconnected during capture".to_string());
+        }
+        if let Ok(Some(raw)) = manager.read_input_blocking(manager.read_settings().timeout_ms.max(1)) {
+            capture.push(&raw, clock::monotonic_us());
+        }
+    }
+    Ok(capture)
+}
+
+impl TesterReport {
+    /// Write `tester-report-<created>.json` and `.html` to `dir`, returning both paths
+    pub fn save(&self, dir: &Path) -> Result<(PathBuf, PathBuf), String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let stem = format!("tester-report-{}", self.created);
+        let json_path = dir.join(format!("{}.json", stem));
+        let html_path = dir.join(format!("{}.html", stem));
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize report: {}", e))?;
+        fs::write(&json_path, json).map_err(|e| format!("Failed to write {}: {}", json_path.display(), e))?;
+        fs::write(&html_path, self.to_html())
+            .map_err(|e| format!("Failed to write {}: {}", html_path.display(), e))?;
+        Ok((json_path, html_path))
+    }
+
+    /// Self-contained page (no scripts or external resources)
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>CtrlSpace tester report</title>\n\
+             <style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse}\
+             td,th{border:1px solid #ccc;padding:4px 8px;text-align:left}.bad{color:#b00}</style>\n\
+             </head><body>\n<h1>CtrlSpace tester report</h1>\n",
+        );
+
+        let mut device = vec![
+            ("App version", self.app_version.clone()),
+            ("Platform", self.platform.clone()),
+            ("Created", self.created.to_string()),
+        ];
+        match &self.device {
+            Some(info) => {
+                device.push(("Product", info.product_name.clone()));
+                device.push(("Connection", info.connection_type.clone()));
+                device.push(("Serial", info.serial.clone()));
+            }
+            None => device.push(("Product", "Not connected".to_string())),
+        }
+        let stamp = |t: Option<u32>| t.map_or_else(|| "unknown".to_string(), |t| t.to_string());
+        if let Some(firmware) = &self.firmware {
+            device.push(("Firmware build", stamp(firmware.build_time)));
+            if firmware.radio_build_time.is_some() {
+                device.push(("Radio firmware build", stamp(firmware.radio_build_time)));
+            }
+        }
+        html.push_str("<h2>Device</h2>\n<table>\n");
+        for (key, value) in device {
+            let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", key, escape(&value));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Anomalies</h2>\n");
+        if self.anomalies.is_empty() {
+            html.push_str("<p>None found.</p>\n");
+        } else {
+            html.push_str("<ul class=\"bad\">\n");
+            for anomaly in &self.anomalies {
+                let _ = writeln!(html, "<li>{}</li>", escape(anomaly));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        let _ = writeln!(
+            html,
+            "<h2>Polling</h2>\n<table>\n<tr><th>Capture</th><td>{} ms</td></tr>\n\
+             <tr><th>Reports</th><td>{} ({} input, {} status, {} other, {} unparseable)</td></tr>\n\
+             <tr><th>Rate</th><td>{:.1} Hz</td></tr>\n<tr><th>Interval</th><td>{}-{} µs</td></tr>\n\
+             <tr><th>Lost</th><td>{}</td></tr>\n<tr><th>Stalls</th><td>{}</td></tr>\n</table>",
+            self.duration_ms,
+            self.reports.total,
+            self.reports.input,
+            self.reports.status,
+            self.reports.other,
+            self.reports.parse_errors,
+            self.polling.mean_hz,
+            self.polling.min_interval_us,
+            self.polling.max_interval_us,
+            self.polling.missed_sequence_numbers,
+            self.polling.stalls
+        );
+
+        html.push_str("<h2>Axes</h2>\n<table>\n<tr><th>Axis</th><th>Min</th><th>Max</th><th>Mean</th><th>Samples</th></tr>\n");
+        for axis in &self.axes {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td></tr>",
+                axis.name, axis.min, axis.max, axis.mean, axis.samples
+            );
+        }
+        html.push_str("</table>\n");
+
+        let buttons = if self.buttons_pressed.is_empty() {
+            "none".to_string()
+        } else {
+            self.buttons_pressed.join(", ")
+        };
+        let _ = writeln!(html, "<h2>Buttons pressed</h2>\n<p>{}</p>", escape(&buttons));
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parser::INPUT_PACKET;
+
+    #[test]
+    fn test_capture_summary_and_anomalies() {
+        let mut capture = TesterCapture::new();
+        for i in 0..100u64 {
+            let mut report = vec![0u8; 64];
+            report[0] = 0x01;
+            report[1] = i as u8;
+            report[2] = INPUT_PACKET;
+            report[8] = 0x01; // RT click, held throughout
+            report[13] = 40; // left trigger never released
+            // A stall halfway, and one lost report
+            let at = if i < 50 { i * 4000 } else { i * 4000 + 100_000 };
+            if i != 70 {
+                capture.push(&report, at);
+            }
+        }
+        capture.push(&[0x02, 0x00], 600_000);
+
+        let report = capture.finish(None, Some(FirmwareInfo { build_time: Some(1), radio_build_time: None }), "0.1.0");
+        assert_eq!(report.reports.total, 100);
+        assert_eq!(report.reports.input, 99);
+        assert_eq!(report.reports.other, 1);
+        assert_eq!(report.polling.stalls, 2);
+        assert_eq!(report.polling.missed_sequence_numbers, 1);
+        assert_eq!(report.buttons_pressed, vec!["rt".to_string()]);
+        let trigger = report.axes.iter().find(|a| a.name == "left_trigger").unwrap();
+        assert_eq!((trigger.min, trigger.max), (40, 40));
+        assert!(report.axes.iter().all(|a| !a.name.contains("pad")), "untouched pads have no samples");
+
+        assert!(report.anomalies.iter().any(|a| a.contains("'rt' was held")));
+        assert!(report.anomalies.iter().any(|a| a.contains("left_trigger never fully released")));
+        assert!(report.anomalies.iter().any(|a| a.contains("1 reports were lost")));
+
+        let html = report.to_html();
+        assert!(html.contains("<td>left_trigger</td><td>40</td>"));
+        assert!(html.contains("Not connected"));
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
@@ -0,0 +1,110 @@
+//! Deadlines for blocking HID work started from a command. A wedged
+//! `open` or feature-report write on a flaky dongle never returns, and the
+//! device lock it holds would stall every later command behind it. The work
+//! runs on a helper thread instead; the caller gets a timeout error and the
+//! thread is left to finish (or be abandoned by the HID watchdog) on its own.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Longest a command waits for the controller. Opening a wireless controller
+/// and the slowest feature reports take a few hundred ms.
+pub const COMMAND_TIMEOUT_MS: u64 = 3000;
+
+/// Timed-out calls still blocked in the HID stack before new ones are refused
+/// outright, so a dead device can't pile up threads
+pub const MAX_PENDING_CALLS: usize = 8;
+
+// Helper threads that haven't returned yet, timed out or not
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+// Counts a helper thread until it ends, however it ends
+struct PendingGuard;
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Run `f` on a helper thread and wait at most `timeout` for it. `what`
+/// names the operation in the error ("Feature report").
+pub fn with_timeout<T, F>(what: &str, timeout: Duration, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    let pending = PENDING.fetch_add(1, Ordering::AcqRel);
+    let guard = PendingGuard;
+    if pending >= MAX_PENDING_CALLS {
+        return Err(format!(
+            "{} refused: the controller is not responding ({} calls still pending)",
+            what, pending
+        ));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("hid-command".to_string())
+        .spawn(move || {
+            let _guard = guard;
+            let _ = tx.send(f());
+        })
+        .map_err(|e| format!("{} failed: could not start helper thread: {}", what, e))?;
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            eprintln!("❌ {} timed out after {} ms", what, timeout.as_millis());
+            Err(format!("{} timed out after {} ms", what, timeout.as_millis()))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(format!("{} failed: helper thread panicked", what)),
+    }
+}
+
+/// [`with_timeout`] with [`COMMAND_TIMEOUT_MS`]
+pub fn with_command_timeout<T, F>(what: &str, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    with_timeout(what, Duration::from_millis(COMMAND_TIMEOUT_MS), f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+
+    #[test]
+    fn test_with_timeout() {
+        let short = Duration::from_millis(20);
+        assert_eq!(with_timeout("Open", short, || Ok(7)), Ok(7));
+        assert_eq!(with_timeout::<(), _>("Open", short, || Err("no device".to_string())), Err("no device".to_string()));
+
+        // Hung calls time out, and once enough are stuck new ones are refused
+        let release = Arc::new(Barrier::new(MAX_PENDING_CALLS + 1));
+        for _ in 0..MAX_PENDING_CALLS {
+            let release = Arc::clone(&release);
+            let result = with_timeout("Feature report", short, move || {
+                release.wait();
+                Ok(())
+            });
+            assert_eq!(result, Err("Feature report timed out after 20 ms".to_string()));
+        }
+        let refused = with_timeout("Feature report", short, || Ok(())).unwrap_err();
+        assert!(refused.contains("not responding"), "{}", refused);
+
+        // Once they return, calls go through again
+        release.wait();
+        for _ in 0..100 {
+            if PENDING.load(Ordering::Acquire) == 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(with_timeout("Feature report", short, || Ok(1)), Ok(1));
+    }
+}
@@ -0,0 +1,304 @@
+//! Touch menus: a pad split into equal angular sectors, one per menu item,
+//! like Steam's radial menus. Touching the pad opens the menu, moving around
+//! highlights the item under the thumb, and clicking (or lifting, if the
+//! profile says so) selects it. The center is a dead spot: selecting from it
+//! picks nothing, so a touch menu can be opened and abandoned.
+//!
+//! The frontend draws the menu from the overlay events; selections are also
+//! emitted as `touch-menu-selection` and run the item's action.
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use crate::gestures::Pad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::overlay::OverlayEvent;
+use crate::profiles::{Action, AnalogMode, Profile};
+
+/// Event name for a touch menu item being selected
+pub const TOUCH_MENU_SELECTION_EVENT: &str = "touch-menu-selection";
+
+/// Distance from center (fraction of full scale) inside which no item is highlighted
+pub const MENU_CENTER_RADIUS: f32 = 0.3;
+
+pub const MIN_MENU_ITEMS: usize = 2;
+pub const MAX_MENU_ITEMS: usize = 16;
+
+/// One sector of a touch menu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MenuItem {
+    pub label: String,
+    #[serde(default)]
+    pub action: Action,
+}
+
+/// What picks the highlighted item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuSelect {
+    /// Clicking the pad; several items can be picked in one touch
+    Click,
+    /// Lifting the thumb off the pad
+    Release,
+}
+
+impl Default for MenuSelect {
+    fn default() -> Self {
+        MenuSelect::Click
+    }
+}
+
+/// Checks the item count and each item's action
+pub fn validate_menu(items: &[MenuItem]) -> Result<(), String> {
+    if !(MIN_MENU_ITEMS..=MAX_MENU_ITEMS).contains(&items.len()) {
+        return Err(format!(
+            "Touch menus need {} to {} items, got {}",
+            MIN_MENU_ITEMS,
+            MAX_MENU_ITEMS,
+            items.len()
+        ));
+    }
+    for item in items {
+        if item.label.trim().is_empty() {
+            return Err("Touch menu items need a label".to_string());
+        }
+        item.action.validate()?;
+    }
+    Ok(())
+}
+
+/// Sector under a touch, for a menu of `count` items. Sector 0 is centered
+/// on the top of the pad and the rest follow clockwise; `None` in the center.
+pub fn sector_at(pad: &TrackpadData, count: usize) -> Option<usize> {
+    let (x, y) = (pad.x as f32 / 32768.0, pad.y as f32 / 32768.0);
+    if count == 0 || (x * x + y * y).sqrt() < MENU_CENTER_RADIUS {
+        return None;
+    }
+    // 0 at the top, increasing clockwise
+    let angle = x.atan2(y).rem_euclid(2.0 * PI);
+    let width = 2.0 * PI / count as f32;
+    Some(((angle + width / 2.0) / width) as usize % count)
+}
+
+/// An item picked from a touch menu; payload of `touch-menu-selection`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TouchMenuSelection {
+    pub pad: Pad,
+    pub index: usize,
+    pub label: String,
+    pub action: Action,
+    /// Play a confirmation tick
+    pub haptics: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TouchMenuEvent {
+    Opened { pad: Pad, items: Vec<String> },
+    /// The thumb moved onto another item
+    Highlighted { pad: Pad, index: usize, label: String, haptics: bool },
+    Selected(TouchMenuSelection),
+    /// Lift-off, or the pad left touch-menu mode; `selected` is the last item picked
+    Closed { pad: Pad, selected: Option<String> },
+}
+
+fn menu_name(pad: Pad) -> String {
+    match pad {
+        Pad::Left => "left_pad".to_string(),
+        Pad::Right => "right_pad".to_string(),
+    }
+}
+
+impl TouchMenuEvent {
+    /// The overlay's view of this event (menus are named after their pad)
+    pub fn to_overlay(&self) -> Option<OverlayEvent> {
+        Some(match self {
+            TouchMenuEvent::Opened { pad, items } => OverlayEvent::MenuOpened { menu: menu_name(*pad), items: items.clone() },
+            TouchMenuEvent::Highlighted { pad, index, label, .. } => {
+                OverlayEvent::MenuSelection { menu: menu_name(*pad), index: *index, item: label.clone() }
+            }
+            TouchMenuEvent::Selected(_) => return None,
+            TouchMenuEvent::Closed { pad, selected } => OverlayEvent::MenuClosed { menu: menu_name(*pad), selected: selected.clone() },
+        })
+    }
+}
+
+/// Touch menu state for one pad
+#[derive(Debug, Clone, Default)]
+pub struct TouchMenu {
+    open: bool,
+    highlighted: Option<usize>,
+    clicked: bool,
+    selected: Option<String>,
+}
+
+impl TouchMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self, pad: Pad, events: &mut Vec<TouchMenuEvent>) {
+        if self.open {
+            events.push(TouchMenuEvent::Closed { pad, selected: self.selected.take() });
+        }
+        *self = Self::default();
+    }
+
+    /// Feed one frame; `menu` is `None` when the pad isn't in touch-menu mode
+    pub fn update(
+        &mut self,
+        pad: Pad,
+        data: &TrackpadData,
+        clicked: bool,
+        menu: Option<(&[MenuItem], MenuSelect, bool)>,
+    ) -> Vec<TouchMenuEvent> {
+        let mut events = Vec::new();
+        let (items, select_on, haptics) = match menu {
+            Some(menu) => menu,
+            None => {
+                self.close(pad, &mut events);
+                return events;
+            }
+        };
+
+        if !data.active {
+            if let (true, MenuSelect::Release, Some(index)) = (self.open, select_on, self.highlighted) {
+                events.push(self.select(pad, index, items, haptics));
+            }
+            self.close(pad, &mut events);
+            return events;
+        }
+
+        if !self.open {
+            self.open = true;
+            events.push(TouchMenuEvent::Opened { pad, items: items.iter().map(|item| item.label.clone()).collect() });
+        }
+        let sector = sector_at(data, items.len());
+        if sector != self.highlighted {
+            self.highlighted = sector;
+            if let Some(index) = sector {
+                events.push(TouchMenuEvent::Highlighted { pad, index, label: items[index].label.clone(), haptics });
+            }
+        }
+        if let (true, false, MenuSelect::Click, Some(index)) = (clicked, self.clicked, select_on, self.highlighted) {
+            events.push(self.select(pad, index, items, haptics));
+        }
+        self.clicked = clicked;
+        events
+    }
+
+    fn select(&mut self, pad: Pad, index: usize, items: &[MenuItem], haptics: bool) -> TouchMenuEvent {
+        let item = &items[index];
+        self.selected = Some(item.label.clone());
+        TouchMenuEvent::Selected(TouchMenuSelection {
+            pad,
+            index,
+            label: item.label.clone(),
+            action: item.action.clone(),
+            haptics,
+        })
+    }
+}
+
+/// Touch menus for both pads, driven by the active profile's pad modes
+#[derive(Debug, Clone, Default)]
+pub struct TouchMenuDetector {
+    left: TouchMenu,
+    right: TouchMenu,
+}
+
+fn menu_of(mode: &AnalogMode) -> Option<(&[MenuItem], MenuSelect, bool)> {
+    match mode {
+        AnalogMode::TouchMenu { items, select_on, haptics } => Some((items.as_slice(), *select_on, *haptics)),
+        _ => None,
+    }
+}
+
+impl TouchMenuDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: &ControllerInput, profile: Option<&Profile>) -> Vec<TouchMenuEvent> {
+        let pads = [
+            (Pad::Left, &mut self.left, &input.left_trackpad, input.buttons.lpad_click, profile.map(|p| &p.left_pad)),
+            (Pad::Right, &mut self.right, &input.right_trackpad, input.buttons.rpad_click, profile.map(|p| &p.right_pad)),
+        ];
+        let mut events = Vec::new();
+        for (pad, menu, data, clicked, mode) in pads {
+            events.extend(menu.update(pad, data, clicked, mode.and_then(menu_of)));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Touch at `degrees` clockwise from the top, `radius` of full scale out
+    fn touch(degrees: f32, radius: f32) -> TrackpadData {
+        let radians = degrees.to_radians();
+        TrackpadData {
+            x: (radians.sin() * radius * 32767.0) as i16,
+            y: (radians.cos() * radius * 32767.0) as i16,
+            active: true,
+        }
+    }
+
+    fn items(labels: &[&str]) -> Vec<MenuItem> {
+        labels
+            .iter()
+            .map(|label| MenuItem { label: label.to_string(), action: Action::Key { key: label.to_string() } })
+            .collect()
+    }
+
+    #[test]
+    fn test_sectors() {
+        // Four items: top, right, bottom, left, each 90 degrees wide
+        assert_eq!(sector_at(&touch(0.0, 0.8), 4), Some(0));
+        assert_eq!(sector_at(&touch(40.0, 0.8), 4), Some(0));
+        assert_eq!(sector_at(&touch(50.0, 0.8), 4), Some(1));
+        assert_eq!(sector_at(&touch(180.0, 0.8), 4), Some(2));
+        assert_eq!(sector_at(&touch(330.0, 0.8), 4), Some(0));
+        assert_eq!(sector_at(&touch(300.0, 0.8), 4), Some(3));
+        assert_eq!(sector_at(&touch(90.0, 0.1), 4), None);
+        assert_eq!(sector_at(&touch(170.0, 1.0), 3), Some(1));
+    }
+
+    #[test]
+    fn test_select_on_click_and_release() {
+        let menu = items(&["1", "2", "3", "4"]);
+        let mut touch_menu = TouchMenu::new();
+        let mut feed = |data: TrackpadData, clicked: bool, select_on: MenuSelect| {
+            touch_menu.update(Pad::Right, &data, clicked, Some((&menu, select_on, false)))
+        };
+
+        let events = feed(touch(0.0, 0.1), false, MenuSelect::Click);
+        assert!(matches!(events.as_slice(), [TouchMenuEvent::Opened { .. }]));
+        // Clicking in the center picks nothing
+        assert!(feed(touch(0.0, 0.1), true, MenuSelect::Click).is_empty());
+        let events = feed(touch(90.0, 0.8), false, MenuSelect::Click);
+        assert!(matches!(events.as_slice(), [TouchMenuEvent::Highlighted { index: 1, .. }]));
+        let events = feed(touch(90.0, 0.8), true, MenuSelect::Click);
+        match events.as_slice() {
+            [TouchMenuEvent::Selected(selection)] => assert_eq!((selection.index, selection.label.as_str()), (1, "2")),
+            other => panic!("{:?}", other),
+        }
+        let events = feed(TrackpadData::default(), false, MenuSelect::Click);
+        assert_eq!(events, vec![TouchMenuEvent::Closed { pad: Pad::Right, selected: Some("2".to_string()) }]);
+
+        // Release mode picks whatever was under the thumb at lift-off
+        feed(touch(180.0, 0.8), false, MenuSelect::Release);
+        let events = feed(TrackpadData::default(), false, MenuSelect::Release);
+        assert!(matches!(events.as_slice(), [TouchMenuEvent::Selected(TouchMenuSelection { index: 2, .. }), TouchMenuEvent::Closed { .. }]));
+
+        // Leaving touch-menu mode mid-touch closes the menu without a pick
+        feed(touch(180.0, 0.8), false, MenuSelect::Release);
+        let events = touch_menu.update(Pad::Right, &touch(180.0, 0.8), false, None);
+        assert_eq!(events, vec![TouchMenuEvent::Closed { pad: Pad::Right, selected: None }]);
+
+        assert!(validate_menu(&items(&["only"])).is_err());
+        assert!(validate_menu(&items(&["a", " "])).is_err());
+        assert!(validate_menu(&menu).is_ok());
+    }
+}
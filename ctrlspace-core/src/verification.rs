@@ -0,0 +1,185 @@
+//! Verification against a second input path: on platforms where a kernel or
+//! SDL driver already exposes the Steam Controller as a gamepad, its view of
+//! the controller (read via gilrs in the app) is compared with our parsed
+//! frames, and any disagreement is flagged as a parser bug candidate.
+//!
+//! The two paths see the same physical report at slightly different times,
+//! so a difference has to persist for a few frames before it counts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::input_parser::ControllerInput;
+use crate::profiles::ButtonId;
+
+/// Event name for confirmed mismatches
+pub const VERIFICATION_MISMATCH_EVENT: &str = "verification-mismatch";
+
+/// The reference driver's view of the controller. Only controls the driver
+/// exposes are filled in; everything else is left out of the comparison.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceInput {
+    pub buttons: BTreeMap<ButtonId, bool>,
+    /// -1.0..=1.0, up positive
+    pub stick: Option<(f32, f32)>,
+    /// 0.0..=1.0
+    pub triggers: Option<(f32, f32)>,
+}
+
+/// A control the two paths disagree on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "control", rename_all = "snake_case")]
+pub enum Mismatch {
+    Button { button: ButtonId, ours: bool, reference: bool },
+    StickX { ours: f32, reference: f32 },
+    StickY { ours: f32, reference: f32 },
+    LeftTrigger { ours: f32, reference: f32 },
+    RightTrigger { ours: f32, reference: f32 },
+}
+
+impl Mismatch {
+    // Identity of the control, so a mismatch that persists is reported once
+    fn control(&self) -> String {
+        match self {
+            Mismatch::Button { button, .. } => format!("{:?}", button),
+            Mismatch::StickX { .. } => "stick_x".to_string(),
+            Mismatch::StickY { .. } => "stick_y".to_string(),
+            Mismatch::LeftTrigger { .. } => "left_trigger".to_string(),
+            Mismatch::RightTrigger { .. } => "right_trigger".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerificationConfig {
+    /// Largest normalized axis difference that still counts as equal
+    pub axis_tolerance: f32,
+    /// Consecutive frames a difference must last before it is reported
+    pub persist_frames: u32,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            axis_tolerance: 0.1,
+            persist_frames: 3,
+        }
+    }
+}
+
+/// Every difference between one of our frames and the reference state
+pub fn compare(ours: &ControllerInput, reference: &ReferenceInput, axis_tolerance: f32) -> Vec<Mismatch> {
+    let mut out: Vec<Mismatch> = reference
+        .buttons
+        .iter()
+        .filter(|(button, pressed)| button.is_pressed(&ours.buttons) != **pressed)
+        .map(|(button, pressed)| Mismatch::Button {
+            button: *button,
+            ours: !*pressed,
+            reference: *pressed,
+        })
+        .collect();
+
+    let mut axis = |ours: f32, reference: f32, mismatch: fn(f32, f32) -> Mismatch| {
+        if (ours - reference).abs() > axis_tolerance {
+            out.push(mismatch(ours, reference));
+        }
+    };
+    if let Some((x, y)) = reference.stick {
+        axis(ours.stick.x as f32 / 32767.0, x, |ours, reference| Mismatch::StickX { ours, reference });
+        axis(ours.stick.y as f32 / 32767.0, y, |ours, reference| Mismatch::StickY { ours, reference });
+    }
+    if let Some((left, right)) = reference.triggers {
+        axis(ours.triggers.left as f32 / 255.0, left, |ours, reference| Mismatch::LeftTrigger { ours, reference });
+        axis(ours.triggers.right as f32 / 255.0, right, |ours, reference| Mismatch::RightTrigger { ours, reference });
+    }
+    out
+}
+
+/// Compares frame by frame and reports each mismatch once it has persisted
+#[derive(Debug, Clone, Default)]
+pub struct Verifier {
+    config: VerificationConfig,
+    /// Consecutive mismatching frames per control
+    streaks: BTreeMap<String, u32>,
+    /// Frames compared / mismatches reported, for the summary
+    frames: u64,
+    reported: u64,
+}
+
+/// Counters for the verification run so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VerificationSummary {
+    pub frames: u64,
+    pub mismatches: u64,
+}
+
+impl Verifier {
+    pub fn new(config: VerificationConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn summary(&self) -> VerificationSummary {
+        VerificationSummary {
+            frames: self.frames,
+            mismatches: self.reported,
+        }
+    }
+
+    /// Mismatches that just reached the persistence threshold
+    pub fn update(&mut self, ours: &ControllerInput, reference: &ReferenceInput) -> Vec<Mismatch> {
+        self.frames += 1;
+        let mismatches = compare(ours, reference, self.config.axis_tolerance);
+
+        let mut streaks = BTreeMap::new();
+        let mut confirmed = Vec::new();
+        for mismatch in mismatches {
+            let control = mismatch.control();
+            let streak = self.streaks.get(&control).copied().unwrap_or(0) + 1;
+            if streak == self.config.persist_frames.max(1) {
+                confirmed.push(mismatch);
+            }
+            streaks.insert(control, streak);
+        }
+        // Controls that agree again start over
+        self.streaks = streaks;
+        self.reported += confirmed.len() as u64;
+        confirmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistent_mismatch_reported_once() {
+        let mut verifier = Verifier::new(VerificationConfig::default());
+        let mut ours = ControllerInput::default();
+        ours.buttons.a = true;
+        ours.stick.x = 32767;
+        let reference = ReferenceInput {
+            buttons: [(ButtonId::A, true), (ButtonId::B, true)].into_iter().collect(),
+            stick: Some((0.95, 0.0)),
+            triggers: None,
+        };
+
+        // B disagrees; the stick is within tolerance
+        let reported: Vec<usize> = (0..5).map(|_| verifier.update(&ours, &reference).len()).collect();
+        assert_eq!(reported, vec![0, 0, 1, 0, 0]);
+        assert_eq!(
+            compare(&ours, &reference, 0.1),
+            vec![Mismatch::Button { button: ButtonId::B, ours: false, reference: true }]
+        );
+
+        // A one-frame blip (timing skew) is never reported
+        let mut verifier = Verifier::new(VerificationConfig::default());
+        verifier.update(&ours, &reference);
+        ours.buttons.b = true;
+        assert!(verifier.update(&ours, &reference).is_empty());
+        assert_eq!(verifier.summary(), VerificationSummary { frames: 2, mismatches: 0 });
+    }
+}
@@ -0,0 +1,335 @@
+//! Virtual Xbox 360 pad: controller input mapped onto an XInput report, so
+//! games that only know standard gamepads can use the controller outside
+//! Steam. The platform side (ViGEm on Windows, uinput on Linux) lives in the
+//! app behind [`VirtualPad`]; this module decides what the pad reports.
+//!
+//! The Steam Controller has no right stick or D-pad, so like
+//! [`crate::gamepad_api`] the right pad stands in for a stick and left pad
+//! clicks become D-pad presses by quadrant.
+
+use serde::{Deserialize, Serialize};
+use crate::gamepad_api::dpad;
+use crate::input_parser::{ControllerInput, TrackpadData};
+use crate::profiles::{AnalogMode, Profile};
+
+// XUSB_REPORT / XINPUT_GAMEPAD button bits
+pub const XUSB_DPAD_UP: u16 = 0x0001;
+pub const XUSB_DPAD_DOWN: u16 = 0x0002;
+pub const XUSB_DPAD_LEFT: u16 = 0x0004;
+pub const XUSB_DPAD_RIGHT: u16 = 0x0008;
+pub const XUSB_START: u16 = 0x0010;
+pub const XUSB_BACK: u16 = 0x0020;
+pub const XUSB_LEFT_THUMB: u16 = 0x0040;
+pub const XUSB_RIGHT_THUMB: u16 = 0x0080;
+pub const XUSB_LEFT_SHOULDER: u16 = 0x0100;
+pub const XUSB_RIGHT_SHOULDER: u16 = 0x0200;
+pub const XUSB_GUIDE: u16 = 0x0400;
+pub const XUSB_A: u16 = 0x1000;
+pub const XUSB_B: u16 = 0x2000;
+pub const XUSB_X: u16 = 0x4000;
+pub const XUSB_Y: u16 = 0x8000;
+
+/// An X360 pad state. Same layout as ViGEm's `XUSB_REPORT` (and XInput's
+/// `XINPUT_GAMEPAD`): sticks are up positive.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct XusbReport {
+    /// Bitmask of `XUSB_*`
+    pub buttons: u16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    pub thumb_lx: i16,
+    pub thumb_ly: i16,
+    pub thumb_rx: i16,
+    pub thumb_ry: i16,
+}
+
+impl XusbReport {
+    pub fn pressed(&self, button: u16) -> bool {
+        self.buttons & button != 0
+    }
+}
+
+/// Which controls drive which X360 controls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadMapping {
+    /// Stick as the left stick, right pad as the right stick, left pad
+    /// clicks as the D-pad; grips are left to the profile
+    Default,
+    /// Sticks swapped for left-handed play: right pad as the left stick,
+    /// stick as the right stick
+    Southpaw,
+    /// [`GamepadMapping::Default`] with the grips as stick clicks, where
+    /// shooters put crouch and melee
+    Fps,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        GamepadMapping::Default
+    }
+}
+
+impl GamepadMapping {
+    pub const ALL: [GamepadMapping; 3] = [GamepadMapping::Default, GamepadMapping::Southpaw, GamepadMapping::Fps];
+}
+
+/// One of the X360 pad's sticks, as a profile analog target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+/// What the frontend shows for the virtual pad
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VirtualGamepadStatus {
+    pub active: bool,
+    pub mapping: GamepadMapping,
+    /// "vigem" or "uinput"; empty where there is no backend
+    pub backend: String,
+    /// Why the last start or update failed
+    pub error: Option<String>,
+}
+
+// Right pad as a stick: centered while untouched
+fn pad_stick(pad: &TrackpadData) -> (i16, i16) {
+    if pad.active {
+        (pad.x, pad.y)
+    } else {
+        (0, 0)
+    }
+}
+
+/// The X360 state for one frame of input
+pub fn map_input(input: &ControllerInput, mapping: GamepadMapping) -> XusbReport {
+    let b = &input.buttons;
+    let [up, down, left, right] = dpad(&input.left_trackpad, b.lpad_click);
+    let (left_thumb, right_thumb) = match mapping {
+        GamepadMapping::Fps => (b.stick_click || b.lgrip, b.rpad_click || b.rgrip),
+        GamepadMapping::Default | GamepadMapping::Southpaw => (b.stick_click, b.rpad_click),
+    };
+    let stick = (input.stick.x, input.stick.y);
+    // A Deck's right stick stands in while the right pad isn't touched
+    let pad = if input.right_trackpad.active {
+        pad_stick(&input.right_trackpad)
+    } else {
+        (input.right_stick.x, input.right_stick.y)
+    };
+    let ((lx, ly), (rx, ry)) = match mapping {
+        GamepadMapping::Southpaw => (pad, stick),
+        GamepadMapping::Default | GamepadMapping::Fps => (stick, pad),
+    };
+
+    let buttons = [
+        (up, XUSB_DPAD_UP),
+        (down, XUSB_DPAD_DOWN),
+        (left, XUSB_DPAD_LEFT),
+        (right, XUSB_DPAD_RIGHT),
+        (b.start, XUSB_START),
+        (b.select, XUSB_BACK),
+        (left_thumb, XUSB_LEFT_THUMB),
+        (right_thumb, XUSB_RIGHT_THUMB),
+        (b.lb, XUSB_LEFT_SHOULDER),
+        (b.rb, XUSB_RIGHT_SHOULDER),
+        (b.steam, XUSB_GUIDE),
+        (b.a, XUSB_A),
+        (b.b, XUSB_B),
+        (b.x, XUSB_X),
+        (b.y, XUSB_Y),
+    ]
+    .iter()
+    .filter(|(pressed, _)| *pressed)
+    .fold(0, |bits, (_, bit)| bits | bit);
+
+    XusbReport {
+        buttons,
+        // The click sits at the end of the pull, so it reads as fully pulled
+        left_trigger: if b.lt { 255 } else { input.triggers.left },
+        right_trigger: if b.rt { 255 } else { input.triggers.right },
+        thumb_lx: lx,
+        thumb_ly: ly,
+        thumb_rx: rx,
+        thumb_ry: ry,
+    }
+}
+
+/// Drive sticks from the profile's `stick` analog modes: pads while touched,
+/// the stick, and the gyro (yaw sideways, pitch up and down), each scaled by
+/// its sensitivity. A stick any mode targets replaces what [`map_input`] put
+/// there with the sum of its sources, clamped to the stick's range.
+pub fn apply_profile(report: &mut XusbReport, input: &ControllerInput, profile: &Profile) {
+    let pad = |pad: &TrackpadData| if pad.active { (pad.x, pad.y) } else { (0, 0) };
+    let sources = [
+        (&profile.left_pad, pad(&input.left_trackpad)),
+        (&profile.right_pad, pad(&input.right_trackpad)),
+        (&profile.stick, (input.stick.x, input.stick.y)),
+        (&profile.gyro, (input.gyro.yaw, input.gyro.pitch)),
+    ];
+    for target in [GamepadStick::Left, GamepadStick::Right].iter() {
+        if sources.iter().any(|(mode, _)| matches!(mode, AnalogMode::Stick { stick, .. } if stick == target)) {
+            match target {
+                GamepadStick::Left => (report.thumb_lx, report.thumb_ly) = (0, 0),
+                GamepadStick::Right => (report.thumb_rx, report.thumb_ry) = (0, 0),
+            }
+        }
+    }
+    for (mode, (x, y)) in sources.iter() {
+        if let AnalogMode::Stick { stick, sensitivity } = mode {
+            let (thumb_x, thumb_y) = match stick {
+                GamepadStick::Left => (&mut report.thumb_lx, &mut report.thumb_ly),
+                GamepadStick::Right => (&mut report.thumb_rx, &mut report.thumb_ry),
+            };
+            let add = |thumb: i16, value: i16| {
+                (thumb as f32 + value as f32 * sensitivity).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+            };
+            *thumb_x = add(*thumb_x, *x);
+            *thumb_y = add(*thumb_y, *y);
+        }
+    }
+}
+
+/// A platform virtual X360 device
+pub trait VirtualPad: Send {
+    /// Backend name for [`VirtualGamepadStatus::backend`]
+    fn backend(&self) -> &'static str;
+    fn update(&mut self, report: &XusbReport) -> Result<(), String>;
+}
+
+/// A virtual pad fed with controller input. Reports only go out when the
+/// mapped state changes, so an idle controller doesn't spam the driver.
+pub struct VirtualGamepad {
+    pad: Box<dyn VirtualPad>,
+    mapping: GamepadMapping,
+    last: Option<XusbReport>,
+}
+
+impl VirtualGamepad {
+    pub fn new(pad: Box<dyn VirtualPad>, mapping: GamepadMapping) -> Self {
+        Self { pad, mapping, last: None }
+    }
+
+    pub fn mapping(&self) -> GamepadMapping {
+        self.mapping
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.pad.backend()
+    }
+
+    /// Takes effect on the next frame
+    pub fn set_mapping(&mut self, mapping: GamepadMapping) {
+        self.mapping = mapping;
+        self.last = None;
+    }
+
+    /// One frame, with the active profile's `stick` modes added
+    pub fn feed(&mut self, input: &ControllerInput, profile: Option<&Profile>) -> Result<(), String> {
+        let mut report = map_input(input, self.mapping);
+        if let Some(profile) = profile {
+            apply_profile(&mut report, input, profile);
+        }
+        self.send(report)
+    }
+
+    /// Release everything, e.g. before the pad goes away or while the
+    /// controller is disconnected, so the game doesn't see held buttons
+    pub fn neutral(&mut self) -> Result<(), String> {
+        self.send(XusbReport::default())
+    }
+
+    fn send(&mut self, report: XusbReport) -> Result<(), String> {
+        if self.last == Some(report) {
+            return Ok(());
+        }
+        // Forget the state on failure so the next frame retries it
+        self.last = None;
+        self.pad.update(&report)?;
+        self.last = Some(report);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct FakePad(Arc<Mutex<Vec<XusbReport>>>);
+
+    impl VirtualPad for FakePad {
+        fn backend(&self) -> &'static str {
+            "fake"
+        }
+
+        fn update(&mut self, report: &XusbReport) -> Result<(), String> {
+            self.0.lock().unwrap().push(*report);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_map_and_feed() {
+        assert_eq!(std::mem::size_of::<XusbReport>(), 12);
+
+        let mut input = ControllerInput::default();
+        input.buttons.a = true;
+        input.buttons.steam = true;
+        input.buttons.rgrip = true;
+        input.buttons.lpad_click = true;
+        input.left_trackpad = TrackpadData { x: 200, y: 9000, active: true };
+        input.right_trackpad = TrackpadData { x: -4000, y: 1200, active: true };
+        input.stick.x = 32767;
+        input.triggers.left = 90;
+
+        let report = map_input(&input, GamepadMapping::Default);
+        assert_eq!(report.buttons, XUSB_A | XUSB_GUIDE | XUSB_DPAD_UP);
+        assert_eq!((report.thumb_lx, report.thumb_ly, report.thumb_rx, report.thumb_ry), (32767, 0, -4000, 1200));
+        assert_eq!((report.left_trigger, report.right_trigger), (90, 0));
+
+        let southpaw = map_input(&input, GamepadMapping::Southpaw);
+        assert_eq!((southpaw.thumb_lx, southpaw.thumb_ly, southpaw.thumb_rx), (-4000, 1200, 32767));
+        assert!(map_input(&input, GamepadMapping::Fps).pressed(XUSB_RIGHT_THUMB));
+
+        // Unchanged frames aren't resent; a new mapping is
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut pad = VirtualGamepad::new(Box::new(FakePad(Arc::clone(&sent))), GamepadMapping::Default);
+        pad.feed(&input, None).unwrap();
+        pad.feed(&input, None).unwrap();
+        pad.set_mapping(GamepadMapping::Southpaw);
+        pad.feed(&input, None).unwrap();
+        input.right_trackpad.active = false;
+        pad.feed(&input, None).unwrap();
+        pad.neutral().unwrap();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 4);
+        assert_eq!(sent[1], southpaw);
+        assert_eq!((sent[2].thumb_lx, sent[2].thumb_ly), (0, 0));
+        assert_eq!(sent[3], XusbReport::default());
+
+        // The right pad mapped to the right stick replaces the default, not adds to it
+        let mut profile = Profile::new("Gyro aim");
+        profile.right_pad = AnalogMode::Stick { stick: GamepadStick::Right, sensitivity: 1.0 };
+        input.right_trackpad.active = true;
+        let mut report = map_input(&input, GamepadMapping::Default);
+        apply_profile(&mut report, &input, &profile);
+        assert_eq!((report.thumb_rx, report.thumb_ry), (-4000, 1200));
+
+        // Gyro to the right stick, added to the right pad
+        profile.gyro = AnalogMode::Stick { stick: GamepadStick::Right, sensitivity: 100.0 };
+        input.gyro.yaw = 50;
+        input.gyro.pitch = -400;
+        let mut report = map_input(&input, GamepadMapping::Default);
+        apply_profile(&mut report, &input, &profile);
+        assert_eq!((report.thumb_rx, report.thumb_ry), (1000, i16::MIN));
+
+        // Gyro alone takes the right stick over; the left stick is untouched
+        profile.right_pad = AnalogMode::default();
+        let mut report = map_input(&input, GamepadMapping::Default);
+        apply_profile(&mut report, &input, &profile);
+        assert_eq!((report.thumb_rx, report.thumb_ry), (5000, i16::MIN));
+        assert_eq!(report.thumb_lx, 32767);
+    }
+}
@@ -0,0 +1,172 @@
+//! Watchdog for HID calls that never return. Flaky dongles occasionally wedge
+//! a read or feature-report write inside the OS HID stack, and whichever thread
+//! made the call (plus everything waiting on the device lock) hangs with it.
+//!
+//! Every HID call registers itself with an [`OpTracker`] while it runs; the
+//! watchdog thread polls for calls older than the stall threshold and hands
+//! them to a recovery callback, which abandons the stuck handle instead of
+//! waiting for it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Event name for recoveries from a stuck HID call
+pub const CONTROLLER_RECOVERED_EVENT: &str = "controller-recovered";
+
+/// Kind of HID call, for diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HidOp {
+    Read,
+    Write,
+    FeatureRead,
+    Enumerate,
+    Open,
+}
+
+/// A call that has been running longer than the stall threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StuckOperation {
+    pub op: HidOp,
+    pub stuck_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// How long one HID call may take before it counts as stuck. Reads time
+    /// out after a few ms and writes take tens of ms, so this is generous.
+    pub stall_ms: u64,
+    pub poll_ms: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stall_ms: 2000,
+            poll_ms: 250,
+        }
+    }
+}
+
+/// HID calls currently in progress
+#[derive(Debug, Default)]
+pub struct OpTracker {
+    next_id: AtomicU64,
+    in_flight: Mutex<BTreeMap<u64, (HidOp, Instant)>>,
+}
+
+/// Unregisters its call when dropped
+pub struct OpGuard {
+    tracker: Arc<OpTracker>,
+    id: u64,
+}
+
+impl Drop for OpGuard {
+    fn drop(&mut self) {
+        self.tracker.in_flight.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl OpTracker {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Register a call for the lifetime of the returned guard
+    pub fn begin(self: &Arc<Self>, op: HidOp) -> OpGuard {
+        self.begin_at(op, Instant::now())
+    }
+
+    fn begin_at(self: &Arc<Self>, op: HidOp, started: Instant) -> OpGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.lock().unwrap().insert(id, (op, started));
+        OpGuard {
+            tracker: Arc::clone(self),
+            id,
+        }
+    }
+
+    /// The longest-running call, if it has exceeded `threshold`
+    pub fn stuck(&self, now: Instant, threshold: Duration) -> Option<StuckOperation> {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .values()
+            .map(|&(op, started)| (op, now.saturating_duration_since(started)))
+            .filter(|(_, elapsed)| *elapsed > threshold)
+            .max_by_key(|(_, elapsed)| *elapsed)
+            .map(|(op, elapsed)| StuckOperation {
+                op,
+                stuck_ms: elapsed.as_millis() as u64,
+            })
+    }
+
+    /// Forget every call, e.g. once their handle has been abandoned. Guards
+    /// of calls that do return later remove nothing.
+    pub fn clear(&self) {
+        self.in_flight.lock().unwrap().clear();
+    }
+}
+
+/// Background thread that polls `check` and passes stuck calls to `on_stuck`
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    pub fn spawn<C, R>(config: WatchdogConfig, check: C, on_stuck: R) -> Self
+    where
+        C: Fn(Duration) -> Option<StuckOperation> + Send + 'static,
+        R: Fn(StuckOperation) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let threshold = Duration::from_millis(config.stall_ms);
+        let interval = Duration::from_millis(config.poll_ms.max(10));
+
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                if let Some(stuck) = check(threshold) {
+                    on_stuck(stuck);
+                }
+                thread::sleep(interval);
+            }
+        });
+        Self { stop }
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stuck_operation_detected() {
+        let tracker = OpTracker::new();
+        let now = Instant::now();
+        let threshold = Duration::from_secs(2);
+
+        let _write = tracker.begin_at(HidOp::Write, now - Duration::from_millis(100));
+        assert_eq!(tracker.stuck(now, threshold), None);
+
+        let read = tracker.begin_at(HidOp::Read, now - Duration::from_secs(5));
+        assert_eq!(tracker.stuck(now, threshold), Some(StuckOperation { op: HidOp::Read, stuck_ms: 5000 }));
+
+        // The call returned after all
+        drop(read);
+        assert_eq!(tracker.stuck(now, threshold), None);
+
+        let _read = tracker.begin_at(HidOp::Read, now - Duration::from_secs(5));
+        tracker.clear();
+        assert_eq!(tracker.stuck(now, threshold), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Guided mapping wizard: prompts for each control in turn, uses learning
+//! mode to find where it lives in the report and assembles a `ReportLayout`.
+
+use serde::Serialize;
+use crate::layout::{AxisField, AxisFormat, AxisId, BitField, ReportLayout};
+use crate::learning::{ChangeKind, LearnResult};
+use crate::profiles::ButtonId;
+
+/// What a wizard step is locating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WizardControl {
+    Button { button: ButtonId },
+    Axis { axis: AxisId },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WizardStep {
+    pub control: WizardControl,
+    pub prompt: &'static str,
+}
+
+const fn button(button: ButtonId, prompt: &'static str) -> WizardStep {
+    WizardStep { control: WizardControl::Button { button }, prompt }
+}
+
+const fn axis(axis: AxisId, prompt: &'static str) -> WizardStep {
+    WizardStep { control: WizardControl::Axis { axis }, prompt }
+}
+
+/// Every step, in the order the user is prompted
+pub const WIZARD_STEPS: &[WizardStep] = &[
+    button(ButtonId::A, "Press A a few times"),
+    button(ButtonId::B, "Press B a few times"),
+    button(ButtonId::X, "Press X a few times"),
+    button(ButtonId::Y, "Press Y a few times"),
+    button(ButtonId::Lb, "Press the left bumper a few times"),
+    button(ButtonId::Rb, "Press the right bumper a few times"),
+    button(ButtonId::Lt, "Fully press the left trigger until it clicks, a few times"),
+    button(ButtonId::Rt, "Fully press the right trigger until it clicks, a few times"),
+    button(ButtonId::Lgrip, "Squeeze the left grip a few times"),
+    button(ButtonId::Rgrip, "Squeeze the right grip a few times"),
+    button(ButtonId::Select, "Press the back (left arrow) button a few times"),
+    button(ButtonId::Start, "Press the start (right arrow) button a few times"),
+    button(ButtonId::Steam, "Press the Steam button a few times"),
+    button(ButtonId::LpadClick, "Click the left pad a few times"),
+    button(ButtonId::RpadClick, "Click the right pad a few times"),
+    button(ButtonId::StickClick, "Click the stick in a few times"),
+    axis(AxisId::LeftTrigger, "Slowly squeeze and release the left trigger (don't click it)"),
+    axis(AxisId::RightTrigger, "Slowly squeeze and release the right trigger (don't click it)"),
+    axis(AxisId::StickX, "Move the stick left and right"),
+    axis(AxisId::StickY, "Move the stick up and down"),
+    axis(AxisId::RpadX, "Slide a finger left and right on the right pad"),
+    axis(AxisId::RpadY, "Slide a finger up and down on the right pad"),
+];
+
+/// Where the wizard is, for the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WizardStatus {
+    pub step: usize,
+    pub total: usize,
+    /// `None` once every step is done or skipped
+    pub current: Option<WizardStep>,
+    pub layout: ReportLayout,
+}
+
+pub struct LayoutWizard {
+    step: usize,
+    layout: ReportLayout,
+}
+
+impl LayoutWizard {
+    pub fn new(name: &str) -> Self {
+        Self {
+            step: 0,
+            layout: ReportLayout::new(name),
+        }
+    }
+
+    pub fn current(&self) -> Option<WizardStep> {
+        WIZARD_STEPS.get(self.step).copied()
+    }
+
+    pub fn status(&self) -> WizardStatus {
+        WizardStatus {
+            step: self.step,
+            total: WIZARD_STEPS.len(),
+            current: self.current(),
+            layout: self.layout.clone(),
+        }
+    }
+
+    /// Take the learning result for the current step and advance. Fails (and
+    /// stays on the step) when nothing usable changed, so the user can retry.
+    pub fn record(&mut self, result: &LearnResult) -> Result<(), String> {
+        let step = self.current().ok_or_else(|| "Wizard already finished".to_string())?;
+
+        match step.control {
+            WizardControl::Button { button } => {
+                let change = result
+                    .changes
+                    .iter()
+                    .find(|c| c.kind == ChangeKind::Digital)
+                    .ok_or_else(|| "No button bit changed - try again".to_string())?;
+                self.layout.buttons.insert(button, BitField { offset: change.offset, mask: change.mask });
+            }
+            WizardControl::Axis { axis } => {
+                let change = result
+                    .changes
+                    .iter()
+                    .find(|c| c.kind == ChangeKind::Analog)
+                    .ok_or_else(|| "No analog value changed - try again".to_string())?;
+                let offset = match axis.format() {
+                    AxisFormat::U8 => change.offset,
+                    // The busiest byte may be either half of a 16-bit value;
+                    // prefer the pair that moved together
+                    AxisFormat::I16Le => {
+                        let moved = |offset: usize| result.changes.iter().any(|c| c.offset == offset);
+                        if moved(change.offset + 1) {
+                            change.offset
+                        } else if change.offset > 0 && moved(change.offset - 1) {
+                            change.offset - 1
+                        } else {
+                            change.offset & !1
+                        }
+                    }
+                };
+                self.layout.axes.insert(axis, AxisField { offset, format: axis.format() });
+            }
+        }
+
+        self.step += 1;
+        Ok(())
+    }
+
+    /// Leave the current control to the built-in parser
+    pub fn skip(&mut self) {
+        if self.step < WIZARD_STEPS.len() {
+            self.step += 1;
+        }
+    }
+
+    pub fn layout(&self) -> &ReportLayout {
+        &self.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::LearnedBits;
+
+    fn learned(changes: &[(usize, u8, ChangeKind)]) -> LearnResult {
+        LearnResult {
+            control: String::new(),
+            changes: changes
+                .iter()
+                .map(|&(offset, mask, kind)| LearnedBits { offset, mask, kind, frames: 10 })
+                .collect(),
+            frames_observed: 20,
+        }
+    }
+
+    #[test]
+    fn test_wizard_builds_layout() {
+        let mut wizard = LayoutWizard::new("Rev B");
+        assert_eq!(wizard.current().unwrap().control, WizardControl::Button { button: ButtonId::A });
+
+        // Nothing pressed - stays on A
+        assert!(wizard.record(&learned(&[])).is_err());
+        wizard
+            .record(&learned(&[(12, 0xff, ChangeKind::Analog), (8, 0x80, ChangeKind::Digital)]))
+            .unwrap();
+        assert_eq!(wizard.layout().buttons[&ButtonId::A], BitField { offset: 8, mask: 0x80 });
+
+        while let Some(step) = wizard.current() {
+            match step.control {
+                WizardControl::Axis { axis: AxisId::StickX } => wizard
+                    .record(&learned(&[(17, 0xff, ChangeKind::Analog), (16, 0xf0, ChangeKind::Analog)]))
+                    .unwrap(),
+                _ => wizard.skip(),
+            }
+        }
+
+        let status = wizard.status();
+        assert_eq!(status.step, WIZARD_STEPS.len());
+        assert!(status.current.is_none());
+        assert_eq!(status.layout.axes[&AxisId::StickX].offset, 16);
+        assert!(wizard.record(&learned(&[])).is_err());
+    }
+}
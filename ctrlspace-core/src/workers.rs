@@ -0,0 +1,431 @@
+//! Per-device workers for multi-controller setups. Each controller gets its
+//! own thread that reads, parses and hands frames to its own output, so a slow
+//! or wedged device only ever delays itself; a [`DeviceMapper`] gives that
+//! output its own layer and held-binding state. A [`Supervisor`] restarts a
+//! worker that panicked or failed, with a backoff and a restart limit; a
+//! worker that ends cleanly (controller unplugged, output gone) stays down.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::clock;
+use crate::controller::ControllerHandle;
+use crate::error::ControllerError;
+use crate::input_parser::{ControllerInput, Packet};
+use crate::layers::{BindingEvent, LayerEngine};
+use crate::mirror::mirror_input;
+use crate::profiles::Profile;
+use crate::response::apply_responses;
+use crate::steam_controller::MAX_READ_BUFFER_SIZE;
+
+/// Event name for worker restarts and failures
+pub const WORKER_STATUS_EVENT: &str = "worker-status";
+
+/// Event name for frames from per-device workers
+pub const DEVICE_INPUT_EVENT: &str = "device-input";
+
+/// Payload of `DEVICE_INPUT_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInput {
+    pub device_id: String,
+    pub input: ControllerInput,
+}
+
+/// Body of a worker; returns when `ctx.should_stop()` or the work is done.
+/// An `Err` (or a panic) is a crash and gets the worker restarted.
+pub type WorkerRun = Arc<dyn Fn(&WorkerContext) -> Result<(), String> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Restarts before a worker is left failed; reset after `stable_ms` of running
+    pub max_restarts: u32,
+    /// Delay before the first restart, doubled for each further one
+    pub backoff_ms: u64,
+    pub stable_ms: u64,
+    /// How often the supervisor checks its workers
+    pub poll_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            backoff_ms: 200,
+            stable_ms: 30_000,
+            poll_ms: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerState {
+    Running,
+    /// Crashed, waiting out the backoff
+    Restarting,
+    /// Ended cleanly; not restarted
+    Stopped,
+    /// Crashed more often than the policy allows
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkerStatus {
+    pub id: String,
+    pub state: WorkerState,
+    pub restarts: u32,
+    /// Frames handled across restarts
+    pub frames: u64,
+    pub last_error: Option<String>,
+}
+
+/// Handed to a running worker
+pub struct WorkerContext {
+    stop: Arc<AtomicBool>,
+    frames: Arc<AtomicU64>,
+}
+
+impl WorkerContext {
+    pub fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Count one handled frame
+    pub fn frame(&self) {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Worker {
+    run: WorkerRun,
+    stop: Arc<AtomicBool>,
+    frames: Arc<AtomicU64>,
+    /// Set by the thread as it ends
+    outcome: Arc<Mutex<Option<Result<(), String>>>>,
+    started: Instant,
+    state: WorkerState,
+    restarts: u32,
+    restart_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+impl Worker {
+    fn spawn(&mut self, name: &str) {
+        let run = Arc::clone(&self.run);
+        let ctx = WorkerContext {
+            stop: Arc::clone(&self.stop),
+            frames: Arc::clone(&self.frames),
+        };
+        let outcome = Arc::new(Mutex::new(None));
+        self.outcome = Arc::clone(&outcome);
+        let spawned = thread::Builder::new()
+            .name(format!("worker-{}", name))
+            .spawn(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| run(&ctx))).unwrap_or_else(|payload| {
+                    let message = payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    Err(format!("Worker panicked: {}", message))
+                });
+                *outcome.lock().unwrap() = Some(result);
+            });
+        match spawned {
+            Ok(_) => {
+                self.started = Instant::now();
+                self.state = WorkerState::Running;
+            }
+            Err(e) => self.crashed(format!("Failed to start worker thread: {}", e)),
+        }
+    }
+
+    fn crashed(&mut self, error: String) {
+        self.state = WorkerState::Restarting;
+        self.last_error = Some(error);
+    }
+
+    fn status(&self, id: &str) -> WorkerStatus {
+        WorkerStatus {
+            id: id.to_string(),
+            state: self.state,
+            restarts: self.restarts,
+            frames: self.frames.load(Ordering::Relaxed),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+type Workers = Mutex<BTreeMap<String, Worker>>;
+
+/// Owns the workers, keyed by device ID. Cheap to clone; clones share them.
+#[derive(Clone)]
+pub struct Supervisor {
+    workers: Arc<Workers>,
+    policy: RestartPolicy,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            workers: Arc::new(Mutex::new(BTreeMap::new())),
+            policy,
+        }
+    }
+
+    /// Poll [`check`](Self::check) on a background thread until every clone of
+    /// the supervisor is gone; `on_change` gets each restart and failure
+    pub fn spawn_monitor(&self, on_change: impl Fn(&WorkerStatus) + Send + 'static) {
+        let workers: Weak<Workers> = Arc::downgrade(&self.workers);
+        let policy = self.policy;
+        let interval = Duration::from_millis(policy.poll_ms.max(10));
+        thread::spawn(move || {
+            while let Some(workers) = workers.upgrade() {
+                let supervisor = Supervisor { workers, policy };
+                for status in supervisor.check() {
+                    on_change(&status);
+                }
+                drop(supervisor);
+                thread::sleep(interval);
+            }
+        });
+    }
+
+    /// Start a worker; one already running under `id` is stopped first
+    pub fn add(&self, id: &str, run: WorkerRun) {
+        self.remove(id);
+        let mut worker = Worker {
+            run,
+            stop: Arc::new(AtomicBool::new(false)),
+            frames: Arc::new(AtomicU64::new(0)),
+            outcome: Arc::new(Mutex::new(None)),
+            started: Instant::now(),
+            state: WorkerState::Running,
+            restarts: 0,
+            restart_at: None,
+            last_error: None,
+        };
+        worker.spawn(id);
+        self.workers.lock().unwrap().insert(id.to_string(), worker);
+    }
+
+    /// Tell a worker to stop; returns whether there was one. Its thread isn't
+    /// joined: one wedged in a HID call must not hold up the caller.
+    pub fn remove(&self, id: &str) -> bool {
+        let worker = self.workers.lock().unwrap().remove(id);
+        match worker {
+            Some(worker) => {
+                worker.stop.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop every worker
+    pub fn clear(&self) {
+        let ids: Vec<String> = self.workers.lock().unwrap().keys().cloned().collect();
+        for id in ids {
+            self.remove(&id);
+        }
+    }
+
+    pub fn status(&self) -> Vec<WorkerStatus> {
+        self.workers.lock().unwrap().iter().map(|(id, w)| w.status(id)).collect()
+    }
+
+    /// Collect finished workers and restart crashed ones that are due.
+    /// Returns the workers whose state changed.
+    pub fn check(&self) -> Vec<WorkerStatus> {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> Vec<WorkerStatus> {
+        let mut changed = Vec::new();
+        let mut workers = self.workers.lock().unwrap();
+        for (id, worker) in workers.iter_mut() {
+            let outcome = worker.outcome.lock().unwrap().take();
+            if let Some(outcome) = outcome {
+                if now.saturating_duration_since(worker.started) >= Duration::from_millis(self.policy.stable_ms) {
+                    worker.restarts = 0;
+                }
+                match outcome {
+                    Ok(()) => worker.state = WorkerState::Stopped,
+                    Err(e) if worker.restarts >= self.policy.max_restarts => {
+                        eprintln!("❌ Worker {} failed for good: {}", id, e);
+                        worker.state = WorkerState::Failed;
+                        worker.last_error = Some(e);
+                    }
+                    Err(e) => {
+                        eprintln!("❌ Worker {} crashed: {}", id, e);
+                        worker.crashed(e);
+                        let backoff = self.policy.backoff_ms.saturating_mul(1 << worker.restarts.min(16));
+                        worker.restart_at = Some(now + Duration::from_millis(backoff));
+                    }
+                }
+                changed.push(worker.status(id));
+            }
+
+            if worker.state == WorkerState::Restarting && worker.restart_at.map_or(true, |at| now >= at) {
+                worker.restarts += 1;
+                worker.restart_at = None;
+                println!("🔧 Restarting worker {} (restart {})", id, worker.restarts);
+                worker.spawn(id);
+                changed.push(worker.status(id));
+            }
+        }
+        changed
+    }
+}
+
+/// Worker body for one controller: read into a reused buffer, parse with the
+/// controller's own parser and calibration, and pass each frame to `output`.
+/// Ends cleanly when the controller goes away or `output` returns false;
+/// a read error is a crash.
+pub fn device_worker(
+    manager: ControllerHandle,
+    output: impl Fn(&ControllerInput) -> bool + Send + Sync + 'static,
+) -> WorkerRun {
+    Arc::new(move |ctx: &WorkerContext| {
+        let mut buf = [0u8; MAX_READ_BUFFER_SIZE];
+        while !ctx.should_stop() {
+            let settings = manager.read_settings();
+            let buffer_size = settings.buffer_size.min(MAX_READ_BUFFER_SIZE);
+            let size = match manager.read_input_into(&mut buf[..buffer_size], settings.timeout_ms.max(1)) {
+                Ok(size) => size,
+                Err(ControllerError::NotConnected) => return Ok(()),
+                Err(e) => return Err(e.to_string()),
+            };
+            if size == 0 {
+                continue;
+            }
+            if let Ok(Packet::InputFrame(input)) = manager.parse_packet_at(&buf[..size], clock::monotonic_us()) {
+                ctx.frame();
+                if !output(&input) {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// One worker's mapping of its frames onto the active profile. Layers and held
+/// bindings are per controller, so a button held on one doesn't shift or
+/// release anything on another.
+#[derive(Debug, Default)]
+pub struct DeviceMapper {
+    layers: LayerEngine,
+}
+
+impl DeviceMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binding events for one frame, seen through the profile's mirroring and responses
+    pub fn map(&mut self, input: &ControllerInput, profile: Option<&Profile>, now: Instant) -> Vec<BindingEvent> {
+        match profile {
+            Some(profile) => {
+                let input = apply_responses(&mirror_input(input, profile.mirror), &profile.response);
+                self.layers.update(&input, Some(profile), now).events
+            }
+            None => self.layers.update(input, None, now).events,
+        }
+    }
+
+    /// Release events for every binding still held, for when the worker stops
+    pub fn release_all(&mut self) -> Vec<BindingEvent> {
+        self.layers.release_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::BindingPhase;
+    use crate::profiles::{Action, ButtonId};
+
+    #[test]
+    fn test_device_mappers_hold_bindings_apart() {
+        let mut profile = Profile::new("Shared");
+        profile.buttons.insert(ButtonId::A, Action::Key { key: "space".to_string() });
+        let (mut first, mut second) = (DeviceMapper::new(), DeviceMapper::new());
+        let now = Instant::now();
+
+        let mut pressed = ControllerInput::default();
+        ButtonId::A.set_pressed(&mut pressed.buttons, true);
+        let events = first.map(&pressed, Some(&profile), now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].phase, BindingPhase::Pressed);
+
+        // The other controller's idle frames leave the first one's key down
+        assert!(second.map(&ControllerInput::default(), Some(&profile), now).is_empty());
+        assert!(second.release_all().is_empty());
+
+        let released = first.release_all();
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].phase, BindingPhase::Released);
+        assert!(first.release_all().is_empty());
+    }
+
+    #[test]
+    fn test_supervisor_restarts_crashed_workers() {
+        let policy = RestartPolicy { max_restarts: 2, backoff_ms: 0, ..RestartPolicy::default() };
+        let supervisor = Supervisor::new(policy);
+
+        // Panics on its first run, then runs until stopped
+        let runs = Arc::new(AtomicU64::new(0));
+        let counted = Arc::clone(&runs);
+        supervisor.add(
+            "flaky",
+            Arc::new(move |ctx: &WorkerContext| {
+                if counted.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("bad frame");
+                }
+                ctx.frame();
+                while !ctx.should_stop() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(())
+            }),
+        );
+        // Always fails
+        supervisor.add("broken", Arc::new(|_: &WorkerContext| Err("read failed".to_string())));
+        // Done straight away
+        supervisor.add("done", Arc::new(|_: &WorkerContext| Ok(())));
+
+        // Threads end on their own schedule; keep checking until everything settled
+        for _ in 0..400 {
+            supervisor.check();
+            let states: Vec<WorkerState> = supervisor.status().iter().map(|s| s.state).collect();
+            let restarted = supervisor.status().iter().any(|s| s.id == "flaky" && s.restarts == 1);
+            if restarted && states == [WorkerState::Failed, WorkerState::Stopped, WorkerState::Running] {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let status: BTreeMap<String, WorkerStatus> =
+            supervisor.status().into_iter().map(|s| (s.id.clone(), s)).collect();
+        assert_eq!(status["flaky"].state, WorkerState::Running);
+        assert_eq!(status["flaky"].restarts, 1);
+        assert_eq!(status["flaky"].last_error.as_deref(), Some("Worker panicked: bad frame"));
+        assert_eq!(status["broken"].state, WorkerState::Failed);
+        assert_eq!(status["broken"].restarts, 2);
+        assert_eq!(status["broken"].last_error.as_deref(), Some("read failed"));
+        assert_eq!(status["done"].state, WorkerState::Stopped);
+        assert_eq!(status["done"].restarts, 0);
+
+        assert!(supervisor.remove("flaky"));
+        assert!(!supervisor.remove("flaky"));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        supervisor.clear();
+        assert!(supervisor.status().is_empty());
+    }
+}
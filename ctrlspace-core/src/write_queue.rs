@@ -0,0 +1,270 @@
+//! Queue of pending feature-report writes, drained by whichever thread holds
+//! the device: a reader sends them between short read slices (see
+//! [`WriteQueue::read_between_writes`]), and a writer thread per manager
+//! picks them up while nobody is reading. Callers never touch the device lock
+//! themselves, so firing a haptic pulse from the input thread doesn't wait on
+//! it, and a write waits at most one read slice for a blocked read.
+//! Configuration writes (lizard mode, registers, raw reports, melodies) always
+//! go before queued haptics, and a pulse still waiting for its side is
+//! replaced by a newer one instead of piling up behind it.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use crate::write_stats::WriteKind;
+
+/// How long the writer thread waits for work before exiting (it is
+/// restarted by the next write)
+pub const WRITER_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest one blocking read holds off queued writes
+pub const READ_SLICE: Duration = Duration::from_millis(2);
+
+/// Haptic writes held at once; the oldest is dropped past this
+pub const MAX_QUEUED_HAPTICS: usize = 16;
+
+/// Where a write goes in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WritePriority {
+    Config,
+    Haptics,
+}
+
+impl WritePriority {
+    pub fn of(kind: WriteKind) -> Self {
+        match kind {
+            WriteKind::Haptics => WritePriority::Haptics,
+            WriteKind::LizardMode | WriteKind::Configuration | WriteKind::Raw | WriteKind::Audio => WritePriority::Config,
+        }
+    }
+}
+
+/// One pending write
+#[derive(Debug)]
+pub struct QueuedWrite {
+    pub kind: WriteKind,
+    pub data: Vec<u8>,
+    /// A queued write with the same kind and key is replaced by this one
+    pub coalesce: Option<u8>,
+    /// Gets the outcome; fire-and-forget writes have none
+    pub reply: Option<mpsc::Sender<Result<(), String>>>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    config: VecDeque<QueuedWrite>,
+    haptics: VecDeque<QueuedWrite>,
+    worker: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct WriteQueue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+impl WriteQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a write. True when no writer thread is running, in which case
+    /// the caller must start one (it is marked as running already).
+    pub fn push(&self, write: QueuedWrite) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let queue = match WritePriority::of(write.kind) {
+            WritePriority::Config => &mut state.config,
+            WritePriority::Haptics => &mut state.haptics,
+        };
+
+        let replaced = write.coalesce.and_then(|key| {
+            queue
+                .iter_mut()
+                .find(|queued| queued.kind == write.kind && queued.coalesce == Some(key))
+        });
+        match replaced {
+            // The replaced write's caller (if any) is told it never went out
+            Some(queued) => {
+                if let Some(reply) = std::mem::replace(queued, write).reply {
+                    let _ = reply.send(Err("Superseded by a newer write".to_string()));
+                }
+            }
+            None => {
+                if write.kind == WriteKind::Haptics && queue.len() >= MAX_QUEUED_HAPTICS {
+                    queue.pop_front();
+                }
+                queue.push_back(write);
+            }
+        }
+
+        self.ready.notify_one();
+        !std::mem::replace(&mut state.worker, true)
+    }
+
+    /// Wait up to `idle` for a write to be queued. False means the writer
+    /// thread should exit (it is marked as stopped).
+    pub fn wait(&self, idle: Duration) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while state.config.is_empty() && state.haptics.is_empty() {
+            let (guard, timeout) = self.ready.wait_timeout(state, idle).unwrap();
+            state = guard;
+            if timeout.timed_out() && state.config.is_empty() && state.haptics.is_empty() {
+                state.worker = false;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Next write, configuration first, without waiting
+    pub fn pop(&self) -> Option<QueuedWrite> {
+        let mut state = self.state.lock().unwrap();
+        state.config.pop_front().or_else(|| state.haptics.pop_front())
+    }
+
+    /// Perform every queued write with `perform` and hand each caller its
+    /// result; failed fire-and-forget writes are logged
+    pub fn drain<P>(&self, mut perform: P)
+    where
+        P: FnMut(&QueuedWrite) -> Result<(), String>,
+    {
+        while let Some(write) = self.pop() {
+            let result = perform(&write);
+            match write.reply {
+                Some(reply) => {
+                    let _ = reply.send(result);
+                }
+                None => {
+                    if let Err(e) = result {
+                        eprintln!("❌ {:?} write failed: {}", write.kind, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A read of up to `timeout` (`None` waits for a report) split into
+    /// slices of at most [`READ_SLICE`], draining the queue with `perform`
+    /// before each one. `read` gets the slice's timeout and returns the
+    /// report length; the first report or error ends the read, and 0 means
+    /// none arrived in time.
+    pub fn read_between_writes<R, P, E>(&self, timeout: Option<Duration>, mut read: R, mut perform: P) -> Result<usize, E>
+    where
+        R: FnMut(Duration) -> Result<usize, E>,
+        P: FnMut(&QueuedWrite) -> Result<(), String>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            self.drain(&mut perform);
+            let slice = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()).min(READ_SLICE),
+                None => READ_SLICE,
+            };
+            let size = read(slice)?;
+            if size > 0 || deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                return Ok(size);
+            }
+        }
+    }
+
+    /// Writes waiting to go out
+    pub fn len(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.config.len() + state.haptics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fail every pending write with `reason`
+    pub fn clear(&self, reason: &str) {
+        let mut state = self.state.lock().unwrap();
+        let QueueState { config, haptics, .. } = &mut *state;
+        for write in config.drain(..).chain(haptics.drain(..)) {
+            if let Some(reply) = write.reply {
+                let _ = reply.send(Err(reason.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn write(kind: WriteKind, id: u8, coalesce: Option<u8>) -> QueuedWrite {
+        QueuedWrite { kind, data: vec![id], coalesce, reply: None }
+    }
+
+    #[test]
+    fn test_config_first_and_haptics_coalesced() {
+        let queue = WriteQueue::new();
+        assert!(queue.push(write(WriteKind::Haptics, 1, Some(0))));
+        assert!(!queue.push(write(WriteKind::Haptics, 2, Some(1))));
+        // Newer pulse for side 0 takes the older one's place
+        queue.push(write(WriteKind::Haptics, 3, Some(0)));
+
+        let (tx, rx) = mpsc::channel();
+        queue.push(QueuedWrite { reply: Some(tx), ..write(WriteKind::Configuration, 4, None) });
+        queue.push(write(WriteKind::LizardMode, 5, None));
+        assert_eq!(queue.len(), 4);
+
+        assert!(queue.wait(Duration::ZERO));
+        let order: Vec<u8> = (0..4).map(|_| queue.pop().unwrap().data[0]).collect();
+        assert_eq!(order, vec![4, 5, 3, 2]);
+        assert!(rx.try_recv().is_err(), "reply is sent by `drain`, not `pop`");
+
+        // Idle: the worker is told to exit and the next push restarts it
+        assert!(!queue.wait(Duration::from_millis(1)));
+        assert!(queue.push(write(WriteKind::Haptics, 6, None)));
+
+        for i in 0..MAX_QUEUED_HAPTICS as u8 {
+            queue.push(write(WriteKind::Haptics, 10 + i, None));
+        }
+        assert_eq!(queue.len(), MAX_QUEUED_HAPTICS);
+        assert_eq!(queue.pop().unwrap().data[0], 10);
+    }
+
+    #[test]
+    fn test_write_completes_during_blocked_read() {
+        let queue = Arc::new(WriteQueue::new());
+        let written = Arc::new(Mutex::new(Vec::new()));
+
+        // A caller queues a configuration write while the read below is blocked
+        let (tx, rx) = mpsc::channel();
+        let sender = Arc::clone(&queue);
+        let caller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            let queued_at = Instant::now();
+            sender.push(QueuedWrite { reply: Some(tx), ..write(WriteKind::Configuration, 7, None) });
+            let result = rx.recv().unwrap();
+            (result, queued_at.elapsed())
+        });
+
+        // Nothing arrives for 300 ms
+        let started = Instant::now();
+        let log = Arc::clone(&written);
+        let size = queue.read_between_writes(
+            Some(Duration::from_millis(300)),
+            |slice| -> Result<usize, ()> {
+                thread::sleep(slice);
+                Ok(0)
+            },
+            |write| {
+                log.lock().unwrap().push(write.data[0]);
+                Ok(())
+            },
+        );
+        assert_eq!(size, Ok(0));
+        assert!(started.elapsed() >= Duration::from_millis(300));
+
+        let (result, waited) = caller.join().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(*written.lock().unwrap(), vec![7]);
+        assert!(waited < Duration::from_millis(150), "write waited {:?} for the read", waited);
+    }
+}
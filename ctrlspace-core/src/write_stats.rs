@@ -0,0 +1,163 @@
+//! Timing of feature-report writes. On some platforms (wireless dongles,
+//! certain Windows HID stacks) a write blocks for several milliseconds, which
+//! shows up as haptics lagging behind the input that triggered them; the
+//! recent latencies per kind of write make that visible.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+/// Writes kept for the stats
+pub const WRITE_HISTORY_LEN: usize = 256;
+
+/// What a feature-report write was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteKind {
+    /// Mouse emulation on/off (0x81)
+    LizardMode,
+    /// Trackpad actuator pulses (0x8f)
+    Haptics,
+    /// Register writes (0x87)
+    Configuration,
+    /// Raw reports from the developer console
+    Raw,
+    /// Melody playback and jingle assignment (0xb6, 0xc1)
+    Audio,
+}
+
+/// One timed write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WriteSample {
+    pub kind: WriteKind,
+    pub report_id: u8,
+    pub latency_us: u64,
+    pub ok: bool,
+    /// Host monotonic time the write started, in µs
+    pub at_us: u64,
+}
+
+/// Latency distribution of the recent writes of one kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub failed: usize,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencySummary {
+    fn from_latencies(mut latencies: Vec<u64>, failed: usize) -> Self {
+        if latencies.is_empty() {
+            return Self { failed, ..Self::default() };
+        }
+        latencies.sort_unstable();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+        Self {
+            count: latencies.len(),
+            failed,
+            mean_us: latencies.iter().sum::<u64>() / latencies.len() as u64,
+            p50_us: percentile(50),
+            p95_us: percentile(95),
+            max_us: *latencies.last().unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WriteStats {
+    pub by_kind: BTreeMap<WriteKind, LatencySummary>,
+    /// Newest last
+    pub recent: Vec<WriteSample>,
+}
+
+/// Rolling history of the last `WRITE_HISTORY_LEN` writes
+#[derive(Debug, Clone, Default)]
+pub struct WriteLatencyLog {
+    samples: VecDeque<WriteSample>,
+    // (writes, failures) per kind since the log was created; not cleared
+    totals: BTreeMap<WriteKind, (u64, u64)>,
+}
+
+impl WriteLatencyLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, kind: WriteKind, report_id: u8, latency: Duration, ok: bool, at_us: u64) {
+        if self.samples.len() == WRITE_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        let (writes, failures) = self.totals.entry(kind).or_default();
+        *writes += 1;
+        *failures += !ok as u64;
+        self.samples.push_back(WriteSample {
+            kind,
+            report_id,
+            latency_us: latency.as_micros() as u64,
+            ok,
+            at_us,
+        });
+    }
+
+    /// (writes, failures) of one kind over the log's lifetime, unaffected by `clear`
+    pub fn totals(&self, kind: WriteKind) -> (u64, u64) {
+        self.totals.get(&kind).copied().unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Per-kind summary (successful writes only) plus the last `recent` samples
+    pub fn stats(&self, recent: usize) -> WriteStats {
+        let mut latencies: BTreeMap<WriteKind, (Vec<u64>, usize)> = BTreeMap::new();
+        for sample in &self.samples {
+            let (ok, failed) = latencies.entry(sample.kind).or_default();
+            if sample.ok {
+                ok.push(sample.latency_us);
+            } else {
+                *failed += 1;
+            }
+        }
+        WriteStats {
+            by_kind: latencies
+                .into_iter()
+                .map(|(kind, (ok, failed))| (kind, LatencySummary::from_latencies(ok, failed)))
+                .collect(),
+            recent: self.samples.iter().skip(self.samples.len().saturating_sub(recent)).copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_per_kind() {
+        let mut log = WriteLatencyLog::new();
+        for ms in 1..=20 {
+            log.record(WriteKind::Haptics, 0x8f, Duration::from_millis(ms), true, ms * 1000);
+        }
+        log.record(WriteKind::Haptics, 0x8f, Duration::from_millis(500), false, 21_000);
+        log.record(WriteKind::LizardMode, 0x81, Duration::from_micros(300), true, 22_000);
+
+        assert_eq!(log.totals(WriteKind::Haptics), (21, 1));
+
+        let stats = log.stats(2);
+        let haptics = stats.by_kind[&WriteKind::Haptics];
+        assert_eq!((haptics.count, haptics.failed), (20, 1));
+        assert_eq!((haptics.p50_us, haptics.p95_us, haptics.max_us), (10_000, 19_000, 20_000));
+        assert_eq!(haptics.mean_us, 10_500);
+        assert_eq!(stats.by_kind[&WriteKind::LizardMode].max_us, 300);
+        assert_eq!(stats.recent.iter().map(|s| s.report_id).collect::<Vec<_>>(), vec![0x8f, 0x81]);
+
+        for _ in 0..WRITE_HISTORY_LEN {
+            log.record(WriteKind::Raw, 0x01, Duration::ZERO, true, 0);
+        }
+        assert_eq!(log.stats(0).by_kind.keys().collect::<Vec<_>>(), vec![&WriteKind::Raw]);
+    }
+}
@@ -0,0 +1,139 @@
+//! XInput slot and conflict detection, done before a virtual X360 pad is
+//! created: XInput has four player slots, and if Steam's own virtual pad is
+//! already mirroring the controller, adding ours gives the game two pads
+//! fighting for player 1.
+
+use serde::{Deserialize, Serialize};
+use crate::enumeration::HidDeviceEntry;
+use crate::steam_controller::VALVE_VENDOR_ID;
+
+/// XInput player slots
+pub const XINPUT_SLOTS: usize = 4;
+
+/// Steam Input's virtual gamepad (the pad games see while Steam remaps the controller)
+pub const STEAM_VIRTUAL_GAMEPAD_PID: u16 = 0x11ff;
+
+/// What to do when a virtual pad would conflict with one already present
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Don't create our pad while Steam's virtual pad is present
+    Refuse,
+    /// Create it anyway, e.g. for games that only read the second pad
+    Duplicate,
+}
+
+impl Default for ConflictPolicy {
+    fn default() -> Self {
+        ConflictPolicy::Refuse
+    }
+}
+
+/// A HID device that also shows up as an XInput pad
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct XInputDevice {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub product: String,
+    pub path: String,
+    pub steam_virtual: bool,
+}
+
+impl XInputDevice {
+    /// XInput-capable interfaces carry `IG_` in their Windows device path;
+    /// Steam's virtual pad is recognized by its IDs on every platform
+    pub fn from_entry(entry: &HidDeviceEntry) -> Option<Self> {
+        let steam_virtual = entry.vendor_id == VALVE_VENDOR_ID && entry.product_id == STEAM_VIRTUAL_GAMEPAD_PID;
+        if !steam_virtual && !entry.path.to_ascii_uppercase().contains("&IG_") {
+            return None;
+        }
+        Some(Self {
+            vendor_id: entry.vendor_id,
+            product_id: entry.product_id,
+            product: entry.product.clone(),
+            path: entry.path.clone(),
+            steam_virtual,
+        })
+    }
+}
+
+/// XInput diagnostics: who holds which slot, and whether a virtual pad may be added
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct XInputReport {
+    /// Slot occupancy as XInput reports it; `None` where XInput doesn't exist
+    pub slots: Option<[bool; XINPUT_SLOTS]>,
+    pub devices: Vec<XInputDevice>,
+    pub steam_virtual_pad: bool,
+}
+
+impl XInputReport {
+    pub fn new(entries: &[HidDeviceEntry], slots: Option<[bool; XINPUT_SLOTS]>) -> Self {
+        let devices: Vec<XInputDevice> = entries.iter().filter_map(XInputDevice::from_entry).collect();
+        let steam_virtual_pad = devices.iter().any(|d| d.steam_virtual);
+        Self {
+            slots,
+            devices,
+            steam_virtual_pad,
+        }
+    }
+
+    /// The player slot a new pad would get, if XInput reports slots at all
+    pub fn free_slot(&self) -> Option<usize> {
+        self.slots.and_then(|slots| slots.iter().position(|used| !used))
+    }
+
+    /// Whether a virtual pad may be created under `policy`
+    pub fn check(&self, policy: ConflictPolicy) -> Result<(), String> {
+        if let Some(slots) = self.slots {
+            if slots.iter().all(|used| *used) {
+                return Err("All four XInput slots are in use".to_string());
+            }
+        }
+        if self.steam_virtual_pad && policy == ConflictPolicy::Refuse {
+            return Err(
+                "Steam's virtual gamepad is already present - disable Steam Input for this controller, or allow duplicates"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(vendor_id: u16, product_id: u16, path: &str) -> HidDeviceEntry {
+        HidDeviceEntry {
+            vendor_id,
+            product_id,
+            product: "Test".to_string(),
+            manufacturer: None,
+            serial: None,
+            path: path.to_string(),
+            interface_number: 0,
+            usage_page: 0x01,
+            usage: 0x05,
+        }
+    }
+
+    #[test]
+    fn test_steam_virtual_pad_conflict() {
+        let xbox = entry(0x045e, 0x028e, r"\\?\HID#VID_045E&PID_028E&IG_00#8&1a2b&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}");
+        let keyboard = entry(0x046d, 0xc31c, r"\\?\HID#VID_046D&PID_C31C&MI_00#7&2c3d&0&0000#{...}");
+        let steam = entry(VALVE_VENDOR_ID, STEAM_VIRTUAL_GAMEPAD_PID, "/dev/hidraw5");
+
+        let report = XInputReport::new(&[xbox.clone(), keyboard], Some([true, false, false, false]));
+        assert_eq!(report.devices.len(), 1);
+        assert_eq!(report.free_slot(), Some(1));
+        assert!(report.check(ConflictPolicy::Refuse).is_ok());
+
+        let report = XInputReport::new(&[xbox, steam], Some([true, true, false, false]));
+        assert!(report.steam_virtual_pad);
+        assert!(report.check(ConflictPolicy::Refuse).is_err());
+        assert!(report.check(ConflictPolicy::Duplicate).is_ok());
+
+        let full = XInputReport::new(&[], Some([true; XINPUT_SLOTS]));
+        assert!(full.check(ConflictPolicy::Duplicate).is_err());
+    }
+}
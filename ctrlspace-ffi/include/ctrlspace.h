@@ -0,0 +1,102 @@
+/*
+ * CtrlSpace - C API for the Steam Controller driver.
+ *
+ * Link against the `ctrlspace` library built from the ctrlspace-ffi crate
+ * (cdylib or staticlib). All functions returning int32_t use the
+ * CTRLSPACE_* status codes; call ctrlspace_last_error() after a failure.
+ */
+#ifndef CTRLSPACE_H
+#define CTRLSPACE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CTRLSPACE_OK                  0
+#define CTRLSPACE_NO_DATA             1
+#define CTRLSPACE_ERROR              -1
+#define CTRLSPACE_INVALID_ARGUMENT   -2
+#define CTRLSPACE_NOT_CONNECTED      -3
+
+#define CTRLSPACE_BUTTON_A            (1u << 0)
+#define CTRLSPACE_BUTTON_B            (1u << 1)
+#define CTRLSPACE_BUTTON_X            (1u << 2)
+#define CTRLSPACE_BUTTON_Y            (1u << 3)
+#define CTRLSPACE_BUTTON_LB           (1u << 4)
+#define CTRLSPACE_BUTTON_RB           (1u << 5)
+#define CTRLSPACE_BUTTON_LT           (1u << 6)
+#define CTRLSPACE_BUTTON_RT           (1u << 7)
+#define CTRLSPACE_BUTTON_LGRIP        (1u << 8)
+#define CTRLSPACE_BUTTON_RGRIP        (1u << 9)
+#define CTRLSPACE_BUTTON_START        (1u << 10)
+#define CTRLSPACE_BUTTON_SELECT       (1u << 11)
+#define CTRLSPACE_BUTTON_STEAM        (1u << 12)
+#define CTRLSPACE_BUTTON_LPAD_CLICK   (1u << 13)
+#define CTRLSPACE_BUTTON_RPAD_CLICK   (1u << 14)
+#define CTRLSPACE_BUTTON_STICK_CLICK  (1u << 15)
+
+typedef struct CtrlSpace CtrlSpace;
+
+typedef struct CtrlSpaceInput {
+    uint32_t buttons;        /* bitmask of CTRLSPACE_BUTTON_* */
+    uint32_t timestamp;      /* controller timestamp */
+    uint8_t  left_trigger;   /* 0-255 */
+    uint8_t  right_trigger;  /* 0-255 */
+    uint8_t  left_pad_active;
+    uint8_t  right_pad_active;
+    int16_t  stick_x;
+    int16_t  stick_y;
+    int16_t  left_pad_x;
+    int16_t  left_pad_y;
+    int16_t  right_pad_x;
+    int16_t  right_pad_y;
+    int16_t  gyro_pitch;
+    int16_t  gyro_yaw;
+    int16_t  gyro_roll;
+} CtrlSpaceInput;
+
+/* Invoked on a driver thread for every parsed frame. */
+typedef void (*CtrlSpaceInputCallback)(const CtrlSpaceInput *input, void *user_data);
+
+/* Create a driver instance; NULL if hidapi could not be initialized. */
+CtrlSpace *ctrlspace_create(void);
+
+/* Stop streaming, restore Lizard Mode and free the handle. */
+void ctrlspace_destroy(CtrlSpace *handle);
+
+/* CTRLSPACE_OK if a controller is present, CTRLSPACE_NOT_CONNECTED otherwise. */
+int32_t ctrlspace_detect(CtrlSpace *handle);
+
+/* Open the controller's vendor-specific interface. */
+int32_t ctrlspace_connect(CtrlSpace *handle);
+
+/* Stop streaming and close the device (re-enables Lizard Mode). */
+void ctrlspace_disconnect(CtrlSpace *handle);
+
+/* 1 if connected, 0 otherwise. */
+int32_t ctrlspace_is_connected(const CtrlSpace *handle);
+
+/* Read one frame into *out. CTRLSPACE_NO_DATA if nothing arrived in time or
+ * the packet wasn't an input report (wireless status, battery),
+ * CTRLSPACE_ERROR (see ctrlspace_last_error) if the read itself failed. */
+int32_t ctrlspace_poll(CtrlSpace *handle, CtrlSpaceInput *out);
+
+/*
+ * Start a reader thread calling `callback` for every frame. Replaces any
+ * previous callback; pass NULL to stop. `user_data` must stay valid until
+ * the callback is cleared or the handle destroyed.
+ */
+int32_t ctrlspace_set_input_callback(CtrlSpace *handle,
+                                     CtrlSpaceInputCallback callback,
+                                     void *user_data);
+
+/* Last error on the calling thread, or NULL. Valid until the next failure. */
+const char *ctrlspace_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CTRLSPACE_H */
@@ -0,0 +1,372 @@
+//! C-compatible API over `ctrlspace-core`, so native applications and game
+//! engines can embed the Steam Controller driver. See `include/ctrlspace.h`.
+//!
+//! Every function returning `i32` uses the `CTRLSPACE_*` status codes; on
+//! failure `ctrlspace_last_error()` describes what went wrong on that thread.
+
+use ctrlspace_core::error::ControllerError;
+use ctrlspace_core::clock;
+use ctrlspace_core::input_parser::{ControllerInput, Packet};
+use ctrlspace_core::input_stream::InputStream;
+use ctrlspace_core::steam_controller::SteamControllerManager;
+use std::cell::RefCell;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+pub const CTRLSPACE_OK: i32 = 0;
+pub const CTRLSPACE_NO_DATA: i32 = 1;
+pub const CTRLSPACE_ERROR: i32 = -1;
+pub const CTRLSPACE_INVALID_ARGUMENT: i32 = -2;
+pub const CTRLSPACE_NOT_CONNECTED: i32 = -3;
+
+pub const CTRLSPACE_BUTTON_A: u32 = 1 << 0;
+pub const CTRLSPACE_BUTTON_B: u32 = 1 << 1;
+pub const CTRLSPACE_BUTTON_X: u32 = 1 << 2;
+pub const CTRLSPACE_BUTTON_Y: u32 = 1 << 3;
+pub const CTRLSPACE_BUTTON_LB: u32 = 1 << 4;
+pub const CTRLSPACE_BUTTON_RB: u32 = 1 << 5;
+pub const CTRLSPACE_BUTTON_LT: u32 = 1 << 6;
+pub const CTRLSPACE_BUTTON_RT: u32 = 1 << 7;
+pub const CTRLSPACE_BUTTON_LGRIP: u32 = 1 << 8;
+pub const CTRLSPACE_BUTTON_RGRIP: u32 = 1 << 9;
+pub const CTRLSPACE_BUTTON_START: u32 = 1 << 10;
+pub const CTRLSPACE_BUTTON_SELECT: u32 = 1 << 11;
+pub const CTRLSPACE_BUTTON_STEAM: u32 = 1 << 12;
+pub const CTRLSPACE_BUTTON_LPAD_CLICK: u32 = 1 << 13;
+pub const CTRLSPACE_BUTTON_RPAD_CLICK: u32 = 1 << 14;
+pub const CTRLSPACE_BUTTON_STICK_CLICK: u32 = 1 << 15;
+
+/// Flat, C-layout copy of `ControllerInput`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CtrlSpaceInput {
+    /// Bitmask of `CTRLSPACE_BUTTON_*`
+    pub buttons: u32,
+    pub timestamp: u32,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    pub left_pad_active: u8,
+    pub right_pad_active: u8,
+    pub stick_x: i16,
+    pub stick_y: i16,
+    pub left_pad_x: i16,
+    pub left_pad_y: i16,
+    pub right_pad_x: i16,
+    pub right_pad_y: i16,
+    pub gyro_pitch: i16,
+    pub gyro_yaw: i16,
+    pub gyro_roll: i16,
+}
+
+impl From<&ControllerInput> for CtrlSpaceInput {
+    fn from(input: &ControllerInput) -> Self {
+        let b = &input.buttons;
+        let flags = [
+            (b.a, CTRLSPACE_BUTTON_A),
+            (b.b, CTRLSPACE_BUTTON_B),
+            (b.x, CTRLSPACE_BUTTON_X),
+            (b.y, CTRLSPACE_BUTTON_Y),
+            (b.lb, CTRLSPACE_BUTTON_LB),
+            (b.rb, CTRLSPACE_BUTTON_RB),
+            (b.lt, CTRLSPACE_BUTTON_LT),
+            (b.rt, CTRLSPACE_BUTTON_RT),
+            (b.lgrip, CTRLSPACE_BUTTON_LGRIP),
+            (b.rgrip, CTRLSPACE_BUTTON_RGRIP),
+            (b.start, CTRLSPACE_BUTTON_START),
+            (b.select, CTRLSPACE_BUTTON_SELECT),
+            (b.steam, CTRLSPACE_BUTTON_STEAM),
+            (b.lpad_click, CTRLSPACE_BUTTON_LPAD_CLICK),
+            (b.rpad_click, CTRLSPACE_BUTTON_RPAD_CLICK),
+            (b.stick_click, CTRLSPACE_BUTTON_STICK_CLICK),
+        ];
+
+        Self {
+            buttons: flags
+                .iter()
+                .filter(|(pressed, _)| *pressed)
+                .fold(0, |mask, (_, bit)| mask | bit),
+            timestamp: input.timestamp,
+            left_trigger: input.triggers.left,
+            right_trigger: input.triggers.right,
+            left_pad_active: input.left_trackpad.active as u8,
+            right_pad_active: input.right_trackpad.active as u8,
+            stick_x: input.stick.x,
+            stick_y: input.stick.y,
+            left_pad_x: input.left_trackpad.x,
+            left_pad_y: input.left_trackpad.y,
+            right_pad_x: input.right_trackpad.x,
+            right_pad_y: input.right_trackpad.y,
+            gyro_pitch: input.gyro.pitch,
+            gyro_yaw: input.gyro.yaw,
+            gyro_roll: input.gyro.roll,
+        }
+    }
+}
+
+/// Called on the driver's reader thread for every parsed frame
+pub type CtrlSpaceInputCallback =
+    Option<unsafe extern "C" fn(input: *const CtrlSpaceInput, user_data: *mut c_void)>;
+
+/// Opaque handle returned by `ctrlspace_create`
+pub struct CtrlSpace {
+    manager: SteamControllerManager,
+    stream: Option<InputStream>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// The callback's user data pointer, moved onto the reader thread.
+/// The caller guarantees it stays valid until the callback is cleared.
+struct UserData(*mut c_void);
+unsafe impl Send for UserData {}
+
+/// Create a driver instance. Returns NULL if hidapi could not be initialized.
+#[no_mangle]
+pub extern "C" fn ctrlspace_create() -> *mut CtrlSpace {
+    match SteamControllerManager::new() {
+        Ok(manager) => Box::into_raw(Box::new(CtrlSpace {
+            manager,
+            stream: None,
+        })),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Stop any callback stream, restore Lizard Mode and free the handle.
+///
+/// # Safety
+/// `handle` must come from `ctrlspace_create` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_destroy(handle: *mut CtrlSpace) {
+    if handle.is_null() {
+        return;
+    }
+    let mut ctx = Box::from_raw(handle);
+    if let Some(stream) = ctx.stream.take() {
+        stream.stop();
+    }
+    if ctx.manager.is_connected() {
+        ctx.manager.disconnect();
+    }
+}
+
+/// Returns `CTRLSPACE_OK` if a Steam Controller is present, `CTRLSPACE_NOT_CONNECTED` otherwise.
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_detect(handle: *mut CtrlSpace) -> i32 {
+    let ctx = match handle.as_ref() {
+        Some(ctx) => ctx,
+        None => {
+            set_last_error("Null handle");
+            return CTRLSPACE_INVALID_ARGUMENT;
+        }
+    };
+    match ctx.manager.detect() {
+        Some(_) => CTRLSPACE_OK,
+        None => {
+            set_last_error("Steam Controller not found");
+            CTRLSPACE_NOT_CONNECTED
+        }
+    }
+}
+
+/// Open the controller's vendor-specific interface.
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_connect(handle: *mut CtrlSpace) -> i32 {
+    let ctx = match handle.as_ref() {
+        Some(ctx) => ctx,
+        None => {
+            set_last_error("Null handle");
+            return CTRLSPACE_INVALID_ARGUMENT;
+        }
+    };
+    match ctx.manager.connect() {
+        Ok(_) => CTRLSPACE_OK,
+        Err(e) => {
+            set_last_error(&e);
+            CTRLSPACE_ERROR
+        }
+    }
+}
+
+/// Stop any callback stream and close the device (re-enabling Lizard Mode).
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_disconnect(handle: *mut CtrlSpace) {
+    let ctx = match handle.as_mut() {
+        Some(ctx) => ctx,
+        None => return,
+    };
+    if let Some(stream) = ctx.stream.take() {
+        stream.stop();
+    }
+    ctx.manager.disconnect();
+}
+
+/// Returns 1 if connected, 0 otherwise.
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create` (or NULL).
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_is_connected(handle: *const CtrlSpace) -> i32 {
+    match handle.as_ref() {
+        Some(ctx) => ctx.manager.is_connected() as i32,
+        None => 0,
+    }
+}
+
+/// Read one report using the configured read settings. Writes into `out` and
+/// returns `CTRLSPACE_OK`, or returns `CTRLSPACE_NO_DATA` if nothing arrived in time
+/// or the packet wasn't an input report (wireless status, battery).
+/// Read failures return `CTRLSPACE_ERROR` with the reason in `ctrlspace_last_error`.
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create`; `out` must point to
+/// writable memory for one `CtrlSpaceInput`.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_poll(handle: *mut CtrlSpace, out: *mut CtrlSpaceInput) -> i32 {
+    let ctx = match handle.as_ref() {
+        Some(ctx) => ctx,
+        None => {
+            set_last_error("Null handle");
+            return CTRLSPACE_INVALID_ARGUMENT;
+        }
+    };
+    if out.is_null() {
+        set_last_error("Null output pointer");
+        return CTRLSPACE_INVALID_ARGUMENT;
+    }
+    if !ctx.manager.is_connected() {
+        set_last_error("Controller not connected");
+        return CTRLSPACE_NOT_CONNECTED;
+    }
+
+    let raw = match ctx.manager.read_input() {
+        Ok(Some(raw)) => raw,
+        Ok(None) => return CTRLSPACE_NO_DATA,
+        Err(ControllerError::NotConnected) => {
+            set_last_error("Controller not connected");
+            return CTRLSPACE_NOT_CONNECTED;
+        }
+        Err(e) => {
+            set_last_error(&e.to_string());
+            return CTRLSPACE_ERROR;
+        }
+    };
+
+    match ctx.manager.parse_packet_at(&raw, clock::monotonic_us()) {
+        Ok(Packet::InputFrame(input)) => {
+            *out = CtrlSpaceInput::from(&input);
+            CTRLSPACE_OK
+        }
+        Ok(_) => CTRLSPACE_NO_DATA,
+        Err(e) => {
+            set_last_error(&e);
+            CTRLSPACE_ERROR
+        }
+    }
+}
+
+/// Start a reader thread that invokes `callback` for every parsed frame.
+/// Replaces any previously registered callback; pass NULL to stop.
+///
+/// # Safety
+/// `handle` must be a live pointer from `ctrlspace_create`. `callback` runs on a
+/// driver thread and `user_data` must remain valid until the callback is cleared
+/// or the handle is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn ctrlspace_set_input_callback(
+    handle: *mut CtrlSpace,
+    callback: CtrlSpaceInputCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    let ctx = match handle.as_mut() {
+        Some(ctx) => ctx,
+        None => {
+            set_last_error("Null handle");
+            return CTRLSPACE_INVALID_ARGUMENT;
+        }
+    };
+
+    if let Some(stream) = ctx.stream.take() {
+        stream.stop();
+    }
+
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return CTRLSPACE_OK,
+    };
+    if !ctx.manager.is_connected() {
+        set_last_error("Controller not connected");
+        return CTRLSPACE_NOT_CONNECTED;
+    }
+
+    let user_data = UserData(user_data);
+    // The callback is synchronous, so every emitted frame is consumed before the
+    // next one - no in-flight limit needed
+    ctx.stream = Some(InputStream::spawn(ctx.manager.handle(), u64::MAX, move |frame| {
+        let user_data = &user_data;
+        let input = CtrlSpaceInput::from(&frame.input);
+        callback(&input, user_data.0);
+        true
+    }));
+
+    CTRLSPACE_OK
+}
+
+/// Last error message on the calling thread, or NULL. Valid until the next
+/// failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn ctrlspace_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| match e.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_mask_conversion() {
+        let mut input = ControllerInput::default();
+        input.buttons.a = true;
+        input.buttons.steam = true;
+        input.triggers.left = 200;
+        input.right_trackpad.active = true;
+
+        let flat = CtrlSpaceInput::from(&input);
+        assert_eq!(flat.buttons, CTRLSPACE_BUTTON_A | CTRLSPACE_BUTTON_STEAM);
+        assert_eq!(flat.left_trigger, 200);
+        assert_eq!(flat.right_pad_active, 1);
+        assert_eq!(flat.left_pad_active, 0);
+    }
+
+    #[test]
+    fn test_null_handle_is_rejected() {
+        let mut out = CtrlSpaceInput::default();
+        let status = unsafe { ctrlspace_poll(ptr::null_mut(), &mut out) };
+        assert_eq!(status, CTRLSPACE_INVALID_ARGUMENT);
+        assert!(!ctrlspace_last_error().is_null());
+        assert_eq!(unsafe { ctrlspace_is_connected(ptr::null()) }, 0);
+    }
+}
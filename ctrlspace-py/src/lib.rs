@@ -0,0 +1,156 @@
+//! Python bindings for `ctrlspace-core`, for prototyping input experiments in
+//! Python on top of this crate's protocol handling.
+//!
+//! ```python
+//! import ctrlspace
+//! sc = ctrlspace.Controller()
+//! sc.connect()
+//! for frame in sc.stream():
+//!     print(frame["buttons"]["a"], frame["stick"])
+//! ```
+
+use ctrlspace_core::clock;
+use ctrlspace_core::input_parser::Packet;
+use ctrlspace_core::steam_controller::{ReadSettings, ReadStrategy, SteamControllerManager};
+use pyo3::exceptions::{PyConnectionError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pythonize::pythonize;
+
+/// Read timeout used by the iterator between checks for Ctrl+C
+const STREAM_READ_TIMEOUT_MS: i32 = 50;
+
+/// A Steam Controller handle. Frames are returned as plain dicts with the same
+/// shape as the desktop app's `controller-input` events.
+#[pyclass]
+struct Controller {
+    manager: SteamControllerManager,
+}
+
+#[pymethods]
+impl Controller {
+    #[new]
+    fn new() -> PyResult<Self> {
+        let manager = SteamControllerManager::new().map_err(PyRuntimeError::new_err)?;
+        Ok(Self { manager })
+    }
+
+    /// Info dict for an attached controller, or None
+    fn detect(&self, py: Python<'_>) -> PyResult<PyObject> {
+        match self.manager.detect() {
+            Some(info) => Ok(pythonize(py, &info)?.unbind()),
+            None => Ok(py.None()),
+        }
+    }
+
+    fn connect(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let info = py
+            .allow_threads(|| self.manager.connect())
+            .map_err(PyConnectionError::new_err)?;
+        Ok(pythonize(py, &info)?.unbind())
+    }
+
+    fn disconnect(&self, py: Python<'_>) {
+        py.allow_threads(|| self.manager.disconnect());
+    }
+
+    #[getter]
+    fn is_connected(&self) -> bool {
+        self.manager.is_connected()
+    }
+
+    /// Tune the reader (see ReadSettings in ctrlspace-core)
+    #[pyo3(signature = (timeout_ms=None, buffer_size=None, blocking=None))]
+    fn set_read_settings(
+        &self,
+        timeout_ms: Option<i32>,
+        buffer_size: Option<usize>,
+        blocking: Option<bool>,
+    ) -> PyResult<()> {
+        let current = self.manager.read_settings();
+        let settings = ReadSettings {
+            timeout_ms: timeout_ms.unwrap_or(current.timeout_ms),
+            buffer_size: buffer_size.unwrap_or(current.buffer_size),
+            strategy: match blocking {
+                Some(true) => ReadStrategy::Blocking,
+                Some(false) => ReadStrategy::NonBlocking,
+                None => current.strategy,
+            },
+        };
+        self.manager.set_read_settings(settings).map_err(PyValueError::new_err)
+    }
+
+    /// Read one parsed frame, or None if nothing arrived within the read timeout
+    /// or the packet wasn't an input report
+    fn read(&self, py: Python<'_>) -> PyResult<PyObject> {
+        if !self.manager.is_connected() {
+            return Err(PyConnectionError::new_err("Controller not connected"));
+        }
+        match py.allow_threads(|| self.manager.read_input()) {
+            Ok(Some(raw)) => match self
+                .manager
+                .parse_packet_at(&raw, clock::monotonic_us())
+                .map_err(PyValueError::new_err)?
+            {
+                Packet::InputFrame(input) => Ok(pythonize(py, &input)?.unbind()),
+                _ => Ok(py.None()),
+            },
+            Ok(None) => Ok(py.None()),
+            Err(e) => Err(PyConnectionError::new_err(e.to_string())),
+        }
+    }
+
+    /// Raw report bytes, or None on timeout
+    fn read_raw(&self, py: Python<'_>) -> PyResult<Option<Vec<u8>>> {
+        if !self.manager.is_connected() {
+            return Err(PyConnectionError::new_err("Controller not connected"));
+        }
+        py.allow_threads(|| self.manager.read_input())
+            .map_err(|e| PyConnectionError::new_err(e.to_string()))
+    }
+
+    /// Iterator of parsed frames that ends when the controller disconnects
+    fn stream(&self) -> InputIterator {
+        InputIterator {
+            manager: self.manager.clone(),
+        }
+    }
+}
+
+#[pyclass]
+struct InputIterator {
+    manager: SteamControllerManager,
+}
+
+#[pymethods]
+impl InputIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        loop {
+            if !self.manager.is_connected() {
+                return Ok(None);
+            }
+
+            let read = py.allow_threads(|| self.manager.read_input_blocking(STREAM_READ_TIMEOUT_MS));
+            if let Ok(Some(raw)) = read {
+                match self.manager.parse_packet_at(&raw, clock::monotonic_us()) {
+                    Ok(Packet::InputFrame(input)) => return Ok(Some(pythonize(py, &input)?.unbind())),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("❌ Skipping unparseable report: {}", e),
+                }
+            }
+
+            // Let KeyboardInterrupt through between reads
+            py.check_signals()?;
+        }
+    }
+}
+
+#[pymodule]
+fn ctrlspace(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Controller>()?;
+    m.add_class::<InputIterator>()?;
+    Ok(())
+}
@@ -7,18 +7,206 @@ mod src_tauri;
 
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            src_tauri::commands::init(app.handle());
+            if let Err(e) = src_tauri::settings::init() {
+                eprintln!("❌ Using default settings: {}", e);
+            }
+            src_tauri::output::set_enabled(src_tauri::settings::get().output_injection);
+            src_tauri::commands::activate_startup_profile();
+            src_tauri::commands::activate_desktop_fallback();
+            let unclean_exit = match src_tauri::recovery::start() {
+                Ok(previous) => previous,
+                Err(e) => {
+                    eprintln!("❌ Crash recovery unavailable: {}", e);
+                    None
+                }
+            };
+            if let Err(e) = src_tauri::commands::reload_plugins() {
+                eprintln!("❌ Plugins unavailable: {}", e);
+            }
+            if let Err(e) = src_tauri::commands::start_configured_hook() {
+                eprintln!("❌ {}", e);
+            }
+            if let Err(e) = src_tauri::hotkeys::init() {
+                eprintln!("❌ Mapping toggle hotkey unavailable: {}", e);
+            }
+            if let Err(e) = src_tauri::hotplug::start() {
+                eprintln!("❌ Device-change notifications unavailable: {}", e);
+            }
+            src_tauri::commands::start_watchdog();
+
+            // Optional integrations - the app works fine without them
+            if let Err(e) = src_tauri::steam_game::start() {
+                eprintln!("❌ Steam game detection unavailable: {}", e);
+            }
+            #[cfg(target_os = "linux")]
+            if let Err(e) = src_tauri::dbus::start() {
+                eprintln!("❌ D-Bus service unavailable: {}", e);
+            }
+            #[cfg(windows)]
+            if let Err(e) = src_tauri::pipe_server::start() {
+                eprintln!("❌ Named pipe server unavailable: {}", e);
+            }
+
+            // Reconnecting can take a moment; don't hold up the window
+            if let Some(previous) = unclean_exit {
+                std::thread::spawn(move || src_tauri::recovery::recover(previous));
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             src_tauri::commands::greet,
             src_tauri::commands::list_devices,
             src_tauri::commands::list_steam_controller_interfaces,
+            src_tauri::commands::get_report_descriptors,
             src_tauri::commands::ping,
             src_tauri::commands::detect_steam_controller,
+            src_tauri::commands::get_controller_health,
             src_tauri::commands::connect_steam_controller,
             src_tauri::commands::disconnect_steam_controller,
             src_tauri::commands::is_steam_controller_connected,
+            src_tauri::commands::list_known_devices,
+            src_tauri::commands::get_device_settings,
+            src_tauri::commands::set_device_settings,
+            src_tauri::commands::get_device_nickname,
+            src_tauri::commands::set_device_nickname,
+            src_tauri::commands::forget_device,
             src_tauri::commands::read_controller_input,
-            src_tauri::commands::read_raw_input_debug
+            src_tauri::commands::read_raw_input_debug,
+            src_tauri::commands::stress_test_controller,
+            src_tauri::commands::export_tester_report,
+            src_tauri::commands::test_haptics,
+            src_tauri::commands::send_haptic_pulse,
+            src_tauri::commands::test_rumble,
+            src_tauri::commands::play_melody,
+            src_tauri::commands::play_tone,
+            src_tauri::commands::set_jingles,
+            src_tauri::commands::sample_curve,
+            src_tauri::commands::check_profile_conflicts,
+            src_tauri::commands::check_profile_script,
+            src_tauri::commands::reload_plugins,
+            src_tauri::commands::list_plugins,
+            src_tauri::commands::set_plugin_grants,
+            src_tauri::commands::start_hook_process,
+            src_tauri::commands::stop_hook_process,
+            src_tauri::commands::get_hook_process,
+            src_tauri::commands::get_running_steam_game,
+            src_tauri::commands::stream_raw_diff,
+            src_tauri::commands::stop_raw_diff_stream,
+            src_tauri::commands::get_app_settings,
+            src_tauri::commands::set_developer_mode,
+            src_tauri::commands::get_write_stats,
+            src_tauri::commands::clear_write_stats,
+            src_tauri::commands::get_haptic_intensity,
+            src_tauri::commands::set_haptic_intensity,
+            src_tauri::commands::get_max_haptic_rate,
+            src_tauri::commands::set_max_haptic_rate,
+            src_tauri::commands::set_virtual_pad_conflict_policy,
+            src_tauri::commands::get_xinput_diagnostics,
+            src_tauri::commands::start_virtual_gamepad,
+            src_tauri::commands::stop_virtual_gamepad,
+            src_tauri::commands::set_virtual_gamepad_mapping,
+            src_tauri::commands::get_virtual_gamepad_status,
+            src_tauri::commands::list_virtual_gamepad_mappings,
+            src_tauri::commands::set_output_injection,
+            src_tauri::commands::get_output_status,
+            src_tauri::commands::test_output_action,
+            src_tauri::commands::get_battery_status,
+            src_tauri::commands::get_battery_thresholds,
+            src_tauri::commands::set_battery_thresholds,
+            src_tauri::commands::get_input_event_config,
+            src_tauri::commands::set_input_event_config,
+            src_tauri::commands::send_feature_report,
+            src_tauri::commands::get_feature_report,
+            src_tauri::commands::debug_exec,
+            src_tauri::commands::capture_fixture,
+            src_tauri::commands::calibrate_gyro,
+            src_tauri::commands::start_session_recording,
+            src_tauri::commands::stop_session_recording,
+            src_tauri::commands::list_session_recordings,
+            src_tauri::commands::start_ghost_replay,
+            src_tauri::commands::stop_ghost_replay,
+            src_tauri::commands::start_learning,
+            src_tauri::commands::finish_learning,
+            src_tauri::commands::start_self_test,
+            src_tauri::commands::get_self_test_status,
+            src_tauri::commands::skip_self_test_step,
+            src_tauri::commands::finish_self_test,
+            src_tauri::commands::start_layout_wizard,
+            src_tauri::commands::get_layout_wizard_status,
+            src_tauri::commands::wizard_capture_start,
+            src_tauri::commands::wizard_capture_finish,
+            src_tauri::commands::wizard_skip_step,
+            src_tauri::commands::save_layout_wizard,
+            src_tauri::commands::get_read_settings,
+            src_tauri::commands::set_read_settings,
+            src_tauri::commands::get_report_rate,
+            src_tauri::commands::set_report_rate,
+            src_tauri::commands::request_high_rate_imu,
+            src_tauri::commands::set_imu_enabled,
+            src_tauri::commands::set_trackpad_mode,
+            src_tauri::commands::stream_controller_input,
+            src_tauri::commands::get_stick_drift,
+            src_tauri::commands::get_health_report,
+            src_tauri::commands::ack_controller_input,
+            src_tauri::commands::stop_controller_input_stream,
+            src_tauri::commands::start_input_stream,
+            src_tauri::commands::stop_input_stream,
+            src_tauri::commands::frontend_ready,
+            src_tauri::commands::list_paired_controllers,
+            src_tauri::commands::start_pairing,
+            src_tauri::commands::stop_pairing,
+            src_tauri::commands::list_steam_controllers,
+            src_tauri::commands::start_combined_mode,
+            src_tauri::commands::set_combine_config,
+            src_tauri::commands::stop_combined_mode,
+            src_tauri::commands::start_device_workers,
+            src_tauri::commands::stop_device_workers,
+            src_tauri::commands::get_worker_status,
+            src_tauri::commands::start_verification,
+            src_tauri::commands::stop_verification,
+            src_tauri::commands::list_presets,
+            src_tauri::commands::clone_preset,
+            src_tauri::commands::import_sc_profile,
+            src_tauri::commands::import_antimicrox_profile,
+            src_tauri::commands::export_antimicrox_profile,
+            src_tauri::commands::set_community_index_url,
+            src_tauri::commands::browse_community_profiles,
+            src_tauri::commands::search_community_profiles,
+            src_tauri::commands::preview_community_profile,
+            src_tauri::commands::install_community_profile,
+            src_tauri::commands::get_overlay_state,
+            src_tauri::commands::open_keyboard,
+            src_tauri::commands::close_keyboard,
+            src_tauri::commands::is_keyboard_open,
+            src_tauri::commands::is_mapping_enabled,
+            src_tauri::commands::set_mapping_enabled,
+            src_tauri::commands::is_dry_run,
+            src_tauri::commands::set_dry_run,
+            src_tauri::commands::toggle_mapping,
+            src_tauri::commands::get_mapping_toggle_bindings,
+            src_tauri::commands::set_toggle_hotkey,
+            src_tauri::commands::set_toggle_chord,
+            src_tauri::commands::get_active_profile,
+            src_tauri::commands::list_profiles,
+            src_tauri::commands::save_profile,
+            src_tauri::commands::set_deadzone,
+            src_tauri::commands::set_response_curve,
+            src_tauri::commands::load_profile,
+            src_tauri::commands::set_active_profile,
+            src_tauri::commands::get_exclusive_status,
+            src_tauri::commands::clear_active_profile,
+            src_tauri::commands::set_desktop_mode,
+            src_tauri::commands::get_profile_switch_config,
+            src_tauri::commands::set_profile_switch_config
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                src_tauri::exclusive::shutdown();
+                src_tauri::recovery::finish();
+            }
+        });
 }
\ No newline at end of file
@@ -1,68 +1,394 @@
 use serde::Serialize;
-use std::sync::{Arc, Mutex};
-use once_cell::sync::Lazy;
-use super::steam_controller::{SteamControllerManager, SteamControllerInfo};
-use super::input_parser::{parse_input_report, ControllerInput};
+use super::{exclusive, hotkeys, output, recovery, settings, steam_game, virtual_gamepad};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use once_cell::sync::{Lazy, OnceCell};
+use tauri::Manager;
+use ctrlspace_core::steam_controller::{InterfaceDescriptor, ReadSettings, SteamControllerManager, SteamControllerInfo, VALVE_VENDOR_ID};
+use super::settings::AppSettings;
+use ctrlspace_core::clock;
+use ctrlspace_core::input_parser::{ControllerInput, Packet};
+use ctrlspace_core::antimicrox::{export_amgp_file, import_amgp_file, AntimicroxImport};
+use ctrlspace_core::battery::{dying_battery_pattern, BatteryMonitor, BatteryStatus, BatteryThresholds, BATTERY_LOW_EVENT};
+use ctrlspace_core::registers::{ReportRateConfig, TrackpadMode};
+use ctrlspace_core::rumble::RumbleCommand;
+use ctrlspace_core::device_registry::{DeviceRegistry, KnownDevice, DEVICE_REGISTRY_FILE};
+use ctrlspace_core::device_settings::DeviceSettings;
+use ctrlspace_core::dongle::DEFAULT_PAIRING_SECS;
+use ctrlspace_core::fixtures::{capture_reports, to_hex, Capture, FIXTURES_DIR_NAME};
+use ctrlspace_core::calibration::{self, GyroCalibration, DEFAULT_GYRO_SAMPLES};
+use ctrlspace_core::combine::{CombineConfig, Combiner};
+use ctrlspace_core::community::{parse_index, validate_url, CommunityEntry, CommunityIndex, CommunityInstall, MAX_INDEX_BYTES, MAX_PROFILE_BYTES};
+use ctrlspace_core::conflicts::{detect_conflicts, MappingWarning, ReservedChord, PROFILE_CONFLICTS_EVENT};
+use ctrlspace_core::controller::{detect_first, ControllerHandle};
+use ctrlspace_core::cursor::CursorTracker;
+use ctrlspace_core::curve::Curve;
+use ctrlspace_core::daisywheel::Daisywheel;
+use ctrlspace_core::delta::{DeltaEncoder, CONTROLLER_INPUT_DELTA_EVENT, DEFAULT_KEYFRAME_INTERVAL};
+use ctrlspace_core::drift::STICK_DRIFT_EVENT;
+use ctrlspace_core::dry_run::{preview_binding, preview_cursor, preview_gesture, preview_gyro_cursor, preview_hook, preview_menu_selection, preview_plugin, preview_script, preview_spin, MAPPING_PREVIEW_EVENT};
+use ctrlspace_core::edge_spin::EdgeSpinDetector;
+use ctrlspace_core::enumeration::{DeviceFilter, HidDeviceEntry};
+use ctrlspace_core::error::ControllerError;
+use ctrlspace_core::exclusive::ExclusiveStatus;
+use ctrlspace_core::feedback::{binding_feedback, gesture_feedback};
+use ctrlspace_core::gamepad_api::{to_standard_gamepad, GAMEPAD_API_EVENT};
+use ctrlspace_core::gestures::{GestureDetector, Pad};
+use ctrlspace_core::gyro_mouse::GyroMouse;
+use ctrlspace_core::ghost::{list_recordings, recording_path, GhostReplay, GhostReplayEvent, Recording, RecordingInfo, SessionRecorder, GHOST_INPUT_EVENT, GHOST_REPLAY_EVENT, RECORDINGS_DIR_NAME};
+use ctrlspace_core::haptics::{validate_haptic_intensity, validate_max_haptic_rate, HapticPulse, HapticSide};
+use ctrlspace_core::health::{HealthReport, HealthStore, HealthTracker, HEALTH_FILE};
+use ctrlspace_core::hook_process::{HookCommand, HookConfig, HookMessage, HookProcess, HookReply, HOOK_ACTION_EVENT, HOOK_EXITED_EVENT};
+use ctrlspace_core::input_events::{InputEvent, InputEventConfig, InputEventDetector, INPUT_EVENTS_EVENT};
+use ctrlspace_core::input_stream::{
+    InputFrame, InputStream, StreamEvent, CONTROLLER_INPUT_EVENT, DEFAULT_MAX_IN_FLIGHT, INPUT_STREAM_ENDED_EVENT,
+};
+use ctrlspace_core::layers::{BindingEvent, LayerEngine, BINDING_ACTION_EVENT};
+use ctrlspace_core::layout::ReportLayout;
+use ctrlspace_core::learning::{LearnResult, LearningRun};
+use ctrlspace_core::mapping::TrackballTracker;
+use ctrlspace_core::melody::{Jingles, Tone};
+use ctrlspace_core::mirror::mirror_input;
+use ctrlspace_core::output::OutputStatus;
+use ctrlspace_core::overlay::{describe_bindings, OverlayBus, OverlayEvent};
+use ctrlspace_core::plugins::{load_plugins, Capability, Plugin, PluginEffects, PluginInfo, PluginOutput, PLUGINS_DIR_NAME, PLUGIN_OUTPUT_EVENT};
+use ctrlspace_core::profile_switch::{resolve_target, ProfileSwitchChord, ProfileSwitchConfig};
+use ctrlspace_core::profiles::{self, Action, ButtonId, Preset, Profile, PROFILES_DIR_NAME};
+use ctrlspace_core::raw_diff::{RawDiffStream, DEFAULT_IGNORED_BYTES, RAW_DIFF_EVENT};
+use ctrlspace_core::response::{apply_responses, AnalogResponse, AnalogSource, ResponseCurve};
+use ctrlspace_core::self_test::{SelfTest, SelfTestReport, SelfTestStatus, SELF_TEST_EVENT};
+use ctrlspace_core::slow_mode::OutputScaler;
+use ctrlspace_core::sc_import::{import_sccprofile_file, ScImport};
+use ctrlspace_core::scripting::{ScriptEffects, ScriptHost, SCRIPT_ERROR_EVENT, SCRIPT_OUTPUT_EVENT};
+use ctrlspace_core::virtual_gamepad::{GamepadMapping, VirtualGamepadStatus};
+use ctrlspace_core::xinput::{ConflictPolicy, XInputReport, XINPUT_SLOTS};
+use ctrlspace_core::write_stats::{WriteKind, WriteStats};
+use ctrlspace_core::wizard::{LayoutWizard, WizardControl, WizardStatus};
+use ctrlspace_core::steam_apps::{profile_for_app, SteamGame, STEAM_GAME_CHANGED_EVENT};
+use ctrlspace_core::stress::{run_stress_test, StressTestReport, MAX_STRESS_DURATION_SECS};
+use ctrlspace_core::tester_report::{capture_tester_report, TesterReport, DEFAULT_TESTER_CAPTURE_SECS, MAX_TESTER_CAPTURE_SECS, TESTER_REPORTS_DIR_NAME};
+use ctrlspace_core::verification::{VerificationConfig, VerificationSummary};
+use ctrlspace_core::debug_console::{parse_debug_script, run_debug_script, DebugCommand, DebugLine};
+use ctrlspace_core::touch_menu::{TouchMenuDetector, TouchMenuEvent, TOUCH_MENU_SELECTION_EVENT};
+use ctrlspace_core::timeout::{with_command_timeout, with_timeout, COMMAND_TIMEOUT_MS};
+use ctrlspace_core::watchdog::{StuckOperation, Watchdog, WatchdogConfig, CONTROLLER_RECOVERED_EVENT};
+use ctrlspace_core::workers::{device_worker, DeviceInput, DeviceMapper, RestartPolicy, Supervisor, WorkerStatus, DEVICE_INPUT_EVENT, WORKER_STATUS_EVENT};
+#[cfg(feature = "gilrs-verify")]
+use ctrlspace_core::verification::{Verifier, VERIFICATION_MISMATCH_EVENT};
+#[cfg(feature = "gilrs-verify")]
+use super::verify::ReferenceReader;
 
-#[derive(Serialize)]
-pub struct DeviceInfo {
-    pub vendor_id: u16,
-    pub product_id: u16,
-    pub product: String,
+// Global Steam Controller manager. Commands clone it out (see `current_manager`)
+// and never hold this lock across HID I/O, so the input path can always get in.
+static SC_MANAGER: Lazy<RwLock<Option<SteamControllerManager>>> = Lazy::new(|| RwLock::new(None));
+
+// Active push stream (replaces polling read_controller_input from the frontend)
+static INPUT_STREAM: Lazy<Mutex<Option<InputStream>>> = Lazy::new(|| Mutex::new(None));
+
+// Overlay hints for external tools (and our own UI via get_overlay_state)
+static OVERLAY_BUS: Lazy<OverlayBus> = Lazy::new(OverlayBus::new);
+
+// Out-of-process listeners (IPC clients) fed from the same stream as the window
+static INPUT_SUBSCRIBERS: Lazy<Mutex<Vec<mpsc::Sender<InputFrame>>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+// Currently active mapping profile
+static ACTIVE_PROFILE: Lazy<Mutex<Option<Profile>>> = Lazy::new(|| Mutex::new(None));
+
+// Reserved chord for switching profiles from the controller
+static PROFILE_SWITCH: Lazy<Mutex<ProfileSwitchChord>> =
+    Lazy::new(|| Mutex::new(ProfileSwitchChord::default()));
+
+// Every controller seen so far, loaded lazily from the app config dir
+static DEVICE_REGISTRY: Lazy<Mutex<Option<DeviceRegistry>>> = Lazy::new(|| Mutex::new(None));
+
+// Registry ID of the connected controller
+static CONNECTED_DEVICE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+// What `connect_steam_controller` returned, for frontends that (re)load later
+static CONTROLLER_INFO: Lazy<Mutex<Option<SteamControllerInfo>>> = Lazy::new(|| Mutex::new(None));
+
+// Where the window input stream sends frames. Swapped when a reloaded
+// frontend asks for the stream again, so the reader thread keeps running.
+static STREAM_TARGET: Lazy<Mutex<Option<StreamTarget>>> = Lazy::new(|| Mutex::new(None));
+
+struct StreamTarget {
+    window: tauri::Window,
+    encoder: Option<DeltaEncoder>,
+    gamepad_api: bool,
+}
+
+// Reverse-engineering diff stream, if running
+static RAW_DIFF_STREAM: Lazy<Mutex<Option<RawDiffStream>>> = Lazy::new(|| Mutex::new(None));
+
+// Button discovery in progress, if any
+static LEARNING: Lazy<Mutex<Option<LearningRun>>> = Lazy::new(|| Mutex::new(None));
+
+// Hardware self-test in progress, if any
+static SELF_TEST: Lazy<Mutex<Option<SelfTest>>> = Lazy::new(|| Mutex::new(None));
+
+// Mapping wizard in progress, if any (captures go through LEARNING)
+static LAYOUT_WIZARD: Lazy<Mutex<Option<LayoutWizard>>> = Lazy::new(|| Mutex::new(None));
+
+// On-screen keyboard, while open it turns pad input into typed keys
+static KEYBOARD: Lazy<Mutex<Option<Daisywheel>>> = Lazy::new(|| Mutex::new(None));
+
+// Tap / double-tap / tap-drag state for both pads
+static GESTURES: Lazy<Mutex<GestureDetector>> = Lazy::new(|| Mutex::new(GestureDetector::new()));
+
+// Combined mode: the second controller and its reader...
+static SECONDARY: Lazy<Mutex<Option<(SteamControllerManager, InputStream, String)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Per-device workers for the controllers beyond the primary one; restarts
+/// and failures go out as `worker-status` events
+static DEVICE_WORKERS: Lazy<Supervisor> = Lazy::new(|| {
+    let supervisor = Supervisor::new(RestartPolicy::default());
+    supervisor.spawn_monitor(|status| {
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(WORKER_STATUS_EVENT, status);
+        }
+    });
+    supervisor
+});
+
+/// A per-device worker's connection and mapping state
+type WorkerDevice = (SteamControllerManager, Arc<Mutex<DeviceMapper>>);
+
+/// Connections owned by the per-device workers, by device ID
+static WORKER_DEVICES: Lazy<Mutex<HashMap<String, WorkerDevice>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// ...and its latest frame merged into every primary frame (None when combined mode is off)
+static COMBINER: Lazy<Mutex<Option<Combiner>>> = Lazy::new(|| Mutex::new(None));
+
+// Touch origins / sub-pixel state for pads in mouse mode
+static CURSOR: Lazy<Mutex<CursorTracker>> = Lazy::new(|| Mutex::new(CursorTracker::new()));
+
+// Frame clock / sub-pixel state for gyro aiming
+static GYRO_MOUSE: Lazy<Mutex<GyroMouse>> = Lazy::new(|| Mutex::new(GyroMouse::new()));
+
+// Spin momentum for pads in trackball mode
+static TRACKBALL: Lazy<Mutex<TrackballTracker>> = Lazy::new(|| Mutex::new(TrackballTracker::new()));
+
+// Slow-mode scaling of cursor output
+static OUTPUT_SCALER: Lazy<Mutex<OutputScaler>> = Lazy::new(|| Mutex::new(OutputScaler::new()));
+
+// Button press edges for per-binding haptic feedback
+
+// Previous frame for pressed/held/released events
+static INPUT_EVENTS: Lazy<Mutex<InputEventDetector>> =
+    Lazy::new(|| Mutex::new(InputEventDetector::new(settings::get().input_events)));
+
+// Rim-spin tracking for pads in edge-spin mode
+static EDGE_SPIN: Lazy<Mutex<EdgeSpinDetector>> = Lazy::new(|| Mutex::new(EdgeSpinDetector::new()));
+
+// Open menus for pads in touch-menu mode
+static TOUCH_MENUS: Lazy<Mutex<TouchMenuDetector>> = Lazy::new(|| Mutex::new(TouchMenuDetector::new()));
+
+// Active binding layer, held buttons and key repeat
+static LAYERS: Lazy<Mutex<LayerEngine>> = Lazy::new(|| Mutex::new(LayerEngine::new()));
+
+// Compiled script of the active profile, if it has one
+static SCRIPT: Lazy<Mutex<Option<ScriptHost>>> = Lazy::new(|| Mutex::new(None));
+
+// WASM plugins loaded from the app config dir
+static PLUGINS: Lazy<Mutex<Vec<Plugin>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// External hook process, if one is running...
+static HOOK_PROCESS: Lazy<Mutex<Option<HookProcess>>> = Lazy::new(|| Mutex::new(None));
+
+// ...and which start it is, so a stopped process's exit isn't reported
+static HOOK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Profile activated for the running Steam game, so it can be dropped when the game exits
+static STEAM_GAME_PROFILE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+// Community repository index, with the URL it was fetched from
+static COMMUNITY_INDEX: Lazy<Mutex<Option<(String, CommunityIndex)>>> = Lazy::new(|| Mutex::new(None));
+
+// Wear tracking for the connected controller, merged into `HEALTH_FILE` now and then
+static HEALTH_TRACKER: Lazy<Mutex<Option<HealthTracker>>> = Lazy::new(|| Mutex::new(None));
+
+// Live input being recorded for ghost replay
+static SESSION_RECORDER: Lazy<Mutex<Option<SessionRecorder>>> = Lazy::new(|| Mutex::new(None));
+
+// Recording being replayed through the mapping engine
+static GHOST_REPLAY: Lazy<Mutex<Option<GhostReplay>>> = Lazy::new(|| Mutex::new(None));
+
+// Dry run: mapping output is described in previews instead of being sent
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+// Low-battery warning state for the wireless controller
+static BATTERY_MONITOR: Lazy<Mutex<BatteryMonitor>> = Lazy::new(|| Mutex::new(BatteryMonitor::new()));
+
+// Parser verification against the OS gamepad driver, while running
+#[cfg(feature = "gilrs-verify")]
+static VERIFICATION: Lazy<Mutex<Option<(ReferenceReader, Verifier)>>> = Lazy::new(|| Mutex::new(None));
+
+// Watches for HID calls that never return
+static WATCHDOG: Lazy<Mutex<Option<Watchdog>>> = Lazy::new(|| Mutex::new(None));
+
+// Needed to emit events and resolve paths from background threads
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
+/// Emitted to all windows with the newly active profile
+pub const PROFILE_CHANGED_EVENT: &str = "profile-changed";
+
+/// Emitted to all windows for each pad gesture, with the active profile's binding
+pub const PAD_GESTURE_EVENT: &str = "pad-gesture";
+
+/// Emitted to all windows with cursor movement (px) from pads in mouse mode
+pub const PAD_CURSOR_EVENT: &str = "pad-cursor";
+
+/// Emitted to all windows with cursor movement (px) from gyro aiming
+pub const GYRO_CURSOR_EVENT: &str = "gyro-cursor";
+
+/// Emitted to all windows with the steps from each rim spin on an edge-spin pad
+pub const PAD_SPIN_EVENT: &str = "pad-spin";
+
+/// Emitted to all windows with each key action typed on the on-screen keyboard
+pub const KEYBOARD_KEY_EVENT: &str = "keyboard-key";
+
+/// Emitted to all windows with the detected controller (or null) after HID devices come or go
+pub const DEVICES_CHANGED_EVENT: &str = "devices-changed";
+
+/// Sent to a (re)loaded frontend by `frontend_ready` with a `SessionSnapshot`
+pub const SESSION_SNAPSHOT_EVENT: &str = "session-snapshot";
+
+/// Writes returned by `get_write_stats` when the caller doesn't say
+const DEFAULT_RECENT_WRITES: usize = 20;
+
+/// Community repository downloads give up after this long
+const COMMUNITY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Points returned by `sample_curve` by default, and at most
+const DEFAULT_CURVE_SAMPLES: usize = 64;
+const MAX_CURVE_SAMPLES: usize = 1024;
+
+/// Remember the app handle for work that happens outside of a command
+pub fn init(app: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app);
 }
 
-// Global Steam Controller manager
-static SC_MANAGER: Lazy<Arc<Mutex<Option<SteamControllerManager>>>> =
-    Lazy::new(|| Arc::new(Mutex::new(None)));
+pub fn app_handle() -> Option<&'static tauri::AppHandle> {
+    APP_HANDLE.get()
+}
 
 #[tauri::command]
 pub fn greet(name: &str) -> String {
     format!("Hello, {}!", name)
 }
 
-#[derive(Serialize)]
-pub struct DetailedDeviceInfo {
-    pub vendor_id: u16,
-    pub product_id: u16,
-    pub product: String,
-    pub interface_number: i32,
-    pub usage_page: u16,
-    pub usage: u16,
-}
-
-#[tauri::command]
-pub fn list_devices() -> Vec<DeviceInfo> {
-    let mut out = vec![];
-    if let Ok(api) = hidapi::HidApi::new() {
-        for d in api.device_list() {
-            out.push(DeviceInfo {
-                vendor_id: d.vendor_id(),
-                product_id: d.product_id(),
-                product: d.product_string().unwrap_or("Unknown").to_string(),
-            });
-        }
+// A manager with the app-wide haptic intensity applied
+fn new_manager() -> Result<SteamControllerManager, String> {
+    let manager = SteamControllerManager::new()?;
+    let settings = settings::get();
+    manager.set_haptic_intensity(settings.haptic_intensity)?;
+    manager.set_max_haptic_rate(settings.max_haptic_rate)?;
+    Ok(manager)
+}
+
+// The manager, created on first use. All HID enumeration goes through its API
+// handle so there is only one live `HidApi` in the process.
+fn shared_manager() -> Result<SteamControllerManager, String> {
+    if let Some(manager) = SC_MANAGER.read().unwrap().clone() {
+        return Ok(manager);
+    }
+    let mut manager = SC_MANAGER.write().unwrap();
+    if manager.is_none() {
+        // A hung HID init must not keep the lock (and every command) forever
+        *manager = Some(with_command_timeout("HID init", new_manager)?);
     }
-    out
+    Ok(manager.as_ref().unwrap().clone())
 }
 
-#[tauri::command]
-pub fn list_steam_controller_interfaces() -> Vec<DetailedDeviceInfo> {
-    let mut out = vec![];
-    if let Ok(api) = hidapi::HidApi::new() {
-        for d in api.device_list() {
-            if d.vendor_id() == 0x28de {  // Valve
-                out.push(DetailedDeviceInfo {
-                    vendor_id: d.vendor_id(),
-                    product_id: d.product_id(),
-                    product: d.product_string().unwrap_or("Unknown").to_string(),
-                    interface_number: d.interface_number(),
-                    usage_page: d.usage_page(),
-                    usage: d.usage(),
-                });
-            }
+// The manager if it exists; the lock is released before the caller does any I/O
+fn current_manager() -> Result<SteamControllerManager, String> {
+    SC_MANAGER
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "Steam Controller manager not initialized".to_string())
+}
+
+fn connected_manager() -> Result<SteamControllerManager, String> {
+    let manager = current_manager()?;
+    if manager.is_connected() {
+        Ok(manager)
+    } else {
+        Err("Controller not connected".to_string())
+    }
+}
+
+// A controller as a device handle, for reader threads that don't care which
+// driver is behind it: the one `device` names (registry device ID or HID
+// path) among the connected one, a combined-mode secondary and the
+// per-device workers', or the connected one when `device` is `None`
+fn controller_for(device: Option<&str>) -> Result<ControllerHandle, String> {
+    let device = match device {
+        Some(device) => device,
+        None => return Ok(connected_manager()?.handle()),
+    };
+    let primary = CONTROLLER_INFO.lock().unwrap().clone();
+    if primary.map_or(false, |info| info.path == device || info.device_id.as_deref() == Some(device)) {
+        return Ok(connected_manager()?.handle());
+    }
+    if let Some((manager, _, path)) = SECONDARY.lock().unwrap().as_ref() {
+        if path == device {
+            return Ok(manager.handle());
         }
     }
-    out
+    if let Some((manager, _)) = WORKER_DEVICES.lock().unwrap().get(device) {
+        return Ok(manager.handle());
+    }
+    Err(format!("No connected controller '{}'", device))
+}
+
+// Commands that read reports themselves (captures, calibration, the debug
+// console's `read`) would split them with a reader thread on the connected
+// controller, each side seeing only part of the stream
+fn require_no_reader() -> Result<(), String> {
+    let streaming = INPUT_STREAM.lock().unwrap().as_ref().map_or(false, |s| s.is_running());
+    if streaming || RAW_DIFF_STREAM.lock().unwrap().is_some() || LEARNING.lock().unwrap().is_some() {
+        return Err("Stop the input stream, raw diff or learning run first".to_string());
+    }
+    Ok(())
+}
+
+// Every controller driver, asked in this order when detecting. A new device
+// family gets its `Controller` implementation listed here.
+fn controller_drivers() -> Result<Vec<ControllerHandle>, String> {
+    Ok(vec![shared_manager()?.handle()])
+}
+
+/// HID interfaces matching `filter`. The device list is cached; pass
+/// `refresh: true` to re-enumerate (e.g. after plugging something in).
+#[tauri::command]
+pub fn list_devices(filter: Option<DeviceFilter>, refresh: Option<bool>) -> Result<Vec<HidDeviceEntry>, String> {
+    let manager = shared_manager()?;
+    with_command_timeout("Device listing", move || {
+        manager.list_devices(&filter.unwrap_or_default(), refresh.unwrap_or(false))
+    })
+}
+
+/// Every Valve (0x28de) HID interface, freshly enumerated
+#[tauri::command]
+pub fn list_steam_controller_interfaces() -> Result<Vec<HidDeviceEntry>, String> {
+    let filter = DeviceFilter {
+        vendor_id: Some(VALVE_VENDOR_ID),
+        ..Default::default()
+    };
+    let manager = shared_manager()?;
+    with_command_timeout("Device listing", move || manager.list_devices(&filter, true))
+}
+
+/// Raw report descriptor of every Steam Controller interface, where the OS allows it
+#[tauri::command]
+pub fn get_report_descriptors() -> Result<Vec<InterfaceDescriptor>, String> {
+    let manager = shared_manager()?;
+    with_command_timeout("Report descriptors", move || manager.report_descriptors())
 }
 
 #[tauri::command]
@@ -72,84 +398,2636 @@ pub fn ping() -> &'static str {
 
 // Steam Controller Commands
 
+/// Called by the hotplug watcher once per burst of device notifications.
+/// Detection refreshes the shared device list, so this is the only re-enumeration.
+pub fn on_devices_changed() {
+    let info = detect_steam_controller();
+    match &info {
+        Some(info) => println!("🔌 Devices changed: {} ({})", info.product_name, info.connection_type),
+        None => println!("🔌 Devices changed: no Steam Controller attached"),
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(DEVICES_CHANGED_EVENT, &info);
+    }
+}
+
+/// Payload of `CONTROLLER_RECOVERED_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct ControllerRecovery {
+    pub stuck: StuckOperation,
+    /// False if hidapi couldn't be reinitialized; the app keeps running but
+    /// sees no devices until it can
+    pub hidapi_reinitialized: bool,
+}
+
+/// Start the thread that watches for HID calls that never return
+pub fn start_watchdog() {
+    let watchdog = Watchdog::spawn(
+        WatchdogConfig::default(),
+        |threshold| SC_MANAGER.read().unwrap().as_ref()?.stuck_operation(threshold),
+        on_stuck_operation,
+    );
+    *WATCHDOG.lock().unwrap() = Some(watchdog);
+    println!("✅ HID watchdog running");
+}
+
+// Abandon the hung handle and its reader threads rather than waiting on them
+fn on_stuck_operation(stuck: StuckOperation) {
+    eprintln!("❌ HID {:?} stuck for {}ms - abandoning the device", stuck.op, stuck.stuck_ms);
+    let manager = match current_manager() {
+        Ok(m) => m,
+        Err(_) => return,
+    };
+
+    // Their threads may be blocked inside the hung call; don't join them
+    if let Some(stream) = INPUT_STREAM.lock().unwrap().take() {
+        stream.abandon();
+    }
+    if let Some(stream) = RAW_DIFF_STREAM.lock().unwrap().take() {
+        stream.abandon();
+    }
+    *CONNECTED_DEVICE.lock().unwrap() = None;
+    *CONTROLLER_INFO.lock().unwrap() = None;
+    end_health_session(&manager);
+    exclusive::set_device(None);
+    virtual_gamepad::neutral();
+    output::release_all();
+    *BATTERY_MONITOR.lock().unwrap() = BatteryMonitor::new();
+    INPUT_EVENTS.lock().unwrap().reset();
+    recovery::update(|state| state.device_serial = None);
+
+    let hidapi_reinitialized = match manager.recover() {
+        Ok(()) => {
+            println!("🔧 HID API reinitialized");
+            true
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            false
+        }
+    };
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(CONTROLLER_RECOVERED_EVENT, ControllerRecovery { stuck, hidapi_reinitialized });
+    }
+    on_devices_changed();
+}
+
+/// Whether the connection is usable: false after a stuck HID call was abandoned,
+/// until the controller is connected again
+#[tauri::command]
+pub fn get_controller_health() -> bool {
+    current_manager().map(|m| m.is_healthy()).unwrap_or(true)
+}
+
 #[tauri::command]
 pub fn detect_steam_controller() -> Option<SteamControllerInfo> {
-    // Initialize manager if not already done
-    {
-        let mut manager = SC_MANAGER.lock().unwrap();
-        if manager.is_none() {
-            *manager = SteamControllerManager::new().ok();
+    let drivers = controller_drivers().ok()?;
+    let detected = with_command_timeout("Detect", move || Ok(detect_first(&drivers))).ok().flatten();
+    let (driver, mut info) = detected?;
+    println!("🔍 Detected {} via the {} driver", info.product_name, driver.kind());
+    if let Ok(Some(device)) = with_device_registry(|registry| Ok(registry.find(&info).cloned())) {
+        info.device_id = Some(device.id);
+        info.nickname = device.settings.nickname;
+    }
+    Some(info)
+}
+
+/// Connect to the first controller, or to the one in dongle `slot` (see
+/// `list_paired_controllers`). While one is connected this returns it as is,
+/// so a reloaded frontend doesn't force a reconnect; asking for another slot
+/// switches to it.
+#[tauri::command]
+pub fn connect_steam_controller(slot: Option<u8>) -> Result<SteamControllerInfo, String> {
+    let manager = shared_manager()?;
+    if manager.is_connected() {
+        if let Some(info) = CONTROLLER_INFO.lock().unwrap().clone() {
+            if slot.map_or(true, |slot| info.slot == Some(slot)) {
+                return Ok(info);
+            }
         }
     }
+    let opening = manager.clone();
+    let mut info = with_command_timeout("Connect", move || match slot {
+        Some(slot) => opening.connect_slot(slot),
+        None => opening.connect(),
+    })?;
+    if let Some(slot) = slot {
+        println!("🎮 Connected to dongle slot {}", slot);
+    }
 
-    // Detect controller
-    let manager = SC_MANAGER.lock().unwrap();
-    manager.as_ref().and_then(|m| m.detect())
+    end_health_session(&manager);
+    match register_device(&info) {
+        Ok((id, settings)) => {
+            apply_device_settings(&manager, &settings);
+            *HEALTH_TRACKER.lock().unwrap() = Some(HealthTracker::new(&id, manager.write_totals(WriteKind::Haptics)));
+            *CONNECTED_DEVICE.lock().unwrap() = Some(id.clone());
+            info.device_id = Some(id);
+            info.nickname = settings.nickname;
+        }
+        Err(e) => eprintln!("❌ Device registry unavailable: {}", e),
+    }
+    exclusive::set_device(Some(info.path.clone()));
+    recovery::update(|state| state.device_serial = Some(info.serial.clone()));
+    *CONTROLLER_INFO.lock().unwrap() = Some(info.clone());
+    Ok(info)
 }
 
-#[tauri::command]
-pub fn connect_steam_controller() -> Result<SteamControllerInfo, String> {
-    // Initialize manager if not already done
-    {
-        let mut manager = SC_MANAGER.lock().unwrap();
-        if manager.is_none() {
-            *manager = SteamControllerManager::new().ok();
+// Calibration and haptics go to the driver; the preferred profile becomes active
+fn apply_device_settings(manager: &SteamControllerManager, settings: &DeviceSettings) {
+    manager.apply_device_settings(settings);
+
+    if let Some(name) = &settings.preferred_profile {
+        if let Err(e) = activate_saved_profile(name) {
+            eprintln!("❌ Could not activate preferred profile: {}", e);
         }
     }
+}
+
+/// Activate a saved profile (or the bundled desktop profile) by name
+pub fn activate_saved_profile(name: &str) -> Result<(), String> {
+    let app = APP_HANDLE.get().ok_or_else(|| "App not initialized".to_string())?;
+    let profile = profiles::load_profiles(&profiles_dir(app)?)?
+        .into_iter()
+        .chain(profiles::desktop_profile())
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Profile '{}' not found", name))?;
+    activate_profile(profile);
+    Ok(())
+}
+
+/// Turn mouse/keyboard emulation back on (crash recovery)
+pub fn restore_lizard_mode() -> Result<(), String> {
+    let manager = connected_manager()?;
+    manager.enable_lizard_mode()?;
+    recovery::update(|state| state.lizard_mode_disabled = !manager.lizard_mode());
+    Ok(())
+}
+
+fn device_registry_path() -> Result<PathBuf, String> {
+    APP_HANDLE
+        .get()
+        .and_then(|app| app.path_resolver().app_config_dir())
+        .map(|dir| dir.join(DEVICE_REGISTRY_FILE))
+        .ok_or_else(|| "Could not resolve app config directory".to_string())
+}
+
+// Run `f` against the registry, loading it from disk on first use
+fn with_device_registry<T>(f: impl FnOnce(&mut DeviceRegistry) -> Result<T, String>) -> Result<T, String> {
+    let mut registry = DEVICE_REGISTRY.lock().unwrap();
+    if registry.is_none() {
+        *registry = Some(DeviceRegistry::load(&device_registry_path()?)?);
+    }
+    f(registry.as_mut().unwrap())
+}
+
+// Give a connected controller its stable ID and persist the registry
+fn register_device(info: &SteamControllerInfo) -> Result<(String, DeviceSettings), String> {
+    with_device_registry(|registry| {
+        let id = registry.identify(info);
+        registry.save(&device_registry_path()?)?;
+        let device = registry.get(&id).cloned();
+        if let Some(device) = &device {
+            println!("🎮 Connected: {} [{}]", device.display_name(), id);
+        }
+        Ok((id, device.map(|d| d.settings).unwrap_or_default()))
+    })
+}
+
+/// Every controller seen so far, with its stable ID
+#[tauri::command]
+pub fn list_known_devices() -> Result<Vec<KnownDevice>, String> {
+    with_device_registry(|registry| Ok(registry.devices().to_vec()))
+}
+
+#[tauri::command]
+pub fn get_device_settings(device_id: String) -> Result<DeviceSettings, String> {
+    with_device_registry(|registry| {
+        registry
+            .get(&device_id)
+            .map(|d| d.settings.clone())
+            .ok_or_else(|| format!("Unknown device '{}'", device_id))
+    })
+}
+
+/// Store settings for one controller; applied immediately if it is connected
+#[tauri::command]
+pub fn set_device_settings(device_id: String, settings: DeviceSettings) -> Result<(), String> {
+    with_device_registry(|registry| {
+        registry.set_settings(&device_id, settings.clone())?;
+        registry.save(&device_registry_path()?)
+    })?;
 
-    // Connect to controller
-    let manager = SC_MANAGER.lock().unwrap();
-    match manager.as_ref() {
-        Some(m) => m.connect(),
-        None => Err("Failed to initialize Steam Controller manager".to_string()),
+    if CONNECTED_DEVICE.lock().unwrap().as_deref() == Some(device_id.as_str()) {
+        if let Ok(m) = current_manager() {
+            m.apply_device_settings(&settings);
+        }
     }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_device_nickname(device_id: String) -> Result<Option<String>, String> {
+    with_device_registry(|registry| {
+        registry
+            .get(&device_id)
+            .map(|d| d.settings.nickname.clone())
+            .ok_or_else(|| format!("Unknown device '{}'", device_id))
+    })
+}
+
+/// Friendly name shown instead of the serial ("Couch SC"); `None` clears it
+#[tauri::command]
+pub fn set_device_nickname(device_id: String, nickname: Option<String>) -> Result<(), String> {
+    with_device_registry(|registry| {
+        registry.set_nickname(&device_id, nickname)?;
+        registry.save(&device_registry_path()?)
+    })
+}
+
+/// Drop a controller from the registry; it gets a new ID if seen again
+#[tauri::command]
+pub fn forget_device(device_id: String) -> Result<bool, String> {
+    with_device_registry(|registry| {
+        let removed = registry.remove(&device_id);
+        if removed {
+            registry.save(&device_registry_path()?)?;
+        }
+        Ok(removed)
+    })
 }
 
 #[tauri::command]
 pub fn disconnect_steam_controller() -> bool {
-    let manager = SC_MANAGER.lock().unwrap();
-    if let Some(m) = manager.as_ref() {
-        m.disconnect();
-        true
-    } else {
-        false
+    if let Some(stream) = INPUT_STREAM.lock().unwrap().take() {
+        stream.stop();
+    }
+    if let Some(stream) = RAW_DIFF_STREAM.lock().unwrap().take() {
+        stream.stop();
+    }
+    stop_combined_mode();
+
+    *CONNECTED_DEVICE.lock().unwrap() = None;
+    *CONTROLLER_INFO.lock().unwrap() = None;
+    exclusive::set_device(None);
+    virtual_gamepad::neutral();
+    output::release_all();
+    *BATTERY_MONITOR.lock().unwrap() = BatteryMonitor::new();
+    INPUT_EVENTS.lock().unwrap().reset();
+
+    match current_manager() {
+        Ok(m) => {
+            end_health_session(&m);
+            m.disconnect();
+            recovery::update(|state| {
+                state.device_serial = None;
+                state.lizard_mode_disabled = !m.lizard_mode();
+            });
+            true
+        }
+        Err(_) => false,
     }
 }
 
 #[tauri::command]
 pub fn is_steam_controller_connected() -> bool {
-    let manager = SC_MANAGER.lock().unwrap();
-    manager.as_ref().map(|m| m.is_connected()).unwrap_or(false)
+    current_manager().map(|m| m.is_connected()).unwrap_or(false)
 }
 
+/// Read and parse one report; `None` when nothing arrived within the read
+/// timeout or the packet wasn't an input report (e.g. a battery status)
 #[tauri::command]
-pub fn read_controller_input() -> Result<ControllerInput, String> {
-    let manager = SC_MANAGER.lock().unwrap();
+pub fn read_controller_input() -> Result<Option<ControllerInput>, ControllerError> {
+    let m = SC_MANAGER.read().unwrap().clone().ok_or(ControllerError::NotInitialized)?;
 
-    match manager.as_ref() {
-        Some(m) => {
-            let raw_data = m.read_input()?;
-            parse_input_report(&raw_data)
-        }
-        None => Err("Steam Controller manager not initialized".to_string()),
+    match read_with_timeout(&m)? {
+        Some(raw_data) => match m.parse_packet_at(&raw_data, clock::monotonic_us()).map_err(ControllerError::Parse)? {
+            Packet::InputFrame(input) => Ok(Some(input)),
+            _ => Ok(None),
+        },
+        None => Ok(None),
     }
 }
 
+/// Hex dump of one raw report; `None` when nothing arrived within the read timeout
 #[tauri::command]
-pub fn read_raw_input_debug() -> Result<String, String> {
-    let manager = SC_MANAGER.lock().unwrap();
+pub fn read_raw_input_debug() -> Result<Option<String>, ControllerError> {
+    let m = SC_MANAGER.read().unwrap().clone().ok_or(ControllerError::NotInitialized)?;
 
-    match manager.as_ref() {
-        Some(m) => {
-            match m.read_input() {
-                Ok(data) => {
-                    // Convert to hex string for debugging
-                    let hex: Vec<String> = data.iter().map(|b| format!("{:02x}", b)).collect();
-                    Ok(format!("Size: {} bytes\nHex: {}", data.len(), hex.join(" ")))
-                }
-                Err(e) => Err(e),
-            }
+    Ok(read_with_timeout(&m)?.map(|data| {
+        // Convert to hex string for debugging
+        let hex: Vec<String> = data.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("Size: {} bytes\nHex: {}", data.len(), hex.join(" "))
+    }))
+}
+
+// One read on a helper thread, so a read stuck behind a hung HID call times out
+fn read_with_timeout(m: &SteamControllerManager) -> Result<Option<Vec<u8>>, ControllerError> {
+    let reader = m.clone();
+    let timeout = Duration::from_millis(COMMAND_TIMEOUT_MS);
+    match with_timeout("Read", timeout, move || Ok(reader.read_input())) {
+        Ok(result) => result,
+        Err(e) => Err(ControllerError::Timeout(e)),
+    }
+}
+
+/// Emit `raw-report-diff` events listing only the bytes that changed since the
+/// previous report. `ignore` defaults to the sequence/timestamp bytes; `device`
+/// (device ID or path) picks a controller other than the connected one.
+#[tauri::command]
+pub fn stream_raw_diff(window: tauri::Window, ignore: Option<Vec<usize>>, device: Option<String>) -> Result<(), String> {
+    let controller = controller_for(device.as_deref())?;
+
+    let mut stream = RAW_DIFF_STREAM.lock().unwrap();
+    if let Some(old) = stream.take() {
+        old.stop();
+    }
+    *stream = Some(RawDiffStream::spawn(
+        controller,
+        ignore.unwrap_or_else(|| DEFAULT_IGNORED_BYTES.to_vec()),
+        move |diff| window.emit(RAW_DIFF_EVENT, diff).is_ok(),
+    ));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_raw_diff_stream() -> bool {
+    match RAW_DIFF_STREAM.lock().unwrap().take() {
+        Some(stream) => {
+            stream.stop();
+            true
         }
-        None => Err("Steam Controller manager not initialized".to_string()),
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn get_app_settings() -> AppSettings {
+    settings::get()
+}
+
+/// Developer mode unlocks the raw feature-report console
+#[tauri::command]
+pub fn set_developer_mode(enabled: bool) -> Result<AppSettings, String> {
+    settings::update(|s| s.developer_mode = enabled)
+}
+
+/// Latest battery report from the connected wireless controller. `None`
+/// until one arrives (they come with the input stream) and for wired
+/// controllers, which don't send them.
+#[tauri::command]
+pub fn get_battery_status() -> Option<BatteryStatus> {
+    BATTERY_MONITOR.lock().unwrap().last_status()
+}
+
+#[tauri::command]
+pub fn get_battery_thresholds() -> BatteryThresholds {
+    settings::get().battery
+}
+
+/// Change when `battery-low` fires; takes effect on the next status report
+#[tauri::command]
+pub fn set_battery_thresholds(thresholds: BatteryThresholds) -> Result<BatteryThresholds, String> {
+    thresholds.validate()?;
+    settings::update(|s| s.battery = thresholds).map(|s| s.battery)
+}
+
+#[tauri::command]
+pub fn get_input_event_config() -> InputEventConfig {
+    settings::get().input_events
+}
+
+/// Change the hold time and trigger threshold behind `input-events`; saved
+/// and used from the next frame
+#[tauri::command]
+pub fn set_input_event_config(config: InputEventConfig) -> Result<InputEventConfig, String> {
+    config.validate()?;
+    INPUT_EVENTS.lock().unwrap().set_config(config);
+    settings::update(|s| s.input_events = config).map(|s| s.input_events)
+}
+
+#[tauri::command]
+pub fn get_haptic_intensity() -> f32 {
+    settings::get().haptic_intensity
+}
+
+/// Scale all pad haptics (detents, feedback, rumble, tests) by `intensity`
+/// (0.0-1.0); saved and applied to the connected controller right away
+#[tauri::command]
+pub fn set_haptic_intensity(intensity: f32) -> Result<f32, String> {
+    validate_haptic_intensity(intensity)?;
+    if let Ok(manager) = current_manager() {
+        manager.set_haptic_intensity(intensity)?;
+    }
+    settings::update(|s| s.haptic_intensity = intensity).map(|s| s.haptic_intensity)
+}
+
+#[tauri::command]
+pub fn get_max_haptic_rate() -> u32 {
+    settings::get().max_haptic_rate
+}
+
+/// Cap pad haptic commands at `rate` pulses per second per actuator (0 for no
+/// cap); faster pulses are folded into the next one. Saved and applied right away.
+#[tauri::command]
+pub fn set_max_haptic_rate(rate: u32) -> Result<u32, String> {
+    validate_max_haptic_rate(rate)?;
+    if let Ok(manager) = current_manager() {
+        manager.set_max_haptic_rate(rate)?;
+    }
+    settings::update(|s| s.max_haptic_rate = rate).map(|s| s.max_haptic_rate)
+}
+
+/// Whether a virtual X360 pad may be created while Steam's virtual pad is present
+#[tauri::command]
+pub fn set_virtual_pad_conflict_policy(policy: ConflictPolicy) -> Result<AppSettings, String> {
+    settings::update(|s| s.virtual_pad_conflict = policy)
+}
+
+/// XInput slot occupancy and pads already present (Steam's virtual pad included)
+#[tauri::command]
+pub fn get_xinput_diagnostics() -> Result<XInputReport, String> {
+    let entries = shared_manager()?.list_devices(&DeviceFilter::default(), true)?;
+    Ok(XInputReport::new(&entries, xinput_slots()))
+}
+
+/// Create the virtual X360 pad and feed it every frame. `mapping` defaults to
+/// the saved one; a new mapping is saved. Refused while it would conflict with
+/// a pad already present (see `set_virtual_pad_conflict_policy`).
+#[tauri::command]
+pub fn start_virtual_gamepad(mapping: Option<GamepadMapping>) -> Result<VirtualGamepadStatus, String> {
+    let manager = connected_manager()?;
+    let settings = match mapping {
+        Some(mapping) => settings::update(|s| s.virtual_pad_mapping = mapping)?,
+        None => settings::get(),
+    };
+    if !virtual_gamepad::is_active() {
+        let entries = manager.list_devices(&DeviceFilter::default(), true)?;
+        XInputReport::new(&entries, xinput_slots()).check(settings.virtual_pad_conflict)?;
+    }
+    let status = virtual_gamepad::start(settings.virtual_pad_mapping)?;
+    ensure_headless_stream(manager);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn stop_virtual_gamepad() -> VirtualGamepadStatus {
+    virtual_gamepad::stop();
+    virtual_gamepad::status()
+}
+
+/// Switch the virtual pad's mapping (live if it is running) and save it
+#[tauri::command]
+pub fn set_virtual_gamepad_mapping(mapping: GamepadMapping) -> Result<VirtualGamepadStatus, String> {
+    settings::update(|s| s.virtual_pad_mapping = mapping)?;
+    virtual_gamepad::set_mapping(mapping);
+    Ok(virtual_gamepad::status())
+}
+
+#[tauri::command]
+pub fn get_virtual_gamepad_status() -> VirtualGamepadStatus {
+    virtual_gamepad::status()
+}
+
+/// The mappings `start_virtual_gamepad` accepts
+#[tauri::command]
+pub fn list_virtual_gamepad_mappings() -> Vec<GamepadMapping> {
+    GamepadMapping::ALL.to_vec()
+}
+
+/// Send bindings, gestures and cursor movement to the OS as keyboard/mouse
+/// input (saved). Dry run still holds them back.
+#[tauri::command]
+pub fn set_output_injection(enabled: bool) -> Result<OutputStatus, String> {
+    settings::update(|s| s.output_injection = enabled)?;
+    output::set_enabled(enabled);
+    println!("🔧 Output injection {}", if enabled { "enabled" } else { "disabled" });
+    Ok(output::status())
+}
+
+#[tauri::command]
+pub fn get_output_status() -> OutputStatus {
+    output::status()
+}
+
+/// Press and release `action` through the OS right away, so the user can
+/// check a binding types what they expect. Works with injection off; returns
+/// the backend used.
+#[tauri::command]
+pub fn test_output_action(action: Action) -> Result<String, String> {
+    action.validate()?;
+    if is_dry_run() {
+        return Err("Dry run is on; turn it off to send test input".to_string());
+    }
+    output::test(&action)
+}
+
+#[cfg(windows)]
+fn xinput_slots() -> Option<[bool; XINPUT_SLOTS]> {
+    use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+    use windows_sys::Win32::UI::Input::XboxController::{XInputGetState, XINPUT_STATE};
+
+    let mut slots = [false; XINPUT_SLOTS];
+    for (index, used) in slots.iter_mut().enumerate() {
+        let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+        *used = unsafe { XInputGetState(index as u32, &mut state) } == ERROR_SUCCESS;
+    }
+    Some(slots)
+}
+
+#[cfg(not(windows))]
+fn xinput_slots() -> Option<[bool; XINPUT_SLOTS]> {
+    None
+}
+
+fn require_developer_mode() -> Result<(), String> {
+    if settings::get().developer_mode {
+        Ok(())
+    } else {
+        Err("Enable developer mode to send raw feature reports".to_string())
     }
-}
\ No newline at end of file
+}
+
+/// Feature-report console: send raw bytes (first byte is the report/command ID).
+/// Only available in developer mode - the wrong bytes can misconfigure the controller.
+#[tauri::command]
+pub fn send_feature_report(bytes: Vec<u8>) -> Result<(), String> {
+    require_developer_mode()?;
+    let m = current_manager()?;
+    println!("🔧 Feature report out: {}", to_hex(&bytes));
+    let writer = m.clone();
+    with_command_timeout("Feature report", move || writer.send_feature_report(&bytes))?;
+    recovery::update(|state| state.lizard_mode_disabled = !m.lizard_mode());
+    Ok(())
+}
+
+/// Feature-report console: read report `id`, up to `len` bytes
+#[tauri::command]
+pub fn get_feature_report(id: u8, len: usize) -> Result<Vec<u8>, String> {
+    require_developer_mode()?;
+    let m = current_manager()?;
+    let reply = with_command_timeout("Feature report", move || m.get_feature_report(id, len))?;
+    println!("🔧 Feature report in: {}", to_hex(&reply));
+    Ok(reply)
+}
+
+/// Developer console: run a debug script (`send fr 81 00`, `get fr 83 64`,
+/// `read 200ms`, `dump state`, `haptic left 5000 3`) against the live
+/// manager. Developer mode only; stops at the first failing line. A script
+/// with `read` needs the input stream stopped, since both would take reports.
+#[tauri::command]
+pub async fn debug_exec(script: String) -> Result<Vec<DebugLine>, String> {
+    require_developer_mode()?;
+    let m = current_manager()?;
+    let windows: Vec<Duration> = parse_debug_script(&script)?
+        .iter()
+        .filter_map(|(_, command)| match command {
+            DebugCommand::Read(window) => Some(*window),
+            _ => None,
+        })
+        .collect();
+    if !windows.is_empty() {
+        require_no_reader()?;
+    }
+    // Each line gets the usual command deadline on top of its read window
+    let budget = windows.into_iter().fold(Duration::from_millis(COMMAND_TIMEOUT_MS), |total, window| total + window);
+
+    println!("🔧 Debug script:\n{}", script.trim_end());
+    let runner = m.clone();
+    let lines = tauri::async_runtime::spawn_blocking(move || {
+        with_timeout("Debug script", budget, move || run_debug_script(&runner, &script))
+    })
+    .await
+    .map_err(|e| format!("Debug script failed: {}", e))??;
+    recovery::update(|state| state.lizard_mode_disabled = !m.lizard_mode());
+    Ok(lines)
+}
+
+/// Capture `count` raw reports with a label ("holding A") as a parser fixture.
+/// Saved to `dir`, or `<app data>/fixtures/` by default; returns the file path.
+#[tauri::command]
+pub async fn capture_fixture(
+    app: tauri::AppHandle,
+    label: String,
+    count: usize,
+    dir: Option<String>,
+) -> Result<String, String> {
+    let manager = connected_manager()?;
+    let dir = match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => app
+            .path_resolver()
+            .app_data_dir()
+            .map(|dir| dir.join(FIXTURES_DIR_NAME))
+            .ok_or_else(|| "Could not resolve app data directory".to_string())?,
+    };
+
+    let reports = capture_reports(&manager, count)?;
+    let device = CONNECTED_DEVICE.lock().unwrap().clone();
+    let path = Capture::new(&label, device, &reports).save(&dir)?;
+    println!("✅ Captured {} reports as '{}' -> {}", reports.len(), label, path.display());
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Average the gyro over `samples` reports (default 200) while the controller
+/// lies flat, and store the result as the connected unit's gyro bias. It reads
+/// reports itself, so the input stream must be stopped.
+#[tauri::command]
+pub async fn calibrate_gyro(samples: Option<usize>) -> Result<GyroCalibration, String> {
+    let manager = connected_manager()?;
+    require_no_reader()?;
+    let device_id = CONNECTED_DEVICE
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "The connected controller isn't in the device registry".to_string())?;
+
+    let current_bias = manager.calibration().gyro_bias;
+    let sampler = manager.clone();
+    let samples = samples.unwrap_or(DEFAULT_GYRO_SAMPLES);
+    let result = tauri::async_runtime::spawn_blocking(move || calibration::calibrate_gyro(&sampler, samples, current_bias))
+        .await
+        .map_err(|e| format!("Gyro calibration failed: {}", e))??;
+    let settings = with_device_registry(|registry| {
+        let mut settings = registry
+            .get(&device_id)
+            .map(|d| d.settings.clone())
+            .ok_or_else(|| format!("Unknown device '{}'", device_id))?;
+        settings.calibration.gyro_bias = result.bias;
+        registry.set_settings(&device_id, settings.clone())?;
+        registry.save(&device_registry_path()?)?;
+        Ok(settings)
+    })?;
+    manager.apply_device_settings(&settings);
+    Ok(result)
+}
+
+fn recordings_dir() -> Result<PathBuf, String> {
+    APP_HANDLE
+        .get()
+        .and_then(|app| app.path_resolver().app_data_dir())
+        .map(|dir| dir.join(RECORDINGS_DIR_NAME))
+        .ok_or_else(|| "Could not resolve app data directory".to_string())
+}
+
+/// Start recording streamed input for ghost replay (up to ~20 minutes)
+#[tauri::command]
+pub fn start_session_recording() -> Result<(), String> {
+    let mut recorder = SESSION_RECORDER.lock().unwrap();
+    if recorder.is_some() {
+        return Err("Already recording".to_string());
+    }
+    *recorder = Some(SessionRecorder::new());
+    println!("🎮 Recording input session");
+    Ok(())
+}
+
+/// Stop recording and save the session as `name` in `<app data>/recordings/`
+#[tauri::command]
+pub fn stop_session_recording(name: String) -> Result<RecordingInfo, String> {
+    let recorder = SESSION_RECORDER
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "Not recording".to_string())?;
+    let device = CONNECTED_DEVICE.lock().unwrap().clone();
+    let info = recorder.finish(&name, device)?.save(&recordings_dir()?)?;
+    println!("✅ Recorded {} frames ({} ms) as '{}'", info.frames, info.duration_ms, info.file);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn list_session_recordings() -> Result<Vec<RecordingInfo>, String> {
+    list_recordings(&recordings_dir()?)
+}
+
+/// Replay a recording through the active profile and outputs at its original
+/// timing; live input is ignored by the mapping until it ends
+#[tauri::command]
+pub fn start_ghost_replay(file: String, looped: Option<bool>) -> Result<RecordingInfo, String> {
+    let recording = Recording::load(&recording_path(&recordings_dir()?, &file)?)?;
+    stop_ghost_replay();
+
+    let info = recording.info(file);
+    let name = recording.name.clone();
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(GHOST_REPLAY_EVENT, GhostReplayEvent::Started { recording: info.clone() });
+    }
+
+    let replay = GhostReplay::spawn(
+        recording,
+        looped.unwrap_or(false),
+        |frame| {
+            on_frame(frame);
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit_all(GHOST_INPUT_EVENT, frame);
+            }
+        },
+        move |completed| {
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit_all(GHOST_REPLAY_EVENT, GhostReplayEvent::Finished { name, completed });
+            }
+        },
+    );
+    *GHOST_REPLAY.lock().unwrap() = Some(replay);
+    Ok(info)
+}
+
+/// Stop the ghost replay; false when none was running
+#[tauri::command]
+pub fn stop_ghost_replay() -> bool {
+    // Not held while joining, so live frames aren't stalled
+    let replay = GHOST_REPLAY.lock().unwrap().take();
+    match replay {
+        Some(replay) => {
+            let running = replay.is_running();
+            replay.stop();
+            running
+        }
+        None => false,
+    }
+}
+
+/// Learning mode, step 1: record an idle baseline (leave the controller alone
+/// for a moment), then watch for changes while the user works `control`.
+/// `device` (device ID or path) picks a controller other than the connected one.
+#[tauri::command]
+pub async fn start_learning(control: String, device: Option<String>) -> Result<(), String> {
+    let controller = controller_for(device.as_deref())?;
+
+    if let Some(old) = LEARNING.lock().unwrap().take() {
+        println!("⏭️ Abandoning learning run for '{}'", old.control());
+    }
+    let run = LearningRun::start(controller, &control)?;
+    *LEARNING.lock().unwrap() = Some(run);
+    Ok(())
+}
+
+/// Learning mode, step 2: stop watching and return the changed offsets/masks
+#[tauri::command]
+pub fn finish_learning() -> Result<LearnResult, String> {
+    match LEARNING.lock().unwrap().take() {
+        Some(run) => Ok(run.finish()),
+        None => Err("No learning run in progress".to_string()),
+    }
+}
+
+/// Begin the guided hardware self-test, replacing any test in progress. It
+/// follows live input, so the input stream must be running.
+#[tauri::command]
+pub fn start_self_test() -> SelfTestStatus {
+    let test = SelfTest::new();
+    let status = test.status();
+    *SELF_TEST.lock().unwrap() = Some(test);
+    println!("🔍 Self-test started");
+    status
+}
+
+#[tauri::command]
+pub fn get_self_test_status() -> Result<SelfTestStatus, String> {
+    match SELF_TEST.lock().unwrap().as_ref() {
+        Some(test) => Ok(test.status()),
+        None => Err("No self-test in progress".to_string()),
+    }
+}
+
+/// Skip the current control (it is reported as skipped)
+#[tauri::command]
+pub fn skip_self_test_step() -> Result<SelfTestStatus, String> {
+    let mut test = SELF_TEST.lock().unwrap();
+    let test = test.as_mut().ok_or_else(|| "No self-test in progress".to_string())?;
+    test.skip();
+    Ok(test.status())
+}
+
+/// End the self-test and get the per-control report; controls not reached count as skipped
+#[tauri::command]
+pub fn finish_self_test() -> Result<SelfTestReport, String> {
+    let test = SELF_TEST.lock().unwrap().take().ok_or_else(|| "No self-test in progress".to_string())?;
+    let report = test.report();
+    println!(
+        "🔍 Self-test finished: {} passed, {} failed, {} skipped",
+        report.passed, report.failed, report.skipped
+    );
+    Ok(report)
+}
+
+/// Begin the mapping wizard for the connected controller, replacing any wizard in progress
+#[tauri::command]
+pub fn start_layout_wizard(name: String) -> WizardStatus {
+    let wizard = LayoutWizard::new(&name);
+    let status = wizard.status();
+    *LAYOUT_WIZARD.lock().unwrap() = Some(wizard);
+    status
+}
+
+#[tauri::command]
+pub fn get_layout_wizard_status() -> Result<WizardStatus, String> {
+    match LAYOUT_WIZARD.lock().unwrap().as_ref() {
+        Some(wizard) => Ok(wizard.status()),
+        None => Err("No mapping wizard in progress".to_string()),
+    }
+}
+
+/// Start watching for the current step's control (the user has read the prompt)
+#[tauri::command]
+pub async fn wizard_capture_start() -> Result<(), String> {
+    let control = {
+        let wizard = LAYOUT_WIZARD.lock().unwrap();
+        let step = wizard
+            .as_ref()
+            .ok_or_else(|| "No mapping wizard in progress".to_string())?
+            .current()
+            .ok_or_else(|| "Mapping wizard already finished".to_string())?;
+        match step.control {
+            WizardControl::Button { button } => format!("{:?}", button),
+            WizardControl::Axis { axis } => format!("{:?}", axis),
+        }
+    };
+    start_learning(control, None).await
+}
+
+/// Stop watching and record what changed; stays on the step if nothing usable did
+#[tauri::command]
+pub fn wizard_capture_finish() -> Result<WizardStatus, String> {
+    let result = finish_learning()?;
+    let mut wizard = LAYOUT_WIZARD.lock().unwrap();
+    let wizard = wizard.as_mut().ok_or_else(|| "No mapping wizard in progress".to_string())?;
+    wizard.record(&result)?;
+    Ok(wizard.status())
+}
+
+/// Leave the current control to the built-in parser
+#[tauri::command]
+pub fn wizard_skip_step() -> Result<WizardStatus, String> {
+    let mut wizard = LAYOUT_WIZARD.lock().unwrap();
+    let wizard = wizard.as_mut().ok_or_else(|| "No mapping wizard in progress".to_string())?;
+    wizard.skip();
+    Ok(wizard.status())
+}
+
+/// Store the wizard's layout with the connected controller's settings and start using it
+#[tauri::command]
+pub fn save_layout_wizard() -> Result<ReportLayout, String> {
+    let device_id = CONNECTED_DEVICE
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "Controller not connected".to_string())?;
+    let layout = match LAYOUT_WIZARD.lock().unwrap().as_ref() {
+        Some(wizard) => wizard.layout().clone(),
+        None => return Err("No mapping wizard in progress".to_string()),
+    };
+
+    let mut settings = get_device_settings(device_id.clone())?;
+    settings.report_layout = Some(layout.clone());
+    set_device_settings(device_id, settings)?;
+
+    *LAYOUT_WIZARD.lock().unwrap() = None;
+    println!("✅ Saved report layout '{}'", layout.name);
+    Ok(layout)
+}
+
+/// Read as fast as possible for `seconds` and report throughput (async so the UI thread stays free)
+#[tauri::command]
+pub async fn stress_test_controller(seconds: u64) -> Result<StressTestReport, String> {
+    if seconds == 0 || seconds > MAX_STRESS_DURATION_SECS {
+        return Err(format!(
+            "Stress test duration must be between 1 and {} seconds",
+            MAX_STRESS_DURATION_SECS
+        ));
+    }
+
+    let manager = current_manager()?;
+    let (device, settings) = (manager.get_device(), manager.read_settings());
+
+    run_stress_test(&device, settings, Duration::from_secs(seconds))
+}
+
+#[derive(Serialize)]
+pub struct TesterReportFiles {
+    pub report: TesterReport,
+    pub json_path: String,
+    pub html_path: String,
+}
+
+/// Capture for `seconds` (default 5) and save a tester report - device,
+/// firmware, axis ranges, polling rate, anomalies - as JSON and HTML in
+/// `<app data>/tester-reports/`, for attaching to bug reports. The capture
+/// reads reports itself, so the input stream must be stopped.
+#[tauri::command]
+pub async fn export_tester_report(app: tauri::AppHandle, seconds: Option<u64>) -> Result<TesterReportFiles, String> {
+    let seconds = seconds.unwrap_or(DEFAULT_TESTER_CAPTURE_SECS);
+    if seconds == 0 || seconds > MAX_TESTER_CAPTURE_SECS {
+        return Err(format!("Capture must be between 1 and {} seconds", MAX_TESTER_CAPTURE_SECS));
+    }
+    let manager = connected_manager()?;
+    require_no_reader()?;
+    let dir = app
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(TESTER_REPORTS_DIR_NAME))
+        .ok_or_else(|| "Could not resolve app data directory".to_string())?;
+
+    let device = detect_steam_controller();
+    let firmware = match manager.firmware_info() {
+        Ok(firmware) => Some(firmware),
+        Err(e) => {
+            eprintln!("❌ Firmware version unavailable: {}", e);
+            None
+        }
+    };
+    println!("🔍 Capturing {} s for a tester report...", seconds);
+    let capture = tauri::async_runtime::spawn_blocking(move || {
+        capture_tester_report(&manager, Duration::from_secs(seconds))
+    })
+    .await
+    .map_err(|e| format!("Tester report capture failed: {}", e))??;
+    let report = capture.finish(device, firmware, env!("CARGO_PKG_VERSION"));
+
+    let (json_path, html_path) = report.save(&dir)?;
+    println!(
+        "✅ Tester report: {} reports, {} anomalies -> {}",
+        report.reports.total,
+        report.anomalies.len(),
+        html_path.display()
+    );
+    Ok(TesterReportFiles {
+        report,
+        json_path: json_path.to_string_lossy().into_owned(),
+        html_path: html_path.to_string_lossy().into_owned(),
+    })
+}
+
+#[tauri::command]
+pub fn get_read_settings() -> Result<ReadSettings, String> {
+    current_manager().map(|m| m.read_settings())
+}
+
+#[tauri::command]
+pub fn set_read_settings(settings: ReadSettings) -> Result<(), String> {
+    current_manager()?.set_read_settings(settings)
+}
+
+/// Latency of recent feature-report writes (lizard mode, haptics, configuration,
+/// raw) per kind, plus the last `recent` writes (default 20)
+#[tauri::command]
+pub fn get_write_stats(recent: Option<usize>) -> Result<WriteStats, String> {
+    current_manager().map(|m| m.write_stats(recent.unwrap_or(DEFAULT_RECENT_WRITES)))
+}
+
+#[tauri::command]
+pub fn clear_write_stats() -> Result<(), String> {
+    current_manager().map(|m| m.clear_write_stats())
+}
+
+#[tauri::command]
+pub fn get_report_rate() -> Result<ReportRateConfig, String> {
+    current_manager().map(|m| m.report_rate())
+}
+
+/// Write report rate, smoothing and IMU reporting to the connected controller
+#[tauri::command]
+pub fn set_report_rate(config: ReportRateConfig) -> Result<(), String> {
+    let m = current_manager()?;
+    with_command_timeout("Report rate", move || m.set_report_rate(config))
+}
+
+/// Turn IMU reporting off to save power, or back on; returns the new config
+#[tauri::command]
+pub fn set_imu_enabled(enabled: bool) -> Result<ReportRateConfig, String> {
+    let m = current_manager()?;
+    with_command_timeout("IMU reporting", move || m.set_imu_enabled(enabled))
+}
+
+/// Firmware mouse emulation mode for one pad, or both when `pad` is omitted
+#[tauri::command]
+pub fn set_trackpad_mode(mode: TrackpadMode, pad: Option<Pad>) -> Result<(), String> {
+    let m = current_manager()?;
+    with_command_timeout("Trackpad mode", move || m.set_trackpad_mode(pad, mode))
+}
+
+/// Switch to full-rate IMU reporting (raw data plus orientation) for gyro-heavy profiles
+#[tauri::command]
+pub fn request_high_rate_imu() -> Result<ReportRateConfig, String> {
+    let config = ReportRateConfig::high_rate_imu();
+    set_report_rate(config)?;
+    Ok(config)
+}
+
+/// Push parsed frames to the window as `controller-input` events instead of being polled.
+/// The frontend acknowledges frames with `ack_controller_input`; once `max_in_flight`
+/// frames are unacknowledged, newer input is coalesced into a single pending frame.
+/// With `delta`, frames go out as `controller-input-delta` events carrying only
+/// the changed fields, with a full keyframe every `DEFAULT_KEYFRAME_INTERVAL` frames.
+/// With `gamepad_api`, each frame is also emitted as a `gamepad-api-input` event
+/// shaped like a W3C Gamepad API `Gamepad`.
+/// If the window stream is already running (a reloaded frontend asking again)
+/// it is pointed at the caller instead of restarted, unless `max_in_flight` changed.
+#[tauri::command]
+pub fn stream_controller_input(
+    window: tauri::Window,
+    max_in_flight: Option<u64>,
+    delta: Option<bool>,
+    gamepad_api: Option<bool>,
+) -> Result<(), String> {
+    let manager = connected_manager()?;
+    let max_in_flight = max_in_flight.unwrap_or(DEFAULT_MAX_IN_FLIGHT).max(1);
+
+    let mut stream = INPUT_STREAM.lock().unwrap();
+    let mut target = STREAM_TARGET.lock().unwrap();
+    let reusable = target.is_some()
+        && stream
+            .as_ref()
+            .map_or(false, |s| s.is_running() && s.max_in_flight() == max_in_flight);
+    *target = Some(StreamTarget {
+        window,
+        encoder: delta.unwrap_or(false).then(|| DeltaEncoder::new(DEFAULT_KEYFRAME_INTERVAL)),
+        gamepad_api: gamepad_api.unwrap_or(false),
+    });
+    drop(target);
+
+    if reusable {
+        // Frames sent to the old page will never be acked
+        if let Some(stream) = stream.as_ref() {
+            stream.ack_all();
+        }
+        println!("📡 Input stream handed to the reloaded frontend");
+        return Ok(());
+    }
+    if let Some(old) = stream.take() {
+        old.stop();
+    }
+
+    let haptics = manager.clone();
+    let drift = manager.clone();
+    *stream = Some(InputStream::spawn_with_events(
+        manager.handle(),
+        max_in_flight,
+        move |frame| {
+            let combined = combine_frame(frame);
+            let frame = combined.as_ref().unwrap_or(frame);
+            on_live_frame(frame);
+            check_drift(&drift);
+            check_health(&drift, &frame.input);
+
+            let mut target = STREAM_TARGET.lock().unwrap();
+            let target = match target.as_mut() {
+                Some(target) => target,
+                None => return false,
+            };
+            if target.gamepad_api {
+                let _ = target.window.emit(GAMEPAD_API_EVENT, to_standard_gamepad(&frame.input));
+            }
+            match &mut target.encoder {
+                Some(encoder) => {
+                    let delta = encoder.encode(frame);
+                    target.window.emit(CONTROLLER_INPUT_DELTA_EVENT, delta).is_ok()
+                }
+                None => target.window.emit(CONTROLLER_INPUT_EVENT, frame).is_ok(),
+            }
+        },
+        move |event| on_stream_event(&haptics, event),
+    ));
+
+    Ok(())
+}
+
+/// State a (re)loaded frontend needs to pick up where the backend is
+#[derive(Clone, Serialize)]
+pub struct SessionSnapshot {
+    pub controller: Option<SteamControllerInfo>,
+    /// The window input stream is running; ask for it again to receive frames
+    pub streaming: bool,
+    pub active_profile: Option<Profile>,
+    pub mapping_enabled: bool,
+}
+
+/// Called by the frontend once it has loaded (including after a reload or a
+/// webview crash). The connection, reader thread and active profile are left
+/// alone; the window gets a `session-snapshot` event to resync from.
+#[tauri::command]
+pub fn frontend_ready(window: tauri::Window) -> Result<(), String> {
+    let connected = current_manager().map(|m| m.is_connected()).unwrap_or(false);
+    // Same lock order as `stream_controller_input`
+    let running = INPUT_STREAM.lock().unwrap().as_ref().map_or(false, |s| s.is_running());
+    let streaming = running && STREAM_TARGET.lock().unwrap().is_some();
+    let snapshot = SessionSnapshot {
+        controller: CONTROLLER_INFO.lock().unwrap().clone().filter(|_| connected),
+        streaming,
+        active_profile: ACTIVE_PROFILE.lock().unwrap().clone(),
+        mapping_enabled: hotkeys::is_mapping_enabled(),
+    };
+    println!(
+        "🔌 Frontend loaded (controller {}, stream {})",
+        if snapshot.controller.is_some() { "connected" } else { "not connected" },
+        if streaming { "running" } else { "stopped" }
+    );
+    window
+        .emit(SESSION_SNAPSHOT_EVENT, snapshot)
+        .map_err(|e| format!("Failed to send session snapshot: {}", e))
+}
+
+// Tell the user once when anti-drift finds the stick resting far off center
+fn check_drift(manager: &SteamControllerManager) {
+    if let Some(warning) = manager.take_drift_warning() {
+        println!("🎮 Stick drift: resting at {:?} ({} from center)", warning.offset, warning.magnitude);
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(STICK_DRIFT_EVENT, warning);
+        }
+    }
+}
+
+fn health_path() -> Result<PathBuf, String> {
+    APP_HANDLE
+        .get()
+        .and_then(|app| app.path_resolver().app_config_dir())
+        .map(|dir| dir.join(HEALTH_FILE))
+        .ok_or_else(|| "Could not resolve app config directory".to_string())
+}
+
+// Merge the session so far into the history on disk
+fn save_health(tracker: &mut HealthTracker, manager: &SteamControllerManager) -> Result<(), String> {
+    let path = health_path()?;
+    let mut store = HealthStore::load(&path)?;
+    let day = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() / 86_400).unwrap_or(0);
+    tracker.flush(&mut store, day, manager.write_totals(WriteKind::Haptics));
+    store.save(&path)
+}
+
+fn check_health(manager: &SteamControllerManager, input: &ControllerInput) {
+    let mut tracker = HEALTH_TRACKER.lock().unwrap();
+    if let Some(tracker) = tracker.as_mut() {
+        if tracker.update(input, manager.drift_offset()) {
+            if let Err(e) = save_health(tracker, manager) {
+                eprintln!("❌ Could not save controller health: {}", e);
+            }
+        }
+    }
+}
+
+// Save and stop tracking the controller that is going away
+fn end_health_session(manager: &SteamControllerManager) {
+    if let Some(mut tracker) = HEALTH_TRACKER.lock().unwrap().take() {
+        if let Err(e) = save_health(&mut tracker, manager) {
+            eprintln!("❌ Could not save controller health: {}", e);
+        }
+    }
+}
+
+/// Long-term wear of a controller (the connected one by default): stick drift
+/// and trigger creep over time, trackpad dead spots and failed haptic writes
+#[tauri::command]
+pub fn get_health_report(device_id: Option<String>) -> Result<HealthReport, String> {
+    let device_id = device_id
+        .or_else(|| CONNECTED_DEVICE.lock().unwrap().clone())
+        .ok_or_else(|| "Controller not connected".to_string())?;
+
+    // Include the running session
+    if let Some(tracker) = HEALTH_TRACKER.lock().unwrap().as_mut().filter(|t| t.device_id() == device_id) {
+        save_health(tracker, &current_manager()?)?;
+    }
+    HealthStore::load(&health_path()?)?
+        .report(&device_id)
+        .ok_or_else(|| format!("No health data for '{}' yet", device_id))
+}
+
+/// Resting stick offset anti-drift is currently correcting, (0, 0) when it is off
+#[tauri::command]
+pub fn get_stick_drift() -> Result<(i16, i16), String> {
+    Ok(connected_manager()?.drift_offset())
+}
+
+/// Receive every streamed frame outside of the window. Starts a headless stream
+/// if the frontend isn't streaming; it is never throttled by frontend acks.
+#[cfg(windows)]
+pub fn subscribe_input() -> Result<mpsc::Receiver<InputFrame>, String> {
+    let manager = connected_manager()?;
+
+    let (tx, rx) = mpsc::channel();
+    INPUT_SUBSCRIBERS.lock().unwrap().push(tx);
+    ensure_headless_stream(manager);
+
+    Ok(rx)
+}
+
+// Start a stream without a window unless one is already running. It ends on
+// its own once nothing consumes frames outside the window (see `keep_streaming`).
+fn ensure_headless_stream(manager: SteamControllerManager) {
+    let mut stream = INPUT_STREAM.lock().unwrap();
+    if !stream.as_ref().map(|s| s.is_running()).unwrap_or(false) {
+        if let Some(old) = stream.take() {
+            old.stop();
+        }
+        *STREAM_TARGET.lock().unwrap() = None;
+        let haptics = manager.clone();
+        *stream = Some(InputStream::spawn_with_events(
+            manager.handle(),
+            u64::MAX,
+            |frame| {
+                let combined = combine_frame(frame);
+                on_live_frame(combined.as_ref().unwrap_or(frame))
+            },
+            move |event| on_stream_event(&haptics, event),
+        ));
+    }
+}
+
+// Whether anything outside the window still wants frames
+fn keep_streaming() -> bool {
+    !INPUT_SUBSCRIBERS.lock().unwrap().is_empty() || virtual_gamepad::is_active()
+}
+
+// In combined mode, the primary frame with the secondary controller's controls merged in
+fn combine_frame(frame: &InputFrame) -> Option<InputFrame> {
+    COMBINER.lock().unwrap().as_ref().map(|combiner| InputFrame {
+        input: combiner.merge_primary(&frame.input),
+        ..frame.clone()
+    })
+}
+
+// Live frames are recorded, and kept out of the mapping while a ghost replay drives it
+fn on_live_frame(frame: &InputFrame) -> bool {
+    if let Some(recorder) = SESSION_RECORDER.lock().unwrap().as_mut() {
+        recorder.push(&frame.input);
+    }
+    if GHOST_REPLAY.lock().unwrap().as_ref().map_or(false, |replay| replay.is_running()) {
+        return keep_streaming();
+    }
+    on_frame(frame)
+}
+
+// Per-frame work shared by every stream: reserved controller chords (which
+// take priority over any binding), then the profile's bindings (on the
+// mirrored input for left-handed profiles) and the virtual pad, then fan-out
+// to subscribers (dropping the ones whose receiver is gone; they get the
+// unmirrored frame). Returns whether a headless stream should keep running.
+fn on_frame(frame: &InputFrame) -> bool {
+    hotkeys::check_chord(&frame.input.buttons);
+    check_profile_switch(&frame.input);
+    check_keyboard(&frame.input);
+    check_self_test(&frame.input);
+
+    {
+        let active = ACTIVE_PROFILE.lock().unwrap();
+        let input = match active.as_ref() {
+            Some(active) => apply_responses(&mirror_input(&frame.input, active.mirror), &active.response),
+            None => frame.input.clone(),
+        };
+        // Everything below sees the bindings of the active layer
+        let layered = check_layers(&input, active.as_ref());
+        let profile = layered.as_ref().or_else(|| active.as_ref());
+        let events = check_input_events(&input);
+        check_gestures(&input, profile);
+        check_binding_feedback(&events, profile);
+        check_edge_spin(&input, profile);
+        check_touch_menus(&input, profile);
+        check_cursor(&input, profile);
+        run_script(|script| script.on_frame(&input));
+        check_plugins(&input);
+        // In dry run the virtual pad stays where `set_dry_run` left it: neutral
+        if !is_dry_run() {
+            virtual_gamepad::feed(&input, profile);
+        }
+    }
+    send_to_hook(&HookMessage::Frame { input: &frame.input });
+    #[cfg(feature = "gilrs-verify")]
+    check_verification(&frame.input);
+
+    INPUT_SUBSCRIBERS.lock().unwrap().retain(|tx| tx.send(frame.clone()).is_ok());
+    keep_streaming()
+}
+
+/// Controllers answering on a wireless dongle, one per slot, for picking which
+/// one `connect_steam_controller(slot)` uses. Sleeping controllers aren't listed.
+#[tauri::command]
+pub fn list_paired_controllers() -> Result<Vec<SteamControllerInfo>, String> {
+    let manager = shared_manager()?;
+    let connected = CONTROLLER_INFO.lock().unwrap().clone().filter(|_| manager.is_connected());
+    let mut paired = with_command_timeout("Dongle probe", move || manager.list_paired_controllers(connected.as_ref()))?;
+    for info in paired.iter_mut().filter(|info| info.device_id.is_none()) {
+        if let Ok(Some(device)) = with_device_registry(|registry| Ok(registry.find(info).cloned())) {
+            info.device_id = Some(device.id);
+            info.nickname = device.settings.nickname;
+        }
+    }
+    Ok(paired)
+}
+
+/// Let a new controller pair with the wireless dongle for `timeout_secs`
+/// (default 60, at most 180); finish with `stop_pairing`
+#[tauri::command]
+pub fn start_pairing(timeout_secs: Option<u8>) -> Result<(), String> {
+    let manager = shared_manager()?;
+    let timeout_secs = timeout_secs.unwrap_or(DEFAULT_PAIRING_SECS);
+    with_command_timeout("Dongle pairing", move || manager.start_pairing(timeout_secs))
+}
+
+/// End pairing, keeping the controller that paired (`keep`, the default) or not
+#[tauri::command]
+pub fn stop_pairing(keep: Option<bool>) -> Result<(), String> {
+    let manager = shared_manager()?;
+    with_command_timeout("Dongle pairing", move || manager.stop_pairing(keep.unwrap_or(true)))
+}
+
+/// Every attached Steam Controller (vendor interface paths for `start_combined_mode`)
+#[tauri::command]
+pub fn list_steam_controllers() -> Result<Vec<SteamControllerInfo>, String> {
+    shared_manager()?.list_controllers()
+}
+
+/// Merge a second controller (by path) into the connected one's frames.
+/// Streams already running pick it up on their next frame.
+#[tauri::command]
+pub fn start_combined_mode(
+    secondary_path: String,
+    config: Option<CombineConfig>,
+) -> Result<SteamControllerInfo, String> {
+    let secondary = connected_manager()?.sibling();
+    stop_combined_mode();
+
+    let info = secondary.connect_path(&secondary_path)?;
+    *COMBINER.lock().unwrap() = Some(Combiner::new(config.unwrap_or_default()));
+    let stream = InputStream::spawn(secondary.handle(), u64::MAX, |frame| {
+        match COMBINER.lock().unwrap().as_mut() {
+            Some(combiner) => {
+                combiner.update_secondary(frame.input.clone());
+                true
+            }
+            None => false,
+        }
+    });
+    *SECONDARY.lock().unwrap() = Some((secondary, stream, secondary_path));
+
+    println!("🎮 Combined mode on - secondary controller {}", info.serial);
+    Ok(info)
+}
+
+/// Change which controller each control group comes from
+#[tauri::command]
+pub fn set_combine_config(config: CombineConfig) -> Result<(), String> {
+    match COMBINER.lock().unwrap().as_mut() {
+        Some(combiner) => {
+            combiner.set_config(config);
+            Ok(())
+        }
+        None => Err("Combined mode is not active".to_string()),
+    }
+}
+
+/// Returns whether combined mode was active
+#[tauri::command]
+pub fn stop_combined_mode() -> bool {
+    *COMBINER.lock().unwrap() = None;
+    match SECONDARY.lock().unwrap().take() {
+        Some((manager, stream, _)) => {
+            stream.stop();
+            manager.disconnect();
+            println!("🎮 Combined mode off");
+            true
+        }
+        None => false,
+    }
+}
+
+/// Give every other attached controller its own worker: a reader thread with
+/// its own connection, parser, calibration and layer state that maps its
+/// frames through the active profile to the output, and also sends them out
+/// as `device-input` events tagged with the device ID. The connected
+/// controller and a combined-mode secondary keep their streams.
+#[tauri::command]
+pub fn start_device_workers() -> Result<Vec<WorkerStatus>, String> {
+    let base = shared_manager()?;
+    let mut taken: Vec<String> = CONTROLLER_INFO.lock().unwrap().iter().map(|info| info.path.clone()).collect();
+    taken.extend(SECONDARY.lock().unwrap().as_ref().map(|(_, _, path)| path.clone()));
+
+    for info in base.list_controllers()? {
+        if taken.contains(&info.path) {
+            continue;
+        }
+        // One controller that won't open doesn't keep the others from starting
+        let manager = base.sibling();
+        let started = manager.connect_path(&info.path).and_then(|info| register_device(&info).map(|r| (info, r)));
+        let (info, (device_id, settings)) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                eprintln!("❌ No worker for {}: {}", info.serial, e);
+                manager.disconnect();
+                continue;
+            }
+        };
+        manager.apply_device_settings(&settings);
+
+        let mapper = Arc::new(Mutex::new(DeviceMapper::new()));
+        let previous = WORKER_DEVICES.lock().unwrap().insert(device_id.clone(), (manager.clone(), Arc::clone(&mapper)));
+        if let Some((old, old_mapper)) = previous {
+            old.disconnect();
+            dispatch_bindings(&old_mapper.lock().unwrap().release_all());
+        }
+        let id = device_id.clone();
+        DEVICE_WORKERS.add(
+            &device_id,
+            device_worker(manager.handle(), move |input| {
+                let app = match APP_HANDLE.get() {
+                    Some(app) => app,
+                    None => return false,
+                };
+                let events = {
+                    let active = ACTIVE_PROFILE.lock().unwrap();
+                    mapper.lock().unwrap().map(input, active.as_ref(), Instant::now())
+                };
+                dispatch_bindings(&events);
+                app.emit_all(DEVICE_INPUT_EVENT, DeviceInput { device_id: id.clone(), input: input.clone() })
+                    .is_ok()
+            }),
+        );
+        println!("🎮 Worker started for {} [{}]", info.serial, device_id);
+    }
+    Ok(DEVICE_WORKERS.status())
+}
+
+/// Stop every per-device worker, let go of what it held and close its
+/// connection; returns how many there were
+#[tauri::command]
+pub fn stop_device_workers() -> usize {
+    DEVICE_WORKERS.clear();
+    let devices: Vec<WorkerDevice> = WORKER_DEVICES.lock().unwrap().drain().map(|(_, device)| device).collect();
+    for (manager, mapper) in &devices {
+        manager.disconnect();
+        dispatch_bindings(&mapper.lock().unwrap().release_all());
+    }
+    if !devices.is_empty() {
+        println!("🎮 Stopped {} device worker(s)", devices.len());
+    }
+    devices.len()
+}
+
+#[tauri::command]
+pub fn get_worker_status() -> Vec<WorkerStatus> {
+    DEVICE_WORKERS.status()
+}
+
+/// Compare parsed frames with the OS gamepad driver's view of the controller
+/// (needs a driver that exposes it, e.g. hid-steam). Mismatches that persist
+/// are emitted as `verification-mismatch` events.
+#[tauri::command]
+pub fn start_verification(config: Option<VerificationConfig>) -> Result<(), String> {
+    #[cfg(feature = "gilrs-verify")]
+    {
+        let reader = ReferenceReader::start()?;
+        let verifier = Verifier::new(config.unwrap_or_default());
+        *VERIFICATION.lock().unwrap() = Some((reader, verifier));
+        Ok(())
+    }
+    #[cfg(not(feature = "gilrs-verify"))]
+    {
+        let _ = config;
+        Err("Built without the gilrs-verify feature".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn stop_verification() -> Result<VerificationSummary, String> {
+    #[cfg(feature = "gilrs-verify")]
+    {
+        let (_, verifier) = VERIFICATION
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| "Verification is not running".to_string())?;
+        let summary = verifier.summary();
+        println!(
+            "🔍 Verification stopped - {} mismatches in {} frames",
+            summary.mismatches, summary.frames
+        );
+        Ok(summary)
+    }
+    #[cfg(not(feature = "gilrs-verify"))]
+    Err("Built without the gilrs-verify feature".to_string())
+}
+
+#[tauri::command]
+pub fn ack_controller_input(seq: u64) {
+    if let Some(stream) = INPUT_STREAM.lock().unwrap().as_ref() {
+        stream.ack(seq);
+    }
+}
+
+/// `stream_controller_input` with the default settings: the reader thread
+/// (`InputStream`) pushes `controller-input` events to the window
+#[tauri::command]
+pub fn start_input_stream(window: tauri::Window) -> Result<(), String> {
+    stream_controller_input(window, None, None, None)
+}
+
+/// Same as `stop_controller_input_stream`
+#[tauri::command]
+pub fn stop_input_stream() -> bool {
+    stop_controller_input_stream()
+}
+
+#[tauri::command]
+pub fn stop_controller_input_stream() -> bool {
+    *STREAM_TARGET.lock().unwrap() = None;
+    match INPUT_STREAM.lock().unwrap().take() {
+        Some(stream) => {
+            stream.stop();
+            true
+        }
+        None => false,
+    }
+}
+
+fn profiles_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_config_dir()
+        .map(|dir| dir.join(PROFILES_DIR_NAME))
+        .ok_or_else(|| "Could not resolve app config directory".to_string())
+}
+
+fn plugins_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_config_dir()
+        .map(|dir| dir.join(PLUGINS_DIR_NAME))
+        .ok_or_else(|| "Could not resolve app config directory".to_string())
+}
+
+/// Load (or reload) every plugin with the capabilities the user granted;
+/// plugins that fail to load are logged and skipped
+#[tauri::command]
+pub fn reload_plugins() -> Result<Vec<PluginInfo>, String> {
+    let app = APP_HANDLE.get().ok_or_else(|| "App not initialized".to_string())?;
+    let (plugins, errors) = load_plugins(&plugins_dir(app)?, &settings::get().plugin_grants);
+    for e in &errors {
+        eprintln!("❌ {}", e);
+    }
+    println!("🔧 Plugins loaded: {}", plugins.len());
+    let infos = plugins.iter().map(Plugin::info).collect();
+    *PLUGINS.lock().unwrap() = plugins;
+    Ok(infos)
+}
+
+#[tauri::command]
+pub fn list_plugins() -> Vec<PluginInfo> {
+    PLUGINS.lock().unwrap().iter().map(Plugin::info).collect()
+}
+
+/// Grant a plugin capabilities (only those its manifest asks for take effect)
+/// and reload plugins; an empty list revokes everything
+#[tauri::command]
+pub fn set_plugin_grants(plugin: String, capabilities: Vec<Capability>) -> Result<Vec<PluginInfo>, String> {
+    settings::update(|s| {
+        if capabilities.is_empty() {
+            s.plugin_grants.remove(&plugin);
+        } else {
+            s.plugin_grants.insert(plugin, capabilities);
+        }
+    })?;
+    reload_plugins()
+}
+
+/// Start a program that gets input events as JSON lines on stdin and sends
+/// actions back on stdout, replacing any running one; it is saved in the
+/// settings and started again at launch
+#[tauri::command]
+pub fn start_hook_process(config: HookConfig) -> Result<(), String> {
+    settings::update(|s| s.hook_process = Some(config.clone()))?;
+    spawn_hook_process(config)
+}
+
+fn spawn_hook_process(config: HookConfig) -> Result<(), String> {
+    let mut hook = HOOK_PROCESS.lock().unwrap();
+    hook.take();
+    let generation = HOOK_GENERATION.fetch_add(1, Ordering::AcqRel) + 1;
+    let program = config.program.clone();
+    *hook = Some(HookProcess::spawn(config, move |reply| on_hook_reply(generation, reply))?);
+    println!("🔧 Hook process started: {}", program);
+    Ok(())
+}
+
+/// Start the hook process saved in the settings, if any
+pub fn start_configured_hook() -> Result<(), String> {
+    match settings::get().hook_process {
+        Some(config) => spawn_hook_process(config),
+        None => Ok(()),
+    }
+}
+
+/// Stop the hook process and forget it; returns whether one was running
+#[tauri::command]
+pub fn stop_hook_process() -> Result<bool, String> {
+    settings::update(|s| s.hook_process = None)?;
+    HOOK_GENERATION.fetch_add(1, Ordering::AcqRel);
+    let hook = HOOK_PROCESS.lock().unwrap().take();
+    Ok(match hook {
+        Some(hook) => {
+            println!("🔧 Hook process stopped ({} events dropped)", hook.dropped());
+            hook.stop();
+            true
+        }
+        None => false,
+    })
+}
+
+/// The running hook process's config
+#[tauri::command]
+pub fn get_hook_process() -> Option<HookConfig> {
+    HOOK_PROCESS.lock().unwrap().as_ref().map(|hook| hook.config().clone())
+}
+
+/// The Steam game running right now
+#[tauri::command]
+pub fn get_running_steam_game() -> Option<SteamGame> {
+    steam_game::running_game()
+}
+
+/// Activate the profile made for the new game, or drop the one activated for
+/// the previous game if it is still active
+pub fn on_steam_game_changed(game: Option<SteamGame>) {
+    match &game {
+        Some(game) => println!(
+            "🎮 Steam game: {} ({})",
+            game.name.as_deref().unwrap_or("unknown"),
+            game.app_id
+        ),
+        None => println!("🎮 Steam game exited"),
+    }
+    let app = match APP_HANDLE.get() {
+        Some(app) => app,
+        None => return,
+    };
+    let _ = app.emit_all(STEAM_GAME_CHANGED_EVENT, &game);
+
+    let profile = match &game {
+        Some(game) => match profiles_dir(app).and_then(|dir| profiles::load_profiles(&dir)) {
+            Ok(profiles) => profile_for_app(&profiles, game.app_id).cloned(),
+            Err(e) => {
+                eprintln!("❌ Steam profile lookup failed: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let previous = STEAM_GAME_PROFILE.lock().unwrap().take();
+    match profile {
+        Some(profile) => {
+            *STEAM_GAME_PROFILE.lock().unwrap() = Some(profile.name.clone());
+            activate_profile(profile);
+        }
+        None => {
+            let active = ACTIVE_PROFILE.lock().unwrap().as_ref().map(|p| p.name.clone());
+            if previous.is_some() && active == previous {
+                clear_active_profile();
+            }
+        }
+    }
+}
+
+/// Built-in profiles shipped with the app
+#[tauri::command]
+pub fn list_presets() -> Vec<Preset> {
+    profiles::list_presets()
+}
+
+/// Copy a preset into the user's profile directory so it can be edited
+#[tauri::command]
+pub fn clone_preset(app: tauri::AppHandle, preset_id: String) -> Result<Profile, String> {
+    profiles::clone_preset(&preset_id, &profiles_dir(&app)?)
+}
+
+/// Convert an sc-controller `.sccprofile` into a CtrlSpace profile. The result
+/// lists every binding feature that could not be carried over.
+#[tauri::command]
+pub fn import_sc_profile(app: tauri::AppHandle, path: String) -> Result<ScImport, String> {
+    import_sccprofile_file(Path::new(&path), &profiles_dir(&app)?)
+}
+
+/// Convert an AntiMicroX `.amgp` profile (keyboard/mouse bindings) into a
+/// CtrlSpace profile, with a report of what couldn't be carried over
+#[tauri::command]
+pub fn import_antimicrox_profile(app: tauri::AppHandle, path: String) -> Result<AntimicroxImport, String> {
+    import_amgp_file(Path::new(&path), &profiles_dir(&app)?)
+}
+
+/// Write a profile as an AntiMicroX `.amgp` file; returns what couldn't be exported
+#[tauri::command]
+pub fn export_antimicrox_profile(profile: Profile, path: String) -> Result<Vec<String>, String> {
+    export_amgp_file(&profile, Path::new(&path))
+}
+
+fn community_index_url() -> Result<String, String> {
+    settings::get()
+        .community_index_url
+        .ok_or_else(|| "No community profile repository configured".to_string())
+}
+
+// GET over HTTPS, refusing bodies larger than `limit`
+fn fetch_text(url: &str, limit: u64) -> Result<String, String> {
+    use std::io::Read;
+    let tls = native_tls::TlsConnector::new().map_err(|e| format!("TLS unavailable: {}", e))?;
+    let agent = ureq::AgentBuilder::new()
+        .tls_connector(Arc::new(tls))
+        .timeout(COMMUNITY_TIMEOUT)
+        .build();
+    let response = agent.get(url).call().map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    let mut body = String::new();
+    response
+        .into_reader()
+        .take(limit + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+    if body.len() as u64 > limit {
+        return Err(format!("{} is larger than {} bytes", url, limit));
+    }
+    Ok(body)
+}
+
+// The cached index, fetched again if asked to or if the URL changed
+fn community_index(refresh: bool) -> Result<CommunityIndex, String> {
+    let url = community_index_url()?;
+    if !refresh {
+        if let Some((cached_url, index)) = COMMUNITY_INDEX.lock().unwrap().as_ref() {
+            if *cached_url == url {
+                return Ok(index.clone());
+            }
+        }
+    }
+    let index = parse_index(&fetch_text(&url, MAX_INDEX_BYTES)?)?;
+    println!("📡 Community index: {} profiles from {}", index.profiles.len(), url);
+    *COMMUNITY_INDEX.lock().unwrap() = Some((url, index.clone()));
+    Ok(index)
+}
+
+// Download a community profile; the caller verifies it
+fn download_community_profile(id: &str) -> Result<(CommunityEntry, String), String> {
+    let index_url = community_index_url()?;
+    let entry = community_index(false)?
+        .entry(id)
+        .cloned()
+        .ok_or_else(|| format!("No community profile '{}'", id))?;
+    let json = fetch_text(&entry.url(&index_url)?, MAX_PROFILE_BYTES)?;
+    Ok((entry, json))
+}
+
+/// Point the app at a community profile repository (the HTTPS URL of its
+/// `index.json`), or `null` to forget it
+#[tauri::command]
+pub fn set_community_index_url(url: Option<String>) -> Result<AppSettings, String> {
+    if let Some(url) = &url {
+        validate_url(url)?;
+    }
+    *COMMUNITY_INDEX.lock().unwrap() = None;
+    settings::update(|s| s.community_index_url = url)
+}
+
+/// Every profile in the community repository (cached; `refresh` fetches the index again)
+#[tauri::command]
+pub async fn browse_community_profiles(refresh: Option<bool>) -> Result<Vec<CommunityEntry>, String> {
+    Ok(community_index(refresh.unwrap_or(false))?.profiles)
+}
+
+/// Community profiles matching every word of `query` (name, description, author, tag or AppID)
+#[tauri::command]
+pub async fn search_community_profiles(query: String) -> Result<Vec<CommunityEntry>, String> {
+    let index = community_index(false)?;
+    Ok(index.search(&query).into_iter().cloned().collect())
+}
+
+/// Download and verify a community profile without installing it
+#[tauri::command]
+pub async fn preview_community_profile(id: String) -> Result<Profile, String> {
+    let (entry, json) = download_community_profile(&id)?;
+    entry.verify(&json)
+}
+
+/// Download, verify and save a community profile; it gets a numbered name if
+/// one with the same name is already installed. Its script and auto-activation
+/// keys are removed and listed in the result.
+#[tauri::command]
+pub async fn install_community_profile(app: tauri::AppHandle, id: String) -> Result<CommunityInstall, String> {
+    let (entry, json) = download_community_profile(&id)?;
+    entry.install(&json, &profiles_dir(&app)?)
+}
+
+/// Show the dual-pad on-screen keyboard; input from the next frame on types keys
+#[tauri::command]
+pub fn open_keyboard() {
+    let wheel = Daisywheel::new();
+    OVERLAY_BUS.publish(wheel.layout_event());
+    *KEYBOARD.lock().unwrap() = Some(wheel);
+    println!("⌨️ On-screen keyboard opened");
+}
+
+/// Hide the on-screen keyboard; returns whether it was open
+#[tauri::command]
+pub fn close_keyboard() -> bool {
+    let was_open = KEYBOARD.lock().unwrap().take().is_some();
+    if was_open {
+        OVERLAY_BUS.publish(OverlayEvent::KeyboardClosed);
+        println!("⌨️ On-screen keyboard closed");
+    }
+    was_open
+}
+
+#[tauri::command]
+pub fn is_keyboard_open() -> bool {
+    KEYBOARD.lock().unwrap().is_some()
+}
+
+// Battery reports from the stream, and the stream giving up on a gone or failing device
+fn on_stream_event(manager: &SteamControllerManager, event: StreamEvent) {
+    match event {
+        StreamEvent::Battery(status) => check_battery(manager, status),
+        StreamEvent::Ended(end) => {
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit_all(INPUT_STREAM_ENDED_EVENT, &end);
+            }
+        }
+    }
+}
+
+// Warn once per threshold crossing: event for the app, optional rumble on the controller
+fn check_battery(manager: &SteamControllerManager, status: BatteryStatus) {
+    let thresholds = settings::get().battery;
+    let event = match BATTERY_MONITOR.lock().unwrap().update(status, &thresholds) {
+        Some(event) => event,
+        None => return,
+    };
+
+    println!("🔋 Battery {:?}: {}% ({} mV)", event.level, status.percent, status.voltage_mv);
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(BATTERY_LOW_EVENT, &event);
+    }
+    if thresholds.haptic_alert {
+        for pulse in dying_battery_pattern(event.level) {
+            if let Err(e) = manager.send_haptic_pulse(pulse) {
+                eprintln!("❌ Battery warning rumble failed: {}", e);
+            }
+        }
+    }
+}
+
+// Resolve button presses against the active layer, publishing layer changes
+// for overlays. Returns the profile as seen with a layer active.
+fn check_layers(input: &ControllerInput, profile: Option<&Profile>) -> Option<Profile> {
+    let mut engine = LAYERS.lock().unwrap();
+    let update = engine.update(input, profile, Instant::now());
+
+    let layered = match (profile, engine.active_layer()) {
+        (Some(profile), Some(layer)) => profile.with_layer(layer),
+        _ => None,
+    };
+    if let (Some(profile), Some(layer)) = (profile, update.layer_changed) {
+        println!("🎮 Layer: {}", layer.as_deref().unwrap_or("base"));
+        OVERLAY_BUS.publish(OverlayEvent::LayerChanged {
+            bindings: describe_bindings(layered.as_ref().unwrap_or(profile)),
+            layer,
+        });
+    }
+    dispatch_bindings(&update.events);
+    layered
+}
+
+// Send binding events to the output (or the dry-run preview), scripts, the
+// hook and plugins; shared by the primary stream and the device workers
+fn dispatch_bindings(events: &[BindingEvent]) {
+    if let Some(app) = APP_HANDLE.get() {
+        for event in events {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_binding(event));
+            } else {
+                let _ = app.emit_all(BINDING_ACTION_EVENT, event);
+                output::binding(event);
+            }
+        }
+    }
+    for event in events {
+        run_script(|script| script.on_binding(event));
+        send_to_hook(&HookMessage::Binding { event });
+        if let Action::Plugin { plugin, action } = &event.action {
+            run_plugin_action(plugin, action, event);
+        }
+    }
+}
+
+// Detect pad taps and resolve them against the active profile
+fn check_gestures(input: &ControllerInput, profile: Option<&Profile>) {
+    let events = GESTURES.lock().unwrap().update(input, Instant::now());
+    if events.is_empty() {
+        return;
+    }
+    let mut pulses = Vec::new();
+    for event in events {
+        pulses.extend(gesture_feedback(&event, profile));
+        let bound = event.bind(profile);
+        if let Some(app) = APP_HANDLE.get() {
+            if !is_dry_run() {
+                let _ = app.emit_all(PAD_GESTURE_EVENT, &bound);
+                output::gesture(&bound);
+            } else if let Some(preview) = preview_gesture(&bound) {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview);
+            }
+        }
+    }
+    play_pulses(pulses);
+}
+
+// The frame's pressed/held/released, touch and trigger events, emitted for
+// the frontend and returned for the mapping below
+fn check_input_events(input: &ControllerInput) -> Vec<InputEvent> {
+    let events = INPUT_EVENTS.lock().unwrap().update(input, Instant::now());
+    if !events.is_empty() {
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(INPUT_EVENTS_EVENT, &events);
+        }
+    }
+    events
+}
+
+// Haptics attached to bindings in the active profile, on press
+fn check_binding_feedback(events: &[InputEvent], profile: Option<&Profile>) {
+    play_pulses(binding_feedback(events, profile));
+}
+
+fn play_pulses(pulses: Vec<HapticPulse>) {
+    if pulses.is_empty() {
+        return;
+    }
+    let manager = SC_MANAGER.read().unwrap().clone();
+    if let Some(manager) = manager {
+        for pulse in pulses {
+            if let Err(e) = manager.send_haptic_pulse(pulse) {
+                eprintln!("❌ {}", e);
+            }
+        }
+    }
+}
+
+// Pads in mouse mode move the cursor (relative or anchored, per profile)
+fn check_cursor(input: &ControllerInput, profile: Option<&Profile>) {
+    let mut moves = CURSOR.lock().unwrap().update(input, profile);
+    moves.extend(TRACKBALL.lock().unwrap().update(input, profile));
+    // Output stage: slow mode (and the script's scale) applies whatever produced the movement
+    let script_scale = SCRIPT.lock().unwrap().as_ref().map_or(1.0, |script| script.cursor_scale());
+    let factor = LAYERS.lock().unwrap().output_scale() * script_scale;
+    let moves = OUTPUT_SCALER.lock().unwrap().scale(moves, factor);
+    // The gyro scales before rounding, so it carries its own remainder
+    let gyro = GYRO_MOUSE.lock().unwrap().update(input, profile, factor);
+    if let Some(app) = APP_HANDLE.get() {
+        for movement in moves {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_cursor(&movement));
+            } else {
+                let _ = app.emit_all(PAD_CURSOR_EVENT, &movement);
+                output::move_cursor(movement.dx, movement.dy);
+            }
+        }
+        if let Some(movement) = gyro {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_gyro_cursor(&movement));
+            } else {
+                let _ = app.emit_all(GYRO_CURSOR_EVENT, &movement);
+                output::move_cursor(movement.dx, movement.dy);
+            }
+        }
+    }
+}
+
+// Turn rim spins into steps for the app, with a detent tick per event
+fn check_edge_spin(input: &ControllerInput, profile: Option<&Profile>) {
+    let events = EDGE_SPIN.lock().unwrap().update(input, profile);
+    if events.is_empty() {
+        return;
+    }
+
+    let manager = SC_MANAGER.read().unwrap().clone();
+    for event in events {
+        if let Some(app) = APP_HANDLE.get() {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_spin(&event));
+            } else {
+                let _ = app.emit_all(PAD_SPIN_EVENT, &event);
+                output::spin(&event);
+            }
+        }
+        if let (true, Some(manager)) = (event.haptics, manager.as_ref()) {
+            if let Err(e) = manager.send_haptic_pulse(HapticPulse::detent(pad_side(event.pad))) {
+                eprintln!("❌ {}", e);
+            }
+        }
+    }
+}
+
+fn check_touch_menus(input: &ControllerInput, profile: Option<&Profile>) {
+    let events = TOUCH_MENUS.lock().unwrap().update(input, profile);
+    let manager = SC_MANAGER.read().unwrap().clone();
+    for event in events {
+        if let Some(overlay) = event.to_overlay() {
+            OVERLAY_BUS.publish(overlay);
+        }
+        let pulse = match &event {
+            TouchMenuEvent::Highlighted { pad, haptics: true, .. } => Some(HapticPulse::detent(pad_side(*pad))),
+            TouchMenuEvent::Selected(selection) => {
+                if let Some(app) = APP_HANDLE.get() {
+                    if is_dry_run() {
+                        let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_menu_selection(selection));
+                    } else {
+                        let _ = app.emit_all(TOUCH_MENU_SELECTION_EVENT, selection);
+                        output::menu_selection(selection);
+                    }
+                }
+                Some(HapticPulse::confirm(pad_side(selection.pad))).filter(|_| selection.haptics)
+            }
+            _ => None,
+        };
+        if let (Some(pulse), Some(manager)) = (pulse, manager.as_ref()) {
+            if let Err(e) = manager.send_haptic_pulse(pulse) {
+                eprintln!("❌ {}", e);
+            }
+        }
+    }
+}
+
+fn pad_side(pad: Pad) -> HapticSide {
+    match pad {
+        Pad::Left => HapticSide::Left,
+        Pad::Right => HapticSide::Right,
+    }
+}
+
+// Run one hook of the active profile's script and send out what it did
+fn run_script<F>(hook: F)
+where
+    F: FnOnce(&mut ScriptHost) -> Result<ScriptEffects, String>,
+{
+    let result = match SCRIPT.lock().unwrap().as_mut() {
+        Some(script) => hook(script),
+        None => return,
+    };
+    let app = APP_HANDLE.get();
+    let effects = match result {
+        Ok(effects) => effects,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            if let Some(app) = app {
+                let _ = app.emit_all(SCRIPT_ERROR_EVENT, &e);
+            }
+            return;
+        }
+    };
+    for line in &effects.logs {
+        println!("🔧 Script: {}", line);
+    }
+    if let Some(app) = app {
+        for output in &effects.outputs {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_script(output));
+            } else {
+                let _ = app.emit_all(SCRIPT_OUTPUT_EVENT, output);
+            }
+        }
+    }
+}
+
+// Feed the frame to every plugin allowed to read input
+fn check_plugins(input: &ControllerInput) {
+    let mut plugins = PLUGINS.lock().unwrap();
+    for plugin in plugins.iter_mut() {
+        let result = plugin.on_frame(input);
+        send_plugin_effects(&plugin.manifest().name, result);
+    }
+}
+
+// A binding fired one of a plugin's actions
+fn run_plugin_action(name: &str, action: &str, event: &BindingEvent) {
+    let mut plugins = PLUGINS.lock().unwrap();
+    match plugins.iter_mut().find(|plugin| plugin.manifest().name == name && plugin.provides(action)) {
+        Some(plugin) => {
+            let result = plugin.on_action(event);
+            send_plugin_effects(name, result);
+        }
+        None => eprintln!("❌ No plugin provides {}: {}", name, action),
+    }
+}
+
+fn send_plugin_effects(name: &str, result: Result<PluginEffects, String>) {
+    let effects = match result {
+        Ok(effects) => effects,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return;
+        }
+    };
+    for line in &effects.logs {
+        println!("🔧 Plugin {}: {}", name, line);
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        for emit in effects.emits {
+            if is_dry_run() {
+                let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview_plugin(name, &emit));
+            } else {
+                let output = PluginOutput { plugin: name.to_string(), emit };
+                let _ = app.emit_all(PLUGIN_OUTPUT_EVENT, &output);
+            }
+        }
+    }
+}
+
+fn send_to_hook(message: &HookMessage) {
+    if let Some(hook) = HOOK_PROCESS.lock().unwrap().as_ref() {
+        hook.send(message);
+    }
+}
+
+fn on_hook_reply(generation: u64, reply: HookReply) {
+    let app = APP_HANDLE.get();
+    match reply {
+        HookReply::Command(HookCommand::Log { message }) => println!("🔧 Hook: {}", message),
+        HookReply::Command(command) => {
+            if let Some(app) = app {
+                if !is_dry_run() {
+                    let _ = app.emit_all(HOOK_ACTION_EVENT, &command);
+                } else if let Some(preview) = preview_hook(&command) {
+                    let _ = app.emit_all(MAPPING_PREVIEW_EVENT, preview);
+                }
+            }
+        }
+        HookReply::Invalid { line, error } => eprintln!("❌ Hook process sent {:?}: {}", line, error),
+        HookReply::Exited => {
+            let mut hook = HOOK_PROCESS.lock().unwrap();
+            if HOOK_GENERATION.load(Ordering::Acquire) != generation {
+                return;
+            }
+            *hook = None;
+            eprintln!("❌ Hook process exited");
+            if let Some(app) = app {
+                let _ = app.emit_all(HOOK_EXITED_EVENT, ());
+            }
+        }
+    }
+}
+
+// Compare a raw (unmirrored) frame with the reference driver, if verifying
+#[cfg(feature = "gilrs-verify")]
+fn check_verification(input: &ControllerInput) {
+    let mut verification = VERIFICATION.lock().unwrap();
+    let (reader, verifier) = match verification.as_mut() {
+        Some(v) => v,
+        None => return,
+    };
+    // Nothing to compare until the driver sees the controller
+    let reference = match reader.latest() {
+        Some(reference) => reference,
+        None => return,
+    };
+    for mismatch in verifier.update(input, &reference) {
+        println!("🔍 Parser mismatch: {:?}", mismatch);
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(VERIFICATION_MISMATCH_EVENT, &mismatch);
+        }
+    }
+}
+
+// Feed a frame to the self-test (if running) and report progress
+fn check_self_test(input: &ControllerInput) {
+    let mut self_test = SELF_TEST.lock().unwrap();
+    let test = match self_test.as_mut() {
+        Some(test) => test,
+        None => return,
+    };
+    let done = test.results().len();
+    if !test.update(input) {
+        return;
+    }
+    if let Some(result) = test.results().get(done) {
+        println!("🔍 Self-test {:?}: {:?} ({})", result.control, result.outcome, result.detail);
+    }
+    let status = test.status();
+    drop(self_test);
+
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(SELF_TEST_EVENT, &status);
+    }
+}
+
+// Feed a frame to the on-screen keyboard (if open) and forward what it produced
+fn check_keyboard(input: &ControllerInput) {
+    let mut keyboard = KEYBOARD.lock().unwrap();
+    let update = match keyboard.as_mut() {
+        Some(wheel) => wheel.update(input),
+        None => return,
+    };
+    if update.closed {
+        *keyboard = None;
+        println!("⌨️ On-screen keyboard closed");
+    }
+    drop(keyboard);
+
+    for event in update.events {
+        OVERLAY_BUS.publish(event);
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        for action in &update.actions {
+            let _ = app.emit_all(KEYBOARD_KEY_EVENT, action);
+        }
+    }
+}
+
+/// Overlay events describing the current state (active profile, layer, open menu, keyboard)
+#[tauri::command]
+pub fn get_overlay_state() -> Vec<OverlayEvent> {
+    OVERLAY_BUS.snapshot()
+}
+
+/// Current overlay state followed by live overlay events, for the IPC transports
+#[cfg(any(windows, target_os = "linux"))]
+pub fn subscribe_overlay() -> mpsc::Receiver<OverlayEvent> {
+    OVERLAY_BUS.subscribe()
+}
+
+#[derive(Serialize)]
+pub struct MappingToggleBindings {
+    pub hotkey: Option<String>,
+    pub chord: Vec<ButtonId>,
+}
+
+#[tauri::command]
+pub fn is_mapping_enabled() -> bool {
+    hotkeys::is_mapping_enabled()
+}
+
+#[tauri::command]
+pub fn set_mapping_enabled(enabled: bool) {
+    hotkeys::set_mapping_enabled(enabled)
+}
+
+#[tauri::command]
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Acquire)
+}
+
+/// Preview a profile on live input: bindings, cursor and spin output become
+/// `mapping-preview` events and nothing reaches the OS; the virtual pad is
+/// held at neutral. Haptics still play.
+#[tauri::command]
+pub fn set_dry_run(enabled: bool) {
+    if DRY_RUN.swap(enabled, Ordering::AcqRel) != enabled {
+        println!("🎮 Dry run {}", if enabled { "on" } else { "off" });
+        // Releases from here on only reach the preview
+        if enabled {
+            output::release_all();
+            virtual_gamepad::neutral();
+        }
+    }
+}
+
+/// Drive one trackpad actuator: `count` pulses of `amplitude`, `period` µs
+/// apart (feature report 0x8f). Queued, so this doesn't wait for the write.
+#[tauri::command]
+pub fn send_haptic_pulse(side: HapticSide, amplitude: u16, period: u16, count: u16) -> Result<(), String> {
+    connected_manager()?.send_haptic_pulse(HapticPulse { side, amplitude, period, count })
+}
+
+/// Play the standard test buzz on one actuator; returns whether the write succeeded
+#[tauri::command]
+pub fn test_haptics(side: HapticSide, strength: f32) -> bool {
+    let result = current_manager().and_then(|m| {
+        with_command_timeout("Haptics test", move || m.send_haptic_pulse(HapticPulse::test_buzz(side, strength)))
+    });
+
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("❌ Haptics test failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Play one of the controller's built-in melodies (0-15). Queued like a tone.
+#[tauri::command]
+pub fn play_melody(id: u8) -> Result<(), String> {
+    connected_manager()?.play_melody(id)
+}
+
+/// Sound a tone on one actuator: `frequency` in Hz (20-4000), `duration` in
+/// ms (up to 10 s). Queued like a haptic pulse; a newer tone on the same side
+/// replaces one still waiting.
+#[tauri::command]
+pub fn play_tone(side: HapticSide, frequency: u32, duration: u32) -> Result<(), String> {
+    connected_manager()?.play_tone(Tone { side, frequency_hz: frequency, duration_ms: duration })
+}
+
+/// Choose the melodies the controller plays when it turns on and off. Both
+/// are needed, since the current ones can't be read back. Stored on the
+/// controller; queued like a tone.
+#[tauri::command]
+pub fn set_jingles(startup: u8, shutdown: u8) -> Result<(), String> {
+    connected_manager()?.set_jingles(Jingles { startup, shutdown })
+}
+
+/// Play a force-feedback update through the active profile's rumble tuning,
+/// so curves can be tried out while editing them
+#[tauri::command]
+pub fn test_rumble(command: RumbleCommand) -> Result<(), String> {
+    let tuning = ACTIVE_PROFILE.lock().unwrap().as_ref().map(|p| p.rumble).unwrap_or_default();
+    let m = current_manager()?;
+    let pulses = tuning.translate(&command);
+    with_command_timeout("Rumble test", move || pulses.into_iter().try_for_each(|pulse| m.send_haptic_pulse(pulse)))
+}
+
+/// Points along a curve as the backend evaluates it, for the curve editor
+#[tauri::command]
+pub fn sample_curve(curve: Curve, samples: Option<usize>) -> Vec<(f32, f32)> {
+    curve.sample(samples.unwrap_or(DEFAULT_CURVE_SAMPLES).min(MAX_CURVE_SAMPLES))
+}
+
+/// Pause/resume all mapping output; returns the new state
+#[tauri::command]
+pub fn toggle_mapping() -> bool {
+    hotkeys::toggle_mapping()
+}
+
+#[tauri::command]
+pub fn get_mapping_toggle_bindings() -> MappingToggleBindings {
+    MappingToggleBindings {
+        hotkey: hotkeys::toggle_hotkey(),
+        chord: hotkeys::toggle_chord(),
+    }
+}
+
+/// System-wide hotkey (e.g. "CmdOrCtrl+Shift+F12"); `None` removes it
+#[tauri::command]
+pub fn set_toggle_hotkey(accelerator: Option<String>) -> Result<(), String> {
+    hotkeys::set_toggle_hotkey(accelerator)
+}
+
+/// Controller chord (at least two buttons held together)
+#[tauri::command]
+pub fn set_toggle_chord(buttons: Vec<ButtonId>) -> Result<(), String> {
+    hotkeys::set_toggle_chord(buttons)
+}
+
+// Chords the app handles before any profile binding
+fn reserved_chords() -> Vec<ReservedChord> {
+    vec![
+        ReservedChord {
+            name: "mapping toggle".to_string(),
+            buttons: hotkeys::toggle_chord(),
+        },
+        ReservedChord {
+            name: "profile switch".to_string(),
+            buttons: vec![PROFILE_SWITCH.lock().unwrap().config().modifier, ButtonId::LpadClick],
+        },
+    ]
+}
+
+/// Bindings in `profile` that never fire or collide with reserved chords, for the editor
+#[tauri::command]
+pub fn check_profile_conflicts(profile: Profile) -> Vec<MappingWarning> {
+    detect_conflicts(&profile, &reserved_chords())
+}
+
+// Compile the profile's script; a broken script is reported and skipped
+fn load_script(profile: &Profile) -> Option<ScriptHost> {
+    let source = profile.script.as_ref()?;
+    match ScriptHost::compile(source) {
+        Ok(script) => Some(script),
+        Err(e) => {
+            eprintln!("❌ Profile '{}': {}", profile.name, e);
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit_all(SCRIPT_ERROR_EVENT, &e);
+            }
+            None
+        }
+    }
+}
+
+/// Compile a profile script without running it, for the editor
+#[tauri::command]
+pub fn check_profile_script(source: String) -> Result<(), String> {
+    ScriptHost::compile(&source).map(|_| ())
+}
+
+fn activate_profile(profile: Profile) {
+    println!("🎮 Active profile: {}", profile.name);
+    *SCRIPT.lock().unwrap() = load_script(&profile);
+    let conflicts = detect_conflicts(&profile, &reserved_chords());
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            eprintln!("⌨️ Binding conflict: {}", conflict.message);
+        }
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(PROFILE_CONFLICTS_EVENT, &conflicts);
+        }
+    }
+    OVERLAY_BUS.publish(OverlayEvent::ProfileActivated {
+        profile: profile.name.clone(),
+        bindings: describe_bindings(&profile),
+    });
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(PROFILE_CHANGED_EVENT, &profile);
+    }
+    exclusive::set_requested(profile.exclusive);
+    recovery::update(|state| state.active_profile = Some(profile.name.clone()));
+    *ACTIVE_PROFILE.lock().unwrap() = Some(profile);
+    // The old bindings' releases never come once the layer engine is replaced
+    *LAYERS.lock().unwrap() = LayerEngine::new();
+    output::release_all();
+}
+
+// Switch profiles when the reserved chord fires, confirming with a haptic tick
+fn check_profile_switch(input: &ControllerInput) {
+    let target = match PROFILE_SWITCH.lock().unwrap().update(input) {
+        Some(target) => target,
+        None => return,
+    };
+    let app = match APP_HANDLE.get() {
+        Some(app) => app,
+        None => return,
+    };
+
+    let profiles = match profiles_dir(app).and_then(|dir| profiles::load_profiles(&dir)) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            eprintln!("❌ Profile switch failed: {}", e);
+            return;
+        }
+    };
+    let active = ACTIVE_PROFILE.lock().unwrap().as_ref().map(|p| p.name.clone());
+    let profile = match resolve_target(&target, &profiles, active.as_deref()) {
+        Some(profile) => profile.clone(),
+        None => {
+            eprintln!("❌ Profile switch: no profile for {:?}", target);
+            return;
+        }
+    };
+    activate_profile(profile);
+
+    let manager = SC_MANAGER.read().unwrap().clone();
+    if let Some(manager) = manager {
+        if let Err(e) = manager.send_haptic_pulse(HapticPulse::confirm(HapticSide::Left)) {
+            eprintln!("❌ {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_active_profile() -> Option<Profile> {
+    ACTIVE_PROFILE.lock().unwrap().clone()
+}
+
+/// Saved profiles, sorted by name
+#[tauri::command]
+pub fn list_profiles(app: tauri::AppHandle) -> Result<Vec<Profile>, String> {
+    profiles::load_profiles(&profiles_dir(&app)?)
+}
+
+/// Save (or overwrite) a profile by name. Saving the active profile applies
+/// the new version right away.
+#[tauri::command]
+pub fn save_profile(app: tauri::AppHandle, profile: Profile) -> Result<Profile, String> {
+    // The same checks as loading it back
+    let profile = Profile::from_json(&profile.to_json()?)?;
+    let path = profiles::save_profile(&profiles_dir(&app)?, &profile)?;
+    println!("✅ Saved profile '{}' to {}", profile.name, path.display());
+
+    let active = ACTIVE_PROFILE.lock().unwrap().as_ref().map_or(false, |p| p.name == profile.name);
+    if active {
+        activate_profile(profile.clone());
+    }
+    Ok(profile)
+}
+
+// Change one source's response in the active profile and save it. The live
+// profile is updated in place rather than reactivated, so held bindings and
+// layers carry on while a slider is dragged.
+fn update_response(app: &tauri::AppHandle, source: AnalogSource, f: impl FnOnce(&mut AnalogResponse)) -> Result<Profile, String> {
+    let mut profile = ACTIVE_PROFILE.lock().unwrap().clone().ok_or("No active profile")?;
+    let mut response = profile.response.get(&source).cloned().unwrap_or_default();
+    f(&mut response);
+    response.validate()?;
+    profile.response.insert(source, response.clone());
+    profiles::save_profile(&profiles_dir(app)?, &profile)?;
+    println!("🕹️ {:?} response: {:?}", source, response);
+
+    let profile = {
+        let mut active = ACTIVE_PROFILE.lock().unwrap();
+        let active = active.as_mut().filter(|active| active.name == profile.name).ok_or("No active profile")?;
+        active.response.insert(source, response);
+        active.clone()
+    };
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_all(PROFILE_CHANGED_EVENT, &profile);
+    }
+    Ok(profile)
+}
+
+/// Set the active profile's dead zones for the stick, a pad or a trigger, as
+/// fractions of full deflection (`outer` defaults to 1)
+#[tauri::command]
+pub fn set_deadzone(app: tauri::AppHandle, source: AnalogSource, inner: f32, outer: Option<f32>) -> Result<Profile, String> {
+    update_response(&app, source, |response| {
+        response.inner_deadzone = inner;
+        response.outer_deadzone = outer.unwrap_or(1.0);
+    })
+}
+
+/// Set the active profile's response curve for the stick, a pad or a trigger
+#[tauri::command]
+pub fn set_response_curve(app: tauri::AppHandle, source: AnalogSource, curve: ResponseCurve) -> Result<Profile, String> {
+    update_response(&app, source, |response| response.curve = curve)
+}
+
+/// A saved profile (or the bundled desktop profile) by name, without activating it
+#[tauri::command]
+pub fn load_profile(app: tauri::AppHandle, name: String) -> Result<Profile, String> {
+    profiles::load_profiles(&profiles_dir(&app)?)?
+        .into_iter()
+        .chain(profiles::desktop_profile())
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Profile '{}' not found", name))
+}
+
+/// Activate a saved profile by name, or clear the active one with `null`. The
+/// choice is saved and activated again at the next launch.
+#[tauri::command]
+pub fn set_active_profile(name: Option<String>) -> Result<Option<Profile>, String> {
+    match &name {
+        Some(name) => activate_saved_profile(name)?,
+        None => {
+            clear_active_profile();
+        }
+    }
+    settings::update(|s| s.active_profile = name)?;
+    Ok(ACTIVE_PROFILE.lock().unwrap().clone())
+}
+
+/// Activate the profile picked with `set_active_profile` last run, if any
+pub fn activate_startup_profile() {
+    if let Some(name) = settings::get().active_profile {
+        if let Err(e) = activate_saved_profile(&name) {
+            eprintln!("❌ Could not activate profile '{}': {}", name, e);
+        }
+    }
+}
+
+/// Activate the desktop profile if desktop mode is on and nothing else is active
+pub fn activate_desktop_fallback() {
+    if !settings::get().desktop_mode || ACTIVE_PROFILE.lock().unwrap().is_some() {
+        return;
+    }
+    match profiles::desktop_profile() {
+        Some(profile) => activate_profile(profile),
+        None => eprintln!("❌ Bundled desktop profile is missing"),
+    }
+}
+
+/// Drop the active profile (e.g. the game exited); desktop mode takes over if enabled
+#[tauri::command]
+pub fn clear_active_profile() -> Option<Profile> {
+    *ACTIVE_PROFILE.lock().unwrap() = None;
+    *LAYERS.lock().unwrap() = LayerEngine::new();
+    output::release_all();
+    recovery::update(|state| state.active_profile = None);
+    *SCRIPT.lock().unwrap() = None;
+    activate_desktop_fallback();
+
+    let active = ACTIVE_PROFILE.lock().unwrap().clone();
+    if active.is_none() {
+        exclusive::set_requested(false);
+        println!("🎮 No active profile");
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_all(PROFILE_CHANGED_EVENT, Option::<Profile>::None);
+        }
+    }
+    active
+}
+
+/// Whether the controller is currently hidden from other processes
+#[tauri::command]
+pub fn get_exclusive_status() -> ExclusiveStatus {
+    exclusive::status()
+}
+
+/// Turning desktop mode off also drops the desktop profile if it is the active one
+#[tauri::command]
+pub fn set_desktop_mode(enabled: bool) -> Result<AppSettings, String> {
+    let settings = settings::update(|s| s.desktop_mode = enabled)?;
+    if enabled {
+        activate_desktop_fallback();
+    } else if *ACTIVE_PROFILE.lock().unwrap() == profiles::desktop_profile() {
+        clear_active_profile();
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_profile_switch_config() -> ProfileSwitchConfig {
+    PROFILE_SWITCH.lock().unwrap().config().clone()
+}
+
+/// Which d-pad directions cycle or jump between profiles while the modifier is held
+#[tauri::command]
+pub fn set_profile_switch_config(config: ProfileSwitchConfig) {
+    *PROFILE_SWITCH.lock().unwrap() = ProfileSwitchChord::new(config);
+}
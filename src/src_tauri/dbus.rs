@@ -0,0 +1,110 @@
+//! D-Bus service (`org.ctrlspace.Controller1` on the session bus) so desktop
+//! environments, status bars and scripts can query and control the controller
+//! without going through the GUI. Every method delegates to the Tauri command
+//! of the same name, so both surfaces behave identically. Overlay events are
+//! broadcast as the `OverlayEvent` signal carrying the event's JSON.
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use once_cell::sync::OnceCell;
+use zbus::blocking::connection;
+use zbus::{interface, SignalContext};
+use super::commands;
+
+pub const BUS_NAME: &str = "org.ctrlspace.Controller1";
+pub const OBJECT_PATH: &str = "/org/ctrlspace/Controller1";
+
+// How often connection state is checked for PropertiesChanged signals
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// Keeps the bus connection (and the exported object) alive for the app lifetime
+static DBUS_CONNECTION: OnceCell<connection::Connection> = OnceCell::new();
+
+struct ControllerService;
+
+#[interface(name = "org.ctrlspace.Controller1")]
+impl ControllerService {
+    #[zbus(property)]
+    fn connected(&self) -> bool {
+        commands::is_steam_controller_connected()
+    }
+
+    /// Controller info (connection_type, product_name, serial); empty if none attached
+    fn detect(&self) -> HashMap<String, String> {
+        let mut out = HashMap::new();
+        if let Some(info) = commands::detect_steam_controller() {
+            out.insert("connection_type".to_string(), info.connection_type);
+            out.insert("product_name".to_string(), info.product_name);
+            out.insert("serial".to_string(), info.serial);
+        }
+        out
+    }
+
+    /// Returns the connected product name
+    fn connect(&self) -> zbus::fdo::Result<String> {
+        commands::connect_steam_controller(None)
+            .map(|info| info.product_name)
+            .map_err(zbus::fdo::Error::Failed)
+    }
+
+    fn disconnect(&self) -> bool {
+        commands::disconnect_steam_controller()
+    }
+
+    /// JSON-encoded `ctrlspace_core::overlay::OverlayEvent`
+    #[zbus(signal)]
+    async fn overlay_event(ctxt: &SignalContext<'_>, event: &str) -> zbus::Result<()>;
+}
+
+/// Register the service on the session bus and start emitting property changes
+pub fn start() -> zbus::Result<()> {
+    let conn = connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, ControllerService)?
+        .build()?;
+
+    let conn = DBUS_CONNECTION.get_or_init(|| conn);
+    println!("✅ D-Bus service registered as {}", BUS_NAME);
+
+    thread::spawn(move || {
+        let mut last_connected = commands::is_steam_controller_connected();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+
+            let connected = commands::is_steam_controller_connected();
+            if connected == last_connected {
+                continue;
+            }
+            last_connected = connected;
+
+            match conn.object_server().interface::<_, ControllerService>(OBJECT_PATH) {
+                Ok(iface) => {
+                    let ctxt = iface.signal_context().to_owned();
+                    if let Err(e) = zbus::block_on(iface.get().connected_changed(&ctxt)) {
+                        eprintln!("❌ Failed to emit D-Bus property change: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("❌ D-Bus interface lookup failed: {}", e),
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        for event in commands::subscribe_overlay() {
+            let json = match serde_json::to_string(&event) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            let ctxt = match SignalContext::new(conn.inner(), OBJECT_PATH) {
+                Ok(ctxt) => ctxt,
+                Err(_) => continue,
+            };
+            if let Err(e) = zbus::block_on(ControllerService::overlay_event(&ctxt, &json)) {
+                eprintln!("❌ Failed to emit D-Bus overlay event: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+//! Keeps other processes away from the controller while a profile with
+//! `exclusive` set is active (see `ctrlspace_core::exclusive`). The grab
+//! follows both the active profile and the connection: it is taken when an
+//! exclusive profile meets a connected controller and released when either
+//! goes away. Taking and releasing it can mean running HidHideCLI, so that
+//! happens on a worker thread rather than on the caller's (input) thread.
+
+use once_cell::sync::Lazy;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use ctrlspace_core::exclusive::ExclusiveStatus;
+
+#[derive(Default)]
+struct State {
+    /// HID path of the connected controller
+    path: Option<String>,
+    requested: bool,
+    /// The grab and the HID path it was taken for
+    grab: Option<(String, platform::Grab)>,
+    error: Option<String>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+static WORKER: Lazy<Mutex<Sender<()>>> = Lazy::new(|| {
+    let (tx, rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        while rx.recv().is_ok() {
+            // A burst of changes only needs the latest state applied
+            while rx.try_recv().is_ok() {}
+            sync(&mut STATE.lock().unwrap());
+        }
+    });
+    Mutex::new(tx)
+});
+
+fn schedule_sync() {
+    let _ = WORKER.lock().unwrap().send(());
+}
+
+/// The connected controller changed (`None` on disconnect)
+pub fn set_device(path: Option<String>) {
+    STATE.lock().unwrap().path = path;
+    schedule_sync();
+}
+
+/// Whether the active profile asks for exclusive access
+pub fn set_requested(requested: bool) {
+    STATE.lock().unwrap().requested = requested;
+    schedule_sync();
+}
+
+/// Release the grab before the app exits; blocks until it is undone
+pub fn shutdown() {
+    let mut state = STATE.lock().unwrap();
+    state.requested = false;
+    release(&mut state);
+}
+
+pub fn status() -> ExclusiveStatus {
+    let state = STATE.lock().unwrap();
+    ExclusiveStatus {
+        requested: state.requested,
+        active: state.grab.is_some(),
+        mechanism: Some(platform::MECHANISM.to_string()).filter(|m| !m.is_empty()),
+        devices: state.grab.as_ref().map(|(_, g)| g.devices()).unwrap_or_default(),
+        error: state.error.clone(),
+    }
+}
+
+fn sync(state: &mut State) {
+    let wanted = state.path.clone().filter(|_| state.requested);
+    // A different unit needs its own grab
+    if matches!(&state.grab, Some((path, _)) if Some(path) != wanted.as_ref()) {
+        release(state);
+    }
+    if let (Some(path), None) = (wanted, &state.grab) {
+        match platform::Grab::acquire(&path) {
+            Ok(grab) => {
+                println!("🔒 Exclusive access via {}: {:?}", platform::MECHANISM, grab.devices());
+                state.grab = Some((path, grab));
+                state.error = None;
+            }
+            Err(e) => {
+                eprintln!("❌ Exclusive access unavailable: {}", e);
+                state.error = Some(e);
+            }
+        }
+    }
+}
+
+fn release(state: &mut State) {
+    if let Some((_, grab)) = state.grab.take() {
+        match grab.release() {
+            Ok(()) => println!("🔓 Exclusive access released"),
+            Err(e) => eprintln!("❌ Failed to release exclusive access: {}", e),
+        }
+    }
+    state.error = None;
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::path::PathBuf;
+    use std::process::Command;
+    use ctrlspace_core::exclusive::hid_instance_id;
+
+    pub const MECHANISM: &str = "hidhide";
+
+    const DEFAULT_CLI: &str = r"C:\Program Files\Nefarius Software Solutions\HidHide\x64\HidHideCLI.exe";
+
+    /// A device hidden with HidHide
+    pub struct Grab {
+        instance: String,
+        /// Path registered with `--app-reg`
+        exe: String,
+    }
+
+    fn cli() -> PathBuf {
+        let installed = PathBuf::from(DEFAULT_CLI);
+        if installed.exists() {
+            installed
+        } else {
+            PathBuf::from("HidHideCLI.exe")
+        }
+    }
+
+    fn run(args: &[&str]) -> Result<(), String> {
+        let output = Command::new(cli())
+            .args(args)
+            .output()
+            .map_err(|e| format!("HidHide is not installed ({})", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!("HidHideCLI {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()))
+        }
+    }
+
+    impl Grab {
+        pub fn acquire(path: &str) -> Result<Self, String> {
+            let instance = hid_instance_id(path).ok_or_else(|| format!("Not a HID device path: {}", path))?;
+            // CtrlSpace itself must keep seeing the device
+            let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+            let exe = exe.to_string_lossy().into_owned();
+            run(&["--app-reg", &exe])?;
+            let grab = Self { instance, exe };
+            // Undo whatever got through if a later step fails
+            if let Err(e) = run(&["--dev-hide", &grab.instance]).and_then(|()| run(&["--cloak-on"])) {
+                let _ = grab.release();
+                return Err(e);
+            }
+            Ok(grab)
+        }
+
+        pub fn devices(&self) -> Vec<String> {
+            vec![self.instance.clone()]
+        }
+
+        /// Turns cloaking off and unregisters CtrlSpace too, so nothing is
+        /// left behind in HidHide's (persistent) configuration
+        pub fn release(self) -> Result<(), String> {
+            let unhidden = run(&["--dev-unhide", &self.instance]);
+            let uncloaked = run(&["--cloak-off"]);
+            let unregistered = run(&["--app-unreg", &self.exe]);
+            unhidden.and(uncloaked).and(unregistered)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs::{self, File};
+    use std::os::raw::{c_int, c_ulong};
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use ctrlspace_core::exclusive::is_controller_ids;
+
+    pub const MECHANISM: &str = "evdev-grab";
+
+    // _IOW('E', 0x90, int)
+    const EVIOCGRAB: c_ulong = 0x4004_4590;
+
+    /// Grabbed evdev nodes; closing them releases the grab too
+    pub struct Grab {
+        nodes: Vec<(String, File)>,
+    }
+
+    fn grab(file: &File, on: bool) -> Result<(), String> {
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGRAB as _, on as c_int) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+
+    // evdev nodes (event*) whose device is a Steam Controller
+    fn controller_nodes() -> Vec<String> {
+        let read = |dir: &Path, name: &str| fs::read_to_string(dir.join("device/id").join(name)).unwrap_or_default();
+        let mut nodes: Vec<String> = fs::read_dir("/sys/class/input")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+                    .filter(|entry| is_controller_ids(&read(&entry.path(), "vendor"), &read(&entry.path(), "product")))
+                    .map(|entry| format!("/dev/input/{}", entry.file_name().to_string_lossy()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        nodes.sort();
+        nodes
+    }
+
+    impl Grab {
+        // The hidraw node itself can't be opened exclusively; what leaks is the
+        // Lizard Mode keyboard/mouse, so those are taken away from everyone else
+        pub fn acquire(_path: &str) -> Result<Self, String> {
+            let mut nodes = Vec::new();
+            for node in controller_nodes() {
+                let file = File::open(&node).map_err(|e| format!("Failed to open {}: {}", node, e))?;
+                grab(&file, true).map_err(|e| format!("Failed to grab {}: {}", node, e))?;
+                nodes.push((node, file));
+            }
+            if nodes.is_empty() {
+                return Err("No controller input devices found to grab".to_string());
+            }
+            Ok(Self { nodes })
+        }
+
+        pub fn devices(&self) -> Vec<String> {
+            self.nodes.iter().map(|(node, _)| node.clone()).collect()
+        }
+
+        pub fn release(self) -> Result<(), String> {
+            self.nodes
+                .iter()
+                .try_for_each(|(node, file)| grab(file, false).map_err(|e| format!("{}: {}", node, e)))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    pub const MECHANISM: &str = "exclusive-open";
+
+    /// hidapi opens devices exclusively on macOS (we don't enable its
+    /// `macos-shared-device` feature), so there is nothing to add
+    pub struct Grab;
+
+    impl Grab {
+        pub fn acquire(_path: &str) -> Result<Self, String> {
+            Ok(Self)
+        }
+
+        pub fn devices(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        pub fn release(self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+mod platform {
+    pub const MECHANISM: &str = "";
+
+    pub struct Grab;
+
+    impl Grab {
+        pub fn acquire(_path: &str) -> Result<Self, String> {
+            Err("Exclusive access isn't supported on this platform".to_string())
+        }
+
+        pub fn devices(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        pub fn release(self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Panic switch for mapping output: a system-wide hotkey and a controller
+//! chord both toggle it, with a notification and a `mapping-toggled` event
+//! so the user always knows which state they're in.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use tauri::api::notification::Notification;
+use tauri::{GlobalShortcutManager, Manager};
+use super::commands::app_handle;
+use ctrlspace_core::chord::{ChordDetector, DEFAULT_TOGGLE_CHORD};
+use ctrlspace_core::input_parser::ButtonState;
+use ctrlspace_core::profiles::ButtonId;
+
+pub const DEFAULT_TOGGLE_HOTKEY: &str = "CmdOrCtrl+Shift+F12";
+
+/// Emitted to all windows with the new enabled state
+pub const MAPPING_TOGGLED_EVENT: &str = "mapping-toggled";
+
+static MAPPING_ENABLED: AtomicBool = AtomicBool::new(true);
+
+static TOGGLE_HOTKEY: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+static TOGGLE_CHORD: Lazy<Mutex<ChordDetector>> =
+    Lazy::new(|| Mutex::new(ChordDetector::new(DEFAULT_TOGGLE_CHORD.to_vec()).unwrap()));
+
+/// Register the default hotkey (after `commands::init`)
+pub fn init() -> Result<(), String> {
+    set_toggle_hotkey(Some(DEFAULT_TOGGLE_HOTKEY.to_string()))
+}
+
+/// Whether mapping output should currently be produced
+pub fn is_mapping_enabled() -> bool {
+    MAPPING_ENABLED.load(Ordering::Acquire)
+}
+
+pub fn set_mapping_enabled(enabled: bool) {
+    if MAPPING_ENABLED.swap(enabled, Ordering::AcqRel) == enabled {
+        return;
+    }
+    let message = if enabled { "Mapping resumed" } else { "Mapping paused" };
+    println!("🎮 {}", message);
+
+    if let Some(app) = app_handle() {
+        let _ = app.emit_all(MAPPING_TOGGLED_EVENT, enabled);
+
+        if let Err(e) = Notification::new(&app.config().tauri.bundle.identifier)
+            .title("CtrlSpace")
+            .body(message)
+            .show()
+        {
+            eprintln!("❌ Failed to show notification: {}", e);
+        }
+    }
+}
+
+pub fn toggle_mapping() -> bool {
+    let enabled = !is_mapping_enabled();
+    set_mapping_enabled(enabled);
+    enabled
+}
+
+pub fn toggle_hotkey() -> Option<String> {
+    TOGGLE_HOTKEY.lock().unwrap().clone()
+}
+
+/// Replace the system-wide toggle hotkey (`None` removes it). Accelerators use
+/// Tauri's format, e.g. "CmdOrCtrl+Shift+F12".
+pub fn set_toggle_hotkey(accelerator: Option<String>) -> Result<(), String> {
+    let app = app_handle().ok_or_else(|| "Hotkeys not initialized".to_string())?;
+    let mut shortcuts = app.global_shortcut_manager();
+    let mut current = TOGGLE_HOTKEY.lock().unwrap();
+
+    if let Some(old) = current.take() {
+        if let Err(e) = shortcuts.unregister(&old) {
+            eprintln!("❌ Failed to unregister hotkey {}: {}", old, e);
+        }
+    }
+
+    if let Some(accelerator) = accelerator {
+        shortcuts
+            .register(&accelerator, || {
+                toggle_mapping();
+            })
+            .map_err(|e| format!("Failed to register hotkey {}: {}", accelerator, e))?;
+        println!("✅ Mapping toggle hotkey: {}", accelerator);
+        *current = Some(accelerator);
+    }
+
+    Ok(())
+}
+
+pub fn toggle_chord() -> Vec<ButtonId> {
+    TOGGLE_CHORD.lock().unwrap().buttons().to_vec()
+}
+
+pub fn set_toggle_chord(buttons: Vec<ButtonId>) -> Result<(), String> {
+    *TOGGLE_CHORD.lock().unwrap() = ChordDetector::new(buttons)?;
+    Ok(())
+}
+
+/// Feed every input frame through the controller chord
+pub fn check_chord(buttons: &ButtonState) {
+    let fired = TOGGLE_CHORD.lock().unwrap().update(buttons);
+    if fired {
+        toggle_mapping();
+    }
+}
@@ -0,0 +1,350 @@
+//! Device-change notifications from the OS, so plugging or unplugging a
+//! controller is noticed immediately instead of on the next enumeration:
+//! - Linux: uevents from the netlink socket udev monitors (hidraw add/remove)
+//! - Windows: `WM_DEVICECHANGE` for the HID interface class, on a message-only window
+//! - macOS: IOKit HID manager matching/removal callbacks
+//!
+//! Each platform source only signals "something changed"; the handler then
+//! refreshes the shared device list once per burst.
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use super::commands;
+
+/// One plug-in produces a notification per interface; wait for the burst to end
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+/// Start the platform watcher and the thread that reacts to it
+pub fn start() -> Result<(), String> {
+    let (tx, rx) = mpsc::channel::<()>();
+    platform::spawn(tx)?;
+
+    thread::spawn(move || {
+        while rx.recv().is_ok() {
+            thread::sleep(SETTLE_TIME);
+            while rx.try_recv().is_ok() {}
+            commands::on_devices_changed();
+        }
+    });
+    println!("✅ Watching for device changes");
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::sync::mpsc::Sender;
+    use std::time::Duration;
+    use std::{io, mem, thread};
+
+    /// Kernel uevents (group 1) and udev's processed events (group 2); the
+    /// latter arrive once device permissions are set up
+    const UEVENT_GROUPS: u32 = 1 | 2;
+
+    /// Re-enumeration interval once the uevent socket has failed for good
+    const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// What to do after a failed `recv` on the uevent socket
+    #[derive(Debug, PartialEq, Eq)]
+    enum RecvFailure {
+        /// Interrupted; nothing was lost
+        Retry,
+        /// The socket buffer overflowed and events were dropped
+        Resync,
+        /// The socket is unusable
+        Fatal,
+    }
+
+    fn classify(errno: Option<i32>) -> RecvFailure {
+        match errno {
+            Some(libc::EINTR) | Some(libc::EAGAIN) => RecvFailure::Retry,
+            Some(libc::ENOBUFS) => RecvFailure::Resync,
+            _ => RecvFailure::Fatal,
+        }
+    }
+
+    pub fn spawn(tx: Sender<()>) -> Result<(), String> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            )
+        };
+        if fd < 0 {
+            return Err(format!("Failed to open uevent socket: {}", io::Error::last_os_error()));
+        }
+
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = UEVENT_GROUPS;
+        let bound = unsafe {
+            libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            let e = io::Error::last_os_error();
+            unsafe { libc::close(fd) };
+            return Err(format!("Failed to bind uevent socket: {}", e));
+        }
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            let mut failed = false;
+            loop {
+                let len = unsafe { libc::recv(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+                if len < 0 {
+                    let e = io::Error::last_os_error();
+                    match classify(e.raw_os_error()) {
+                        RecvFailure::Retry => continue,
+                        // Whatever was dropped, one full re-enumeration catches up
+                        RecvFailure::Resync => {
+                            eprintln!("⚠️ Device watcher missed events ({}), re-enumerating", e);
+                            if tx.send(()).is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        RecvFailure::Fatal => {
+                            eprintln!("❌ Device watcher failed ({}), polling every {:?} instead", e, FALLBACK_POLL_INTERVAL);
+                            failed = true;
+                            break;
+                        }
+                    }
+                }
+                if is_hidraw_change(&buf[..len as usize]) && tx.send(()).is_err() {
+                    break;
+                }
+            }
+            unsafe { libc::close(fd) };
+
+            if failed {
+                while tx.send(()).is_ok() {
+                    thread::sleep(FALLBACK_POLL_INTERVAL);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Both message formats carry NUL-separated `KEY=value` properties
+    fn is_hidraw_change(message: &[u8]) -> bool {
+        let mut hidraw = false;
+        let mut add_or_remove = false;
+        for field in message.split(|&b| b == 0) {
+            match field {
+                b"SUBSYSTEM=hidraw" => hidraw = true,
+                b"ACTION=add" | b"ACTION=remove" => add_or_remove = true,
+                _ => {}
+            }
+        }
+        hidraw && add_or_remove
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_is_hidraw_change() {
+            let kernel = b"add@/devices/pci0000:00/usb1/1-2/1-2:1.2/0003:28DE:1142.0004/hidraw/hidraw3\0ACTION=add\0SUBSYSTEM=hidraw\0DEVNAME=hidraw3\0";
+            assert!(is_hidraw_change(kernel));
+            let input = b"change@/devices/virtual/input/input7\0ACTION=change\0SUBSYSTEM=input\0";
+            assert!(!is_hidraw_change(input));
+            let bind = b"bind@/devices/.../0003:28DE:1142.0004\0ACTION=bind\0SUBSYSTEM=hid\0";
+            assert!(!is_hidraw_change(bind));
+        }
+
+        #[test]
+        fn test_classify_recv_failure() {
+            assert_eq!(classify(Some(libc::EINTR)), RecvFailure::Retry);
+            assert_eq!(classify(Some(libc::EAGAIN)), RecvFailure::Retry);
+            assert_eq!(classify(Some(libc::ENOBUFS)), RecvFailure::Resync);
+            assert_eq!(classify(Some(libc::EBADF)), RecvFailure::Fatal);
+            assert_eq!(classify(None), RecvFailure::Fatal);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::sync::mpsc::{self, Sender};
+    use std::sync::Mutex;
+    use std::{mem, ptr, thread};
+    use once_cell::sync::OnceCell;
+    use windows_sys::core::GUID;
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        RegisterDeviceNotificationW, TranslateMessage, DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE,
+        DBT_DEVTYP_DEVICEINTERFACE, DEVICE_NOTIFY_WINDOW_HANDLE, DEV_BROADCAST_DEVICEINTERFACE_W,
+        HWND_MESSAGE, MSG, WM_DEVICECHANGE, WNDCLASSW,
+    };
+
+    const GUID_DEVINTERFACE_HID: GUID = GUID::from_u128(0x4d1e55b2_f16f_11cf_88cb_001111000030);
+
+    static SENDER: OnceCell<Mutex<Sender<()>>> = OnceCell::new();
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        let event = wparam as u32;
+        if msg == WM_DEVICECHANGE && (event == DBT_DEVICEARRIVAL || event == DBT_DEVICEREMOVECOMPLETE) {
+            if let Some(tx) = SENDER.get() {
+                let _ = tx.lock().unwrap().send(());
+            }
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    pub fn spawn(tx: Sender<()>) -> Result<(), String> {
+        SENDER
+            .set(Mutex::new(tx))
+            .map_err(|_| "Device watcher already running".to_string())?;
+
+        // The window has to be created on the thread that pumps its messages
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || {
+            if let Err(e) = unsafe { create_window() } {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+            let _ = ready_tx.send(Ok(()));
+            unsafe {
+                let mut msg: MSG = mem::zeroed();
+                while GetMessageW(&mut msg, 0, 0, 0) > 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+        });
+        ready_rx
+            .recv()
+            .map_err(|_| "Device watcher thread exited".to_string())?
+    }
+
+    unsafe fn create_window() -> Result<HWND, String> {
+        let class_name: Vec<u16> = "CtrlSpaceDeviceWatcher\0".encode_utf16().collect();
+        let instance = GetModuleHandleW(ptr::null());
+
+        let mut class: WNDCLASSW = mem::zeroed();
+        class.lpfnWndProc = Some(window_proc);
+        class.hInstance = instance;
+        class.lpszClassName = class_name.as_ptr();
+        if RegisterClassW(&class) == 0 {
+            return Err("Failed to register device watcher window class".to_string());
+        }
+
+        let hwnd = CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            0,
+            instance,
+            ptr::null(),
+        );
+        if hwnd == 0 {
+            return Err("Failed to create device watcher window".to_string());
+        }
+
+        let mut filter: DEV_BROADCAST_DEVICEINTERFACE_W = mem::zeroed();
+        filter.dbcc_size = mem::size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32;
+        filter.dbcc_devicetype = DBT_DEVTYP_DEVICEINTERFACE;
+        filter.dbcc_classguid = GUID_DEVINTERFACE_HID;
+        let notify = RegisterDeviceNotificationW(
+            hwnd,
+            &filter as *const DEV_BROADCAST_DEVICEINTERFACE_W as *const c_void,
+            DEVICE_NOTIFY_WINDOW_HANDLE,
+        );
+        if notify.is_null() {
+            return Err("Failed to register for HID device notifications".to_string());
+        }
+        Ok(hwnd)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+    use std::sync::mpsc::Sender;
+    use std::{ptr, thread};
+
+    type CFStringRef = *const c_void;
+    type CFRunLoopRef = *mut c_void;
+    type IOHIDManagerRef = *mut c_void;
+    type IOReturn = i32;
+    type IOHIDDeviceCallback =
+        extern "C" fn(context: *mut c_void, result: IOReturn, sender: *mut c_void, device: *mut c_void);
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOHIDManagerCreate(allocator: *const c_void, options: u32) -> IOHIDManagerRef;
+        fn IOHIDManagerSetDeviceMatching(manager: IOHIDManagerRef, matching: *const c_void);
+        fn IOHIDManagerRegisterDeviceMatchingCallback(
+            manager: IOHIDManagerRef,
+            callback: IOHIDDeviceCallback,
+            context: *mut c_void,
+        );
+        fn IOHIDManagerRegisterDeviceRemovalCallback(
+            manager: IOHIDManagerRef,
+            callback: IOHIDDeviceCallback,
+            context: *mut c_void,
+        );
+        fn IOHIDManagerScheduleWithRunLoop(manager: IOHIDManagerRef, run_loop: CFRunLoopRef, mode: CFStringRef);
+        fn IOHIDManagerOpen(manager: IOHIDManagerRef, options: u32) -> IOReturn;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopDefaultMode: CFStringRef;
+        fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+        fn CFRunLoopRun();
+    }
+
+    extern "C" fn device_changed(context: *mut c_void, _result: IOReturn, _sender: *mut c_void, _device: *mut c_void) {
+        let tx = unsafe { &*(context as *const Sender<()>) };
+        let _ = tx.send(());
+    }
+
+    pub fn spawn(tx: Sender<()>) -> Result<(), String> {
+        thread::spawn(move || unsafe {
+            let manager = IOHIDManagerCreate(ptr::null(), 0);
+            if manager.is_null() {
+                eprintln!("❌ Device watcher unavailable: failed to create IOHIDManager");
+                return;
+            }
+            // No matching dictionary = every HID device
+            IOHIDManagerSetDeviceMatching(manager, ptr::null());
+            // Lives as long as the run loop below, i.e. the app
+            let context = Box::into_raw(Box::new(tx)) as *mut c_void;
+            IOHIDManagerRegisterDeviceMatchingCallback(manager, device_changed, context);
+            IOHIDManagerRegisterDeviceRemovalCallback(manager, device_changed, context);
+            IOHIDManagerScheduleWithRunLoop(manager, CFRunLoopGetCurrent(), kCFRunLoopDefaultMode);
+            let result = IOHIDManagerOpen(manager, 0);
+            if result != 0 {
+                eprintln!("❌ Device watcher unavailable: IOHIDManagerOpen returned {:#x}", result);
+                return;
+            }
+            CFRunLoopRun();
+        });
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+mod platform {
+    use std::sync::mpsc::Sender;
+
+    pub fn spawn(_tx: Sender<()>) -> Result<(), String> {
+        Err("Device-change notifications aren't supported on this platform".to_string())
+    }
+}
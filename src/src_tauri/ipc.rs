@@ -0,0 +1,145 @@
+//! Line-delimited JSON protocol shared by the out-of-process control surfaces
+//! (currently the Windows named pipe). One request object per line:
+//!
+//! ```text
+//! {"cmd":"status"}
+//! {"cmd":"connect"}
+//! {"cmd":"subscribe"}
+//! ```
+//!
+//! Every request gets exactly one reply, `{"ok":true,"data":...}` or
+//! `{"ok":false,"error":"..."}`. After `subscribe`, parsed frames are pushed as
+//! `{"event":"controller-input","data":{...}}` lines until `unsubscribe`.
+//! `{"cmd":"subscribe","delta":true}` pushes `controller-input-delta` lines
+//! instead, carrying only changed fields (see `ctrlspace_core::delta`).
+//! `subscribe_overlay` does the same for overlay events (`"event":"overlay"`,
+//! see `ctrlspace_core::overlay` for the payloads).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use super::commands;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum IpcRequest {
+    Status,
+    Detect,
+    Connect,
+    Disconnect,
+    Subscribe {
+        /// Send only changed fields, with periodic keyframes
+        #[serde(default)]
+        delta: bool,
+    },
+    Unsubscribe,
+    SubscribeOverlay,
+    UnsubscribeOverlay,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub fn ok(data: Value) -> Self {
+        Self { ok: true, data: Some(data), error: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, data: None, error: Some(message.into()) }
+    }
+
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_else(|_| r#"{"ok":false}"#.to_string());
+        line.push('\n');
+        line
+    }
+}
+
+/// Parse a single request line
+pub fn parse_request(line: &str) -> Result<IpcRequest, String> {
+    serde_json::from_str(line.trim()).map_err(|e| format!("Invalid request: {}", e))
+}
+
+/// Run a request that doesn't touch the connection's subscription state
+pub fn handle_request(request: &IpcRequest) -> IpcResponse {
+    match request {
+        IpcRequest::Status => IpcResponse::ok(json!({
+            "connected": commands::is_steam_controller_connected(),
+        })),
+        IpcRequest::Detect => IpcResponse::ok(json!(commands::detect_steam_controller())),
+        IpcRequest::Connect => match commands::connect_steam_controller(None) {
+            Ok(info) => IpcResponse::ok(json!(info)),
+            Err(e) => IpcResponse::error(e),
+        },
+        IpcRequest::Disconnect => IpcResponse::ok(json!(commands::disconnect_steam_controller())),
+        // Transports own the subscription; acknowledging here keeps replies uniform
+        IpcRequest::Subscribe { .. }
+        | IpcRequest::Unsubscribe
+        | IpcRequest::SubscribeOverlay
+        | IpcRequest::UnsubscribeOverlay => IpcResponse::ok(Value::Null),
+    }
+}
+
+/// Serialize a pushed event (input frame, overlay event) as a line
+pub fn event_line<T: Serialize>(event: &str, data: &T) -> String {
+    let mut line = json!({ "event": event, "data": data }).to_string();
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ctrlspace_core::input_parser::ControllerInput;
+    use ctrlspace_core::input_stream::{InputFrame, CONTROLLER_INPUT_EVENT};
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(parse_request(r#"{"cmd":"status"}"#), Ok(IpcRequest::Status));
+        assert_eq!(parse_request("  {\"cmd\":\"subscribe\"}\r\n"), Ok(IpcRequest::Subscribe { delta: false }));
+        assert_eq!(
+            parse_request(r#"{"cmd":"subscribe","delta":true}"#),
+            Ok(IpcRequest::Subscribe { delta: true })
+        );
+        assert_eq!(parse_request(r#"{"cmd":"subscribe_overlay"}"#), Ok(IpcRequest::SubscribeOverlay));
+        assert!(parse_request(r#"{"cmd":"reboot"}"#).is_err());
+        assert!(parse_request("status").is_err());
+    }
+
+    #[test]
+    fn test_response_lines() {
+        let ok = IpcResponse::ok(json!({ "connected": false })).to_line();
+        assert_eq!(ok, "{\"ok\":true,\"data\":{\"connected\":false}}\n");
+
+        let err = IpcResponse::error("Controller not connected").to_line();
+        assert_eq!(err, "{\"ok\":false,\"error\":\"Controller not connected\"}\n");
+    }
+
+    #[test]
+    fn test_status_without_controller() {
+        let response = handle_request(&IpcRequest::Status);
+        assert!(response.ok);
+        assert_eq!(response.data, Some(json!({ "connected": false })));
+    }
+
+    #[test]
+    fn test_event_line() {
+        let frame = InputFrame {
+            seq: 7,
+            coalesced: 0,
+            input: ControllerInput::default(),
+        };
+        let line = event_line(CONTROLLER_INPUT_EVENT, &frame);
+        assert!(line.ends_with('\n'));
+
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], CONTROLLER_INPUT_EVENT);
+        assert_eq!(value["data"]["seq"], 7);
+    }
+}
@@ -1,4 +1,18 @@
 pub mod commands;
 pub mod devices;
-pub mod steam_controller;
-pub mod input_parser;
\ No newline at end of file
+pub mod exclusive;
+pub mod hotkeys;
+pub mod hotplug;
+pub mod output;
+pub mod recovery;
+pub mod settings;
+pub mod steam_game;
+pub mod virtual_gamepad;
+#[cfg(feature = "gilrs-verify")]
+pub mod verify;
+#[cfg(any(windows, test))]
+pub mod ipc;
+#[cfg(target_os = "linux")]
+pub mod dbus;
+#[cfg(windows)]
+pub mod pipe_server;
\ No newline at end of file
@@ -0,0 +1,392 @@
+//! OS keyboard/mouse injection (see `ctrlspace_core::output`): SendInput on
+//! Windows, a uinput keyboard/mouse on Linux. The backend is created on first
+//! use. Bindings, gestures and cursor movement only reach it while injection
+//! is enabled and dry run is off; `test` sends regardless so a binding can be
+//! checked before turning injection on.
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use ctrlspace_core::gestures::{BoundGesture, GestureEvent};
+use ctrlspace_core::layers::{BindingEvent, BindingPhase};
+use ctrlspace_core::edge_spin::SpinEvent;
+use ctrlspace_core::output::{action_events, spin_events, tap_events, Output, OutputEvent, OutputStatus};
+use ctrlspace_core::profiles::Action;
+use ctrlspace_core::touch_menu::TouchMenuSelection;
+
+#[derive(Default)]
+struct State {
+    output: Option<Output>,
+    enabled: bool,
+    error: Option<String>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+/// Turning injection off releases anything still held and drops the device
+pub fn set_enabled(enabled: bool) {
+    let mut state = STATE.lock().unwrap();
+    state.enabled = enabled;
+    if !enabled {
+        if let Some(mut output) = state.output.take() {
+            let _ = output.release_all();
+        }
+    }
+    state.error = None;
+}
+
+pub fn status() -> OutputStatus {
+    let state = STATE.lock().unwrap();
+    OutputStatus {
+        enabled: state.enabled,
+        backend: platform::BACKEND.to_string(),
+        error: state.error.clone(),
+    }
+}
+
+// Send through the backend, creating it first if needed. A backend that fails
+// is dropped so the next call starts over; the error shows up in `status`.
+fn send_with(state: &mut State, events: &[OutputEvent]) -> Result<(), String> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    if state.output.is_none() {
+        match platform::Backend::create() {
+            Ok(backend) => {
+                println!("🔧 Keyboard/mouse output via {}", platform::BACKEND);
+                state.output = Some(Output::new(Box::new(backend)));
+            }
+            Err(e) => {
+                state.error = Some(e.clone());
+                return Err(e);
+            }
+        }
+    }
+    let result = state.output.as_mut().map_or(Ok(()), |output| output.send(events));
+    if let Err(e) = &result {
+        state.output = None;
+        state.error = Some(e.clone());
+    }
+    result
+}
+
+fn send_live(events: Result<Vec<OutputEvent>, String>) {
+    let mut state = STATE.lock().unwrap();
+    if !state.enabled {
+        return;
+    }
+    if let Err(e) = events.and_then(|events| send_with(&mut state, &events)) {
+        eprintln!("❌ Output injection failed: {}", e);
+    }
+}
+
+/// One `binding-action` event
+pub fn binding(event: &BindingEvent) {
+    send_live(action_events(&event.action, event.phase));
+}
+
+/// One bound gesture: taps press and release, tap-drags hold until they end
+pub fn gesture(bound: &BoundGesture) {
+    let action = match &bound.action {
+        Some(action) => action,
+        None => return,
+    };
+    let events = match bound.event {
+        GestureEvent::Fired(_) => tap_events(action),
+        GestureEvent::Started(_) => action_events(action, BindingPhase::Pressed),
+        GestureEvent::Ended(_) => action_events(action, BindingPhase::Released),
+    };
+    send_live(events);
+}
+
+/// A touch menu item was picked: press and release its action
+pub fn menu_selection(selection: &TouchMenuSelection) {
+    send_live(tap_events(&selection.action));
+}
+
+/// One frame of rim spinning: scroll, volume or zoom steps
+pub fn spin(event: &SpinEvent) {
+    send_live(spin_events(event));
+}
+
+/// Relative pointer movement in pixels
+pub fn move_cursor(dx: i32, dy: i32) {
+    send_live(Ok(vec![OutputEvent::Move { dx, dy }]));
+}
+
+/// Release every held key and button (controller disconnected, profile
+/// switched, dry run turned on)
+pub fn release_all() {
+    if let Some(output) = STATE.lock().unwrap().output.as_mut() {
+        let _ = output.release_all();
+    }
+}
+
+/// Press and release `action` now, enabled or not. Returns the backend used.
+pub fn test(action: &Action) -> Result<String, String> {
+    let events = tap_events(action)?;
+    if events.is_empty() {
+        return Err("This action doesn't send keyboard or mouse input".to_string());
+    }
+    send_with(&mut STATE.lock().unwrap(), &events)?;
+    Ok(platform::BACKEND.to_string())
+}
+
+#[cfg(windows)]
+mod platform {
+    use ctrlspace_core::output::{OutputBackend, OutputEvent};
+    use ctrlspace_core::profiles::MouseButton;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+    use windows_sys::Win32::UI::WindowsAndMessaging::WHEEL_DELTA;
+
+    pub const BACKEND: &str = "SendInput";
+
+    // Keys that sit in the extended block (arrows, navigation cluster, Windows key)
+    const EXTENDED_VKS: [u16; 11] = [0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x2d, 0x2e, 0x5b];
+
+    pub struct Backend;
+
+    impl Backend {
+        pub fn create() -> Result<Self, String> {
+            Ok(Backend)
+        }
+    }
+
+    fn keyboard(vk: u16, down: bool) -> INPUT {
+        let mut flags = if down { 0 } else { KEYEVENTF_KEYUP };
+        if EXTENDED_VKS.contains(&vk) {
+            flags |= KEYEVENTF_EXTENDEDKEY;
+        }
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 { ki: KEYBDINPUT { wVk: vk, wScan: 0, dwFlags: flags, time: 0, dwExtraInfo: 0 } },
+        }
+    }
+
+    fn mouse(dx: i32, dy: i32, data: i32, flags: u32) -> INPUT {
+        INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 { mi: MOUSEINPUT { dx, dy, mouseData: data, dwFlags: flags, time: 0, dwExtraInfo: 0 } },
+        }
+    }
+
+    impl OutputBackend for Backend {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn send(&mut self, events: &[OutputEvent]) -> Result<(), String> {
+            let inputs: Vec<INPUT> = events
+                .iter()
+                .map(|event| match *event {
+                    OutputEvent::Key { key, down } => keyboard(key.vk, down),
+                    OutputEvent::Button { button, down } => {
+                        let flags = match (button, down) {
+                            (MouseButton::Left, true) => MOUSEEVENTF_LEFTDOWN,
+                            (MouseButton::Left, false) => MOUSEEVENTF_LEFTUP,
+                            (MouseButton::Right, true) => MOUSEEVENTF_RIGHTDOWN,
+                            (MouseButton::Right, false) => MOUSEEVENTF_RIGHTUP,
+                            (MouseButton::Middle, true) => MOUSEEVENTF_MIDDLEDOWN,
+                            (MouseButton::Middle, false) => MOUSEEVENTF_MIDDLEUP,
+                        };
+                        mouse(0, 0, 0, flags)
+                    }
+                    OutputEvent::Scroll { amount } => mouse(0, 0, amount * WHEEL_DELTA as i32, MOUSEEVENTF_WHEEL),
+                    OutputEvent::Move { dx, dy } => mouse(dx, dy, 0, MOUSEEVENTF_MOVE),
+                })
+                .collect();
+            let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+            if sent as usize == inputs.len() {
+                Ok(())
+            } else {
+                // Blocked by UIPI, e.g. an elevated window has focus
+                Err(format!("SendInput injected {} of {} events: {}", sent, inputs.len(), std::io::Error::last_os_error()))
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::os::raw::{c_int, c_ulong};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+    use std::time::Duration;
+    use ctrlspace_core::output::{OutputBackend, OutputEvent};
+    use ctrlspace_core::profiles::MouseButton;
+
+    pub const BACKEND: &str = "uinput";
+
+    // _IO('U', 1), _IO('U', 2), _IOW('U', 100/101/102, int)
+    const UI_DEV_CREATE: c_ulong = 0x5501;
+    const UI_DEV_DESTROY: c_ulong = 0x5502;
+    const UI_SET_EVBIT: c_ulong = 0x4004_5564;
+    const UI_SET_KEYBIT: c_ulong = 0x4004_5565;
+    const UI_SET_RELBIT: c_ulong = 0x4004_5566;
+
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const EV_REL: u16 = 0x02;
+    const SYN_REPORT: u16 = 0;
+
+    const REL_X: u16 = 0x00;
+    const REL_Y: u16 = 0x01;
+    const REL_WHEEL: u16 = 0x08;
+
+    const BTN_LEFT: u16 = 0x110;
+    const BTN_RIGHT: u16 = 0x111;
+    const BTN_MIDDLE: u16 = 0x112;
+    // KEY_ESC up to KEY_F24 covers every key a profile can name
+    const LAST_KEY: u16 = 194;
+
+    const BUS_VIRTUAL: u16 = 0x06;
+    const ABS_CNT: usize = 0x40;
+
+    #[repr(C)]
+    struct InputId {
+        bustype: u16,
+        vendor: u16,
+        product: u16,
+        version: u16,
+    }
+
+    // struct uinput_user_dev
+    #[repr(C)]
+    struct UinputUserDev {
+        name: [u8; 80],
+        id: InputId,
+        ff_effects_max: u32,
+        absmax: [i32; ABS_CNT],
+        absmin: [i32; ABS_CNT],
+        absfuzz: [i32; ABS_CNT],
+        absflat: [i32; ABS_CNT],
+    }
+
+    /// A uinput device with a keyboard's keys and a wheel mouse
+    pub struct Backend {
+        file: File,
+    }
+
+    fn ioctl(file: &File, request: c_ulong, value: u16) -> Result<(), String> {
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), request as _, value as c_int) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+
+    fn event(kind: u16, code: u16, value: i32) -> libc::input_event {
+        let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+        event.type_ = kind;
+        event.code = code;
+        event.value = value;
+        event
+    }
+
+    impl Backend {
+        pub fn create() -> Result<Self, String> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|e| format!("Failed to open /dev/uinput ({}) - is the uinput module loaded and writable?", e))?;
+
+            let mut dev = UinputUserDev {
+                name: [0; 80],
+                id: InputId { bustype: BUS_VIRTUAL, vendor: 0, product: 0, version: 1 },
+                ff_effects_max: 0,
+                absmax: [0; ABS_CNT],
+                absmin: [0; ABS_CNT],
+                absfuzz: [0; ABS_CNT],
+                absflat: [0; ABS_CNT],
+            };
+            let name = b"CtrlSpace Virtual Keyboard/Mouse";
+            dev.name[..name.len()].copy_from_slice(name);
+
+            ioctl(&file, UI_SET_EVBIT, EV_KEY)?;
+            ioctl(&file, UI_SET_EVBIT, EV_REL)?;
+            for code in (1..=LAST_KEY).chain([BTN_LEFT, BTN_RIGHT, BTN_MIDDLE]) {
+                ioctl(&file, UI_SET_KEYBIT, code)?;
+            }
+            for axis in [REL_X, REL_Y, REL_WHEEL] {
+                ioctl(&file, UI_SET_RELBIT, axis)?;
+            }
+
+            let bytes = unsafe {
+                std::slice::from_raw_parts(&dev as *const UinputUserDev as *const u8, std::mem::size_of::<UinputUserDev>())
+            };
+            (&file).write_all(bytes).map_err(|e| format!("Failed to set up the uinput device: {}", e))?;
+            ioctl(&file, UI_DEV_CREATE, 0).map_err(|e| format!("Failed to create the uinput device: {}", e))?;
+            // Events sent before the desktop has opened the new device are lost
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(Self { file })
+        }
+    }
+
+    impl OutputBackend for Backend {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn send(&mut self, events: &[OutputEvent]) -> Result<(), String> {
+            let mut raw = Vec::with_capacity(events.len() * 3);
+            for output in events {
+                match *output {
+                    OutputEvent::Key { key, down } => raw.push(event(EV_KEY, key.evdev, down as i32)),
+                    OutputEvent::Button { button, down } => {
+                        let code = match button {
+                            MouseButton::Left => BTN_LEFT,
+                            MouseButton::Right => BTN_RIGHT,
+                            MouseButton::Middle => BTN_MIDDLE,
+                        };
+                        raw.push(event(EV_KEY, code, down as i32));
+                    }
+                    OutputEvent::Scroll { amount } => raw.push(event(EV_REL, REL_WHEEL, amount)),
+                    OutputEvent::Move { dx, dy } => {
+                        raw.push(event(EV_REL, REL_X, dx));
+                        raw.push(event(EV_REL, REL_Y, dy));
+                    }
+                }
+                // One report per event, so a press and its release aren't merged
+                raw.push(event(EV_SYN, SYN_REPORT, 0));
+            }
+            let bytes = unsafe {
+                std::slice::from_raw_parts(raw.as_ptr() as *const u8, raw.len() * std::mem::size_of::<libc::input_event>())
+            };
+            (&self.file).write_all(bytes).map_err(|e| format!("uinput write failed: {}", e))
+        }
+    }
+
+    impl Drop for Backend {
+        fn drop(&mut self) {
+            let _ = ioctl(&self.file, UI_DEV_DESTROY, 0);
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use ctrlspace_core::output::{OutputBackend, OutputEvent};
+
+    pub const BACKEND: &str = "";
+
+    pub struct Backend;
+
+    impl Backend {
+        pub fn create() -> Result<Self, String> {
+            Err("Keyboard/mouse output isn't supported on this platform".to_string())
+        }
+    }
+
+    impl OutputBackend for Backend {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn send(&mut self, _events: &[OutputEvent]) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Named-pipe server (`\\.\pipe\ctrlspace`) so companion tools on Windows -
+//! stream decks, AutoHotkey scripts - can drive the running instance. Speaks
+//! the line-delimited JSON protocol from `ipc`; each client gets its own thread.
+
+use std::io::{BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde::Serialize;
+use ctrlspace_core::delta::{DeltaEncoder, CONTROLLER_INPUT_DELTA_EVENT, DEFAULT_KEYFRAME_INTERVAL};
+use ctrlspace_core::input_stream::CONTROLLER_INPUT_EVENT;
+use ctrlspace_core::overlay::OVERLAY_EVENT;
+use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream, PipeListenerOptions, SendPipeStream};
+use super::commands;
+use super::ipc::{self, IpcRequest, IpcResponse};
+
+pub const PIPE_NAME: &str = r"\\.\pipe\ctrlspace";
+
+type SharedWriter = Arc<Mutex<SendPipeStream<pipe_mode::Bytes>>>;
+
+/// Create the pipe and accept clients on a background thread
+pub fn start() -> Result<(), String> {
+    let listener = PipeListenerOptions::new()
+        .path(PIPE_NAME)
+        .create_duplex::<pipe_mode::Bytes>()
+        .map_err(|e| format!("Failed to create {}: {}", PIPE_NAME, e))?;
+
+    println!("✅ Named pipe listening on {}", PIPE_NAME);
+
+    thread::spawn(move || {
+        for conn in listener.incoming() {
+            match conn {
+                Ok(stream) => {
+                    thread::spawn(move || serve_client(stream));
+                }
+                Err(e) => eprintln!("❌ Named pipe accept failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn serve_client(stream: DuplexPipeStream<pipe_mode::Bytes>) {
+    println!("🔌 Named pipe client connected");
+
+    let (recv, send) = stream.split();
+    let writer: SharedWriter = Arc::new(Mutex::new(send));
+    let mut input: Option<Arc<AtomicBool>> = None;
+    let mut overlay: Option<Arc<AtomicBool>> = None;
+
+    for line in BufReader::new(recv).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match ipc::parse_request(&line) {
+            Ok(request @ IpcRequest::Subscribe { delta }) => {
+                let subscribed = if input.is_some() {
+                    Ok(())
+                } else {
+                    commands::subscribe_input().map(|frames| {
+                        input = Some(if delta {
+                            let mut encoder = DeltaEncoder::new(DEFAULT_KEYFRAME_INTERVAL);
+                            forward_with(&writer, frames, CONTROLLER_INPUT_DELTA_EVENT, move |frame| {
+                                encoder.encode(&frame)
+                            })
+                        } else {
+                            forward(&writer, frames, CONTROLLER_INPUT_EVENT)
+                        })
+                    })
+                };
+                match subscribed {
+                    Ok(()) => ipc::handle_request(&request),
+                    Err(e) => IpcResponse::error(e),
+                }
+            }
+            Ok(IpcRequest::SubscribeOverlay) => {
+                if overlay.is_none() {
+                    overlay = Some(forward(&writer, commands::subscribe_overlay(), OVERLAY_EVENT));
+                }
+                ipc::handle_request(&IpcRequest::SubscribeOverlay)
+            }
+            Ok(IpcRequest::Unsubscribe) => {
+                cancel(&mut input);
+                ipc::handle_request(&IpcRequest::Unsubscribe)
+            }
+            Ok(IpcRequest::UnsubscribeOverlay) => {
+                cancel(&mut overlay);
+                ipc::handle_request(&IpcRequest::UnsubscribeOverlay)
+            }
+            Ok(request) => ipc::handle_request(&request),
+            Err(e) => IpcResponse::error(e),
+        };
+
+        if send_line(&writer, &response.to_line()).is_err() {
+            break;
+        }
+    }
+
+    cancel(&mut input);
+    cancel(&mut overlay);
+    println!("🔌 Named pipe client disconnected");
+}
+
+// Push events from `events` to this client until cancelled or it hangs up
+fn forward<T>(writer: &SharedWriter, events: Receiver<T>, event: &'static str) -> Arc<AtomicBool>
+where
+    T: Serialize + Send + 'static,
+{
+    forward_with(writer, events, event, |data| data)
+}
+
+// Like `forward`, converting each event with `map` before it is written
+fn forward_with<T, U, F>(writer: &SharedWriter, events: Receiver<T>, event: &'static str, mut map: F) -> Arc<AtomicBool>
+where
+    T: Send + 'static,
+    U: Serialize,
+    F: FnMut(T) -> U + Send + 'static,
+{
+    let active = Arc::new(AtomicBool::new(true));
+    let thread_active = Arc::clone(&active);
+    let writer = Arc::clone(writer);
+
+    thread::spawn(move || {
+        // Ends on the first event after cancelling or once the client is gone
+        for data in events {
+            if !thread_active.load(Ordering::Relaxed) {
+                break;
+            }
+            if send_line(&writer, &ipc::event_line(event, &map(data))).is_err() {
+                break;
+            }
+        }
+    });
+
+    active
+}
+
+fn cancel(subscription: &mut Option<Arc<AtomicBool>>) {
+    if let Some(active) = subscription.take() {
+        active.store(false, Ordering::Relaxed);
+    }
+}
+
+fn send_line(writer: &SharedWriter, line: &str) -> std::io::Result<()> {
+    let mut writer = writer.lock().unwrap();
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
@@ -0,0 +1,83 @@
+//! Crash recovery (see `ctrlspace_core::runtime_state`): keeps the state file
+//! current while the app runs, and after an unclean exit turns Lizard Mode
+//! back on, reconnects the controller and reactivates the profile.
+
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+use super::commands::{self, app_handle};
+use ctrlspace_core::runtime_state::{RuntimeState, RUNTIME_STATE_FILE, SESSION_RECOVERED_EVENT};
+
+static STATE: Lazy<Mutex<RuntimeState>> = Lazy::new(|| Mutex::new(RuntimeState::default()));
+
+fn state_path() -> Result<PathBuf, String> {
+    app_handle()
+        .and_then(|app| app.path_resolver().app_data_dir())
+        .map(|dir| dir.join(RUNTIME_STATE_FILE))
+        .ok_or_else(|| "Could not resolve app data directory".to_string())
+}
+
+/// Mark this run as running (after `commands::init`). Returns what the last
+/// run left behind if it didn't exit cleanly.
+pub fn start() -> Result<Option<RuntimeState>, String> {
+    let path = state_path()?;
+    let previous = RuntimeState::load(&path).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        None
+    });
+    let mut state = STATE.lock().unwrap();
+    state.running = true;
+    state.save(&path)?;
+    Ok(RuntimeState::unclean_exit(previous))
+}
+
+/// Change the state and save it; nothing is written before `start` or after `finish`
+pub fn update(f: impl FnOnce(&mut RuntimeState)) {
+    let mut state = STATE.lock().unwrap();
+    if !state.running {
+        return;
+    }
+    let before = state.clone();
+    f(&mut state);
+    if *state != before {
+        if let Err(e) = state_path().and_then(|path| state.save(&path)) {
+            eprintln!("❌ Runtime state not saved: {}", e);
+        }
+    }
+}
+
+/// Clean exit: the next launch has nothing to recover
+pub fn finish() {
+    update(|state| state.running = false);
+}
+
+/// Put back what an unclean exit left behind, then tell the window
+pub fn recover(previous: RuntimeState) {
+    println!("🔧 The last run didn't exit cleanly - restoring its state");
+    if previous.device_serial.is_some() || previous.lizard_mode_disabled {
+        match commands::connect_steam_controller(None) {
+            Ok(info) => {
+                if previous.device_serial.as_deref() != Some(info.serial.as_str()) {
+                    println!("🔌 Reconnected to {} (the last run used {:?})", info.serial, previous.device_serial);
+                }
+                if previous.lizard_mode_disabled {
+                    match commands::restore_lizard_mode() {
+                        Ok(()) => println!("🦎 Lizard Mode restored"),
+                        Err(e) => eprintln!("❌ Could not restore Lizard Mode: {}", e),
+                    }
+                }
+            }
+            Err(e) => eprintln!("❌ Could not reconnect: {}", e),
+        }
+    }
+    if let Some(name) = &previous.active_profile {
+        if let Err(e) = commands::activate_saved_profile(name) {
+            eprintln!("❌ Could not reactivate profile: {}", e);
+        }
+    }
+
+    if let Some(app) = app_handle() {
+        let _ = app.emit_all(SESSION_RECOVERED_EVENT, &previous);
+    }
+}
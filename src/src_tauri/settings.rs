@@ -0,0 +1,118 @@
+//! App-wide settings that belong to neither a profile nor a controller,
+//! persisted as `settings.json` in the app config dir.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use super::commands::app_handle;
+use ctrlspace_core::battery::BatteryThresholds;
+use ctrlspace_core::haptics::{DEFAULT_HAPTIC_INTENSITY, DEFAULT_MAX_HAPTIC_RATE};
+use ctrlspace_core::hook_process::HookConfig;
+use ctrlspace_core::input_events::InputEventConfig;
+use ctrlspace_core::plugins::Capability;
+use ctrlspace_core::virtual_gamepad::GamepadMapping;
+use ctrlspace_core::xinput::ConflictPolicy;
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Unlocks raw controller access (feature-report console)
+    pub developer_mode: bool,
+    /// When to emit `battery-low` for wireless controllers
+    pub battery: BatteryThresholds,
+    /// Hold time and trigger threshold for `input-events`
+    pub input_events: InputEventConfig,
+    /// Fall back to the bundled desktop profile whenever no other profile is active
+    pub desktop_mode: bool,
+    /// Profile activated at launch: the last one picked with `set_active_profile`
+    pub active_profile: Option<String>,
+    /// Whether a virtual X360 pad may be added next to Steam's own
+    pub virtual_pad_conflict: ConflictPolicy,
+    /// Mapping the virtual X360 pad starts with
+    pub virtual_pad_mapping: GamepadMapping,
+    /// Send bindings and cursor movement to the OS as keyboard/mouse input
+    pub output_injection: bool,
+    /// Scales every pad haptic (0.0-1.0), on top of per-device and per-profile strengths
+    pub haptic_intensity: f32,
+    /// Most pulses per second sent to each pad actuator (0 for no limit)
+    pub max_haptic_rate: u32,
+    /// Capabilities the user granted each plugin, by plugin name
+    pub plugin_grants: BTreeMap<String, Vec<Capability>>,
+    /// External program fed input events over stdin, started at launch
+    pub hook_process: Option<HookConfig>,
+    /// HTTPS URL of the community profile repository's `index.json`
+    pub community_index_url: Option<String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            developer_mode: false,
+            battery: BatteryThresholds::default(),
+            input_events: InputEventConfig::default(),
+            desktop_mode: true,
+            active_profile: None,
+            virtual_pad_conflict: ConflictPolicy::default(),
+            virtual_pad_mapping: GamepadMapping::default(),
+            output_injection: false,
+            haptic_intensity: DEFAULT_HAPTIC_INTENSITY,
+            max_haptic_rate: DEFAULT_MAX_HAPTIC_RATE,
+            plugin_grants: BTreeMap::new(),
+            hook_process: None,
+            community_index_url: None,
+        }
+    }
+}
+
+static SETTINGS: Lazy<Mutex<AppSettings>> = Lazy::new(|| Mutex::new(AppSettings::default()));
+
+fn settings_path() -> Result<PathBuf, String> {
+    app_handle()
+        .and_then(|app| app.path_resolver().app_config_dir())
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .ok_or_else(|| "Could not resolve app config directory".to_string())
+}
+
+/// Load saved settings (after `commands::init`); defaults if there are none
+pub fn init() -> Result<(), String> {
+    let path = settings_path()?;
+    let mut settings: AppSettings = match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid settings {}: {}", path.display(), e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => AppSettings::default(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    // Hand-edited values the setters would have refused
+    if let Err(e) = settings.input_events.validate() {
+        eprintln!("⚠️ {}; using the default input event thresholds", e);
+        settings.input_events = InputEventConfig::default();
+    }
+    *SETTINGS.lock().unwrap() = settings;
+    Ok(())
+}
+
+pub fn get() -> AppSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+/// Change settings and save them; returns the new settings
+pub fn update(f: impl FnOnce(&mut AppSettings)) -> Result<AppSettings, String> {
+    let mut settings = SETTINGS.lock().unwrap();
+    let mut updated = settings.clone();
+    f(&mut updated);
+
+    let path = settings_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    *settings = updated.clone();
+    Ok(updated)
+}
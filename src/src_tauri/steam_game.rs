@@ -0,0 +1,120 @@
+//! Watches which Steam game is running, for profiles keyed by AppID:
+//! - Windows: `RunningAppID` / `SteamPath` under `HKCU\Software\Valve\Steam`
+//! - Linux and macOS: the same keys in Steam's `registry.vdf`
+//!
+//! Steam has no change notification for these, so they are polled; the
+//! handler only runs when the AppID actually changes.
+
+use std::thread;
+use std::time::Duration;
+use ctrlspace_core::steam_apps::{app_name, library_folders, SteamGame};
+use super::commands;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Name it from the app manifest, if it can be found
+fn game(app_id: u32) -> SteamGame {
+    let name = platform::steam_root().and_then(|root| app_name(&library_folders(&root), app_id));
+    SteamGame { app_id, name }
+}
+
+/// The running game, if any
+pub fn running_game() -> Option<SteamGame> {
+    platform::running_app_id().ok().flatten().map(game)
+}
+
+/// Start the thread that polls for the running game
+pub fn start() -> Result<(), String> {
+    if platform::steam_root().is_none() {
+        return Err("Steam installation not found".to_string());
+    }
+    thread::spawn(|| {
+        let mut last = None;
+        loop {
+            // A read error (Steam mid-write) is not the game exiting
+            if let Ok(app_id) = platform::running_app_id() {
+                if app_id != last {
+                    last = app_id;
+                    commands::on_steam_game_changed(app_id.map(game));
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    println!("✅ Watching for Steam games");
+    Ok(())
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::path::PathBuf;
+    use std::{mem, ptr};
+    use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD, RRF_RT_REG_SZ};
+
+    const STEAM_KEY: &str = "Software\\Valve\\Steam";
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// A missing value means Steam isn't running
+    pub fn running_app_id() -> Result<Option<u32>, String> {
+        let (key, value) = (wide(STEAM_KEY), wide("RunningAppID"));
+        let mut id = 0u32;
+        let mut size = mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_DWORD,
+                ptr::null_mut(),
+                &mut id as *mut u32 as *mut c_void,
+                &mut size,
+            )
+        };
+        Ok(Some(id).filter(|&id| status == ERROR_SUCCESS && id != 0))
+    }
+
+    pub fn steam_root() -> Option<PathBuf> {
+        let (key, value) = (wide(STEAM_KEY), wide("SteamPath"));
+        let mut buf = [0u16; 1024];
+        let mut size = mem::size_of_val(&buf) as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                ptr::null_mut(),
+                buf.as_mut_ptr() as *mut c_void,
+                &mut size,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return None;
+        }
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        Some(PathBuf::from(String::from_utf16_lossy(&buf[..len])))
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use std::fs;
+    use std::path::PathBuf;
+    use ctrlspace_core::steam_apps::{default_steam_root, registry_vdf_path, running_app_id_from_registry};
+
+    pub fn running_app_id() -> Result<Option<u32>, String> {
+        let path = registry_vdf_path().ok_or_else(|| "HOME is not set".to_string())?;
+        // Steam rewrites the file in place, so a read can catch it half-written
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        running_app_id_from_registry(&text)
+    }
+
+    pub fn steam_root() -> Option<PathBuf> {
+        default_steam_root()
+    }
+}
@@ -0,0 +1,100 @@
+//! Reference reader for verification mode (`gilrs-verify` feature): reads the
+//! Steam Controller through the OS gamepad driver via gilrs, alongside our own
+//! HID path, and keeps its latest view for `ctrlspace_core::verification`.
+//!
+//! Only useful where a driver already exposes the controller as a gamepad
+//! (hid-steam on Linux, Steam Input off), otherwise there's nothing to read.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use gilrs::{Axis, Button, Gamepad, Gilrs};
+use ctrlspace_core::profiles::ButtonId;
+use ctrlspace_core::steam_controller::VALVE_VENDOR_ID;
+use ctrlspace_core::verification::ReferenceInput;
+
+/// How often gilrs is drained; well under one report interval at 125 Hz
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Buttons gilrs reports the same way we do. Triggers are compared as analog
+/// values instead, since the driver's click threshold differs from the SC's.
+const BUTTONS: [(Button, ButtonId); 10] = [
+    (Button::South, ButtonId::A),
+    (Button::East, ButtonId::B),
+    (Button::West, ButtonId::X),
+    (Button::North, ButtonId::Y),
+    (Button::LeftTrigger, ButtonId::Lb),
+    (Button::RightTrigger, ButtonId::Rb),
+    (Button::Select, ButtonId::Select),
+    (Button::Start, ButtonId::Start),
+    (Button::Mode, ButtonId::Steam),
+    (Button::LeftThumb, ButtonId::StickClick),
+];
+
+fn snapshot(gamepad: &Gamepad) -> ReferenceInput {
+    let trigger = |button| gamepad.button_data(button).map_or(0.0, |data| data.value());
+    ReferenceInput {
+        buttons: BUTTONS
+            .iter()
+            .map(|&(button, id)| (id, gamepad.is_pressed(button)))
+            .collect(),
+        stick: Some((gamepad.value(Axis::LeftStickX), gamepad.value(Axis::LeftStickY))),
+        triggers: Some((trigger(Button::LeftTrigger2), trigger(Button::RightTrigger2))),
+    }
+}
+
+/// Background gilrs reader; stops when dropped
+pub struct ReferenceReader {
+    latest: Arc<Mutex<Option<ReferenceInput>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl ReferenceReader {
+    pub fn start() -> Result<Self, String> {
+        let latest = Arc::new(Mutex::new(None));
+        let running = Arc::new(AtomicBool::new(true));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let (thread_latest, thread_running) = (Arc::clone(&latest), Arc::clone(&running));
+        // gilrs isn't Send on every platform, so it lives on its own thread
+        thread::spawn(move || {
+            let mut gilrs = match Gilrs::new() {
+                Ok(gilrs) => gilrs,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(format!("Failed to start gilrs: {}", e)));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+
+            while thread_running.load(Ordering::Relaxed) {
+                // Events update gilrs' cached gamepad state
+                while gilrs.next_event().is_some() {}
+                let state = gilrs
+                    .gamepads()
+                    .find(|(_, gamepad)| gamepad.vendor_id() == Some(VALVE_VENDOR_ID))
+                    .map(|(_, gamepad)| snapshot(&gamepad));
+                *thread_latest.lock().unwrap() = state;
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| "gilrs reader thread exited".to_string())??;
+        println!("🔍 Verification reader started");
+        Ok(Self { latest, running })
+    }
+
+    /// The driver's current view, if it sees a Steam Controller
+    pub fn latest(&self) -> Option<ReferenceInput> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+impl Drop for ReferenceReader {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,440 @@
+//! The virtual X360 pad (see `ctrlspace_core::virtual_gamepad`): a ViGEm
+//! target on Windows, a uinput device on Linux. It is fed from the same
+//! per-frame path as the bindings and released to neutral whenever the
+//! controller goes away, so games never see a button stuck down.
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use ctrlspace_core::input_parser::ControllerInput;
+use ctrlspace_core::profiles::Profile;
+use ctrlspace_core::virtual_gamepad::{GamepadMapping, VirtualGamepad, VirtualGamepadStatus};
+
+#[derive(Default)]
+struct State {
+    pad: Option<VirtualGamepad>,
+    mapping: GamepadMapping,
+    error: Option<String>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+/// Create the pad (or switch a running one to `mapping`)
+pub fn start(mapping: GamepadMapping) -> Result<VirtualGamepadStatus, String> {
+    let mut state = STATE.lock().unwrap();
+    state.mapping = mapping;
+    match state.pad.as_mut() {
+        Some(pad) => pad.set_mapping(mapping),
+        None => match platform::Pad::create() {
+            Ok(pad) => {
+                println!("🎮 Virtual X360 pad created via {} ({:?} mapping)", platform::BACKEND, mapping);
+                state.pad = Some(VirtualGamepad::new(Box::new(pad), mapping));
+                state.error = None;
+            }
+            Err(e) => {
+                eprintln!("❌ Virtual X360 pad unavailable: {}", e);
+                state.error = Some(e.clone());
+                return Err(e);
+            }
+        },
+    }
+    Ok(status_of(&state))
+}
+
+pub fn stop() {
+    let mut state = STATE.lock().unwrap();
+    if let Some(mut pad) = state.pad.take() {
+        let _ = pad.neutral();
+        println!("🔌 Virtual X360 pad removed");
+    }
+    state.error = None;
+}
+
+/// Used by the next frame, and by the next `start`
+pub fn set_mapping(mapping: GamepadMapping) {
+    let mut state = STATE.lock().unwrap();
+    state.mapping = mapping;
+    if let Some(pad) = state.pad.as_mut() {
+        pad.set_mapping(mapping);
+    }
+}
+
+pub fn is_active() -> bool {
+    STATE.lock().unwrap().pad.is_some()
+}
+
+pub fn status() -> VirtualGamepadStatus {
+    status_of(&STATE.lock().unwrap())
+}
+
+fn status_of(state: &State) -> VirtualGamepadStatus {
+    VirtualGamepadStatus {
+        active: state.pad.is_some(),
+        mapping: state.mapping,
+        backend: platform::BACKEND.to_string(),
+        error: state.error.clone(),
+    }
+}
+
+/// One frame of input, with `profile`'s stick modes. A pad whose driver went
+/// away is dropped; the error shows up in `status`.
+pub fn feed(input: &ControllerInput, profile: Option<&Profile>) {
+    let mut state = STATE.lock().unwrap();
+    let result = match state.pad.as_mut() {
+        Some(pad) => pad.feed(input, profile),
+        None => return,
+    };
+    if let Err(e) = result {
+        eprintln!("❌ Virtual X360 pad update failed, removing it: {}", e);
+        state.pad = None;
+        state.error = Some(e);
+    }
+}
+
+/// Release every button and center the sticks (controller disconnected)
+pub fn neutral() {
+    if let Some(pad) = STATE.lock().unwrap().pad.as_mut() {
+        let _ = pad.neutral();
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::HMODULE;
+    use windows_sys::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+    use ctrlspace_core::virtual_gamepad::{VirtualPad, XusbReport};
+
+    pub const BACKEND: &str = "vigem";
+
+    const VIGEM_ERROR_NONE: u32 = 0x2000_0000;
+
+    type Handle = *mut c_void;
+
+    // The ViGEmClient.dll exports we use. Loaded at runtime so the app still
+    // starts where ViGEmBus isn't installed.
+    struct Api {
+        alloc: unsafe extern "C" fn() -> Handle,
+        free: unsafe extern "C" fn(Handle),
+        connect: unsafe extern "C" fn(Handle) -> u32,
+        disconnect: unsafe extern "C" fn(Handle),
+        target_x360_alloc: unsafe extern "C" fn() -> Handle,
+        target_free: unsafe extern "C" fn(Handle),
+        target_add: unsafe extern "C" fn(Handle, Handle) -> u32,
+        target_remove: unsafe extern "C" fn(Handle, Handle) -> u32,
+        target_x360_update: unsafe extern "C" fn(Handle, Handle, XusbReport) -> u32,
+    }
+
+    unsafe fn symbol<T: Copy>(module: HMODULE, name: &[u8]) -> Result<T, String> {
+        match GetProcAddress(module, name.as_ptr()) {
+            Some(proc) => Ok(std::mem::transmute_copy(&proc)),
+            None => Err(format!(
+                "ViGEmClient.dll has no {}",
+                String::from_utf8_lossy(&name[..name.len() - 1])
+            )),
+        }
+    }
+
+    impl Api {
+        fn load() -> Result<Self, String> {
+            let name: Vec<u16> = std::ffi::OsStr::new("ViGEmClient.dll").encode_wide().chain(Some(0)).collect();
+            let module = unsafe { LoadLibraryW(name.as_ptr()) };
+            if module == 0 {
+                return Err("ViGEmClient.dll not found - install ViGEmBus".to_string());
+            }
+            unsafe {
+                Ok(Self {
+                    alloc: symbol(module, b"vigem_alloc\0")?,
+                    free: symbol(module, b"vigem_free\0")?,
+                    connect: symbol(module, b"vigem_connect\0")?,
+                    disconnect: symbol(module, b"vigem_disconnect\0")?,
+                    target_x360_alloc: symbol(module, b"vigem_target_x360_alloc\0")?,
+                    target_free: symbol(module, b"vigem_target_free\0")?,
+                    target_add: symbol(module, b"vigem_target_add\0")?,
+                    target_remove: symbol(module, b"vigem_target_remove\0")?,
+                    target_x360_update: symbol(module, b"vigem_target_x360_update\0")?,
+                })
+            }
+        }
+    }
+
+    /// An X360 target plugged into ViGEmBus
+    pub struct Pad {
+        api: Api,
+        client: Handle,
+        target: Handle,
+    }
+
+    // The client handle may be used from any thread; `STATE` serializes access
+    unsafe impl Send for Pad {}
+
+    impl Pad {
+        pub fn create() -> Result<Self, String> {
+            let api = Api::load()?;
+            unsafe {
+                let client = (api.alloc)();
+                if client.is_null() {
+                    return Err("vigem_alloc failed".to_string());
+                }
+                let error = (api.connect)(client);
+                if error != VIGEM_ERROR_NONE {
+                    (api.free)(client);
+                    return Err(format!("ViGEmBus is not running (error 0x{:08x})", error));
+                }
+                let target = (api.target_x360_alloc)();
+                if target.is_null() {
+                    (api.disconnect)(client);
+                    (api.free)(client);
+                    return Err("vigem_target_x360_alloc failed".to_string());
+                }
+                let error = (api.target_add)(client, target);
+                if error != VIGEM_ERROR_NONE {
+                    (api.target_free)(target);
+                    (api.disconnect)(client);
+                    (api.free)(client);
+                    return Err(format!("Failed to plug in the X360 target (error 0x{:08x})", error));
+                }
+                Ok(Self { api, client, target })
+            }
+        }
+    }
+
+    impl VirtualPad for Pad {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn update(&mut self, report: &XusbReport) -> Result<(), String> {
+            let error = unsafe { (self.api.target_x360_update)(self.client, self.target, *report) };
+            if error == VIGEM_ERROR_NONE {
+                Ok(())
+            } else {
+                Err(format!("vigem_target_x360_update failed (error 0x{:08x})", error))
+            }
+        }
+    }
+
+    impl Drop for Pad {
+        fn drop(&mut self) {
+            unsafe {
+                (self.api.target_remove)(self.client, self.target);
+                (self.api.target_free)(self.target);
+                (self.api.disconnect)(self.client);
+                (self.api.free)(self.client);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::os::raw::{c_int, c_ulong};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+    use ctrlspace_core::virtual_gamepad::*;
+
+    pub const BACKEND: &str = "uinput";
+
+    // _IO('U', 1), _IO('U', 2), _IOW('U', 100/101/103, int)
+    const UI_DEV_CREATE: c_ulong = 0x5501;
+    const UI_DEV_DESTROY: c_ulong = 0x5502;
+    const UI_SET_EVBIT: c_ulong = 0x4004_5564;
+    const UI_SET_KEYBIT: c_ulong = 0x4004_5565;
+    const UI_SET_ABSBIT: c_ulong = 0x4004_5567;
+
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const EV_ABS: u16 = 0x03;
+    const SYN_REPORT: u16 = 0;
+
+    const ABS_X: u16 = 0x00;
+    const ABS_Y: u16 = 0x01;
+    const ABS_Z: u16 = 0x02;
+    const ABS_RX: u16 = 0x03;
+    const ABS_RY: u16 = 0x04;
+    const ABS_RZ: u16 = 0x05;
+    const ABS_HAT0X: u16 = 0x10;
+    const ABS_HAT0Y: u16 = 0x11;
+    const ABS_CNT: usize = 0x40;
+
+    const BUS_USB: u16 = 0x03;
+    // Microsoft Xbox 360 Controller, so games apply their X360 layout
+    const XBOX_VENDOR_ID: u16 = 0x045e;
+    const XBOX_360_PID: u16 = 0x028e;
+
+    // Buttons as the kernel's xpad driver reports them (BTN_A..BTN_THUMBR)
+    const BUTTONS: [(u16, u16); 11] = [
+        (XUSB_A, 0x130),
+        (XUSB_B, 0x131),
+        (XUSB_X, 0x133),
+        (XUSB_Y, 0x134),
+        (XUSB_LEFT_SHOULDER, 0x136),
+        (XUSB_RIGHT_SHOULDER, 0x137),
+        (XUSB_BACK, 0x13a),
+        (XUSB_START, 0x13b),
+        (XUSB_GUIDE, 0x13c),
+        (XUSB_LEFT_THUMB, 0x13d),
+        (XUSB_RIGHT_THUMB, 0x13e),
+    ];
+
+    #[repr(C)]
+    struct InputId {
+        bustype: u16,
+        vendor: u16,
+        product: u16,
+        version: u16,
+    }
+
+    // struct uinput_user_dev
+    #[repr(C)]
+    struct UinputUserDev {
+        name: [u8; 80],
+        id: InputId,
+        ff_effects_max: u32,
+        absmax: [i32; ABS_CNT],
+        absmin: [i32; ABS_CNT],
+        absfuzz: [i32; ABS_CNT],
+        absflat: [i32; ABS_CNT],
+    }
+
+    /// A uinput device shaped like an xpad-driven X360 pad
+    pub struct Pad {
+        file: File,
+    }
+
+    fn ioctl(file: &File, request: c_ulong, value: u16) -> Result<(), String> {
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), request as _, value as c_int) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+
+    fn event(kind: u16, code: u16, value: i32) -> libc::input_event {
+        let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+        event.type_ = kind;
+        event.code = code;
+        event.value = value;
+        event
+    }
+
+    impl Pad {
+        pub fn create() -> Result<Self, String> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|e| format!("Failed to open /dev/uinput ({}) - is the uinput module loaded and writable?", e))?;
+
+            let mut dev = UinputUserDev {
+                name: [0; 80],
+                id: InputId { bustype: BUS_USB, vendor: XBOX_VENDOR_ID, product: XBOX_360_PID, version: 0x0110 },
+                ff_effects_max: 0,
+                absmax: [0; ABS_CNT],
+                absmin: [0; ABS_CNT],
+                absfuzz: [0; ABS_CNT],
+                absflat: [0; ABS_CNT],
+            };
+            let name = b"CtrlSpace Virtual X360 Pad";
+            dev.name[..name.len()].copy_from_slice(name);
+
+            ioctl(&file, UI_SET_EVBIT, EV_KEY)?;
+            ioctl(&file, UI_SET_EVBIT, EV_ABS)?;
+            for (_, code) in BUTTONS.iter() {
+                ioctl(&file, UI_SET_KEYBIT, *code)?;
+            }
+            // Ranges, fuzz and flat as xpad sets them
+            for axis in [ABS_X, ABS_Y, ABS_RX, ABS_RY] {
+                ioctl(&file, UI_SET_ABSBIT, axis)?;
+                let axis = axis as usize;
+                dev.absmin[axis] = -32768;
+                dev.absmax[axis] = 32767;
+                dev.absfuzz[axis] = 16;
+                dev.absflat[axis] = 128;
+            }
+            for axis in [ABS_Z, ABS_RZ] {
+                ioctl(&file, UI_SET_ABSBIT, axis)?;
+                dev.absmax[axis as usize] = 255;
+            }
+            for axis in [ABS_HAT0X, ABS_HAT0Y] {
+                ioctl(&file, UI_SET_ABSBIT, axis)?;
+                dev.absmin[axis as usize] = -1;
+                dev.absmax[axis as usize] = 1;
+            }
+
+            let bytes = unsafe {
+                std::slice::from_raw_parts(&dev as *const UinputUserDev as *const u8, std::mem::size_of::<UinputUserDev>())
+            };
+            (&file).write_all(bytes).map_err(|e| format!("Failed to set up the uinput device: {}", e))?;
+            ioctl(&file, UI_DEV_CREATE, 0).map_err(|e| format!("Failed to create the uinput device: {}", e))?;
+            Ok(Self { file })
+        }
+    }
+
+    impl VirtualPad for Pad {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn update(&mut self, report: &XusbReport) -> Result<(), String> {
+            let hat = |negative: u16, positive: u16| report.pressed(positive) as i32 - report.pressed(negative) as i32;
+            let mut events: Vec<libc::input_event> = BUTTONS
+                .iter()
+                .map(|(bit, code)| event(EV_KEY, *code, report.pressed(*bit) as i32))
+                .collect();
+            // evdev sticks are down positive; `!` flips without overflowing at -32768
+            events.extend_from_slice(&[
+                event(EV_ABS, ABS_X, report.thumb_lx as i32),
+                event(EV_ABS, ABS_Y, !report.thumb_ly as i32),
+                event(EV_ABS, ABS_RX, report.thumb_rx as i32),
+                event(EV_ABS, ABS_RY, !report.thumb_ry as i32),
+                event(EV_ABS, ABS_Z, report.left_trigger as i32),
+                event(EV_ABS, ABS_RZ, report.right_trigger as i32),
+                event(EV_ABS, ABS_HAT0X, hat(XUSB_DPAD_LEFT, XUSB_DPAD_RIGHT)),
+                event(EV_ABS, ABS_HAT0Y, hat(XUSB_DPAD_UP, XUSB_DPAD_DOWN)),
+                event(EV_SYN, SYN_REPORT, 0),
+            ]);
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    events.as_ptr() as *const u8,
+                    events.len() * std::mem::size_of::<libc::input_event>(),
+                )
+            };
+            (&self.file).write_all(bytes).map_err(|e| format!("uinput write failed: {}", e))
+        }
+    }
+
+    impl Drop for Pad {
+        fn drop(&mut self) {
+            let _ = ioctl(&self.file, UI_DEV_DESTROY, 0);
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use ctrlspace_core::virtual_gamepad::{VirtualPad, XusbReport};
+
+    pub const BACKEND: &str = "";
+
+    pub struct Pad;
+
+    impl Pad {
+        pub fn create() -> Result<Self, String> {
+            Err("Virtual gamepads aren't supported on this platform".to_string())
+        }
+    }
+
+    impl VirtualPad for Pad {
+        fn backend(&self) -> &'static str {
+            BACKEND
+        }
+
+        fn update(&mut self, _report: &XusbReport) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}